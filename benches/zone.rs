@@ -0,0 +1,47 @@
+//! Times the deserializer (and, with the `serializer` feature, the
+//! serializer) on synthetic worst-case zones. Run with
+//! `cargo bench --features bench` (or `--features bench,serializer`).
+
+use t5_xfile_defs::XFilePlatform;
+use t5_xfile_deserializer::bench::BenchZone;
+
+const ITERATIONS: usize = 10;
+
+fn main() {
+    let zones = [
+        (
+            "script strings",
+            BenchZone::new(XFilePlatform::Windows)
+                .with_script_strings(60_000)
+                // the serializer needs at least one asset
+                .with_rawfiles(1, 16),
+        ),
+        (
+            "rawfiles",
+            BenchZone::new(XFilePlatform::Windows).with_rawfiles(256, 256 * 1024),
+        ),
+        (
+            "pack index",
+            BenchZone::new(XFilePlatform::Windows).with_pack_index_entries(1_000_000),
+        ),
+        (
+            "menu key handlers",
+            BenchZone::new(XFilePlatform::Windows).with_menu_key_handlers(1_000_000),
+        ),
+        ("worst case", BenchZone::worst_case(XFilePlatform::Windows)),
+        (
+            "worst case (PS3)",
+            BenchZone::worst_case(XFilePlatform::PS3),
+        ),
+    ];
+
+    for (name, zone) in zones {
+        println!("{name}");
+        println!("  {}", zone.bench_deserialize(ITERATIONS).unwrap());
+        #[cfg(feature = "serializer")]
+        match zone.bench_serialize(ITERATIONS) {
+            Ok(m) => println!("  {m}"),
+            Err(e) => println!("  serialize: {e}"),
+        }
+    }
+}
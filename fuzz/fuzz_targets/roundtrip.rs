@@ -0,0 +1,23 @@
+//! Generates arbitrary assets, serializes them, and deserializes them back,
+//! failing whenever the two sides disagree.
+//!
+//! Assets the serializer rejects are skipped, since arbitrary ones break all
+//! sorts of invariants that real assets uphold.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use t5_xfile_defs::{XFilePlatform, xasset::XAsset};
+use t5_xfile_deserializer::roundtrip::{self, RoundTripOutcome};
+
+fuzz_target!(|asset: XAsset| {
+    // console assets are unsupported by the serializer
+    if !matches!(asset, XAsset::PC(_)) {
+        return;
+    }
+
+    let report = roundtrip::check_asset(&asset, XFilePlatform::Windows);
+    if !matches!(report.outcome, RoundTripOutcome::SerializeFailed(_)) {
+        assert!(report.is_ok(), "{report:#?}");
+    }
+});
@@ -0,0 +1,188 @@
+//! Deserialization that doesn't block the async runtime.
+//!
+//! The Fastfile is read with [`tokio::io`], then inflated and deserialized on
+//! tokio's blocking thread pool, with assets sent back as a [`Stream`] as
+//! they're deserialized.
+
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{io::SeekFrom, path::PathBuf};
+
+use futures_core::Stream;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt},
+    sync::mpsc,
+};
+
+use t5_xfile_defs::{XFilePlatform, xasset::XAsset};
+
+use crate::{Error, ErrorKind, Result, T5XFileDeserializerBuilder, file_line_col};
+
+/// Number of deserialized assets that can be waiting to be polled before
+/// deserialization pauses.
+const ASSET_BUFFER_LEN: usize = 16;
+
+pub struct T5XFileDeserializerAsync<R> {
+    reader: R,
+    platform: XFilePlatform,
+    silent: bool,
+    allow_unsupported_platforms: bool,
+    allocation_limit: Option<usize>,
+    max_local_clients: Option<usize>,
+    max_ptr_depth: Option<usize>,
+    follow_real_ptrs: bool,
+    record_ptr_markers: bool,
+    game_dir: Option<PathBuf>,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> T5XFileDeserializerAsync<R> {
+    pub fn new(reader: R, platform: XFilePlatform) -> Self {
+        Self {
+            reader,
+            platform,
+            silent: true,
+            allow_unsupported_platforms: false,
+            allocation_limit: None,
+            max_local_clients: None,
+            max_ptr_depth: None,
+            follow_real_ptrs: false,
+            record_ptr_markers: false,
+            game_dir: None,
+        }
+    }
+
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    pub fn with_allow_unsupported_platforms(mut self, allow_unsupported_platforms: bool) -> Self {
+        self.allow_unsupported_platforms = allow_unsupported_platforms;
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_allocation_limit`].
+    pub fn with_allocation_limit(mut self, bytes: usize) -> Self {
+        self.allocation_limit = Some(bytes);
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_max_local_clients`].
+    pub fn with_max_local_clients(mut self, max_local_clients: usize) -> Self {
+        self.max_local_clients = Some(max_local_clients);
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_max_ptr_depth`].
+    pub fn with_max_ptr_depth(mut self, depth: usize) -> Self {
+        self.max_ptr_depth = Some(depth);
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_follow_real_ptrs`].
+    pub fn with_follow_real_ptrs(mut self, follow: bool) -> Self {
+        self.follow_real_ptrs = follow;
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_record_ptr_markers`].
+    pub fn with_record_ptr_markers(mut self, record: bool) -> Self {
+        self.record_ptr_markers = record;
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_game_dir`].
+    pub fn with_game_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(dir.into());
+        self
+    }
+
+    /// Reads the whole Fastfile from the start, then starts deserializing it
+    /// on the blocking thread pool.
+    ///
+    /// Must be called from within a tokio runtime. The returned stream yields
+    /// each asset in order, and ends after the last asset or after the first
+    /// [`Err`]. Dropping the stream stops deserialization.
+    pub async fn deserialize(mut self) -> Result<XAssetStream> {
+        let mut bytes = Vec::new();
+        self.reader
+            .seek(SeekFrom::Start(0))
+            .await
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        self.reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+
+        let (tx, rx) = mpsc::channel(ASSET_BUFFER_LEN);
+
+        tokio::task::spawn_blocking(move || {
+            let mut builder = T5XFileDeserializerBuilder::from_bytes(
+                &bytes,
+                self.platform,
+                self.allow_unsupported_platforms,
+            )
+            .with_silent(self.silent);
+            if let Some(limit) = self.allocation_limit {
+                builder = builder.with_allocation_limit(limit);
+            }
+            if let Some(max_local_clients) = self.max_local_clients {
+                builder = builder.with_max_local_clients(max_local_clients);
+            }
+            if let Some(depth) = self.max_ptr_depth {
+                builder = builder.with_max_ptr_depth(depth);
+            }
+            builder = builder
+                .with_follow_real_ptrs(self.follow_real_ptrs)
+                .with_record_ptr_markers(self.record_ptr_markers);
+            if let Some(dir) = self.game_dir {
+                builder = builder.with_game_dir(dir);
+            }
+
+            let de = builder
+                .build()
+                .and_then(|de| de.inflate())
+                .and_then(|de| de.no_cache());
+
+            let mut de = match de {
+                Ok(de) => de,
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(e));
+                    return;
+                }
+            };
+
+            loop {
+                let asset = match de.deserialize_next() {
+                    Ok(Some(asset)) => Ok(asset),
+                    Ok(None) => return,
+                    Err(e) => Err(e),
+                };
+
+                let is_err = asset.is_err();
+                // the stream was dropped, so no one cares about the rest
+                if tx.blocking_send(asset).is_err() || is_err {
+                    return;
+                }
+            }
+        });
+
+        Ok(XAssetStream { rx })
+    }
+}
+
+/// A [`Stream`] of the assets deserialized by
+/// [`T5XFileDeserializerAsync::deserialize`].
+pub struct XAssetStream {
+    rx: mpsc::Receiver<Result<XAsset>>,
+}
+
+impl Stream for XAssetStream {
+    type Item = Result<XAsset>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
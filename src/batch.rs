@@ -0,0 +1,193 @@
+//! Deserializing every Fastfile in a directory at once, e.g. to index an
+//! entire game install.
+//!
+//! Fastfiles are deserialized on a pool of worker threads, and each one's
+//! assets are handed to a callback on the calling thread as soon as they're
+//! ready. Only a bounded number of deserialized Fastfiles are ever waiting
+//! for the callback, so memory use doesn't grow with the number of
+//! Fastfiles.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+use crate::{Error, ErrorKind, Result, T5XFileDeserializerBuilder, ZONE_EXTENSION, file_line_col};
+
+use t5_xfile_defs::{XFilePlatform, xasset::XAssetBag};
+
+/// Deserializes every Fastfile in `dir` (see [`Batch`]) with the default
+/// options, and calls `f` with each one's assets.
+pub fn process_dir(
+    dir: impl AsRef<Path>,
+    platform: XFilePlatform,
+    mut f: impl FnMut(XAssetBag),
+) -> Result<BatchReport> {
+    Batch::new(dir, platform).run(|_, bag| f(bag))
+}
+
+/// What happened to the Fastfiles of a [`Batch`].
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Every Fastfile that was deserialized, in order of their paths.
+    pub processed: Vec<PathBuf>,
+    /// Every Fastfile that failed to deserialize, along with why, in order
+    /// of their paths.
+    pub failed: Vec<(PathBuf, Error)>,
+}
+
+/// Deserializes every Fastfile (every file with the [`ZONE_EXTENSION`]) in
+/// a directory and its subdirectories.
+pub struct Batch {
+    dir: PathBuf,
+    platform: XFilePlatform,
+    threads: Option<usize>,
+    max_pending: usize,
+    allow_unsupported_platforms: bool,
+    allocation_limit: Option<usize>,
+}
+
+impl Batch {
+    pub fn new(dir: impl AsRef<Path>, platform: XFilePlatform) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            platform,
+            threads: None,
+            max_pending: 1,
+            allow_unsupported_platforms: false,
+            allocation_limit: None,
+        }
+    }
+
+    /// Uses `threads` worker threads, rather than one per CPU (see
+    /// [`std::thread::available_parallelism`]).
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    /// Lets up to `max_pending` deserialized Fastfiles wait for the callback
+    /// before workers stop to wait for it, rather than 1. At most this many
+    /// Fastfiles, plus one per worker, are in memory at once.
+    pub fn with_max_pending(mut self, max_pending: usize) -> Self {
+        self.max_pending = max_pending;
+        self
+    }
+
+    pub fn with_allow_unsupported_platforms(mut self, allow_unsupported_platforms: bool) -> Self {
+        self.allow_unsupported_platforms = allow_unsupported_platforms;
+        self
+    }
+
+    /// See [`T5XFileDeserializerBuilder::with_allocation_limit`].
+    pub fn with_allocation_limit(mut self, bytes: usize) -> Self {
+        self.allocation_limit = Some(bytes);
+        self
+    }
+
+    /// Every Fastfile the batch will deserialize, in order of their paths.
+    pub fn paths(&self) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        find_fastfiles(&self.dir, &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Deserializes every Fastfile, and calls `f` with each one's path and
+    /// assets, in the order they finish deserializing.
+    ///
+    /// A Fastfile that fails to deserialize doesn't stop the batch; it's
+    /// recorded in the returned [`BatchReport`] instead. Returns [`Err`]
+    /// only if the Fastfiles can't be found.
+    pub fn run(&self, mut f: impl FnMut(&Path, XAssetBag)) -> Result<BatchReport> {
+        let paths = self.paths()?;
+        let threads = self
+            .threads
+            .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+            .unwrap_or(1)
+            .min(paths.len().max(1));
+
+        let next = AtomicUsize::new(0);
+        let (tx, rx) = mpsc::sync_channel(self.max_pending);
+        let mut report = BatchReport::default();
+
+        std::thread::scope(|s| {
+            for _ in 0..threads {
+                let tx = tx.clone();
+                let (paths, next) = (&paths, &next);
+                s.spawn(move || {
+                    while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let res = self.deserialize(path);
+                        // the receiver only hangs up if the callback panics
+                        if tx.send((path, res)).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+            drop(tx);
+
+            for (path, res) in rx {
+                match res {
+                    Ok(bag) => {
+                        f(path, bag);
+                        report.processed.push(path.clone());
+                    }
+                    Err(e) => report.failed.push((path.clone(), e)),
+                }
+            }
+        });
+
+        report.processed.sort();
+        report.failed.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(report)
+    }
+
+    fn deserialize(&self, path: &Path) -> Result<XAssetBag> {
+        let mut file = std::fs::File::open(path)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+
+        let de = T5XFileDeserializerBuilder::from_file(
+            &mut file,
+            self.platform,
+            self.allow_unsupported_platforms,
+        )
+        .with_silent(true);
+        let de = match self.allocation_limit {
+            Some(bytes) => de.with_allocation_limit(bytes),
+            None => de,
+        };
+
+        let mut de = de.build()?.inflate()?.no_cache()?;
+        let mut bag = XAssetBag::new();
+        while let Some(asset) = de.deserialize_next()? {
+            bag.insert(asset);
+        }
+
+        Ok(bag)
+    }
+}
+
+fn find_fastfiles(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+
+    for entry in entries {
+        let path = entry
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?
+            .path();
+        if path.is_dir() {
+            find_fastfiles(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case(ZONE_EXTENSION))
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
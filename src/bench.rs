@@ -0,0 +1,252 @@
+//! Synthetic worst-case Fastfiles, and timing the deserializer (and
+//! serializer) on them.
+//!
+//! [`BenchZone`] builds a Fastfile (with [`SyntheticXFile`]) out of the
+//! shapes that are the most expensive to load, each scaled independently:
+//!
+//! * script strings, which are all loaded up front,
+//! * large rawfiles, which are loaded in bulk,
+//! * a pack index with many entries, which are decoded one at a time like
+//!   vertices and every other array of structs,
+//! * a menu with a long `on_key` linked list, which is walked node by node.
+//!
+//! [`BenchZone::bench_deserialize`] (and, with the `serializer` feature,
+//! [`BenchZone::bench_serialize`]) then times loading it, so performance
+//! regressions can be quantified without any game data. `cargo bench
+//! --features bench` runs the presets in `benches/zone.rs`.
+
+use std::time::{Duration, Instant};
+
+use t5_xfile_defs::{Result, XFilePlatform, xasset::XAsset};
+
+#[cfg(feature = "serializer")]
+use t5_xfile_defs::xasset::XAssetType;
+
+use crate::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+/// The contents of a synthetic Fastfile to benchmark with. See the
+/// [module docs](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BenchZone {
+    pub platform: XFilePlatform,
+    /// At most [`u16::MAX`] to be serializable, as script strings are
+    /// referred to by 16-bit indices.
+    pub script_strings: usize,
+    pub rawfiles: usize,
+    /// The size of each rawfile, in bytes.
+    pub rawfile_size: usize,
+    /// The number of entries in the pack index. Nothing else is added if
+    /// it's 0.
+    pub pack_index_entries: usize,
+    /// The length of the menu's `on_key` list. Nothing else is added if
+    /// it's 0.
+    pub menu_key_handlers: usize,
+}
+
+impl BenchZone {
+    /// An empty zone for `platform`.
+    pub fn new(platform: XFilePlatform) -> Self {
+        Self {
+            platform,
+            script_strings: 0,
+            rawfiles: 0,
+            rawfile_size: 0,
+            pack_index_entries: 0,
+            menu_key_handlers: 0,
+        }
+    }
+
+    /// A zone with every shape scaled up to roughly the size of the largest
+    /// ones in the game's own Fastfiles, or beyond.
+    pub fn worst_case(platform: XFilePlatform) -> Self {
+        Self::new(platform)
+            .with_script_strings(10_000)
+            .with_rawfiles(64, 256 * 1024)
+            .with_pack_index_entries(500_000)
+            .with_menu_key_handlers(100_000)
+    }
+
+    pub fn with_script_strings(mut self, script_strings: usize) -> Self {
+        self.script_strings = script_strings;
+        self
+    }
+
+    pub fn with_rawfiles(mut self, rawfiles: usize, rawfile_size: usize) -> Self {
+        self.rawfiles = rawfiles;
+        self.rawfile_size = rawfile_size;
+        self
+    }
+
+    pub fn with_pack_index_entries(mut self, pack_index_entries: usize) -> Self {
+        self.pack_index_entries = pack_index_entries;
+        self
+    }
+
+    pub fn with_menu_key_handlers(mut self, menu_key_handlers: usize) -> Self {
+        self.menu_key_handlers = menu_key_handlers;
+        self
+    }
+
+    pub fn to_synthetic(&self) -> SyntheticXFile {
+        let mut xfile = SyntheticXFile::new(self.platform);
+
+        for i in 0..self.script_strings {
+            xfile = xfile.with_script_string(format!("bench_string_{i}"));
+        }
+
+        let contents = (0..self.rawfile_size)
+            .map(|i| b'a' + (i % 26) as u8)
+            .collect::<Vec<_>>();
+        for i in 0..self.rawfiles {
+            xfile = xfile.with_rawfile(&format!("bench/rawfile_{i}.txt"), &contents);
+        }
+
+        if self.pack_index_entries != 0 {
+            let entries = (0..self.pack_index_entries as u32)
+                .map(|i| (i.wrapping_mul(0x9E3779B9), i * 16, 16))
+                .collect::<Vec<_>>();
+            xfile = xfile.with_pack_index("bench/pack_index", &entries);
+        }
+
+        if self.menu_key_handlers != 0 {
+            xfile = xfile.with_menu("bench_menu", self.menu_key_handlers);
+        }
+
+        xfile
+    }
+
+    /// Returns the complete Fastfile, as
+    /// [`SyntheticXFile::to_bytes`] does.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_synthetic().to_bytes()
+    }
+
+    /// Times inflating and deserializing the zone `iterations` times.
+    pub fn bench_deserialize(&self, iterations: usize) -> Result<Measurement> {
+        let xfile = self.to_synthetic();
+        let bytes = xfile.to_bytes();
+        measure("deserialize", xfile.payload().len(), iterations, || {
+            deserialize(&bytes, self.platform)
+        })
+    }
+
+    /// Times serializing and deflating the zone's assets `iterations` times.
+    ///
+    /// The serializer skips asset types it doesn't support yet (like menus),
+    /// so they're left out of the timing. It can't write a Fastfile without
+    /// any assets either, so fails with [`ErrorKind::BrokenInvariant`] if
+    /// the zone has nothing but script strings and menus.
+    ///
+    /// [`ErrorKind::BrokenInvariant`]: t5_xfile_defs::ErrorKind::BrokenInvariant
+    #[cfg(feature = "serializer")]
+    pub fn bench_serialize(&self, iterations: usize) -> Result<Measurement> {
+        if self.rawfiles == 0 && self.pack_index_entries == 0 {
+            return Err(t5_xfile_defs::Error::new(
+                crate::file_line_col!(),
+                t5_xfile_defs::ErrorKind::BrokenInvariant(String::from(
+                    "the zone has no assets the serializer supports",
+                )),
+            ));
+        }
+
+        let xfile = self.to_synthetic();
+        let bytes = xfile.to_bytes();
+        // Cloning (like dropping) a deep linked list recursively could
+        // overflow the stack, and the serializer would skip menus anyway
+        let assets = deserialize(&bytes, self.platform)?
+            .into_iter()
+            .filter(|asset| asset.asset_type() != XAssetType::MENU)
+            .collect::<Vec<_>>();
+        let script_strings = (0..self.script_strings)
+            .map(|i| format!("bench_string_{i}"))
+            .collect::<Vec<_>>();
+
+        measure("serialize", xfile.payload().len(), iterations, || {
+            let mut ser = crate::T5XFileSerializerBuilder::new(self.platform)
+                .with_silent(true)
+                .with_script_strings(script_strings.iter().cloned())
+                .build()?;
+            ser.serialize_assets(assets.iter().cloned())?;
+            ser.deflate()
+        })
+    }
+}
+
+/// Inflates and deserializes every asset in `bytes`.
+fn deserialize(bytes: &[u8], platform: XFilePlatform) -> Result<Vec<XAsset>> {
+    T5XFileDeserializerBuilder::from_bytes(bytes, platform, true)
+        .with_silent(true)
+        .build()?
+        .inflate()?
+        .no_cache()?
+        .deserialize_remaining()
+}
+
+/// The times one benchmark took, as returned by [`measure`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub name: String,
+    /// The size of the inflated payload, which throughput is measured
+    /// against.
+    pub bytes: usize,
+    /// One per iteration, in the order they ran.
+    pub times: Vec<Duration>,
+}
+
+impl Measurement {
+    /// Returns [`Duration::ZERO`] if there were no iterations.
+    pub fn median(&self) -> Duration {
+        let mut times = self.times.clone();
+        times.sort();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.times.iter().min().copied().unwrap_or_default()
+    }
+
+    /// [`Self::bytes`] per second at the [median](Self::median) time, in
+    /// MiB.
+    pub fn throughput(&self) -> f64 {
+        self.bytes as f64 / (1024.0 * 1024.0) / self.median().as_secs_f64()
+    }
+}
+
+impl core::fmt::Display for Measurement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}: {:?} median, {:?} min over {} runs ({} bytes, {:.1} MiB/s)",
+            self.name,
+            self.median(),
+            self.min(),
+            self.times.len(),
+            self.bytes,
+            self.throughput()
+        )
+    }
+}
+
+/// Runs `f` `iterations` times (plus once beforehand to warm up), timing
+/// each run. Stops at the first error.
+pub fn measure<T>(
+    name: &str,
+    bytes: usize,
+    iterations: usize,
+    mut f: impl FnMut() -> Result<T>,
+) -> Result<Measurement> {
+    core::hint::black_box(f()?);
+
+    let mut times = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        core::hint::black_box(f()?);
+        times.push(start.elapsed());
+    }
+
+    Ok(Measurement {
+        name: name.to_string(),
+        bytes,
+        times,
+    })
+}
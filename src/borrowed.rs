@@ -0,0 +1,252 @@
+//! Deserializing assets that borrow from the inflated Fastfile data instead
+//! of copying out of it (see [`T5XFileBorrowedDeserializer`]).
+//!
+//! The large blobs that are borrowed are [`RawFile`](t5_xfile_defs::RawFile)
+//! buffers ([`XAssetRef::RawFile`]), shader programs
+//! ([`XAssetRef::TechniqueSet`]), sound samples ([`XAssetRef::Sound`]) and
+//! model vertices ([`XAssetRef::XModel`]). Everything else is still copied
+//! into [`XAssetRef::Owned`] assets like any other deserializer would, and
+//! the borrowed blobs stay in the file's byte order until they're copied
+//! with [`XAssetRef::into_owned`].
+
+use alloc::collections::VecDeque;
+
+use std::io::{Cursor, Seek};
+
+use crate::{Error, ErrorKind, Result, StreamLen, file_line_col, read_asset_list, reader};
+
+use t5_xfile_defs::{
+    Ptr32, PtrDepth, ScriptString, T5XFileDeserialize, T5XFileDeserializeBorrowed, XArray, XFile,
+    XFilePlatform, XFileRead,
+    encoding::StringEncoding,
+    xasset::{XAssetListRaw, XAssetRaw, XAssetRef},
+};
+
+/// Deserializes [`XAssetRef`]s out of an inflated payload owned by the
+/// caller, borrowing large blobs from it instead of copying them. See the
+/// [module docs](self) for which ones.
+///
+/// Usually obtained from an inflated deserializer with
+/// [`T5XFileDeserializer::borrowed`](crate::T5XFileDeserializer::borrowed),
+/// which keeps its settings, or with [`Self::new`] over a payload read
+/// some other way (e.g. from a cache file).
+pub struct T5XFileBorrowedDeserializer<'buf> {
+    silent: bool,
+    xfile: XFile,
+    script_strings: Vec<String>,
+    reader: Cursor<&'buf [u8]>,
+    xassets_raw: VecDeque<XAssetRaw<'buf>>,
+    deserialized_assets: usize,
+    non_null_assets: usize,
+    platform: XFilePlatform,
+    max_local_clients: usize,
+    ptr_depth: PtrDepth,
+    string_encoding: StringEncoding,
+}
+
+impl<'buf> T5XFileBorrowedDeserializer<'buf> {
+    /// Reads the header and asset list at the start of `payload`.
+    pub fn new(payload: &'buf [u8], platform: XFilePlatform, silent: bool) -> Result<Self> {
+        Self::with_payload(payload, platform, silent).read_asset_list()
+    }
+
+    /// A deserializer over `payload` that still has to read the header and
+    /// asset list (see [`Self::read_asset_list`]), or be given them (see
+    /// [`Self::with_assets`]).
+    pub(crate) fn with_payload(payload: &'buf [u8], platform: XFilePlatform, silent: bool) -> Self {
+        Self {
+            silent,
+            xfile: XFile::default(),
+            script_strings: Vec::new(),
+            reader: Cursor::new(payload),
+            xassets_raw: VecDeque::new(),
+            deserialized_assets: 0,
+            non_null_assets: 0,
+            platform,
+            max_local_clients: platform.max_local_clients(),
+            ptr_depth: PtrDepth::default(),
+            string_encoding: StringEncoding::default(),
+        }
+    }
+
+    /// Reads the header and asset list at the start of the payload, and the
+    /// script strings and assets the list points to.
+    pub(crate) fn read_asset_list(mut self) -> Result<Self> {
+        self.reader.set_position(0);
+        self.xfile = reader::read_from_cursor::<XFile>(&mut self.reader, self.platform.is_le())?;
+        let xasset_list =
+            reader::read_from_cursor::<XAssetListRaw>(&mut self.reader, self.platform.is_le())?;
+
+        if !self.silent {
+            println!("Fastfile contains {} assets.", xasset_list.assets.len());
+        }
+
+        let (script_strings, xassets_raw) = read_asset_list(&mut self, xasset_list)?;
+        self.script_strings = script_strings;
+        self.xassets_raw = xassets_raw;
+        Ok(self)
+    }
+
+    /// Uses a header, script strings and assets that were already read
+    /// (e.g. from a cache file), with the assets' data starting at
+    /// `data_offset`.
+    pub(crate) fn with_assets<'a>(
+        mut self,
+        xfile: XFile,
+        data_offset: u64,
+        script_strings: Vec<String>,
+        xassets_raw: impl IntoIterator<Item = &'a XAssetRaw<'a>>,
+    ) -> Self {
+        self.xfile = xfile;
+        self.reader.set_position(data_offset);
+        self.script_strings = script_strings;
+        self.xassets_raw = xassets_raw
+            .into_iter()
+            .map(|a| XAssetRaw {
+                asset_type: a.asset_type,
+                asset_data: Ptr32::from_u32(a.asset_data.as_u32()),
+            })
+            .collect();
+        self
+    }
+
+    /// See
+    /// [`T5XFileDeserializerBuilder::with_max_local_clients`](crate::T5XFileDeserializerBuilder::with_max_local_clients).
+    pub fn with_max_local_clients(mut self, max_local_clients: usize) -> Self {
+        self.max_local_clients = max_local_clients;
+        self
+    }
+
+    /// See
+    /// [`T5XFileDeserializerBuilder::with_max_ptr_depth`](crate::T5XFileDeserializerBuilder::with_max_ptr_depth).
+    pub fn with_max_ptr_depth(mut self, depth: usize) -> Self {
+        self.ptr_depth = PtrDepth::new(depth);
+        self
+    }
+
+    /// Decodes strings deserialized from now on with `string_encoding`.
+    pub(crate) fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    pub fn xfile(&self) -> XFile {
+        self.xfile
+    }
+
+    /// Deserializes the next [`XAssetRef`].
+    ///
+    /// Returns [`Ok(Some)`] if one or more assets remain, [`Ok(None)`] if
+    /// zero assets remain, or [`Err`] if an error occurs during
+    /// deserialization.
+    pub fn deserialize_next(&mut self) -> Result<Option<XAssetRef<'buf>>> {
+        let Some(asset) = self.xassets_raw.pop_front() else {
+            return Ok(None);
+        };
+
+        self.ptr_depth.reset();
+        let asset = XAssetRef::try_get_with_max_local_clients(self, asset, self.max_local_clients)?;
+        self.deserialized_assets += 1;
+        if asset.is_some() {
+            self.non_null_assets += 1;
+        }
+
+        if !self.silent {
+            println!(
+                "Successfully deserialized {} asset{} ({} non-null).",
+                self.deserialized_assets,
+                if self.deserialized_assets > 1 {
+                    "s"
+                } else {
+                    ""
+                },
+                self.non_null_assets,
+            );
+        }
+
+        // nothing after a blob can be read (see `XAsset::ends_zone`)
+        if asset.ends_zone() {
+            self.xassets_raw.clear();
+        }
+
+        Ok(Some(asset))
+    }
+
+    /// Deserializes the remaining [`XAssetRef`]s.
+    ///
+    /// Returns [`Ok`] if all remaining assets are deserialized successfully,
+    /// or [`Err`] if an error occurs during deserialization.
+    pub fn deserialize_remaining(mut self) -> Result<Vec<XAssetRef<'buf>>> {
+        let mut deserialized_assets = Vec::new();
+
+        while let Some(asset) = self.deserialize_next()? {
+            deserialized_assets.push(asset);
+        }
+
+        Ok(deserialized_assets)
+    }
+}
+
+impl<'buf> T5XFileDeserialize for T5XFileBorrowedDeserializer<'buf> {
+    fn stream_pos(&mut self) -> Result<u64> {
+        self.reader
+            .stream_position()
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        StreamLen::stream_len(&mut self.reader)
+    }
+
+    fn silent(&self) -> bool {
+        self.silent
+    }
+
+    fn is_little_endian(&self) -> bool {
+        self.platform.is_le()
+    }
+
+    fn string_encoding(&self) -> StringEncoding {
+        self.string_encoding
+    }
+
+    fn load_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.load_slice(len).map(<[u8]>::to_vec)
+    }
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T> {
+        reader::read_from_cursor(&mut self.reader, self.platform.is_le())
+    }
+
+    fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>> {
+        Ok(self
+            .script_strings
+            .get(string.as_u16() as usize)
+            .map(|s| &**s))
+    }
+
+    fn enter_ptr(&mut self) -> Result<()> {
+        self.ptr_depth.enter(self.reader.position())
+    }
+
+    fn leave_ptr(&mut self) {
+        self.ptr_depth.leave();
+    }
+}
+
+impl<'buf> T5XFileDeserializeBorrowed<'buf> for T5XFileBorrowedDeserializer<'buf> {
+    fn load_slice(&mut self, len: usize) -> Result<&'buf [u8]> {
+        let buf: &'buf [u8] = self.reader.get_ref();
+        let pos = self.reader.position() as usize;
+        let Some(slice) = pos.checked_add(len).and_then(|end| buf.get(pos..end)) else {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::Io(std::io::ErrorKind::UnexpectedEof.into()),
+            ));
+        };
+
+        self.reader.set_position((pos + len) as _);
+        Ok(slice)
+    }
+}
@@ -0,0 +1,382 @@
+//! The on-disk cache of an inflated Fastfile.
+//!
+//! Inflating a large Fastfile takes a while, so the inflated payload can be
+//! cached (see [`T5XFileDeserializer::cache`](crate::T5XFileDeserializer::cache))
+//! and later loaded with
+//! [`T5XFileDeserializerBuilder::from_cache_file`](crate::T5XFileDeserializerBuilder::from_cache_file).
+//!
+//! A cache file consists of a [`CacheHeader`] (encoded the same way a
+//! little-endian Fastfile is) followed by a single zstd frame containing the
+//! inflated payload. The header also stores the script strings and the asset
+//! list, so they don't have to be re-read from the payload, and identifies
+//! the Fastfile the cache was created from, so stale caches can be detected
+//! with [`cache_is_fresh`].
+//!
+//! Caches are normally created and used implicitly (see
+//! [`T5XFileDeserializer::open`](crate::T5XFileDeserializer::open)), but can
+//! also be managed directly with [`build_cache`], [`clear_cache`], and
+//! [`cache_info`].
+//!
+//! An archive (see
+//! [`T5XFileDeserializer::archive`](crate::T5XFileDeserializer::archive)) is
+//! a cache meant for long-term storage in place of the original Fastfile. It
+//! has the same layout, but starts with [`ARCHIVE_MAGIC`] and its payload is
+//! compressed much harder, which makes it slower to write but usually a good
+//! deal smaller than the Fastfile. Archives are loaded the same way caches
+//! are, and don't need the Fastfile they were created from to be around.
+
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BincodeOptions, CacheSuccess, Error, ErrorKind, Result, StreamLen, T5XFileDeserializerBuilder,
+    file_line_col,
+};
+
+use t5_xfile_defs::{XFile, XFilePlatform, xasset::XAssetRaw};
+
+/// The extension of a cache kept next to its Fastfile.
+pub const CACHE_FILE_EXTENSION: &str = "cache";
+/// The extension of an archive.
+pub const ARCHIVE_FILE_EXTENSION: &str = "archive";
+
+pub const CACHE_MAGIC: [u8; 8] = *b"T5XFCACH";
+pub const ARCHIVE_MAGIC: [u8; 8] = *b"T5XFARCH";
+
+/// Bumped whenever the layout of [`CacheHeader`] changes. Version 1 was the
+/// raw inflated payload, without any header, and version 2 didn't hash the
+/// Fastfile (see [`CacheSource::hash`]).
+pub const CACHE_VERSION: u32 = 3;
+
+const ZSTD_LEVEL: i32 = 3;
+const ARCHIVE_ZSTD_LEVEL: i32 = 19;
+
+/// Identifies the Fastfile a cache was created from.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSource {
+    pub len: u64,
+    /// Nanoseconds since the UNIX epoch, or `0` if unavailable. Only kept
+    /// for information, since copying a Fastfile changes its modification
+    /// time but not its contents.
+    pub modified: u64,
+    /// The XXH3 hash of the whole Fastfile.
+    pub hash: u64,
+}
+
+impl CacheSource {
+    /// Reads `file` from the start to hash it, leaving it at the end.
+    pub fn from_file(mut file: &File) -> Result<Self> {
+        let metadata = file
+            .metadata()
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+        let mut buf = vec![0u8; 1 << 16];
+        loop {
+            let n = file
+                .read(&mut buf)
+                .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+
+        Ok(Self {
+            len: metadata.len(),
+            modified,
+            hash: hasher.digest(),
+        })
+    }
+
+    /// Returns `true` if `self` and `other` identify the same Fastfile,
+    /// i.e., if they have the same length and hash.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.len == other.len && self.hash == other.hash
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CacheHeader<'a> {
+    pub(crate) magic: [u8; 8],
+    pub(crate) version: u32,
+    pub(crate) platform: u8,
+    pub(crate) source: CacheSource,
+    pub(crate) xfile: XFile,
+    pub(crate) script_strings: Vec<String>,
+    pub(crate) assets: Vec<XAssetRaw<'a>>,
+    /// Offset into the payload of the first asset's data.
+    pub(crate) data_offset: u64,
+    pub(crate) payload_len: u64,
+}
+
+impl<'a> CacheHeader<'a> {
+    pub(crate) fn new(
+        platform: XFilePlatform,
+        source: CacheSource,
+        xfile: XFile,
+        script_strings: Vec<String>,
+        assets: Vec<XAssetRaw<'a>>,
+        data_offset: u64,
+        payload_len: u64,
+    ) -> Self {
+        Self {
+            magic: CACHE_MAGIC,
+            version: CACHE_VERSION,
+            platform: platform as u8,
+            source,
+            xfile,
+            script_strings,
+            assets,
+            data_offset,
+            payload_len,
+        }
+    }
+
+    /// Turns the header into an archive's.
+    pub(crate) fn into_archive(self) -> Self {
+        Self {
+            magic: ARCHIVE_MAGIC,
+            ..self
+        }
+    }
+
+    pub(crate) fn is_archive(&self) -> bool {
+        self.magic == ARCHIVE_MAGIC
+    }
+
+    /// Reads and validates the header, leaving `reader` at the start of the
+    /// compressed payload.
+    pub(crate) fn read(reader: &mut (impl Read + Seek), platform: XFilePlatform) -> Result<Self> {
+        let header = Self::read_any_platform(reader)?;
+        if header.platform != platform as u8 {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                12,
+                ErrorKind::BadCacheFile(format!("cache wasn't created for {platform}")),
+            ));
+        }
+
+        Ok(header)
+    }
+
+    /// Same as [`Self::read`], but accepts a cache for any platform.
+    fn read_any_platform(reader: &mut (impl Read + Seek)) -> Result<Self> {
+        let opts = BincodeOptions::new(true);
+        // The header can't be any bigger than the file, so corrupt lengths
+        // are caught before they're allocated.
+        let pos = reader
+            .stream_position()
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        let limit = StreamLen::stream_len(reader)? - pos;
+
+        let magic = opts
+            .deserialize_from::<[u8; 8]>(&mut *reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        if magic != CACHE_MAGIC && magic != ARCHIVE_MAGIC {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::BadCacheFile(format!(
+                    "bad magic {magic:02X?} (probably a version 1 cache)"
+                )),
+            ));
+        }
+
+        let version = opts
+            .deserialize_from::<u32>(&mut *reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 8, ErrorKind::Bincode(e)))?;
+        if version != CACHE_VERSION {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                8,
+                ErrorKind::BadCacheFile(format!("version is {version}, expected {CACHE_VERSION}")),
+            ));
+        }
+
+        let rest = opts
+            .deserialize_from_with_limit::<CacheHeaderRest>(&mut *reader, limit.saturating_sub(12))
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 12, ErrorKind::Bincode(e)))?;
+
+        Ok(Self {
+            magic,
+            version,
+            platform: rest.platform,
+            source: rest.source,
+            xfile: rest.xfile,
+            script_strings: rest.script_strings,
+            assets: rest.assets,
+            data_offset: rest.data_offset,
+            payload_len: rest.payload_len,
+        })
+    }
+
+    /// Reads the header and the payload.
+    pub(crate) fn read_with_payload(
+        reader: &mut (impl Read + Seek),
+        platform: XFilePlatform,
+    ) -> Result<(Self, Vec<u8>)> {
+        let header = Self::read(reader, platform)?;
+
+        let payload = zstd::stream::decode_all(reader)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        if payload.len() as u64 != header.payload_len {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BadCacheFile(format!(
+                    "payload is {} bytes, expected {}",
+                    payload.len(),
+                    header.payload_len
+                )),
+            ));
+        }
+
+        Ok((header, payload))
+    }
+
+    pub(crate) fn write(&self, payload: &[u8], mut writer: impl Write) -> Result<()> {
+        BincodeOptions::new(true)
+            .serialize_into(&mut writer, self)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Bincode(e)))?;
+        let level = if self.is_archive() {
+            ARCHIVE_ZSTD_LEVEL
+        } else {
+            ZSTD_LEVEL
+        };
+        zstd::stream::copy_encode(payload, &mut writer, level)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+    }
+}
+
+/// Everything in [`CacheHeader`] after the magic and version, which have to
+/// be checked before the rest can be trusted.
+#[derive(Deserialize)]
+struct CacheHeaderRest<'a> {
+    platform: u8,
+    source: CacheSource,
+    xfile: XFile,
+    script_strings: Vec<String>,
+    assets: Vec<XAssetRaw<'a>>,
+    data_offset: u64,
+    payload_len: u64,
+}
+
+/// Returns `true` if the cache at `cache_path` is a valid cache for
+/// `platform` and was created from the Fastfile at `source_path` as it
+/// currently exists (see [`CacheSource::matches`]).
+///
+/// Caches written by an incompatible version of this library, caches for a
+/// different platform, and corrupt caches are all reported as not fresh
+/// rather than as errors, so the caller can just recreate them.
+pub fn cache_is_fresh(
+    cache_path: impl AsRef<Path>,
+    source_path: impl AsRef<Path>,
+    platform: XFilePlatform,
+) -> Result<bool> {
+    let source = File::open(source_path)
+        .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+        .and_then(|f| CacheSource::from_file(&f))?;
+
+    let mut cache = match File::open(cache_path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(Error::new(file_line_col!(), ErrorKind::Io(e))),
+    };
+
+    match CacheHeader::read(&mut cache, platform) {
+        Ok(header) => Ok(header.source.matches(&source)),
+        Err(e) if matches!(e.kind(), ErrorKind::BadCacheFile(_) | ErrorKind::Bincode(_)) => {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Where the cache of the Fastfile at `source_path` is kept.
+pub fn cache_path_for(source_path: impl AsRef<Path>) -> PathBuf {
+    source_path.as_ref().with_extension(CACHE_FILE_EXTENSION)
+}
+
+/// Inflates the Fastfile at `source_path` and writes its cache (see
+/// [`cache_path_for`]), without printing anything. If the cache is already
+/// fresh (see [`cache_is_fresh`]), it's left alone and [`None`] is returned,
+/// unless `force` is set.
+pub fn build_cache(
+    source_path: impl AsRef<Path>,
+    platform: XFilePlatform,
+    allow_unsupported_platforms: bool,
+    force: bool,
+) -> Result<Option<CacheSuccess>> {
+    let source_path = source_path.as_ref();
+    let cache_path = cache_path_for(source_path);
+    if !force && cache_is_fresh(&cache_path, source_path, platform)? {
+        return Ok(None);
+    }
+
+    let mut file = File::open(source_path)
+        .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+    T5XFileDeserializerBuilder::from_file(&mut file, platform, allow_unsupported_platforms)
+        .with_silent(true)
+        .build()?
+        .inflate()?
+        .cache(cache_path)
+        .map(|(_, success)| Some(success))
+}
+
+/// Deletes the cache of the Fastfile at `source_path` (see
+/// [`cache_path_for`]). Returns `false` if there wasn't one.
+pub fn clear_cache(source_path: impl AsRef<Path>) -> Result<bool> {
+    match std::fs::remove_file(cache_path_for(source_path)) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(Error::new(file_line_col!(), ErrorKind::Io(e))),
+    }
+}
+
+/// Returned by [`cache_info`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheInfo {
+    /// Whether the file is an archive rather than a cache.
+    pub archive: bool,
+    /// [`None`] if the cache claims to be for a platform that doesn't
+    /// exist.
+    pub platform: Option<XFilePlatform>,
+    pub source: CacheSource,
+    pub script_strings: usize,
+    pub assets: usize,
+    /// The size of the inflated payload.
+    pub payload_len: u64,
+}
+
+/// Reads the header of the cache or archive at `cache_path`, whatever
+/// platform it's for, without inflating the payload.
+pub fn cache_info(cache_path: impl AsRef<Path>) -> Result<CacheInfo> {
+    let mut file = File::open(cache_path)
+        .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+    let header = CacheHeader::read_any_platform(&mut file)?;
+
+    Ok(CacheInfo {
+        archive: header.is_archive(),
+        platform: XFilePlatform::ALL
+            .into_iter()
+            .find(|&p| p as u8 == header.platform),
+        source: header.source,
+        script_strings: header.script_strings.len(),
+        assets: header.assets.len(),
+        payload_len: header.payload_len,
+    })
+}
@@ -0,0 +1,72 @@
+//! Compression of Fastfile payloads.
+//!
+//! Which [`CompressionCodec`] a Fastfile's payload is compressed with is
+//! normally picked from its header (see [`codec_for_header`]), but zones
+//! compressed some other way can be read by handing the deserializer a codec
+//! of their own (see
+//! [`T5XFileDeserializerBuilder::with_codec`](crate::T5XFileDeserializerBuilder::with_codec)).
+
+use t5_xfile_defs::{Error, ErrorKind, Result, XFileHeader};
+
+use crate::file_line_col;
+
+pub trait CompressionCodec: Send + Sync {
+    /// A short name for the codec, for messages.
+    fn name(&self) -> &str;
+
+    /// Decompresses a payload (everything after the [`XFileHeader`]).
+    fn inflate(&self, compressed: &[u8]) -> Result<Vec<u8>>;
+
+    /// Compresses a payload.
+    fn deflate(&self, payload: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// A zlib stream, used by every retail T5 Fastfile (magics `IWffu100` and
+/// `IWff0100`).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Zlib;
+
+impl CompressionCodec for Zlib {
+    fn name(&self) -> &str {
+        "zlib"
+    }
+
+    fn inflate(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        inflate::inflate_bytes_zlib(compressed)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Inflate(e)))
+    }
+
+    fn deflate(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        Ok(deflate::deflate_bytes_zlib(payload))
+    }
+}
+
+/// A DEFLATE stream without the zlib header and checksum (magic
+/// `IWffa100`).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RawDeflate;
+
+impl CompressionCodec for RawDeflate {
+    fn name(&self) -> &str {
+        "raw DEFLATE"
+    }
+
+    fn inflate(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        inflate::inflate_bytes(compressed)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Inflate(e)))
+    }
+
+    fn deflate(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        Ok(deflate::deflate_bytes(payload))
+    }
+}
+
+/// The codec for the header's compression byte (see
+/// [`XFileHeader::compression`]), or [`None`] if it's not one that's known.
+pub fn codec_for_header(header: &XFileHeader) -> Option<Box<dyn CompressionCodec>> {
+    match header.compression() {
+        b'u' | b'0' => Some(Box::new(Zlib)),
+        b'a' => Some(Box::new(RawDeflate)),
+        _ => None,
+    }
+}
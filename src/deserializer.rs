@@ -1,19 +1,56 @@
-use core::marker::PhantomData;
+use core::{
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
 
 use alloc::collections::VecDeque;
-use serde::de::DeserializeOwned;
 
+use std::io::{Cursor, Read, Seek};
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "arc")]
 use std::{
-    io::{Cursor, Read, Seek, Write},
-    path::Path,
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use crate::{
+    Error, ErrorKind, Result, StreamLen,
+    codec::{CompressionCodec, codec_for_header},
+    file_line_col,
+    ipak::ImagePacks,
+    reader, size_of,
+    snapshot::DeserializerSnapshot,
+    sound_bank::SoundBanks,
 };
 
-use crate::{BincodeOptions, Error, ErrorKind, Result, StreamLen, file_line_col, size_of};
+#[cfg(feature = "cache")]
+use t5_xfile_defs::XStringRaw;
+
+#[cfg(feature = "cache")]
+use crate::{
+    ARCHIVE_FILE_EXTENSION, CACHE_FILE_EXTENSION, CacheHeader, CacheSource, cache_is_fresh,
+    cache_path_for,
+};
+
+#[cfg(feature = "trace")]
+use crate::{
+    reader::FixedLayoutReader,
+    trace::{Trace, short_type_name},
+};
 
 use t5_xfile_defs::{
-    FatPointer, ScriptString, T5XFileDeserialize, XFile, XFileDeserializeInto, XFileHeader,
-    XFilePlatform, XFileVersion,
-    xasset::{XAsset, XAssetListRaw, XAssetRaw},
+    PtrDepth, PtrMarker, ScriptString, T5XFileDeserialize, XArray, XFILE_VERSION, XFile,
+    XFileDeserializeInto, XFileHeader, XFileOffset, XFilePlatform, XFileProtection, XFileRead,
+    XFileVersion,
+    blocks::{InlinePtr, RealPtr, XFileBlocks},
+    encoding::StringEncoding,
+    read,
+    skip::AssetExtent,
+    visitor::XAssetVisitor,
+    xasset::{XAsset, XAssetFamily, XAssetListRaw, XAssetRaw, XAssetType},
 };
 
 pub enum InflateSuccess {
@@ -34,11 +71,57 @@ pub struct D3D9State<'a> {
 #[cfg(not(feature = "d3d9"))]
 pub(crate) struct D3D9State<'a>(PhantomData<&'a ()>);
 
+/// A file the deserializer either borrows or, when opened by
+/// [`T5XFileDeserializer::open`], owns.
+pub(crate) enum FileRef<'a> {
+    Borrowed(&'a mut std::fs::File),
+    Owned(std::fs::File),
+}
+
+impl Deref for FileRef<'_> {
+    type Target = std::fs::File;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Borrowed(f) => f,
+            Self::Owned(f) => f,
+        }
+    }
+}
+
+impl DerefMut for FileRef<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Borrowed(f) => f,
+            Self::Owned(f) => f,
+        }
+    }
+}
+
+/// Where a Fastfile is read from.
+pub(crate) enum XFileInput<'a> {
+    File(FileRef<'a>),
+    Bytes(&'a [u8]),
+    /// Read from sequentially, so it doesn't need to implement [`Seek`].
+    Reader(Box<dyn Read + 'a>),
+}
+
 /// Trait to seal [`T5XFileDeserializer`]'s typestates.
 pub(crate) trait T5XFileDeserializerTypestate {}
 
+/// The first stage of a [`T5XFileDeserializer`], returned by
+/// [`T5XFileDeserializerBuilder::build`]. The header has been read, but the
+/// payload is still compressed. [`T5XFileDeserializer::inflate`] moves on to
+/// [`T5XFileDeserializerInflated`].
 pub enum T5XFileDeserializerDeflated {}
+/// The second stage of a [`T5XFileDeserializer`]. The payload has been
+/// inflated (or loaded from a cache), and can be written to a cache or
+/// archive before moving on to [`T5XFileDeserializerDeserialize`] with
+/// [`T5XFileDeserializer::cache`], [`T5XFileDeserializer::archive`], or
+/// [`T5XFileDeserializer::no_cache`].
 pub enum T5XFileDeserializerInflated {}
+/// The last stage of a [`T5XFileDeserializer`]. The script strings and asset
+/// list have been read, and assets can be deserialized one after another.
 pub enum T5XFileDeserializerDeserialize {}
 
 impl T5XFileDeserializerTypestate for T5XFileDeserializerDeflated {}
@@ -51,57 +134,189 @@ pub struct T5XFileDeserializer<'a, T: T5XFileDeserializerTypestate = T5XFileDese
     silent: bool,
     xfile: XFile,
     script_strings: Vec<String>,
-    file: Option<&'a mut std::fs::File>,
-    cache_file: Option<&'a mut std::fs::File>,
+    file: Option<XFileInput<'a>>,
+    cache_file: Option<FileRef<'a>>,
     reader: Option<Cursor<Vec<u8>>>,
     xasset_list: XAssetListRaw<'a>,
     xassets_raw: VecDeque<XAssetRaw<'a>>,
     deserialized_assets: usize,
     non_null_assets: usize,
-    opts: BincodeOptions,
     platform: XFilePlatform,
     d3d9_state: Option<D3D9State<'a>>,
+    /// What the payload is compressed with. [`None`] once it's been
+    /// inflated, or if it's read from a cache file.
+    codec: Option<Box<dyn CompressionCodec>>,
+    /// The Fastfile being deserialized, if known. Recorded in cache files.
+    #[cfg(feature = "cache")]
+    source: Option<CacheSource>,
+    /// Set when the script strings and asset list were loaded from a cache
+    /// file rather than from the payload.
+    index_loaded: bool,
+    /// Maximum number of bytes the arrays of a single asset may take up.
+    allocation_limit: Option<usize>,
+    /// See [`T5XFileDeserializerBuilder::with_string_encoding`].
+    string_encoding: StringEncoding,
+    /// Number of bytes the arrays of the current asset take up so far.
+    allocated: usize,
+    /// See [`T5XFileDeserializerBuilder::with_max_local_clients`].
+    max_local_clients: usize,
+    /// How deeply the inline pointers of the current asset are nested.
+    ptr_depth: PtrDepth,
+    blocks: XFileBlocks,
+    /// See [`T5XFileDeserializerBuilder::with_game_dir`].
+    image_packs: Option<ImagePacks>,
+    sound_banks: Option<SoundBanks>,
+    #[cfg(feature = "trace")]
+    trace: Option<Trace>,
+    /// Objects loaded behind [`Shared`](t5_xfile_defs::Shared) pointers, by
+    /// where in the payload they were loaded from (see
+    /// [`T5XFileDeserialize::intern`]).
+    #[cfg(feature = "arc")]
+    interned: HashMap<(u64, TypeId), Arc<dyn Any + Send + Sync>>,
     _p: PhantomData<T>,
 }
 
+/// Configures a [`T5XFileDeserializer`] and builds it.
+///
+/// Deserializing a Fastfile goes through three stages, each its own type
+/// (see [`T5XFileDeserializerDeflated`], [`T5XFileDeserializerInflated`],
+/// and [`T5XFileDeserializerDeserialize`]):
+///
+/// 1. [`Self::build`] reads and checks the header.
+/// 2. [`T5XFileDeserializer::inflate`] inflates the payload, or loads it
+///    from the cache the builder was created with.
+/// 3. [`T5XFileDeserializer::cache`], [`T5XFileDeserializer::archive`], or
+///    [`T5XFileDeserializer::no_cache`] reads the script strings and asset
+///    list, after writing the payload to disk if asked to.
+///
+/// [`T5XFileDeserializer::open`] goes through all three at once, deciding
+/// whether to use a cache at runtime, for when that level of control isn't
+/// needed.
 pub struct T5XFileDeserializerBuilder<'a> {
-    file: Option<&'a mut std::fs::File>,
-    cache_file: Option<&'a mut std::fs::File>,
+    file: Option<XFileInput<'a>>,
+    #[cfg(feature = "cache")]
+    cache_file: Option<FileRef<'a>>,
     silent: bool,
     platform: XFilePlatform,
     allow_unsupported_platforms: bool,
+    allocation_limit: Option<usize>,
+    string_encoding: StringEncoding,
+    max_local_clients: Option<usize>,
+    max_ptr_depth: Option<usize>,
+    follow_real_ptrs: bool,
+    record_ptr_markers: bool,
+    game_dir: Option<PathBuf>,
+    #[cfg(feature = "trace")]
+    trace: bool,
     d3d9_state: Option<D3D9State<'a>>,
+    codec: Option<Box<dyn CompressionCodec>>,
 }
 
 impl<'a> T5XFileDeserializerBuilder<'a> {
-    pub fn from_file(
-        file: &'a mut std::fs::File,
+    /// Every constructor goes through here, so the defaults only live in one
+    /// place.
+    fn new(
+        file: Option<XFileInput<'a>>,
         platform: XFilePlatform,
         allow_unsupported_platforms: bool,
     ) -> Self {
         Self {
-            file: Some(file),
+            file,
+            #[cfg(feature = "cache")]
             cache_file: None,
             platform,
             silent: false,
             allow_unsupported_platforms,
+            allocation_limit: None,
+            string_encoding: StringEncoding::default(),
+            max_local_clients: None,
+            max_ptr_depth: None,
+            follow_real_ptrs: false,
+            record_ptr_markers: false,
+            game_dir: None,
+            #[cfg(feature = "trace")]
+            trace: false,
             d3d9_state: None,
+            codec: None,
         }
     }
 
+    pub fn from_file(
+        file: &'a mut std::fs::File,
+        platform: XFilePlatform,
+        allow_unsupported_platforms: bool,
+    ) -> Self {
+        Self::new(
+            Some(XFileInput::File(FileRef::Borrowed(file))),
+            platform,
+            allow_unsupported_platforms,
+        )
+    }
+
+    /// Same as [`Self::from_file`], but for a Fastfile that's already in
+    /// memory (e.g., one fetched over the network).
+    pub fn from_bytes(
+        bytes: &'a [u8],
+        platform: XFilePlatform,
+        allow_unsupported_platforms: bool,
+    ) -> Self {
+        Self::new(
+            Some(XFileInput::Bytes(bytes)),
+            platform,
+            allow_unsupported_platforms,
+        )
+    }
+
+    /// Same as [`Self::from_file`], but for any [`Read`]. `reader` is only
+    /// ever read from front to back, so it doesn't need to implement
+    /// [`Seek`].
+    pub fn from_reader(
+        reader: impl Read + 'a,
+        platform: XFilePlatform,
+        allow_unsupported_platforms: bool,
+    ) -> Self {
+        Self::new(
+            Some(XFileInput::Reader(Box::new(reader))),
+            platform,
+            allow_unsupported_platforms,
+        )
+    }
+
+    /// Loads the inflated payload from a cache or archive (see
+    /// [`crate::cache`]) rather than a Fastfile.
+    #[cfg(feature = "cache")]
     pub fn from_cache_file(
         cache_file: &'a mut std::fs::File,
         platform: XFilePlatform,
         allow_unsupported_platforms: bool,
     ) -> Self {
         Self {
-            file: None,
-            cache_file: Some(cache_file),
+            cache_file: Some(FileRef::Borrowed(cache_file)),
+            ..Self::new(None, platform, allow_unsupported_platforms)
+        }
+    }
+
+    /// Same as [`Self::from_file`] (or [`Self::from_cache_file`] if
+    /// `is_cache` is set), but the deserializer keeps `file` itself.
+    fn from_owned_file(
+        file: std::fs::File,
+        #[cfg_attr(not(feature = "cache"), allow(unused_variables))] is_cache: bool,
+        platform: XFilePlatform,
+        allow_unsupported_platforms: bool,
+    ) -> Self {
+        #[cfg(feature = "cache")]
+        if is_cache {
+            return Self {
+                cache_file: Some(FileRef::Owned(file)),
+                ..Self::new(None, platform, allow_unsupported_platforms)
+            };
+        }
+
+        Self::new(
+            Some(XFileInput::File(FileRef::Owned(file))),
             platform,
-            silent: false,
             allow_unsupported_platforms,
-            d3d9_state: None,
-        }
+        )
     }
 
     pub fn with_silent(mut self, silent: bool) -> Self {
@@ -114,6 +329,104 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
         self
     }
 
+    /// Limits the memory that the arrays of a single asset may take up to
+    /// `bytes`. Assets exceeding the limit fail to deserialize with
+    /// [`ErrorKind::BrokenInvariant`] rather than exhausting memory. The
+    /// script strings and asset list, read by
+    /// [`T5XFileDeserializer::no_cache`], count as one asset.
+    ///
+    /// Regardless of this limit, arrays that are larger than what's left of
+    /// the payload are always rejected.
+    pub fn with_allocation_limit(mut self, bytes: usize) -> Self {
+        self.allocation_limit = Some(bytes);
+        self
+    }
+
+    /// Deserializes assets as if the Fastfile had room for
+    /// `max_local_clients` local clients, rather than the platform's default
+    /// (see [`XFilePlatform::max_local_clients`]). Useful for Fastfiles that
+    /// don't follow their platform's convention, e.g. ones built for a
+    /// split-screen PC mod.
+    ///
+    /// Only 1 and 4 are supported (see
+    /// [`XAsset::try_get_with_max_local_clients`]). The number is only
+    /// needed once assets are deserialized, so an unsupported one isn't
+    /// rejected until then.
+    pub fn with_max_local_clients(mut self, max_local_clients: usize) -> Self {
+        self.max_local_clients = Some(max_local_clients);
+        self
+    }
+
+    /// Limits how deeply the inline pointers of a single asset may be nested
+    /// to `depth`, instead of
+    /// [`DEFAULT_MAX_PTR_DEPTH`](t5_xfile_defs::DEFAULT_MAX_PTR_DEPTH). Assets exceeding the
+    /// limit fail to deserialize with [`ErrorKind::PtrDepthExceeded`] rather
+    /// than overflowing the stack.
+    pub fn with_max_ptr_depth(mut self, depth: usize) -> Self {
+        self.max_ptr_depth = Some(depth);
+        self
+    }
+
+    /// Loads the objects that real offsets (see
+    /// [`Ptr32::is_real`](t5_xfile_defs::Ptr32::is_real)) point to again,
+    /// instead of leaving them out, whenever they can be resolved (see
+    /// [`t5_xfile_defs::blocks`]). Offsets that point back at an object
+    /// that's still being loaded are left out regardless.
+    ///
+    /// Every real offset is recorded either way (see
+    /// [`T5XFileDeserializer::real_ptrs`]).
+    pub fn with_follow_real_ptrs(mut self, follow: bool) -> Self {
+        self.follow_real_ptrs = follow;
+        self
+    }
+
+    /// Records which marker every inline pointer was serialized with (see
+    /// [`PtrMarker`], [`T5XFileDeserializer::inline_ptrs`], and
+    /// [`T5XFileDeserializer::ptr_markers`]). Markers are counted either way
+    /// (see [`T5XFileDeserializer::ptr_marker_count`]).
+    pub fn with_record_ptr_markers(mut self, record: bool) -> Self {
+        self.record_ptr_markers = record;
+        self
+    }
+
+    /// Decodes strings with `string_encoding` rather than
+    /// [`StringEncoding::default`], e.g. to reject strings that aren't valid
+    /// UTF-8 (see [`t5_xfile_defs::encoding`]).
+    pub fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    /// Fuses the streamed mips of every [`GfxImage`](t5_xfile_defs::techset::GfxImage)
+    /// with the image packs in the game install at `dir` (see
+    /// [`crate::ipak`]), and loads the audio of every streamed sound from
+    /// its sound banks (see [`crate::sound_bank`]). Packs and banks are
+    /// looked for in the install's root and zone directories (see
+    /// [`GameDir::companion_dirs`](crate::GameDir::companion_dirs)), and
+    /// are opened when the deserializer is built.
+    pub fn with_game_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(dir.into());
+        self
+    }
+
+    /// Records the offset and length of every value and field read from the
+    /// payload (see [`crate::trace`] and [`T5XFileDeserializer::trace`]).
+    /// Slows deserialization down considerably.
+    #[cfg(feature = "trace")]
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Inflates the payload with `codec` rather than the one the header's
+    /// compression byte asks for (see [`codec_for_header`]), for zones
+    /// compressed some other way. The compression byte isn't checked at all
+    /// when a codec is given.
+    pub fn with_codec(mut self, codec: impl CompressionCodec + 'static) -> Self {
+        self.codec = Some(Box::new(codec));
+        self
+    }
+
     #[cfg(feature = "d3d9")]
     pub fn with_d3d9(mut self, d3d9_state: Option<D3D9State<'a>>) -> Self {
         self.d3d9_state = d3d9_state;
@@ -121,35 +434,285 @@ impl<'a> T5XFileDeserializerBuilder<'a> {
     }
 
     pub fn build(mut self) -> Result<T5XFileDeserializer<'a, T5XFileDeserializerDeflated>> {
-        if self.file.is_some() {
-            T5XFileDeserializer::from_file(
-                self.file.take().unwrap(),
-                self.silent,
-                self.allow_unsupported_platforms,
-                self.platform,
-                self.d3d9_state,
-            )
-        } else if self.cache_file.is_some() {
-            T5XFileDeserializer::from_cache_file(
-                self.cache_file.take().unwrap(),
+        let image_packs = self
+            .game_dir
+            .as_ref()
+            .map(ImagePacks::from_game_dir)
+            .transpose()?;
+        let sound_banks = self
+            .game_dir
+            .as_ref()
+            .map(SoundBanks::from_game_dir)
+            .transpose()?;
+
+        #[cfg(feature = "cache")]
+        if let Some(cache_file) = self.cache_file.take() {
+            let de = T5XFileDeserializer::from_cache_file(
+                cache_file,
                 self.silent,
                 self.allow_unsupported_platforms,
                 self.platform,
                 self.d3d9_state,
             )
-        } else {
-            unreachable!()
+            .map(|de| {
+                de.with_allocation_limit(self.allocation_limit)
+                    .with_max_local_clients(self.max_local_clients)
+                    .with_max_ptr_depth(self.max_ptr_depth)
+                    .with_follow_real_ptrs(self.follow_real_ptrs)
+                    .with_record_ptr_markers(self.record_ptr_markers)
+                    .with_string_encoding(self.string_encoding)
+                    .with_companions(image_packs, sound_banks)
+            });
+            #[cfg(feature = "trace")]
+            let de = de.map(|de| de.with_trace(self.trace));
+            return de;
+        }
+
+        let de = T5XFileDeserializer::from_file(
+            // safe since the constructors had to populate either self.file or
+            // self.cache_file
+            self.file.take().unwrap(),
+            self.silent,
+            self.allow_unsupported_platforms,
+            self.platform,
+            self.d3d9_state,
+            self.codec,
+        )
+        .map(|de| {
+            de.with_allocation_limit(self.allocation_limit)
+                .with_max_local_clients(self.max_local_clients)
+                .with_max_ptr_depth(self.max_ptr_depth)
+                .with_follow_real_ptrs(self.follow_real_ptrs)
+                .with_record_ptr_markers(self.record_ptr_markers)
+                .with_string_encoding(self.string_encoding)
+                .with_companions(image_packs, sound_banks)
+        });
+        #[cfg(feature = "trace")]
+        let de = de.map(|de| de.with_trace(self.trace));
+        de
+    }
+}
+
+/// How [`T5XFileDeserializer::open`] uses the cache next to a Fastfile
+/// (see [`crate::cache`]).
+#[cfg(feature = "cache")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Loads the cache if it's fresh (see [`cache_is_fresh`]), or if the
+    /// Fastfile isn't around. Otherwise, inflates the Fastfile and
+    /// (re)creates the cache.
+    #[default]
+    ReadWrite,
+    /// Loads the cache like [`Self::ReadWrite`], but never writes it.
+    ReadOnly,
+    /// Always inflates the Fastfile, and never touches the cache.
+    Disabled,
+}
+
+/// Options for [`T5XFileDeserializer::open`]. Each option defaults to, and
+/// does the same thing as, the [`T5XFileDeserializerBuilder`] method of the
+/// same name.
+pub struct T5XFileOpenOptions<'a> {
+    platform: XFilePlatform,
+    allow_unsupported_platforms: bool,
+    silent: bool,
+    #[cfg(feature = "cache")]
+    cache: CacheMode,
+    allocation_limit: Option<usize>,
+    string_encoding: StringEncoding,
+    max_local_clients: Option<usize>,
+    max_ptr_depth: Option<usize>,
+    follow_real_ptrs: bool,
+    record_ptr_markers: bool,
+    game_dir: Option<PathBuf>,
+    #[cfg(feature = "trace")]
+    trace: bool,
+    d3d9_state: Option<D3D9State<'a>>,
+    codec: Option<Box<dyn CompressionCodec>>,
+}
+
+impl<'a> T5XFileOpenOptions<'a> {
+    pub fn new(platform: XFilePlatform) -> Self {
+        Self {
+            platform,
+            allow_unsupported_platforms: false,
+            silent: false,
+            #[cfg(feature = "cache")]
+            cache: CacheMode::default(),
+            allocation_limit: None,
+            string_encoding: StringEncoding::default(),
+            max_local_clients: None,
+            max_ptr_depth: None,
+            follow_real_ptrs: false,
+            record_ptr_markers: false,
+            game_dir: None,
+            #[cfg(feature = "trace")]
+            trace: false,
+            d3d9_state: None,
+            codec: None,
+        }
+    }
+
+    pub fn with_allow_unsupported_platforms(mut self, allow_unsupported_platforms: bool) -> Self {
+        self.allow_unsupported_platforms = allow_unsupported_platforms;
+        self
+    }
+
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    #[cfg(feature = "cache")]
+    pub fn with_cache(mut self, cache: CacheMode) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_allocation_limit(mut self, bytes: usize) -> Self {
+        self.allocation_limit = Some(bytes);
+        self
+    }
+
+    pub fn with_max_local_clients(mut self, max_local_clients: usize) -> Self {
+        self.max_local_clients = Some(max_local_clients);
+        self
+    }
+
+    pub fn with_max_ptr_depth(mut self, depth: usize) -> Self {
+        self.max_ptr_depth = Some(depth);
+        self
+    }
+
+    pub fn with_follow_real_ptrs(mut self, follow: bool) -> Self {
+        self.follow_real_ptrs = follow;
+        self
+    }
+
+    pub fn with_record_ptr_markers(mut self, record: bool) -> Self {
+        self.record_ptr_markers = record;
+        self
+    }
+
+    pub fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    pub fn with_game_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(dir.into());
+        self
+    }
+
+    #[cfg(feature = "trace")]
+    pub fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    pub fn with_codec(mut self, codec: impl CompressionCodec + 'static) -> Self {
+        self.codec = Some(Box::new(codec));
+        self
+    }
+
+    #[cfg(feature = "d3d9")]
+    pub fn with_d3d9(mut self, d3d9_state: Option<D3D9State<'a>>) -> Self {
+        self.d3d9_state = d3d9_state;
+        self
+    }
+
+    fn builder(self, file: std::fs::File, is_cache: bool) -> T5XFileDeserializerBuilder<'a> {
+        let mut builder = T5XFileDeserializerBuilder::from_owned_file(
+            file,
+            is_cache,
+            self.platform,
+            self.allow_unsupported_platforms,
+        )
+        .with_silent(self.silent)
+        .with_follow_real_ptrs(self.follow_real_ptrs)
+        .with_record_ptr_markers(self.record_ptr_markers)
+        .with_string_encoding(self.string_encoding);
+        builder.allocation_limit = self.allocation_limit;
+        builder.max_local_clients = self.max_local_clients;
+        builder.max_ptr_depth = self.max_ptr_depth;
+        builder.game_dir = self.game_dir;
+        builder.d3d9_state = self.d3d9_state;
+        builder.codec = self.codec;
+        #[cfg(feature = "trace")]
+        let builder = builder.with_trace(self.trace);
+        builder
+    }
+}
+
+impl<'a> T5XFileDeserializer<'a> {
+    /// Opens the Fastfile at `path` and readies it to be deserialized, going
+    /// through every stage of [`T5XFileDeserializerBuilder`] at once.
+    ///
+    /// With the `cache` feature, `path` can also be a cache or archive
+    /// (going by its extension, see [`CACHE_FILE_EXTENSION`] and
+    /// [`ARCHIVE_FILE_EXTENSION`]), which is loaded as is. Otherwise, the
+    /// cache next to the Fastfile is used as set by
+    /// [`T5XFileOpenOptions::with_cache`].
+    pub fn open(path: impl AsRef<Path>, options: T5XFileOpenOptions<'a>) -> Result<Self> {
+        let path = path.as_ref();
+        let open = |path: &Path| {
+            std::fs::File::open(path)
+                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))
+        };
+
+        #[cfg(feature = "cache")]
+        let cache_path = cache_path_for(path);
+        #[cfg(feature = "cache")]
+        let cache = options.cache;
+        #[cfg(feature = "cache")]
+        {
+            let is_cache = path
+                .extension()
+                .is_some_and(|ext| ext == CACHE_FILE_EXTENSION || ext == ARCHIVE_FILE_EXTENSION);
+            if is_cache {
+                return options
+                    .builder(open(path)?, true)
+                    .build()?
+                    .inflate()?
+                    .no_cache();
+            }
+
+            let fresh = match cache {
+                CacheMode::Disabled => false,
+                _ if !path.exists() => cache_path.exists(),
+                _ => cache_is_fresh(&cache_path, path, options.platform)?,
+            };
+            if fresh {
+                return options
+                    .builder(open(&cache_path)?, true)
+                    .build()?
+                    .inflate()?
+                    .no_cache();
+            }
+            if cache == CacheMode::ReadWrite && !options.silent && cache_path.exists() {
+                println!("Cache is stale or invalid, recreating it...");
+            }
+        }
+
+        let de = options.builder(open(path)?, false).build()?.inflate()?;
+
+        #[cfg(feature = "cache")]
+        if cache == CacheMode::ReadWrite {
+            return de.cache(cache_path).map(|(de, _)| de);
         }
+
+        de.no_cache()
     }
 }
 
 impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
     fn from_file(
-        file: &'a mut std::fs::File,
+        mut file: XFileInput<'a>,
         silent: bool,
         allow_unsupported_platforms: bool,
         platform: XFilePlatform,
         d3d9_state: Option<D3D9State<'a>>,
+        codec: Option<Box<dyn CompressionCodec>>,
     ) -> Result<Self> {
         if platform == XFilePlatform::Wii {
             if !silent {
@@ -164,12 +727,14 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
         }
 
         if platform == XFilePlatform::Xbox360 || platform == XFilePlatform::PS3 {
-            if allow_unsupported_platforms && !silent {
-                println!(
-                    "Warning: {platform} Fastfiles might (and probably do) have differences \
-                     from Windows Fastfiles that aren't accounted for in this \
-                     library. Expect problems."
-                );
+            if allow_unsupported_platforms {
+                if !silent {
+                    println!(
+                        "Warning: {platform} Fastfiles might (and probably do) have differences \
+                         from Windows Fastfiles that aren't accounted for in this \
+                         library. Expect problems."
+                    );
+                }
             } else {
                 if !silent {
                     println!(
@@ -213,17 +778,40 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             println!("Found file, reading header...");
         }
 
-        let opts = BincodeOptions::from_platform(platform);
-
-        let header = opts
-            .deserialize_from::<XFileHeader>(&mut *file)
-            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        let header = match &mut file {
+            XFileInput::File(f) => reader::read_from::<XFileHeader>(&mut **f, platform.is_le()),
+            XFileInput::Bytes(b) => reader::read_from::<XFileHeader>(*b, platform.is_le()),
+            XFileInput::Reader(r) => reader::read_from::<XFileHeader>(r, platform.is_le()),
+        }?;
 
         // dbg!(&header);
 
-        if !header.magic_is_valid() {
+        if let Some(protection) = header.protection() {
             if !silent {
-                println!("Fastfile header magic invalid: valid values are IWffu100 and IWff0100");
+                println!(
+                    "Fastfile is {protection} (magic {}), probably because it's from a later \
+                     title or a protected build. Strip or decrypt it with a tool for its game first.",
+                    header.magic_string()
+                );
+            }
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::ProtectedFile(protection),
+            ));
+        }
+
+        let magic_is_valid = if codec.is_some() {
+            header.magic_is_valid_for_any_compression()
+        } else {
+            header.magic_is_valid()
+        };
+        if !magic_is_valid {
+            if !silent {
+                println!(
+                    "Fastfile header magic invalid: valid values are IWffu100, IWff0100, and \
+                     IWffa100"
+                );
             }
             return Err(Error::new_with_offset(
                 file_line_col!(),
@@ -250,8 +838,7 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             if !silent {
                 println!(
                     "Fastfile is wrong version (version={:#010X}, expected {:#010X})",
-                    header.version,
-                    XFileVersion::from_platform(platform).as_u32()
+                    header.version, XFILE_VERSION
                 );
             }
 
@@ -262,6 +849,9 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             ));
         }
 
+        // the magic was checked above, so there's always a codec for it
+        let codec = codec.or_else(|| codec_for_header(&header));
+
         if !silent {
             println!("Header verified, reading playload...");
         }
@@ -277,17 +867,33 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xassets_raw: VecDeque::new(),
             deserialized_assets: 0,
             non_null_assets: 0,
-            opts,
             platform,
             d3d9_state,
+            codec,
+            #[cfg(feature = "cache")]
+            source: None,
+            index_loaded: false,
+            allocation_limit: None,
+            string_encoding: StringEncoding::default(),
+            allocated: 0,
+            max_local_clients: platform.max_local_clients(),
+            ptr_depth: PtrDepth::default(),
+            blocks: XFileBlocks::new(),
+            image_packs: None,
+            sound_banks: None,
+            #[cfg(feature = "trace")]
+            trace: None,
+            #[cfg(feature = "arc")]
+            interned: HashMap::new(),
             _p: PhantomData,
         };
 
         Ok(de)
     }
 
+    #[cfg(feature = "cache")]
     fn from_cache_file(
-        file: &'a mut std::fs::File,
+        file: FileRef<'a>,
         silent: bool,
         allow_unsupported_platforms: bool,
         platform: XFilePlatform,
@@ -306,12 +912,14 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
         }
 
         if platform == XFilePlatform::Xbox360 || platform == XFilePlatform::PS3 {
-            if allow_unsupported_platforms && !silent {
-                println!(
-                    "Warning: {platform} Fastfiles might (and probably do) have differences \
-                     from Windows Fastfiles that aren't accounted for in this \
-                     library. Expect problems."
-                );
+            if allow_unsupported_platforms {
+                if !silent {
+                    println!(
+                        "Warning: {platform} Fastfiles might (and probably do) have differences \
+                         from Windows Fastfiles that aren't accounted for in this \
+                         library. Expect problems."
+                    );
+                }
             } else {
                 if !silent {
                     println!(
@@ -366,38 +974,198 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xassets_raw: VecDeque::new(),
             deserialized_assets: 0,
             non_null_assets: 0,
-            opts: BincodeOptions::from_platform(platform),
             platform,
             d3d9_state,
+            codec: None,
+            #[cfg(feature = "cache")]
+            source: None,
+            index_loaded: false,
+            allocation_limit: None,
+            string_encoding: StringEncoding::default(),
+            allocated: 0,
+            max_local_clients: platform.max_local_clients(),
+            ptr_depth: PtrDepth::default(),
+            blocks: XFileBlocks::new(),
+            image_packs: None,
+            sound_banks: None,
+            #[cfg(feature = "trace")]
+            trace: None,
+            #[cfg(feature = "arc")]
+            interned: HashMap::new(),
             _p: PhantomData,
         })
     }
 
+    fn with_allocation_limit(mut self, allocation_limit: Option<usize>) -> Self {
+        self.allocation_limit = allocation_limit;
+        self
+    }
+
+    fn with_max_local_clients(mut self, max_local_clients: Option<usize>) -> Self {
+        if let Some(max_local_clients) = max_local_clients {
+            self.max_local_clients = max_local_clients;
+        }
+        self
+    }
+
+    fn with_follow_real_ptrs(mut self, follow: bool) -> Self {
+        self.blocks = self.blocks.with_follow(follow);
+        self
+    }
+
+    fn with_record_ptr_markers(mut self, record: bool) -> Self {
+        self.blocks = self.blocks.with_record_markers(record);
+        self
+    }
+
+    fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    fn with_companions(
+        mut self,
+        image_packs: Option<ImagePacks>,
+        sound_banks: Option<SoundBanks>,
+    ) -> Self {
+        self.image_packs = image_packs;
+        self.sound_banks = sound_banks;
+        self
+    }
+
+    fn with_max_ptr_depth(mut self, max_ptr_depth: Option<usize>) -> Self {
+        if let Some(max_ptr_depth) = max_ptr_depth {
+            self.ptr_depth = PtrDepth::new(max_ptr_depth);
+        }
+        self
+    }
+
+    #[cfg(feature = "trace")]
+    fn with_trace(mut self, trace: bool) -> Self {
+        self.trace = trace.then(Trace::new);
+        self
+    }
+
     pub fn inflate(mut self) -> Result<T5XFileDeserializer<'a, T5XFileDeserializerInflated>> {
         assert!(self.reader.is_none());
 
-        let reader = if let Some(f) = self.cache_file.take() {
-            let mut decompressed_payload = Vec::new();
-            f.read_to_end(&mut decompressed_payload)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
-            Cursor::new(decompressed_payload)
-        } else if let Some(f) = self.file.take() {
+        #[cfg(feature = "cache")]
+        if let Some(mut f) = self.cache_file.take() {
+            let (header, payload) = CacheHeader::read_with_payload(&mut *f, self.platform)?;
+
+            if !self.silent {
+                println!(
+                    "Cache read, inflated size: {} bytes, {} assets.",
+                    payload.len(),
+                    header.assets.len()
+                );
+            }
+
+            let mut reader = Cursor::new(payload);
+            reader.set_position(header.data_offset);
+
+            self.reader = Some(reader);
+            self.xfile = header.xfile;
+            self.script_strings = header.script_strings;
+            self.xassets_raw = VecDeque::from(header.assets);
+            self.source = Some(header.source);
+            self.index_loaded = true;
+
+            // the script strings and asset list were allocated from the
+            // blocks even though they're not read from the payload here
+            self.blocks
+                .alloc(4, self.script_strings.len() * size_of!(XStringRaw));
+            for string in &self.script_strings {
+                self.blocks.alloc(1, string.len() + 1);
+            }
+            self.blocks
+                .alloc(4, self.xassets_raw.len() * size_of!(XAssetRaw));
+
+            return Ok(T5XFileDeserializer::<T5XFileDeserializerInflated> {
+                silent: self.silent,
+                xfile: self.xfile,
+                script_strings: self.script_strings,
+                file: self.file,
+                cache_file: self.cache_file,
+                reader: self.reader,
+                xasset_list: self.xasset_list,
+                xassets_raw: self.xassets_raw,
+                deserialized_assets: self.deserialized_assets,
+                non_null_assets: self.non_null_assets,
+                platform: self.platform,
+                d3d9_state: self.d3d9_state,
+                codec: None,
+                #[cfg(feature = "cache")]
+                source: self.source,
+                index_loaded: self.index_loaded,
+                allocation_limit: self.allocation_limit,
+                string_encoding: self.string_encoding,
+                allocated: self.allocated,
+                max_local_clients: self.max_local_clients,
+                ptr_depth: self.ptr_depth,
+                blocks: self.blocks,
+                image_packs: self.image_packs,
+                sound_banks: self.sound_banks,
+                #[cfg(feature = "trace")]
+                trace: self.trace,
+                #[cfg(feature = "arc")]
+                interned: self.interned,
+                _p: PhantomData,
+            });
+        }
+
+        let reader = if let Some(input) = self.file.take() {
             let mut compressed_payload = Vec::new();
-            f.seek(std::io::SeekFrom::Start(size_of!(XFileHeader) as _))
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
-            // dbg!(f.stream_position().map_err(|e| Error::new_with_offset(
-            //     file_line_col!(),
-            //     0,
-            //     ErrorKind::Io(e)
-            // ))?);
-            let bytes_read = f
-                .read_to_end(&mut compressed_payload)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+            let compressed_payload = match input {
+                XFileInput::File(mut f) => {
+                    #[cfg(feature = "cache")]
+                    {
+                        self.source = Some(CacheSource::from_file(&f)?);
+                    }
+
+                    f.seek(std::io::SeekFrom::Start(size_of!(XFileHeader) as _))
+                        .map_err(|e| {
+                            Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e))
+                        })?;
+                    // dbg!(f.stream_position().map_err(|e| Error::new_with_offset(
+                    //     file_line_col!(),
+                    //     0,
+                    //     ErrorKind::Io(e)
+                    // ))?);
+                    f.read_to_end(&mut compressed_payload).map_err(|e| {
+                        Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e))
+                    })?;
+                    &compressed_payload[..]
+                }
+                XFileInput::Bytes(b) => b.get(size_of!(XFileHeader)..).unwrap_or_default(),
+                // the header was already read from `r`, so the payload is
+                // whatever's left
+                XFileInput::Reader(mut r) => {
+                    r.read_to_end(&mut compressed_payload).map_err(|e| {
+                        Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e))
+                    })?;
+                    &compressed_payload[..]
+                }
+            };
+            if let Some(protection) = XFileProtection::from_payload(compressed_payload) {
+                if !self.silent {
+                    println!(
+                        "Fastfile payload is {protection}. Strip or decrypt it with a tool for \
+                         its game first."
+                    );
+                }
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    size_of!(XFileHeader) as _,
+                    ErrorKind::ProtectedFile(protection),
+                ));
+            }
+            let bytes_read = compressed_payload.len();
             if !self.silent {
                 println!("Payload read, inflating... (this may take a while)");
             }
-            let decompressed_payload = inflate::inflate_bytes_zlib(&compressed_payload)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Inflate(e)))?;
+            // safe since `from_file` always picks a codec
+            let decompressed_payload = self.codec.take().unwrap().inflate(compressed_payload)?;
             if !self.silent {
                 println!(
                     "Payload inflated, compressed size: {} bytes, decompressed size: {} bytes",
@@ -413,11 +1181,8 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
         self.reader = Some(reader);
 
         let xasset_list = {
-            let mut file = self.reader.as_mut().unwrap();
-            let xfile = self
-                .opts
-                .deserialize_from::<XFile>(&mut file)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+            let file = self.reader.as_mut().unwrap();
+            let xfile = reader::read_from_cursor::<XFile>(file, self.platform.is_le())?;
 
             dbg!(xfile);
             //dbg!(StreamLen::stream_len(&mut file)?);
@@ -428,10 +1193,8 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             //     0,
             //     ErrorKind::Io(e)
             // ))?);
-            let xasset_list = self
-                .opts
-                .deserialize_from::<XAssetListRaw>(&mut file)
-                .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+            let xasset_list =
+                reader::read_from_cursor::<XAssetListRaw>(file, self.platform.is_le())?;
             //dbg!(&xasset_list);
             // dbg!(file.stream_position().map_err(|e| Error::new(
             //     file_line_col!(),
@@ -441,24 +1204,52 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
             xasset_list
         };
 
+        #[cfg(feature = "trace")]
+        if let Some(trace) = self.trace.as_mut() {
+            let xfile_len = size_of!(XFile) as u64;
+            let xasset_list_len = size_of!(XAssetListRaw) as u64;
+            trace.push("XFile", 0, xfile_len, Vec::new());
+            trace.push(
+                "XAssetListRaw",
+                xfile_len,
+                xfile_len + xasset_list_len,
+                Vec::new(),
+            );
+        }
+
         if !self.silent {
-            println!("Fastfile contains {} assets.", xasset_list.assets.size());
+            println!("Fastfile contains {} assets.", xasset_list.assets.len());
         }
 
         let de = T5XFileDeserializer::<T5XFileDeserializerInflated> {
             silent: self.silent,
             xfile: self.xfile,
-            script_strings: Vec::new(),
+            script_strings: self.script_strings,
             file: self.file,
             cache_file: self.cache_file,
             reader: self.reader,
             xasset_list,
-            xassets_raw: VecDeque::new(),
+            xassets_raw: self.xassets_raw,
             deserialized_assets: self.deserialized_assets,
             non_null_assets: self.non_null_assets,
-            opts: self.opts,
             platform: self.platform,
             d3d9_state: self.d3d9_state,
+            codec: None,
+            #[cfg(feature = "cache")]
+            source: self.source,
+            index_loaded: self.index_loaded,
+            allocation_limit: self.allocation_limit,
+            string_encoding: self.string_encoding,
+            allocated: self.allocated,
+            max_local_clients: self.max_local_clients,
+            ptr_depth: self.ptr_depth,
+            blocks: self.blocks,
+            image_packs: self.image_packs,
+            sound_banks: self.sound_banks,
+            #[cfg(feature = "trace")]
+            trace: self.trace,
+            #[cfg(feature = "arc")]
+            interned: self.interned,
             _p: PhantomData,
         };
 
@@ -467,50 +1258,82 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeflated> {
 }
 
 impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
+    /// Writes the inflated payload, along with the script strings and asset
+    /// list, to a cache file at `path` (see [`crate::cache`]).
+    #[cfg(feature = "cache")]
     pub fn cache(
-        mut self,
+        self,
         path: impl AsRef<Path>,
     ) -> Result<(
         T5XFileDeserializer<'a, T5XFileDeserializerDeserialize>,
         CacheSuccess,
     )> {
-        if !self.silent {
-            println!("Caching decompressed payload to disk...");
-        }
+        self.write_cache(path, false)
+    }
 
-        let cache_exists = path.as_ref().exists();
+    /// Like [`Self::cache`], but writes an archive, whose payload is
+    /// compressed much harder so it can be kept instead of the Fastfile (see
+    /// [`crate::cache`]). Archives are loaded with
+    /// [`T5XFileDeserializerBuilder::from_cache_file`].
+    #[cfg(feature = "cache")]
+    pub fn archive(
+        self,
+        path: impl AsRef<Path>,
+    ) -> Result<(
+        T5XFileDeserializer<'a, T5XFileDeserializerDeserialize>,
+        CacheSuccess,
+    )> {
+        self.write_cache(path, true)
+    }
 
-        let mut f = std::fs::File::create(path)
-            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
-        let pos = self.reader.as_ref().unwrap().position();
-        let v = self.reader.take().unwrap().into_inner();
-        f.write_all(&v)
-            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
-        self.reader = Some(Cursor::new(v));
-        self.reader.as_mut().unwrap().set_position(pos);
+    #[cfg(feature = "cache")]
+    fn write_cache(
+        self,
+        path: impl AsRef<Path>,
+        archive: bool,
+    ) -> Result<(
+        T5XFileDeserializer<'a, T5XFileDeserializerDeserialize>,
+        CacheSuccess,
+    )> {
+        let de = self.no_cache()?;
 
-        if !self.silent {
-            println!("Decompressed payload cached.");
+        if !de.silent {
+            if archive {
+                println!("Archiving decompressed payload to disk...");
+            } else {
+                println!("Caching decompressed payload to disk...");
+            }
         }
 
-        let mut de = T5XFileDeserializer::<'a, T5XFileDeserializerDeserialize> {
-            silent: self.silent,
-            xfile: self.xfile,
-            script_strings: Vec::new(),
-            file: self.file,
-            cache_file: self.cache_file,
-            reader: self.reader,
-            xasset_list: self.xasset_list,
-            xassets_raw: self.xassets_raw,
-            deserialized_assets: self.deserialized_assets,
-            non_null_assets: self.non_null_assets,
-            opts: self.opts,
-            platform: self.platform,
-            d3d9_state: self.d3d9_state,
-            _p: PhantomData,
+        let cache_exists = path.as_ref().exists();
+
+        let reader = de.reader.as_ref().unwrap();
+        let header = CacheHeader::new(
+            de.platform,
+            de.source.unwrap_or_default(),
+            de.xfile,
+            de.script_strings.clone(),
+            de.xassets_raw.iter().copied().collect(),
+            reader.position(),
+            reader.get_ref().len() as _,
+        );
+        let header = if archive {
+            header.into_archive()
+        } else {
+            header
         };
 
-        de.get_script_strings_and_assets()?;
+        let f = std::fs::File::create(path)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        header.write(reader.get_ref(), std::io::BufWriter::new(f))?;
+
+        if !de.silent {
+            if archive {
+                println!("Decompressed payload archived.");
+            } else {
+                println!("Decompressed payload cached.");
+            }
+        }
 
         if cache_exists {
             Ok((de, CacheSuccess::CacheOverwritten))
@@ -519,11 +1342,42 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
         }
     }
 
+    /// Returns the inflated payload, so it can be handed to a
+    /// [`T5XFileBorrowedDeserializer`](crate::T5XFileBorrowedDeserializer).
+    pub fn into_payload(self) -> Vec<u8> {
+        self.reader.unwrap().into_inner()
+    }
+
+    /// Returns a [`T5XFileBorrowedDeserializer`](crate::T5XFileBorrowedDeserializer)
+    /// over the inflated payload, with the same settings as `self`.
+    pub fn borrowed(&self) -> Result<crate::T5XFileBorrowedDeserializer<'_>> {
+        let reader = self.reader.as_ref().unwrap();
+        let de = crate::T5XFileBorrowedDeserializer::with_payload(
+            reader.get_ref(),
+            self.platform,
+            self.silent,
+        )
+        .with_max_local_clients(self.max_local_clients)
+        .with_max_ptr_depth(self.ptr_depth.max())
+        .with_string_encoding(self.string_encoding);
+
+        if self.index_loaded {
+            Ok(de.with_assets(
+                self.xfile,
+                reader.position(),
+                self.script_strings.clone(),
+                &self.xassets_raw,
+            ))
+        } else {
+            de.read_asset_list()
+        }
+    }
+
     pub fn no_cache(self) -> Result<T5XFileDeserializer<'a, T5XFileDeserializerDeserialize>> {
         let mut de = T5XFileDeserializer::<'a, T5XFileDeserializerDeserialize> {
             silent: self.silent,
             xfile: self.xfile,
-            script_strings: Vec::new(),
+            script_strings: self.script_strings,
             file: self.file,
             cache_file: self.cache_file,
             reader: self.reader,
@@ -531,9 +1385,24 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerInflated> {
             xassets_raw: self.xassets_raw,
             deserialized_assets: self.deserialized_assets,
             non_null_assets: self.non_null_assets,
-            opts: self.opts,
             platform: self.platform,
             d3d9_state: self.d3d9_state,
+            codec: None,
+            #[cfg(feature = "cache")]
+            source: self.source,
+            index_loaded: self.index_loaded,
+            allocation_limit: self.allocation_limit,
+            string_encoding: self.string_encoding,
+            allocated: self.allocated,
+            max_local_clients: self.max_local_clients,
+            ptr_depth: self.ptr_depth,
+            blocks: self.blocks,
+            image_packs: self.image_packs,
+            sound_banks: self.sound_banks,
+            #[cfg(feature = "trace")]
+            trace: self.trace,
+            #[cfg(feature = "arc")]
+            interned: self.interned,
             _p: PhantomData,
         };
 
@@ -554,8 +1423,42 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
             return Ok(None);
         };
 
-        let asset = XAsset::try_get(self, asset, self.platform);
+        self.allocated = 0;
+        self.ptr_depth.reset();
+        self.blocks.begin_asset(asset.asset_data.marker());
+
+        #[cfg(feature = "trace")]
+        let offset = self.stream_pos()?;
+        #[cfg(feature = "trace")]
+        if let Some(trace) = self.trace.as_mut() {
+            trace.begin_asset();
+        }
+
+        let mut asset = XAsset::try_get_with_max_local_clients(self, asset, self.max_local_clients);
         //dbg!(&asset);
+
+        if let Ok(ref mut a) = asset
+            && let Some(image_packs) = self.image_packs.as_mut()
+            && let Some(image) = a.image_mut()
+        {
+            image_packs.fuse(image)?;
+        }
+
+        if let Ok(ref mut a) = asset
+            && let Some(sound_banks) = self.sound_banks.as_mut()
+            && let Some(bank) = a.snd_bank_mut()
+        {
+            sound_banks.fuse(bank)?;
+        }
+
+        #[cfg(feature = "trace")]
+        if let Ok(ref a) = asset {
+            let end = self.stream_pos()?;
+            if let Some(trace) = self.trace.as_mut() {
+                trace.end_asset(a.asset_type(), a.name().map(ToOwned::to_owned), offset, end);
+            }
+        }
+
         if let Ok(ref a) = asset {
             self.deserialized_assets += 1;
             if a.is_some() {
@@ -574,11 +1477,163 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
                     self.non_null_assets,
                 );
             }
+
+            if a.ends_zone() {
+                self.end_zone_at(a.asset_type());
+            }
         }
 
         asset.map(Some)
     }
 
+    /// Drops the remaining assets after one from a family that isn't
+    /// enabled, since it took up the rest of the zone (see
+    /// [`XAsset::ends_zone`]).
+    fn end_zone_at(&mut self, asset_type: XAssetType) {
+        if !self.silent && !self.xassets_raw.is_empty() {
+            println!(
+                "Stopping at {asset_type:?}, since the `{}` feature isn't enabled \
+                 ({} assets left unread).",
+                asset_type.family().map_or("", XAssetFamily::feature),
+                self.xassets_raw.len(),
+            );
+        }
+
+        self.xassets_raw.clear();
+    }
+
+    /// Returns everything read from the payload so far, if tracing was
+    /// enabled with [`T5XFileDeserializerBuilder::with_trace`].
+    #[cfg(feature = "trace")]
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Same as [`Self::trace`], but leaves tracing disabled from here on.
+    #[cfg(feature = "trace")]
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    /// Takes a snapshot of how far deserialization has gotten, to be
+    /// resumed later with [`Self::resume`] (see [`crate::snapshot`]).
+    ///
+    /// Should only be taken between assets. If the last asset failed to
+    /// deserialize, resuming starts with the asset after it.
+    pub fn snapshot(&self) -> DeserializerSnapshot {
+        let reader = self.reader.as_ref().unwrap();
+        DeserializerSnapshot {
+            platform: self.platform as u8,
+            payload_len: reader.get_ref().len() as _,
+            script_strings: self.script_strings.clone(),
+            remaining_assets: self.xassets_raw.len(),
+            deserialized_assets: self.deserialized_assets,
+            non_null_assets: self.non_null_assets,
+            pos: reader.position(),
+            blocks: self.blocks.clone(),
+        }
+    }
+
+    /// Picks up where `snapshot` was taken, skipping every asset before it
+    /// without reading them.
+    ///
+    /// Returns [`ErrorKind::BadSnapshot`] if `snapshot` wasn't taken from
+    /// the same Fastfile, or if it was taken with fewer assets deserialized
+    /// than this deserializer already has.
+    pub fn resume(&mut self, snapshot: &DeserializerSnapshot) -> Result<()> {
+        let bad_snapshot =
+            |reason: String| Error::new(file_line_col!(), ErrorKind::BadSnapshot(reason));
+
+        let payload_len = self.reader.as_ref().unwrap().get_ref().len() as u64;
+        if snapshot.platform != self.platform as u8 {
+            return Err(bad_snapshot(format!(
+                "snapshot wasn't taken for {}",
+                self.platform
+            )));
+        }
+        if snapshot.payload_len != payload_len
+            || snapshot.script_strings != self.script_strings
+            || snapshot.pos > payload_len
+        {
+            return Err(bad_snapshot(
+                "snapshot was taken from a different Fastfile".to_string(),
+            ));
+        }
+        let Some(skipped) = self
+            .xassets_raw
+            .len()
+            .checked_sub(snapshot.remaining_assets)
+        else {
+            return Err(bad_snapshot(format!(
+                "snapshot has {} assets left, but only {} are",
+                snapshot.remaining_assets,
+                self.xassets_raw.len()
+            )));
+        };
+
+        self.xassets_raw.drain(..skipped);
+        self.deserialized_assets = snapshot.deserialized_assets;
+        self.non_null_assets = snapshot.non_null_assets;
+        self.blocks = snapshot.blocks.clone();
+        self.reader.as_mut().unwrap().set_position(snapshot.pos);
+        Ok(())
+    }
+
+    /// Every real offset encountered so far, and where in the payload the
+    /// object it points to was loaded from, if it could be resolved (see
+    /// [`t5_xfile_defs::blocks`]).
+    pub fn real_ptrs(&self) -> &[RealPtr] {
+        self.blocks.real_ptrs()
+    }
+
+    /// Every inline pointer encountered so far, along with the marker it was
+    /// serialized with. Empty unless enabled with
+    /// [`T5XFileDeserializerBuilder::with_record_ptr_markers`].
+    pub fn inline_ptrs(&self) -> &[InlinePtr] {
+        self.blocks.inline_ptrs()
+    }
+
+    /// The markers of the inline pointers in each asset deserialized so
+    /// far, in the order they appear in the payload, starting with the one
+    /// the asset was listed with (see [`XFileBlocks::asset_ptr_markers`]).
+    /// Empty unless enabled with
+    /// [`T5XFileDeserializerBuilder::with_record_ptr_markers`].
+    ///
+    /// Passing these to `T5XFileSerializerBuilder::with_ptr_markers` (with
+    /// the `serializer` feature) writes the assets with the same markers.
+    pub fn ptr_markers(&self) -> &[Vec<PtrMarker>] {
+        self.blocks.asset_ptr_markers()
+    }
+
+    /// How many inline pointers were serialized with `marker` so far.
+    pub fn ptr_marker_count(&self, marker: PtrMarker) -> u64 {
+        self.blocks.marker_count(marker)
+    }
+
+    #[cfg(feature = "trace")]
+    fn load_traced<T: XFileRead>(&mut self) -> Result<T> {
+        let reader = self.reader.as_mut().unwrap();
+        let offset = reader.position();
+
+        let mut de = FixedLayoutReader::new(reader.get_ref(), offset as _, self.platform.is_le())
+            .with_trace();
+        let t = T::xfile_read(&mut de)?;
+        let end = de.position() as u64;
+        let fields = de.take_trace();
+
+        reader.set_position(end);
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(&short_type_name::<T>(), offset, end, fields);
+        }
+
+        Ok(t)
+    }
+
+    fn load_untraced<T: XFileRead>(&mut self) -> Result<T> {
+        // FIXME: unwrap
+        reader::read_from_cursor(self.reader.as_mut().unwrap(), self.platform.is_le())
+    }
+
     /// Deserializes the remaining [`XAsset`]s.
     ///
     /// Returns [`Ok`] if all remaining assets are deserialized successfully,
@@ -593,19 +1648,71 @@ impl<'a> T5XFileDeserializer<'a, T5XFileDeserializerDeserialize> {
         Ok(deserialized_assets)
     }
 
-    fn get_script_strings_and_assets(&mut self) -> Result<()> {
-        let xasset_list = self.xasset_list;
+    /// Deserializes the remaining [`XAsset`]s, and has `visitor` visit each
+    /// one (see [`XAsset::accept`]) as soon as it's deserialized, instead of
+    /// keeping them all around. Returns how many assets were visited.
+    ///
+    /// Returns [`Err`] as soon as an error occurs during deserialization.
+    pub fn accept_remaining(mut self, visitor: &mut impl XAssetVisitor) -> Result<usize> {
+        let mut visited = 0;
+
+        while let Some(asset) = self.deserialize_next()? {
+            asset.accept(visitor);
+            visited += 1;
+        }
+
+        Ok(visited)
+    }
+
+    /// Skips over the next asset without keeping it, and returns where it
+    /// was in the payload along with its type and name (see
+    /// [`XAsset::skip`]).
+    ///
+    /// Returns [`Ok(None)`] if zero assets remain.
+    pub fn skip_next(&mut self) -> Result<Option<AssetExtent>> {
+        let Some(asset) = self.xassets_raw.pop_front() else {
+            return Ok(None);
+        };
+
+        self.allocated = 0;
+        self.ptr_depth.reset();
+
+        let max_local_clients = self.max_local_clients;
+        let extent = XAsset::skip(self, asset, max_local_clients)?;
+        if extent.ends_zone() {
+            self.end_zone_at(extent.asset_type);
+        }
 
-        self.script_strings = xasset_list
-            .strings
-            .to_vec(self)?
-            .into_iter()
-            .map(|s| s.xfile_deserialize_into(self, ()).map(|s| s.0))
-            .collect::<Result<Vec<_>>>()?;
-        //dbg!(&strings);
+        Ok(Some(extent))
+    }
+
+    /// Gets the type and name of every remaining asset, without
+    /// deserializing any more of each one than necessary (see
+    /// [`Self::skip_next`]). Null assets have no name.
+    ///
+    /// Raw files, images, sound banks, models, world geometry, etc. are
+    /// skipped over by their computed sizes instead of being read (see
+    /// [`XAsset::skip`]), so this is much faster than
+    /// [`Self::deserialize_remaining`] on large zones. Nothing is fused from
+    /// image packs or sound banks.
+    pub fn scan_names(mut self) -> Result<Vec<(XAssetType, Option<String>)>> {
+        let mut names = Vec::with_capacity(self.xassets_raw.len());
+
+        while let Some(extent) = self.skip_next()? {
+            names.push((extent.asset_type, extent.name));
+        }
+
+        Ok(names)
+    }
+
+    fn get_script_strings_and_assets(&mut self) -> Result<()> {
+        if self.index_loaded {
+            return Ok(());
+        }
 
-        let assets = xasset_list.assets.to_vec(self)?;
-        self.xassets_raw = VecDeque::from_iter(assets);
+        let (script_strings, xassets_raw) = read_asset_list(self, self.xasset_list)?;
+        self.script_strings = script_strings;
+        self.xassets_raw = xassets_raw;
 
         Ok(())
     }
@@ -638,17 +1745,161 @@ impl<'a> T5XFileDeserialize for T5XFileDeserializer<'a> {
         self.silent
     }
 
-    fn load_from_xfile<T: DeserializeOwned>(&mut self) -> Result<T> {
-        // FIXME: unwrap
-        self.opts
-            .deserialize_from(self.reader.as_mut().unwrap())
+    fn is_little_endian(&self) -> bool {
+        self.platform.is_le()
+    }
+
+    fn string_encoding(&self) -> StringEncoding {
+        self.string_encoding
+    }
+
+    fn load_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        #[cfg(feature = "trace")]
+        let offset = self.stream_pos()?;
+
+        self.check_remaining(len, 1)?;
+        let mut bytes = vec![0; len];
+        self.reader
+            .as_mut()
+            .unwrap()
+            .read_exact(&mut bytes)
             .map_err(|e| {
                 Error::new_with_offset(
                     file_line_col!(),
                     self.stream_pos().unwrap() as _,
-                    ErrorKind::Bincode(e),
+                    ErrorKind::Io(e),
                 )
-            })
+            })?;
+
+        #[cfg(feature = "trace")]
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(
+                &format!("[u8; {len}]"),
+                offset,
+                offset + len as u64,
+                Vec::new(),
+            );
+        }
+
+        self.blocks.alloc(1, len);
+        Ok(bytes)
+    }
+
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        let reader = self.reader.as_mut().unwrap();
+        let pos = reader.position();
+        let end = pos + len as u64;
+        if end > StreamLen::stream_len(reader)? {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::Io(std::io::ErrorKind::UnexpectedEof.into()),
+            ));
+        }
+
+        reader.set_position(end);
+
+        #[cfg(feature = "trace")]
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(&format!("[u8; {len}]"), pos, end, Vec::new());
+        }
+
+        self.blocks.alloc(1, len);
+        Ok(())
+    }
+
+    fn reserve_allocation(&mut self, bytes: usize) -> Result<()> {
+        self.allocated = self.allocated.saturating_add(bytes);
+
+        match self.allocation_limit {
+            Some(limit) if self.allocated > limit => Err(Error::new_with_offset(
+                file_line_col!(),
+                self.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(format!(
+                    "asset needs more than the allocation limit of {limit} bytes"
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn enter_ptr(&mut self) -> Result<()> {
+        let pos = self.stream_pos()?;
+        self.ptr_depth.enter(pos)
+    }
+
+    fn leave_ptr(&mut self) {
+        self.ptr_depth.leave();
+    }
+
+    fn mark_ptr_target(
+        &mut self,
+        marker: Option<PtrMarker>,
+        align: usize,
+        len: usize,
+    ) -> Result<()> {
+        let pos = self.stream_pos()?;
+        self.blocks.mark(marker, align, len, pos);
+        Ok(())
+    }
+
+    fn resolve_real_ptr(&mut self, offset: XFileOffset) -> Result<Option<u64>> {
+        let pos = self.stream_pos()?;
+        Ok(self.blocks.resolve(offset, pos))
+    }
+
+    fn enter_real_ptr(&mut self, target: u64) -> Result<Option<u64>> {
+        if !self.blocks.enter(target) {
+            return Ok(None);
+        }
+
+        let reader = self.reader.as_mut().unwrap();
+        let pos = reader.position();
+        reader.set_position(target);
+        Ok(Some(pos))
+    }
+
+    fn leave_real_ptr(&mut self, pos: u64) -> Result<()> {
+        self.blocks.leave();
+        self.reader.as_mut().unwrap().set_position(pos);
+        Ok(())
+    }
+
+    #[cfg(feature = "arc")]
+    fn interned<U: Any + Send + Sync>(&self, offset: u64) -> Option<Arc<U>> {
+        self.interned
+            .get(&(offset, TypeId::of::<U>()))
+            .and_then(|u| Arc::clone(u).downcast().ok())
+    }
+
+    #[cfg(feature = "arc")]
+    fn intern<U: Any + Send + Sync>(&mut self, offset: u64, object: Arc<U>) {
+        self.interned.insert((offset, TypeId::of::<U>()), object);
+    }
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T> {
+        let start = self.reader.as_ref().unwrap().position();
+
+        #[cfg(feature = "trace")]
+        let t = if self.trace.is_some() {
+            self.load_traced()
+        } else {
+            self.load_untraced()
+        };
+        #[cfg(not(feature = "trace"))]
+        let t = self.load_untraced();
+
+        let len = self.reader.as_ref().unwrap().position() - start;
+        self.blocks.alloc(core::mem::align_of::<T>(), len as _);
+
+        if t.is_ok() && self.blocks.record_markers() {
+            let bytes = &self.reader.as_ref().unwrap().get_ref()[start as usize..];
+            let markers = read::ptr_markers::<T>(bytes, self.platform.is_le())?;
+            self.blocks
+                .record_asset_ptr_markers(markers.into_iter().map(|(_, marker)| marker));
+        }
+
+        t
     }
 
     fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>> {
@@ -658,3 +1909,22 @@ impl<'a> T5XFileDeserialize for T5XFileDeserializer<'a> {
             .map(|s| &**s))
     }
 }
+
+/// Reads the script strings and assets `xasset_list` points to, which `de`
+/// has to be positioned at.
+pub(crate) fn read_asset_list<'a>(
+    de: &mut impl T5XFileDeserialize,
+    xasset_list: XAssetListRaw<'a>,
+) -> Result<(Vec<String>, VecDeque<XAssetRaw<'a>>)> {
+    let script_strings = xasset_list
+        .strings
+        .to_vec(de)?
+        .into_iter()
+        .map(|s| s.xfile_deserialize_into(de, ()).map(|s| s.0))
+        .collect::<Result<Vec<_>>>()?;
+    //dbg!(&strings);
+
+    let assets = xasset_list.assets.to_vec(de)?;
+
+    Ok((script_strings, VecDeque::from_iter(assets)))
+}
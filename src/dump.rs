@@ -0,0 +1,327 @@
+//! Dumps of deserialized assets, which can be loaded again without the
+//! Fastfile they came from.
+//!
+//! Deserializing a large Fastfile is slow, so a pipeline can do it once,
+//! [`write_dump`] the resulting [`XAssetBag`], and have every later step
+//! load the dump instead ([`read_dump`], or [`DumpReader`] for just the
+//! assets a step needs).
+//!
+//! A dump consists of
+//!
+//! * [`DUMP_MAGIC`],
+//! * the [`DumpVersion`] it was written with, as two `u16`s,
+//! * the length of the [`DumpManifest`] as a `u64`, then the manifest,
+//! * every asset, one after the other, at the offsets listed in the
+//!   manifest (relative to the end of the manifest).
+//!
+//! Everything is encoded the same way a little-endian Fastfile is.
+//!
+//! # Versioning
+//!
+//! Dumps are versioned separately from the crate, with a major and a minor
+//! version ([`DUMP_VERSION`]):
+//!
+//! * The major version is bumped whenever a dump written by an older version
+//!   can't be read as-is anymore, e.g. because a field was added to an asset.
+//!   Dumps with an older major version are upgraded as they're read if there
+//!   is a [`DumpMigration`] for them (see [`DumpVersion::migration`]), and
+//!   rejected with [`ErrorKind::BadDump`] otherwise, in which case the
+//!   Fastfile has to be dumped again.
+//! * The minor version is bumped when fields are appended to the
+//!   [`DumpManifest`], which older readers skip. Dumps with the same major
+//!   version can be read regardless of their minor version.
+//!
+//! Dumps with a newer major version than [`DUMP_VERSION`] are always
+//! rejected.
+
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BincodeOptions, Error, ErrorKind, Result, file_line_col};
+
+use t5_xfile_defs::xasset::{XAsset, XAssetBag, XAssetType};
+
+pub const DUMP_MAGIC: [u8; 8] = *b"T5XFDUMP";
+
+/// The version dumps are written with. See the
+/// [module docs](self#versioning).
+pub const DUMP_VERSION: DumpVersion = DumpVersion { major: 1, minor: 0 };
+
+/// The size of everything before the manifest.
+const HEADER_LEN: u64 = 8 + 2 + 2 + 8;
+
+/// A dump's format version. See the [module docs](self#versioning).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DumpVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl DumpVersion {
+    /// Whether a dump with this version can be read as-is.
+    pub fn is_compatible(&self) -> bool {
+        self.major == DUMP_VERSION.major
+    }
+
+    /// Whether a dump with this version can be upgraded to
+    /// [`DUMP_VERSION`] as it's read, i.e. whether there's a
+    /// [`DumpMigration`] for its major version.
+    pub fn can_migrate(&self) -> bool {
+        self.migration().is_some()
+    }
+
+    /// The migration that upgrades dumps with this version to
+    /// [`DUMP_VERSION`], if there is one.
+    pub fn migration(&self) -> Option<&'static DumpMigration> {
+        MIGRATIONS.iter().find(|m| m.from_major == self.major)
+    }
+}
+
+/// Upgrades the assets of dumps with an older major version as they're read.
+pub struct DumpMigration {
+    /// The major version this migrates from. Each major version has at most
+    /// one migration, straight to [`DUMP_VERSION`].
+    pub from_major: u16,
+    /// Decodes an asset in the old major version's layout and converts it
+    /// to an [`XAsset`].
+    pub decode: fn(&[u8]) -> bincode::Result<XAsset>,
+}
+
+/// Every migration, keyed on [`DumpMigration::from_major`]. When the major
+/// version is bumped, add one here for the previous major version if its
+/// dumps can be converted.
+///
+/// No major version but the current one has been released yet, so there's
+/// nothing to migrate from.
+const MIGRATIONS: &[DumpMigration] = &[];
+
+impl core::fmt::Display for DumpVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Describes the contents of a dump.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpManifest {
+    /// The version of this crate the dump was written by (not to be
+    /// confused with the dump's [`DumpVersion`]), for diagnostics.
+    pub crate_version: String,
+    /// In the order the assets were in the [`XAssetBag`].
+    pub entries: Vec<DumpEntry>,
+}
+
+/// Where one asset is in a dump.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DumpEntry {
+    pub asset_type: XAssetType,
+    pub name: String,
+    /// Relative to the end of the manifest.
+    pub offset: u64,
+    pub len: u64,
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::new(file_line_col!(), ErrorKind::Io(e))
+}
+
+fn bincode_error(e: bincode::Error) -> Error {
+    Error::new(file_line_col!(), ErrorKind::Bincode(e))
+}
+
+fn bad_dump(reason: String) -> Error {
+    Error::new(file_line_col!(), ErrorKind::BadDump(reason))
+}
+
+/// Writes every asset in `bag` to `writer` as a dump.
+pub fn write_dump(bag: &XAssetBag, mut writer: impl Write) -> Result<()> {
+    let opts = BincodeOptions::new(true);
+
+    let mut assets = Vec::new();
+    let mut entries = Vec::new();
+    for asset in bag.iter() {
+        // bags never hold null assets
+        let Some(name) = asset.name() else {
+            continue;
+        };
+
+        let offset = assets.len() as u64;
+        opts.serialize_into(&mut assets, asset)
+            .map_err(bincode_error)?;
+        entries.push(DumpEntry {
+            asset_type: asset.asset_type(),
+            name: name.to_string(),
+            offset,
+            len: assets.len() as u64 - offset,
+        });
+    }
+
+    let manifest = DumpManifest {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        entries,
+    };
+    let mut manifest_bytes = Vec::new();
+    opts.serialize_into(&mut manifest_bytes, &manifest)
+        .map_err(bincode_error)?;
+
+    opts.serialize_into(
+        &mut writer,
+        (
+            DUMP_MAGIC,
+            DUMP_VERSION.major,
+            DUMP_VERSION.minor,
+            manifest_bytes.len() as u64,
+        ),
+    )
+    .map_err(bincode_error)?;
+    writer.write_all(&manifest_bytes).map_err(io_error)?;
+    writer.write_all(&assets).map_err(io_error)
+}
+
+/// Reads every asset from a dump written by [`write_dump`].
+pub fn read_dump(reader: impl Read) -> Result<XAssetBag> {
+    // DumpReader needs to seek, but reads everything in order here anyway
+    let mut bytes = Vec::new();
+    let mut reader = reader;
+    reader.read_to_end(&mut bytes).map_err(io_error)?;
+
+    let mut dump = DumpReader::new(Cursor::new(bytes))?;
+    let mut bag = XAssetBag::new();
+    for i in 0..dump.manifest().entries.len() {
+        bag.insert(dump.load_entry(i)?);
+    }
+    Ok(bag)
+}
+
+/// Reads assets from a dump one at a time, so only the ones that are needed
+/// have to be decoded.
+pub struct DumpReader<R: Read + Seek> {
+    reader: R,
+    version: DumpVersion,
+    /// Decodes assets in the dump's version, see [`DumpMigration::decode`].
+    decode: fn(&[u8]) -> bincode::Result<XAsset>,
+    manifest: DumpManifest,
+    /// Where the first asset starts.
+    assets_start: u64,
+}
+
+impl<R: Read + Seek> DumpReader<R> {
+    /// Reads the header and manifest of the dump in `reader`, which starts
+    /// at its current position.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let start = reader.stream_position().map_err(io_error)?;
+        let opts = BincodeOptions::new(true);
+
+        let (magic, major, minor, manifest_len) = opts
+            .deserialize_from::<([u8; 8], u16, u16, u64)>(&mut reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        if magic != DUMP_MAGIC {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::BadDump(format!("bad magic {magic:02X?}")),
+            ));
+        }
+
+        let version = DumpVersion { major, minor };
+        let decode = if version.is_compatible() {
+            decode_current
+        } else if let Some(migration) = version.migration() {
+            migration.decode
+        } else {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                8,
+                ErrorKind::BadDump(format!(
+                    "version is {version}, expected {}.x",
+                    DUMP_VERSION.major
+                )),
+            ));
+        };
+
+        // Minor versions may append fields to the manifest, so it's read
+        // from exactly its own bytes and whatever's left over is skipped
+        let manifest_bytes = read_exact(&mut reader, manifest_len)?;
+        let manifest = opts
+            .deserialize_from::<DumpManifest>(&manifest_bytes[..])
+            .map_err(|e| {
+                Error::new_with_offset(file_line_col!(), HEADER_LEN as _, ErrorKind::Bincode(e))
+            })?;
+
+        Ok(Self {
+            reader,
+            version,
+            decode,
+            manifest,
+            assets_start: start + HEADER_LEN + manifest_len,
+        })
+    }
+
+    /// The version the dump was written with.
+    pub fn version(&self) -> DumpVersion {
+        self.version
+    }
+
+    pub fn manifest(&self) -> &DumpManifest {
+        &self.manifest
+    }
+
+    /// Decodes the asset described by the `i`th entry of the
+    /// [`manifest`](Self::manifest).
+    pub fn load_entry(&mut self, i: usize) -> Result<XAsset> {
+        let Some(entry) = self.manifest.entries.get(i) else {
+            return Err(bad_dump(format!(
+                "entry {i} is out of range (there are {})",
+                self.manifest.entries.len()
+            )));
+        };
+
+        let pos = self.assets_start + entry.offset;
+        self.reader.seek(SeekFrom::Start(pos)).map_err(io_error)?;
+        let bytes = read_exact(&mut self.reader, entry.len)?;
+
+        let asset = (self.decode)(&bytes).map_err(|e| {
+            Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Bincode(e))
+        })?;
+        if asset.asset_type() != entry.asset_type {
+            return Err(bad_dump(format!(
+                "entry {i} should be a {:?}, but is a {:?}",
+                entry.asset_type,
+                asset.asset_type()
+            )));
+        }
+
+        Ok(asset)
+    }
+
+    /// Decodes the asset of type `asset_type` named `name`, if the dump has
+    /// one.
+    pub fn load(&mut self, asset_type: XAssetType, name: &str) -> Result<Option<XAsset>> {
+        let i = self
+            .manifest
+            .entries
+            .iter()
+            .position(|e| e.asset_type == asset_type && e.name == name);
+        i.map(|i| self.load_entry(i)).transpose()
+    }
+}
+
+/// Decodes an asset in [`DUMP_VERSION`]'s layout.
+fn decode_current(bytes: &[u8]) -> bincode::Result<XAsset> {
+    BincodeOptions::new(true).deserialize_from(bytes)
+}
+
+/// Reads exactly `len` bytes, without allocating all of them up front in
+/// case `len` is corrupt.
+fn read_exact(reader: impl Read, len: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes).map_err(io_error)?;
+    if (bytes.len() as u64) < len {
+        return Err(bad_dump(format!(
+            "expected {len} bytes, but only {} are left",
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
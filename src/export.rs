@@ -0,0 +1,779 @@
+//! Exporting models to formats 3D tools can open.
+//!
+//! Assets are first converted to a [`Scene`], a plain list of triangle
+//! meshes, the materials they use, and the nodes that place them, which can
+//! then be written as glTF ([`Scene::to_gltf`]) or Wavefront OBJ
+//! ([`Scene::to_obj`]). Textures aren't embedded: materials only refer to
+//! their color map by image name, and it's up to the caller to write the
+//! images out (e.g., with [`png::from_image`](crate::png::from_image)) where
+//! [`Scene::to_gltf`] or [`Scene::to_obj`] are told they'll be.
+//!
+//! Models are exported in their bind pose, without their skeleton. Maps
+//! ([`Scene::add_gfx_world`]) are exported as their render geometry with
+//! every static model placed in it, optionally along with their collision
+//! ([`Scene::add_clip_map`]).
+//!
+//! Scenes are built in the game's coordinates, and converted to
+//! [`Scene::convention`] as they're written.
+//!
+//! A map's AI navigation data can also be exported, as a graph of its path
+//! nodes (see
+//! [`PathData::graph`](t5_xfile_defs::gameworld::PathData::graph)) written
+//! as GraphViz DOT ([`path_graph_to_dot`]) or JSON
+//! ([`path_graph_to_json`]).
+
+use std::{borrow::Cow, collections::HashMap, fmt::Write};
+
+use t5_xfile_defs::{
+    clipmap::ClipMap,
+    common::CoordinateConvention,
+    gameworld::PathGraph,
+    gfx_world::{GfxPackedPlacement, GfxWorld},
+    techset::{Material, MaterialTextureDefInfo, Semantic},
+    xmodel::XModel,
+};
+
+/// A column-major 4x4 identity matrix.
+pub const IDENTITY: [f32; 16] = [
+    1.0, 0.0, 0.0, 0.0, //
+    0.0, 1.0, 0.0, 0.0, //
+    0.0, 0.0, 1.0, 0.0, //
+    0.0, 0.0, 0.0, 1.0, //
+];
+
+/// A triangle mesh with one material.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// With (0, 0) at the top left, as in Direct3D and glTF.
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+    /// An index into [`Scene::materials`].
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportMaterial {
+    pub name: String,
+    /// The name of the material's color map, if it has one.
+    pub color_map: Option<String>,
+}
+
+/// Places [`Scene::meshes`] in the scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub name: String,
+    pub meshes: Vec<usize>,
+    /// Column-major.
+    pub matrix: [f32; 16],
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    /// In the game's coordinates.
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<ExportMaterial>,
+    /// In the game's coordinates.
+    pub nodes: Vec<Node>,
+    /// What [`Self::to_gltf`] and [`Self::to_obj`] convert the scene to.
+    /// Defaults to the game's own coordinates.
+    pub convention: CoordinateConvention,
+}
+
+/// The column-major matrix for a static model's placement: scaled by
+/// [`GfxPackedPlacement::scale`], rotated into
+/// [`GfxPackedPlacement::axis`], and moved to
+/// [`GfxPackedPlacement::origin`].
+pub fn placement_matrix(placement: &GfxPackedPlacement) -> [f32; 16] {
+    let [x, y, z] = placement
+        .axis
+        .get()
+        .map(|axis| axis.map(|c| c * placement.scale));
+    let [ox, oy, oz] = placement.origin.get();
+    [
+        x[0], x[1], x[2], 0.0, //
+        y[0], y[1], y[2], 0.0, //
+        z[0], z[1], z[2], 0.0, //
+        ox, oy, oz, 1.0, //
+    ]
+}
+
+/// Replaces the characters that can't appear in file names on some
+/// platforms, so that asset names can be used as file names.
+pub fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_floats(floats: &[f32]) -> String {
+    let floats = floats.iter().map(f32::to_string).collect::<Vec<_>>();
+    format!("[{}]", floats.join(","))
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Writes a path-node graph as a GraphViz digraph.
+///
+/// Each node is labeled with its index and type (and its targetname, if it
+/// has one), and pinned to its origin as seen from above, so that
+/// `neato -n` draws the graph to the map's scale. Disabled nodes and
+/// negotiation links are dashed, and disconnected links are red.
+pub fn path_graph_to_dot(graph: &PathGraph) -> String {
+    let mut dot = String::from("digraph paths {\n    node [shape=box];\n");
+    for node in &graph.nodes {
+        let mut label = format!("{}: {:?}", node.index, node.type_);
+        if !node.targetname.is_empty() {
+            write!(label, "\\n{}", dot_escape(node.targetname)).unwrap();
+        }
+        let [x, y, _] = node.origin;
+        write!(
+            dot,
+            "    {} [label=\"{label}\", pos=\"{x},{y}!\"",
+            node.index
+        )
+        .unwrap();
+        if node.disabled {
+            dot.push_str(", style=dashed");
+        }
+        dot.push_str("];\n");
+    }
+    for link in &graph.links {
+        write!(
+            dot,
+            "    {} -> {} [label=\"{}\"",
+            link.from, link.to, link.dist
+        )
+        .unwrap();
+        if link.negotiation {
+            dot.push_str(", style=dashed");
+        }
+        if link.disconnected {
+            dot.push_str(", color=red");
+        }
+        dot.push_str("];\n");
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Writes a path-node graph as JSON: an object with a `nodes` array and a
+/// `links` array, whose elements have the same fields as
+/// [`PathGraphNode`](t5_xfile_defs::gameworld::PathGraphNode) and
+/// [`PathGraphLink`](t5_xfile_defs::gameworld::PathGraphLink).
+pub fn path_graph_to_json(graph: &PathGraph) -> String {
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            format!(
+                "{{\"index\":{},\"type\":\"{:?}\",\"origin\":{},\"angle\":{},\
+                 \"targetname\":{},\"disabled\":{}}}",
+                node.index,
+                node.type_,
+                json_floats(&node.origin),
+                node.angle,
+                json_string(node.targetname),
+                node.disabled
+            )
+        })
+        .collect::<Vec<_>>();
+    let links = graph
+        .links
+        .iter()
+        .map(|link| {
+            format!(
+                "{{\"from\":{},\"to\":{},\"dist\":{},\"negotiation\":{},\
+                 \"disconnected\":{}}}",
+                link.from, link.to, link.dist, link.negotiation, link.disconnected
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\"nodes\":[{}],\"links\":[{}]}}",
+        nodes.join(","),
+        links.join(",")
+    )
+}
+
+impl ExportMaterial {
+    pub fn from_material(material: &Material) -> Self {
+        let color_map = material.textures.iter().find_map(|t| match &t.u {
+            MaterialTextureDefInfo::Image(Some(image)) if t.semantic == Semantic::COLOR_MAP => {
+                Some(image.name.get().to_string())
+            }
+            _ => None,
+        });
+
+        Self {
+            name: material.info.name.get().to_string(),
+            color_map,
+        }
+    }
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_convention(mut self, convention: CoordinateConvention) -> Self {
+        self.convention = convention;
+        self
+    }
+
+    /// The scene in [`Self::convention`]'s coordinates.
+    fn converted(&self) -> Cow<'_, Self> {
+        let convention = self.convention;
+        if convention == CoordinateConvention::NATIVE {
+            return Cow::Borrowed(self);
+        }
+
+        let mut scene = self.clone();
+        for mesh in &mut scene.meshes {
+            for p in &mut mesh.positions {
+                *p = convention.convert_point(*p);
+            }
+            for n in &mut mesh.normals {
+                *n = convention.convert_direction(*n);
+            }
+            if convention.flips_winding() {
+                for tri in mesh.indices.chunks_exact_mut(3) {
+                    tri.swap(1, 2);
+                }
+            }
+        }
+        for node in &mut scene.nodes {
+            let m = node.matrix;
+            let matrix = convention.convert_matrix(
+                [0, 1, 2, 3].map(|i| [m[i * 4], m[i * 4 + 1], m[i * 4 + 2], m[i * 4 + 3]]),
+            );
+            node.matrix = core::array::from_fn(|i| matrix[i / 4][i % 4]);
+        }
+        Cow::Owned(scene)
+    }
+
+    /// Adds `material` unless a material with the same name was already
+    /// added, and returns its index.
+    pub fn add_material(&mut self, material: ExportMaterial) -> usize {
+        match self.materials.iter().position(|m| m.name == material.name) {
+            Some(i) => i,
+            None => {
+                self.materials.push(material);
+                self.materials.len() - 1
+            }
+        }
+    }
+
+    /// Adds the meshes of `model`'s most detailed LOD, one per surface, and
+    /// returns their indices.
+    pub fn add_xmodel(&mut self, model: &XModel) -> Vec<usize> {
+        self.add_xmodel_lod(model, 0)
+    }
+
+    /// Adds the meshes of `model`'s LOD `lod`, one per surface, and returns
+    /// their indices (none if the model doesn't have that LOD).
+    pub fn add_xmodel_lod(&mut self, model: &XModel, lod: usize) -> Vec<usize> {
+        let Some(range) = model.lod_surface_range(lod) else {
+            return Vec::new();
+        };
+        let first = range.start;
+        let surfs = range.zip(model.surfaces_for_lod(lod));
+
+        let mut meshes = Vec::new();
+        for (i, surf) in surfs {
+            let material = model
+                .material_handles
+                .get(i)
+                .map(|m| self.add_material(ExportMaterial::from_material(m)));
+
+            let name = match lod {
+                0 => format!("{}_{}", model.name.get(), i - first),
+                _ => format!("{}_lod{}_{}", model.name.get(), lod, i - first),
+            };
+            let mut mesh = Mesh {
+                name,
+                indices: surf.tri_indices.iter().map(|&i| i as u32).collect(),
+                material,
+                ..Default::default()
+            };
+            for (position, normal, uv, _) in surf.unpack_vertices() {
+                mesh.positions.push(position.get());
+                mesh.normals.push(normal.get());
+                mesh.uvs.push(uv.get());
+            }
+
+            self.meshes.push(mesh);
+            meshes.push(self.meshes.len() - 1);
+        }
+
+        meshes
+    }
+
+    /// Creates a scene with just `model` in it, at the origin.
+    pub fn from_xmodel(model: &XModel) -> Self {
+        let mut scene = Self::new();
+        let meshes = scene.add_xmodel(model);
+        scene.nodes.push(Node {
+            name: model.name.get().to_string(),
+            meshes,
+            matrix: IDENTITY,
+        });
+        scene
+    }
+
+    /// Creates a scene with every LOD of `model` in it, at the origin, each
+    /// under its own node (`<name>` for LOD 0, `<name>_lod<n>` for the rest).
+    pub fn from_xmodel_lods(model: &XModel) -> Self {
+        let mut scene = Self::new();
+        for lod in 0..model.lod_count() {
+            let meshes = scene.add_xmodel_lod(model, lod);
+            let name = match lod {
+                0 => model.name.get().to_string(),
+                _ => format!("{}_lod{lod}", model.name.get()),
+            };
+            scene.nodes.push(Node {
+                name,
+                meshes,
+                matrix: IDENTITY,
+            });
+        }
+        scene
+    }
+
+    /// Adds the world's surfaces, merged into one mesh per material, under
+    /// a node named after the world, and a node for each of its static
+    /// models. Each model's meshes are only added once, however many times
+    /// it's placed.
+    ///
+    /// Surfaces whose triangles refer past the end of the world's vertices
+    /// are left out.
+    pub fn add_gfx_world<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        world: &GfxWorld<MAX_LOCAL_CLIENTS>,
+    ) {
+        let vertices = world.draw.unpack_vertices().collect::<Vec<_>>();
+
+        // for each material, the mesh its surfaces go in, and where each of
+        // the world's vertices ended up in it
+        let mut merged = HashMap::<Option<usize>, (usize, HashMap<usize, u32>)>::new();
+        let mut world_meshes = Vec::new();
+        for surf in &world.dpvs.surfaces {
+            let tris = &surf.tris;
+            let first_vertex = tris.first_vertex.max(0) as usize;
+            let Some(indices) = world
+                .draw
+                .indices
+                .get(tris.base_index..tris.base_index + tris.tri_count * 3)
+            else {
+                continue;
+            };
+            if indices
+                .iter()
+                .any(|&i| first_vertex + i as usize >= vertices.len())
+            {
+                continue;
+            }
+
+            let material = surf
+                .material
+                .as_deref()
+                .map(|m| self.add_material(ExportMaterial::from_material(m)));
+            let (mesh, remap) = merged.entry(material).or_insert_with(|| {
+                let name = match material {
+                    Some(m) => format!("{}_{}", world.name.get(), self.materials[m].name),
+                    None => world.name.get().to_string(),
+                };
+                self.meshes.push(Mesh {
+                    name,
+                    material,
+                    ..Default::default()
+                });
+                world_meshes.push(self.meshes.len() - 1);
+                (self.meshes.len() - 1, HashMap::new())
+            });
+
+            let mesh = &mut self.meshes[*mesh];
+            for &i in indices {
+                let vertex = first_vertex + i as usize;
+                let index = *remap.entry(vertex).or_insert_with(|| {
+                    let (position, normal, uv, _, _) = vertices[vertex];
+                    mesh.positions.push(position.get());
+                    mesh.normals.push(normal.get());
+                    mesh.uvs.push(uv.get());
+                    mesh.positions.len() as u32 - 1
+                });
+                mesh.indices.push(index);
+            }
+        }
+
+        self.nodes.push(Node {
+            name: world.name.get().to_string(),
+            meshes: world_meshes,
+            matrix: IDENTITY,
+        });
+
+        let mut models = HashMap::<&str, Vec<usize>>::new();
+        for inst in &world.dpvs.smodel_draw_insts {
+            let Some(model) = &inst.model else {
+                continue;
+            };
+
+            let meshes = models
+                .entry(model.name.get())
+                .or_insert_with(|| self.add_xmodel(model))
+                .clone();
+            self.nodes.push(Node {
+                name: model.name.get().to_string(),
+                meshes,
+                matrix: placement_matrix(&inst.placement),
+            });
+        }
+    }
+
+    /// Adds the map's collision under a node named after it: one mesh for
+    /// its terrain triangles, and one for its brushes (see
+    /// [`CBrush::polygons`](t5_xfile_defs::clipmap::CBrush::polygons)).
+    /// Neither has a material, normals, or texture coordinates.
+    ///
+    /// Every brush is added, including ones that only block players or are
+    /// triggers, and brush models are left where they are in the map file.
+    pub fn add_clip_map(&mut self, clip_map: &ClipMap) {
+        let mut meshes = Vec::new();
+
+        let positions = clip_map.verts.iter().map(|v| v.get()).collect::<Vec<_>>();
+        let indices = clip_map
+            .tri_indices
+            .chunks_exact(3)
+            .filter(|tri| tri.iter().all(|&i| (i as usize) < positions.len()))
+            .flatten()
+            .map(|&i| i as u32)
+            .collect::<Vec<_>>();
+        if !indices.is_empty() {
+            self.meshes.push(Mesh {
+                name: format!("{}_terrain", clip_map.name.get()),
+                positions,
+                indices,
+                ..Default::default()
+            });
+            meshes.push(self.meshes.len() - 1);
+        }
+
+        let mut brushes = Mesh {
+            name: format!("{}_brushes", clip_map.name.get()),
+            ..Default::default()
+        };
+        for polygon in clip_map.brushes.iter().flat_map(|b| b.polygons()) {
+            let base = brushes.positions.len() as u32;
+            brushes.positions.extend_from_slice(&polygon);
+            // the polygons are convex, so they can be triangulated as fans
+            for i in 1..polygon.len() as u32 - 1 {
+                brushes
+                    .indices
+                    .extend_from_slice(&[base, base + i, base + i + 1]);
+            }
+        }
+        if !brushes.indices.is_empty() {
+            self.meshes.push(brushes);
+            meshes.push(self.meshes.len() - 1);
+        }
+
+        self.nodes.push(Node {
+            name: format!("{}_collision", clip_map.name.get()),
+            meshes,
+            matrix: IDENTITY,
+        });
+    }
+
+    /// The path textures are written to, relative to the exported scene.
+    fn texture_uri(texture_dir: &str, image: &str) -> String {
+        if texture_dir.is_empty() {
+            format!("{}.png", file_name(image))
+        } else {
+            format!("{texture_dir}/{}.png", file_name(image))
+        }
+    }
+
+    /// Writes the scene as glTF, returning the JSON and the binary buffer
+    /// it refers to as `bin_uri`. Color maps are referred to as PNGs in
+    /// `texture_dir` (relative to the JSON), named after the image (see
+    /// [`file_name`]).
+    ///
+    /// glTF is meant to be Y-up and in meters, so unless something else
+    /// expects otherwise, [`Self::convention`] should be
+    /// [`CoordinateConvention::Y_UP_METERS`].
+    pub fn to_gltf(&self, bin_uri: &str, texture_dir: &str) -> (String, Vec<u8>) {
+        self.converted().write_gltf(bin_uri, texture_dir)
+    }
+
+    fn write_gltf(&self, bin_uri: &str, texture_dir: &str) -> (String, Vec<u8>) {
+        let mut bin = Vec::new();
+        let mut views = Vec::new();
+        let mut accessors = Vec::new();
+
+        // adds a buffer view and an accessor for `data`, and returns the
+        // accessor's index
+        let mut push = |data: &[u8], target: u32, accessor: String| {
+            let offset = bin.len();
+            bin.extend_from_slice(data);
+            views.push(format!(
+                "{{\"buffer\":0,\"byteOffset\":{offset},\"byteLength\":{},\"target\":{target}}}",
+                data.len()
+            ));
+            accessors.push(format!("{{\"bufferView\":{},{accessor}}}", views.len() - 1));
+            accessors.len() - 1
+        };
+
+        let mut meshes = Vec::new();
+        for mesh in &self.meshes {
+            let floats = |v: &[f32]| v.iter().flat_map(|f| f.to_le_bytes()).collect::<Vec<_>>();
+            let (min, max) =
+                mesh.positions
+                    .iter()
+                    .fold(([f32::MAX; 3], [f32::MIN; 3]), |(min, max), p| {
+                        (
+                            [0, 1, 2].map(|i| min[i].min(p[i])),
+                            [0, 1, 2].map(|i| max[i].max(p[i])),
+                        )
+                    });
+            let count = mesh.positions.len();
+
+            let mut attributes = vec![format!(
+                "\"POSITION\":{}",
+                push(
+                    &floats(mesh.positions.as_flattened()),
+                    34962,
+                    format!(
+                        "\"componentType\":5126,\"count\":{count},\"type\":\"VEC3\",\
+                         \"min\":{},\"max\":{}",
+                        json_floats(&min),
+                        json_floats(&max)
+                    ),
+                )
+            )];
+            if mesh.normals.len() == count {
+                attributes.push(format!(
+                    "\"NORMAL\":{}",
+                    push(
+                        &floats(mesh.normals.as_flattened()),
+                        34962,
+                        format!("\"componentType\":5126,\"count\":{count},\"type\":\"VEC3\""),
+                    )
+                ));
+            }
+            if mesh.uvs.len() == count {
+                attributes.push(format!(
+                    "\"TEXCOORD_0\":{}",
+                    push(
+                        &floats(mesh.uvs.as_flattened()),
+                        34962,
+                        format!("\"componentType\":5126,\"count\":{count},\"type\":\"VEC2\""),
+                    )
+                ));
+            }
+
+            let indices = push(
+                &mesh
+                    .indices
+                    .iter()
+                    .flat_map(|i| i.to_le_bytes())
+                    .collect::<Vec<_>>(),
+                34963,
+                format!(
+                    "\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"",
+                    mesh.indices.len()
+                ),
+            );
+
+            let material = mesh
+                .material
+                .map(|m| format!(",\"material\":{m}"))
+                .unwrap_or_default();
+            meshes.push(format!(
+                "{{\"name\":{},\"primitives\":[{{\"attributes\":{{{}}},\"indices\":{indices}{material}}}]}}",
+                json_string(&mesh.name),
+                attributes.join(",")
+            ));
+        }
+
+        let mut images = Vec::new();
+        let materials = self
+            .materials
+            .iter()
+            .map(|material| {
+                let texture = material
+                    .color_map
+                    .as_ref()
+                    .map(|image| {
+                        images.push(format!(
+                            "{{\"uri\":{}}}",
+                            json_string(&Self::texture_uri(texture_dir, image))
+                        ));
+                        format!("\"baseColorTexture\":{{\"index\":{}}},", images.len() - 1)
+                    })
+                    .unwrap_or_default();
+                format!(
+                    "{{\"name\":{},\"pbrMetallicRoughness\":{{{texture}\"metallicFactor\":0}}}}",
+                    json_string(&material.name)
+                )
+            })
+            .collect::<Vec<_>>();
+        let textures = (0..images.len())
+            .map(|i| format!("{{\"source\":{i}}}"))
+            .collect::<Vec<_>>();
+
+        // glTF nodes only have one mesh, so nodes with more get a child for
+        // each, right after them
+        let mut node_list = Vec::new();
+        let mut roots = Vec::new();
+        for node in &self.nodes {
+            let index = node_list.len();
+            roots.push(index.to_string());
+
+            let (mesh, children) = match node.meshes.as_slice() {
+                [] => (String::new(), String::new()),
+                [mesh] => (format!(",\"mesh\":{mesh}"), String::new()),
+                meshes => {
+                    let children = (1..=meshes.len())
+                        .map(|i| (index + i).to_string())
+                        .collect::<Vec<_>>();
+                    (
+                        String::new(),
+                        format!(",\"children\":[{}]", children.join(",")),
+                    )
+                }
+            };
+            node_list.push(format!(
+                "{{\"name\":{}{mesh},\"matrix\":{}{children}}}",
+                json_string(&node.name),
+                json_floats(&node.matrix)
+            ));
+
+            if node.meshes.len() > 1 {
+                for mesh in &node.meshes {
+                    node_list.push(format!("{{\"mesh\":{mesh}}}"));
+                }
+            }
+        }
+
+        let mut json = String::from(
+            "{\"asset\":{\"version\":\"2.0\",\"generator\":\"t5-xfile-deserializer\"},\"scene\":0",
+        );
+        write!(json, ",\"scenes\":[{{\"nodes\":[{}]}}]", roots.join(",")).unwrap();
+        for (key, values) in [
+            ("nodes", &node_list),
+            ("meshes", &meshes),
+            ("materials", &materials),
+            ("textures", &textures),
+            ("images", &images),
+            ("accessors", &accessors),
+            ("bufferViews", &views),
+        ] {
+            if !values.is_empty() {
+                write!(json, ",\"{key}\":[{}]", values.join(",")).unwrap();
+            }
+        }
+        write!(
+            json,
+            ",\"buffers\":[{{\"uri\":{},\"byteLength\":{}}}]}}",
+            json_string(bin_uri),
+            bin.len()
+        )
+        .unwrap();
+
+        (json, bin)
+    }
+
+    /// Writes the scene as an OBJ file that uses the materials in the MTL
+    /// file `mtl_uri`, and returns both. Color maps are referred to the same
+    /// way as in [`Self::to_gltf`].
+    ///
+    /// OBJ has no notion of nodes, so every mesh is written with its nodes'
+    /// transforms applied, once per node that uses it.
+    pub fn to_obj(&self, mtl_uri: &str, texture_dir: &str) -> (String, String) {
+        self.converted().write_obj(mtl_uri, texture_dir)
+    }
+
+    fn write_obj(&self, mtl_uri: &str, texture_dir: &str) -> (String, String) {
+        let mut obj = format!("mtllib {mtl_uri}\n");
+        // OBJ indices are 1-based and count every vertex before them
+        let mut base = 1;
+
+        for node in &self.nodes {
+            let m = &node.matrix;
+            let transform = |p: &[f32; 3], w: f32| {
+                [0, 1, 2].map(|i| m[i] * p[0] + m[4 + i] * p[1] + m[8 + i] * p[2] + m[12 + i] * w)
+            };
+
+            for mesh in node.meshes.iter().filter_map(|&i| self.meshes.get(i)) {
+                writeln!(obj, "o {}", mesh.name).unwrap();
+                for p in &mesh.positions {
+                    let [x, y, z] = transform(p, 1.0);
+                    writeln!(obj, "v {x} {y} {z}").unwrap();
+                }
+                for uv in &mesh.uvs {
+                    writeln!(obj, "vt {} {}", uv[0], 1.0 - uv[1]).unwrap();
+                }
+                for n in &mesh.normals {
+                    let [x, y, z] = transform(n, 0.0);
+                    writeln!(obj, "vn {x} {y} {z}").unwrap();
+                }
+                if let Some(material) = mesh.material.and_then(|m| self.materials.get(m)) {
+                    writeln!(obj, "usemtl {}", material.name).unwrap();
+                }
+
+                let has_uvs = mesh.uvs.len() == mesh.positions.len();
+                let has_normals = mesh.normals.len() == mesh.positions.len();
+                for tri in mesh.indices.chunks_exact(3) {
+                    obj.push('f');
+                    for &i in tri {
+                        let i = base + i as usize;
+                        match (has_uvs, has_normals) {
+                            (true, true) => write!(obj, " {i}/{i}/{i}"),
+                            (true, false) => write!(obj, " {i}/{i}"),
+                            (false, true) => write!(obj, " {i}//{i}"),
+                            (false, false) => write!(obj, " {i}"),
+                        }
+                        .unwrap();
+                    }
+                    obj.push('\n');
+                }
+
+                base += mesh.positions.len();
+            }
+        }
+
+        let mut mtl = String::new();
+        for material in &self.materials {
+            writeln!(mtl, "newmtl {}", material.name).unwrap();
+            writeln!(mtl, "Kd 1 1 1").unwrap();
+            if let Some(image) = &material.color_map {
+                writeln!(mtl, "map_Kd {}", Self::texture_uri(texture_dir, image)).unwrap();
+            }
+        }
+
+        (obj, mtl)
+    }
+}
@@ -0,0 +1,195 @@
+//! Finding Fastfiles and their companion files (image packs, sound banks,
+//! and localized zones) in a game install.
+//!
+//! An install keeps its Fastfiles in `zone/`, split into `zone/Common/` for
+//! zones every language uses and one directory per language (e.g.
+//! `zone/English/`) for localized zones. Companion files live next to the
+//! Fastfiles, or in the root of the install.
+
+use std::path::{Path, PathBuf};
+
+use crate::{Error, ErrorKind, Result, file_line_col};
+
+/// The directory in a game install that holds the Fastfiles.
+pub const ZONE_DIR: &str = "zone";
+
+/// The directory in [`ZONE_DIR`] for zones that aren't localized.
+pub const COMMON_ZONE_DIR: &str = "Common";
+
+pub const ZONE_EXTENSION: &str = "ff";
+
+/// A game install.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameDir {
+    root: PathBuf,
+}
+
+impl GameDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Every directory companion files are looked for in: the root of the
+    /// install, [`ZONE_DIR`], [`COMMON_ZONE_DIR`], then each language's
+    /// directory in order of their names. Directories that don't exist are
+    /// left out, but the root has to.
+    pub fn companion_dirs(&self) -> Result<Vec<PathBuf>> {
+        if !self.root.is_dir() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{} isn't a directory", self.root.display()),
+                )),
+            ));
+        }
+
+        let mut dirs = vec![self.root.clone()];
+        let zone_dir = self.root.join(ZONE_DIR);
+        if zone_dir.is_dir() {
+            dirs.push(zone_dir);
+            dirs.extend(self.common_dir()?);
+            dirs.extend(self.language_dirs()?.into_iter().map(|(_, dir)| dir));
+        }
+
+        Ok(dirs)
+    }
+
+    /// Every file in the [`companion_dirs`](Self::companion_dirs) (but not
+    /// their subdirectories) with one of `extensions`, ignoring case. Files
+    /// are in the order of their directories, then of their names.
+    pub fn companion_files(&self, extensions: &[&str]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+
+        for dir in self.companion_dirs()? {
+            let mut paths = read_dir(&dir)?;
+            paths.retain(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+            });
+            files.extend(paths);
+        }
+
+        Ok(files)
+    }
+
+    /// The names of the languages with a directory in [`ZONE_DIR`], in
+    /// order.
+    pub fn languages(&self) -> Result<Vec<String>> {
+        Ok(self
+            .language_dirs()?
+            .into_iter()
+            .map(|(language, _)| language)
+            .collect())
+    }
+
+    /// Finds the Fastfile for the zone `name` (with or without the
+    /// [`ZONE_EXTENSION`]). Zones in [`COMMON_ZONE_DIR`] take priority over
+    /// ones directly in [`ZONE_DIR`] or the root of the install, which take
+    /// priority over localized ones (see [`Self::localized_zones`]).
+    pub fn find_zone(&self, name: &str) -> Result<Option<PathBuf>> {
+        let file_name = zone_file_name(name);
+        let zone_dir = self.root.join(ZONE_DIR);
+
+        let mut dirs = Vec::new();
+        if zone_dir.is_dir() {
+            dirs.extend(self.common_dir()?);
+            dirs.push(zone_dir);
+        }
+        dirs.push(self.root.clone());
+
+        if let Some(path) = dirs
+            .into_iter()
+            .map(|dir| dir.join(&file_name))
+            .find(|p| p.is_file())
+        {
+            return Ok(Some(path));
+        }
+
+        Ok(self
+            .localized_zones(name)?
+            .into_iter()
+            .next()
+            .map(|(_, path)| path))
+    }
+
+    /// Every language's version of the zone `name` (with or without the
+    /// [`ZONE_EXTENSION`]), along with the language, in order of the
+    /// languages.
+    pub fn localized_zones(&self, name: &str) -> Result<Vec<(String, PathBuf)>> {
+        let file_name = zone_file_name(name);
+
+        Ok(self
+            .language_dirs()?
+            .into_iter()
+            .map(|(language, dir)| (language, dir.join(&file_name)))
+            .filter(|(_, path)| path.is_file())
+            .collect())
+    }
+
+    fn common_dir(&self) -> Result<Option<PathBuf>> {
+        Ok(self
+            .zone_subdirs()?
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(COMMON_ZONE_DIR))
+            .map(|(_, dir)| dir))
+    }
+
+    fn language_dirs(&self) -> Result<Vec<(String, PathBuf)>> {
+        let mut dirs = self.zone_subdirs()?;
+        dirs.retain(|(name, _)| !name.eq_ignore_ascii_case(COMMON_ZONE_DIR));
+        Ok(dirs)
+    }
+
+    /// Every directory in [`ZONE_DIR`], along with its name, in order of
+    /// their names.
+    fn zone_subdirs(&self) -> Result<Vec<(String, PathBuf)>> {
+        let zone_dir = self.root.join(ZONE_DIR);
+        if !zone_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        Ok(read_dir(&zone_dir)?
+            .into_iter()
+            .filter(|p| p.is_dir())
+            .filter_map(|p| {
+                let name = p.file_name()?.to_str()?.to_string();
+                Some((name, p))
+            })
+            .collect())
+    }
+}
+
+fn zone_file_name(name: &str) -> PathBuf {
+    let name = Path::new(name);
+    let has_extension = name
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(ZONE_EXTENSION));
+    if has_extension {
+        name.to_path_buf()
+    } else {
+        let mut file_name = name.as_os_str().to_os_string();
+        file_name.push(".");
+        file_name.push(ZONE_EXTENSION);
+        file_name.into()
+    }
+}
+
+/// Every entry in `dir`, in order of their names.
+fn read_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?
+        .map(|e| {
+            e.map(|e| e.path())
+                .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
@@ -0,0 +1,361 @@
+//! Image packs, which hold the high mip levels of streamed images (see
+//! [`GfxImage::streaming`]).
+//!
+//! Fastfiles usually only contain the low mips of large textures, and the
+//! rest are streamed in from image packs in the game directory (`.ipak`
+//! files on PC, `.xpak` on consoles) as they're needed. Both have the same
+//! layout, in the platform's endianness:
+//!
+//! | Offset | Size | Contents                                             |
+//! |--------|------|------------------------------------------------------|
+//! | 0x00   | 16   | An [`ImagePackHeader`].                              |
+//! | 0x10   | *    | `section_count` [`ImagePackSection`]s.               |
+//!
+//! The index section contains one [`ImagePackEntry`] per image. An image's
+//! data consists of one or more blocks, each a 128-byte header (the offset
+//! of the block within the image, and up to 31 chunk sizes, each with a
+//! compression flag) followed by its chunks. Chunks are either stored as-is
+//! or compressed with LZO1X.
+//!
+//! [`ImagePacks::from_game_dir`] opens every pack in a game directory, and
+//! [`T5XFileDeserializerBuilder::with_game_dir`](crate::T5XFileDeserializerBuilder::with_game_dir)
+//! uses them to fuse the streamed mips into every [`GfxImage`] it
+//! deserializes.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{BincodeOptions, Error, ErrorKind, GameDir, Result, file_line_col, lzo};
+
+use t5_xfile_defs::techset::GfxImage;
+
+/// The magic of a little-endian pack. Big-endian packs have the same magic
+/// in reverse, so it reads as `IPAK` either way.
+pub const IPAK_MAGIC: [u8; 4] = *b"KAPI";
+
+pub const IPAK_VERSION: u32 = 0x50000;
+
+/// The extensions of image packs, on PC and on consoles.
+pub const IPAK_EXTENSIONS: [&str; 2] = ["ipak", "xpak"];
+
+/// The section type of the index.
+pub const IPAK_SECTION_INDEX: u32 = 1;
+/// The section type of the image data.
+pub const IPAK_SECTION_DATA: u32 = 2;
+
+const IPAK_BLOCK_HEADER_SIZE: u64 = 128;
+const IPAK_BLOCK_MAX_CHUNKS: usize = 31;
+
+const IPAK_CHUNK_STORED: u32 = 0;
+const IPAK_CHUNK_LZO: u32 = 1;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct ImagePackHeader {
+    pub magic: [u8; 4],
+    pub version: u32,
+    /// The size of the whole pack.
+    pub size: u32,
+    pub section_count: u32,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct ImagePackSection {
+    /// [`IPAK_SECTION_INDEX`] or [`IPAK_SECTION_DATA`].
+    pub section_type: u32,
+    pub offset: u32,
+    pub size: u32,
+    pub item_count: u32,
+}
+
+/// An image in a pack.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct ImagePackEntry {
+    /// A hash of the image's data, which isn't checked.
+    pub data_hash: u32,
+    /// The [`GfxImage::hash`] of the image the mips are for.
+    pub name_hash: u32,
+    /// The offset of the image's data, relative to the data section.
+    pub offset: u32,
+    /// The size of the image's data as stored in the pack, including every
+    /// block header.
+    pub size: u32,
+}
+
+/// A single image pack.
+pub struct ImagePack<R> {
+    reader: R,
+    little_endian: bool,
+    header: ImagePackHeader,
+    data_offset: u64,
+    /// Keyed by [`ImagePackEntry::name_hash`].
+    entries: BTreeMap<u32, ImagePackEntry>,
+}
+
+impl ImagePack<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> ImagePack<R> {
+    /// Reads and validates the header and the index. Image data isn't read
+    /// until it's asked for.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        let little_endian = if magic == IPAK_MAGIC {
+            true
+        } else {
+            magic.reverse();
+            if magic != IPAK_MAGIC {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    0,
+                    ErrorKind::BadImagePack(format!("bad magic {magic:02X?}")),
+                ));
+            }
+            false
+        };
+
+        reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        let opts = BincodeOptions::new(little_endian);
+        let header = opts
+            .deserialize_from::<ImagePackHeader>(&mut reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        if header.version != IPAK_VERSION {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                4,
+                ErrorKind::BadImagePack(format!(
+                    "version is {:#X}, expected {IPAK_VERSION:#X}",
+                    header.version
+                )),
+            ));
+        }
+
+        let sections = (0..header.section_count)
+            .map(|i| {
+                opts.deserialize_from::<ImagePackSection>(&mut reader)
+                    .map_err(|e| {
+                        Error::new_with_offset(file_line_col!(), 16 + i * 16, ErrorKind::Bincode(e))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let section = |section_type| {
+            sections
+                .iter()
+                .find(|s| s.section_type == section_type)
+                .copied()
+                .ok_or_else(|| {
+                    Error::new(
+                        file_line_col!(),
+                        ErrorKind::BadImagePack(format!("no section of type {section_type}")),
+                    )
+                })
+        };
+        let index = section(IPAK_SECTION_INDEX)?;
+        let data = section(IPAK_SECTION_DATA)?;
+
+        reader
+            .seek(SeekFrom::Start(index.offset as _))
+            .map_err(|e| {
+                Error::new_with_offset(file_line_col!(), index.offset, ErrorKind::Io(e))
+            })?;
+        let mut entries = BTreeMap::new();
+        for i in 0..index.item_count {
+            let entry = opts
+                .deserialize_from::<ImagePackEntry>(&mut reader)
+                .map_err(|e| {
+                    Error::new_with_offset(
+                        file_line_col!(),
+                        index.offset + i * 16,
+                        ErrorKind::Bincode(e),
+                    )
+                })?;
+            if entry.offset as u64 + entry.size as u64 > data.size as u64 {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    index.offset + i * 16,
+                    ErrorKind::BadImagePack(format!(
+                        "entry {i} ({:#010X}) lies outside the data section",
+                        entry.name_hash
+                    )),
+                ));
+            }
+            entries.entry(entry.name_hash).or_insert(entry);
+        }
+
+        Ok(Self {
+            reader,
+            little_endian,
+            header,
+            data_offset: data.offset as _,
+            entries,
+        })
+    }
+
+    pub fn header(&self) -> ImagePackHeader {
+        self.header
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &ImagePackEntry> {
+        self.entries.values()
+    }
+
+    pub fn get(&self, name_hash: u32) -> Option<&ImagePackEntry> {
+        self.entries.get(&name_hash)
+    }
+
+    /// Reads and decompresses an image's data.
+    pub fn read(&mut self, entry: ImagePackEntry) -> Result<Vec<u8>> {
+        let start = self.data_offset + entry.offset as u64;
+        let end = start + entry.size as u64;
+        let opts = BincodeOptions::new(self.little_endian);
+
+        let mut data = Vec::new();
+        let mut pos = start;
+        while pos < end {
+            self.reader.seek(SeekFrom::Start(pos)).map_err(|e| {
+                Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Io(e))
+            })?;
+            let (count_and_offset, commands) = opts
+                .deserialize_from::<(u32, [u32; IPAK_BLOCK_MAX_CHUNKS])>(&mut self.reader)
+                .map_err(|e| {
+                    Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Bincode(e))
+                })?;
+
+            let count = (count_and_offset >> 24) as usize;
+            let offset = count_and_offset & 0xFFFFFF;
+            if count == 0 || count > IPAK_BLOCK_MAX_CHUNKS || offset as usize != data.len() {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    pos as _,
+                    ErrorKind::BadImagePack(format!(
+                        "bad block header (count={count}, offset={offset:#X}, \
+                         expected offset {:#X})",
+                        data.len()
+                    )),
+                ));
+            }
+            pos += IPAK_BLOCK_HEADER_SIZE;
+
+            for command in &commands[..count] {
+                let size = command & 0xFFFFFF;
+                let mut chunk = vec![0u8; size as _];
+                self.reader.read_exact(&mut chunk).map_err(|e| {
+                    Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Io(e))
+                })?;
+
+                match command >> 24 {
+                    IPAK_CHUNK_STORED => data.append(&mut chunk),
+                    IPAK_CHUNK_LZO => {
+                        let mut chunk =
+                            lzo::decompress(&chunk, size as usize * 2).map_err(|e| {
+                                Error::new_with_offset(
+                                    file_line_col!(),
+                                    pos as _,
+                                    ErrorKind::BadImagePack(format!("bad LZO chunk: {e}")),
+                                )
+                            })?;
+                        data.append(&mut chunk);
+                    }
+                    compression => {
+                        return Err(Error::new_with_offset(
+                            file_line_col!(),
+                            pos as _,
+                            ErrorKind::BadImagePack(format!(
+                                "unknown chunk compression {compression}"
+                            )),
+                        ));
+                    }
+                }
+                pos += size as u64;
+            }
+        }
+
+        if pos != end {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::BadImagePack(format!(
+                    "image {:#010X} overruns its entry by {} bytes",
+                    entry.name_hash,
+                    pos - end
+                )),
+            ));
+        }
+
+        Ok(data)
+    }
+}
+
+/// Every image pack in a game directory.
+pub struct ImagePacks {
+    packs: Vec<(PathBuf, ImagePack<BufReader<File>>)>,
+}
+
+impl ImagePacks {
+    /// Opens every file with one of the [`IPAK_EXTENSIONS`] in the companion
+    /// directories of the game install at `dir` (see
+    /// [`GameDir::companion_files`]).
+    pub fn from_game_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let paths = GameDir::new(dir.as_ref()).companion_files(&IPAK_EXTENSIONS)?;
+
+        let packs = paths
+            .into_iter()
+            .map(|p| ImagePack::open(&p).map(|pack| (p, pack)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { packs })
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.packs.iter().map(|(p, _)| p.as_path())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packs.is_empty()
+    }
+
+    /// Reads the streamed mips of the image with `name_hash` from the first
+    /// pack containing it.
+    pub fn read_image(&mut self, name_hash: u32) -> Result<Option<Vec<u8>>> {
+        for (_, pack) in &mut self.packs {
+            if let Some(&entry) = pack.get(name_hash) {
+                return pack.read(entry).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Fuses `image`'s streamed mips into it (see
+    /// [`GfxImage::fuse_streamed_mips`]). Returns `false` if the image isn't
+    /// streamed or none of the packs contain it.
+    pub fn fuse(&mut self, image: &mut GfxImage) -> Result<bool> {
+        if image.streamed_level_count() == 0 {
+            return Ok(false);
+        }
+
+        match self.read_image(image.hash)? {
+            Some(mips) => Ok(image.fuse_streamed_mips(mips)),
+            None => Ok(false),
+        }
+    }
+}
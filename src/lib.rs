@@ -13,12 +13,105 @@ pub use deserializer::*;
 #[cfg(feature = "serializer")]
 pub mod serializer;
 
+#[cfg(feature = "serializer")]
+mod ptr_slots;
+
+pub mod codec;
+
+#[cfg(feature = "deserializer")]
+mod reader;
+
+#[cfg(feature = "trace")]
+pub mod trace;
+
+#[cfg(feature = "deserializer")]
+pub mod borrowed;
+
+#[cfg(feature = "deserializer")]
+pub mod batch;
+
+#[cfg(feature = "deserializer")]
+pub mod game_dir;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+#[cfg(feature = "deserializer")]
+pub mod ipak;
+
+#[cfg(feature = "deserializer")]
+mod lzo;
+
+#[cfg(feature = "deserializer")]
+pub mod sound_bank;
+
+pub mod png;
+
+pub mod export;
+
+pub mod summary;
+
+#[cfg(feature = "navmesh")]
+pub mod navmesh;
+
+#[cfg(feature = "cache")]
+pub mod cache;
+
+#[cfg(feature = "cache")]
+pub use cache::*;
+
+#[cfg(feature = "deserializer")]
+pub use borrowed::*;
+
+#[cfg(feature = "deserializer")]
+pub mod zone;
+
+#[cfg(feature = "deserializer")]
+pub mod snapshot;
+
+#[cfg(all(feature = "deserializer", feature = "serde"))]
+pub mod dump;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "async")]
+pub mod async_deserializer;
+
+#[cfg(feature = "async")]
+pub use async_deserializer::*;
+
+#[cfg(feature = "deserializer")]
+pub use zone::*;
+
+#[cfg(feature = "deserializer")]
+pub use snapshot::*;
+
+#[cfg(feature = "deserializer")]
+pub use game_dir::*;
+
 #[cfg(feature = "serializer")]
 pub use serializer::*;
 
+#[cfg(all(feature = "serializer", feature = "deserializer"))]
+pub mod roundtrip;
+
+#[cfg(feature = "testgen")]
+pub mod testgen;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+pub mod prelude;
+
+pub use t5_xfile_defs;
+
 use std::io::{Seek, SeekFrom};
 
-use t5_xfile_defs::{Error, ErrorKind, Result, XFilePlatform};
+use t5_xfile_defs::{Error, ErrorKind, Result};
+
+#[cfg(feature = "serializer")]
+use t5_xfile_defs::XFilePlatform;
 
 use bincode::{
     DefaultOptions, Options,
@@ -70,6 +163,7 @@ impl BincodeOptions {
         }
     }
 
+    #[cfg(feature = "serializer")]
     fn from_platform(platform: XFilePlatform) -> Self {
         Self::new(platform.is_le())
     }
@@ -85,7 +179,21 @@ impl BincodeOptions {
         }
     }
 
-    #[cfg(feature = "serializer")]
+    /// Same as [`Self::deserialize_from`], but fails instead of reading (or
+    /// allocating) more than `limit` bytes.
+    #[cfg(feature = "cache")]
+    fn deserialize_from_with_limit<T: serde::de::DeserializeOwned>(
+        &self,
+        reader: impl std::io::Read,
+        limit: u64,
+    ) -> bincode::Result<T> {
+        match self {
+            Self::LE(opts) => opts.with_limit(limit).deserialize_from(reader),
+            Self::BE(opts) => opts.with_limit(limit).deserialize_from(reader),
+        }
+    }
+
+    #[cfg(feature = "serde")]
     fn serialize_into<T: serde::ser::Serialize>(
         &self,
         writer: impl std::io::Write,
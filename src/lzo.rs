@@ -0,0 +1,159 @@
+//! A decompressor for LZO1X, which image packs (see [`crate::ipak`])
+//! compress their chunks with. Mirrors `lzo1x_decompress_safe` from
+//! miniLZO, so every read and back-reference is bounds-checked.
+
+/// How an instruction below 16 is interpreted depends on what came before
+/// it.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    /// A literal run.
+    Instruction,
+    /// A 3-byte match, at least 0x801 bytes back.
+    AfterLiteralRun,
+    /// A 2-byte match.
+    AfterTrailingLiterals,
+}
+
+struct Decompressor<'a> {
+    src: &'a [u8],
+    ip: usize,
+    out: Vec<u8>,
+}
+
+impl<'a> Decompressor<'a> {
+    fn byte(&mut self) -> Result<usize, String> {
+        let b = self
+            .src
+            .get(self.ip)
+            .ok_or_else(|| format!("input overrun at {}", self.ip))?;
+        self.ip += 1;
+        Ok(*b as _)
+    }
+
+    fn le16(&mut self) -> Result<usize, String> {
+        Ok(self.byte()? | self.byte()? << 8)
+    }
+
+    /// Lengths that don't fit in an instruction are extended by a run of
+    /// zero bytes (255 each) and a final non-zero byte.
+    fn extended(&mut self, base: usize) -> Result<usize, String> {
+        let mut len = base;
+        loop {
+            match self.byte()? {
+                0 => len += 255,
+                b => return Ok(len + b),
+            }
+        }
+    }
+
+    fn literals(&mut self, len: usize) -> Result<(), String> {
+        let literals = self
+            .src
+            .get(self.ip..self.ip + len)
+            .ok_or_else(|| format!("input overrun at {}", self.ip))?;
+        self.out.extend_from_slice(literals);
+        self.ip += len;
+        Ok(())
+    }
+
+    fn copy_match(&mut self, dist: usize, len: usize) -> Result<(), String> {
+        if dist > self.out.len() {
+            return Err(format!(
+                "lookbehind overrun at {} (distance {dist}, output {})",
+                self.ip,
+                self.out.len()
+            ));
+        }
+
+        // matches can overlap what they're copying, so copy byte by byte
+        let start = self.out.len() - dist;
+        for i in start..start + len {
+            self.out.push(self.out[i]);
+        }
+        Ok(())
+    }
+}
+
+/// Decompresses `src`, which has to be a complete LZO1X stream (i.e., end
+/// with an end-of-stream marker). `len` is only used as a capacity hint.
+pub(crate) fn decompress(src: &[u8], len: usize) -> Result<Vec<u8>, String> {
+    let mut d = Decompressor {
+        src,
+        ip: 0,
+        out: Vec::with_capacity(len),
+    };
+
+    // a first byte above 17 is a literal run without an instruction
+    let mut state = State::Instruction;
+    if let Some(&b) = src.first()
+        && b > 17
+    {
+        d.ip = 1;
+        let len = b as usize - 17;
+        d.literals(len)?;
+        state = if len < 4 {
+            State::AfterTrailingLiterals
+        } else {
+            State::AfterLiteralRun
+        };
+    }
+
+    loop {
+        let op = d.byte()?;
+
+        if op < 16 {
+            match state {
+                State::Instruction => {
+                    let len = if op == 0 { d.extended(15)? } else { op };
+                    d.literals(len + 3)?;
+                    state = State::AfterLiteralRun;
+                    continue;
+                }
+                State::AfterLiteralRun => {
+                    let dist = 0x801 + (op >> 2) + (d.byte()? << 2);
+                    d.copy_match(dist, 3)?;
+                }
+                State::AfterTrailingLiterals => {
+                    let dist = 1 + (op >> 2) + (d.byte()? << 2);
+                    d.copy_match(dist, 2)?;
+                }
+            }
+        } else if op >= 64 {
+            let dist = 1 + ((op >> 2) & 7) + (d.byte()? << 3);
+            d.copy_match(dist, (op >> 5) + 1)?;
+        } else if op >= 32 {
+            let len = match op & 31 {
+                0 => d.extended(31)?,
+                len => len,
+            };
+            let dist = 1 + (d.le16()? >> 2);
+            d.copy_match(dist, len + 2)?;
+        } else {
+            let high = (op & 8) << 11;
+            let len = match op & 7 {
+                0 => d.extended(7)?,
+                len => len,
+            };
+            let dist = high + (d.le16()? >> 2);
+            if dist == 0 {
+                // end of stream
+                return if d.ip == src.len() {
+                    Ok(d.out)
+                } else {
+                    Err(format!("{} bytes after end of stream", src.len() - d.ip))
+                };
+            }
+            d.copy_match(dist + 0x4000, len + 2)?;
+        }
+
+        // the number of literals following a match is stored in the low two
+        // bits of the byte two back
+        let trailing = src[d.ip - 2] as usize & 3;
+        if trailing > 0 {
+            d.literals(trailing)?;
+            state = State::AfterTrailingLiterals;
+        } else {
+            state = State::Instruction;
+        }
+    }
+}
@@ -1,23 +1,43 @@
-use std::path::Path;
+use std::{
+    collections::BTreeSet,
+    path::{Component, Path, PathBuf},
+};
 
-use t5_xfile_defs::XFilePlatform;
-use t5_xfile_deserializer::T5XFileDeserializerBuilder;
+use t5_xfile_defs::{
+    XFilePlatform,
+    access::HasImages,
+    common::CoordinateConvention,
+    gameworld::PathData,
+    gfx_world::GfxWorld,
+    sound::{SndBank, SoundFileRef},
+    stats::XAssetStats,
+    techset::GfxImage,
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+use t5_xfile_deserializer::{
+    ARCHIVE_FILE_EXTENSION, CACHE_FILE_EXTENSION, CacheSource, GameDir, T5XFileDeserializer,
+    T5XFileDeserializerBuilder, T5XFileOpenOptions, build_cache, cache_info, cache_path_for,
+    clear_cache,
+    export::{self, Scene},
+    png,
+};
 
-use clap::{arg, command};
-
-const CACHE_FILE_EXT: &str = "cache";
+use clap::{Command, arg, command};
 
 fn main() {
-    let matches = command!()
-        .arg(arg!([FILENAME] "Filename to use (should have .ff or .cache extension)"))
+    let command = command!()
         .arg(arg!(
-            -p --platform <PLATFORM>
-            "Specifies which platform the Fastfile is expected to be for. Should be one of:\n\
-             \twindows\n\
-             \tmacos\n\
-             \txbox360\n\
-             \tps3"
+            [FILENAME]
+            "Filename to use (should have .ff, .cache, or .archive extension), or the name of \
+             a zone in the game install given by --game_dir"
         ))
+        .arg(
+            arg!(
+                -p --platform <PLATFORM>
+                "Specifies which platform the Fastfile is expected to be for"
+            )
+            .value_parser(clap::value_parser!(XFilePlatform)),
+        )
         .arg(
             arg!(
                 -a --allow_unsupported_platforms
@@ -26,28 +46,250 @@ fn main() {
             )
             .required(false),
         )
-        .get_matches();
+        .arg(
+            arg!(
+                --max_local_clients <COUNT>
+                "Deserializes assets as if the Fastfile had room for COUNT local \
+                 clients (1 or 4). Defaults to 4 on consoles and 1 on PC."
+            )
+            .required(false)
+            .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            arg!(
+                --game_dir <DIR>
+                "Loads streamed image mips and sounds from the image packs and \
+                 sound banks in the game install at DIR, and looks FILENAME up \
+                 in its zone directories if it isn't a path to a file."
+            )
+            .required(false),
+        )
+        .subcommand(
+            Command::new("capabilities")
+                .about("Prints which asset types can be deserialized and serialized"),
+        )
+        .subcommand(
+            Command::new("cache")
+                .about("Manages the cache of the inflated payload kept next to the Fastfile")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("build")
+                        .about(
+                            "Inflates the Fastfile and writes its cache, unless the cache \
+                             is already up to date with it",
+                        )
+                        .arg(
+                            arg!(--force "Rewrites the cache even if it's up to date")
+                                .required(false),
+                        ),
+                )
+                .subcommand(Command::new("clear").about("Deletes the Fastfile's cache"))
+                .subcommand(Command::new("info").about(
+                    "Prints what the cache (or FILENAME, if it's a cache or archive) \
+                     was created from, and whether it's up to date with the Fastfile",
+                )),
+        )
+        .subcommand(
+            Command::new("archive")
+                .about(
+                    "Writes the inflated payload and asset list to OUT, compressed for \
+                     long-term storage. OUT can be used as FILENAME in place of the \
+                     Fastfile.",
+                )
+                .arg(arg!(<OUT> "Path of the archive (should have .archive extension)")),
+        )
+        .subcommand(Command::new("list").about(
+            "Prints the type and name of every asset in the Fastfile, only reading as \
+             much of each asset as it takes to find its name",
+        ))
+        .subcommand(Command::new("stats").about(
+            "Prints the number and estimated size of each type of asset in the Fastfile, \
+             its largest assets, and its total vertex, triangle, and sound sample counts",
+        ))
+        .subcommand(
+            Command::new("extract-sounds")
+                .about(
+                    "Writes every loaded sound to OUT_DIR, as a .wav file if possible. \
+                     Streamed sounds are only written if --game_dir is given.",
+                )
+                .arg(arg!(<OUT_DIR> "Directory to write the sounds to")),
+        )
+        .subcommand(
+            Command::new("extract-images")
+                .about(
+                    "Writes every image in the Fastfile (on their own or used by \
+                     materials) whose name matches PATTERN to OUT_DIR. Only PC \
+                     images are supported.",
+                )
+                .arg(arg!(<OUT_DIR> "Directory to write the images to"))
+                .arg(
+                    arg!(--format <FORMAT> "Format to write the images in")
+                        .value_parser(["dds", "png"])
+                        .default_value("dds"),
+                )
+                .arg(
+                    arg!(--filter <PATTERN> "Only write images whose names match PATTERN, \
+                         where * matches any number of characters and ? matches any one")
+                    .default_value("*"),
+                ),
+        )
+        .subcommand(
+            Command::new("extract-models")
+                .about(
+                    "Writes the most detailed LOD of every model in the Fastfile whose \
+                     name matches PATTERN to OUT_DIR/models, in its bind pose",
+                )
+                .arg(
+                    arg!(--all_lods "Writes every LOD of each model, one node per LOD")
+                        .required(false),
+                )
+                .arg(arg!(<OUT_DIR> "Directory to write the models to"))
+                .arg(
+                    arg!(--format <FORMAT> "Format to write the models in")
+                        .value_parser(["gltf", "obj"])
+                        .default_value("gltf"),
+                )
+                .arg(convention_arg())
+                .arg(
+                    arg!(--with_textures "Also writes the models' color maps to \
+                         OUT_DIR/textures as PNGs (PC only)")
+                    .required(false),
+                )
+                .arg(
+                    arg!(--filter <PATTERN> "Only write models whose names match PATTERN, \
+                         where * matches any number of characters and ? matches any one")
+                    .default_value("*"),
+                ),
+        )
+        .subcommand(
+            Command::new("weapon-deps")
+                .about(
+                    "Prints the animations, models, materials, effects, and sound \
+                     aliases of every weapon in the Fastfile whose name matches \
+                     PATTERN, as zone source lines",
+                )
+                .arg(
+                    arg!(--filter <PATTERN> "Only print weapons whose names match PATTERN, \
+                         where * matches any number of characters and ? matches any one")
+                    .default_value("*"),
+                ),
+        )
+        .subcommand(
+            Command::new("extract-map")
+                .about(
+                    "Writes the map in the Fastfile to OUT_DIR as one glTF scene, with \
+                     its render geometry and every static model placed in it",
+                )
+                .arg(arg!(<OUT_DIR> "Directory to write the map to"))
+                .arg(
+                    arg!(--with_collision "Also writes the map's collision brushes and \
+                         terrain")
+                    .required(false),
+                )
+                .arg(
+                    arg!(--with_paths "Also writes the map's AI path-node graph as \
+                         GraphViz DOT and JSON")
+                    .required(false),
+                )
+                .arg(convention_arg())
+                .arg(
+                    arg!(--with_textures "Also writes the map's color maps to \
+                         OUT_DIR/textures as PNGs (PC only)")
+                    .required(false),
+                ),
+        );
+
+    #[cfg(feature = "trace")]
+    let command = command.arg(
+        arg!(
+            --trace <FILE>
+            "Writes the offset and length of every value and field read from the \
+             inflated payload to FILE, as JSON."
+        )
+        .required(false),
+    );
+
+    #[cfg(feature = "serializer")]
+    let command = command.subcommand(
+        Command::new("rewrite")
+            .about(
+                "Deserializes every asset in the Fastfile and serializes them into a new \
+                 Fastfile at OUT, keeping the original's pointer markers and script \
+                 string order",
+            )
+            .arg(arg!(<OUT> "Path of the new Fastfile"))
+            .arg(
+                arg!(
+                    --deterministic
+                    "Writes the canonical form of the assets instead, which only \
+                     depends on the assets themselves, for reproducible builds and \
+                     diffing"
+                )
+                .required(false),
+            ),
+    );
+
+    #[cfg(feature = "sqlite")]
+    let command = command.subcommand(
+        Command::new("index")
+            .about(
+                "Adds every asset in the Fastfile to the SQLite asset index at DB \
+                 (creating it if it doesn't exist), as the zone named after the \
+                 Fastfile",
+            )
+            .arg(arg!(<DB> "Path of the asset index")),
+    );
+
+    let matches = command.get_matches();
+
+    if matches.subcommand_matches("capabilities").is_some() {
+        print_capabilities();
+        return;
+    }
 
     let Some(filename) = matches.get_one::<String>("FILENAME") else {
         println!(
-            "must specify a file to operate on (should have .ff or .{CACHE_FILE_EXT} extension)"
+            "must specify a file to operate on \
+             (should have .ff, .{CACHE_FILE_EXTENSION}, or .{ARCHIVE_FILE_EXTENSION} extension)"
         );
         return;
     };
 
-    let platform = if let Some(p) = matches.get_one::<String>("platform") {
-        let p = p.as_str();
-        match p {
-            "windows" => XFilePlatform::Windows,
-            "macos" => XFilePlatform::macOS,
-            "xbox360" => XFilePlatform::Xbox360,
-            "ps3" => XFilePlatform::PS3,
-            _ => {
-                println!("invalid platform (see --help for a list of valid platforms)");
-                return;
+    let game_dir = matches.get_one::<String>("game_dir").map(GameDir::new);
+    let filename = match &game_dir {
+        Some(game_dir) if !Path::new(filename).is_file() => {
+            match game_dir.find_zone(filename).unwrap() {
+                Some(path) => path,
+                None => {
+                    println!(
+                        "{filename} isn't a file or a zone in {}",
+                        game_dir.root().display()
+                    );
+                    return;
+                }
             }
         }
-    } else {
+        _ => PathBuf::from(filename),
+    };
+
+    let cache_command = matches.subcommand_matches("cache");
+    match cache_command.and_then(|m| m.subcommand_name()) {
+        Some("clear") => {
+            if clear_cache(&filename).unwrap() {
+                println!("Removed {}", cache_path_for(&filename).display());
+            } else {
+                println!("{} has no cache", filename.display());
+            }
+            return;
+        }
+        Some("info") => {
+            print_cache_info(&filename);
+            return;
+        }
+        _ => (),
+    }
+
+    let Some(&platform) = matches.get_one::<XFilePlatform>("platform") else {
         println!(
             "must specify the expected platform for the Fastfile \
              (-p/--platform, see --help for a list of valid platforms)"
@@ -55,15 +297,6 @@ fn main() {
         return;
     };
 
-    let cached_filename = Path::new(&filename).with_extension(CACHE_FILE_EXT);
-    let cache_exists = cached_filename.exists();
-
-    let mut file = if cache_exists {
-        std::fs::File::open(&cached_filename).unwrap()
-    } else {
-        std::fs::File::open(filename).unwrap()
-    };
-
     let allow_unsupported_platforms =
         if let Some(a) = matches.get_one::<bool>("allow_unsupported_platforms") {
             *a
@@ -71,31 +304,734 @@ fn main() {
             false
         };
 
-    let de = if cache_exists {
-        T5XFileDeserializerBuilder::from_cache_file(
-            &mut file,
-            platform,
-            allow_unsupported_platforms,
-        )
-    } else {
-        T5XFileDeserializerBuilder::from_file(&mut file, platform, allow_unsupported_platforms)
+    if let Some(m) = cache_command.and_then(|m| m.subcommand_matches("build")) {
+        let force = m.get_flag("force");
+        match build_cache(&filename, platform, allow_unsupported_platforms, force).unwrap() {
+            Some(_) => println!("Wrote {}", cache_path_for(&filename).display()),
+            None => println!("{} is up to date", cache_path_for(&filename).display()),
+        }
+        return;
     }
-    .with_silent(false);
 
-    #[cfg(feature = "d3d9")]
-    let de = de.with_d3d9(None);
+    let options = T5XFileOpenOptions::new(platform)
+        .with_allow_unsupported_platforms(allow_unsupported_platforms)
+        .with_silent(false);
 
-    let de = de.build().unwrap().inflate().unwrap();
+    let options = if let Some(&max_local_clients) = matches.get_one::<usize>("max_local_clients") {
+        options.with_max_local_clients(max_local_clients)
+    } else {
+        options
+    };
 
-    let de = if !cache_exists {
-        de.cache(cached_filename).unwrap().0
+    let options = if let Some(game_dir) = &game_dir {
+        options.with_game_dir(game_dir.root())
     } else {
-        de.no_cache().unwrap()
+        options
     };
 
-    let assets = de.deserialize_remaining().unwrap();
-    for (i, asset) in assets.into_iter().enumerate() {
+    #[cfg(feature = "trace")]
+    let trace_filename = matches.get_one::<String>("trace");
+    #[cfg(feature = "trace")]
+    let options = options.with_trace(trace_filename.is_some());
+
+    if let Some(out) = matches
+        .subcommand_matches("archive")
+        .and_then(|m| m.get_one::<String>("OUT"))
+    {
+        // archiving always starts from the Fastfile, or from another archive
+        let mut file = std::fs::File::open(&filename).unwrap();
+        let de = if filename.extension() == Some(ARCHIVE_FILE_EXTENSION.as_ref()) {
+            T5XFileDeserializerBuilder::from_cache_file(
+                &mut file,
+                platform,
+                allow_unsupported_platforms,
+            )
+        } else {
+            T5XFileDeserializerBuilder::from_file(&mut file, platform, allow_unsupported_platforms)
+        };
+        de.build().unwrap().inflate().unwrap().archive(out).unwrap();
+        return;
+    }
+
+    #[cfg(feature = "serializer")]
+    if let Some(m) = matches.subcommand_matches("rewrite") {
+        let out = m.get_one::<String>("OUT").unwrap();
+        let mut file = std::fs::File::open(&filename).unwrap();
+        let de =
+            T5XFileDeserializerBuilder::from_file(&mut file, platform, allow_unsupported_platforms);
+        let bytes = rewrite(de, platform, m.get_flag("deterministic")).unwrap();
+        std::fs::write(out, bytes).unwrap();
+        println!("Wrote {out}");
+        return;
+    }
+
+    #[allow(unused_mut)]
+    let mut de = T5XFileDeserializer::open(&filename, options).unwrap();
+
+    if matches.subcommand_matches("list").is_some() {
+        for (asset_type, name) in de.scan_names().unwrap() {
+            println!("{asset_type:?} {}", name.as_deref().unwrap_or("(null)"));
+        }
+        return;
+    }
+
+    let stats = matches.subcommand_matches("stats").is_some();
+    #[cfg(feature = "sqlite")]
+    let index_path = matches
+        .subcommand_matches("index")
+        .and_then(|m| m.get_one::<String>("DB"));
+    #[cfg(not(feature = "sqlite"))]
+    let index_path: Option<&String> = None;
+    let mut bag = XAssetBag::new();
+
+    let sounds_dir = matches
+        .subcommand_matches("extract-sounds")
+        .and_then(|m| m.get_one::<String>("OUT_DIR"))
+        .map(PathBuf::from);
+    let mut extracted = ExtractedSounds::default();
+
+    let images = matches
+        .subcommand_matches("extract-images")
+        .map(|m| ExtractImages {
+            dir: PathBuf::from(m.get_one::<String>("OUT_DIR").unwrap()),
+            png: m.get_one::<String>("format").unwrap() == "png",
+            filter: m.get_one::<String>("filter").unwrap().clone(),
+            seen: BTreeSet::new(),
+            written: 0,
+            unsupported: 0,
+        });
+    let mut images = match images {
+        Some(_) if !matches!(platform, XFilePlatform::Windows | XFilePlatform::macOS) => {
+            println!("extract-images only supports PC Fastfiles");
+            return;
+        }
+        images => images,
+    };
+
+    let mut models = matches
+        .subcommand_matches("extract-models")
+        .map(|m| ExtractModels {
+            dir: PathBuf::from(m.get_one::<String>("OUT_DIR").unwrap()),
+            gltf: m.get_one::<String>("format").unwrap() == "gltf",
+            all_lods: m.get_flag("all_lods"),
+            textures: m.get_flag("with_textures").then(|| ExtractImages {
+                dir: PathBuf::from(m.get_one::<String>("OUT_DIR").unwrap()).join("textures"),
+                png: true,
+                filter: String::from("*"),
+                seen: BTreeSet::new(),
+                written: 0,
+                unsupported: 0,
+            }),
+            filter: m.get_one::<String>("filter").unwrap().clone(),
+            convention: convention(m),
+            written: 0,
+        });
+    if models.as_ref().is_some_and(|m| m.textures.is_some())
+        && !matches!(platform, XFilePlatform::Windows | XFilePlatform::macOS)
+    {
+        println!("--with_textures only supports PC Fastfiles");
+        return;
+    }
+
+    let mut map = matches
+        .subcommand_matches("extract-map")
+        .map(|m| ExtractMap {
+            dir: PathBuf::from(m.get_one::<String>("OUT_DIR").unwrap()),
+            scene: Scene::new().with_convention(convention(m)),
+            name: None,
+            collision: m.get_flag("with_collision"),
+            paths: m.get_flag("with_paths"),
+            path_graph: None,
+            textures: m.get_flag("with_textures").then(|| ExtractImages {
+                dir: PathBuf::from(m.get_one::<String>("OUT_DIR").unwrap()).join("textures"),
+                png: true,
+                filter: String::from("*"),
+                seen: BTreeSet::new(),
+                written: 0,
+                unsupported: 0,
+            }),
+        });
+    if map.as_ref().is_some_and(|m| m.textures.is_some())
+        && !matches!(platform, XFilePlatform::Windows | XFilePlatform::macOS)
+    {
+        println!("--with_textures only supports PC Fastfiles");
+        return;
+    }
+
+    let weapon_filter = matches
+        .subcommand_matches("weapon-deps")
+        .map(|m| m.get_one::<String>("filter").unwrap().clone());
+    let mut weapon_deps = Vec::new();
+
+    let mut i = 0;
+    while let Some(mut asset) = de.deserialize_next().unwrap() {
         println!("Found asset '{}' ({})", asset.name().unwrap_or_default(), i);
+        i += 1;
+        if let (Some(dir), Some(bank)) = (&sounds_dir, asset.snd_bank_mut()) {
+            extract_sounds(dir, bank, &mut extracted);
+        }
+        if let Some(images) = images.as_mut() {
+            extract_images(&asset, images);
+        }
+        if let Some(models) = models.as_mut() {
+            extract_model(&asset, models);
+        }
+        if let Some(map) = map.as_mut() {
+            extract_map(&asset, map);
+        }
+        if let Some(filter) = &weapon_filter {
+            weapon_deps.extend(weapon_zone_source(&asset, filter));
+        }
+        if stats || index_path.is_some() {
+            bag.insert(asset);
+        }
+    }
+
+    for source in &weapon_deps {
+        println!("\n{source}");
+    }
+
+    if stats {
+        print_stats(&bag.stats());
+    }
+
+    #[cfg(feature = "sqlite")]
+    if let Some(index_path) = index_path {
+        let zone = filename.file_stem().unwrap_or_default();
+        t5_xfile_deserializer::sqlite::AssetIndex::open(index_path)
+            .unwrap()
+            .insert_zone(&zone.to_string_lossy(), &bag)
+            .unwrap();
+        println!("Indexed {} assets in {index_path}", bag.len());
+    }
+
+    if let Some(dir) = &sounds_dir {
+        println!(
+            "Wrote {} sounds to {} ({} streamed sounds weren't in any sound bank)",
+            extracted.written,
+            dir.display(),
+            extracted.missing
+        );
+    }
+
+    if let Some(images) = &images {
+        println!(
+            "Wrote {} images to {} ({} were in unsupported formats)",
+            images.written,
+            images.dir.display(),
+            images.unsupported
+        );
+    }
+
+    if let Some(models) = &models {
+        println!(
+            "Wrote {} models to {}",
+            models.written,
+            models.dir.join("models").display()
+        );
+        if let Some(textures) = &models.textures {
+            println!(
+                "Wrote {} textures to {} ({} were in unsupported formats)",
+                textures.written,
+                textures.dir.display(),
+                textures.unsupported
+            );
+        }
+    }
+
+    if let Some(map) = &map {
+        write_map(map);
+    }
+
+    #[cfg(feature = "trace")]
+    if let (Some(filename), Some(trace)) = (trace_filename, de.trace()) {
+        std::fs::write(filename, trace.to_json()).unwrap();
+        println!("Wrote trace to {filename}");
     }
     //dbg!(assets);
 }
+
+/// Serializes every asset `de` deserializes into a new Fastfile, with the original's pointer markers and script strings unless
+/// `deterministic` is set (see
+/// [`T5XFileSerializerBuilder::with_deterministic`]).
+#[cfg(feature = "serializer")]
+fn rewrite(
+    de: T5XFileDeserializerBuilder,
+    platform: XFilePlatform,
+    deterministic: bool,
+) -> t5_xfile_defs::Result<Vec<u8>> {
+    use t5_xfile_defs::T5XFileDeserialize;
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    let mut de = de
+        .with_record_ptr_markers(!deterministic)
+        .build()?
+        .inflate()?
+        .no_cache()?;
+    let mut assets = Vec::new();
+    while let Some(asset) = de.deserialize_next()? {
+        assets.push(asset);
+    }
+
+    let ser = T5XFileSerializerBuilder::new(platform).with_deterministic(deterministic);
+    let ser = if deterministic {
+        ser
+    } else {
+        ser.with_script_strings(de.script_strings()?.into_iter().map(str::to_string))
+            .with_ptr_markers(de.ptr_markers().to_vec())
+    };
+    let mut ser = ser.build()?;
+    ser.serialize_assets(assets.into_iter())?;
+    ser.deflate()
+}
+
+#[derive(Default)]
+struct ExtractedSounds {
+    written: usize,
+    missing: usize,
+}
+
+/// Joins `name` (which uses either kind of slash) onto `dir`, leaving out
+/// any components that would escape `dir`.
+fn output_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let name = name.replace('\\', "/");
+    let mut path = dir.to_path_buf();
+    path.extend(
+        Path::new(&name)
+            .components()
+            .filter(|c| matches!(c, Component::Normal(_))),
+    );
+    path.set_extension(extension);
+    path
+}
+
+fn extract_sounds(dir: &Path, bank: &SndBank, extracted: &mut ExtractedSounds) {
+    let files = bank
+        .aliases
+        .iter()
+        .flat_map(|list| &list.aliases)
+        .filter_map(|alias| alias.sound_file.as_deref());
+
+    for file in files {
+        let loaded = match &file.u {
+            SoundFileRef::Loaded(Some(loaded)) => loaded,
+            SoundFileRef::Streamed(Some(_)) => {
+                extracted.missing += 1;
+                continue;
+            }
+            _ => continue,
+        };
+
+        let sound = &loaded.sound;
+        let path = output_path(dir, loaded.name.get(), sound.format.extension());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        let bytes = sound.to_wav().unwrap_or_else(|| sound.data.clone());
+        std::fs::write(&path, bytes).unwrap();
+        extracted.written += 1;
+    }
+}
+
+fn convention_arg() -> clap::Arg {
+    arg!(--convention <CONVENTION> "Coordinate system to write in: y-up-meters \
+         (as glTF expects), the game's own Z-up inches, or unreal (Z-up, left-handed, \
+         centimeters)")
+    .value_parser(["y-up-meters", "native", "unreal"])
+    .default_value("y-up-meters")
+}
+
+fn convention(matches: &clap::ArgMatches) -> CoordinateConvention {
+    match matches.get_one::<String>("convention").unwrap().as_str() {
+        "native" => CoordinateConvention::NATIVE,
+        "unreal" => CoordinateConvention::UNREAL,
+        _ => CoordinateConvention::Y_UP_METERS,
+    }
+}
+
+struct ExtractImages {
+    dir: PathBuf,
+    png: bool,
+    filter: String,
+    /// Materials often share images, so each one is only looked at once.
+    seen: BTreeSet<String>,
+    written: usize,
+    unsupported: usize,
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any number of
+/// characters and `?` matches any one.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.as_bytes(), name.as_bytes());
+    let (mut p, mut n) = (0, 0);
+    // where the last `*` was, and how much of `name` it's matched so far
+    let mut star = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    p = sp + 1;
+                    n = sn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+fn extract_images(asset: &XAsset, extract: &mut ExtractImages) {
+    let mut images = asset
+        .as_has_images()
+        .map(|a| a.images())
+        .unwrap_or_default();
+    if let XAsset::PC(XAssetGeneric::Image(Some(image))) = asset {
+        images.push(image);
+    }
+
+    write_images(&images, extract);
+}
+
+/// Writes `images` out with [`export::file_name`] names, so exported models
+/// can refer to them.
+fn write_images(images: &[&GfxImage], extract: &mut ExtractImages) {
+    for image in images {
+        let name = image.name.get();
+        if !matches_pattern(&extract.filter, name) || !extract.seen.insert(name.to_string()) {
+            continue;
+        }
+
+        let (bytes, extension) = if extract.png {
+            (png::from_image(image), "png")
+        } else {
+            (image.to_dds(), "dds")
+        };
+        let Some(bytes) = bytes else {
+            extract.unsupported += 1;
+            continue;
+        };
+
+        std::fs::create_dir_all(&extract.dir).unwrap();
+        let path = extract
+            .dir
+            .join(format!("{}.{extension}", export::file_name(name)));
+        std::fs::write(&path, bytes).unwrap();
+        extract.written += 1;
+    }
+}
+
+struct ExtractModels {
+    dir: PathBuf,
+    gltf: bool,
+    all_lods: bool,
+    textures: Option<ExtractImages>,
+    filter: String,
+    convention: CoordinateConvention,
+    written: usize,
+}
+
+fn extract_model(asset: &XAsset, extract: &mut ExtractModels) {
+    let model = match asset {
+        XAsset::PC(XAssetGeneric::XModel(Some(model)))
+        | XAsset::Console(XAssetGeneric::XModel(Some(model))) => model,
+        _ => return,
+    };
+    if !matches_pattern(&extract.filter, model.name.get()) {
+        return;
+    }
+
+    let scene = if extract.all_lods {
+        Scene::from_xmodel_lods(model)
+    } else {
+        Scene::from_xmodel(model)
+    }
+    .with_convention(extract.convention);
+    let dir = extract.dir.join("models");
+    std::fs::create_dir_all(&dir).unwrap();
+    let name = export::file_name(model.name.get());
+    if extract.gltf {
+        let bin_name = format!("{name}.bin");
+        let (json, bin) = scene.to_gltf(&bin_name, "../textures");
+        std::fs::write(dir.join(format!("{name}.gltf")), json).unwrap();
+        std::fs::write(dir.join(bin_name), bin).unwrap();
+    } else {
+        let mtl_name = format!("{name}.mtl");
+        let (obj, mtl) = scene.to_obj(&mtl_name, "../textures");
+        std::fs::write(dir.join(format!("{name}.obj")), obj).unwrap();
+        std::fs::write(dir.join(mtl_name), mtl).unwrap();
+    }
+    extract.written += 1;
+
+    if let Some(textures) = extract.textures.as_mut() {
+        let images = model
+            .material_handles
+            .iter()
+            .flat_map(|m| m.images())
+            .filter(|image| {
+                scene
+                    .materials
+                    .iter()
+                    .any(|m| m.color_map.as_deref() == Some(image.name.get()))
+            })
+            .collect::<Vec<_>>();
+        write_images(&images, textures);
+    }
+}
+
+fn weapon_zone_source(asset: &XAsset, filter: &str) -> Option<String> {
+    let weapon = match asset {
+        XAsset::PC(XAssetGeneric::Weapon(Some(weapon)))
+        | XAsset::Console(XAssetGeneric::Weapon(Some(weapon))) => weapon,
+        _ => return None,
+    };
+    if !matches_pattern(filter, weapon.internal_name.get()) {
+        return None;
+    }
+
+    Some(format!(
+        "// {}\n{}",
+        weapon.internal_name.get(),
+        weapon.dependencies().to_zone_source()
+    ))
+}
+
+struct ExtractMap {
+    dir: PathBuf,
+    /// Built up as the world and its collision are found, and written once
+    /// every asset has been.
+    scene: Scene,
+    /// The name of the world, once it's been found.
+    name: Option<String>,
+    collision: bool,
+    paths: bool,
+    /// The map's path-node graph as DOT and JSON, once it's been found.
+    path_graph: Option<(String, String)>,
+    textures: Option<ExtractImages>,
+}
+
+fn extract_map(asset: &XAsset, extract: &mut ExtractMap) {
+    match asset {
+        XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => extract_gfx_world(world, extract),
+        XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => extract_gfx_world(world, extract),
+        XAsset::PC(
+            XAssetGeneric::ClipMap(Some(clip_map)) | XAssetGeneric::ClipMapPVS(Some(clip_map)),
+        )
+        | XAsset::Console(
+            XAssetGeneric::ClipMap(Some(clip_map)) | XAssetGeneric::ClipMapPVS(Some(clip_map)),
+        ) if extract.collision => extract.scene.add_clip_map(clip_map),
+        XAsset::PC(XAssetGeneric::GameWorldSp(Some(world)))
+        | XAsset::Console(XAssetGeneric::GameWorldSp(Some(world)))
+            if extract.paths =>
+        {
+            extract_path_graph(&world.path, extract)
+        }
+        XAsset::PC(XAssetGeneric::GameWorldMp(Some(world)))
+        | XAsset::Console(XAssetGeneric::GameWorldMp(Some(world)))
+            if extract.paths =>
+        {
+            extract_path_graph(&world.path, extract)
+        }
+        _ => {}
+    }
+}
+
+fn extract_path_graph(path: &PathData, extract: &mut ExtractMap) {
+    let graph = path.graph();
+    extract.path_graph = Some((
+        export::path_graph_to_dot(&graph),
+        export::path_graph_to_json(&graph),
+    ));
+}
+
+fn extract_gfx_world<const MAX_LOCAL_CLIENTS: usize>(
+    world: &GfxWorld<MAX_LOCAL_CLIENTS>,
+    extract: &mut ExtractMap,
+) {
+    extract.scene.add_gfx_world(world);
+    extract.name = Some(world.name.get().to_string());
+
+    if let Some(textures) = extract.textures.as_mut() {
+        let surfaces = world
+            .dpvs
+            .surfaces
+            .iter()
+            .filter_map(|s| s.material.as_deref());
+        let models = world
+            .dpvs
+            .smodel_draw_insts
+            .iter()
+            .filter_map(|inst| inst.model.as_deref())
+            .flat_map(|model| model.material_handles.iter().map(AsRef::as_ref));
+        let images = surfaces
+            .chain(models)
+            .flat_map(|m| m.images())
+            .filter(|image| {
+                extract
+                    .scene
+                    .materials
+                    .iter()
+                    .any(|m| m.color_map.as_deref() == Some(image.name.get()))
+            })
+            .collect::<Vec<_>>();
+        write_images(&images, textures);
+    }
+}
+
+fn write_map(extract: &ExtractMap) {
+    let Some(name) = &extract.name else {
+        println!("No map found in the Fastfile");
+        return;
+    };
+
+    std::fs::create_dir_all(&extract.dir).unwrap();
+    let name = export::file_name(name);
+    let bin_name = format!("{name}.bin");
+    let (json, bin) = extract.scene.to_gltf(&bin_name, "textures");
+    std::fs::write(extract.dir.join(format!("{name}.gltf")), json).unwrap();
+    std::fs::write(extract.dir.join(bin_name), bin).unwrap();
+    println!(
+        "Wrote {name}.gltf to {} ({} meshes, {} nodes)",
+        extract.dir.display(),
+        extract.scene.meshes.len(),
+        extract.scene.nodes.len()
+    );
+    if let Some(textures) = &extract.textures {
+        println!(
+            "Wrote {} textures to {} ({} were in unsupported formats)",
+            textures.written,
+            textures.dir.display(),
+            textures.unsupported
+        );
+    }
+    if extract.paths {
+        match &extract.path_graph {
+            Some((dot, json)) => {
+                std::fs::write(extract.dir.join(format!("{name}.paths.dot")), dot).unwrap();
+                std::fs::write(extract.dir.join(format!("{name}.paths.json")), json).unwrap();
+                println!(
+                    "Wrote {name}.paths.dot and {name}.paths.json to {}",
+                    extract.dir.display()
+                );
+            }
+            None => println!("No path nodes found in the Fastfile"),
+        }
+    }
+}
+
+fn print_stats(stats: &XAssetStats) {
+    println!();
+    println!("{:<20}{:>8}{:>16}", "ASSET TYPE", "COUNT", "EST. SIZE");
+    for (asset_type, type_stats) in &stats.types {
+        println!(
+            "{:<20}{:>8}{:>16}",
+            format!("{asset_type:?}"),
+            type_stats.count,
+            type_stats.estimated_size
+        );
+    }
+    println!(
+        "{:<20}{:>8}{:>16}",
+        "TOTAL",
+        stats.total_count(),
+        stats.total_estimated_size()
+    );
+
+    println!();
+    println!(
+        "{:<20}{:<48}{:>16}",
+        "ASSET TYPE", "LARGEST ASSETS", "EST. SIZE"
+    );
+    for asset in &stats.largest {
+        println!(
+            "{:<20}{:<48}{:>16}",
+            format!("{:?}", asset.asset_type),
+            asset.name,
+            asset.estimated_size
+        );
+    }
+
+    println!();
+    println!("{:<20}{:>24}", "vertices", stats.vertex_count);
+    println!("{:<20}{:>24}", "triangles", stats.triangle_count);
+    println!("{:<20}{:>24}", "sound samples", stats.sound_sample_count);
+}
+
+/// Prints [`cache_info`] for `filename` if it's a cache or archive, or for
+/// its cache otherwise, and compares it with the Fastfile if it's around.
+fn print_cache_info(filename: &Path) {
+    let is_cache = filename
+        .extension()
+        .is_some_and(|ext| ext == CACHE_FILE_EXTENSION || ext == ARCHIVE_FILE_EXTENSION);
+    let cache_path = if is_cache {
+        filename.to_path_buf()
+    } else {
+        cache_path_for(filename)
+    };
+    if !cache_path.exists() {
+        println!("{} doesn't exist", cache_path.display());
+        return;
+    }
+
+    let info = match cache_info(&cache_path) {
+        Ok(info) => info,
+        Err(e) => {
+            println!("{} is invalid: {e}", cache_path.display());
+            return;
+        }
+    };
+
+    let kind = if info.archive { "archive" } else { "cache" };
+    println!("{} ({kind})", cache_path.display());
+    match info.platform {
+        Some(platform) => println!("  platform: {platform}"),
+        None => println!("  platform: unknown"),
+    }
+    println!("  payload: {} bytes", info.payload_len);
+    println!("  assets: {}", info.assets);
+    println!("  script strings: {}", info.script_strings);
+    println!(
+        "  source: {} bytes, hash {:016x}",
+        info.source.len, info.source.hash
+    );
+
+    let source_path = cache_path.with_extension("ff");
+    if info.archive {
+        return;
+    }
+    match std::fs::File::open(&source_path).map(|f| CacheSource::from_file(&f)) {
+        Ok(Ok(source)) if source.matches(&info.source) => {
+            println!("  up to date with {}", source_path.display())
+        }
+        Ok(Ok(_)) => println!("  stale, {} has changed", source_path.display()),
+        Ok(Err(e)) => println!("  couldn't read {}: {e}", source_path.display()),
+        Err(_) => println!("  {} doesn't exist", source_path.display()),
+    }
+}
+
+fn print_capabilities() {
+    println!("{:<20}{:<14}SERIALIZE", "ASSET TYPE", "DESERIALIZE");
+    for asset_type in XAssetType::ALL {
+        let status = asset_type.support_status();
+        println!(
+            "{:<20}{:<14}{}",
+            format!("{asset_type:?}"),
+            if status.can_deserialize() {
+                "yes"
+            } else {
+                "no"
+            },
+            if status.can_serialize() { "yes" } else { "no" },
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Walkable navigation meshes, built from a map's collision, for bots and
+//! other server-side pathfinding.
+//!
+//! Rather than voxelizing the world the way Recast does, the navmesh is
+//! extracted directly from the [`ClipMap`]: every face of a solid brush
+//! (see [`CBrush::polygons`]) and every terrain triangle that faces up and
+//! is flat enough to walk on becomes a polygon of the navmesh, unless
+//! something solid is less than [`NavMeshOptions::agent_height`] above it.
+//! Faces aren't merged or split, so a face that's only partly covered is
+//! kept whole.
+//!
+//! The result can be written as Wavefront OBJ ([`NavMesh::to_obj`]) or in a
+//! simple tiled format ([`NavMesh::to_tiles`]).
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use t5_xfile_defs::{
+    clipmap::{CBrush, ClipMap},
+    common::Vec3,
+};
+
+/// Contents of brushes and terrain that block everything.
+pub const CONTENTS_SOLID: i32 = 0x0000_0001;
+/// Contents of brushes that only block players.
+pub const CONTENTS_PLAYERCLIP: i32 = 0x0001_0000;
+
+/// The magic at the start of [`NavMesh::to_tiles`]'s output.
+pub const TILES_MAGIC: [u8; 4] = *b"T5NM";
+/// The version of [`NavMesh::to_tiles`]'s output.
+pub const TILES_VERSION: u32 = 1;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NavMeshOptions {
+    /// The steepest slope (in degrees) that can be walked on.
+    pub max_slope: f32,
+    /// How much room there has to be above a surface to walk on it.
+    pub agent_height: f32,
+    /// The contents of the brushes and terrain that can be walked on and
+    /// that block headroom.
+    pub contents_mask: i32,
+}
+
+impl Default for NavMeshOptions {
+    /// The engine's limits for a standing player: surfaces whose normal is
+    /// at least 0.7 up, with 70 units of headroom.
+    fn default() -> Self {
+        Self {
+            max_slope: 45.57,
+            agent_height: 70.0,
+            contents_mask: CONTENTS_SOLID | CONTENTS_PLAYERCLIP,
+        }
+    }
+}
+
+impl NavMeshOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_slope(mut self, max_slope: f32) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    pub fn with_agent_height(mut self, agent_height: f32) -> Self {
+        self.agent_height = agent_height;
+        self
+    }
+
+    pub fn with_contents_mask(mut self, contents_mask: i32) -> Self {
+        self.contents_mask = contents_mask;
+        self
+    }
+}
+
+/// Convex polygons that can be walked on, wound counterclockwise seen from
+/// above, in the game's coordinates.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NavMesh {
+    pub vertices: Vec<[f32; 3]>,
+    /// Indices into [`Self::vertices`]. Vertices are snapped to 1/64 of a
+    /// unit, so that polygons that touch share the vertices they have in
+    /// common.
+    pub polygons: Vec<Vec<u32>>,
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// The z of `polygon`'s unit normal, by Newell's method.
+fn normal_z(polygon: &[[f32; 3]]) -> f32 {
+    let normal =
+        polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .fold([0.0; 3], |n, (&a, &b)| {
+                let c = cross(a, b);
+                [n[0] + c[0], n[1] + c[1], n[2] + c[2]]
+            });
+    let len = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len == 0.0 { 0.0 } else { normal[2] / len }
+}
+
+impl NavMesh {
+    /// Builds the navmesh of the world's brushes and terrain. Brush models
+    /// ([`ClipMap::cmodels`] other than the world) and static models are
+    /// left out, like they are by [`ClipMap::trace_ray`].
+    ///
+    /// Terrain triangles are solid on both sides, so a triangle facing down
+    /// is walkable from above.
+    pub fn from_clip_map(clip_map: &ClipMap, options: &NavMeshOptions) -> Self {
+        let brushes = clip_map
+            .brushes
+            .iter()
+            .filter(|b| b.contents & options.contents_mask != 0)
+            .flat_map(CBrush::polygons);
+
+        // trees can share partitions, so triangles can come up more than once
+        let mut visited = vec![false; clip_map.tri_indices.len() / 3];
+        let mut triangles = Vec::new();
+        let partitions = clip_map
+            .aabb_trees
+            .iter()
+            .filter(|tree| tree.child_count == 0)
+            .filter(|tree| {
+                clip_map
+                    .materials
+                    .get(tree.material_index)
+                    .is_some_and(|m| m.content_flags & options.contents_mask != 0)
+            })
+            .filter_map(|tree| clip_map.partitions.get(tree.index));
+        for partition in partitions {
+            let first = partition.first_tri.max(0) as usize;
+            for tri in first..first + partition.tri_count as usize {
+                let Some(visited) = visited.get_mut(tri) else {
+                    continue;
+                };
+                if core::mem::replace(visited, true) {
+                    continue;
+                }
+
+                let Some(mut triangle) = clip_map.tri_indices[tri * 3..tri * 3 + 3]
+                    .iter()
+                    .map(|&i| clip_map.verts.get(i as usize).map(|v| v.get()))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+                if normal_z(&triangle) < 0.0 {
+                    triangle.reverse();
+                }
+                triangles.push(triangle);
+            }
+        }
+
+        Self::from_polygons(brushes.chain(triangles), options, |start, end| {
+            clip_map
+                .trace_ray(start.into(), end.into(), options.contents_mask)
+                .is_some()
+        })
+    }
+
+    /// Builds the navmesh of `brushes` alone, e.g. those of a single brush
+    /// model.
+    pub fn from_brushes(brushes: &[CBrush], options: &NavMeshOptions) -> Self {
+        let brushes = brushes
+            .iter()
+            .filter(|b| b.contents & options.contents_mask != 0)
+            .collect::<Vec<_>>();
+
+        Self::from_polygons(
+            brushes.iter().flat_map(|b| b.polygons()),
+            options,
+            |start, end| {
+                let (start, end) = (Vec3::from(start), Vec3::from(end));
+                brushes.iter().any(|b| b.trace(start, end).is_some())
+            },
+        )
+    }
+
+    /// Keeps the polygons that are walkable, where `blocked` tells whether
+    /// something is in the way of a segment.
+    fn from_polygons(
+        polygons: impl Iterator<Item = Vec<[f32; 3]>>,
+        options: &NavMeshOptions,
+        blocked: impl Fn([f32; 3], [f32; 3]) -> bool,
+    ) -> Self {
+        // just clear of the surface, so it doesn't block itself
+        const NUDGE: f32 = 0.125;
+        const SNAP: f32 = 64.0;
+
+        let min_normal_z = options.max_slope.to_radians().cos();
+        let mut navmesh = Self::default();
+        let mut vertex_indices = HashMap::new();
+
+        for polygon in polygons {
+            if polygon.len() < 3 || normal_z(&polygon) < min_normal_z {
+                continue;
+            }
+
+            let n = polygon.len() as f32;
+            let center = [0, 1, 2].map(|i| polygon.iter().map(|p| p[i]).sum::<f32>() / n);
+            let start = [center[0], center[1], center[2] + NUDGE];
+            let end = [center[0], center[1], center[2] + options.agent_height];
+            if blocked(start, end) {
+                continue;
+            }
+
+            let mut indices = polygon
+                .iter()
+                // adding 0.0 turns -0.0 into 0.0, which has different bits
+                .map(|p| p.map(|c| (c * SNAP).round() / SNAP + 0.0))
+                .map(|p| {
+                    *vertex_indices
+                        .entry(p.map(f32::to_bits))
+                        .or_insert_with(|| {
+                            navmesh.vertices.push(p);
+                            navmesh.vertices.len() as u32 - 1
+                        })
+                })
+                .collect::<Vec<_>>();
+            // snapping can merge vertices of tiny edges
+            indices.dedup();
+            if indices.len() > 1 && indices.first() == indices.last() {
+                indices.pop();
+            }
+            if indices.len() >= 3 {
+                navmesh.polygons.push(indices);
+            }
+        }
+
+        navmesh
+    }
+
+    /// Groups [`Self::polygons`] (by index) into square tiles `tile_size`
+    /// units wide, keyed by the tile's column and row along X and Y. Each
+    /// polygon goes in the tile its center is in.
+    pub fn tiles(&self, tile_size: f32) -> BTreeMap<(i32, i32), Vec<usize>> {
+        let mut tiles = BTreeMap::<_, Vec<_>>::new();
+        for (i, polygon) in self.polygons.iter().enumerate() {
+            let n = polygon.len() as f32;
+            let [x, y] = [0, 1].map(|axis| {
+                let sum = polygon
+                    .iter()
+                    .map(|&v| self.vertices[v as usize][axis])
+                    .sum::<f32>();
+                (sum / n / tile_size).floor() as i32
+            });
+            tiles.entry((x, y)).or_default().push(i);
+        }
+        tiles
+    }
+
+    /// Writes the navmesh as Wavefront OBJ, with one face per polygon.
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::new();
+        for [x, y, z] in &self.vertices {
+            writeln!(obj, "v {x} {y} {z}").unwrap();
+        }
+        for polygon in &self.polygons {
+            obj.push('f');
+            for &i in polygon {
+                // OBJ indices are 1-based
+                write!(obj, " {}", i + 1).unwrap();
+            }
+            obj.push('\n');
+        }
+        obj
+    }
+
+    /// Writes the navmesh in a simple tiled format, with its polygons
+    /// grouped as by [`Self::tiles`]. All values are little-endian:
+    ///
+    /// - [`TILES_MAGIC`], [`TILES_VERSION`] as a `u32`, and `tile_size` as
+    ///   an `f32`
+    /// - the number of vertices as a `u32`, then each vertex as three `f32`s
+    /// - the number of tiles as a `u32`, then for each tile: its column and
+    ///   row as `i32`s, its number of polygons as a `u32`, then for each
+    ///   polygon its number of vertices as a `u32` followed by their indices
+    ///   as `u32`s
+    pub fn to_tiles(&self, tile_size: f32) -> Vec<u8> {
+        let mut bytes = TILES_MAGIC.to_vec();
+        bytes.extend(TILES_VERSION.to_le_bytes());
+        bytes.extend(tile_size.to_le_bytes());
+
+        bytes.extend((self.vertices.len() as u32).to_le_bytes());
+        for vertex in &self.vertices {
+            bytes.extend(vertex.iter().flat_map(|c| c.to_le_bytes()));
+        }
+
+        let tiles = self.tiles(tile_size);
+        bytes.extend((tiles.len() as u32).to_le_bytes());
+        for ((x, y), polygons) in &tiles {
+            bytes.extend(x.to_le_bytes());
+            bytes.extend(y.to_le_bytes());
+            bytes.extend((polygons.len() as u32).to_le_bytes());
+            for polygon in polygons.iter().map(|&i| &self.polygons[i]) {
+                bytes.extend((polygon.len() as u32).to_le_bytes());
+                bytes.extend(polygon.iter().flat_map(|i| i.to_le_bytes()));
+            }
+        }
+
+        bytes
+    }
+}
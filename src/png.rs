@@ -0,0 +1,61 @@
+//! A minimal PNG encoder, for writing images out (see
+//! [`GfxImage::to_rgba8`]).
+
+use t5_xfile_defs::techset::GfxImage;
+
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |crc, _| {
+            (crc >> 1) ^ (0xEDB88320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Encodes `rgba` (`width * height` 8-bit RGBA pixels, row by row) as a
+/// PNG.
+///
+/// # Panics
+///
+/// Panics if `rgba` isn't exactly `width * height * 4` bytes.
+pub fn encode_rgba8(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let stride = width as usize * 4;
+    assert_eq!(rgba.len(), stride * height as usize);
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, default compression/filtering, no interlacing
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    // every row starts with its filter type (none)
+    let mut scanlines = Vec::with_capacity(rgba.len() + height as usize);
+    for row in rgba.chunks_exact(stride.max(1)) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &deflate::deflate_bytes_zlib(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Decodes the largest loaded mip level of `image` (see
+/// [`GfxImage::to_rgba8`]) and encodes it as a PNG, or returns [`None`] if
+/// it can't be decoded.
+pub fn from_image(image: &GfxImage) -> Option<Vec<u8>> {
+    let (width, height, rgba) = image.to_rgba8()?;
+    Some(encode_rgba8(width, height, &rgba))
+}
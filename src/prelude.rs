@@ -0,0 +1,23 @@
+//! The types most code that reads or writes Fastfiles needs, so it can
+//! `use t5_xfile_deserializer::prelude::*;` instead of reaching into
+//! individual modules of this crate and of [`t5_xfile_defs`], whose layout
+//! is free to change.
+
+pub use t5_xfile_defs::{
+    Error, ErrorKind, Result, XFilePlatform,
+    access::{HasImages, HasMaterials, HasName},
+    common::{Aabb, Mat3, Mat4, Vec2, Vec3, Vec4},
+    visitor::XAssetVisitor,
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetList, XAssetType},
+};
+
+#[cfg(feature = "deserializer")]
+pub use crate::{
+    GameDir, T5XFileDeserializer, T5XFileDeserializerBuilder, T5XFileOpenOptions, ZoneSet,
+};
+
+#[cfg(feature = "cache")]
+pub use crate::CacheMode;
+
+#[cfg(feature = "serializer")]
+pub use crate::{T5XFileSerializer, T5XFileSerializerBuilder};
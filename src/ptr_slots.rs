@@ -0,0 +1,351 @@
+//! Finding the pointers in a value the serializer stores, so the markers of
+//! the inline ones can be set (see
+//! [`T5XFileSerializerBuilder::with_ptr_markers`](crate::T5XFileSerializerBuilder::with_ptr_markers)).
+//!
+//! [`ptr_slots`] walks a value's [`Serialize`] impl the same way bincode's
+//! fixint encoding does, but rather than writing anything, it counts the
+//! bytes that would have been written and notes where each
+//! [`Ptr32`](t5_xfile_defs::Ptr32) starts. It relies on `Ptr32`'s derived
+//! impl serializing it as a tuple struct named `Ptr32`.
+
+use serde::{Serialize, ser};
+use t5_xfile_defs::{Error, ErrorKind, Result};
+
+use crate::file_line_col;
+
+/// Returns where each pointer in `t`, as bincode encodes it, starts, in
+/// order.
+pub(crate) fn ptr_slots<T: Serialize + ?Sized>(t: &T) -> Result<Vec<usize>> {
+    let mut locator = Locator::default();
+    t.serialize(&mut locator)?;
+    Ok(locator.slots)
+}
+
+#[derive(Default)]
+struct Locator {
+    /// How many bytes bincode would have written so far.
+    pos: usize,
+    slots: Vec<usize>,
+}
+
+impl Locator {
+    fn skip(&mut self, len: usize) -> SerResult {
+        self.pos += len;
+        Ok(())
+    }
+}
+
+/// The error of [`Locator`], which only occurs when a [`Serialize`] impl
+/// raises one.
+#[derive(Debug)]
+struct LocatorError(Error);
+
+impl core::fmt::Display for LocatorError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for LocatorError {}
+
+impl ser::Error for LocatorError {
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        Self(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(msg.to_string()),
+        ))
+    }
+}
+
+impl From<LocatorError> for Error {
+    fn from(value: LocatorError) -> Self {
+        value.0
+    }
+}
+
+type SerResult = core::result::Result<(), LocatorError>;
+
+/// The length prefix bincode writes before sequences, maps, and strings.
+const LEN_SIZE: usize = size_of::<u64>();
+
+/// The variant index bincode writes before enum variants.
+const VARIANT_SIZE: usize = size_of::<u32>();
+
+impl ser::Serializer for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _v: bool) -> SerResult {
+        self.skip(1)
+    }
+
+    fn serialize_i8(self, _v: i8) -> SerResult {
+        self.skip(1)
+    }
+
+    fn serialize_i16(self, _v: i16) -> SerResult {
+        self.skip(2)
+    }
+
+    fn serialize_i32(self, _v: i32) -> SerResult {
+        self.skip(4)
+    }
+
+    fn serialize_i64(self, _v: i64) -> SerResult {
+        self.skip(8)
+    }
+
+    fn serialize_i128(self, _v: i128) -> SerResult {
+        self.skip(16)
+    }
+
+    fn serialize_u8(self, _v: u8) -> SerResult {
+        self.skip(1)
+    }
+
+    fn serialize_u16(self, _v: u16) -> SerResult {
+        self.skip(2)
+    }
+
+    fn serialize_u32(self, _v: u32) -> SerResult {
+        self.skip(4)
+    }
+
+    fn serialize_u64(self, _v: u64) -> SerResult {
+        self.skip(8)
+    }
+
+    fn serialize_u128(self, _v: u128) -> SerResult {
+        self.skip(16)
+    }
+
+    fn serialize_f32(self, _v: f32) -> SerResult {
+        self.skip(4)
+    }
+
+    fn serialize_f64(self, _v: f64) -> SerResult {
+        self.skip(8)
+    }
+
+    fn serialize_char(self, v: char) -> SerResult {
+        self.skip(v.len_utf8())
+    }
+
+    fn serialize_str(self, v: &str) -> SerResult {
+        self.skip(LEN_SIZE + v.len())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> SerResult {
+        self.skip(LEN_SIZE + v.len())
+    }
+
+    fn serialize_none(self) -> SerResult {
+        self.skip(1)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> SerResult {
+        self.skip(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> SerResult {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SerResult {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> SerResult {
+        self.skip(VARIANT_SIZE)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerResult {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> SerResult {
+        self.skip(VARIANT_SIZE)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> core::result::Result<Self, LocatorError> {
+        self.skip(LEN_SIZE)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> core::result::Result<Self, LocatorError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self, LocatorError> {
+        if name == "Ptr32" {
+            self.slots.push(self.pos);
+        }
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self, LocatorError> {
+        self.skip(VARIANT_SIZE)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> core::result::Result<Self, LocatorError> {
+        self.skip(LEN_SIZE)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self, LocatorError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> core::result::Result<Self, LocatorError> {
+        self.skip(VARIANT_SIZE)?;
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> SerResult {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Locator {
+    type Ok = ();
+    type Error = LocatorError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> SerResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerResult {
+        Ok(())
+    }
+}
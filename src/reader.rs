@@ -0,0 +1,226 @@
+//! [`XFileReader`]s for decoding the fixed-layout `Raw` structs.
+//!
+//! [`FixedLayoutReader`] reads directly out of the inflated payload, without
+//! going through [`std::io::Read`] for every field, which adds up with the
+//! number of tiny structs a Fastfile contains. [`IoReader`] works with any
+//! [`Read`], and is what's used when the `fast-reader` feature is disabled.
+//!
+//! [`FixedLayoutReader`] can also record the offset and length of every
+//! field it reads (see [`FixedLayoutReader::with_trace`]), which is what the
+//! `trace` feature is built on.
+
+use std::io::{Cursor, Read};
+
+use t5_xfile_defs::{Error, ErrorKind, Result, XFileRead, XFileReader, read::Field};
+
+use crate::file_line_col;
+
+fn eof(pos: u64) -> Error {
+    Error::new_with_offset(
+        file_line_col!(),
+        pos as _,
+        ErrorKind::Io(std::io::ErrorKind::UnexpectedEof.into()),
+    )
+}
+
+/// Reads a `T` from `reader`'s current position, and advances it past the
+/// `T`.
+pub(crate) fn read_from<T: XFileRead>(reader: impl Read, little_endian: bool) -> Result<T> {
+    T::xfile_read(&mut IoReader::new(reader, 0, little_endian))
+}
+
+/// Same as [`read_from`], but reads straight out of `reader`'s buffer with
+/// the fixed-layout reader if the `fast-reader` feature is enabled.
+pub(crate) fn read_from_cursor<T: XFileRead>(
+    reader: &mut Cursor<impl AsRef<[u8]>>,
+    little_endian: bool,
+) -> Result<T> {
+    #[cfg(feature = "fast-reader")]
+    {
+        let mut r = FixedLayoutReader::new(
+            reader.get_ref().as_ref(),
+            reader.position() as _,
+            little_endian,
+        );
+        let t = T::xfile_read(&mut r);
+        reader.set_position(r.position() as _);
+        t
+    }
+
+    #[cfg(not(feature = "fast-reader"))]
+    {
+        let pos = reader.position();
+        T::xfile_read(&mut IoReader::new(reader, pos, little_endian))
+    }
+}
+
+/// An [`XFileReader`] for any [`Read`].
+pub(crate) struct IoReader<R: Read> {
+    reader: R,
+    /// Only used for errors.
+    pos: u64,
+    little_endian: bool,
+}
+
+impl<R: Read> IoReader<R> {
+    /// `pos` is where `reader` currently is, for errors.
+    pub(crate) fn new(reader: R, pos: u64, little_endian: bool) -> Self {
+        Self {
+            reader,
+            pos,
+            little_endian,
+        }
+    }
+}
+
+impl<R: Read> XFileReader for IoReader<R> {
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.reader.read_exact(buf).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                eof(self.pos)
+            } else {
+                Error::new_with_offset(file_line_col!(), self.pos as _, ErrorKind::Io(e))
+            }
+        })?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg_attr(not(any(feature = "fast-reader", feature = "trace")), allow(dead_code))]
+pub(crate) struct FixedLayoutReader<'de> {
+    buf: &'de [u8],
+    pos: usize,
+    little_endian: bool,
+    /// Fields read so far, if tracing.
+    trace: Option<Vec<TracedField>>,
+    /// Path from the value being read to the field currently being read,
+    /// along with where each field started.
+    path: Vec<Entered>,
+}
+
+/// A field read while tracing.
+#[allow(dead_code)]
+pub(crate) struct TracedField {
+    pub offset: usize,
+    pub len: usize,
+    /// Nesting depth, starting at 1 for the fields of the value being read.
+    pub depth: usize,
+    /// Path relative to the value being read, e.g. `.cells` or
+    /// `.hull_axis[1]`.
+    pub path: String,
+}
+
+struct Entered {
+    field: Field,
+    start: usize,
+    /// How many fields had been traced when this one was entered.
+    children_start: usize,
+}
+
+#[cfg_attr(not(any(feature = "fast-reader", feature = "trace")), allow(dead_code))]
+impl<'de> FixedLayoutReader<'de> {
+    pub(crate) fn new(buf: &'de [u8], pos: usize, little_endian: bool) -> Self {
+        Self {
+            buf,
+            pos,
+            little_endian,
+            trace: None,
+            path: Vec::new(),
+        }
+    }
+
+    /// Records every field read from here on (see [`Self::take_trace`]).
+    #[allow(dead_code)]
+    pub(crate) fn with_trace(mut self) -> Self {
+        self.trace = Some(Vec::new());
+        self
+    }
+
+    /// Returns the fields read since tracing started, in the order they
+    /// appear in the payload (outer fields before their own fields).
+    #[allow(dead_code)]
+    pub(crate) fn take_trace(&mut self) -> Vec<TracedField> {
+        self.trace.take().unwrap_or_default()
+    }
+
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn path_string(&self) -> String {
+        let mut path = String::new();
+        for entered in self.path.iter() {
+            match entered.field {
+                Field::Named(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+                Field::Index(i) => path.push_str(&format!("[{i}]")),
+            }
+        }
+        path
+    }
+}
+
+impl<'de> XFileReader for FixedLayoutReader<'de> {
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        let bytes = self
+            .pos
+            .checked_add(buf.len())
+            .and_then(|end| self.buf.get(self.pos..end))
+            .ok_or_else(|| eof(self.pos as _))?;
+        buf.copy_from_slice(bytes);
+        self.pos += buf.len();
+        Ok(())
+    }
+
+    fn is_tracing(&self) -> bool {
+        self.trace.is_some()
+    }
+
+    fn enter_field(&mut self, field: Field) {
+        self.path.push(Entered {
+            field,
+            start: self.pos,
+            children_start: self.trace.as_ref().map_or(0, Vec::len),
+        });
+    }
+
+    fn leave_field(&mut self) {
+        let path = self.path_string();
+        let depth = self.path.len();
+        let Some(entered) = self.path.pop() else {
+            return;
+        };
+        let Some(trace) = self.trace.as_mut() else {
+            return;
+        };
+
+        // struct fields are always recorded, array elements only if they
+        // have fields of their own, since arrays of primitives are only
+        // recorded as a whole
+        let len = self.pos - entered.start;
+        let has_children = trace.len() > entered.children_start;
+        if len != 0 && (matches!(entered.field, Field::Named(_)) || has_children) {
+            // before its own fields, so the trace stays in payload order
+            trace.insert(
+                entered.children_start,
+                TracedField {
+                    offset: entered.start,
+                    len,
+                    depth,
+                    path,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Checks that an asset survives being serialized and deserialized again.
+//!
+//! Meant for downstream tools and fuzzers to verify, per asset type, that
+//! the serializer and deserializer agree with each other.
+
+use std::io::{Cursor, Seek};
+
+use t5_xfile_defs::{
+    ScriptString, T5XFileDeserialize, T5XFileSerialize, XFilePlatform, XFileRead,
+    xasset::{XAsset, XAssetRaw, XAssetType},
+};
+
+use crate::{Error, ErrorKind, Result, StreamLen, T5XFileSerializerBuilder, file_line_col, reader};
+
+#[derive(Debug)]
+pub enum RoundTripOutcome {
+    /// The re-deserialized asset is identical to the original.
+    Identical,
+    /// The re-deserialized asset differs from the original. `line` is the
+    /// first line (0-based) of the assets' pretty-printed [`Debug`]
+    /// representations that differs.
+    Mismatch {
+        line: usize,
+        expected: String,
+        found: String,
+    },
+    /// Deserialization didn't consume every serialized byte.
+    TrailingBytes(usize),
+    SerializeFailed(Error),
+    DeserializeFailed(Error),
+}
+
+#[derive(Debug)]
+pub struct RoundTripReport {
+    pub asset_type: XAssetType,
+    pub name: Option<String>,
+    /// Number of bytes the asset serialized to, if serialization succeeded.
+    pub serialized_len: Option<usize>,
+    pub outcome: RoundTripOutcome,
+}
+
+impl RoundTripReport {
+    pub fn is_ok(&self) -> bool {
+        matches!(self.outcome, RoundTripOutcome::Identical)
+    }
+}
+
+/// Serializes `asset` for `platform`, deserializes it again, and compares
+/// the result against `asset`.
+pub fn check_asset(asset: &XAsset, platform: XFilePlatform) -> RoundTripReport {
+    let mut report = RoundTripReport {
+        asset_type: asset.asset_type(),
+        name: asset.name().map(ToOwned::to_owned),
+        serialized_len: None,
+        outcome: RoundTripOutcome::Identical,
+    };
+
+    let serialized = T5XFileSerializerBuilder::new(platform)
+        .with_silent(true)
+        .build()
+        .and_then(|mut ser| {
+            ser.serialize_assets(core::iter::once(asset.clone()))?;
+            let bytes = ser.asset_bytes().unwrap_or_default().to_vec();
+            let script_strings = ser
+                .script_strings()
+                .into_iter()
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+            Ok((bytes, script_strings))
+        });
+    let (bytes, script_strings) = match serialized {
+        Ok(s) => s,
+        Err(e) => {
+            report.outcome = RoundTripOutcome::SerializeFailed(e);
+            return report;
+        }
+    };
+    report.serialized_len = Some(bytes.len());
+
+    let mut de = AssetDeserializer {
+        reader: Cursor::new(&bytes),
+        script_strings,
+        platform,
+    };
+    let deserialized = de
+        .load_from_xfile::<XAssetRaw>()
+        .and_then(|raw| XAsset::try_get(&mut de, raw, platform));
+    let deserialized = match deserialized {
+        Ok(a) => a,
+        Err(e) => {
+            report.outcome = RoundTripOutcome::DeserializeFailed(e);
+            return report;
+        }
+    };
+
+    let expected = format!("{asset:#?}");
+    let found = format!("{deserialized:#?}");
+    if let Some((line, (e, f))) = expected
+        .lines()
+        .chain(core::iter::repeat(""))
+        .zip(found.lines().chain(core::iter::repeat("")))
+        .take(expected.lines().count().max(found.lines().count()))
+        .enumerate()
+        .find(|(_, (e, f))| e != f)
+    {
+        report.outcome = RoundTripOutcome::Mismatch {
+            line,
+            expected: e.trim().to_owned(),
+            found: f.trim().to_owned(),
+        };
+        return report;
+    }
+
+    let trailing = bytes.len() - de.reader.position() as usize;
+    if trailing != 0 {
+        report.outcome = RoundTripOutcome::TrailingBytes(trailing);
+    }
+
+    report
+}
+
+/// Runs [`check_asset`] on every asset in `assets`.
+pub fn check_assets<'a>(
+    assets: impl IntoIterator<Item = &'a XAsset>,
+    platform: XFilePlatform,
+) -> Vec<RoundTripReport> {
+    assets
+        .into_iter()
+        .map(|a| check_asset(a, platform))
+        .collect()
+}
+
+/// Deserializes a single asset that was serialized on its own.
+struct AssetDeserializer<'a> {
+    reader: Cursor<&'a Vec<u8>>,
+    script_strings: Vec<String>,
+    platform: XFilePlatform,
+}
+
+impl<'a> T5XFileDeserialize for AssetDeserializer<'a> {
+    fn stream_pos(&mut self) -> Result<u64> {
+        self.reader
+            .stream_position()
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        StreamLen::stream_len(&mut self.reader)
+    }
+
+    fn silent(&self) -> bool {
+        true
+    }
+
+    fn is_little_endian(&self) -> bool {
+        self.platform.is_le()
+    }
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T> {
+        reader::read_from_cursor(&mut self.reader, self.platform.is_le())
+    }
+
+    fn get_script_string(&self, string: ScriptString) -> Result<Option<&str>> {
+        Ok(self
+            .script_strings
+            .get(string.as_u16() as usize)
+            .map(|s| &**s))
+    }
+}
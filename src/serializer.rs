@@ -1,21 +1,82 @@
+//! Serialization of [`XAsset`]s back into a Fastfile.
+//!
+//! Serializing the same assets in the same order with the same options
+//! always produces byte-identical Fastfiles:
+//!
+//! * Script strings are numbered in the order they're first used (after
+//!   any strings given to [`T5XFileSerializerBuilder::with_script_strings`]),
+//!   and written to the string table in that order.
+//! * Absent pointers are written as `0`, and present pointers as
+//!   `0xFFFFFFFF` (i.e., "follows inline"), or with the markers given to
+//!   [`T5XFileSerializerBuilder::with_ptr_markers`].
+//! * Padding and unknown fields are written exactly as they're stored in the
+//!   owned types, which means as they were read from the original Fastfile,
+//!   or zero for assets constructed by hand.
+//! * The payload is compressed with the `deflate` crate's default settings,
+//!   which don't depend on the machine or the time, and the header has no
+//!   timestamp.
+//!
+//! Script string lookups go through a [`HashMap`], but it's never iterated.
+//!
+//! Pointer markers, and the original bytes copied by
+//! [`T5XFileSerializer::serialize_assets_reusing`], carry over how the
+//! original Fastfile happened to be written, so two Fastfiles with the same
+//! assets can still serialize differently. For output that only depends on
+//! the assets, e.g. for reproducible builds or to diff zones, use
+//! [`T5XFileSerializerBuilder::with_deterministic`] (`--deterministic` on the
+//! command line), which ignores both.
+//!
+//! The [`XFile`] header's block sizes are computed from what's stored into
+//! each block (see [`T5XFileSerializer::xfile`]).
+//!
+//! With the `trace` feature, assets that haven't changed since they were
+//! deserialized can be copied from the original payload rather than
+//! serialized again (see [`T5XFileSerializer::serialize_assets_reusing`]).
+
 use serde::Serialize;
 
 use std::{
-    collections::HashSet,
+    collections::{HashMap, VecDeque},
     io::{Cursor, Seek, Write},
 };
 
-use crate::{BincodeOptions, file_line_col};
+use crate::{
+    BincodeOptions,
+    codec::{CompressionCodec, Zlib},
+    file_line_col,
+    ptr_slots::ptr_slots,
+};
 
 use t5_xfile_defs::{
-    Error, ErrorKind, FatPointerCountFirstU32, Ptr32, Result, ScriptString, T5XFileSerialize,
-    XFile, XFileHeader, XFilePlatform, XFileSerialize,
+    Error, ErrorKind, FatPointerCountFirstU32, Ptr32, PtrMarker, Result, ScriptString,
+    T5XFileSerialize, XFile, XFileBlock, XFileHeader, XFilePlatform, XFileSerialize,
+    encoding::StringEncoding,
+    size_of,
     xasset::{XAsset, XAssetListRaw},
 };
 
+#[cfg(feature = "trace")]
+use t5_xfile_defs::{
+    blocks::RealPtr,
+    xasset::{XAssetRaw, XAssetType},
+};
+
+#[cfg(feature = "trace")]
+use crate::trace::Trace;
+
+/// The engine aligns most allocations to 4 bytes, so every store is padded
+/// to this when computing block sizes. That overestimates the blocks a bit
+/// for stores that don't need it, which is harmless, where underestimating
+/// them would make the engine overrun them.
+const BLOCK_ALIGNMENT: u32 = 4;
+
 pub struct T5XFileSerializerBuilder {
     silent: bool,
     platform: XFilePlatform,
+    script_strings: Vec<String>,
+    string_encoding: StringEncoding,
+    ptr_markers: Vec<Vec<PtrMarker>>,
+    deterministic: bool,
 }
 
 impl T5XFileSerializerBuilder {
@@ -23,6 +84,10 @@ impl T5XFileSerializerBuilder {
         Self {
             platform,
             silent: false,
+            script_strings: Vec::new(),
+            string_encoding: StringEncoding::default(),
+            ptr_markers: Vec::new(),
+            deterministic: false,
         }
     }
 
@@ -31,8 +96,71 @@ impl T5XFileSerializerBuilder {
         self
     }
 
+    /// Pre-populates the script string table, in order, before any assets
+    /// are serialized. Passing the script strings of the Fastfile the assets
+    /// were deserialized from (see [`T5XFileDeserialize::script_strings`])
+    /// keeps every [`ScriptString`] index the same as in the original.
+    ///
+    /// [`T5XFileDeserialize::script_strings`]: t5_xfile_defs::T5XFileDeserialize::script_strings
+    pub fn with_script_strings(mut self, script_strings: impl IntoIterator<Item = String>) -> Self {
+        self.script_strings = script_strings.into_iter().collect();
+        self
+    }
+
+    /// Encodes strings with `string_encoding` rather than
+    /// [`StringEncoding::default`]. Should match what the assets were
+    /// deserialized with (see
+    /// [`T5XFileDeserializerBuilder::with_string_encoding`](crate::T5XFileDeserializerBuilder::with_string_encoding))
+    /// for their strings to come out the same.
+    pub fn with_string_encoding(mut self, string_encoding: StringEncoding) -> Self {
+        self.string_encoding = string_encoding;
+        self
+    }
+
+    /// Writes the inline pointers of each asset with the markers in
+    /// `ptr_markers` rather than always with [`PtrMarker::Following`]. The
+    /// first element is for the first asset serialized, and so on, and each
+    /// lists the markers of the asset's inline pointers in the order they're
+    /// written. Passing the markers recorded while deserializing the assets
+    /// (see
+    /// [`T5XFileDeserializer::ptr_markers`](crate::T5XFileDeserializer::ptr_markers))
+    /// writes them the same way they were read.
+    ///
+    /// Assets without markers, and pointers past the end of their asset's
+    /// markers, are written with [`PtrMarker::Following`]. Since the
+    /// serializer writes everything inline, an asset that was read through
+    /// real offsets has more inline pointers than markers recorded, and the
+    /// rest may be matched up with the wrong ones.
+    pub fn with_ptr_markers(
+        mut self,
+        ptr_markers: impl IntoIterator<Item = Vec<PtrMarker>>,
+    ) -> Self {
+        self.ptr_markers = ptr_markers.into_iter().collect();
+        self
+    }
+
+    /// Writes the canonical form of the assets, which only depends on the
+    /// assets themselves: every present pointer is written as
+    /// [`PtrMarker::Following`] (any markers given to
+    /// [`Self::with_ptr_markers`] are ignored), and
+    /// [`T5XFileSerializer::serialize_assets_reusing`] serializes every asset
+    /// rather than copying any from the original payload.
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
     pub fn build(self) -> Result<T5XFileSerializer> {
-        T5XFileSerializer::new(self.silent, self.platform)
+        let mut ser = T5XFileSerializer::new(self.silent, self.platform)?;
+        ser.string_encoding = self.string_encoding;
+        ser.deterministic = self.deterministic;
+        if !self.deterministic {
+            ser.ptr_markers = self.ptr_markers.into();
+        }
+        for string in self.script_strings.iter() {
+            ser.get_or_insert_script_string(string)?;
+        }
+        Ok(ser)
     }
 }
 
@@ -40,11 +168,26 @@ impl T5XFileSerializerBuilder {
 pub struct T5XFileSerializer {
     silent: bool,
     xfile: XFile,
-    script_strings: HashSet<String>,
+    /// In insertion order, so each string's index is its [`ScriptString`].
+    script_strings: Vec<String>,
+    script_string_indices: HashMap<String, u16>,
     asset_bytes: Option<Cursor<Vec<u8>>>,
     serialized_assets: usize,
+    /// Blocks pushed with [`T5XFileSerialize::push_block`], innermost last.
+    blocks: Vec<XFileBlock>,
+    /// Bytes stored into each block so far, indexed by
+    /// [`XFileBlock::index`].
+    block_sizes: [u32; XFileBlock::COUNT],
     opts: BincodeOptions,
     platform: XFilePlatform,
+    string_encoding: StringEncoding,
+    /// See [`T5XFileSerializerBuilder::with_ptr_markers`], for the assets
+    /// that haven't been serialized yet.
+    ptr_markers: VecDeque<Vec<PtrMarker>>,
+    /// What's left of the current asset's markers.
+    asset_ptr_markers: VecDeque<PtrMarker>,
+    /// See [`T5XFileSerializerBuilder::with_deterministic`].
+    deterministic: bool,
 }
 
 impl<'a> T5XFileSerializer {
@@ -52,19 +195,24 @@ impl<'a> T5XFileSerializer {
         Ok(Self {
             silent,
             xfile: XFile::default(),
-            script_strings: HashSet::new(),
+            script_strings: Vec::new(),
+            script_string_indices: HashMap::new(),
             asset_bytes: None,
             serialized_assets: 0,
+            blocks: Vec::new(),
+            block_sizes: [0; XFileBlock::COUNT],
             opts: BincodeOptions::from_platform(platform),
             platform,
+            string_encoding: StringEncoding::default(),
+            ptr_markers: VecDeque::new(),
+            asset_ptr_markers: VecDeque::new(),
+            deterministic: false,
         })
     }
 
-    pub fn serialize_assets<const MAX_LOCAL_CLIENTS: usize>(
-        &mut self,
-        assets: impl Iterator<Item = XAsset>,
-    ) -> Result<()> {
+    pub fn serialize_assets(&mut self, assets: impl Iterator<Item = XAsset>) -> Result<()> {
         for asset in assets {
+            self.next_asset_ptr_markers();
             asset.xfile_serialize(self, ())?;
             self.serialized_assets += 1;
         }
@@ -72,6 +220,111 @@ impl<'a> T5XFileSerializer {
         Ok(())
     }
 
+    /// Same as [`Self::serialize_assets`], but every asset that's identical
+    /// to the one with the same type and name in `original` is copied from
+    /// its payload as-is instead of being serialized again, which is much
+    /// faster when only a few assets in a large Fastfile were changed.
+    ///
+    /// The copied bytes refer to script strings by index, so the serializer
+    /// has to have been built with `original`'s script strings (see
+    /// [`T5XFileSerializerBuilder::with_script_strings`]); if it wasn't,
+    /// [`ErrorKind::BrokenInvariant`] is returned before anything is
+    /// serialized.
+    ///
+    /// Everything copied is allocated from [`XFileBlock::Virtual`], since
+    /// the trace doesn't record which blocks the original asset was loaded
+    /// into.
+    ///
+    /// Nothing is copied if the serializer was built
+    /// [`with_deterministic`](T5XFileSerializerBuilder::with_deterministic).
+    ///
+    /// Returns the number of assets that were copied.
+    #[cfg(feature = "trace")]
+    pub fn serialize_assets_reusing(
+        &mut self,
+        assets: impl Iterator<Item = XAsset>,
+        original: &OriginalXFile,
+    ) -> Result<usize> {
+        if !self.script_strings.starts_with(&original.script_strings) {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(
+                    "T5XFileSerializer: the script strings don't start with the original's \
+                     (see T5XFileSerializerBuilder::with_script_strings)"
+                        .to_string(),
+                ),
+            ));
+        }
+
+        let mut reused = 0;
+        for asset in assets {
+            // copied assets keep the markers they already had
+            self.next_asset_ptr_markers();
+            if !self.deterministic
+                && let Some(bytes) = original.unchanged_bytes(&asset)
+            {
+                self.store_into_xfile(XAssetRaw {
+                    asset_type: asset.asset_type() as _,
+                    asset_data: Ptr32::unreal(),
+                })?;
+                self.store_bytes(bytes);
+                reused += 1;
+            } else {
+                asset.xfile_serialize(self, ())?;
+            }
+            self.serialized_assets += 1;
+        }
+
+        Ok(reused)
+    }
+
+    /// Moves on to the markers of the next asset (see
+    /// [`T5XFileSerializerBuilder::with_ptr_markers`]).
+    fn next_asset_ptr_markers(&mut self) {
+        self.asset_ptr_markers = self.ptr_markers.pop_front().unwrap_or_default().into();
+    }
+
+    /// Appends `bytes` to the serialized assets as they are.
+    #[cfg(feature = "trace")]
+    fn store_bytes(&mut self, bytes: &[u8]) {
+        let asset_bytes = self.asset_bytes.get_or_insert(Cursor::new(Vec::new()));
+        asset_bytes.write_all(bytes).unwrap();
+        let block = self.current_block();
+        Self::allocate(&mut self.block_sizes, block, bytes.len());
+    }
+
+    /// The block that stores are currently allocated from.
+    pub fn current_block(&self) -> XFileBlock {
+        self.blocks.last().copied().unwrap_or_default()
+    }
+
+    fn allocate(block_sizes: &mut [u32; XFileBlock::COUNT], block: XFileBlock, len: usize) {
+        let len = (len as u32).next_multiple_of(BLOCK_ALIGNMENT);
+        block_sizes[block.index()] = block_sizes[block.index()].saturating_add(len);
+    }
+
+    /// The [`XFile`] header for the assets serialized so far.
+    ///
+    /// Each block's size is the total of what was stored into it, with the
+    /// asset list and script strings (written by [`Self::deflate`])
+    /// allocated from [`XFileBlock::Virtual`].
+    pub fn xfile(&self) -> XFile {
+        let mut block_sizes = self.block_sizes;
+        let list_len = size_of!(XAssetListRaw)
+            + self
+                .script_strings
+                .iter()
+                .map(|s| s.len() + 1)
+                .sum::<usize>();
+        Self::allocate(&mut block_sizes, XFileBlock::Virtual, list_len);
+
+        XFile {
+            size: block_sizes.iter().fold(0u32, |a, &b| a.saturating_add(b)),
+            external_size: self.xfile.external_size,
+            block_size: block_sizes,
+        }
+    }
+
     fn serialize<T: Serialize>(&mut self, mut writer: impl Write + Seek, t: T) -> Result<()> {
         self.opts.serialize_into(&mut writer, t).map_err(|e| {
             Error::new_with_offset(
@@ -93,13 +346,18 @@ impl<'a> T5XFileSerializer {
         }
 
         let mut bytes = Cursor::new(Vec::new());
-        let header = XFileHeader::new(self.platform);
+        let header = XFileHeader::new(self.platform).ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::UnimplementedPlatform(self.platform),
+            )
+        })?;
 
         self.serialize(&mut bytes, header)?;
 
         let mut blob = Cursor::new(Vec::new());
 
-        self.serialize(&mut blob, self.xfile)?;
+        self.serialize(&mut blob, self.xfile())?;
 
         if self.serialized_assets == 0 && !self.silent {
             println!("Warning: serializing fastfile with zero assets.");
@@ -138,7 +396,7 @@ impl<'a> T5XFileSerializer {
         let asset_bytes = self.asset_bytes.take().unwrap_or_default().into_inner();
         self.serialize(&mut blob, asset_bytes)?;
 
-        let deflated_blob = deflate::deflate_bytes_zlib(&blob.into_inner());
+        let deflated_blob = Zlib.deflate(&blob.into_inner())?;
 
         let mut bytes = bytes.into_inner();
         bytes.extend_from_slice(&deflated_blob);
@@ -149,38 +407,63 @@ impl<'a> T5XFileSerializer {
 
 impl T5XFileSerialize for T5XFileSerializer {
     fn store_into_xfile<T: Serialize>(&mut self, t: T) -> Result<()> {
-        self.opts
-            .serialize_into(self.asset_bytes.get_or_insert(Cursor::new(Vec::new())), t)
-            .map_err(|e| {
-                Error::new_with_offset(
-                    file_line_col!(),
-                    self.asset_bytes.as_ref().unwrap().position() as _,
-                    ErrorKind::Bincode(e),
-                )
-            })
+        let asset_bytes = self.asset_bytes.get_or_insert(Cursor::new(Vec::new()));
+        let start = asset_bytes.position();
+        if let Err(e) = self.opts.serialize_into(&mut *asset_bytes, &t) {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                asset_bytes.position() as _,
+                ErrorKind::Bincode(e),
+            ));
+        }
+
+        if !self.asset_ptr_markers.is_empty() {
+            // the serializer always writes `PtrMarker::Following`, so those
+            // are the inline pointers
+            let le = self.platform.is_le();
+            let bytes = &mut asset_bytes.get_mut()[start as usize..];
+            for slot in ptr_slots(&t)? {
+                let Some(ptr) = bytes.get_mut(slot..slot + size_of::<u32>()) else {
+                    break;
+                };
+                if ptr != PtrMarker::Following.as_u32().to_ne_bytes() {
+                    continue;
+                }
+                let Some(marker) = self.asset_ptr_markers.pop_front() else {
+                    break;
+                };
+                let marker = marker.as_u32();
+                ptr.copy_from_slice(&if le {
+                    marker.to_le_bytes()
+                } else {
+                    marker.to_be_bytes()
+                });
+            }
+        }
+
+        let len = asset_bytes.position() - start;
+        let block = self.current_block();
+        Self::allocate(&mut self.block_sizes, block, len as _);
+        Ok(())
     }
 
     fn get_or_insert_script_string(&mut self, string: &str) -> Result<ScriptString> {
+        if let Some(&i) = self.script_string_indices.get(string) {
+            return Ok(ScriptString(i));
+        }
+
         if self.script_strings.len() >= u16::MAX as usize {
-            Err(Error::new_with_offset(
+            return Err(Error::new_with_offset(
                 file_line_col!(),
                 0,
                 ErrorKind::ScriptStringOverflow,
-            ))
-        } else {
-            let string = string.to_owned();
-            if !self.script_strings.contains(&string) {
-                self.script_strings.insert(string.clone());
-            }
-
-            let (i, _) = self
-                .script_strings
-                .iter()
-                .enumerate()
-                .find(|(_, s)| s.as_str() == string.as_str())
-                .unwrap();
-            Ok(ScriptString(i as _))
+            ));
         }
+
+        let i = self.script_strings.len() as u16;
+        self.script_strings.push(string.to_owned());
+        self.script_string_indices.insert(string.to_owned(), i);
+        Ok(ScriptString(i))
     }
 
     fn script_strings(&self) -> Vec<&str> {
@@ -194,4 +477,131 @@ impl T5XFileSerialize for T5XFileSerializer {
     fn asset_bytes(&self) -> Option<&[u8]> {
         self.asset_bytes.as_ref().map(|a| &**a.get_ref())
     }
+
+    fn is_little_endian(&self) -> bool {
+        self.platform.is_le()
+    }
+
+    fn string_encoding(&self) -> StringEncoding {
+        self.string_encoding
+    }
+
+    fn push_block(&mut self, block: XFileBlock) {
+        self.blocks.push(block);
+    }
+
+    fn pop_block(&mut self) {
+        self.blocks.pop();
+    }
+}
+
+/// A Fastfile that was deserialized with tracing enabled (see
+/// [`T5XFileDeserializerBuilder::with_trace`]), for
+/// [`T5XFileSerializer::serialize_assets_reusing`] to copy unchanged assets
+/// from.
+///
+/// [`T5XFileDeserializerBuilder::with_trace`]: crate::T5XFileDeserializerBuilder::with_trace
+#[cfg(feature = "trace")]
+pub struct OriginalXFile<'a> {
+    payload: &'a [u8],
+    trace: &'a Trace,
+    assets: &'a [XAsset],
+    script_strings: Vec<String>,
+    /// Index into `assets` of the first asset with each type and name.
+    indices: HashMap<(XAssetType, Option<&'a str>), usize>,
+    real_ptrs: &'a [RealPtr],
+}
+
+#[cfg(feature = "trace")]
+impl<'a> OriginalXFile<'a> {
+    /// `payload` is the inflated payload (see
+    /// [`T5XFileDeserializer::into_payload`]), `assets` every asset
+    /// deserialized from it in order, `trace` the trace recorded while
+    /// deserializing them, and `script_strings` the Fastfile's script
+    /// strings.
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if `trace` doesn't have an
+    /// entry for every asset in `assets`, or if those entries are out of
+    /// `payload`'s bounds.
+    ///
+    /// [`T5XFileDeserializer::into_payload`]: crate::T5XFileDeserializer::into_payload
+    pub fn new(
+        payload: &'a [u8],
+        trace: &'a Trace,
+        assets: &'a [XAsset],
+        script_strings: impl IntoIterator<Item = String>,
+    ) -> Result<Self> {
+        if trace.assets().len() != assets.len() {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "OriginalXFile: {} assets, but {} in the trace",
+                    assets.len(),
+                    trace.assets().len()
+                )),
+            ));
+        }
+
+        if let Some(traced) = trace
+            .assets()
+            .iter()
+            .find(|a| a.offset.saturating_add(a.len) > payload.len() as u64)
+        {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "OriginalXFile: asset at {:#X} ({:#X} bytes) is past the end of the payload ({:#X})",
+                    traced.offset,
+                    traced.len,
+                    payload.len()
+                )),
+            ));
+        }
+
+        let mut indices = HashMap::new();
+        for (i, asset) in assets.iter().enumerate() {
+            indices
+                .entry((asset.asset_type(), asset.name()))
+                .or_insert(i);
+        }
+
+        Ok(Self {
+            payload,
+            trace,
+            assets,
+            script_strings: script_strings.into_iter().collect(),
+            indices,
+            real_ptrs: &[],
+        })
+    }
+
+    /// The real offsets encountered while deserializing the original (see
+    /// [`T5XFileDeserializer::real_ptrs`]). Assets containing one are never
+    /// copied, since what they point to may have moved.
+    ///
+    /// [`T5XFileDeserializer::real_ptrs`]: crate::T5XFileDeserializer::real_ptrs
+    pub fn with_real_ptrs(mut self, real_ptrs: &'a [RealPtr]) -> Self {
+        self.real_ptrs = real_ptrs;
+        self
+    }
+
+    /// The original bytes of `asset`'s header and everything loaded after
+    /// it, if the original has an asset with the same type and name that's
+    /// identical to it.
+    pub fn unchanged_bytes(&self, asset: &XAsset) -> Option<&'a [u8]> {
+        let &i = self.indices.get(&(asset.asset_type(), asset.name()))?;
+        if &self.assets[i] != asset {
+            return None;
+        }
+
+        let traced = &self.trace.assets()[i];
+        let (start, end) = (traced.offset, traced.offset + traced.len);
+        if traced.asset_type != asset.asset_type()
+            || self.real_ptrs.iter().any(|p| p.pos >= start && p.pos < end)
+        {
+            return None;
+        }
+
+        Some(&self.payload[start as usize..end as usize])
+    }
 }
@@ -0,0 +1,104 @@
+//! Snapshots of how far a deserializer has gotten, so deserialization can
+//! be picked up again later.
+//!
+//! A snapshot is taken between two assets with
+//! [`T5XFileDeserializer::snapshot`](crate::T5XFileDeserializer::snapshot),
+//! and handed to
+//! [`T5XFileDeserializer::resume`](crate::T5XFileDeserializer::resume) on
+//! another deserializer for the same Fastfile (or a cache of it, see
+//! [`crate::cache`]), possibly in another process. That way a very large
+//! zone can be processed a chunk at a time, and a batch job that crashes can
+//! start over from the last asset it finished rather than from the
+//! beginning.
+//!
+//! A snapshot consists of where the next asset starts in the payload, the
+//! asset counters, and the state needed to resolve real offsets (see
+//! [`t5_xfile_defs::blocks`]). The script strings and the length of the
+//! payload are kept as well, so a snapshot can't be resumed with the wrong
+//! Fastfile by accident. Objects interned with the `arc` feature aren't, so
+//! assets after a snapshot don't share them with assets before it.
+
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::{BincodeOptions, Error, ErrorKind, Result, file_line_col};
+
+use t5_xfile_defs::blocks::XFileBlocks;
+
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"T5XFSNAP";
+
+/// Bumped whenever the layout of [`DeserializerSnapshot`] changes.
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Returned by
+/// [`T5XFileDeserializer::snapshot`](crate::T5XFileDeserializer::snapshot).
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeserializerSnapshot {
+    pub(crate) platform: u8,
+    pub(crate) payload_len: u64,
+    pub(crate) script_strings: Vec<String>,
+    pub(crate) remaining_assets: usize,
+    pub(crate) deserialized_assets: usize,
+    pub(crate) non_null_assets: usize,
+    /// Where in the payload the next asset starts.
+    pub(crate) pos: u64,
+    pub(crate) blocks: XFileBlocks,
+}
+
+impl DeserializerSnapshot {
+    /// How many assets were left when the snapshot was taken.
+    pub fn remaining_assets(&self) -> usize {
+        self.remaining_assets
+    }
+
+    /// How many assets had been deserialized successfully when the snapshot
+    /// was taken. Skipped assets and assets that failed to deserialize
+    /// aren't counted.
+    pub fn deserialized_assets(&self) -> usize {
+        self.deserialized_assets
+    }
+
+    /// Writes the snapshot to `writer`, encoded the same way a
+    /// little-endian Fastfile is.
+    #[cfg(feature = "serde")]
+    pub fn write(&self, mut writer: impl Write) -> Result<()> {
+        let opts = BincodeOptions::new(true);
+        opts.serialize_into(&mut writer, (SNAPSHOT_MAGIC, SNAPSHOT_VERSION))
+            .and_then(|_| opts.serialize_into(&mut writer, self))
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Bincode(e)))
+    }
+
+    /// Reads a snapshot written by [`Self::write`].
+    #[cfg(feature = "serde")]
+    pub fn read(mut reader: impl Read) -> Result<Self> {
+        let opts = BincodeOptions::new(true);
+
+        let (magic, version) = opts
+            .deserialize_from::<([u8; 8], u32)>(&mut reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        if magic != SNAPSHOT_MAGIC {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::BadSnapshot(format!("bad magic {magic:02X?}")),
+            ));
+        }
+        if version != SNAPSHOT_VERSION {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                8,
+                ErrorKind::BadSnapshot(format!(
+                    "version is {version}, expected {SNAPSHOT_VERSION}"
+                )),
+            ));
+        }
+
+        opts.deserialize_from(reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 12, ErrorKind::Bincode(e)))
+    }
+}
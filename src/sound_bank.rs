@@ -0,0 +1,297 @@
+//! Sound banks, which hold the audio of sounds that aren't in the Fastfile
+//! (see [`SoundFileRef::Streamed`]).
+//!
+//! Most sounds are too large to keep in a Fastfile, so the Fastfile only
+//! references them, and the audio is loaded from a sound bank in the game
+//! directory instead (`.sabs` files for sounds that are streamed as they
+//! play, `.sabl` for ones that are loaded all at once). Both have the same
+//! layout, in the platform's endianness: a [`SoundBankHeader`], then
+//! `entry_count` [`SoundBankEntry`]s at `entry_offset`, each
+//! `entry_size` bytes apart. Each entry's audio is stored as-is.
+//!
+//! [`SoundBanks::from_game_dir`] opens every bank in a game directory, and
+//! [`T5XFileDeserializerBuilder::with_game_dir`](crate::T5XFileDeserializerBuilder::with_game_dir)
+//! uses them to load the audio of every streamed sound it deserializes.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{BincodeOptions, Error, ErrorKind, GameDir, Result, file_line_col};
+
+use t5_xfile_defs::sound::{
+    SndAsset, SndAssetChannel, SndAssetFlags, SndAssetFormat, SndBank, SoundFileRef,
+};
+
+/// The magic of a little-endian bank, read as a little-endian [`u32`].
+/// Big-endian banks have the same magic in reverse.
+pub const SAB_MAGIC: u32 = 0x23585532;
+
+/// The extensions of sound banks, for streamed and for loaded sounds.
+pub const SAB_EXTENSIONS: [&str; 2] = ["sabs", "sabl"];
+
+/// The frame rates [`SoundBankEntry::frame_rate_index`] indexes into.
+pub const SAB_FRAME_RATES: [u32; 9] = [
+    8000, 12000, 16000, 24000, 32000, 44100, 48000, 96000, 192000,
+];
+
+const SAB_ENTRY_SIZE: u32 = 20;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct SoundBankHeader {
+    pub magic: u32,
+    pub version: u32,
+    pub entry_size: u32,
+    pub checksum_size: u32,
+    pub dependency_size: u32,
+    pub entry_count: u32,
+    pub dependency_count: u32,
+    pad: u32,
+    pub file_size: u64,
+    pub entry_offset: u64,
+    pub checksum_offset: u64,
+}
+
+/// A sound in a bank.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct SoundBankEntry {
+    /// The [`SndAlias::id`](t5_xfile_defs::sound::SndAlias::id) of the alias
+    /// the sound is for.
+    pub id: u32,
+    pub size: u32,
+    /// The offset of the sound's audio, relative to the start of the bank.
+    pub offset: u32,
+    pub frame_count: u32,
+    /// See [`SAB_FRAME_RATES`].
+    pub frame_rate_index: u8,
+    pub channel_count: u8,
+    pub looping: u8,
+    /// See [`SndAssetFormat`].
+    pub format: u8,
+}
+
+/// A single sound bank.
+pub struct SoundBankFile<R> {
+    reader: R,
+    header: SoundBankHeader,
+    /// Keyed by [`SoundBankEntry::id`].
+    entries: BTreeMap<u32, SoundBankEntry>,
+}
+
+impl SoundBankFile<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read + Seek> SoundBankFile<R> {
+    /// Reads and validates the header and the entries. Audio isn't read
+    /// until it's asked for.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        reader
+            .read_exact(&mut magic)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        let little_endian = if u32::from_le_bytes(magic) == SAB_MAGIC {
+            true
+        } else if u32::from_be_bytes(magic) == SAB_MAGIC {
+            false
+        } else {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                0,
+                ErrorKind::BadSoundBank(format!("bad magic {magic:02X?}")),
+            ));
+        };
+
+        reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Io(e)))?;
+        let opts = BincodeOptions::new(little_endian);
+        let header = opts
+            .deserialize_from::<SoundBankHeader>(&mut reader)
+            .map_err(|e| Error::new_with_offset(file_line_col!(), 0, ErrorKind::Bincode(e)))?;
+        if header.entry_size < SAB_ENTRY_SIZE {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                8,
+                ErrorKind::BadSoundBank(format!(
+                    "entries are {} bytes, expected at least {SAB_ENTRY_SIZE}",
+                    header.entry_size
+                )),
+            ));
+        }
+
+        let mut entries = BTreeMap::new();
+        for i in 0..header.entry_count as u64 {
+            let pos = header.entry_offset + i * header.entry_size as u64;
+            reader.seek(SeekFrom::Start(pos)).map_err(|e| {
+                Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Io(e))
+            })?;
+            let entry = opts
+                .deserialize_from::<SoundBankEntry>(&mut reader)
+                .map_err(|e| {
+                    Error::new_with_offset(file_line_col!(), pos as _, ErrorKind::Bincode(e))
+                })?;
+            if entry.offset as u64 + entry.size as u64 > header.file_size {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    pos as _,
+                    ErrorKind::BadSoundBank(format!(
+                        "entry {i} ({:#010X}) lies outside the bank",
+                        entry.id
+                    )),
+                ));
+            }
+            entries.entry(entry.id).or_insert(entry);
+        }
+
+        Ok(Self {
+            reader,
+            header,
+            entries,
+        })
+    }
+
+    pub fn header(&self) -> SoundBankHeader {
+        self.header
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &SoundBankEntry> {
+        self.entries.values()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&SoundBankEntry> {
+        self.entries.get(&id)
+    }
+
+    /// Reads a sound's audio, along with everything the entry says about
+    /// it. The sound's channels are assumed to be the first
+    /// `channel_count` of [`SndAssetChannel`], in order.
+    pub fn read(&mut self, entry: SoundBankEntry) -> Result<SndAsset> {
+        let format = SndAssetFormat::from_u32(entry.format as _).ok_or_else(|| {
+            Error::new_with_offset(
+                file_line_col!(),
+                entry.offset,
+                ErrorKind::BadSoundBank(format!("bad format {}", entry.format)),
+            )
+        })?;
+        let frame_rate = *SAB_FRAME_RATES
+            .get(entry.frame_rate_index as usize)
+            .ok_or_else(|| {
+                Error::new_with_offset(
+                    file_line_col!(),
+                    entry.offset,
+                    ErrorKind::BadSoundBank(format!(
+                        "bad frame rate index {}",
+                        entry.frame_rate_index
+                    )),
+                )
+            })?;
+
+        self.reader
+            .seek(SeekFrom::Start(entry.offset as _))
+            .map_err(|e| {
+                Error::new_with_offset(file_line_col!(), entry.offset, ErrorKind::Io(e))
+            })?;
+        let mut data = vec![0u8; entry.size as _];
+        self.reader.read_exact(&mut data).map_err(|e| {
+            Error::new_with_offset(file_line_col!(), entry.offset, ErrorKind::Io(e))
+        })?;
+
+        let channel_flags = (0..entry.channel_count.min(8))
+            .fold(SndAssetChannel::empty(), |flags, i| {
+                flags | SndAssetChannel::from_bits_truncate(1 << i)
+            });
+        let flags = if entry.looping != 0 {
+            SndAssetFlags::LOOPING
+        } else {
+            SndAssetFlags::empty()
+        };
+
+        Ok(SndAsset {
+            version: self.header.version,
+            frame_count: entry.frame_count,
+            frame_rate,
+            channel_count: entry.channel_count as _,
+            header_size: 0,
+            block_size: 0,
+            buffer_size: entry.size,
+            format,
+            channel_flags,
+            flags,
+            seek_table: Vec::new(),
+            data,
+        })
+    }
+}
+
+/// Every sound bank in a game directory.
+pub struct SoundBanks {
+    banks: Vec<(PathBuf, SoundBankFile<BufReader<File>>)>,
+}
+
+impl SoundBanks {
+    /// Opens every file with one of the [`SAB_EXTENSIONS`] in the companion
+    /// directories of the game install at `dir` (see
+    /// [`GameDir::companion_files`]).
+    pub fn from_game_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let paths = GameDir::new(dir.as_ref()).companion_files(&SAB_EXTENSIONS)?;
+
+        let banks = paths
+            .into_iter()
+            .map(|p| SoundBankFile::open(&p).map(|bank| (p, bank)))
+            .collect::<Result<_>>()?;
+
+        Ok(Self { banks })
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.banks.iter().map(|(p, _)| p.as_path())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.banks.is_empty()
+    }
+
+    /// Reads the sound with `id` from the first bank containing it.
+    pub fn read_sound(&mut self, id: u32) -> Result<Option<SndAsset>> {
+        for (_, bank) in &mut self.banks {
+            if let Some(&entry) = bank.get(id) {
+                return bank.read(entry).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Loads the audio of every streamed sound in `bank` (see
+    /// [`SoundFile::fuse_streamed`](t5_xfile_defs::sound::SoundFile::fuse_streamed)).
+    /// Returns how many were loaded; sounds that aren't in any of the banks
+    /// are left as they are.
+    pub fn fuse(&mut self, bank: &mut SndBank) -> Result<usize> {
+        let mut fused = 0;
+
+        for alias in bank.aliases.iter_mut().flat_map(|list| &mut list.aliases) {
+            let Some(file) = alias.sound_file.as_deref_mut() else {
+                continue;
+            };
+            if !matches!(file.u, SoundFileRef::Streamed(_)) {
+                continue;
+            }
+
+            if let Some(sound) = self.read_sound(alias.id)? {
+                file.fuse_streamed(sound);
+                fused += 1;
+            }
+        }
+
+        Ok(fused)
+    }
+}
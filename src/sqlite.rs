@@ -0,0 +1,230 @@
+//! An index of the assets in one or many Fastfiles, stored in a SQLite
+//! database so it can be queried across zones (e.g. "which zones contain
+//! material X").
+//!
+//! The database has three tables:
+//!
+//! * `zones (id, name)`, with one row per indexed Fastfile.
+//! * `assets (id, zone_id, type, name, size, hash)`, with one row per asset.
+//!   `type` is the name of the [`XAssetType`] (e.g. `MATERIAL`), `size` is
+//!   [`XAsset::estimated_size`](t5_xfile_defs::xasset::XAsset::estimated_size),
+//!   and `hash` is the asset's [`StableHash`], reinterpreted as a signed
+//!   integer.
+//! * `dependencies (asset_id, type, name)`, with one row per asset an asset
+//!   references (see
+//!   [`XAsset::dependencies`](t5_xfile_defs::xasset::XAsset::dependencies)).
+
+use std::path::Path;
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::{
+    Error, ErrorKind, Result,
+    batch::{Batch, BatchReport},
+    file_line_col,
+};
+
+use t5_xfile_defs::{
+    hash::StableHash,
+    xasset::{XAssetBag, XAssetType},
+};
+
+const SCHEMA: &str = "
+    PRAGMA foreign_keys = ON;
+    CREATE TABLE IF NOT EXISTS zones (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS assets (
+        id INTEGER PRIMARY KEY,
+        zone_id INTEGER NOT NULL REFERENCES zones (id) ON DELETE CASCADE,
+        type TEXT NOT NULL,
+        name TEXT NOT NULL,
+        size INTEGER NOT NULL,
+        hash INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS assets_type_name ON assets (type, name);
+    CREATE TABLE IF NOT EXISTS dependencies (
+        asset_id INTEGER NOT NULL REFERENCES assets (id) ON DELETE CASCADE,
+        type TEXT NOT NULL,
+        name TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS dependencies_type_name ON dependencies (type, name);
+";
+
+fn db_error(where_: String, e: rusqlite::Error) -> Error {
+    Error::new(where_, ErrorKind::Database(e.to_string()))
+}
+
+/// The name an [`XAssetType`] is stored as.
+fn type_name(asset_type: XAssetType) -> String {
+    format!("{asset_type:?}")
+}
+
+pub struct AssetIndex {
+    conn: Connection,
+}
+
+impl AssetIndex {
+    /// Opens the index at `path`, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path).map_err(|e| db_error(file_line_col!(), e))?;
+        Self::new(conn)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().map_err(|e| db_error(file_line_col!(), e))?;
+        Self::new(conn)
+    }
+
+    fn new(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| db_error(file_line_col!(), e))?;
+        Ok(Self { conn })
+    }
+
+    /// The underlying database, for queries this type doesn't have a method
+    /// for.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Indexes every asset in `assets` as being in `zone`, replacing
+    /// whatever was indexed for `zone` before. Assets without a name are
+    /// left out.
+    pub fn insert_zone(&mut self, zone: &str, assets: &XAssetBag) -> Result<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .map_err(|e| db_error(file_line_col!(), e))?;
+
+        tx.execute("DELETE FROM zones WHERE name = ?1", params![zone])
+            .map_err(|e| db_error(file_line_col!(), e))?;
+        tx.execute("INSERT INTO zones (name) VALUES (?1)", params![zone])
+            .map_err(|e| db_error(file_line_col!(), e))?;
+        let zone_id = tx.last_insert_rowid();
+
+        {
+            let mut insert_asset = tx
+                .prepare(
+                    "INSERT INTO assets (zone_id, type, name, size, hash)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )
+                .map_err(|e| db_error(file_line_col!(), e))?;
+            let mut insert_dependency = tx
+                .prepare("INSERT INTO dependencies (asset_id, type, name) VALUES (?1, ?2, ?3)")
+                .map_err(|e| db_error(file_line_col!(), e))?;
+
+            for asset in assets.iter() {
+                let Some(name) = asset.name() else {
+                    continue;
+                };
+
+                let hash = asset.stable_hash()? as i64;
+                let asset_id = insert_asset
+                    .insert(params![
+                        zone_id,
+                        type_name(asset.asset_type()),
+                        name,
+                        asset.estimated_size() as i64,
+                        hash
+                    ])
+                    .map_err(|e| db_error(file_line_col!(), e))?;
+
+                for (dep_type, dep_name) in asset.dependencies() {
+                    insert_dependency
+                        .execute(params![asset_id, type_name(dep_type), dep_name])
+                        .map_err(|e| db_error(file_line_col!(), e))?;
+                }
+            }
+        }
+
+        tx.commit().map_err(|e| db_error(file_line_col!(), e))
+    }
+
+    /// Deserializes and indexes every Fastfile in `batch` (see
+    /// [`Batch::run`]), each as the zone named after its file stem.
+    ///
+    /// Fastfiles that fail to deserialize are skipped and recorded in the
+    /// returned [`BatchReport`], but returns [`Err`] if any Fastfile fails to
+    /// be indexed.
+    pub fn insert_batch(&mut self, batch: &Batch) -> Result<BatchReport> {
+        let mut res = Ok(());
+
+        let report = batch.run(|path, bag| {
+            if res.is_err() {
+                return;
+            }
+            let zone = path.file_stem().unwrap_or_default().to_string_lossy();
+            res = self.insert_zone(&zone, &bag);
+        })?;
+
+        res.map(|_| report)
+    }
+
+    /// The names of every indexed zone, in order.
+    pub fn zones(&self) -> Result<Vec<String>> {
+        self.query_names("SELECT name FROM zones ORDER BY name", params![])
+    }
+
+    /// The names of every zone that contains the asset with `asset_type`
+    /// and `name`, in order.
+    pub fn zones_containing(&self, asset_type: XAssetType, name: &str) -> Result<Vec<String>> {
+        self.query_names(
+            "SELECT DISTINCT zones.name FROM assets
+             JOIN zones ON zones.id = assets.zone_id
+             WHERE assets.type = ?1 AND assets.name = ?2
+             ORDER BY zones.name",
+            params![type_name(asset_type), name],
+        )
+    }
+
+    /// The [`StableHash`] of the asset with `asset_type` and `name` in
+    /// `zone`, if it's indexed.
+    pub fn hash(&self, zone: &str, asset_type: XAssetType, name: &str) -> Result<Option<u64>> {
+        self.conn
+            .query_row(
+                "SELECT assets.hash FROM assets
+                 JOIN zones ON zones.id = assets.zone_id
+                 WHERE zones.name = ?1 AND assets.type = ?2 AND assets.name = ?3",
+                params![zone, type_name(asset_type), name],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()
+            .map(|hash| hash.map(|hash| hash as u64))
+            .map_err(|e| db_error(file_line_col!(), e))
+    }
+
+    /// The type and name of every asset that depends on the asset with
+    /// `asset_type` and `name` (see
+    /// [`XAsset::dependencies`](t5_xfile_defs::xasset::XAsset::dependencies)),
+    /// in any zone, in order.
+    pub fn dependents(&self, asset_type: XAssetType, name: &str) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT assets.type, assets.name FROM dependencies
+                 JOIN assets ON assets.id = dependencies.asset_id
+                 WHERE dependencies.type = ?1 AND dependencies.name = ?2
+                 ORDER BY assets.type, assets.name",
+            )
+            .map_err(|e| db_error(file_line_col!(), e))?;
+
+        stmt.query_map(params![type_name(asset_type), name], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|e| db_error(file_line_col!(), e))
+    }
+
+    fn query_names(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare(sql)
+            .map_err(|e| db_error(file_line_col!(), e))?;
+
+        stmt.query_map(params, |row| row.get(0))
+            .and_then(|rows| rows.collect())
+            .map_err(|e| db_error(file_line_col!(), e))
+    }
+}
@@ -0,0 +1,556 @@
+//! Canonical text summaries of assets, for snapshot testing.
+//!
+//! The Fastfiles this crate reads can't be shared, so it can't check its own
+//! output for them. Downstream users who own them can instead snapshot
+//! [`summarize`]'s output for a few representative assets (e.g. with
+//! `insta::assert_snapshot!`) and be told when a new version of the crate
+//! decodes them differently.
+//!
+//! Summaries are meant to stay the same across versions for assets that
+//! decode the same, rather than to be read back:
+//!
+//! * Struct fields and map entries are sorted by name, so reordering a
+//!   struct's fields or iterating a `HashMap` doesn't change them.
+//! * Floats are rounded to [`SummaryOptions::float_digits`] decimal places,
+//!   and `-0` is printed as `0`, so they don't depend on how the last bit of
+//!   an `f32` happens to be rounded.
+//! * Sequences longer than [`SummaryOptions::max_elements`] (e.g. vertex or
+//!   pixel data) are replaced by their length and a hash of their summary,
+//!   which keeps snapshots small while still catching changes to them.
+//!
+//! Every value is on its own line, so snapshot diffs point at the field
+//! that changed.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Serialize, ser};
+use t5_xfile_defs::{Error, ErrorKind, Result};
+
+use crate::file_line_col;
+
+/// How [`summarize`] prints values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SummaryOptions {
+    /// The number of decimal places floats are rounded to.
+    pub float_digits: usize,
+    /// Sequences with more elements than this are summarized by their length
+    /// and hash instead of listing each element. [`None`] lists every
+    /// element.
+    pub max_elements: Option<usize>,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        Self {
+            float_digits: 4,
+            max_elements: Some(32),
+        }
+    }
+}
+
+impl SummaryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_float_digits(mut self, float_digits: usize) -> Self {
+        self.float_digits = float_digits;
+        self
+    }
+
+    pub fn with_max_elements(mut self, max_elements: Option<usize>) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+}
+
+/// Summarizes `value` with [`SummaryOptions::default`].
+///
+/// Works for any [`Serialize`] type, e.g. an
+/// [`XAsset`](t5_xfile_defs::xasset::XAsset) or a single field of one.
+pub fn summarize<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    summarize_with(value, SummaryOptions::default())
+}
+
+/// Summarizes `value` as described in the [module docs](self).
+pub fn summarize_with<T: Serialize + ?Sized>(value: &T, options: SummaryOptions) -> Result<String> {
+    let value = value.serialize(ValueSerializer)?;
+    let mut s = String::new();
+    Printer { options }.print(&mut s, &value, 0);
+    Ok(s)
+}
+
+/// A serialized value, before it's printed.
+enum Value {
+    Unit,
+    /// `None` or a unit enum variant, printed without quotes.
+    Ident(&'static str),
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    Seq(Vec<Value>),
+    /// Struct fields and map entries, sorted by key.
+    Map(BTreeMap<String, Value>),
+    /// A newtype, tuple, or struct enum variant.
+    Variant(&'static str, Box<Value>),
+}
+
+struct Printer {
+    options: SummaryOptions,
+}
+
+impl Printer {
+    fn too_long(&self, len: usize) -> bool {
+        self.options.max_elements.is_some_and(|max| len > max)
+    }
+
+    fn float(&self, f: f64) -> String {
+        if f.is_nan() {
+            return String::from("NaN");
+        } else if f.is_infinite() {
+            return String::from(if f < 0.0 { "-inf" } else { "inf" });
+        }
+
+        let mut s = format!("{:.*}", self.options.float_digits, f);
+        if s.contains('.') {
+            s.truncate(s.trim_end_matches('0').trim_end_matches('.').len());
+        }
+        if s == "-0" {
+            s.remove(0);
+        }
+        s
+    }
+
+    /// Prints `value` at the end of the current line, followed by a newline.
+    fn print(&self, s: &mut String, value: &Value, indent: usize) {
+        match value {
+            Value::Unit => s.push_str("()\n"),
+            Value::Ident(i) => writeln!(s, "{i}").unwrap(),
+            Value::Bool(b) => writeln!(s, "{b}").unwrap(),
+            Value::Int(i) => writeln!(s, "{i}").unwrap(),
+            Value::Float(f) => writeln!(s, "{}", self.float(*f)).unwrap(),
+            Value::Char(c) => writeln!(s, "{c:?}").unwrap(),
+            Value::Str(v) => writeln!(s, "{v:?}").unwrap(),
+            Value::Bytes(b) if self.too_long(b.len()) => {
+                writeln!(s, "<{} bytes, hash {:016x}>", b.len(), fnv1a(b)).unwrap()
+            }
+            Value::Bytes(b) => writeln!(s, "{b:02x?}").unwrap(),
+            Value::Seq(v) if v.is_empty() => s.push_str("[]\n"),
+            Value::Seq(v) if self.too_long(v.len()) => {
+                // Hashes the full summary, so the options decide which
+                // changes it picks up just like they would for a listing
+                let mut elements = String::new();
+                let printer = Printer {
+                    options: self.options.with_max_elements(None),
+                };
+                for e in v {
+                    printer.print(&mut elements, e, 0);
+                }
+                let hash = fnv1a(elements.as_bytes());
+                writeln!(s, "<{} elements, hash {hash:016x}>", v.len()).unwrap();
+            }
+            Value::Seq(v) => {
+                s.push('\n');
+                for (i, e) in v.iter().enumerate() {
+                    write!(s, "{:indent$}[{i}]: ", "", indent = indent + 2).unwrap();
+                    self.print(s, e, indent + 2);
+                }
+            }
+            Value::Map(m) if m.is_empty() => s.push_str("{}\n"),
+            Value::Map(m) => {
+                s.push('\n');
+                for (k, v) in m {
+                    write!(s, "{:indent$}{k}: ", "", indent = indent + 2).unwrap();
+                    self.print(s, v, indent + 2);
+                }
+            }
+            Value::Variant(name, v) => {
+                write!(s, "{name} ").unwrap();
+                self.print(s, v, indent);
+            }
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which is simple enough to never change.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF29CE484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001B3)
+    })
+}
+
+/// The error of [`ValueSerializer`], which only occurs when a [`Serialize`]
+/// impl raises one.
+#[derive(Debug)]
+struct SummaryError(Error);
+
+impl core::fmt::Display for SummaryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for SummaryError {}
+
+impl ser::Error for SummaryError {
+    fn custom<T: core::fmt::Display>(msg: T) -> Self {
+        Self(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(msg.to_string()),
+        ))
+    }
+}
+
+type SerResult<T> = core::result::Result<T, SummaryError>;
+
+impl From<SummaryError> for Error {
+    fn from(value: SummaryError) -> Self {
+        value.0
+    }
+}
+
+/// Map keys are printed as-is if they're strings, and as their one-line
+/// summary otherwise.
+fn key(value: Value) -> String {
+    match value {
+        Value::Str(s) => s,
+        value => {
+            let mut s = String::new();
+            Printer {
+                options: SummaryOptions::default().with_max_elements(None),
+            }
+            .print(&mut s, &value, 0);
+            s.trim_end().replace('\n', " ")
+        }
+    }
+}
+
+struct ValueSerializer;
+
+struct SeqSerializer {
+    variant: Option<&'static str>,
+    elements: Vec<Value>,
+}
+
+struct MapSerializer {
+    variant: Option<&'static str>,
+    entries: BTreeMap<String, Value>,
+    key: Option<String>,
+}
+
+fn wrap(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => Value::Variant(variant, Box::new(value)),
+        None => value,
+    }
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> SerResult<Value> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_i16(self, v: i16) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_i32(self, v: i32) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_i64(self, v: i64) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_i128(self, v: i128) -> SerResult<Value> {
+        Ok(Value::Int(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_u16(self, v: u16) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_u32(self, v: u32) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_u64(self, v: u64) -> SerResult<Value> {
+        Ok(Value::Int(v as _))
+    }
+
+    fn serialize_u128(self, v: u128) -> SerResult<Value> {
+        // Nothing in an XFile is anywhere near this big, so a string is fine
+        // for the few that don't fit
+        Ok(match i128::try_from(v) {
+            Ok(v) => Value::Int(v),
+            Err(_) => Value::Str(v.to_string()),
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> SerResult<Value> {
+        Ok(Value::Float(v as _))
+    }
+
+    fn serialize_f64(self, v: f64) -> SerResult<Value> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> SerResult<Value> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> SerResult<Value> {
+        Ok(Value::Str(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> SerResult<Value> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> SerResult<Value> {
+        Ok(Value::Ident("None"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> SerResult<Value> {
+        Ok(Value::Variant("Some", Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> SerResult<Value> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SerResult<Value> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> SerResult<Value> {
+        Ok(Value::Ident(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerResult<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> SerResult<Value> {
+        Ok(Value::Variant(variant, Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> SerResult<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: None,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> SerResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> SerResult<SeqSerializer> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> SerResult<SeqSerializer> {
+        Ok(SeqSerializer {
+            variant: Some(variant),
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> SerResult<MapSerializer> {
+        Ok(MapSerializer {
+            variant: None,
+            entries: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> SerResult<MapSerializer> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> SerResult<MapSerializer> {
+        Ok(MapSerializer {
+            variant: Some(variant),
+            entries: BTreeMap::new(),
+            key: None,
+        })
+    }
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.elements.push(value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> SerResult<Value> {
+        Ok(wrap(self.variant, Value::Seq(self.elements)))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl MapSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> SerResult<()> {
+        self.entries.insert(key, value.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> SerResult<Value> {
+        Ok(wrap(self.variant, Value::Map(self.entries)))
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, k: &T) -> SerResult<()> {
+        self.key = Some(key(k.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> SerResult<()> {
+        let Some(key) = self.key.take() else {
+            return Err(ser::Error::custom("map value serialized before its key"));
+        };
+        self.insert(key, value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> SerResult<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = SummaryError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> SerResult<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> SerResult<Value> {
+        self.finish()
+    }
+}
@@ -0,0 +1,351 @@
+//! Synthesizes tiny, valid Fastfiles, so the deserializer can be tested
+//! without any (copyrighted) game data.
+//!
+//! Assets are written out byte by byte instead of through
+//! [`crate::serializer`], so the deserializer gets checked against the file
+//! format itself rather than against the serializer's idea of it.
+
+use t5_xfile_defs::{
+    XFILE_HEADER_MAGIC_0_RAW, XFILE_HEADER_MAGIC_U_RAW, XFILE_VERSION, XFilePlatform,
+    misc::string_table_hash, xasset::XAssetType,
+};
+
+/// Pointer value meaning "the data follows inline".
+const INLINE: u32 = 0xFFFFFFFF;
+
+/// Writes the primitives that make up a Fastfile in the platform's byte
+/// order.
+struct Writer {
+    bytes: Vec<u8>,
+    little_endian: bool,
+}
+
+impl Writer {
+    fn new(platform: XFilePlatform) -> Self {
+        Self {
+            bytes: Vec::new(),
+            little_endian: platform.is_le(),
+        }
+    }
+
+    fn u32(&mut self, v: u32) -> &mut Self {
+        if self.little_endian {
+            self.bytes.extend_from_slice(&v.to_le_bytes());
+        } else {
+            self.bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        self
+    }
+
+    fn i32(&mut self, v: i32) -> &mut Self {
+        self.u32(v as u32)
+    }
+
+    fn f32(&mut self, v: f32) -> &mut Self {
+        self.u32(v.to_bits())
+    }
+
+    fn i16(&mut self, v: i16) -> &mut Self {
+        if self.little_endian {
+            self.bytes.extend_from_slice(&v.to_le_bytes());
+        } else {
+            self.bytes.extend_from_slice(&v.to_be_bytes());
+        }
+        self
+    }
+
+    /// Writes a pointer to `s`, which is [`INLINE`] unless `s` is empty.
+    fn str_ptr(&mut self, s: &str) -> &mut Self {
+        self.u32(if s.is_empty() { 0 } else { INLINE })
+    }
+
+    /// Writes the NUL-terminated contents of `s` if [`Self::str_ptr`] would
+    /// have made it inline.
+    fn str(&mut self, s: &str) -> &mut Self {
+        if !s.is_empty() {
+            self.bytes.extend_from_slice(s.as_bytes());
+            self.bytes.push(b'\0');
+        }
+        self
+    }
+
+    fn bytes(&mut self, b: &[u8]) -> &mut Self {
+        self.bytes.extend_from_slice(b);
+        self
+    }
+}
+
+/// A Fastfile built up asset by asset. [`Self::to_bytes`] produces a file
+/// that can be handed straight to
+/// [`T5XFileDeserializerBuilder::from_bytes`](crate::T5XFileDeserializerBuilder::from_bytes).
+#[derive(Clone, Debug)]
+pub struct SyntheticXFile {
+    platform: XFilePlatform,
+    script_strings: Vec<String>,
+    assets: Vec<(XAssetType, Option<Vec<u8>>)>,
+}
+
+impl SyntheticXFile {
+    pub fn new(platform: XFilePlatform) -> Self {
+        Self {
+            platform,
+            script_strings: Vec::new(),
+            assets: Vec::new(),
+        }
+    }
+
+    /// A Fastfile with one null asset of each type the deserializer supports
+    /// (see [`XAssetType::support_status`]), in [`XAssetType::ALL`] order.
+    pub fn every_asset_type(platform: XFilePlatform) -> Self {
+        XAssetType::ALL
+            .into_iter()
+            .filter(|t| t.support_status().can_deserialize())
+            .fold(Self::new(platform), Self::with_null_asset)
+    }
+
+    pub fn platform(&self) -> XFilePlatform {
+        self.platform
+    }
+
+    pub fn with_script_string(mut self, string: impl Into<String>) -> Self {
+        self.script_strings.push(string.into());
+        self
+    }
+
+    /// Adds an asset of type `asset_type` whose header pointer is null.
+    pub fn with_null_asset(mut self, asset_type: XAssetType) -> Self {
+        self.assets.push((asset_type, None));
+        self
+    }
+
+    /// Adds an asset of type `asset_type` whose header follows inline.
+    /// `data` is the header and everything loaded after it, exactly as it
+    /// should appear in the payload (in the platform's byte order).
+    pub fn with_raw_asset(mut self, asset_type: XAssetType, data: Vec<u8>) -> Self {
+        self.assets.push((asset_type, Some(data)));
+        self
+    }
+
+    pub fn with_rawfile(self, name: &str, contents: &[u8]) -> Self {
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .i32(contents.len() as _)
+            .u32(INLINE)
+            .str(name)
+            .bytes(contents)
+            .bytes(b"\0");
+        self.with_raw_asset(XAssetType::RAWFILE, w.bytes)
+    }
+
+    pub fn with_localize_entry(self, name: &str, value: &str) -> Self {
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(value).str_ptr(name).str(value).str(name);
+        self.with_raw_asset(XAssetType::LOCALIZE_ENTRY, w.bytes)
+    }
+
+    pub fn with_map_ents(self, name: &str, entity_string: &str) -> Self {
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .u32(INLINE)
+            .u32(entity_string.len() as u32 + 1)
+            .str(name)
+            .str(entity_string);
+        self.with_raw_asset(XAssetType::MAP_ENTS, w.bytes)
+    }
+
+    /// Adds a pack index with `entries`, each a hash, offset, and size.
+    pub fn with_pack_index(self, name: &str, entries: &[(u32, u32, u32)]) -> Self {
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .u32(0)
+            .u32(0)
+            .u32(entries.len() as _)
+            .u32(0)
+            .u32(0)
+            .u32(if entries.is_empty() { 0 } else { INLINE })
+            .str(name);
+        for (hash, offset, size) in entries {
+            w.u32(*hash).u32(*offset).u32(*size);
+        }
+        self.with_raw_asset(XAssetType::PACKINDEX, w.bytes)
+    }
+
+    /// Adds a string table with `column_count` columns. `cells` are in
+    /// row-major order, and their count must be a multiple of
+    /// `column_count`.
+    pub fn with_string_table(self, name: &str, column_count: usize, cells: &[&str]) -> Self {
+        assert!(column_count != 0 && cells.len().is_multiple_of(column_count));
+
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .i32(column_count as _)
+            .i32((cells.len() / column_count) as _)
+            .u32(if cells.is_empty() { 0 } else { INLINE })
+            .u32(if cells.is_empty() { 0 } else { INLINE })
+            .str(name);
+        for cell in cells {
+            w.str_ptr(cell).i32(string_table_hash(cell));
+        }
+        for cell in cells {
+            w.str(cell);
+        }
+        for i in 0..cells.len() {
+            w.i16(i as _);
+        }
+        self.with_raw_asset(XAssetType::STRINGTABLE, w.bytes)
+    }
+
+    /// Adds model pieces with null models, at `offsets`.
+    pub fn with_xmodel_pieces(self, name: &str, offsets: &[[f32; 3]]) -> Self {
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .u32(offsets.len() as _)
+            .u32(if offsets.is_empty() { 0 } else { INLINE })
+            .str(name);
+        for offset in offsets {
+            w.u32(0);
+            for v in offset {
+                w.f32(*v);
+            }
+        }
+        self.with_raw_asset(XAssetType::XMODELPIECES, w.bytes)
+    }
+
+    /// Adds a menu with nothing but a name and an `on_key` list of
+    /// `key_handlers` handlers, with keys counting up from 0.
+    pub fn with_menu(self, name: &str, key_handlers: usize) -> Self {
+        self.with_menu_items(name, key_handlers, &[])
+    }
+
+    /// Same as [`Self::with_menu`], but the menu also has an item for each
+    /// of `parents`, with nothing but its `parent` pointer set to it.
+    pub fn with_menu_items(self, name: &str, key_handlers: usize, parents: &[u32]) -> Self {
+        let clients = self.platform.max_local_clients();
+        let window = 160 + 4 * clients;
+        // Everything up to on_key, which follows on_event
+        let before_on_key = 264 + 8 * clients;
+        let before_item_count = window + 12;
+        let before_items = 116;
+
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name)
+            .bytes(&vec![0; before_item_count - 4])
+            .i32(parents.len() as _)
+            .bytes(&vec![0; before_on_key - before_item_count - 4])
+            .u32(if key_handlers == 0 { 0 } else { INLINE })
+            .bytes(&vec![0; before_items])
+            .u32(if parents.is_empty() { 0 } else { INLINE })
+            .bytes(&[0; 4])
+            .str(name);
+        for key in 0..key_handlers {
+            let next = if key + 1 == key_handlers { 0 } else { INLINE };
+            w.i32(key as _).u32(0).u32(next);
+        }
+        for _ in parents {
+            w.u32(INLINE);
+        }
+        // everything up to parent, then everything after it
+        for parent in parents {
+            w.bytes(&vec![0; window + 32]).u32(*parent).bytes(&[0; 72]);
+        }
+        self.with_raw_asset(XAssetType::MENU, w.bytes)
+    }
+
+    /// Adds a map's world with nothing but a name, zeroed terrain scorch
+    /// images (which can't be null), and two water buffers, whose contents
+    /// are `water_buffers`.
+    pub fn with_gfx_world(self, name: &str, water_buffers: [&[[f32; 4]]; 2]) -> Self {
+        // Everything else is a count or null pointer. The sun settings
+        // before the images are per local client.
+        let before_images = 268 + 108 * self.platform.max_local_clients();
+        let images = 31;
+        let image_len = 52;
+        let before_water_buffers = 648 - 4 * images;
+        let after_water_buffers = 44;
+
+        let mut w = Writer::new(self.platform);
+        w.str_ptr(name).bytes(&vec![0; before_images - 4]);
+        for _ in 0..images {
+            w.u32(INLINE);
+        }
+        w.bytes(&vec![0; before_water_buffers]);
+        for buffer in water_buffers {
+            w.u32(buffer.len() as _)
+                .u32(if buffer.is_empty() { 0 } else { INLINE });
+        }
+        w.bytes(&vec![0; after_water_buffers])
+            .str(name)
+            .bytes(&vec![0; images * image_len]);
+        for v in water_buffers.into_iter().flatten().flatten() {
+            w.f32(*v);
+        }
+        self.with_raw_asset(XAssetType::GFXWORLD, w.bytes)
+    }
+
+    /// Returns the asset types, in order.
+    pub fn asset_types(&self) -> impl Iterator<Item = XAssetType> + '_ {
+        self.assets.iter().map(|(t, _)| *t)
+    }
+
+    /// Returns the inflated payload.
+    pub fn payload(&self) -> Vec<u8> {
+        let mut w = Writer::new(self.platform);
+
+        let data_len = self
+            .script_strings
+            .iter()
+            .map(|s| s.len() + 1)
+            .sum::<usize>()
+            + self
+                .assets
+                .iter()
+                .map(|(_, d)| d.as_ref().map_or(0, Vec::len))
+                .sum::<usize>();
+
+        // XFile. None of the block sizes are checked by the deserializer.
+        w.u32(data_len as _);
+        for _ in 0..8 {
+            w.u32(0);
+        }
+
+        // XAssetList
+        w.u32(self.script_strings.len() as _)
+            .u32(if self.script_strings.is_empty() {
+                0
+            } else {
+                INLINE
+            })
+            .u32(self.assets.len() as _)
+            .u32(if self.assets.is_empty() { 0 } else { INLINE });
+
+        for s in self.script_strings.iter() {
+            w.str_ptr(s);
+        }
+        for s in self.script_strings.iter() {
+            w.str(s);
+        }
+
+        for (asset_type, data) in self.assets.iter() {
+            w.u32(*asset_type as u32)
+                .u32(if data.is_some() { INLINE } else { 0 });
+        }
+        for data in self.assets.iter().filter_map(|(_, d)| d.as_ref()) {
+            w.bytes(data);
+        }
+
+        w.bytes
+    }
+
+    /// Returns the complete Fastfile (header and deflated payload).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::new(self.platform);
+        w.bytes(if self.platform.is_console() {
+            &XFILE_HEADER_MAGIC_0_RAW
+        } else {
+            &XFILE_HEADER_MAGIC_U_RAW
+        })
+        .u32(XFILE_VERSION)
+        .bytes(&deflate::deflate_bytes_zlib(&self.payload()));
+        w.bytes
+    }
+}
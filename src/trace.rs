@@ -0,0 +1,178 @@
+//! Records the offset and length of everything the deserializer reads from
+//! the inflated payload, down to individual struct fields.
+//!
+//! Meant as a companion for reverse engineering: a [`Trace`] can be
+//! exported as JSON (see [`Trace::to_json`]) and lined up against the
+//! payload in a hex editor. Enable it with
+//! [`T5XFileDeserializerBuilder::with_trace`](crate::T5XFileDeserializerBuilder::with_trace).
+//!
+//! Tracing always reads through the fixed-layout reader, whether or not the
+//! `fast-reader` feature is enabled.
+
+use serde::Serialize;
+
+use t5_xfile_defs::xasset::XAssetType;
+
+use crate::reader::TracedField;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TraceEntry {
+    /// Absolute offset within the inflated payload.
+    pub offset: u64,
+    pub len: u64,
+    /// 0 for the values the deserializer loaded, 1 for their fields, 2 for
+    /// their fields' fields, and so on.
+    pub depth: usize,
+    /// The loaded type, followed by the path of the field, e.g.
+    /// `GfxPortalRaw.writable.hull_points`.
+    pub path: String,
+    /// Index into [`Trace::assets`] of the asset being deserialized when this
+    /// entry was read, or [`None`] for the asset list and script strings.
+    pub asset: Option<usize>,
+}
+
+impl TraceEntry {
+    pub const fn end(&self) -> u64 {
+        self.offset + self.len
+    }
+
+    pub const fn contains(&self, offset: u64) -> bool {
+        offset >= self.offset && offset < self.end()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TracedAsset {
+    pub asset_type: XAssetType,
+    pub name: Option<String>,
+    /// Offset of the asset's header within the inflated payload.
+    pub offset: u64,
+    /// Length of the header and everything loaded after it.
+    pub len: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Trace {
+    entries: Vec<TraceEntry>,
+    assets: Vec<TracedAsset>,
+    #[serde(skip)]
+    current_asset: Option<usize>,
+}
+
+impl Trace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every entry, in payload order. Fields come right after the value
+    /// they're a part of.
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    pub fn assets(&self) -> &[TracedAsset] {
+        &self.assets
+    }
+
+    /// Returns the innermost entry containing `offset`.
+    pub fn lookup(&self, offset: u64) -> Option<&TraceEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.contains(offset))
+            .max_by_key(|e| e.depth)
+    }
+
+    /// Returns every entry of the asset at `index` (into [`Self::assets`]).
+    pub fn asset_entries(&self, index: usize) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter().filter(move |e| e.asset == Some(index))
+    }
+
+    /// Exports the trace as a JSON object with an `entries` array and an
+    /// `assets` array, each element of which has the same fields as
+    /// [`TraceEntry`] and [`TracedAsset`] respectively.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub(crate) fn begin_asset(&mut self) {
+        self.current_asset = Some(self.assets.len());
+    }
+
+    pub(crate) fn end_asset(
+        &mut self,
+        asset_type: XAssetType,
+        name: Option<String>,
+        offset: u64,
+        end: u64,
+    ) {
+        self.assets.push(TracedAsset {
+            asset_type,
+            name,
+            offset,
+            len: end - offset,
+        });
+        self.current_asset = None;
+    }
+
+    /// Records a value of type `name`, read from `offset` to `end`, along
+    /// with its fields.
+    pub(crate) fn push(&mut self, name: &str, offset: u64, end: u64, fields: Vec<TracedField>) {
+        if end == offset {
+            return;
+        }
+
+        // strings and arrays of primitives are loaded one element at a
+        // time, but are much more useful as one entry
+        if fields.is_empty()
+            && let Some(last) = self.entries.last_mut()
+            && last.depth == 0
+            && last.path == name
+            && last.end() == offset
+            && last.asset == self.current_asset
+        {
+            last.len += end - offset;
+            return;
+        }
+
+        self.entries.push(TraceEntry {
+            offset,
+            len: end - offset,
+            depth: 0,
+            path: name.to_owned(),
+            asset: self.current_asset,
+        });
+        self.entries.extend(fields.into_iter().map(|f| TraceEntry {
+            offset: f.offset as _,
+            len: f.len as _,
+            depth: f.depth,
+            path: format!("{name}{}", f.path),
+            asset: self.current_asset,
+        }));
+    }
+}
+
+/// Strips the module paths and elided lifetimes out of
+/// [`core::any::type_name`], e.g.
+/// `t5_xfile_defs::util::Ptr32<'_, t5_xfile_defs::misc::RawFileRaw<'_>>`
+/// becomes `Ptr32<RawFileRaw>`.
+pub(crate) fn short_type_name<T: ?Sized>() -> String {
+    let mut short = String::new();
+    let mut ident = String::new();
+    let mut chars = core::any::type_name::<T>().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            ident.clear();
+        } else if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+        } else {
+            short.push_str(&ident);
+            ident.clear();
+            short.push(c);
+        }
+    }
+
+    short.push_str(&ident);
+    short.replace("<'_>", "").replace("'_, ", "")
+}
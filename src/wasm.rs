@@ -0,0 +1,28 @@
+//! Bindings for `wasm32-unknown-unknown`, for use with `wasm-bindgen`.
+
+use wasm_bindgen::prelude::*;
+
+use t5_xfile_defs::XFilePlatform;
+
+use crate::T5XFileDeserializerBuilder;
+
+/// Deserializes every asset in the Windows Fastfile `bytes`, returning them
+/// as an array of objects with the same shape as [`XAsset`]'s serde
+/// representation.
+///
+/// Only Windows Fastfiles are supported, same as with the default settings
+/// of [`T5XFileDeserializerBuilder`].
+///
+/// [`XAsset`]: t5_xfile_defs::xasset::XAsset
+#[wasm_bindgen(js_name = parseFastfile)]
+pub fn parse_fastfile(bytes: &[u8]) -> Result<JsValue, JsError> {
+    let assets = T5XFileDeserializerBuilder::from_bytes(bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+        .map_err(|e| JsError::new(&e.to_string()))?;
+
+    serde_wasm_bindgen::to_value(&assets).map_err(|e| JsError::new(&e.to_string()))
+}
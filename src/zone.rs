@@ -0,0 +1,340 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "hash")]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{Error, ErrorKind, GameDir, Result, T5XFileDeserializerBuilder, file_line_col};
+
+#[cfg(feature = "hash")]
+use t5_xfile_defs::hash::StableHash;
+use t5_xfile_defs::{
+    XFilePlatform,
+    xasset::{XAsset, XAssetBag, XAssetType},
+};
+
+/// A set of Fastfiles that get loaded together, like the engine does when it
+/// loads a map (e.g. `common_mp.ff`, `patch_mp.ff`, then `mp_nuked.ff`).
+///
+/// Zones are deserialized in the order they're added, and every asset is
+/// inserted into a single [`XAssetBag`]. Same as in-engine, an asset from a
+/// later zone replaces an earlier asset with the same type and name.
+pub struct ZoneSet {
+    zones: Vec<PathBuf>,
+    platform: XFilePlatform,
+    silent: bool,
+    allow_unsupported_platforms: bool,
+    game_dir: Option<PathBuf>,
+}
+
+impl ZoneSet {
+    pub fn new(platform: XFilePlatform) -> Self {
+        Self {
+            zones: Vec::new(),
+            platform,
+            silent: true,
+            allow_unsupported_platforms: false,
+            game_dir: None,
+        }
+    }
+
+    /// Appends a zone. Zones added later take priority over zones added
+    /// earlier.
+    pub fn with_zone(mut self, path: impl AsRef<Path>) -> Self {
+        self.zones.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    pub fn with_allow_unsupported_platforms(mut self, allow_unsupported_platforms: bool) -> Self {
+        self.allow_unsupported_platforms = allow_unsupported_platforms;
+        self
+    }
+
+    /// Appends the zone `name` from the game install at `dir` (see
+    /// [`GameDir::find_zone`]). Returns [`Err`] if there's no such zone.
+    pub fn with_zone_name(self, dir: &GameDir, name: &str) -> Result<Self> {
+        let path = dir.find_zone(name)?.ok_or_else(|| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no zone {name} in {}", dir.root().display()),
+                )),
+            )
+        })?;
+        Ok(self.with_zone(path))
+    }
+
+    /// Loads every zone's companion files from the game install at `dir`
+    /// (see [`T5XFileDeserializerBuilder::with_game_dir`]).
+    pub fn with_game_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.game_dir = Some(dir.into());
+        self
+    }
+
+    pub fn zones(&self) -> &[PathBuf] {
+        &self.zones
+    }
+
+    /// Deserializes every zone, in order, into one [`XAssetBag`].
+    ///
+    /// Returns [`Err`] as soon as any zone fails to deserialize.
+    pub fn deserialize(&self) -> Result<XAssetBag> {
+        let mut bag = XAssetBag::new();
+
+        for zone in &self.zones {
+            let mut overridden = 0;
+            self.for_each_asset(zone, |asset| {
+                if bag.insert(asset).is_some() {
+                    overridden += 1;
+                }
+            })?;
+
+            if !self.silent {
+                println!(
+                    "Zone {} loaded ({} asset{} overridden).",
+                    zone.display(),
+                    overridden,
+                    if overridden == 1 { "" } else { "s" }
+                );
+            }
+        }
+
+        Ok(bag)
+    }
+
+    /// Deserializes every zone, in order, keeping only one copy of each
+    /// asset that's identical across zones (see [`DedupedZones`]). Assets
+    /// are compared by their
+    /// [`StableHash`](t5_xfile_defs::hash::StableHash), and duplicates are
+    /// dropped as soon as they're deserialized, so only the unique assets
+    /// are ever kept in memory at once.
+    ///
+    /// Returns [`Err`] as soon as any zone fails to deserialize.
+    #[cfg(feature = "hash")]
+    pub fn dedupe(&self) -> Result<DedupedZones> {
+        let mut deduped = DedupedZones {
+            zones: self.zones.clone(),
+            assets: Vec::new(),
+            zone_assets: Vec::new(),
+            by_hash: HashMap::new(),
+        };
+
+        for (zone_index, zone) in self.zones.iter().enumerate() {
+            let mut zone_assets = BTreeMap::new();
+            let mut res = Ok(());
+            self.for_each_asset(zone, |asset| {
+                if res.is_ok() {
+                    res = deduped.insert(zone_index, asset).map(|(key, index)| {
+                        zone_assets.insert(key, index);
+                    });
+                }
+            })?;
+            res?;
+            deduped.zone_assets.push(zone_assets);
+
+            if !self.silent {
+                println!(
+                    "Zone {} loaded ({} unique assets so far).",
+                    zone.display(),
+                    deduped.assets.len()
+                );
+            }
+        }
+
+        Ok(deduped)
+    }
+
+    /// Deserializes every zone (see [`Self::deserialize`]) and splits the
+    /// assets up by type. Each type's assets come with everything they
+    /// depend on (see [`XAssetBag::closure`]), so each part can be loaded on
+    /// its own, e.g. a pack of just a map's weapons.
+    pub fn split_by_type(&self) -> Result<BTreeMap<XAssetType, XAssetBag>> {
+        let bag = self.deserialize()?;
+
+        let mut types = bag.iter().map(XAsset::asset_type).collect::<Vec<_>>();
+        types.sort();
+        types.dedup();
+
+        Ok(types
+            .into_iter()
+            .map(|asset_type| {
+                let roots = bag.iter_type(asset_type).filter_map(|a| a.name());
+                (
+                    asset_type,
+                    bag.closure(roots.map(|name| (asset_type, name))),
+                )
+            })
+            .collect())
+    }
+
+    /// Deserializes every zone (see [`Self::deserialize`]) into one set of
+    /// assets that can be loaded as a single zone, with every asset after
+    /// the assets it depends on.
+    pub fn merge(&self) -> Result<XAssetBag> {
+        let bag = self.deserialize()?;
+        Ok(bag.closure(bag.iter().filter_map(|a| Some((a.asset_type(), a.name()?)))))
+    }
+
+    fn for_each_asset(&self, zone: &Path, mut f: impl FnMut(XAsset)) -> Result<()> {
+        if !self.silent {
+            println!("Loading zone {}...", zone.display());
+        }
+
+        let mut file = std::fs::File::open(zone)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))?;
+
+        let de = T5XFileDeserializerBuilder::from_file(
+            &mut file,
+            self.platform,
+            self.allow_unsupported_platforms,
+        )
+        .with_silent(self.silent);
+        let de = match &self.game_dir {
+            Some(dir) => de.with_game_dir(dir),
+            None => de,
+        };
+
+        let mut de = de.build()?.inflate()?.no_cache()?;
+        while let Some(asset) = de.deserialize_next()? {
+            f(asset);
+        }
+
+        Ok(())
+    }
+}
+
+/// Serializes `assets` into a new Fastfile at `path`, e.g. one of the parts
+/// from [`ZoneSet::split_by_type`] or the result of [`ZoneSet::merge`].
+#[cfg(feature = "serializer")]
+pub fn write_zone(
+    path: impl AsRef<Path>,
+    platform: XFilePlatform,
+    assets: impl IntoIterator<Item = XAsset>,
+) -> Result<()> {
+    let mut ser = crate::serializer::T5XFileSerializerBuilder::new(platform)
+        .with_silent(true)
+        .build()?;
+    ser.serialize_assets(assets.into_iter())?;
+    let bytes = ser.deflate()?;
+    std::fs::write(path, bytes).map_err(|e| Error::new(file_line_col!(), ErrorKind::Io(e)))
+}
+
+/// The assets of a [`ZoneSet`], with only one copy of each asset that's
+/// identical across zones (see [`ZoneSet::dedupe`]).
+#[cfg(feature = "hash")]
+#[derive(Clone, Debug)]
+pub struct DedupedZones {
+    zones: Vec<PathBuf>,
+    /// Every unique asset, along with the index of every zone it's in.
+    assets: Vec<(XAsset, Vec<usize>)>,
+    /// Each zone's assets, as indices into `assets`.
+    zone_assets: Vec<BTreeMap<(XAssetType, String), usize>>,
+    /// Indices into `assets`. Almost always one per hash, unless two
+    /// different assets' hashes collide.
+    by_hash: HashMap<u64, Vec<usize>>,
+}
+
+#[cfg(feature = "hash")]
+impl DedupedZones {
+    fn insert(
+        &mut self,
+        zone_index: usize,
+        asset: XAsset,
+    ) -> Result<((XAssetType, String), usize)> {
+        let key = (
+            asset.asset_type(),
+            asset.name().unwrap_or_default().to_string(),
+        );
+        let candidates = self.by_hash.entry(asset.stable_hash()?).or_default();
+
+        let existing = candidates
+            .iter()
+            .copied()
+            .find(|&i| self.assets[i].0 == asset);
+        let index = match existing {
+            Some(index) => {
+                let zones = &mut self.assets[index].1;
+                if zones.last() != Some(&zone_index) {
+                    zones.push(zone_index);
+                }
+                index
+            }
+            None => {
+                candidates.push(self.assets.len());
+                self.assets.push((asset, vec![zone_index]));
+                self.assets.len() - 1
+            }
+        };
+
+        Ok((key, index))
+    }
+
+    pub fn zones(&self) -> &[PathBuf] {
+        &self.zones
+    }
+
+    /// The number of unique assets across every zone.
+    pub fn unique_count(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// The number of assets across every zone, counting each zone's copy of
+    /// an asset separately.
+    pub fn total_count(&self) -> usize {
+        self.zone_assets.iter().map(BTreeMap::len).sum()
+    }
+
+    /// Every unique asset, along with the paths of every zone it's in, in
+    /// the order they were first deserialized.
+    pub fn iter(&self) -> impl Iterator<Item = (&XAsset, impl Iterator<Item = &Path>)> {
+        self.assets
+            .iter()
+            .map(|(asset, zones)| (asset, zones.iter().map(|&zone| self.zones[zone].as_path())))
+    }
+
+    /// Every asset that's in more than one zone, along with how many zones
+    /// it's in.
+    pub fn duplicates(&self) -> impl Iterator<Item = (&XAsset, usize)> {
+        self.assets
+            .iter()
+            .filter(|(_, zones)| zones.len() > 1)
+            .map(|(asset, zones)| (asset, zones.len()))
+    }
+
+    /// The assets of the zone at `zone_index` (in [`Self::zones`]), in order
+    /// of their types and names.
+    pub fn zone(&self, zone_index: usize) -> impl Iterator<Item = &XAsset> {
+        self.zone_assets
+            .get(zone_index)
+            .into_iter()
+            .flat_map(|assets| assets.values())
+            .map(|&i| &self.assets[i].0)
+    }
+
+    /// The asset with `asset_type` and `name` in the zone at `zone_index`.
+    pub fn get_in_zone(
+        &self,
+        zone_index: usize,
+        asset_type: XAssetType,
+        name: &str,
+    ) -> Option<&XAsset> {
+        self.zone_assets
+            .get(zone_index)?
+            .get(&(asset_type, name.to_string()))
+            .map(|&i| &self.assets[i].0)
+    }
+
+    /// The asset with `asset_type` and `name` that the engine would use
+    /// after loading every zone, i.e., the one from the last zone it's in
+    /// (same as [`ZoneSet::deserialize`]).
+    pub fn get(&self, asset_type: XAssetType, name: &str) -> Option<&XAsset> {
+        (0..self.zone_assets.len())
+            .rev()
+            .find_map(|zone| self.get_in_zone(zone, asset_type, name))
+    }
+}
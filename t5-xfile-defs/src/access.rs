@@ -0,0 +1,310 @@
+//! Traits for getting at what different types of assets have in common,
+//! so code can be generic over, e.g., every asset that uses materials,
+//! rather than matching on every variant of [`XAsset`].
+//!
+//! [`XAsset::as_has_materials`] and [`XAsset::as_has_images`] get at them
+//! from an [`XAsset`] of any type.
+
+use alloc::vec::Vec;
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    LocalizeEntry, MapEnts, PackIndex, RawFile, StringTable, XGlobals,
+    ddl::DdlRoot,
+    emblem::EmblemSet,
+    techset::{GfxImage, Material, MaterialTechniqueSet, MaterialTextureDefInfo},
+    xasset::{XAsset, XAssetGeneric},
+};
+
+#[cfg(feature = "world")]
+use crate::clipmap::ClipMap;
+#[cfg(feature = "world")]
+use crate::com_world::ComWorld;
+#[cfg(feature = "world")]
+use crate::destructible::DestructibleDef;
+#[cfg(feature = "ui")]
+use crate::font::Font;
+#[cfg(feature = "fx")]
+use crate::fx::{FxEffectDef, FxImpactTable};
+#[cfg(feature = "world")]
+use crate::gameworld::{GameWorldMp, GameWorldSp};
+#[cfg(feature = "world")]
+use crate::gfx_world::GfxWorld;
+#[cfg(feature = "world")]
+use crate::glass::Glasses;
+#[cfg(feature = "world")]
+use crate::light::GfxLightDef;
+#[cfg(feature = "ui")]
+use crate::menu::{MenuDef, MenuList};
+#[cfg(feature = "audio")]
+use crate::sound::{SndBank, SndDriverGlobals, SndPatch};
+#[cfg(feature = "fx")]
+use crate::weapon::WeaponVariantDef;
+#[cfg(feature = "models")]
+use crate::xanim::XAnimParts;
+#[cfg(feature = "models")]
+use crate::xmodel::{PhysConstraints, PhysPreset, XModel, XModelPieces};
+
+/// An asset with a name (the one [`XAsset::name`] returns).
+pub trait HasName {
+    fn name(&self) -> &str;
+}
+
+/// An asset that uses materials directly.
+pub trait HasMaterials {
+    /// Every material the asset uses directly, in order, including
+    /// duplicates.
+    fn materials(&self) -> Vec<&Material>;
+}
+
+/// An asset that uses images directly, rather than only through its
+/// materials.
+pub trait HasImages {
+    /// Every image the asset uses directly, in order, including duplicates.
+    fn images(&self) -> Vec<&GfxImage>;
+}
+
+macro_rules! impl_has_name {
+    ($($(#[$attr:meta])* $ty:ty => $($field:ident).+),+ $(,)?) => {
+        $(
+            $(#[$attr])*
+            impl HasName for $ty {
+                fn name(&self) -> &str {
+                    self.$($field).+.get()
+                }
+            }
+        )+
+    };
+}
+
+impl_has_name!(
+    #[cfg(feature = "models")]
+    XModelPieces => name,
+    #[cfg(feature = "models")]
+    PhysPreset => name,
+    #[cfg(feature = "models")]
+    PhysConstraints => name,
+    #[cfg(feature = "world")]
+    DestructibleDef => name,
+    #[cfg(feature = "models")]
+    XAnimParts => name,
+    #[cfg(feature = "models")]
+    XModel => name,
+    Material => info.name,
+    MaterialTechniqueSet => name,
+    GfxImage => name,
+    #[cfg(feature = "audio")]
+    SndBank => name,
+    #[cfg(feature = "audio")]
+    SndPatch => name,
+    #[cfg(feature = "world")]
+    ClipMap => name,
+    #[cfg(feature = "world")]
+    ComWorld => name,
+    #[cfg(feature = "world")]
+    GameWorldSp => name,
+    #[cfg(feature = "world")]
+    GameWorldMp => name,
+    MapEnts => name,
+    #[cfg(feature = "world")]
+    GfxLightDef => name,
+    #[cfg(feature = "ui")]
+    Font => font_name,
+    LocalizeEntry => name,
+    #[cfg(feature = "fx")]
+    WeaponVariantDef => internal_name,
+    #[cfg(feature = "audio")]
+    SndDriverGlobals => name,
+    #[cfg(feature = "fx")]
+    FxEffectDef => name,
+    #[cfg(feature = "fx")]
+    FxImpactTable => name,
+    RawFile => name,
+    StringTable => name,
+    PackIndex => name,
+    XGlobals => name,
+    DdlRoot => name,
+    #[cfg(feature = "world")]
+    Glasses => name,
+);
+
+#[cfg(feature = "world")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasName for GfxWorld<MAX_LOCAL_CLIENTS> {
+    fn name(&self) -> &str {
+        self.name.get()
+    }
+}
+
+#[cfg(feature = "ui")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasName for MenuList<MAX_LOCAL_CLIENTS> {
+    fn name(&self) -> &str {
+        self.name.get()
+    }
+}
+
+#[cfg(feature = "ui")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasName for MenuDef<MAX_LOCAL_CLIENTS> {
+    fn name(&self) -> &str {
+        self.window.name.get()
+    }
+}
+
+#[cfg(feature = "models")]
+impl HasMaterials for XModel {
+    fn materials(&self) -> Vec<&Material> {
+        self.material_handles.iter().map(AsRef::as_ref).collect()
+    }
+}
+
+#[cfg(feature = "ui")]
+impl HasMaterials for Font {
+    fn materials(&self) -> Vec<&Material> {
+        [&self.material, &self.glow_material]
+            .into_iter()
+            .flatten()
+            .map(AsRef::as_ref)
+            .collect()
+    }
+}
+
+#[cfg(feature = "world")]
+impl HasMaterials for Glasses {
+    fn materials(&self) -> Vec<&Material> {
+        self.glasses
+            .iter()
+            .filter_map(|glass| glass.glass_def.as_deref())
+            .flat_map(|def| {
+                [
+                    &def.pristine_material,
+                    &def.cracked_material,
+                    &def.shard_material,
+                ]
+            })
+            .flatten()
+            .map(AsRef::as_ref)
+            .collect()
+    }
+}
+
+#[cfg(feature = "world")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasMaterials for GfxWorld<MAX_LOCAL_CLIENTS> {
+    fn materials(&self) -> Vec<&Material> {
+        [
+            &self.water_material,
+            &self.corona_material,
+            &self.rope_material,
+        ]
+        .into_iter()
+        .flatten()
+        .map(AsRef::as_ref)
+        .collect()
+    }
+}
+
+#[cfg(feature = "ui")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasMaterials for MenuDef<MAX_LOCAL_CLIENTS> {
+    fn materials(&self) -> Vec<&Material> {
+        self.window.background.as_deref().into_iter().collect()
+    }
+}
+
+impl HasMaterials for EmblemSet {
+    fn materials(&self) -> Vec<&Material> {
+        self.backgrounds
+            .iter()
+            .filter_map(|background| background.material.as_deref())
+            .collect()
+    }
+}
+
+impl HasImages for Material {
+    fn images(&self) -> Vec<&GfxImage> {
+        self.textures
+            .iter()
+            .filter_map(|texture| match &texture.u {
+                MaterialTextureDefInfo::Image(image) => image.as_deref(),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "world")]
+impl HasImages for GfxLightDef {
+    fn images(&self) -> Vec<&GfxImage> {
+        self.attenuation.image.as_deref().into_iter().collect()
+    }
+}
+
+#[cfg(feature = "world")]
+impl<const MAX_LOCAL_CLIENTS: usize> HasImages for GfxWorld<MAX_LOCAL_CLIENTS> {
+    fn images(&self) -> Vec<&GfxImage> {
+        [&self.sky_image, &self.outdoor_image]
+            .into_iter()
+            .flatten()
+            .map(AsRef::as_ref)
+            .collect()
+    }
+}
+
+impl HasImages for EmblemSet {
+    fn images(&self) -> Vec<&GfxImage> {
+        self.icons
+            .iter()
+            .filter_map(|icon| icon.image.as_deref())
+            .collect()
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+    /// The asset, if it's a (non-null) asset with materials.
+    pub fn as_has_materials(&self) -> Option<&dyn HasMaterials> {
+        match self {
+            #[cfg(feature = "models")]
+            Self::XModel(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "ui")]
+            Self::Font(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "world")]
+            Self::Glasses(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "world")]
+            Self::GfxWorld(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "ui")]
+            Self::Menu(Some(a)) => Some(a.as_ref()),
+            Self::EmblemSet(Some(a)) => Some(a.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The asset, if it's a (non-null) asset with images.
+    pub fn as_has_images(&self) -> Option<&dyn HasImages> {
+        match self {
+            Self::Material(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "world")]
+            Self::LightDef(Some(a)) => Some(a.as_ref()),
+            #[cfg(feature = "world")]
+            Self::GfxWorld(Some(a)) => Some(a.as_ref()),
+            Self::EmblemSet(Some(a)) => Some(a.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl XAsset {
+    /// See [`XAssetGeneric::as_has_materials`].
+    pub fn as_has_materials(&self) -> Option<&dyn HasMaterials> {
+        match self {
+            Self::PC(a) => a.as_has_materials(),
+            Self::Console(a) => a.as_has_materials(),
+        }
+    }
+
+    /// See [`XAssetGeneric::as_has_images`].
+    pub fn as_has_images(&self) -> Option<&dyn HasImages> {
+        match self {
+            Self::PC(a) => a.as_has_images(),
+            Self::Console(a) => a.as_has_images(),
+        }
+    }
+}
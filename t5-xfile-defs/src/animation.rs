@@ -0,0 +1,421 @@
+//! Evaluating an [`XAnimParts`] against an [`XModel`]'s
+//! [`Skeleton`](crate::skeleton::Skeleton).
+//!
+//! The keyframes of an animation are packed into [`XAnimParts`]'s data
+//! arrays. [`XAnimParts::tracks`] unpacks them into a [`BoneTrack`] per
+//! part, [`XAnimParts::bind`] matches the parts to a skeleton's bones by
+//! name, and [`BoundAnim::sample`] evaluates the result at a point in time.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result,
+    common::{Mat3, Mat4, Vec3, Vec4},
+    file_line_col,
+    skeleton::Skeleton,
+    xanim::{
+        PART_TYPE_ALL, PART_TYPE_FULL_QUAT, PART_TYPE_FULL_QUAT_NO_SIZE, PART_TYPE_HALF_QUAT,
+        PART_TYPE_HALF_QUAT_NO_SIZE, PART_TYPE_NO_QUAT, PART_TYPE_SMALL_TRANS, PART_TYPE_TRANS,
+        PART_TYPE_TRANS_NO_SIZE, XAnimIndices, XAnimParts,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Rotations are stored as shorts scaled by this.
+const QUAT_SCALE: f32 = 1.0 / 32767.0;
+
+/// Tracks with at least this many keyframes are preceded by a table for
+/// seeking to a frame quickly, which isn't needed here.
+const SEEK_TABLE_MIN_SIZE: u16 = 0x40;
+
+/// Values of type `T` at certain frames, in order.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Keyframes<T> {
+    pub frames: Vec<u16>,
+    pub values: Vec<T>,
+}
+
+impl<T: Copy> Keyframes<T> {
+    /// A single value for every frame.
+    pub fn constant(value: T) -> Self {
+        Self {
+            frames: alloc::vec![0],
+            values: alloc::vec![value],
+        }
+    }
+
+    /// The value at `frame`, interpolated between the keyframes around it
+    /// with `lerp`, or [`None`] if there are no keyframes. Frames before
+    /// the first or after the last keyframe get its value.
+    pub fn sample(&self, frame: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+        let len = self.frames.len().min(self.values.len());
+        if len == 0 {
+            return None;
+        }
+
+        let next = self.frames[..len].partition_point(|&f| f as f32 <= frame);
+        if next == 0 {
+            return Some(self.values[0]);
+        }
+        if next == len {
+            return Some(self.values[len - 1]);
+        }
+
+        let (start, end) = (self.frames[next - 1] as f32, self.frames[next] as f32);
+        let t = (frame - start) / (end - start);
+        Some(lerp(self.values[next - 1], self.values[next], t))
+    }
+}
+
+/// The keyframes of one part (bone) of an animation. Parts that aren't
+/// rotated or translated by the animation have [`None`] for that track, and
+/// keep their pose from the model.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct BoneTrack {
+    pub name: String,
+    /// Rotations relative to the bone's parent, as quaternions
+    /// (x, y, z, w). They aren't necessarily normalized.
+    pub rotation: Option<Keyframes<Vec4>>,
+    /// Translations relative to the bone's parent.
+    pub translation: Option<Keyframes<Vec3>>,
+}
+
+/// Reads through one of [`XAnimParts`]'s data arrays.
+struct Cursor<'a, T> {
+    name: &'static str,
+    data: &'a [T],
+    pos: usize,
+}
+
+impl<'a, T: Copy> Cursor<'a, T> {
+    fn new(name: &'static str, data: &'a [T]) -> Self {
+        Self { name, data, pos: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [T]> {
+        let values = self
+            .data
+            .get(self.pos..self.pos.saturating_add(count))
+            .ok_or(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XAnimParts::tracks: {} has {} entries, needed more than {}",
+                    self.name,
+                    self.data.len(),
+                    self.pos,
+                )),
+            ))?;
+        self.pos += count;
+        Ok(values)
+    }
+
+    fn next(&mut self) -> Result<T> {
+        self.take(1).map(|values| values[0])
+    }
+}
+
+/// The frame indices of the keyframes, which are bytes if the animation
+/// has fewer than 256 frames and shorts otherwise.
+enum FrameCursor<'a> {
+    _1(Cursor<'a, u8>),
+    _2(Cursor<'a, u16>),
+}
+
+impl FrameCursor<'_> {
+    fn take(&mut self, count: usize) -> Result<Vec<u16>> {
+        Ok(match self {
+            Self::_1(c) => c.take(count)?.iter().map(|&f| f as u16).collect(),
+            Self::_2(c) => c.take(count)?.to_vec(),
+        })
+    }
+}
+
+struct TrackReader<'a> {
+    data_byte: Cursor<'a, u8>,
+    data_short: Cursor<'a, i16>,
+    data_int: Cursor<'a, i32>,
+    random_data_byte: Cursor<'a, u8>,
+    random_data_short: Cursor<'a, i16>,
+    indices: FrameCursor<'a>,
+}
+
+impl TrackReader<'_> {
+    /// Reads the keyframe count of an animated track and the frames of its
+    /// keyframes.
+    fn frames(&mut self) -> Result<Vec<u16>> {
+        let size = self.data_short.next()? as u16;
+        if size >= SEEK_TABLE_MIN_SIZE {
+            self.data_short.take(((size as usize - 1) >> 8) + 2)?;
+        }
+        self.indices.take(size as usize + 1)
+    }
+
+    fn vec3(&mut self) -> Result<Vec3> {
+        let v = self.data_int.take(3)?;
+        Ok([0, 1, 2].map(|i| f32::from_bits(v[i] as u32)).into())
+    }
+}
+
+fn quat(q: &[i16]) -> Vec4 {
+    match *q {
+        [z, w] => [0.0, 0.0, z as f32 * QUAT_SCALE, w as f32 * QUAT_SCALE],
+        _ => [q[0], q[1], q[2], q[3]].map(|c| c as f32 * QUAT_SCALE),
+    }
+    .into()
+}
+
+impl XAnimParts {
+    /// Unpacks the animation's keyframes, one track per part (in the order
+    /// of [`Self::names`]).
+    ///
+    /// The parts are sorted by how they're rotated, as counted by
+    /// [`Self::bone_count`]: first the parts that aren't rotated, then
+    /// those rotated about Z only (two components per rotation) and those
+    /// rotated freely (four), then the same for parts with a single
+    /// rotation. An animated rotation's keyframe count minus one is in
+    /// [`Self::data_short`], followed by its frames in [`Self::indices`]
+    /// and its values in [`Self::random_data_short`]; a single rotation is
+    /// in [`Self::data_short`].
+    ///
+    /// Translations follow, each starting with its part's index in
+    /// [`Self::data_byte`]. An animated translation's keyframe count is
+    /// read like a rotation's, followed by the minimum and the size of a
+    /// step in [`Self::data_int`] (as floats), and its values as steps in
+    /// [`Self::random_data_byte`] (for small translations) or
+    /// [`Self::random_data_short`]. A single translation is three floats in
+    /// [`Self::data_int`].
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if the data runs out or a
+    /// translation's part doesn't exist.
+    pub fn tracks(&self) -> Result<Vec<BoneTrack>> {
+        let mut tracks = self
+            .names
+            .iter()
+            .take(self.bone_count[PART_TYPE_ALL] as _)
+            .map(|name| BoneTrack {
+                name: name.get().to_string(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut r = TrackReader {
+            data_byte: Cursor::new("data_byte", &self.data_byte),
+            data_short: Cursor::new("data_short", &self.data_short),
+            data_int: Cursor::new("data_int", &self.data_int),
+            random_data_byte: Cursor::new("random_data_byte", &self.random_data_byte),
+            random_data_short: Cursor::new("random_data_short", &self.random_data_short),
+            indices: match &self.indices {
+                XAnimIndices::_1(v) => FrameCursor::_1(Cursor::new("indices", v)),
+                XAnimIndices::_2(v) => FrameCursor::_2(Cursor::new("indices", v)),
+            },
+        };
+
+        let mut part = self.bone_count[PART_TYPE_NO_QUAT] as usize;
+        let rotation_types = [
+            (PART_TYPE_HALF_QUAT, 2, true),
+            (PART_TYPE_FULL_QUAT, 4, true),
+            (PART_TYPE_HALF_QUAT_NO_SIZE, 2, false),
+            (PART_TYPE_FULL_QUAT_NO_SIZE, 4, false),
+        ];
+        for (part_type, components, animated) in rotation_types {
+            for _ in 0..self.bone_count[part_type] {
+                let keyframes = if animated {
+                    let frames = r.frames()?;
+                    let values = r.random_data_short.take(frames.len() * components)?;
+                    Keyframes {
+                        values: values.chunks_exact(components).map(quat).collect(),
+                        frames,
+                    }
+                } else {
+                    Keyframes::constant(quat(r.data_short.take(components)?))
+                };
+
+                if let Some(track) = tracks.get_mut(part) {
+                    track.rotation = Some(keyframes);
+                }
+                part += 1;
+            }
+        }
+
+        let translation_types = [
+            PART_TYPE_SMALL_TRANS,
+            PART_TYPE_TRANS,
+            PART_TYPE_TRANS_NO_SIZE,
+        ];
+        for part_type in translation_types {
+            for _ in 0..self.bone_count[part_type] {
+                let part = r.data_byte.next()? as usize;
+                let keyframes = if part_type == PART_TYPE_TRANS_NO_SIZE {
+                    Keyframes::constant(r.vec3()?)
+                } else {
+                    let frames = r.frames()?;
+                    let (mins, step) = (r.vec3()?.get(), r.vec3()?.get());
+                    let steps = if part_type == PART_TYPE_SMALL_TRANS {
+                        let v = r.random_data_byte.take(frames.len() * 3)?;
+                        v.iter().map(|&s| s as f32).collect::<Vec<_>>()
+                    } else {
+                        let v = r.random_data_short.take(frames.len() * 3)?;
+                        v.iter().map(|&s| s as u16 as f32).collect()
+                    };
+                    let values = steps
+                        .chunks_exact(3)
+                        .map(|s| [0, 1, 2].map(|i| mins[i] + step[i] * s[i]).into())
+                        .collect();
+                    Keyframes { frames, values }
+                };
+
+                let track = tracks.get_mut(part).ok_or(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "XAnimParts::tracks: translated part {part} doesn't exist",
+                    )),
+                ))?;
+                track.translation = Some(keyframes);
+            }
+        }
+
+        Ok(tracks)
+    }
+
+    /// The animation's length in seconds.
+    pub fn duration(&self) -> f32 {
+        if self.framerate > 0.0 {
+            self.numframes as f32 / self.framerate
+        } else {
+            0.0
+        }
+    }
+
+    /// Unpacks the animation's tracks (see [`Self::tracks`]) and matches
+    /// them to the bones of `skeleton` by name.
+    pub fn bind<'a>(&self, skeleton: &'a Skeleton) -> Result<BoundAnim<'a>> {
+        let tracks = self.tracks()?;
+        let bones = skeleton
+            .bones
+            .iter()
+            .map(|bone| tracks.iter().position(|track| track.name == bone.name))
+            .collect();
+
+        Ok(BoundAnim {
+            skeleton,
+            tracks,
+            bones,
+            numframes: self.numframes,
+            framerate: self.framerate,
+            looping: self.loop_,
+        })
+    }
+}
+
+fn lerp_vec3(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    let (a, b) = (a.get(), b.get());
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t).into()
+}
+
+/// Interpolates linearly between two rotations, along the shorter way
+/// around. The result isn't normalized.
+fn nlerp_quat(a: Vec4, b: Vec4, t: f32) -> Vec4 {
+    let (a, mut b) = (a.get(), b.get());
+    if (0..4).map(|i| a[i] * b[i]).sum::<f32>() < 0.0 {
+        b = b.map(|c| -c);
+    }
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t).into()
+}
+
+/// An animation bound to a skeleton by [`XAnimParts::bind`].
+///
+/// The animation's root motion ([`XAnimParts::delta_part`]), which moves
+/// the entity playing it rather than a bone, isn't applied.
+#[derive(Clone, Debug)]
+pub struct BoundAnim<'a> {
+    pub skeleton: &'a Skeleton,
+    pub tracks: Vec<BoneTrack>,
+    /// The index into [`Self::tracks`] of each bone's track, if it has one.
+    pub bones: Vec<Option<usize>>,
+    pub numframes: u16,
+    pub framerate: f32,
+    pub looping: bool,
+}
+
+impl BoundAnim<'_> {
+    /// The names of the tracks that didn't match any of the skeleton's
+    /// bones.
+    pub fn unbound_tracks(&self) -> impl Iterator<Item = &str> + '_ {
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.bones.contains(&Some(*i)))
+            .map(|(_, track)| track.name.as_str())
+    }
+
+    /// The (fractional) frame shown `time` seconds into the animation.
+    /// Looping animations wrap around, and the rest stop at their last
+    /// frame.
+    pub fn frame_at(&self, time: f32) -> f32 {
+        let frame = time * self.framerate;
+        let last = self.numframes as f32;
+        if last <= 0.0 || frame.is_nan() {
+            0.0
+        } else if self.looping {
+            frame.rem_euclid(last)
+        } else {
+            frame.clamp(0.0, last)
+        }
+    }
+
+    /// Every bone's transform relative to its parent, `time` seconds into
+    /// the animation. Bones the animation doesn't move keep their local
+    /// transform from the model.
+    pub fn local_pose(&self, time: f32) -> Vec<Mat4> {
+        let frame = self.frame_at(time);
+        self.skeleton
+            .bones
+            .iter()
+            .zip(&self.bones)
+            .map(|(bone, &track)| {
+                let track = track.map(|t| &self.tracks[t]);
+                let rotation = track
+                    .and_then(|t| t.rotation.as_ref())
+                    .and_then(|k| k.sample(frame, nlerp_quat))
+                    .unwrap_or(bone.local_rotation);
+                let translation = track
+                    .and_then(|t| t.translation.as_ref())
+                    .and_then(|k| k.sample(frame, lerp_vec3))
+                    .unwrap_or(bone.local_translation);
+                Mat4::from_rotation_translation(Mat3::from_quat(rotation), translation)
+            })
+            .collect()
+    }
+
+    /// Every bone's model-space transform, `time` seconds into the
+    /// animation.
+    pub fn sample(&self, time: f32) -> Vec<Mat4> {
+        self.skeleton.world_matrices(&self.local_pose(time))
+    }
+
+    /// Every bone's transform from its bind pose to where the animation
+    /// has it at `time` seconds, for skinning vertices in model space.
+    pub fn skinning_matrices(&self, time: f32) -> Vec<Mat4> {
+        self.sample(time)
+            .into_iter()
+            .zip(&self.skeleton.bones)
+            .map(|(world, bone)| {
+                let inverse_rotation = Mat3::from_quat(bone.bind_rotation).transpose();
+                let t = bone.bind_translation.get();
+                let inverse_translation = inverse_rotation * Vec3::from(t.map(|c| -c));
+                world * Mat4::from_rotation_translation(inverse_rotation, inverse_translation)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,264 @@
+//! Resolution of real offsets (see [`Ptr32::is_real`]).
+//!
+//! A real offset points into one of the engine's memory blocks (see
+//! [`XFileBlock`]) rather than the payload, at an object that was loaded
+//! earlier. To find out which one, [`XFileBlocks`] replays how the engine
+//! allocates the payload from its blocks while it's deserialized, and
+//! remembers where every object behind an inline pointer ended up.
+//!
+//! The engine aligns every allocation, but the payload itself isn't padded,
+//! so offsets within a block drift away from positions in the payload. Each
+//! load is aligned to the Rust alignment of the raw type being loaded, which
+//! matches the alignment of the C type it mirrors.
+//!
+//! [`XFileBlocks`] can also record which [`PtrMarker`] every inline pointer
+//! used (see [`InlinePtr`]).
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{PtrMarker, XFileBlock, XFileOffset};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A real offset encountered while deserializing.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RealPtr {
+    pub offset: XFileOffset,
+    /// Where in the payload deserialization was when the pointer was
+    /// encountered. Pointers are only looked at once the struct containing
+    /// them has been loaded, so this is usually somewhat past the pointer
+    /// itself.
+    pub pos: u64,
+    /// Where in the payload the object the pointer points to was loaded
+    /// from, if it could be resolved.
+    pub target: Option<u64>,
+    /// The marker of the inline pointer the object was originally loaded
+    /// through, if it could be resolved and was behind a pointer.
+    pub target_marker: Option<PtrMarker>,
+}
+
+/// An inline pointer encountered while deserializing.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InlinePtr {
+    pub marker: PtrMarker,
+    /// Where the object the pointer points to was allocated.
+    pub offset: XFileOffset,
+    /// Where in the payload the object the pointer points to was loaded
+    /// from.
+    pub pos: u64,
+}
+
+/// Tracks how the payload is allocated from the engine's blocks, for
+/// implementations of [`T5XFileDeserialize`](crate::T5XFileDeserialize)'s
+/// real offset hooks (see the [module documentation](self)).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default)]
+pub struct XFileBlocks {
+    offsets: [u32; XFileBlock::COUNT],
+    stack: Vec<XFileBlock>,
+    /// The start of every marked region, keyed by [`XFileOffset::as_u32`],
+    /// along with where in the payload it was loaded from, its length, and
+    /// the marker of the pointer it was loaded through.
+    targets: BTreeMap<u32, (u64, u32, Option<PtrMarker>)>,
+    real_ptrs: Vec<RealPtr>,
+    record_markers: bool,
+    inline_ptrs: Vec<InlinePtr>,
+    /// The markers of the inline pointers in each asset, in payload order
+    /// (see [`Self::asset_ptr_markers`]).
+    asset_ptr_markers: Vec<Vec<PtrMarker>>,
+    /// How often [`PtrMarker::Following`] and [`PtrMarker::Inserted`] were
+    /// used, respectively.
+    marker_counts: [u64; 2],
+    follow: bool,
+    /// The targets of the real offsets currently being followed.
+    following: Vec<u64>,
+}
+
+impl XFileBlocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes [`Self::enter`] follow resolved real offsets.
+    pub fn with_follow(mut self, follow: bool) -> Self {
+        self.follow = follow;
+        self
+    }
+
+    pub fn follow(&self) -> bool {
+        self.follow
+    }
+
+    /// Makes [`Self::mark`] record every inline pointer (see
+    /// [`Self::inline_ptrs`]). Markers are always counted.
+    pub fn with_record_markers(mut self, record_markers: bool) -> Self {
+        self.record_markers = record_markers;
+        self
+    }
+
+    pub fn record_markers(&self) -> bool {
+        self.record_markers
+    }
+
+    /// The block allocations currently come from. [`XFileBlock::Virtual`]
+    /// unless another one was pushed.
+    pub fn current(&self) -> XFileBlock {
+        self.stack.last().copied().unwrap_or_default()
+    }
+
+    pub fn push(&mut self, block: XFileBlock) {
+        self.stack.push(block);
+    }
+
+    pub fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// How much of `block` has been allocated so far.
+    pub fn block_len(&self, block: XFileBlock) -> u32 {
+        self.offsets[block.index()]
+    }
+
+    fn marker_index(marker: PtrMarker) -> usize {
+        match marker {
+            PtrMarker::Following => 0,
+            PtrMarker::Inserted => 1,
+        }
+    }
+
+    fn align(&mut self, align: usize) -> XFileOffset {
+        let block = self.current();
+        let offset = &mut self.offsets[block.index()];
+        *offset = offset.next_multiple_of(align.max(1) as u32);
+        XFileOffset::new(block, *offset)
+    }
+
+    /// Allocates `len` bytes aligned to `align` from the current block.
+    /// Nothing is allocated while a real offset is being followed, since
+    /// the object it points to was already allocated the first time around.
+    pub fn alloc(&mut self, align: usize, len: usize) {
+        if !self.following.is_empty() {
+            return;
+        }
+
+        let block = self.align(align).block();
+        let offset = &mut self.offsets[block as usize];
+        *offset = offset.saturating_add(len as u32);
+    }
+
+    /// Records that the `len` bytes about to be allocated (aligned to
+    /// `align`) are loaded from `pos` through a pointer with `marker`, so
+    /// real offsets pointing into them can be resolved.
+    pub fn mark(&mut self, marker: Option<PtrMarker>, align: usize, len: usize, pos: u64) {
+        if !self.following.is_empty() {
+            return;
+        }
+
+        let offset = self.align(align);
+        self.targets
+            .insert(offset.as_u32(), (pos, len as u32, marker));
+
+        if let Some(marker) = marker {
+            self.marker_counts[Self::marker_index(marker)] += 1;
+            if self.record_markers {
+                self.inline_ptrs.push(InlinePtr {
+                    marker,
+                    offset,
+                    pos,
+                });
+            }
+        }
+    }
+
+    /// Looks up where in the payload the object `offset` points to was
+    /// loaded from, and records the pointer (see [`Self::real_ptrs`]). `pos`
+    /// is where the pointer was encountered.
+    pub fn resolve(&mut self, offset: XFileOffset, pos: u64) -> Option<u64> {
+        let key = offset.as_u32();
+        let (target, target_marker) = self
+            .targets
+            .range(..=key)
+            .next_back()
+            .filter(|&(&start, &(_, len, _))| key - start < len.max(1))
+            .map(|(&start, &(target, _, marker))| (Some(target + (key - start) as u64), marker))
+            .unwrap_or_default();
+
+        self.real_ptrs.push(RealPtr {
+            offset,
+            pos,
+            target,
+            target_marker,
+        });
+        target
+    }
+
+    /// Starts following a real offset that resolved to `target`. Returns
+    /// `false` if following is disabled, or if `target` is already being
+    /// followed (i.e., the offsets form a cycle).
+    pub fn enter(&mut self, target: u64) -> bool {
+        if !self.follow || self.following.contains(&target) {
+            return false;
+        }
+
+        self.following.push(target);
+        true
+    }
+
+    pub fn leave(&mut self) {
+        self.following.pop();
+    }
+
+    /// Starts recording the markers of the next asset's inline pointers
+    /// (see [`Self::asset_ptr_markers`]), beginning with `marker`, the one
+    /// the asset was listed with, if it's inline. Does nothing unless
+    /// recording was enabled with [`Self::with_record_markers`].
+    pub fn begin_asset(&mut self, marker: Option<PtrMarker>) {
+        if self.record_markers {
+            self.asset_ptr_markers.push(marker.into_iter().collect());
+        }
+    }
+
+    /// Records the markers of inline pointers read from the payload for the
+    /// current asset, in the order they were read. Does nothing before the
+    /// first [`Self::begin_asset`], or while following a real offset, since
+    /// what's loaded then isn't part of the asset's own data.
+    pub fn record_asset_ptr_markers(&mut self, markers: impl IntoIterator<Item = PtrMarker>) {
+        if self.following.is_empty()
+            && let Some(current) = self.asset_ptr_markers.last_mut()
+        {
+            current.extend(markers);
+        }
+    }
+
+    /// Every real offset encountered so far, in order.
+    pub fn real_ptrs(&self) -> &[RealPtr] {
+        &self.real_ptrs
+    }
+
+    /// Every inline pointer encountered so far, in order. Empty unless
+    /// recording was enabled with [`Self::with_record_markers`].
+    pub fn inline_ptrs(&self) -> &[InlinePtr] {
+        &self.inline_ptrs
+    }
+
+    /// The markers of the inline pointers in each asset so far, in the
+    /// order they appear in the payload, which is the order a serializer
+    /// writes them in. Unlike [`Self::inline_ptrs`], which is in the order
+    /// the objects they point to were loaded, this can be used to write the
+    /// same markers again. Empty unless recording was enabled with
+    /// [`Self::with_record_markers`].
+    pub fn asset_ptr_markers(&self) -> &[Vec<PtrMarker>] {
+        &self.asset_ptr_markers
+    }
+
+    /// How many inline pointers used `marker` so far.
+    pub fn marker_count(&self, marker: PtrMarker) -> u64 {
+        self.marker_counts[Self::marker_index(marker)]
+    }
+}
@@ -5,9 +5,10 @@ use alloc::{
 };
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, MapEnts, MapEntsRaw, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
-    common::{Mat3, Vec3, Vec4},
+    Error, ErrorKind, FatPointerCountFirstU32, FixedString, MapEnts, MapEntsRaw, Ptr32, Result,
+    ScriptString, Shared, T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString,
+    XStringRaw, assert_size,
+    common::{Aabb, Mat3, Vec3, Vec4},
     file_line_col,
     fx::{FxEffectDef, FxEffectDefRaw},
     techset::{Material, MaterialRaw},
@@ -19,10 +20,11 @@ use crate::{
 
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ClipMapRaw<'a> {
     pub name: XStringRaw<'a>,
     pub is_in_use: i32,
@@ -55,6 +57,7 @@ pub(crate) struct ClipMapRaw<'a> {
     pub box_model: CModelRaw,
     pub original_dyn_ent_count: u16,
     pub dyn_ent_count: [u16; 4],
+    #[allow(dead_code)]
     pad: [u8; 2],
     pub dyn_ent_def_list: [Ptr32<'a, DynEntityDefRaw<'a>>; 2],
     pub dyn_ent_pose_list: [Ptr32<'a, DynEntityPoseRaw>; 2],
@@ -68,7 +71,8 @@ pub(crate) struct ClipMapRaw<'a> {
 assert_size!(ClipMapRaw, 332);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ClipMap {
     pub name: XString,
     pub is_in_use: bool,
@@ -125,19 +129,25 @@ impl<'a> XFileDeserializeInto<ClipMap, ()> for ClipMapRaw<'a> {
         let nodes = self.nodes.xfile_deserialize_into(de, ())?;
         let leafs = self.leafs.to_vec_into(de)?;
         let leafbrush_nodes = self.leafbrush_nodes.xfile_deserialize_into(de, ())?;
-        let leafbrushes = self.leafbrushes.to_vec(de)?;
-        let leafsurfaces = self.leafsurfaces.to_vec(de)?;
+        let leafbrushes = self.leafbrushes.to_vec_bulk(de)?;
+        let leafsurfaces = self.leafsurfaces.to_vec_bulk(de)?;
         let verts = self.verts.to_vec_into(de)?;
         let brush_verts = self.brush_verts.to_vec_into(de)?;
-        let uinds = self.uinds.to_vec(de)?;
+        let uinds = self.uinds.to_vec_bulk(de)?;
         let tri_indices = self
             .tri_indices
-            .to_array(self.tri_count as usize * 3)
-            .to_vec(de)?;
+            .to_array((self.tri_count as usize).saturating_mul(3))
+            .to_vec_bulk(de)?;
         let tri_edge_is_walkable = self
             .tri_edge_is_walkable
-            .to_array((((self.tri_count as usize * 3) + 31) >> 5) * 4)
-            .to_vec(de)?;
+            .to_array(
+                ((self.tri_count as usize)
+                    .saturating_mul(3)
+                    .saturating_add(31)
+                    >> 5)
+                    * 4,
+            )
+            .to_vec_bulk(de)?;
         let borders = self.borders.to_vec_into(de)?;
         let partitions = self.partitions.xfile_deserialize_into(de, ())?;
         let aabb_trees = self.aabb_trees.to_vec_into(de)?;
@@ -145,8 +155,8 @@ impl<'a> XFileDeserializeInto<ClipMap, ()> for ClipMapRaw<'a> {
         let brushes = self.brushes.xfile_deserialize_into(de, ())?;
         let visibility = self
             .visibility
-            .to_array(self.cluster_bytes as usize * self.num_clusters as usize)
-            .to_vec(de)?;
+            .to_array((self.cluster_bytes as usize).saturating_mul(self.num_clusters as usize))
+            .to_vec_bulk(de)?;
         let map_ents = self.map_ents.xfile_deserialize_into(de, ())?;
         let box_brush = self.box_brush.xfile_deserialize_into(de, ())?;
         let box_model = self.box_model.into();
@@ -243,8 +253,230 @@ impl<'a> XFileDeserializeInto<ClipMap, ()> for ClipMapRaw<'a> {
     }
 }
 
+/// What a [`ClipMap::trace_ray`] hit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TraceHit {
+    /// An index into [`ClipMap::brushes`].
+    Brush(usize),
+    /// The index of a triangle in [`ClipMap::tri_indices`] (i.e. its first
+    /// index divided by 3).
+    Triangle(usize),
+}
+
+/// The result of a [`ClipMap::trace_ray`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Trace {
+    /// How far along the segment the hit is, from `0.0` at the start to
+    /// `1.0` at the end.
+    pub fraction: f32,
+    /// The unit normal of the surface that was hit, facing back towards
+    /// the start of the segment. Zero if [`Self::start_solid`] is set.
+    pub normal: Vec3,
+    /// The segment started inside the brush that was hit.
+    pub start_solid: bool,
+    pub hit: TraceHit,
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// `core` has no `f32::sqrt`, and the normals only need to be close to unit
+/// length, so a few Newton-Raphson steps from the bit-level estimate are
+/// plenty.
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let len_sq = dot(v, v);
+    if len_sq == 0.0 {
+        return v;
+    }
+
+    let mut inv = f32::from_bits(0x5F37_59DF - (len_sq.to_bits() >> 1));
+    for _ in 0..3 {
+        inv *= 1.5 - 0.5 * len_sq * inv * inv;
+    }
+    v.map(|c| c * inv)
+}
+
+/// Keeps the part of `polygon` behind the plane (within `epsilon` of it).
+fn clip_polygon(polygon: &[[f32; 3]], normal: [f32; 3], dist: f32, epsilon: f32) -> Vec<[f32; 3]> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (k, &a) in polygon.iter().enumerate() {
+        let b = polygon[(k + 1) % polygon.len()];
+        let (da, db) = (dot(a, normal) - dist, dot(b, normal) - dist);
+        if da <= epsilon {
+            clipped.push(a);
+        }
+        // points within `epsilon` of the plane are kept as they are
+        if (da > epsilon && db < -epsilon) || (da < -epsilon && db > epsilon) {
+            let f = da / (da - db);
+            clipped.push([0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * f));
+        }
+    }
+    clipped
+}
+
+impl ClipMap {
+    /// Traces the segment from `start` to `end` through the world's brushes
+    /// and terrain, returning the first hit, or [`None`] if nothing is in
+    /// the way.
+    ///
+    /// Only brushes and terrain materials with contents in `contents_mask`
+    /// are considered. Brushes are clipped against their bounds and
+    /// [`CBrush::sides`], and terrain triangles are found by walking the
+    /// [`CollisionAabbTree`]s of every leaf.
+    ///
+    /// Brush models ([`Self::cmodels`] other than the world) and static
+    /// models aren't traced, since they may have been moved at runtime.
+    pub fn trace_ray(&self, start: Vec3, end: Vec3, contents_mask: i32) -> Option<Trace> {
+        let brushes = self
+            .brushes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.contents & contents_mask != 0)
+            .filter_map(|(i, b)| Some((i, b.trace(start, end)?)))
+            .map(|(i, (fraction, normal))| Trace {
+                fraction,
+                normal: normal.into(),
+                start_solid: fraction == 0.0 && normal == [0.0; 3],
+                hit: TraceHit::Brush(i),
+            });
+
+        let triangles =
+            self.trace_terrain(start, end, contents_mask)
+                .map(|(i, fraction, normal)| Trace {
+                    fraction,
+                    normal: normal.into(),
+                    start_solid: false,
+                    hit: TraceHit::Triangle(i),
+                });
+
+        brushes
+            .chain(triangles)
+            .min_by(|a, b| a.fraction.total_cmp(&b.fraction))
+    }
+
+    /// Returns `(triangle, fraction, normal)` for the closest terrain
+    /// triangle the segment hits.
+    fn trace_terrain(
+        &self,
+        start: Vec3,
+        end: Vec3,
+        contents_mask: i32,
+    ) -> Option<(usize, f32, [f32; 3])> {
+        let mut closest: Option<(usize, f32, [f32; 3])> = None;
+        let mut visited = alloc::vec![false; self.aabb_trees.len()];
+
+        let mut stack = self
+            .leafs
+            .iter()
+            .flat_map(|l| l.first_coll_aabb_index..l.first_coll_aabb_index + l.coll_aabb_count)
+            .collect::<Vec<_>>();
+        while let Some(i) = stack.pop() {
+            // trees can be shared between leafs, and malformed ones could
+            // otherwise loop
+            let Some(tree) = self.aabb_trees.get(i) else {
+                continue;
+            };
+            if core::mem::replace(&mut visited[i], true) {
+                continue;
+            }
+
+            let Some((enter, _)) = Aabb::from_origin_half_size(tree.origin, tree.half_size)
+                .segment_intersection(start, end)
+            else {
+                continue;
+            };
+            if closest.is_some_and(|(_, f, _)| f < enter) {
+                continue;
+            }
+
+            if tree.child_count != 0 {
+                stack.extend(tree.index..tree.index + tree.child_count);
+                continue;
+            }
+
+            if self
+                .materials
+                .get(tree.material_index)
+                .is_none_or(|m| m.content_flags & contents_mask == 0)
+            {
+                continue;
+            }
+
+            let Some(partition) = self.partitions.get(tree.index) else {
+                continue;
+            };
+            let first = partition.first_tri.max(0) as usize;
+            for tri in first..first + partition.tri_count as usize {
+                let Some(hit) = self.trace_triangle(tri, start, end) else {
+                    continue;
+                };
+                if closest.is_none_or(|(_, f, _)| hit.0 < f) {
+                    closest = Some((tri, hit.0, hit.1));
+                }
+            }
+        }
+
+        closest
+    }
+
+    /// Möller-Trumbore, restricted to the segment. Both sides of the
+    /// triangle are solid.
+    fn trace_triangle(&self, tri: usize, start: Vec3, end: Vec3) -> Option<(f32, [f32; 3])> {
+        let indices = self.tri_indices.get(tri * 3..tri * 3 + 3)?;
+        let [a, b, c] = [0, 1, 2].map(|i| self.verts.get(indices[i] as usize).map(|v| v.get()));
+        let (a, b, c) = (a?, b?, c?);
+        let (start, end) = (start.get(), end.get());
+
+        let dir = sub(end, start);
+        let (e1, e2) = (sub(b, a), sub(c, a));
+        let p = cross(dir, e2);
+        let det = dot(e1, p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = sub(start, a);
+        let u = dot(s, p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = cross(s, e1);
+        let v = dot(dir, q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = dot(e2, q) * inv_det;
+        if !(0.0..=1.0).contains(&t) {
+            return None;
+        }
+
+        let normal = normalize(cross(e1, e2));
+        let normal = if dot(normal, dir) > 0.0 {
+            normal.map(|c| -c)
+        } else {
+            normal
+        };
+        Some((t, normal))
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CStaticModelRaw<'a> {
     pub writable: CStaticModelWritable,
     pub xmodel: Ptr32<'a, XModelRaw<'a>>,
@@ -256,7 +488,8 @@ pub(crate) struct CStaticModelRaw<'a> {
 assert_size!(CStaticModelRaw, 80);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CStaticModel {
     pub writable: CStaticModelWritable,
     pub xmodel: Option<Box<XModel>>,
@@ -289,48 +522,26 @@ impl<'a> XFileDeserializeInto<CStaticModel, ()> for CStaticModelRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct CStaticModelWritable {
     pub next_model_in_world_sector: u16,
 }
 assert_size!(CStaticModelWritable, 2);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
-pub(crate) struct DMaterialName(#[serde(with = "serde_arrays")] [u8; 64]);
-
-impl core::fmt::Display for DMaterialName {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let len = self
-            .0
-            .into_iter()
-            .enumerate()
-            .find(|(_, c)| *c == 0)
-            .map(|(i, _)| i)
-            .unwrap_or(64);
-        let s = self.0[..len].iter().map(|c| *c as char).collect::<String>();
-        write!(f, "{}", s)
-    }
-}
-
-impl Default for DMaterialName {
-    fn default() -> Self {
-        Self([0u8; 64])
-    }
-}
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct DMaterialRaw {
-    pub material: DMaterialName,
+    pub material: FixedString<64>,
     pub surface_flags: i32,
     pub content_flags: i32,
 }
 assert_size!(DMaterialRaw, 72);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DMaterial {
     pub material: String,
     pub surface_flags: i32,
@@ -348,7 +559,7 @@ impl From<DMaterialRaw> for DMaterial {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CNodeRaw<'a> {
     pub plane: Ptr32<'a, CPlaneRaw>,
     pub children: [i16; 2],
@@ -356,7 +567,8 @@ pub(crate) struct CNodeRaw<'a> {
 assert_size!(CNodeRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CNode {
     pub plane: Option<Box<CPlane>>,
     pub children: [i16; 2],
@@ -374,7 +586,7 @@ impl<'a> XFileDeserializeInto<CNode, ()> for CNodeRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CLeafRaw {
     pub first_coll_aabb_index: u16,
     pub coll_aabb_count: u16,
@@ -388,7 +600,8 @@ pub(crate) struct CLeafRaw {
 assert_size!(CLeafRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CLeaf {
     pub first_coll_aabb_index: usize,
     pub coll_aabb_count: usize,
@@ -416,7 +629,7 @@ impl From<CLeafRaw> for CLeaf {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CLeafBrushNodeRaw<'a> {
     pub axis: u8,
     pub leaf_brush_count: i16,
@@ -426,7 +639,8 @@ pub(crate) struct CLeafBrushNodeRaw<'a> {
 assert_size!(CLeafBrushNodeRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CLeafBrushNode {
     pub axis: u8,
     pub leaf_brush_count: usize,
@@ -454,12 +668,13 @@ impl<'a> XFileDeserializeInto<CLeafBrushNode, ()> for CLeafBrushNodeRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CLeafBrushNodeDataRaw<'a>(Ptr32<'a, ()>);
 assert_size!(CLeafBrushNodeDataRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum CLeafBrushNodeData {
     Leaf(CLeafBrushNodeLeaf),
     Children(CLeafBrushNodeChildren),
@@ -480,12 +695,11 @@ impl<'a> XFileDeserializeInto<Option<CLeafBrushNodeData>, i16> for CLeafBrushNod
         if leaf_brush_count < 1 {
             Ok(None)
         } else {
-            let Some(leaf) = self
+            let leaf: Option<Box<_>> = self
                 .0
                 .cast::<CLeafBrushNodeLeafRaw>()
-                .xfile_deserialize_into(de, leaf_brush_count)?
-                .map(|l| CLeafBrushNodeData::Leaf(*l))
-            else {
+                .xfile_deserialize_into(de, leaf_brush_count)?;
+            let Some(leaf) = leaf.map(|l| CLeafBrushNodeData::Leaf(*l)) else {
                 return Ok(None);
             };
             Ok(Some(leaf))
@@ -494,14 +708,15 @@ impl<'a> XFileDeserializeInto<Option<CLeafBrushNodeData>, i16> for CLeafBrushNod
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CLeafBrushNodeLeafRaw<'a> {
     pub brushes: Ptr32<'a, u16>,
 }
 assert_size!(CLeafBrushNodeLeafRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct CLeafBrushNodeLeaf {
     pub brushes: Vec<u16>,
 }
@@ -512,14 +727,18 @@ impl<'a> XFileDeserializeInto<CLeafBrushNodeLeaf, i16> for CLeafBrushNodeLeafRaw
         de: &mut impl T5XFileDeserialize,
         leaf_brush_count: i16,
     ) -> Result<CLeafBrushNodeLeaf> {
-        let brushes = self.brushes.to_array(leaf_brush_count as _).to_vec(de)?;
+        let brushes = self
+            .brushes
+            .to_array(leaf_brush_count as _)
+            .to_vec_bulk(de)?;
 
         Ok(CLeafBrushNodeLeaf { brushes })
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct CLeafBrushNodeChildren {
     pub dist: f32,
     pub range: f32,
@@ -528,7 +747,7 @@ pub struct CLeafBrushNodeChildren {
 assert_size!(CLeafBrushNodeChildren, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CollisionBorderRaw {
     pub dist_eq: [f32; 3],
     pub z_slope: f32,
@@ -539,7 +758,8 @@ pub(crate) struct CollisionBorderRaw {
 assert_size!(CollisionBorderRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CollisionBorder {
     pub dist_eq: Vec3,
     pub z_slope: f32,
@@ -561,7 +781,7 @@ impl From<CollisionBorderRaw> for CollisionBorder {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CollisionPartitionRaw<'a> {
     pub tri_count: u8,
     pub border_count: u8,
@@ -573,7 +793,8 @@ pub(crate) struct CollisionPartitionRaw<'a> {
 assert_size!(CollisionPartitionRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CollisionPartition {
     pub tri_count: u8,
     pub border_count: u8,
@@ -603,7 +824,7 @@ impl<'a> XFileDeserializeInto<CollisionPartition, ()> for CollisionPartitionRaw<
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CollisionAabbTreeRaw {
     pub origin: [f32; 3],
     pub material_index: u16,
@@ -614,7 +835,8 @@ pub(crate) struct CollisionAabbTreeRaw {
 assert_size!(CollisionAabbTreeRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CollisionAabbTree {
     pub origin: Vec3,
     pub material_index: usize,
@@ -636,7 +858,7 @@ impl From<CollisionAabbTreeRaw> for CollisionAabbTree {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CModelRaw {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -646,7 +868,8 @@ pub(crate) struct CModelRaw {
 assert_size!(CModelRaw, 72);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CModel {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -666,7 +889,7 @@ impl From<CModelRaw> for CModel {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CBrushRaw<'a> {
     pub mins: [f32; 3],
     pub contents: i32,
@@ -675,12 +898,14 @@ pub(crate) struct CBrushRaw<'a> {
     pub axial_cflags: [[i32; 3]; 2],
     pub axial_sflags: [[i32; 3]; 2],
     pub verts: FatPointerCountFirstU32<'a, [f32; 3]>,
+    #[allow(dead_code)]
     pad: [u8; 4],
 }
 assert_size!(CBrushRaw, 96);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct CBrush {
     pub mins: Vec3,
     pub contents: i32,
@@ -714,8 +939,128 @@ impl<'a> XFileDeserializeInto<CBrush, ()> for CBrushRaw<'a> {
     }
 }
 
+impl CBrush {
+    /// Every plane bounding the brush, as a normal and a distance from the
+    /// origin, starting with the six from its bounds.
+    fn planes(&self) -> impl Iterator<Item = ([f32; 3], f32)> + '_ {
+        // the axial planes aren't stored as sides
+        let (mins, maxs) = (self.mins.get(), self.maxs.get());
+        let axial = (0..3).flat_map(move |i| {
+            let mut normal = [0.0; 3];
+            normal[i] = 1.0;
+            let pos = (normal, maxs[i]);
+            normal[i] = -1.0;
+            [pos, (normal, -mins[i])]
+        });
+        let sides = self
+            .sides
+            .iter()
+            .filter_map(|s| s.plane.as_ref())
+            .map(|p| (p.normal.get(), p.dist));
+
+        axial.chain(sides)
+    }
+
+    /// Builds the faces of the brush, as convex polygons wound
+    /// counterclockwise seen from outside it.
+    ///
+    /// Each face starts as a quad covering the brush's bounds on one of its
+    /// planes, and is clipped against every other plane. Planes that end up
+    /// with nothing left (e.g., sides that don't touch the brush) don't get
+    /// a face, and neither do duplicates of an earlier plane.
+    pub fn polygons(&self) -> Vec<Vec<[f32; 3]>> {
+        const EPSILON: f32 = 0.01;
+
+        let planes = self.planes().collect::<Vec<_>>();
+        let (mins, maxs) = (self.mins.get(), self.maxs.get());
+        let center = [0, 1, 2].map(|i| (mins[i] + maxs[i]) * 0.5);
+        let size = (0..3).map(|i| maxs[i] - mins[i]).sum::<f32>() + 1.0;
+
+        let mut polygons = Vec::new();
+        'planes: for (i, &(normal, dist)) in planes.iter().enumerate() {
+            // a right-handed basis on the plane, from the axis least like
+            // its normal
+            let axis = match normal.map(f32::abs) {
+                [x, y, z] if x <= y && x <= z => [1.0, 0.0, 0.0],
+                [_, y, z] if y <= z => [0.0, 1.0, 0.0],
+                _ => [0.0, 0.0, 1.0],
+            };
+            let u = normalize(cross(normal, axis));
+            let v = cross(normal, u);
+
+            let d = dot(center, normal) - dist;
+            let origin = [0, 1, 2].map(|k| center[k] - normal[k] * d);
+            let mut polygon = [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)]
+                .map(|(a, b)| [0, 1, 2].map(|k| origin[k] + (u[k] * a + v[k] * b) * size))
+                .to_vec();
+
+            for (j, &(clip_normal, clip_dist)) in planes.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                if dot(normal, clip_normal) > 1.0 - EPSILON && (dist - clip_dist).abs() < EPSILON {
+                    if j < i {
+                        continue 'planes;
+                    }
+                    continue;
+                }
+
+                polygon = clip_polygon(&polygon, clip_normal, clip_dist, EPSILON);
+                if polygon.len() < 3 {
+                    continue 'planes;
+                }
+            }
+
+            polygons.push(polygon);
+        }
+
+        polygons
+    }
+    /// Clips the segment from `start` to `end` against the brush, returning
+    /// the fraction along it where it enters the brush and the normal of the
+    /// plane it enters through, or [`None`] if it misses.
+    ///
+    /// A segment starting inside the brush returns `(0.0, [0.0; 3])`.
+    pub fn trace(&self, start: Vec3, end: Vec3) -> Option<(f32, [f32; 3])> {
+        Aabb::new(self.mins, self.maxs).segment_intersection(start, end)?;
+
+        let (start, end) = (start.get(), end.get());
+        let (mut enter, mut exit) = (-1.0f32, 1.0f32);
+        let mut enter_normal = [0.0; 3];
+        for (normal, dist) in self.planes() {
+            let d_start = dot(start, normal) - dist;
+            let d_end = dot(end, normal) - dist;
+            if d_start > 0.0 && d_end > 0.0 {
+                return None;
+            }
+            if d_start <= 0.0 && d_end <= 0.0 {
+                continue;
+            }
+
+            let f = d_start / (d_start - d_end);
+            if d_start > d_end {
+                if f > enter {
+                    enter = f;
+                    enter_normal = normal;
+                }
+            } else {
+                exit = exit.min(f);
+            }
+            if enter > exit {
+                return None;
+            }
+        }
+
+        if enter < 0.0 {
+            Some((0.0, [0.0; 3]))
+        } else {
+            Some((enter, enter_normal))
+        }
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct DynEntityDefRaw<'a> {
     pub type_: i32,
     pub pose: GfxPlacementRaw,
@@ -736,8 +1081,9 @@ pub(crate) struct DynEntityDefRaw<'a> {
 }
 assert_size!(DynEntityDefRaw, 84);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize, PartialEq, Eq, FromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, FromPrimitive)]
 pub enum DynEntityType {
     #[default]
     INVALID = 0,
@@ -747,7 +1093,8 @@ pub enum DynEntityType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DynEntityDef {
     pub type_: DynEntityType,
     pub pose: GfxPlacement,
@@ -809,7 +1156,7 @@ impl<'a> XFileDeserializeInto<DynEntityDef, ()> for DynEntityDefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxPlacementRaw {
     pub quat: [f32; 4],
     pub origin: [f32; 3],
@@ -817,7 +1164,8 @@ pub(crate) struct GfxPlacementRaw {
 assert_size!(GfxPlacementRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxPlacement {
     pub quat: Vec4,
     pub origin: Vec3,
@@ -833,7 +1181,7 @@ impl From<GfxPlacementRaw> for GfxPlacement {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct DynEntityPoseRaw {
     pub pose: GfxPlacementRaw,
     pub radius: f32,
@@ -841,7 +1189,8 @@ pub(crate) struct DynEntityPoseRaw {
 assert_size!(DynEntityPoseRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DynEntityPose {
     pub pose: GfxPlacement,
     pub radius: f32,
@@ -856,8 +1205,9 @@ impl From<DynEntityPoseRaw> for DynEntityPose {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct DynEntityClient {
     pub phys_obj_id: i32,
     pub flags: u16,
@@ -869,8 +1219,9 @@ pub struct DynEntityClient {
 }
 assert_size!(DynEntityClient, 20);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct DynEntityServer {
     pub flags: u16,
     pub health: i32,
@@ -878,7 +1229,7 @@ pub struct DynEntityServer {
 assert_size!(DynEntityServer, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct DynEntityCollRaw {
     pub sector: u16,
     pub next_ent_in_sector: u16,
@@ -889,7 +1240,8 @@ pub(crate) struct DynEntityCollRaw {
 assert_size!(DynEntityCollRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DynEntityColl {
     pub sector: u16,
     pub next_ent_in_sector: u16,
@@ -911,7 +1263,7 @@ impl From<DynEntityCollRaw> for DynEntityColl {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct RopeRaw<'a> {
     pub m_particles: [ParRaw; 25],
     pub m_constraints: [ConstraintRaw; 30],
@@ -940,12 +1292,14 @@ pub(crate) struct RopeRaw<'a> {
     pub m_stable_count: i32,
     pub m_static_rope: i32,
     pub m_lighting_handle: u16,
+    #[allow(dead_code)]
     pad: [u8; 2],
 }
 assert_size!(RopeRaw, 3188);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Rope {
     pub m_particles: [Par; 25],
     pub m_constraints: [Constraint; 30],
@@ -963,7 +1317,7 @@ pub struct Rope {
     pub m_visible: bool,
     pub m_dist_constraint: i32,
     pub m_flags: i32,
-    pub m_material: Option<Box<Material>>,
+    pub m_material: Option<Shared<Material>>,
     pub m_seglen: f32,
     pub m_length: f32,
     pub m_width: f32,
@@ -1019,7 +1373,7 @@ impl<'a> XFileDeserializeInto<Rope, ()> for RopeRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ParRaw {
     pub p: [f32; 3],
     pub p0: [f32; 3],
@@ -1029,7 +1383,8 @@ pub(crate) struct ParRaw {
 assert_size!(ParRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Par {
     pub p: Vec3,
     pub p0: Vec3,
@@ -1049,7 +1404,7 @@ impl From<ParRaw> for Par {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ConstraintRaw {
     pub p: [f32; 3],
     pub type_: i32,
@@ -1061,7 +1416,8 @@ pub(crate) struct ConstraintRaw {
 assert_size!(ConstraintRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Constraint {
     pub p: Vec3,
     pub type_: RopeConstraint,
@@ -1071,8 +1427,9 @@ pub struct Constraint {
     pub pi2: u8,
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize, PartialEq, Eq, FromPrimitive)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, FromPrimitive)]
 pub enum RopeConstraint {
     #[default]
     PAIR = 0,
@@ -1101,7 +1458,7 @@ impl TryFrom<ConstraintRaw> for Constraint {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct RopeClientVertsRaw {
     pub frame_verts: [RopeFrameVertsRaw; 2],
     pub frame_index: u32,
@@ -1109,7 +1466,8 @@ pub(crate) struct RopeClientVertsRaw {
 assert_size!(RopeClientVertsRaw, 1212);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct RopeClientVerts {
     pub frame_verts: [RopeFrameVerts; 2],
     pub frame_index: usize,
@@ -1125,10 +1483,10 @@ impl From<RopeClientVertsRaw> for RopeClientVerts {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct RopeFrameVertsRaw {
     pub num_verts: i32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub v: [[f32; 3]; 50],
 }
 assert_size!(RopeFrameVertsRaw, 604);
@@ -1143,10 +1501,11 @@ impl Default for RopeFrameVertsRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct RopeFrameVerts {
     pub num_verts: i32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub v: [Vec3; 50],
 }
 
@@ -0,0 +1,250 @@
+//! Building the [`XSurfaceCollisionTree`]s rigid surfaces are traced
+//! against, e.g. for models that were imported rather than loaded from a
+//! Fastfile.
+//!
+//! A tree is a bounding volume hierarchy over the triangles of one
+//! [`XRigidVertList`]. Its bounds are quantized to 16 bits per axis (see
+//! [`XSurfaceCollisionTree::quantize`]). Node 0 is the root, and a node's
+//! children are the [`XSurfaceCollisionNode::child_count`] nodes starting at
+//! [`XSurfaceCollisionNode::child_begin_index`]. Nodes without children are
+//! leaf nodes, whose `child_begin_index` is the index of an
+//! [`XSurfaceCollisionLeaf`] instead. Each leaf covers the triangles from
+//! its [`XSurfaceCollisionLeaf::triangle_begin_index`] up to the next
+//! leaf's.
+
+use alloc::{boxed::Box, format, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result,
+    common::Vec3,
+    file_line_col,
+    xmodel::{
+        XRigidVertList, XSurface, XSurfaceCollisionAabb, XSurfaceCollisionLeaf,
+        XSurfaceCollisionNode, XSurfaceCollisionTree,
+    },
+};
+
+/// The largest quantized coordinate.
+const QUANTIZED_MAX: f32 = u16::MAX as f32;
+
+impl XSurfaceCollisionTree {
+    /// Quantizes a position in model space into the tree's bounds, clamping
+    /// it to them if it's outside.
+    pub fn quantize(&self, position: Vec3) -> [u16; 3] {
+        let (p, trans, scale) = (position.get(), self.trans.get(), self.scale.get());
+        core::array::from_fn(|i| ((p[i] + trans[i]) * scale[i]).clamp(0.0, QUANTIZED_MAX) as u16)
+    }
+
+    /// The inverse of [`Self::quantize`], give or take the precision lost.
+    pub fn dequantize(&self, quantized: [u16; 3]) -> Vec3 {
+        let (trans, scale) = (self.trans.get(), self.scale.get());
+        Vec3::from(core::array::from_fn(|i| {
+            quantized[i] as f32 / scale[i] - trans[i]
+        }))
+    }
+}
+
+/// Builds [`XSurfaceCollisionTree`]s. See the [module docs](self).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct XSurfaceCollisionTreeBuilder {
+    max_leaf_triangles: usize,
+}
+
+impl Default for XSurfaceCollisionTreeBuilder {
+    fn default() -> Self {
+        Self {
+            max_leaf_triangles: 4,
+        }
+    }
+}
+
+impl XSurfaceCollisionTreeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The most triangles a leaf can cover (at least 1). Smaller leaves
+    /// make for tighter bounds, but bigger trees.
+    pub fn with_max_leaf_triangles(mut self, max_leaf_triangles: usize) -> Self {
+        self.max_leaf_triangles = max_leaf_triangles.max(1);
+        self
+    }
+
+    /// Builds a tree over `triangles`, which index into `positions`.
+    ///
+    /// Leaves cover consecutive triangles, so `triangles` are reordered to
+    /// group nearby ones together, and the tree's triangle indices are
+    /// relative to the start of `triangles`.
+    ///
+    /// Fails with [`ErrorKind::BrokenInvariant`] if a triangle refers to a
+    /// position that doesn't exist, or if there are more triangles or nodes
+    /// than the tree can index.
+    pub fn build(
+        &self,
+        positions: &[Vec3],
+        triangles: &mut [[u16; 3]],
+    ) -> Result<XSurfaceCollisionTree> {
+        if let Some(&index) = triangles
+            .as_flattened()
+            .iter()
+            .find(|&&i| i as usize >= positions.len())
+        {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurfaceCollisionTreeBuilder: index {index} >= positions.len() ({})",
+                    positions.len()
+                )),
+            ));
+        }
+        if triangles.len() > u16::MAX as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurfaceCollisionTreeBuilder: {} triangles > u16::MAX",
+                    triangles.len()
+                )),
+            ));
+        }
+
+        let positions = positions.iter().map(|p| p.get()).collect::<Vec<_>>();
+        let (mins, maxs) = bounds(&positions, triangles);
+        let extent: [f32; 3] = core::array::from_fn(|i| maxs[i] - mins[i]);
+        let mut tree = XSurfaceCollisionTree {
+            trans: Vec3::from(mins.map(|m| -m)),
+            scale: Vec3::from(extent.map(|e| if e > 0.0 { QUANTIZED_MAX / e } else { 1.0 })),
+            nodes: Vec::new(),
+            leafs: Vec::new(),
+        };
+        if triangles.is_empty() {
+            return Ok(tree);
+        }
+
+        let mut build = Build {
+            positions: &positions,
+            max_leaf_triangles: self.max_leaf_triangles,
+            tree: &mut tree,
+        };
+        build.tree.nodes.push(XSurfaceCollisionNode::default());
+        build.node(0, triangles, 0);
+
+        if tree.nodes.len() > u16::MAX as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurfaceCollisionTreeBuilder: {} nodes > u16::MAX (try more triangles per leaf)",
+                    tree.nodes.len()
+                )),
+            ));
+        }
+        Ok(tree)
+    }
+
+    /// Builds a tree for each of `surface`'s [`XRigidVertList`]s (see
+    /// [`Self::build`]), replacing any it already had. Each list's triangles
+    /// are reordered within its range of [`XSurface::tri_indices`].
+    ///
+    /// Skinned surfaces have no rigid vertex lists, and so no trees.
+    pub fn build_for_surface(&self, surface: &mut XSurface) -> Result<()> {
+        let positions = surface.verts0.iter().map(|v| v.xyz).collect::<Vec<_>>();
+        let triangles = surface.tri_indices.as_chunks_mut::<3>().0;
+
+        for vert_list in &mut surface.vert_list {
+            let XRigidVertList {
+                tri_offset,
+                tri_count,
+                ..
+            } = *vert_list;
+            let Some(triangles) = triangles.get_mut(tri_offset..tri_offset + tri_count) else {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "XSurface: vert_list triangles {tri_offset}..{} are out of range (there are {})",
+                        tri_offset + tri_count,
+                        triangles.len()
+                    )),
+                ));
+            };
+            vert_list.collision_tree = Some(Box::new(self.build(&positions, triangles)?));
+        }
+
+        Ok(())
+    }
+}
+
+/// The bounds of every position `triangles` refer to.
+fn bounds(positions: &[[f32; 3]], triangles: &[[u16; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut mins = [f32::MAX; 3];
+    let mut maxs = [f32::MIN; 3];
+    for &i in triangles.as_flattened() {
+        let p = positions[i as usize];
+        for axis in 0..3 {
+            mins[axis] = mins[axis].min(p[axis]);
+            maxs[axis] = maxs[axis].max(p[axis]);
+        }
+    }
+    (mins, maxs)
+}
+
+struct Build<'a> {
+    positions: &'a [[f32; 3]],
+    max_leaf_triangles: usize,
+    tree: &'a mut XSurfaceCollisionTree,
+}
+
+impl Build<'_> {
+    /// Fills in the (already allocated) node `index` over `triangles`, the
+    /// first of which is triangle `first` of the whole tree.
+    fn node(&mut self, index: usize, triangles: &mut [[u16; 3]], first: usize) {
+        let (mins, maxs) = bounds(self.positions, triangles);
+        let aabb = XSurfaceCollisionAabb {
+            mins: self.tree.quantize(Vec3::from(mins)),
+            maxs: self.tree.quantize(Vec3::from(maxs)).map(|m| {
+                // round up, so the bounds contain every triangle
+                m.saturating_add(1)
+            }),
+        };
+
+        if triangles.len() <= self.max_leaf_triangles {
+            self.tree.nodes[index] = XSurfaceCollisionNode {
+                aabb,
+                child_begin_index: self.tree.leafs.len(),
+                child_count: 0,
+            };
+            self.tree.leafs.push(XSurfaceCollisionLeaf {
+                triangle_begin_index: first,
+            });
+            return;
+        }
+
+        // Split at the median centroid along the longest axis
+        let axis = (0..3)
+            .max_by(|&a, &b| (maxs[a] - mins[a]).total_cmp(&(maxs[b] - mins[b])))
+            .unwrap();
+        let centroid = |t: &[u16; 3]| {
+            t.iter()
+                .map(|&i| self.positions[i as usize][axis])
+                .sum::<f32>()
+        };
+        triangles.sort_by(|a, b| centroid(a).total_cmp(&centroid(b)));
+        let mid = triangles.len() / 2;
+
+        let child_begin_index = self.tree.nodes.len();
+        self.tree.nodes[index] = XSurfaceCollisionNode {
+            aabb,
+            child_begin_index,
+            child_count: 2,
+        };
+        self.tree.nodes.extend([
+            XSurfaceCollisionNode::default(),
+            XSurfaceCollisionNode::default(),
+        ]);
+
+        let (left, right) = triangles.split_at_mut(mid);
+        self.node(child_begin_index, left, first);
+        self.node(child_begin_index + 1, right, first + mid);
+    }
+}
@@ -1,15 +1,22 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32ArrayConst, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, Ptr32ArrayConst, Result,
+    T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     common::{Vec3, Vec4},
+    file_line_col,
+    gfx_world::{GfxLightRegion, GfxShadowGeometry, GfxWorld},
 };
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize};
 
+use num::FromPrimitive;
+use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, XFileRead)]
 pub(crate) struct ComWorldRaw<'a> {
     pub name: XStringRaw<'a>,
     pub is_in_use: i32,
@@ -21,7 +28,8 @@ pub(crate) struct ComWorldRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct ComWorld {
     pub name: XString,
     pub is_in_use: bool,
@@ -32,6 +40,90 @@ pub struct ComWorld {
     pub burnable_cells: Vec<ComBurnableCell>,
 }
 
+impl ComWorld {
+    /// Combines the primary lights of this world with what `world` (the
+    /// [`GfxWorld`] of the same map) knows about them. See
+    /// [`PrimaryLight::correlate`].
+    pub fn light_list<'a, const MAX_LOCAL_CLIENTS: usize>(
+        &'a self,
+        world: &'a GfxWorld<MAX_LOCAL_CLIENTS>,
+    ) -> Result<Vec<PrimaryLight<'a>>> {
+        PrimaryLight::correlate(
+            &self.primary_lights,
+            world.sun_primary_light_index,
+            world.primary_light_count as _,
+            &world.shadow_geom,
+            &world.light_region,
+        )
+    }
+}
+
+/// A map's primary light, as described by both its [`ComWorld`] and its
+/// [`GfxWorld`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PrimaryLight<'a> {
+    /// The light's index into [`ComWorld::primary_lights`], which the
+    /// [`GfxWorld`] refers to it by as well (e.g. in
+    /// [`GfxLightGridEntry::primary_light_index`](crate::gfx_world::GfxLightGridEntry::primary_light_index)).
+    pub id: usize,
+    pub light: &'a ComPrimaryLight,
+    pub is_sun: bool,
+    /// The surfaces and static models the light casts shadows from.
+    pub shadow_geometry: Option<&'a GfxShadowGeometry>,
+    /// The hulls the light is limited to.
+    pub region: Option<&'a GfxLightRegion>,
+}
+
+impl<'a> PrimaryLight<'a> {
+    /// Combines a [`ComWorld`]'s primary lights with the parts of a
+    /// [`GfxWorld`] indexed by them ([`GfxWorld::sun_primary_light_index`],
+    /// [`GfxWorld::primary_light_count`], [`GfxWorld::shadow_geom`] and
+    /// [`GfxWorld::light_region`]), into one list ordered by
+    /// [`PrimaryLight::id`].
+    ///
+    /// The first light is a placeholder that's never used, and is left out.
+    ///
+    /// Fails with [`ErrorKind::BrokenInvariant`] if the two worlds don't
+    /// agree on the number of lights, e.g. because they're from different
+    /// maps.
+    pub fn correlate(
+        lights: &'a [ComPrimaryLight],
+        sun_primary_light_index: usize,
+        primary_light_count: usize,
+        shadow_geom: &'a [GfxShadowGeometry],
+        light_region: &'a [GfxLightRegion],
+    ) -> Result<Vec<Self>> {
+        if lights.len() != primary_light_count {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "PrimaryLight: the ComWorld has {} primary lights, but the GfxWorld has {}",
+                    lights.len(),
+                    primary_light_count
+                )),
+            ));
+        }
+
+        Ok(lights
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(id, light)| PrimaryLight {
+                id,
+                light,
+                is_sun: id == sun_primary_light_index,
+                shadow_geometry: shadow_geom.get(id),
+                region: light_region.get(id),
+            })
+            .collect())
+    }
+
+    /// See [`ComPrimaryLight::light_type`].
+    pub fn light_type(&self) -> Option<GfxLightType> {
+        self.light.light_type()
+    }
+}
+
 impl<'a> XFileDeserializeInto<ComWorld, ()> for ComWorldRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -55,6 +147,7 @@ impl<'a> XFileDeserializeInto<ComWorld, ()> for ComWorldRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComWorld {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -84,7 +177,7 @@ impl XFileSerialize<()> for ComWorld {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, XFileRead)]
 pub(crate) struct ComPrimaryLightRaw<'a> {
     pub type_: u8,
     pub can_use_shadow_map: u8,
@@ -117,7 +210,8 @@ pub(crate) struct ComPrimaryLightRaw<'a> {
 assert_size!(ComPrimaryLightRaw, 220);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct ComPrimaryLight {
     pub type_: u8,
     pub can_use_shadow_map: bool,
@@ -146,6 +240,23 @@ pub struct ComPrimaryLight {
     pub def_name: XString,
 }
 
+impl ComPrimaryLight {
+    /// The light's type, or [`None`] if [`Self::type_`] isn't a
+    /// [`GfxLightType`].
+    pub fn light_type(&self) -> Option<GfxLightType> {
+        FromPrimitive::from_u8(self.type_)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+pub enum GfxLightType {
+    NONE = 0,
+    DIR = 1,
+    SPOT = 2,
+    OMNI = 3,
+}
+
 impl<'a> XFileDeserializeInto<ComPrimaryLight, ()> for ComPrimaryLightRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -196,6 +307,7 @@ impl<'a> XFileDeserializeInto<ComPrimaryLight, ()> for ComPrimaryLightRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComPrimaryLight {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let def_name = XStringRaw::from_str(self.def_name.get());
@@ -234,8 +346,9 @@ impl XFileSerialize<()> for ComPrimaryLight {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct ComWaterHeader {
     pub minx: i32,
     pub miny: i32,
@@ -244,14 +357,16 @@ pub struct ComWaterHeader {
 }
 assert_size!(ComWaterHeader, 16);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComWaterHeader {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct ComWaterCell {
     pub waterheight: i16,
     pub flooroffset: u8,
@@ -260,14 +375,16 @@ pub struct ComWaterCell {
 }
 assert_size!(ComWaterCell, 8);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComWaterCell {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct ComBurnableHeader {
     pub minx: i32,
     pub miny: i32,
@@ -276,6 +393,7 @@ pub struct ComBurnableHeader {
 }
 assert_size!(ComWaterHeader, 16);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComBurnableHeader {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -283,7 +401,7 @@ impl XFileSerialize<()> for ComBurnableHeader {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ComBurnableCellRaw<'a> {
     pub x: i32,
     pub y: i32,
@@ -292,7 +410,8 @@ pub(crate) struct ComBurnableCellRaw<'a> {
 assert_size!(ComWaterHeader, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct ComBurnableCell {
     pub x: i32,
     pub y: i32,
@@ -318,6 +437,7 @@ impl<'a> XFileDeserializeInto<ComBurnableCell, ()> for ComBurnableCellRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComBurnableCell {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let data = self
@@ -337,13 +457,15 @@ impl XFileSerialize<()> for ComBurnableCell {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct ComBurnableSample {
     pub state: u8,
 }
 assert_size!(ComBurnableSample, 1);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for ComBurnableSample {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -4,7 +4,9 @@
 #[cfg(feature = "serde")]
 use core::mem::transmute;
 
-use crate::{Result, T5XFileSerialize, XFileSerialize, assert_size, size_of};
+use crate::{Result, XFileRead, XFileReader};
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize, assert_size, size_of};
 
 #[cfg(feature = "serde")]
 use serde::de::Visitor;
@@ -13,7 +15,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[cfg(all(feature = "serde", feature = "cgmath"))]
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec2(pub cgmath::Vector2<f32>);
 #[cfg(feature = "cgmath")]
@@ -51,7 +53,8 @@ impl<'de> Deserialize<'de> for Vec2 {
 
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec2(pub [f32; 2]);
 #[cfg(not(feature = "cgmath"))]
@@ -61,6 +64,7 @@ impl From<[f32; 2]> for Vec2 {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Vec2 {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(self.0)
@@ -80,7 +84,7 @@ impl Vec2 {
 }
 
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec3(cgmath::Vector3<f32>);
 #[cfg(feature = "cgmath")]
@@ -122,7 +126,8 @@ impl<'de> Deserialize<'de> for Vec3 {
 }
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec3(pub [f32; 3]);
 #[cfg(not(feature = "cgmath"))]
@@ -132,6 +137,7 @@ impl From<[f32; 3]> for Vec3 {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Vec3 {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(self.0)
@@ -148,10 +154,21 @@ impl Vec3 {
     pub fn get(self) -> [f32; 3] {
         [self.0.x, self.0.y, self.0.z]
     }
+
+    /// Converts a position from the game's coordinates to `convention`'s.
+    pub fn convert_point(self, convention: CoordinateConvention) -> Self {
+        convention.convert_point(self.get()).into()
+    }
+
+    /// Converts a direction (e.g., a normal) from the game's coordinates to
+    /// `convention`'s, leaving its length as is.
+    pub fn convert_direction(self, convention: CoordinateConvention) -> Self {
+        convention.convert_direction(self.get()).into()
+    }
 }
 
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec4(cgmath::Vector4<f32>);
 #[cfg(feature = "cgmath")]
@@ -196,7 +213,8 @@ impl<'de> Deserialize<'de> for Vec4 {
 }
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Vec4(pub [f32; 4]);
 #[cfg(not(feature = "cgmath"))]
@@ -219,7 +237,7 @@ impl Vec4 {
 }
 
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat2(pub cgmath::Matrix2<f32>);
 #[cfg(feature = "cgmath")]
@@ -255,12 +273,25 @@ impl<'de> Deserialize<'de> for Mat2 {
 }
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat2(pub [Vec2; 2]);
 
+impl Mat2 {
+    #[cfg(not(feature = "cgmath"))]
+    pub fn get(self) -> [[f32; 2]; 2] {
+        [self.0[0].get(), self.0[1].get()]
+    }
+
+    #[cfg(feature = "cgmath")]
+    pub fn get(self) -> [[f32; 2]; 2] {
+        [[self.0.x.x, self.0.x.y], [self.0.y.x, self.0.y.y]]
+    }
+}
+
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat3(pub cgmath::Matrix3<f32>);
 #[cfg(feature = "cgmath")]
@@ -342,7 +373,8 @@ impl<'de> Deserialize<'de> for Mat3 {
 }
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat3(pub [Vec3; 3]);
 #[cfg(not(feature = "cgmath"))]
@@ -366,10 +398,17 @@ impl Mat3 {
             [self.0.z.x, self.0.z.y, self.0.z.z],
         ]
     }
+
+    /// Converts a rotation (e.g., a static model's
+    /// [`axis`](crate::gfx_world::GfxPackedPlacement::axis)) from the game's
+    /// coordinates to `convention`'s.
+    pub fn convert(self, convention: CoordinateConvention) -> Self {
+        convention.convert_rotation(self.get()).into()
+    }
 }
 
 #[cfg(feature = "cgmath")]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat4(pub cgmath::Matrix4<f32>);
 #[cfg(feature = "cgmath")]
@@ -460,7 +499,8 @@ impl<'de> Deserialize<'de> for Mat4 {
 }
 #[cfg(not(feature = "cgmath"))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(transparent)]
 pub struct Mat4(pub [Vec4; 4]);
 #[cfg(not(feature = "cgmath"))]
@@ -475,6 +515,581 @@ impl From<[[f32; 4]; 4]> for Mat4 {
     }
 }
 
+impl Mat4 {
+    #[cfg(not(feature = "cgmath"))]
+    pub fn get(self) -> [[f32; 4]; 4] {
+        [
+            self.0[0].get(),
+            self.0[1].get(),
+            self.0[2].get(),
+            self.0[3].get(),
+        ]
+    }
+
+    #[cfg(feature = "cgmath")]
+    pub fn get(self) -> [[f32; 4]; 4] {
+        self.0.into()
+    }
+
+    /// Converts a transform from the game's coordinates to `convention`'s
+    /// (see [`CoordinateConvention::convert_matrix`]).
+    pub fn convert(self, convention: CoordinateConvention) -> Self {
+        convention.convert_matrix(self.get()).into()
+    }
+}
+
+macro_rules! impl_xfile_read_from_array {
+    ($($t:ty: $a:ty,)+) => {
+        $(
+            impl XFileRead for $t {
+                fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+                    <$a>::xfile_read(r).map(Into::into)
+                }
+            }
+        )+
+    };
+}
+
+impl_xfile_read_from_array!(
+    Vec2: [f32; 2],
+    Vec3: [f32; 3],
+    Vec4: [f32; 4],
+    Mat3: [[f32; 3]; 3],
+    Mat4: [[f32; 4]; 4],
+);
+
+// Matrices are treated as a list of where each axis ends up, so that the
+// inner arrays of `Mat3::get` and `Mat4::get` are columns for the usual
+// `M * v` math (and rows for Direct3D's `v * M`).
+
+impl core::ops::Mul for Mat3 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let cols = rhs.get().map(|col| (self * Vec3::from(col)).get());
+        cols.into()
+    }
+}
+
+impl core::ops::Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        let (m, v) = (self.get(), rhs.get());
+        [0, 1, 2]
+            .map(|i| m[0][i] * v[0] + m[1][i] * v[1] + m[2][i] * v[2])
+            .into()
+    }
+}
+
+impl core::ops::Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let m = self.get();
+        let cols = rhs
+            .get()
+            .map(|v| [0, 1, 2, 3].map(|i| (0..4).map(|k| m[k][i] * v[k]).sum::<f32>()));
+        cols.into()
+    }
+}
+
+impl Mat4 {
+    /// Transforms `point`, including the translation. The projective part of
+    /// the matrix is ignored.
+    pub fn transform_point(self, point: Vec3) -> Vec3 {
+        let (m, p) = (self.get(), point.get());
+        [0, 1, 2]
+            .map(|i| m[0][i] * p[0] + m[1][i] * p[1] + m[2][i] * p[2] + m[3][i])
+            .into()
+    }
+
+    /// Transforms `vector`, without the translation.
+    pub fn transform_vector(self, vector: Vec3) -> Vec3 {
+        let (m, v) = (self.get(), vector.get());
+        [0, 1, 2]
+            .map(|i| m[0][i] * v[0] + m[1][i] * v[1] + m[2][i] * v[2])
+            .into()
+    }
+}
+
+impl Mat3 {
+    pub fn identity() -> Self {
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]].into()
+    }
+
+    pub fn transpose(self) -> Self {
+        let m = self.get();
+        [0, 1, 2].map(|i| [m[0][i], m[1][i], m[2][i]]).into()
+    }
+
+    /// The rotation `quat` (x, y, z, w) describes. `quat` doesn't need to be
+    /// normalized; a zero quaternion gives the identity.
+    pub fn from_quat(quat: Vec4) -> Self {
+        let [x, y, z, w] = quat.get();
+        let len_sq = x * x + y * y + z * z + w * w;
+        if len_sq == 0.0 {
+            return Self::identity();
+        }
+
+        let s = 2.0 / len_sq;
+        let (xx, yy, zz) = (x * x * s, y * y * s, z * z * s);
+        let (xy, xz, yz) = (x * y * s, x * z * s, y * z * s);
+        let (wx, wy, wz) = (w * x * s, w * y * s, w * z * s);
+        [
+            [1.0 - yy - zz, xy + wz, xz - wy],
+            [xy - wz, 1.0 - xx - zz, yz + wx],
+            [xz + wy, yz - wx, 1.0 - xx - yy],
+        ]
+        .into()
+    }
+}
+
+impl Mat4 {
+    pub fn identity() -> Self {
+        Self::from_rotation_translation(Mat3::identity(), [0.0; 3].into())
+    }
+
+    pub fn from_rotation_translation(rotation: Mat3, translation: Vec3) -> Self {
+        let [x, y, z] = rotation.get();
+        let t = translation.get();
+        [
+            [x[0], x[1], x[2], 0.0],
+            [y[0], y[1], y[2], 0.0],
+            [z[0], z[1], z[2], 0.0],
+            [t[0], t[1], t[2], 1.0],
+        ]
+        .into()
+    }
+}
+
+#[cfg(feature = "glam")]
+mod glam_conversions {
+    use super::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+    impl From<Vec2> for glam::Vec2 {
+        fn from(value: Vec2) -> Self {
+            Self::from_array(value.get())
+        }
+    }
+    impl From<glam::Vec2> for Vec2 {
+        fn from(value: glam::Vec2) -> Self {
+            value.to_array().into()
+        }
+    }
+    impl From<Vec3> for glam::Vec3 {
+        fn from(value: Vec3) -> Self {
+            Self::from_array(value.get())
+        }
+    }
+    impl From<glam::Vec3> for Vec3 {
+        fn from(value: glam::Vec3) -> Self {
+            value.to_array().into()
+        }
+    }
+    impl From<Vec4> for glam::Vec4 {
+        fn from(value: Vec4) -> Self {
+            Self::from_array(value.get())
+        }
+    }
+    impl From<glam::Vec4> for Vec4 {
+        fn from(value: glam::Vec4) -> Self {
+            value.to_array().into()
+        }
+    }
+    impl From<Mat3> for glam::Mat3 {
+        fn from(value: Mat3) -> Self {
+            Self::from_cols_array_2d(&value.get())
+        }
+    }
+    impl From<glam::Mat3> for Mat3 {
+        fn from(value: glam::Mat3) -> Self {
+            value.to_cols_array_2d().into()
+        }
+    }
+    impl From<Mat4> for glam::Mat4 {
+        fn from(value: Mat4) -> Self {
+            Self::from_cols_array_2d(&value.get())
+        }
+    }
+    impl From<glam::Mat4> for Mat4 {
+        fn from(value: glam::Mat4) -> Self {
+            value.to_cols_array_2d().into()
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+mod nalgebra_conversions {
+    use super::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+    impl From<Vec2> for nalgebra::Vector2<f32> {
+        fn from(value: Vec2) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Vector2<f32>> for Vec2 {
+        fn from(value: nalgebra::Vector2<f32>) -> Self {
+            Into::<[f32; 2]>::into(value).into()
+        }
+    }
+    impl From<Vec3> for nalgebra::Vector3<f32> {
+        fn from(value: Vec3) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Vector3<f32>> for Vec3 {
+        fn from(value: nalgebra::Vector3<f32>) -> Self {
+            Into::<[f32; 3]>::into(value).into()
+        }
+    }
+    impl From<Vec3> for nalgebra::Point3<f32> {
+        fn from(value: Vec3) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Point3<f32>> for Vec3 {
+        fn from(value: nalgebra::Point3<f32>) -> Self {
+            Into::<[f32; 3]>::into(value).into()
+        }
+    }
+    impl From<Vec4> for nalgebra::Vector4<f32> {
+        fn from(value: Vec4) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Vector4<f32>> for Vec4 {
+        fn from(value: nalgebra::Vector4<f32>) -> Self {
+            Into::<[f32; 4]>::into(value).into()
+        }
+    }
+    // nalgebra's arrays of arrays are columns, like `get`'s
+    impl From<Mat3> for nalgebra::Matrix3<f32> {
+        fn from(value: Mat3) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Matrix3<f32>> for Mat3 {
+        fn from(value: nalgebra::Matrix3<f32>) -> Self {
+            Into::<[[f32; 3]; 3]>::into(value).into()
+        }
+    }
+    impl From<Mat4> for nalgebra::Matrix4<f32> {
+        fn from(value: Mat4) -> Self {
+            value.get().into()
+        }
+    }
+    impl From<nalgebra::Matrix4<f32>> for Mat4 {
+        fn from(value: nalgebra::Matrix4<f32>) -> Self {
+            Into::<[[f32; 4]; 4]>::into(value).into()
+        }
+    }
+}
+
+/// Which axis points up.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum UpAxis {
+    Y,
+    #[default]
+    Z,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum Handedness {
+    #[default]
+    Right,
+    Left,
+}
+
+/// What one unit of distance is.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum Units {
+    /// The game's units.
+    #[default]
+    Inches,
+    Centimeters,
+    Meters,
+}
+
+impl Units {
+    /// How many of these units are in an inch.
+    pub fn per_inch(self) -> f32 {
+        match self {
+            Self::Inches => 1.0,
+            Self::Centimeters => 2.54,
+            Self::Meters => 0.0254,
+        }
+    }
+}
+
+/// A coordinate system to convert positions, directions, and transforms
+/// into from the game's, which is right-handed, with X forward, Y left,
+/// and Z up, in inches ([`Self::NATIVE`]).
+///
+/// Converted axes keep pointing the same way relative to each other: forward
+/// stays forward and up stays up, and with [`UpAxis::Y`], X points left (or
+/// right, if left-handed) and Z forward, as in glTF.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct CoordinateConvention {
+    pub up: UpAxis,
+    pub handedness: Handedness,
+    pub units: Units,
+}
+
+impl CoordinateConvention {
+    /// The game's own coordinates, which converting to does nothing.
+    pub const NATIVE: Self = Self {
+        up: UpAxis::Z,
+        handedness: Handedness::Right,
+        units: Units::Inches,
+    };
+
+    /// glTF's coordinates, which Blender's importers expect.
+    pub const Y_UP_METERS: Self = Self {
+        up: UpAxis::Y,
+        handedness: Handedness::Right,
+        units: Units::Meters,
+    };
+
+    /// Unreal's coordinates.
+    pub const UNREAL: Self = Self {
+        up: UpAxis::Z,
+        handedness: Handedness::Left,
+        units: Units::Centimeters,
+    };
+
+    pub fn new(up: UpAxis, handedness: Handedness, units: Units) -> Self {
+        Self {
+            up,
+            handedness,
+            units,
+        }
+    }
+
+    /// Returns `true` if converting mirrors things, so that triangles have
+    /// to have their winding order reversed to keep facing the same way.
+    pub fn flips_winding(self) -> bool {
+        self.handedness == Handedness::Left
+    }
+
+    pub fn convert_direction(self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        // subtracted rather than negated, so that 0 doesn't become -0
+        match (self.up, self.handedness) {
+            (UpAxis::Z, Handedness::Right) => [x, y, z],
+            (UpAxis::Z, Handedness::Left) => [x, 0.0 - y, z],
+            (UpAxis::Y, Handedness::Right) => [y, z, x],
+            (UpAxis::Y, Handedness::Left) => [0.0 - y, z, x],
+        }
+    }
+
+    pub fn convert_point(self, point: [f32; 3]) -> [f32; 3] {
+        let scale = self.units.per_inch();
+        self.convert_direction(point).map(|c| c * scale)
+    }
+
+    /// The conversion as a matrix `C`, such that
+    /// [`Self::convert_direction`] is `C * v`.
+    fn axes(self) -> [[f32; 3]; 3] {
+        let column = |axis: [f32; 3]| self.convert_direction(axis);
+        let [x, y, z] = [
+            column([1.0, 0.0, 0.0]),
+            column([0.0, 1.0, 0.0]),
+            column([0.0, 0.0, 1.0]),
+        ];
+        [0, 1, 2].map(|i| [x[i], y[i], z[i]])
+    }
+
+    /// Converts a rotation, as `C * rotation * C^T`. Since `C` only swaps
+    /// and negates axes, it's the same whether `rotation`'s rows or columns
+    /// are its axes.
+    pub fn convert_rotation(self, rotation: [[f32; 3]; 3]) -> [[f32; 3]; 3] {
+        let c = self.axes();
+        let mul = |a: [[f32; 3]; 3], b: [[f32; 3]; 3]| {
+            [0, 1, 2].map(|i| [0, 1, 2].map(|j| (0..3).map(|k| a[i][k] * b[k][j]).sum::<f32>()))
+        };
+        let c_t = [0, 1, 2].map(|i| [0, 1, 2].map(|j| c[j][i]));
+        mul(mul(c, rotation), c_t)
+    }
+
+    /// Converts an affine transform laid out as Direct3D lays them out, with
+    /// each row being where one axis ends up, and the last row being the
+    /// translation. That's the same layout as a column-major matrix in
+    /// OpenGL or glTF, flattened.
+    pub fn convert_matrix(self, matrix: [[f32; 4]; 4]) -> [[f32; 4]; 4] {
+        let rows = [0, 1, 2].map(|i| [matrix[i][0], matrix[i][1], matrix[i][2]]);
+        // the new axes are combinations of the old ones, in the same way
+        // directions are
+        let c = self.axes();
+        let mut converted = [[0.0; 4]; 4];
+        for (i, row) in converted.iter_mut().take(3).enumerate() {
+            let axis = [0, 1, 2].map(|k| (0..3).map(|j| c[i][j] * rows[j][k]).sum::<f32>());
+            let [x, y, z] = self.convert_direction(axis);
+            *row = [x, y, z, matrix[i][3]];
+        }
+        let [x, y, z] = self.convert_point([matrix[3][0], matrix[3][1], matrix[3][2]]);
+        converted[3] = [x, y, z, matrix[3][3]];
+        converted
+    }
+}
+
+/// An axis-aligned bounding box, in world units.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Aabb {
+    pub mins: Vec3,
+    pub maxs: Vec3,
+}
+
+impl Aabb {
+    pub fn new(mins: Vec3, maxs: Vec3) -> Self {
+        Self { mins, maxs }
+    }
+
+    /// The form [`CollisionAabbTree`](crate::clipmap::CollisionAabbTree)
+    /// stores its bounds in.
+    pub fn from_origin_half_size(origin: Vec3, half_size: Vec3) -> Self {
+        let (o, h) = (origin.get(), half_size.get());
+        Self {
+            mins: [0, 1, 2].map(|i| o[i] - h[i]).into(),
+            maxs: [0, 1, 2].map(|i| o[i] + h[i]).into(),
+        }
+    }
+
+    pub fn contains(self, point: Vec3) -> bool {
+        let (mins, maxs, p) = (self.mins.get(), self.maxs.get(), point.get());
+        (0..3).all(|i| mins[i] <= p[i] && p[i] <= maxs[i])
+    }
+
+    /// Returns `true` if `self` and `other` overlap or touch.
+    pub fn intersects(self, other: Aabb) -> bool {
+        let (a_mins, a_maxs) = (self.mins.get(), self.maxs.get());
+        let (b_mins, b_maxs) = (other.mins.get(), other.maxs.get());
+        (0..3).all(|i| a_mins[i] <= b_maxs[i] && b_mins[i] <= a_maxs[i])
+    }
+
+    /// Returns the fractions along the segment from `start` to `end` at
+    /// which it enters and leaves the box, or [`None`] if it misses the box
+    /// entirely. A segment starting inside the box enters it at `0.0`.
+    pub fn segment_intersection(self, start: Vec3, end: Vec3) -> Option<(f32, f32)> {
+        let (mins, maxs) = (self.mins.get(), self.maxs.get());
+        let (start, end) = (start.get(), end.get());
+
+        let (mut enter, mut exit) = (0.0f32, 1.0f32);
+        for i in 0..3 {
+            let delta = end[i] - start[i];
+            if delta == 0.0 {
+                if start[i] < mins[i] || start[i] > maxs[i] {
+                    return None;
+                }
+                continue;
+            }
+
+            let (t0, t1) = ((mins[i] - start[i]) / delta, (maxs[i] - start[i]) / delta);
+            let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+            enter = enter.max(t0);
+            exit = exit.min(t1);
+            if enter > exit {
+                return None;
+            }
+        }
+
+        Some((enter, exit))
+    }
+}
+
+/// Equality within a tolerance, for types made of floats.
+///
+/// The derived [`PartialEq`] impls compare floats exactly, which is what you
+/// want when checking that a round-trip preserved every bit, but not when
+/// comparing data that went through a lossy conversion (e.g. a model that was
+/// exported to glTF and imported back).
+pub trait ApproxEq {
+    /// Returns `true` if every float in `self` is within `epsilon` of the
+    /// corresponding float in `other`.
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self == other || (self - other).abs() <= epsilon
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for [T] {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(a, b)| a.approx_eq(b, epsilon))
+    }
+}
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.as_slice().approx_eq(other.as_slice(), epsilon)
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for alloc::vec::Vec<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.as_slice().approx_eq(other.as_slice(), epsilon)
+    }
+}
+
+impl<T: ApproxEq> ApproxEq for Option<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        match (self, other) {
+            (Some(a), Some(b)) => a.approx_eq(b, epsilon),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: ApproxEq + ?Sized> ApproxEq for alloc::boxed::Box<T> {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        (**self).approx_eq(other, epsilon)
+    }
+}
+
+impl ApproxEq for Vec2 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
+impl ApproxEq for Vec3 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
+impl ApproxEq for Vec4 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
+impl ApproxEq for Mat2 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
+impl ApproxEq for Mat3 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
+impl ApproxEq for Mat4 {
+    fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.get().approx_eq(&other.get(), epsilon)
+    }
+}
+
 #[cfg(feature = "serde")]
 struct D3D9Visitor {}
 
@@ -536,9 +1151,20 @@ type D3D9VS = windows::Win32::Graphics::Direct3D9::IDirect3DVertexShader9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9VS = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxVertexShader(pub D3D9VS);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxVertexShader {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxVertexShader {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -563,9 +1189,20 @@ type D3D9PS = windows::Win32::Graphics::Direct3D9::IDirect3DPixelShader9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9PS = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxPixelShader(pub D3D9PS);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxPixelShader {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxPixelShader {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -595,9 +1232,20 @@ type D3D9Tex = windows::Win32::Graphics::Direct3D9::IDirect3DTexture9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9Tex = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxTexture(D3D9Tex);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxTexture {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxTexture {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -622,9 +1270,20 @@ type D3D9VolTex = windows::Win32::Graphics::Direct3D9::IDirect3DVolumeTexture9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9VolTex = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxVolumeTexture(D3D9VolTex);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxVolumeTexture {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxVolumeTexture {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -650,9 +1309,20 @@ type D3D9CubeTex = windows::Win32::Graphics::Direct3D9::IDirect3DCubeTexture9;
 #[allow(dead_code)]
 type D3D9CubeTex = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxCubeTexture(D3D9Tex);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxCubeTexture {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxCubeTexture {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -677,9 +1347,20 @@ type D3D9VB = windows::Win32::Graphics::Direct3D9::IDirect3DVertexBuffer9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9VB = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxVertexBuffer(pub D3D9VB);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxVertexBuffer {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxVertexBuffer {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -704,9 +1385,20 @@ type D3D9IB = windows::Win32::Graphics::Direct3D9::IDirect3DIndexBuffer9;
 #[cfg(not(feature = "d3d9"))]
 type D3D9IB = ();
 
-#[derive(Clone, Debug)]
+#[cfg_attr(
+    all(feature = "arbitrary", not(feature = "d3d9")),
+    derive(arbitrary::Arbitrary)
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "d3d9"), derive(Hash))]
 #[repr(transparent)]
 pub struct GfxIndexBuffer(D3D9IB);
+#[cfg(feature = "d3d9")]
+impl core::hash::Hash for GfxIndexBuffer {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        windows::core::Interface::as_raw(&self.0).hash(state)
+    }
+}
 #[cfg(feature = "serde")]
 impl Serialize for GfxIndexBuffer {
     fn serialize<S: Serializer>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error> {
@@ -0,0 +1,92 @@
+//! Minimal RFC 4180 CSV reading and writing, used to export text-based
+//! assets to a format that spreadsheets understand, and to re-import them.
+
+use alloc::{format, string::String, vec::Vec};
+
+use crate::{Error, ErrorKind, Result, file_line_col};
+
+fn needs_quotes(field: &str) -> bool {
+    field.contains([',', '"', '\r', '\n'])
+}
+
+/// Appends one record (terminated by `\r\n`) to `out`, quoting fields where
+/// needed.
+pub(crate) fn write_record<S: AsRef<str>>(out: &mut String, fields: impl IntoIterator<Item = S>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+
+        let field = field.as_ref();
+        if needs_quotes(field) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Parses `s` into records. Both `\r\n` and `\n` line endings are accepted,
+/// and a trailing line ending is optional.
+pub(crate) fn parse(s: &str) -> Result<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                c => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '"' => {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::Parse(format!("CSV: unexpected quote on line {line}")),
+                ));
+            }
+            ',' => record.push(core::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                record.push(core::mem::take(&mut field));
+                records.push(core::mem::take(&mut record));
+                line += 1;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::Parse(format!("CSV: unterminated quoted field on line {line}")),
+        ));
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
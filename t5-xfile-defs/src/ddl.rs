@@ -1,14 +1,28 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
+    T5XFileDeserialize, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
+    encoding::StringEncoding, file_line_col,
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
+use num::FromPrimitive;
+use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DdlRootRaw<'a> {
     pub name: XStringRaw<'a>,
     pub ddl_def: Ptr32<'a, DdlDefRaw<'a>>,
@@ -16,7 +30,8 @@ pub(crate) struct DdlRootRaw<'a> {
 assert_size!(DdlRootRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DdlRoot {
     pub name: XString,
     pub ddl_defs: Vec<Box<DdlDef>>,
@@ -47,6 +62,7 @@ impl<'a> XFileDeserializeInto<DdlRoot, ()> for DdlRootRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DdlRoot {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -68,7 +84,7 @@ impl XFileSerialize<()> for DdlRoot {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DdlDefRaw<'a> {
     pub version: i32,
     pub size: i32,
@@ -79,7 +95,8 @@ pub(crate) struct DdlDefRaw<'a> {
 assert_size!(DdlDefRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DdlDef {
     pub version: i32,
     pub size: i32,
@@ -87,6 +104,23 @@ pub struct DdlDef {
     pub enum_list: Vec<DdlEnumDef>,
 }
 
+impl DdlDef {
+    /// The struct every other struct is nested in.
+    pub fn root(&self) -> Option<&DdlStructDef> {
+        self.struct_list.first()
+    }
+
+    /// Reads the value at `path` (relative to [`Self::root`]) from `buffer`,
+    /// which holds data described by this DDL, e.g. a player's stats. See
+    /// [`DdlStructDef::get`].
+    pub fn get(&self, buffer: &[u8], path: &str) -> Result<DdlValue<'_>> {
+        let root = self
+            .root()
+            .ok_or_else(|| bad_ddl_path(path, "the DDL has no structs"))?;
+        root.get(self, buffer, path)
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlDef, ()> for DdlDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -105,6 +139,7 @@ impl<'a> XFileDeserializeInto<DdlDef, ()> for DdlDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<bool> for DdlDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, is_last: bool) -> Result<()> {
         let struct_list = FatPointerCountLastU32::from_slice(&self.struct_list);
@@ -129,7 +164,7 @@ impl XFileSerialize<bool> for DdlDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DdlStructDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub size: i32,
@@ -138,13 +173,107 @@ pub(crate) struct DdlStructDefRaw<'a> {
 assert_size!(DdlStructDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DdlStructDef {
     pub name: XString,
     pub size: i32,
     pub members: Vec<DdlMemberDef>,
 }
 
+impl DdlStructDef {
+    /// The member named `name` (ignoring ASCII case), if there is one.
+    pub fn member(&self, name: &str) -> Option<&DdlMemberDef> {
+        self.members
+            .iter()
+            .find(|member| member.name.get().eq_ignore_ascii_case(name))
+    }
+
+    /// Reads the value at `path` (relative to this struct, which is assumed
+    /// to start at the beginning of `buffer`) from `buffer`. `def` is the DDL
+    /// this struct belongs to.
+    ///
+    /// `path` names members separated by `.`, like `"stats.kills"`. Array
+    /// members are indexed with either `[i]` or `.i`, where `i` is a number
+    /// or, for arrays indexed by an enum, one of its members, like
+    /// `"weapons[ak47].kills"`.
+    ///
+    /// Fails with [`ErrorKind::BadDdlPath`] if `path` doesn't end at a
+    /// value, e.g. because it names a struct, or with
+    /// [`ErrorKind::InvalidSeek`] if the value isn't within `buffer`.
+    pub fn get<'a>(&'a self, def: &'a DdlDef, buffer: &[u8], path: &str) -> Result<DdlValue<'a>> {
+        let mut keys = path
+            .split(['.', '['])
+            .map(|key| key.strip_suffix(']').unwrap_or(key));
+
+        let mut struct_def = self;
+        let mut base = 0u64;
+        loop {
+            let Some(key) = keys.next() else {
+                return Err(bad_ddl_path(path, "path ends at a struct"));
+            };
+            let member = struct_def.member(key).ok_or_else(|| {
+                bad_ddl_path(
+                    path,
+                    &format!("{} has no member {key}", struct_def.name.get()),
+                )
+            })?;
+            let ty = member.ddl_type().ok_or(Error::new(
+                file_line_col!(),
+                ErrorKind::BadFromPrimitive(member.type_ as _),
+            ))?;
+
+            let mut offset = base + member.offset as u64;
+            let mut size = member.size as u64;
+            if member.array_size > 1 {
+                let array_size = member.array_size as u64;
+                let key = keys
+                    .next()
+                    .ok_or_else(|| bad_ddl_path(path, "path ends at an array"))?;
+                let index = member.index(def, key).ok_or_else(|| {
+                    bad_ddl_path(
+                        path,
+                        &format!("{key} isn't an index of {}", member.name.get()),
+                    )
+                })? as u64;
+                if index >= array_size {
+                    return Err(bad_ddl_path(
+                        path,
+                        &format!("{} has only {array_size} elements", member.name.get()),
+                    ));
+                }
+
+                size /= array_size;
+                offset += index * size;
+            }
+
+            if ty == DdlType::STRUCT {
+                struct_def = usize::try_from(member.external_index)
+                    .ok()
+                    .and_then(|i| def.struct_list.get(i))
+                    .ok_or(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "DDL: struct index {} of {} is out of range",
+                            member.external_index,
+                            member.name.get()
+                        )),
+                    ))?;
+                base = offset;
+                continue;
+            }
+
+            if keys.next().is_some() {
+                return Err(bad_ddl_path(
+                    path,
+                    &format!("{} isn't a struct", member.name.get()),
+                ));
+            }
+            return read_ddl_value(def, member, ty, buffer, offset, size);
+        }
+    }
+}
+
 impl<'a> XFileDeserializeInto<DdlStructDef, ()> for DdlStructDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -162,6 +291,7 @@ impl<'a> XFileDeserializeInto<DdlStructDef, ()> for DdlStructDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DdlStructDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -180,7 +310,7 @@ impl XFileSerialize<()> for DdlStructDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DdlMemberDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub size: i32,
@@ -198,10 +328,13 @@ pub(crate) struct DdlMemberDefRaw<'a> {
 assert_size!(DdlMemberDefRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DdlMemberDef {
     pub name: XString,
+    /// In bits. For arrays, the size of the whole array.
     pub size: i32,
+    /// In bits, from the start of the struct.
     pub offset: i32,
     pub type_: i32,
     pub external_index: i32,
@@ -214,6 +347,141 @@ pub struct DdlMemberDef {
     pub permission: i32,
 }
 
+/// The type of a [`DdlMemberDef`] (see [`DdlMemberDef::ddl_type`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+pub enum DdlType {
+    BYTE = 0,
+    SHORT = 1,
+    UINT = 2,
+    INT = 3,
+    INT64 = 4,
+    FLOAT = 5,
+    FIXEDPOINT = 6,
+    STRING = 7,
+    /// A [`DdlStructDef`], indexed by [`DdlMemberDef::external_index`].
+    STRUCT = 8,
+    /// A member of a [`DdlEnumDef`], indexed by
+    /// [`DdlMemberDef::external_index`].
+    ENUM = 9,
+}
+
+impl DdlMemberDef {
+    /// The member's type, or [`None`] if [`Self::type_`] isn't a
+    /// [`DdlType`].
+    pub fn ddl_type(&self) -> Option<DdlType> {
+        FromPrimitive::from_i32(self.type_)
+    }
+
+    /// The enum the member (if it's an array) is indexed by.
+    pub fn index_enum<'a>(&self, def: &'a DdlDef) -> Option<&'a DdlEnumDef> {
+        usize::try_from(self.enum_index)
+            .ok()
+            .and_then(|i| def.enum_list.get(i))
+    }
+
+    fn index(&self, def: &DdlDef, key: &str) -> Option<usize> {
+        key.parse().ok().or_else(|| {
+            self.index_enum(def)?
+                .members
+                .iter()
+                .position(|m| m.get().eq_ignore_ascii_case(key))
+        })
+    }
+}
+
+/// A value read with [`DdlDef::get`] or [`DdlStructDef::get`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DdlValue<'a> {
+    /// A [`DdlType::SHORT`], [`DdlType::INT`] or [`DdlType::INT64`].
+    Int(i64),
+    /// A [`DdlType::BYTE`] or [`DdlType::UINT`].
+    UInt(u64),
+    Float(f32),
+    String(String),
+    /// The member of the enum, or [`None`] if `value` is out of its range.
+    Enum {
+        value: u64,
+        name: Option<&'a str>,
+    },
+}
+
+fn bad_ddl_path(path: &str, reason: &str) -> Error {
+    Error::new(
+        file_line_col!(),
+        ErrorKind::BadDdlPath(format!("{path}: {reason}")),
+    )
+}
+
+/// Reads `width` bits from `buffer`, starting `offset` bits in. DDL members
+/// are packed least significant bit first.
+fn read_ddl_bits(buffer: &[u8], offset: u64, width: u64) -> Result<u64> {
+    if width > 64 {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::BrokenInvariant(format!("DDL: {width}-bit value is too wide")),
+        ));
+    }
+    if offset + width > buffer.len() as u64 * 8 {
+        return Err(Error::new(
+            file_line_col!(),
+            ErrorKind::InvalidSeek {
+                off: ((offset + width).div_ceil(8)) as _,
+                max: buffer.len() as _,
+            },
+        ));
+    }
+
+    Ok((0..width).fold(0, |value, i| {
+        let bit = offset + i;
+        let set = buffer[(bit / 8) as usize] >> (bit % 8) & 1;
+        value | (set as u64) << i
+    }))
+}
+
+fn read_ddl_value<'a>(
+    def: &'a DdlDef,
+    member: &DdlMemberDef,
+    ty: DdlType,
+    buffer: &[u8],
+    offset: u64,
+    size: u64,
+) -> Result<DdlValue<'a>> {
+    let value = match ty {
+        DdlType::BYTE | DdlType::UINT => DdlValue::UInt(read_ddl_bits(buffer, offset, size)?),
+        DdlType::SHORT | DdlType::INT | DdlType::INT64 => {
+            let bits = read_ddl_bits(buffer, offset, size)?;
+            // sign-extend
+            let shift = 64 - size as u32;
+            DdlValue::Int(bits.checked_shl(shift).map_or(0, |b| b as i64 >> shift))
+        }
+        DdlType::FLOAT => DdlValue::Float(f32::from_bits(read_ddl_bits(buffer, offset, 32)? as _)),
+        DdlType::STRING => {
+            let bytes = (0..size / 8)
+                .map(|i| read_ddl_bits(buffer, offset + i * 8, 8).map(|b| b as u8))
+                .collect::<Result<Vec<_>>>()?;
+            let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            DdlValue::String(StringEncoding::default().decode(&bytes[..len])?)
+        }
+        DdlType::ENUM => {
+            let value = read_ddl_bits(buffer, offset, size)?;
+            let name = usize::try_from(member.external_index)
+                .ok()
+                .and_then(|i| def.enum_list.get(i))
+                .and_then(|e| e.members.get(value as usize))
+                .map(XString::get);
+            DdlValue::Enum { value, name }
+        }
+        DdlType::FIXEDPOINT => {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo("DDL: fixed-point members".to_string()),
+            ));
+        }
+        DdlType::STRUCT => return Err(bad_ddl_path(&member.name.0, "member is a struct")),
+    };
+    Ok(value)
+}
+
 impl<'a> XFileDeserializeInto<DdlMemberDef, ()> for DdlMemberDefRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -239,6 +507,7 @@ impl<'a> XFileDeserializeInto<DdlMemberDef, ()> for DdlMemberDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DdlMemberDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -265,7 +534,7 @@ impl XFileSerialize<()> for DdlMemberDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DdlEnumDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub members: FatPointerCountFirstU32<'a, XStringRaw<'a>>,
@@ -273,7 +542,8 @@ pub(crate) struct DdlEnumDefRaw<'a> {
 assert_size!(DdlEnumDefRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DdlEnumDef {
     pub name: XString,
     pub members: Vec<XString>,
@@ -292,6 +562,7 @@ impl<'a> XFileDeserializeInto<DdlEnumDef, ()> for DdlEnumDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DdlEnumDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
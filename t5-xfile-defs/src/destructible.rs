@@ -1,16 +1,19 @@
 use alloc::{boxed::Box, vec::Vec};
 
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32, Result, ScriptString, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointerCountFirstU32, Ptr32, Result, ScriptString, T5XFileDeserialize, XFileDeserializeInto,
+    XFileRead, XString, XStringRaw, assert_size, deserialize_array_n,
     fx::{FxEffectDef, FxEffectDefRaw},
     xmodel::{PhysConstraints, PhysConstraintsRaw, PhysPreset, PhysPresetRaw, XModel, XModelRaw},
 };
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, XFileRead)]
 pub(crate) struct DestructibleDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub model: Ptr32<'a, XModelRaw<'a>>,
@@ -21,7 +24,8 @@ pub(crate) struct DestructibleDefRaw<'a> {
 assert_size!(DestructibleDefRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DestructibleDef {
     pub name: XString,
     pub model: Option<Box<XModel>>,
@@ -51,6 +55,7 @@ impl<'a> XFileDeserializeInto<DestructibleDef, ()> for DestructibleDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DestructibleDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -75,7 +80,7 @@ impl XFileSerialize<()> for DestructibleDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, XFileRead)]
 pub(crate) struct DestructiblePieceRaw<'a> {
     pub stages: [DestructibleStageRaw<'a>; 5],
     pub parent_piece: u8,
@@ -100,7 +105,8 @@ pub(crate) struct DestructiblePieceRaw<'a> {
 assert_size!(DestructiblePieceRaw, 312);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DestructiblePiece {
     pub stages: [DestructibleStage; 5],
     pub parent_piece: u8,
@@ -125,13 +131,7 @@ impl<'a> XFileDeserializeInto<DestructiblePiece, ()> for DestructiblePieceRaw<'a
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<DestructiblePiece> {
-        let stages = [
-            self.stages[0].xfile_deserialize_into(de, ())?,
-            self.stages[1].xfile_deserialize_into(de, ())?,
-            self.stages[2].xfile_deserialize_into(de, ())?,
-            self.stages[3].xfile_deserialize_into(de, ())?,
-            self.stages[4].xfile_deserialize_into(de, ())?,
-        ];
+        let stages = deserialize_array_n(&self.stages, de, ())?;
         let phys_constraints = self.phys_constraints.xfile_deserialize_into(de, ())?;
         let damage_sound = self.damage_sound.xfile_deserialize_into(de, ())?;
         let burn_effect = self.burn_effect.xfile_deserialize_into(de, ())?;
@@ -157,6 +157,7 @@ impl<'a> XFileDeserializeInto<DestructiblePiece, ()> for DestructiblePieceRaw<'a
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DestructiblePiece {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let stages = self
@@ -228,7 +229,7 @@ impl XFileSerialize<()> for DestructiblePiece {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, XFileRead)]
 pub(crate) struct DestructibleStageRaw<'a> {
     pub show_bone: ScriptString,
     pub break_health: f32,
@@ -244,7 +245,8 @@ pub(crate) struct DestructibleStageRaw<'a> {
 assert_size!(DestructibleStageRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DestructibleStage {
     pub show_bone: XString,
     pub break_health: f32,
@@ -273,11 +275,7 @@ impl<'a> XFileDeserializeInto<DestructibleStage, ()> for DestructibleStageRaw<'a
             break_sound: self.break_sound.xfile_deserialize_into(de, ())?,
             break_notify: self.break_notify.xfile_deserialize_into(de, ())?,
             loop_sound: self.loop_sound.xfile_deserialize_into(de, ())?,
-            spawn_model: [
-                self.spawn_model[0].xfile_deserialize_into(de, ())?,
-                self.spawn_model[1].xfile_deserialize_into(de, ())?,
-                self.spawn_model[2].xfile_deserialize_into(de, ())?,
-            ],
+            spawn_model: deserialize_array_n(&self.spawn_model, de, ())?,
             phys_preset: self.phys_preset.xfile_deserialize_into(de, ())?,
         })
     }
@@ -1,13 +1,16 @@
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 use crate::{
-    FatPointer, FatPointerCountFirstU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointerCountFirstU32, Ptr32, Result, Shared, T5XFileDeserialize, XArray,
+    XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     techset::{GfxImage, GfxImageRaw, Material, MaterialRaw},
 };
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct EmblemSetRaw<'a> {
     pub color_count: i32,
     pub layers: FatPointerCountFirstU32<'a, EmblemLayer>,
@@ -19,7 +22,8 @@ pub(crate) struct EmblemSetRaw<'a> {
 assert_size!(EmblemSetRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EmblemSet {
     pub color_count: i32,
     pub layers: Vec<EmblemLayer>,
@@ -39,7 +43,7 @@ impl<'a> XFileDeserializeInto<EmblemSet, ()> for EmblemSetRaw<'a> {
         let categories = self.categories.xfile_deserialize_into(de, ())?;
         let icons = self.icons.xfile_deserialize_into(de, ())?;
         let backgrounds = self.backgrounds.xfile_deserialize_into(de, ())?;
-        let background_lookup = self.background_lookup.to_vec(de)?;
+        let background_lookup = self.background_lookup.to_vec_bulk(de)?;
 
         Ok(EmblemSet {
             color_count: self.color_count,
@@ -52,6 +56,7 @@ impl<'a> XFileDeserializeInto<EmblemSet, ()> for EmblemSetRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for EmblemSet {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let color_count = self.color_count;
@@ -79,8 +84,9 @@ impl XFileSerialize<()> for EmblemSet {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct EmblemLayer {
     pub cost: i32,
     pub unlock_level: i32,
@@ -88,6 +94,7 @@ pub struct EmblemLayer {
 }
 assert_size!(EmblemLayer, 12);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for EmblemLayer {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -95,7 +102,7 @@ impl XFileSerialize<()> for EmblemLayer {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct EmblemCategoryRaw<'a> {
     pub name: XStringRaw<'a>,
     pub description: XStringRaw<'a>,
@@ -103,7 +110,8 @@ pub(crate) struct EmblemCategoryRaw<'a> {
 assert_size!(EmblemCategoryRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct EmblemCategory {
     pub name: XString,
     pub description: XString,
@@ -122,6 +130,7 @@ impl<'a> XFileDeserializeInto<EmblemCategory, ()> for EmblemCategoryRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for EmblemCategory {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -136,7 +145,7 @@ impl XFileSerialize<()> for EmblemCategory {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct EmblemIconRaw<'a> {
     pub image: Ptr32<'a, GfxImageRaw<'a>>,
     pub description: XStringRaw<'a>,
@@ -152,7 +161,8 @@ pub(crate) struct EmblemIconRaw<'a> {
 assert_size!(EmblemIconRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EmblemIcon {
     pub image: Option<Box<GfxImage>>,
     pub description: XString,
@@ -190,6 +200,7 @@ impl<'a> XFileDeserializeInto<EmblemIcon, ()> for EmblemIconRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for EmblemIcon {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let image = Ptr32::from_box(&self.image);
@@ -215,7 +226,7 @@ impl XFileSerialize<()> for EmblemIcon {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct EmblemBackgroundRaw<'a> {
     pub material: Ptr32<'a, MaterialRaw<'a>>,
     pub description: XStringRaw<'a>,
@@ -227,9 +238,10 @@ pub(crate) struct EmblemBackgroundRaw<'a> {
 assert_size!(EmblemBackgroundRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct EmblemBackground {
-    pub material: Option<Box<Material>>,
+    pub material: Option<Shared<Material>>,
     pub description: XString,
     pub cost: i32,
     pub unlock_level: i32,
@@ -257,6 +269,7 @@ impl<'a> XFileDeserializeInto<EmblemBackground, ()> for EmblemBackgroundRaw<'a>
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for EmblemBackground {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let material = Ptr32::from_box(&self.material);
@@ -0,0 +1,204 @@
+//! Decoding and encoding the text of [`XString`](crate::XString)s.
+//!
+//! The engine treats strings as plain bytes, so what they mean depends on
+//! who wrote them. Western localizations use Windows-1252, while others
+//! (and some mods) use UTF-8. Neither is marked in the Fastfile, so
+//! [`Charset::Auto`] guesses from the bytes themselves.
+
+use alloc::{string::String, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{Error, ErrorKind, Result, file_line_col};
+
+/// How the bytes of a string are interpreted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Charset {
+    Windows1252,
+    Utf8,
+    /// Strings that are valid UTF-8 are decoded as such, and everything else
+    /// as Windows-1252. Strings are encoded as Windows-1252 if they can be,
+    /// and as UTF-8 otherwise.
+    ///
+    /// Windows-1252 strings that happen to be valid UTF-8 (which takes two
+    /// or more non-ASCII characters in a row, like `Ã©`) are misread, but
+    /// that's rare in practice.
+    #[default]
+    Auto,
+}
+
+/// What happens to bytes that don't decode to a character, and characters
+/// that can't be encoded.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum EncodingFallback {
+    /// Decodes to U+FFFD and encodes to `?`.
+    #[default]
+    Replace,
+    /// Decodes each byte to the character of the same value, like ISO
+    /// 8859-1 does, and encodes characters up to U+00FF back to that byte
+    /// (anything else to `?`). Unlike [`Self::Replace`], strings decoded
+    /// this way with [`Charset::Windows1252`] encode back to the same bytes.
+    /// [`Charset::Auto`] doesn't guarantee that, since it picks the charset
+    /// again from the decoded text (UTF-8 `é` encodes back as Windows-1252,
+    /// and an undefined Windows-1252 byte as UTF-8).
+    Latin1,
+    /// Fails with [`ErrorKind::BadChar`].
+    Error,
+}
+
+/// Windows-1252's characters for bytes `0x80..=0x9F`, where it differs from
+/// ISO 8859-1. [`None`] for the five bytes it leaves undefined.
+const WINDOWS_1252_C1: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+fn windows_1252_to_char(b: u8) -> Option<char> {
+    match b {
+        0x80..=0x9F => WINDOWS_1252_C1[b as usize - 0x80],
+        _ => Some(b as char),
+    }
+}
+
+fn char_to_windows_1252(c: char) -> Option<u8> {
+    match c as u32 {
+        0..=0x7F | 0xA0..=0xFF => Some(c as u8),
+        _ => WINDOWS_1252_C1
+            .iter()
+            .position(|&d| d == Some(c))
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+/// How strings are decoded (see
+/// [`T5XFileDeserialize::string_encoding`](crate::T5XFileDeserialize::string_encoding))
+/// and encoded (see
+/// [`T5XFileSerialize::string_encoding`](crate::T5XFileSerialize::string_encoding)).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StringEncoding {
+    pub charset: Charset,
+    pub fallback: EncodingFallback,
+}
+
+impl StringEncoding {
+    pub const fn new(charset: Charset, fallback: EncodingFallback) -> Self {
+        Self { charset, fallback }
+    }
+
+    fn decode_fallback(&self, b: u8) -> Result<char> {
+        match self.fallback {
+            EncodingFallback::Replace => Ok(char::REPLACEMENT_CHARACTER),
+            EncodingFallback::Latin1 => Ok(b as char),
+            EncodingFallback::Error => {
+                Err(Error::new(file_line_col!(), ErrorKind::BadChar(b as _)))
+            }
+        }
+    }
+
+    fn encode_fallback(&self, c: char) -> Result<u8> {
+        match self.fallback {
+            EncodingFallback::Replace => Ok(b'?'),
+            EncodingFallback::Latin1 => Ok(u8::try_from(c).unwrap_or(b'?')),
+            EncodingFallback::Error => {
+                Err(Error::new(file_line_col!(), ErrorKind::BadChar(c as _)))
+            }
+        }
+    }
+
+    /// Returns the longest prefix of `s` that takes up at most `len` bytes
+    /// once encoded, without cutting a character in half.
+    pub(crate) fn truncate<'a>(&self, s: &'a str, len: usize) -> &'a str {
+        // Windows-1252 is a byte per character (fallbacks included), and
+        // `Auto` only switches to UTF-8 from the first character it can't
+        // encode on, so the encoded length only grows with the prefix.
+        let mut windows_1252 = self.charset != Charset::Utf8;
+        let mut end = 0;
+        for (n, (i, c)) in s.char_indices().enumerate() {
+            if self.charset == Charset::Auto {
+                windows_1252 &= char_to_windows_1252(c).is_some();
+            }
+            let next = i + c.len_utf8();
+            if (if windows_1252 { n + 1 } else { next }) > len {
+                break;
+            }
+            end = next;
+        }
+        &s[..end]
+    }
+
+    /// Decodes `bytes`, which shouldn't include the null terminator.
+    pub fn decode(&self, bytes: &[u8]) -> Result<String> {
+        let charset = match self.charset {
+            Charset::Auto if core::str::from_utf8(bytes).is_ok() => Charset::Utf8,
+            Charset::Auto => Charset::Windows1252,
+            charset => charset,
+        };
+
+        match charset {
+            Charset::Utf8 => {
+                let mut s = String::with_capacity(bytes.len());
+                for chunk in bytes.utf8_chunks() {
+                    s.push_str(chunk.valid());
+                    for &b in chunk.invalid() {
+                        s.push(self.decode_fallback(b)?);
+                    }
+                }
+                Ok(s)
+            }
+            _ => bytes
+                .iter()
+                .map(|&b| windows_1252_to_char(b).map_or_else(|| self.decode_fallback(b), Ok))
+                .collect(),
+        }
+    }
+
+    /// Encodes `s`, without adding a null terminator.
+    pub fn encode(&self, s: &str) -> Result<Vec<u8>> {
+        let charset = match self.charset {
+            Charset::Auto if s.chars().all(|c| char_to_windows_1252(c).is_some()) => {
+                Charset::Windows1252
+            }
+            Charset::Auto => Charset::Utf8,
+            charset => charset,
+        };
+
+        match charset {
+            Charset::Utf8 => Ok(s.as_bytes().to_vec()),
+            _ => s
+                .chars()
+                .map(|c| char_to_windows_1252(c).map_or_else(|| self.encode_fallback(c), Ok))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Parsing, querying, and writing the entity lumps of [`MapEnts`] assets.
+//!
+//! A map's entities (spawn points, triggers, `script_struct`s, and so on)
+//! are stored in [`MapEnts::entity_string`] as a list of blocks of quoted
+//! key/value pairs, like:
+//!
+//! ```text
+//! {
+//! "classname" "trigger_multiple"
+//! "targetname" "door_trig"
+//! "origin" "0 0 64"
+//! }
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{Error, ErrorKind, MapEnts, Result, XString, file_line_col};
+
+/// One entity of an entity lump, with its key/value pairs in the order
+/// they appear in it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Entity {
+    pub pairs: Vec<(String, String)>,
+}
+
+impl Entity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value of `key`, if the entity has it.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Sets `key` to `value`, returning its old value. New keys are added
+    /// after the existing ones.
+    pub fn set(&mut self, key: &str, value: &str) -> Option<String> {
+        match self.pairs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => Some(core::mem::replace(v, value.to_string())),
+            None => {
+                self.pairs.push((key.to_string(), value.to_string()));
+                None
+            }
+        }
+    }
+
+    /// Removes `key`, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let i = self.pairs.iter().position(|(k, _)| k == key)?;
+        Some(self.pairs.remove(i).1)
+    }
+
+    pub fn classname(&self) -> Option<&str> {
+        self.get("classname")
+    }
+
+    pub fn targetname(&self) -> Option<&str> {
+        self.get("targetname")
+    }
+
+    /// The entity's `origin`, if it has one made of three numbers.
+    pub fn origin(&self) -> Option<[f32; 3]> {
+        let mut coords = self.get("origin")?.split_whitespace().map(str::parse);
+        let origin = [
+            coords.next()?.ok()?,
+            coords.next()?.ok()?,
+            coords.next()?.ok()?,
+        ];
+        coords.next().is_none().then_some(origin)
+    }
+}
+
+/// The entities of `entities` whose `classname` is `classname`.
+pub fn by_classname<'a>(
+    entities: &'a [Entity],
+    classname: &'a str,
+) -> impl Iterator<Item = &'a Entity> {
+    entities
+        .iter()
+        .filter(move |e| e.classname() == Some(classname))
+}
+
+/// The entities of `entities` whose `targetname` is `targetname`.
+pub fn by_targetname<'a>(
+    entities: &'a [Entity],
+    targetname: &'a str,
+) -> impl Iterator<Item = &'a Entity> {
+    entities
+        .iter()
+        .filter(move |e| e.targetname() == Some(targetname))
+}
+
+fn parse_error(message: String) -> Error {
+    Error::new(
+        file_line_col!(),
+        ErrorKind::Parse(format!("entities: {message}")),
+    )
+}
+
+/// Parses an entity lump. Whitespace (including newlines) between tokens
+/// is ignored.
+pub fn parse(s: &str) -> Result<Vec<Entity>> {
+    let mut entities = Vec::new();
+    let mut entity: Option<Entity> = None;
+    let mut key: Option<String> = None;
+    let mut line = 1;
+
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() || c == '\0' => {}
+            '{' if entity.is_none() => entity = Some(Entity::new()),
+            '}' if key.is_some() => {
+                return Err(parse_error(format!("key without a value on line {line}")));
+            }
+            '}' if entity.is_some() => entities.push(entity.take().unwrap()),
+            '"' if entity.is_some() => {
+                let start = line;
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            token.push(c);
+                        }
+                        None => {
+                            return Err(parse_error(format!(
+                                "unterminated string on line {start}"
+                            )));
+                        }
+                    }
+                }
+
+                match key.take() {
+                    Some(key) => entity.as_mut().unwrap().pairs.push((key, token)),
+                    None => key = Some(token),
+                }
+            }
+            c => return Err(parse_error(format!("unexpected {c:?} on line {line}"))),
+        }
+    }
+
+    if entity.is_some() {
+        return Err(parse_error("unterminated entity".to_string()));
+    }
+    Ok(entities)
+}
+
+/// Writes `entities` as an entity lump, in the format the map compiler
+/// writes them in.
+///
+/// Fails with [`ErrorKind::BrokenInvariant`] if a key or value contains a
+/// `"`, since the format has no way to escape it.
+pub fn write(entities: &[Entity]) -> Result<String> {
+    let mut s = String::new();
+    for entity in entities {
+        s.push_str("{\n");
+        for (key, value) in &entity.pairs {
+            if key.contains('"') || value.contains('"') {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::BrokenInvariant(format!(
+                        "entities: \"{key}\" \"{value}\" contains a quote"
+                    )),
+                ));
+            }
+            s.push_str(&format!("\"{key}\" \"{value}\"\n"));
+        }
+        s.push_str("}\n");
+    }
+    Ok(s)
+}
+
+impl MapEnts {
+    /// Parses [`Self::entity_string`]. See [`parse`].
+    pub fn entities(&self) -> Result<Vec<Entity>> {
+        parse(self.entity_string.get())
+    }
+
+    /// The entities whose `classname` is `classname`.
+    pub fn find_by_classname(&self, classname: &str) -> Result<Vec<Entity>> {
+        let entities = self.entities()?;
+        Ok(by_classname(&entities, classname).cloned().collect())
+    }
+
+    /// The entities whose `targetname` is `targetname`.
+    pub fn find_by_targetname(&self, targetname: &str) -> Result<Vec<Entity>> {
+        let entities = self.entities()?;
+        Ok(by_targetname(&entities, targetname).cloned().collect())
+    }
+
+    /// Replaces [`Self::entity_string`] with `entities`. See [`write`].
+    pub fn set_entities(&mut self, entities: &[Entity]) -> Result<()> {
+        self.entity_string = XString(write(entities)?);
+        Ok(())
+    }
+}
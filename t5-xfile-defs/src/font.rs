@@ -1,15 +1,18 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::vec::Vec;
 
 use crate::{
-    FatPointer, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto,
-    XFileSerialize, XString, XStringRaw, assert_size,
+    Ptr32, Result, Shared, T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString,
+    XStringRaw, assert_size,
     techset::{Material, MaterialRaw},
 };
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize};
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FontRaw<'a> {
     pub font_name: XStringRaw<'a>,
     pub pixel_height: i32,
@@ -21,12 +24,13 @@ pub(crate) struct FontRaw<'a> {
 assert_size!(FontRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Font {
     pub font_name: XString,
     pub pixel_height: i32,
-    pub material: Option<Box<Material>>,
-    pub glow_material: Option<Box<Material>>,
+    pub material: Option<Shared<Material>>,
+    pub glow_material: Option<Shared<Material>>,
     pub glyphs: Vec<Glyph>,
 }
 
@@ -42,6 +46,7 @@ impl<'a> XFileDeserializeInto<Font, ()> for FontRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'a> XFileSerialize<()> for Font {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let font_name = XStringRaw::from_str(self.font_name.get());
@@ -67,8 +72,9 @@ impl<'a> XFileSerialize<()> for Font {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct Glyph {
     pub letter: u16,
     pub x0: i8,
@@ -84,6 +90,7 @@ pub struct Glyph {
 }
 assert_size!(Glyph, 24);
 
+#[cfg(feature = "serde")]
 impl<T: Copy> XFileSerialize<T> for Glyph {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: T) -> Result<()> {
         ser.store_into_xfile(*self)
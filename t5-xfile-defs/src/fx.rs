@@ -1,26 +1,26 @@
-use core::mem::transmute;
-
 use alloc::{boxed::Box, vec, vec::Vec};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, Ptr32, Ptr32ArrayConst, Result,
-    T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString,
-    XStringRaw, assert_size,
+    Error, ErrorKind, FatPointerCountFirstU32, Ptr32, Ptr32ArrayConst, Result, Shared,
+    T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     common::{Vec2, Vec3, Vec4},
-    file_line_col,
+    deserialize_array_n, file_line_col,
     techset::{Material, MaterialRaw},
     xmodel::{XModel, XModelRaw},
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
 use bitflags::bitflags;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxEffectDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub flags: u8,
@@ -40,7 +40,8 @@ assert_size!(FxEffectDefRaw, 60);
 
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    #[derive(Clone, Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
     pub struct FxEffectDefFlags: u8 {
         const NEEDS_LIGHTING = 0x01;
         const IS_SEE_THRU_DECAL = 0x02;
@@ -48,7 +49,8 @@ bitflags! {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxEffectDef {
     pub name: XString,
     pub flags: FxEffectDefFlags,
@@ -104,6 +106,7 @@ impl<'a> XFileDeserializeInto<FxEffectDef, ()> for FxEffectDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxEffectDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -131,7 +134,7 @@ impl XFileSerialize<()> for FxEffectDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemDefRaw<'a> {
     pub flags: i32,
     pub spawn: [i32; 2],
@@ -184,7 +187,8 @@ pub(crate) struct FxElemDefRaw<'a> {
 assert_size!(FxElemDefRaw, 292);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u8)]
 pub enum FxElemType {
     UNKNOWN = 0x00,
@@ -200,7 +204,8 @@ pub enum FxElemType {
 
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    #[derive(Clone, Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Clone, Debug, PartialEq, Eq, Hash)]
     pub struct FxElemFlags: i32 {
         const SPAWN_RELATIVE_TO_EFFECT = 0x00000002;
         const SPAWN_FRUSTUM_CULL = 0x00000004;
@@ -230,7 +235,8 @@ bitflags! {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxElemDef {
     pub flags: FxElemFlags,
     pub spawn: [i32; 2],
@@ -382,6 +388,7 @@ impl<'a> XFileDeserializeInto<FxElemDef, ()> for FxElemDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let vel_samples = Ptr32::from_slice(&self.vel_samples);
@@ -432,8 +439,12 @@ impl XFileSerialize<()> for FxElemDef {
         let trail_def = Ptr32::from_box(&self.trail_def);
         let u = if let Some(u) = &self.u {
             match u {
-                FxElemDefUnion::Billboard(b) => unsafe { transmute(b) },
-                FxElemDefUnion::CloudDensityRange(r) => unsafe { transmute(r) },
+                FxElemDefUnion::Billboard(b) => {
+                    union_bytes(b.top_width.to_bits(), b.bottom_width.to_bits(), ser)
+                }
+                FxElemDefUnion::CloudDensityRange(r) => {
+                    union_bytes(r.base as _, r.amplitude as _, ser)
+                }
             }
         } else {
             [0u8; 8]
@@ -499,30 +510,48 @@ impl XFileSerialize<()> for FxElemDef {
         self.effect_emitted.xfile_serialize(ser, ())?;
         self.effect_attached.xfile_serialize(ser, ())?;
         self.trail_def.xfile_serialize(ser, ())?;
-        self.spawn_sound.spawn_sound.xfile_serialize(ser, ())
+        self.spawn_sound.xfile_serialize(ser, ())
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FxElemDefUnion {
     Billboard(FxBillboardTrim),
     CloudDensityRange(FxIntRange),
 }
 
+/// Lays out the two 4-byte members of an [`FxElemDefUnion`] in `ser`'s byte
+/// order.
+#[cfg(feature = "serde")]
+fn union_bytes(a: u32, b: u32, ser: &impl T5XFileSerialize) -> [u8; 8] {
+    let (a, b) = if ser.is_little_endian() {
+        (a.to_le_bytes(), b.to_le_bytes())
+    } else {
+        (a.to_be_bytes(), b.to_be_bytes())
+    };
+    let mut u = [0u8; 8];
+    u[..4].copy_from_slice(&a);
+    u[4..].copy_from_slice(&b);
+    u
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxBillboardTrim {
     pub top_width: f32,
     pub bottom_width: f32,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct FxEffectDefRefRaw<'a>(Ptr32<'a, ()>);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FxEffectDefRef {
     Name(XString),
     Handle(Option<Box<FxEffectDef>>),
@@ -543,6 +572,7 @@ impl<'a> XFileDeserializeInto<FxEffectDefRef, ()> for FxEffectDefRefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxEffectDefRef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let effect_def_ref = FxEffectDefRefRaw(match self {
@@ -560,12 +590,13 @@ impl XFileSerialize<()> for FxEffectDefRef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemDefVisualsRaw<'a>(Ptr32<'a, ()>);
 assert_size!(FxElemDefVisualsRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FxElemDefVisuals {
     MarkArray(Vec<FxElemMarkVisuals>),
     Array(Vec<FxElemVisuals>),
@@ -588,7 +619,7 @@ impl<'a> XFileDeserializeInto<Option<FxElemDefVisuals>, (u8, u8)> for FxElemDefV
                 .xfile_deserialize_into(de, ())?;
             Ok(Some(FxElemDefVisuals::MarkArray(mark_array)))
         } else if visual_count < 2 {
-            let instance = self
+            let instance: Option<Box<_>> = self
                 .0
                 .cast::<FxElemVisualsRaw>()
                 .xfile_deserialize_into(de, elem_type)?;
@@ -609,6 +640,7 @@ impl<'a> XFileDeserializeInto<Option<FxElemDefVisuals>, (u8, u8)> for FxElemDefV
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemDefVisuals {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         // intentionally don't serialize FxElemDefVisualsRaw since it's only
@@ -636,16 +668,17 @@ impl XFileSerialize<()> for FxElemDefVisuals {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemMarkVisualsRaw<'a> {
     pub materials: [Ptr32<'a, MaterialRaw<'a>>; 2],
 }
 assert_size!(FxElemMarkVisualsRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxElemMarkVisuals {
-    pub materials: [Option<Box<Material>>; 2],
+    pub materials: [Option<Shared<Material>>; 2],
 }
 
 impl<'a> XFileDeserializeInto<FxElemMarkVisuals, ()> for FxElemMarkVisualsRaw<'a> {
@@ -657,14 +690,12 @@ impl<'a> XFileDeserializeInto<FxElemMarkVisuals, ()> for FxElemMarkVisualsRaw<'a
         //dbg!(self);
 
         Ok(FxElemMarkVisuals {
-            materials: [
-                self.materials[0].xfile_deserialize_into(de, ())?,
-                self.materials[1].xfile_deserialize_into(de, ())?,
-            ],
+            materials: deserialize_array_n(&self.materials, de, ())?,
         })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemMarkVisuals {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let mark_visuals = FxElemMarkVisualsRaw {
@@ -681,14 +712,15 @@ impl XFileSerialize<()> for FxElemMarkVisuals {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemVisualsRaw<'a>(Ptr32<'a, ()>);
 assert_size!(FxElemVisualsRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FxElemVisuals {
-    Material(Option<Box<Material>>),
+    Material(Option<Shared<Material>>),
     Model(Option<Box<XModel>>),
     EffectDef(FxEffectDefRef),
     SoundName(XString),
@@ -706,7 +738,7 @@ impl<'a> XFileDeserializeInto<Option<FxElemVisuals>, u8> for FxElemVisualsRaw<'a
             let model = self.0.cast::<XModelRaw>().xfile_deserialize_into(de, ())?;
             Ok(Some(FxElemVisuals::Model(model)))
         } else if elem_type == FxElemType::RUNNER as _ {
-            let effect_def = self
+            let effect_def: Option<Box<_>> = self
                 .0
                 .cast::<FxEffectDefRefRaw>()
                 .xfile_deserialize_into(de, ())?;
@@ -729,6 +761,7 @@ impl<'a> XFileDeserializeInto<Option<FxElemVisuals>, u8> for FxElemVisualsRaw<'a
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemVisuals {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let visuals = FxElemVisualsRaw(match self {
@@ -755,24 +788,27 @@ impl XFileSerialize<()> for FxElemVisuals {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct FxFloatRange {
     pub base: f32,
     pub amplitude: f32,
 }
 assert_size!(FxFloatRange, 8);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct FxIntRange {
     pub base: i32,
     pub amplitude: i32,
 }
 assert_size!(FxIntRange, 8);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct FxElemAtlas {
     pub behavior: u8,
     pub index: u8,
@@ -784,30 +820,34 @@ pub struct FxElemAtlas {
 }
 assert_size!(FxElemAtlas, 8);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct FxElemVelStateSample {
     pub local: FxElemVelStateInFrame,
     pub world: FxElemVelStateInFrame,
 }
 assert_size!(FxElemVelStateSample, 96);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemVelStateSample {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct FxElemVelStateInFrame {
     pub velocity: FxElemVec3Range,
     pub total_delta: FxElemVec3Range,
 }
 assert_size!(FxElemVelStateInFrame, 48);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct FxElemVec3Range {
     pub base: [f32; 3],
     pub amplitude: [f32; 3],
@@ -815,7 +855,7 @@ pub struct FxElemVec3Range {
 assert_size!(FxElemVec3Range, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemVisStateSampleRaw {
     pub base: FxElemVisualStateRaw,
     pub amplitude: FxElemVisualStateRaw,
@@ -823,7 +863,8 @@ pub(crate) struct FxElemVisStateSampleRaw {
 assert_size!(FxElemVisStateSampleRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxElemVisStateSample {
     pub base: FxElemVisualState,
     pub amplitude: FxElemVisualState,
@@ -838,6 +879,7 @@ impl From<FxElemVisStateSampleRaw> for FxElemVisStateSample {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxElemVisStateSample {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let sample = FxElemVisStateSampleRaw {
@@ -862,7 +904,7 @@ impl XFileSerialize<()> for FxElemVisStateSample {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemVisualStateRaw {
     pub color: [u8; 4],
     pub rotation_delta: f32,
@@ -873,7 +915,8 @@ pub(crate) struct FxElemVisualStateRaw {
 assert_size!(FxElemVisualStateRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxElemVisualState {
     pub color: [u8; 4],
     pub rotation_delta: f32,
@@ -895,7 +938,7 @@ impl Into<FxElemVisualState> for FxElemVisualStateRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxTrailDefRaw<'a> {
     pub scroll_time_msec: i32,
     pub repeat_dist: i32,
@@ -906,7 +949,8 @@ pub(crate) struct FxTrailDefRaw<'a> {
 assert_size!(FxTrailDefRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxTrailDef {
     pub scroll_time_msec: i32,
     pub repeat_dist: i32,
@@ -928,11 +972,12 @@ impl<'a> XFileDeserializeInto<FxTrailDef, ()> for FxTrailDefRaw<'a> {
             repeat_dist: self.repeat_dist,
             split_dist: self.split_dist,
             verts: self.verts.to_vec_into(de)?,
-            inds: self.inds.to_vec(de)?,
+            inds: self.inds.to_vec_bulk(de)?,
         })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxTrailDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let verts = FatPointerCountFirstU32::from_slice(&self.verts);
@@ -953,7 +998,7 @@ impl XFileSerialize<()> for FxTrailDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxTrailVertexRaw {
     pub pos: [f32; 2],
     pub normal: [f32; 2],
@@ -962,7 +1007,8 @@ pub(crate) struct FxTrailVertexRaw {
 assert_size!(FxTrailVertexRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxTrailVertex {
     pub pos: Vec2,
     pub normal: Vec2,
@@ -979,6 +1025,7 @@ impl From<FxTrailVertexRaw> for FxTrailVertex {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxTrailVertex {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let vertex = FxTrailVertexRaw {
@@ -992,14 +1039,15 @@ impl XFileSerialize<()> for FxTrailVertex {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxElemSpawnSoundRaw<'a> {
     pub spawn_sound: XStringRaw<'a>,
 }
 assert_size!(FxElemSpawnSoundRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct FxElemSpawnSound {
     pub spawn_sound: XString,
 }
@@ -1019,15 +1067,23 @@ impl<'a> XFileDeserializeInto<FxElemSpawnSound, ()> for FxElemSpawnSoundRaw<'a>
     }
 }
 
+#[cfg(feature = "serde")]
+impl XFileSerialize<()> for FxElemSpawnSound {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        self.spawn_sound.xfile_serialize(ser, ())
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxImpactTableRaw<'a> {
     pub name: XStringRaw<'a>,
     pub table: Ptr32ArrayConst<'a, FxImpactEntryRaw<'a>, 21>,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxImpactTable {
     pub name: XString,
     pub table: Vec<FxImpactEntry>,
@@ -1050,6 +1106,7 @@ impl<'a> XFileDeserializeInto<FxImpactTable, ()> for FxImpactTableRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxImpactTable {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -1064,14 +1121,15 @@ impl XFileSerialize<()> for FxImpactTable {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct FxImpactEntryRaw<'a> {
     pub nonflesh: [Ptr32<'a, FxEffectDefRaw<'a>>; 31],
     pub flesh: [Ptr32<'a, FxEffectDefRaw<'a>>; 4],
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FxImpactEntry {
     pub nonflesh: [Option<Box<FxEffectDef>>; 31],
     pub flesh: [Option<Box<FxEffectDef>>; 4],
@@ -1105,6 +1163,7 @@ impl<'a> XFileDeserializeInto<FxImpactEntry, ()> for FxImpactEntryRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for FxImpactEntry {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let nonflesh = self
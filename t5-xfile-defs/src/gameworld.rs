@@ -1,28 +1,33 @@
+#[cfg(feature = "serde")]
 use core::mem::transmute;
 
 use alloc::{boxed::Box, vec::Vec};
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU16, FatPointerCountFirstU32, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointerCountFirstU16, FatPointerCountFirstU32, Ptr32, Result,
+    ScriptString, T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString, XStringRaw,
+    assert_size,
     common::{Vec2, Vec3},
-    file_line_col,
+    file_line_col, read,
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
 use bitflags::bitflags;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GameWorldSpRaw<'a> {
     pub name: XStringRaw<'a>,
     pub path: PathDataRaw<'a>,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GameWorldSp {
     pub name: XString,
     pub path: PathData,
@@ -41,6 +46,7 @@ impl<'a> XFileDeserializeInto<GameWorldSp, ()> for GameWorldSpRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GameWorldSp {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -83,14 +89,15 @@ impl XFileSerialize<()> for GameWorldSp {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GameWorldMpRaw<'a> {
     pub name: XStringRaw<'a>,
     pub path: PathDataRaw<'a>,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GameWorldMp {
     pub name: XString,
     pub path: PathData,
@@ -109,6 +116,7 @@ impl<'a> XFileDeserializeInto<GameWorldMp, ()> for GameWorldMpRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GameWorldMp {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -151,11 +159,12 @@ impl XFileSerialize<()> for GameWorldMp {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathDataRaw<'a> {
     pub node_count: u32,
     pub nodes: Ptr32<'a, PathNodeRaw<'a>>,
     pub basenodes: Ptr32<'a, PathBaseNodeRaw>,
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
     pub chain_node_count: u32,
     pub chain_node_for_node: Ptr32<'a, u16>,
     pub node_for_chain_node: Ptr32<'a, u16>,
@@ -164,7 +173,8 @@ pub(crate) struct PathDataRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PathData {
     pub nodes: Vec<PathNode>,
     pub basenodes: Vec<PathBaseNode>,
@@ -191,12 +201,12 @@ impl<'a> XFileDeserializeInto<PathData, ()> for PathDataRaw<'a> {
         let chain_node_for_node = self
             .chain_node_for_node
             .to_array(self.node_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let node_for_chain_node = self
             .node_for_chain_node
             .to_array(self.node_count as _)
-            .to_vec(de)?;
-        let path_vis = self.path_vis.to_vec(de)?;
+            .to_vec_bulk(de)?;
+        let path_vis = self.path_vis.to_vec_bulk(de)?;
         let node_tree = self.node_tree.xfile_deserialize_into(de, ())?;
 
         Ok(PathData {
@@ -210,8 +220,88 @@ impl<'a> XFileDeserializeInto<PathData, ()> for PathDataRaw<'a> {
     }
 }
 
+impl PathData {
+    /// The navigation graph the nodes and their links form.
+    ///
+    /// Unused nodes ([`NodeType::BADNODE`], like the spare ones at the end
+    /// of [`Self::nodes`]) are left out, along with links to them or to
+    /// nodes that don't exist.
+    pub fn graph(&self) -> PathGraph<'_> {
+        let is_used = |i: usize| {
+            self.nodes
+                .get(i)
+                .is_some_and(|n| n.constant.type_ != NodeType::BADNODE)
+        };
+
+        let mut graph = PathGraph::default();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if !is_used(index) {
+                continue;
+            }
+
+            let constant = &node.constant;
+            graph.nodes.push(PathGraphNode {
+                index,
+                type_: constant.type_,
+                origin: constant.origin.get(),
+                angle: constant.angle,
+                targetname: constant.targetname.get(),
+                disabled: constant.spawnflags.contains(SpawnFlags::DISABLED),
+            });
+            graph.links.extend(
+                constant
+                    .links
+                    .iter()
+                    .filter(|link| is_used(link.node_num as usize))
+                    .map(|link| PathGraphLink {
+                        from: index,
+                        to: link.node_num as usize,
+                        dist: link.dist,
+                        negotiation: link.negotiation_link != 0,
+                        disconnected: link.disconnect_count != 0,
+                    }),
+            );
+        }
+        graph
+    }
+}
+
+/// The path nodes of a [`PathData`] and the links between them, as returned
+/// by [`PathData::graph`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathGraph<'a> {
+    pub nodes: Vec<PathGraphNode<'a>>,
+    /// Links are one-way, so two nodes linked both ways have two links.
+    pub links: Vec<PathGraphLink>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathGraphNode<'a> {
+    /// The node's index in [`PathData::nodes`], which [`PathGraphLink`]s
+    /// refer to it by.
+    pub index: usize,
+    pub type_: NodeType,
+    pub origin: [f32; 3],
+    /// The yaw the node faces, in degrees.
+    pub angle: f32,
+    pub targetname: &'a str,
+    pub disabled: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathGraphLink {
+    pub from: usize,
+    pub to: usize,
+    pub dist: f32,
+    /// Whether the link is traversed with a negotiation (e.g. a mantle or a
+    /// jump) rather than by walking.
+    pub negotiation: bool,
+    /// Whether the link is disconnected, e.g. by a closed door.
+    pub disconnected: bool,
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeRaw<'a> {
     pub constant: PathNodeConstantRaw<'a>,
     pub dynamic: PathNodeDynamicRaw,
@@ -219,7 +309,8 @@ pub(crate) struct PathNodeRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PathNode {
     pub constant: PathNodeConstant,
     pub dynamic: PathNodeDynamic,
@@ -240,6 +331,7 @@ impl<'a> XFileDeserializeInto<PathNode, ()> for PathNodeRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PathNode {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let links = FatPointerCountFirstU16::from_slice(&self.constant.links);
@@ -304,7 +396,7 @@ impl XFileSerialize<()> for PathNode {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeConstantRaw<'a> {
     pub type_: u16,
     pub spawnflags: u16,
@@ -328,7 +420,8 @@ pub(crate) struct PathNodeConstantRaw<'a> {
 assert_size!(PathNodeConstantRaw, 68);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 pub enum NodeType {
     #[default]
     BADNODE = 0x00,
@@ -357,7 +450,8 @@ pub enum NodeType {
 
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    #[derive(Clone, Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
     pub struct SpawnFlags: u16 {
         const DONTLINK = 0x0001;
         const NOTCHAIN = 0x0002;
@@ -378,8 +472,9 @@ bitflags! {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct PathLink {
     pub dist: f32,
     pub node_num: u16,
@@ -389,6 +484,7 @@ pub struct PathLink {
 }
 assert_size!(PathLink, 12);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PathLink {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -396,7 +492,8 @@ impl XFileSerialize<()> for PathLink {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PathNodeConstant {
     pub type_: NodeType,
     pub spawnflags: SpawnFlags,
@@ -456,7 +553,7 @@ impl<'a> XFileDeserializeInto<PathNodeConstant, ()> for PathNodeConstantRaw<'a>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeDynamicRaw {
     pub owner: SentientHandleRaw,
     pub free_time: i32,
@@ -470,7 +567,8 @@ pub(crate) struct PathNodeDynamicRaw {
 assert_size!(PathNodeDynamicRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct PathNodeDynamic {
     pub owner: SentientHandle,
     pub free_time: i32,
@@ -498,7 +596,7 @@ impl Into<PathNodeDynamic> for PathNodeDynamicRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SentientHandleRaw {
     pub number: i16,
     pub info_index: i16,
@@ -506,7 +604,8 @@ pub(crate) struct SentientHandleRaw {
 assert_size!(SentientHandleRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct SentientHandle {
     pub number: i16,
     pub info_index: usize,
@@ -522,7 +621,7 @@ impl Into<SentientHandle> for SentientHandleRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeTransientRaw<'a> {
     pub search_frame: i32,
     #[allow(dead_code)]
@@ -538,7 +637,8 @@ pub(crate) struct PathNodeTransientRaw<'a> {
 assert_size!(PathNodeTransientRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PathNodeTransient {
     pub search_frame: i32,
     pub cost: f32,
@@ -558,7 +658,7 @@ impl<'a> Into<PathNodeTransient> for PathNodeTransientRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathBaseNodeRaw {
     pub origin: [f32; 3],
     pub type_: u32,
@@ -566,7 +666,8 @@ pub(crate) struct PathBaseNodeRaw {
 assert_size!(PathBaseNodeRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PathBaseNode {
     pub origin: Vec3,
     pub type_: u32,
@@ -581,6 +682,7 @@ impl From<PathBaseNodeRaw> for PathBaseNode {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PathBaseNode {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let node = PathBaseNodeRaw {
@@ -593,7 +695,7 @@ impl XFileSerialize<()> for PathBaseNode {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeTreeRaw {
     pub axis: i32,
     pub dist: f32,
@@ -602,14 +704,16 @@ pub(crate) struct PathNodeTreeRaw {
 assert_size!(PathNodeTreeRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum PathNodeTreeInfo {
     S(PathNodeTreeNodes),
     Child((Option<Box<PathNodeTree>>, Option<Box<PathNodeTree>>)),
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PathNodeTree {
     pub axis: i32,
     pub dist: f32,
@@ -624,11 +728,18 @@ impl XFileDeserializeInto<PathNodeTree, ()> for PathNodeTreeRaw {
     ) -> Result<PathNodeTree> {
         let u = if self.axis < 0 {
             PathNodeTreeInfo::S(
-                unsafe { transmute::<_, PathNodeTreeNodesRaw>(self.u) }
+                read::from_bytes::<PathNodeTreeNodesRaw>(&self.u, de.is_little_endian())?
                     .xfile_deserialize_into(de, ())?,
             )
         } else {
-            unimplemented!()
+            let [a, b] = read::from_bytes::<[Ptr32<'_, PathNodeTreeRaw>; 2]>(
+                &self.u,
+                de.is_little_endian(),
+            )?;
+            PathNodeTreeInfo::Child((
+                a.xfile_deserialize_into(de, ())?,
+                b.xfile_deserialize_into(de, ())?,
+            ))
         };
 
         Ok(PathNodeTree {
@@ -639,6 +750,7 @@ impl XFileDeserializeInto<PathNodeTree, ()> for PathNodeTreeRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PathNodeTree {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = match &self.u {
@@ -674,14 +786,15 @@ impl XFileSerialize<()> for PathNodeTree {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PathNodeTreeNodesRaw<'a> {
     pub nodes: FatPointerCountFirstU32<'a, u16>,
 }
 assert_size!(PathNodeTreeNodesRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PathNodeTreeNodes {
     pub nodes: Vec<u16>,
 }
@@ -693,7 +806,7 @@ impl<'a> XFileDeserializeInto<PathNodeTreeNodes, ()> for PathNodeTreeNodesRaw<'a
         _data: (),
     ) -> Result<PathNodeTreeNodes> {
         Ok(PathNodeTreeNodes {
-            nodes: self.nodes.to_vec(de)?,
+            nodes: self.nodes.to_vec_bulk(de)?,
         })
     }
 }
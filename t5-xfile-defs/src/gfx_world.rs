@@ -1,31 +1,30 @@
-use core::fmt::Display;
 #[cfg(feature = "d3d9")]
 use core::ptr::addr_of_mut;
 
-use alloc::{
-    boxed::Box,
-    string::{String, ToString},
-    vec::Vec,
-};
+use alloc::{boxed::Box, format, string::ToString, vec, vec::Vec};
 
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU8, FatPointerCountLastU32, Ptr32,
-    Result, T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
-    common::{GfxVertexBuffer, Mat3, Mat4, Vec2, Vec3, Vec4},
+    BackRef, Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU8,
+    FatPointerCountLastU32, FixedString, InternTable, Ptr32, Result, Shared, T5XFileDeserialize,
+    XArray, XFileDeserializeInto, XFilePlatform, XFileRead, XString, XStringRaw, assert_size,
+    common::{Aabb, GfxVertexBuffer, Mat3, Mat4, Vec2, Vec3, Vec4},
+    deserialize_array_n, file_line_col,
     light::{GfxLightDef, GfxLightDefRaw},
+    size_of,
     techset::{
         GfxDrawSurf, GfxImage, GfxImageRaw, GfxTexture, GfxTextureRaw, Material, MaterialRaw,
     },
-    xmodel::{CPlane, CPlaneRaw, GfxColor, XModel, XModelDrawInfo, XModelRaw},
+    xmodel::{CPlane, CPlaneRaw, GfxColor, UnitVec, XModel, XModelDrawInfo, XModelRaw},
 };
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "d3d9")]
 use windows::Win32::Graphics::Direct3D9::{D3DPOOL_DEFAULT, IDirect3DVertexBuffer9};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub name: XStringRaw<'a>,
     pub base_name: XStringRaw<'a>,
@@ -92,7 +91,8 @@ pub(crate) struct GfxWorldRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
 assert_size!(GfxWorldRaw<1>, 1084);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorld<const MAX_LOCAL_CLIENTS: usize> {
     pub name: XString,
     pub base_name: XString,
@@ -144,9 +144,9 @@ pub struct GfxWorld<const MAX_LOCAL_CLIENTS: usize> {
     pub world_lod_surfaces: Vec<u32>,
     pub water_direction: f32,
     pub water_buffers: [GfxWaterBuffer; 2],
-    pub water_material: Option<Box<Material>>,
-    pub corona_material: Option<Box<Material>>,
-    pub rope_material: Option<Box<Material>>,
+    pub water_material: Option<Shared<Material>>,
+    pub corona_material: Option<Shared<Material>>,
+    pub rope_material: Option<Shared<Material>>,
     pub occluders: Vec<Occluder>,
     pub outdoor_bounds: Vec<GfxOutdoorBounds>,
     pub hero_lights: Vec<GfxHeroLight>,
@@ -197,7 +197,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
                 ((self.dpvs_planes.cell_count as usize + 31) >> 5)
                     * self.dpvs_planes.cell_count as usize,
             )
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let scene_dyn_model = self
             .scene_dyn_model
             .to_array(self.dpvs_dyn.dyn_ent_client_count[0] as _)
@@ -212,7 +212,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
                 (self.primary_light_count as usize - self.sun_primary_light_index as usize + 1)
                     * 8192,
             )
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let primary_light_dyn_ent_shadow_vis = [
             self.primary_light_dyn_ent_shadow_vis[0]
                 .to_array(
@@ -230,7 +230,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
         let non_sun_primary_light_for_model_dyn_ent = self
             .non_sun_primary_light_for_model_dyn_ent
             .to_array(self.dpvs_dyn.dyn_ent_client_count[0] as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let shadow_geom = self
             .shadow_geom
             .to_array(self.primary_light_count as _)
@@ -247,11 +247,8 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
             .xfile_deserialize_into(de, self.dpvs_planes.cell_count as _)?;
         let world_lod_chains = self.world_lod_chains.to_vec_into(de)?;
         let world_lod_infos = self.world_lod_infos.to_vec(de)?;
-        let world_lod_surfaces = self.world_lod_surfaces.to_vec(de)?;
-        let water_buffers = [
-            self.water_buffers[0].xfile_deserialize_into(de, ())?,
-            self.water_buffers[0].xfile_deserialize_into(de, ())?,
-        ];
+        let world_lod_surfaces = self.world_lod_surfaces.to_vec_bulk(de)?;
+        let water_buffers = deserialize_array_n(&self.water_buffers, de, ())?;
         let water_material = self.water_material.xfile_deserialize_into(de, ())?;
         let corona_material = self.corona_material.xfile_deserialize_into(de, ())?;
         let rope_material = self.rope_material.xfile_deserialize_into(de, ())?;
@@ -328,8 +325,98 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<GfxWorld<MAX_LOCAL
     }
 }
 
+impl<const MAX_LOCAL_CLIENTS: usize> GfxWorld<MAX_LOCAL_CLIENTS> {
+    /// Iterates over every portal of every cell, in cell order. This is the
+    /// order [`GfxPortalWritable::queued_parent`] indexes into.
+    pub fn portals(&self) -> impl Iterator<Item = &GfxPortal> {
+        self.cells.iter().flat_map(|c| c.portals.iter())
+    }
+
+    pub fn portal(&self, index: usize) -> Option<&GfxPortal> {
+        self.portals().nth(index)
+    }
+
+    /// Resolves the back-references of every portal (see [`BackRef`]).
+    ///
+    /// `cells` maps the offsets of the world's cells to indices into
+    /// [`Self::cells`], and `portals` maps the offsets of the cells' portals
+    /// to indices into [`Self::portals`].
+    ///
+    /// Returns the number of references that couldn't be resolved.
+    pub fn resolve_back_refs(&mut self, cells: &InternTable, portals: &InternTable) -> usize {
+        let mut unresolved = 0;
+        for portal in self.cells.iter_mut().flat_map(|c| c.portals.iter_mut()) {
+            if !portal.cell.resolve(cells) {
+                unresolved += 1;
+            }
+            if !portal.writable.queued_parent.resolve(portals) {
+                unresolved += 1;
+            }
+        }
+        unresolved
+    }
+
+    /// Returns the indices into [`GfxWorldDpvsStatic::surfaces`] of every
+    /// surface whose bounds intersect `bounds`, sorted and without
+    /// duplicates.
+    ///
+    /// Walks each cell's [`GfxAabbTree`] the way the engine does for
+    /// culling, only visiting the children of nodes that intersect
+    /// `bounds`. Nodes that point outside the tree are skipped.
+    pub fn surfaces_in_aabb(&self, bounds: Aabb) -> Vec<usize> {
+        let mut surfaces = Vec::new();
+
+        for cell in self.cells.iter() {
+            if !Aabb::new(cell.mins, cell.maxs).intersects(bounds) {
+                continue;
+            }
+
+            let mut stack = if cell.aabb_tree.is_empty() {
+                Vec::new()
+            } else {
+                vec![0]
+            };
+            while let Some(i) = stack.pop() {
+                let Some(tree) = cell.aabb_tree.get(i) else {
+                    continue;
+                };
+                if !Aabb::new(tree.mins, tree.maxs).intersects(bounds) {
+                    continue;
+                }
+
+                if tree.child_count != 0 {
+                    // children always follow their parent, which also keeps
+                    // malformed trees from looping
+                    if let Some(first) = tree.first_child(i)
+                        && first > i
+                    {
+                        stack.extend(first..first + tree.child_count);
+                    }
+                    continue;
+                }
+
+                let sorted = tree.start_surf_index..tree.start_surf_index + tree.surface_count;
+                surfaces.extend(
+                    sorted
+                        .filter_map(|s| self.dpvs.sorted_surf_index.get(s))
+                        .map(|&s| s as usize)
+                        .filter(|&s| {
+                            self.dpvs.surfaces.get(s).is_some_and(|s| {
+                                Aabb::new(s.bounds[0], s.bounds[1]).intersects(bounds)
+                            })
+                        }),
+                );
+            }
+        }
+
+        surfaces.sort_unstable();
+        surfaces.dedup();
+        surfaces
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldStreamInfoRaw<'a> {
     pub aabb_trees: FatPointerCountFirstU32<'a, GfxStreamingAabbTreeRaw>,
     pub leaf_refs: FatPointerCountFirstU32<'a, i32>,
@@ -337,7 +424,8 @@ pub(crate) struct GfxWorldStreamInfoRaw<'a> {
 assert_size!(GfxWorldStreamInfoRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldStreamInfo {
     pub aabb_trees: Vec<GfxStreamingAabbTree>,
     pub leaf_refs: Vec<i32>,
@@ -360,7 +448,7 @@ impl<'a> XFileDeserializeInto<GfxWorldStreamInfo, ()> for GfxWorldStreamInfoRaw<
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxStreamingAabbTreeRaw {
     pub first_item: u16,
     pub item_count: u16,
@@ -372,7 +460,8 @@ pub(crate) struct GfxStreamingAabbTreeRaw {
 assert_size!(GfxStreamingAabbTreeRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxStreamingAabbTree {
     pub first_item: u16,
     pub item_count: u16,
@@ -396,60 +485,19 @@ impl From<GfxStreamingAabbTreeRaw> for GfxStreamingAabbTree {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
-pub(crate) struct GfxName64(#[serde(with = "serde_arrays")] [u8; 64]);
-
-impl Display for GfxName64 {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let len = self
-            .0
-            .into_iter()
-            .enumerate()
-            .find(|(_, c)| *c == 0)
-            .map(|(i, _)| i)
-            .unwrap_or(64);
-        let s = self.0[..len].iter().map(|c| *c as char).collect::<String>();
-        write!(f, "{}", s)
-    }
-}
-
-impl Default for GfxName64 {
-    fn default() -> Self {
-        Self([0u8; 64])
-    }
-}
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
-pub(crate) struct GfxName16([u8; 16]);
-
-impl Display for GfxName16 {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let len = self
-            .0
-            .into_iter()
-            .enumerate()
-            .find(|(_, c)| *c == 0)
-            .map(|(i, _)| i)
-            .unwrap_or(64);
-        let s = self.0[..len].iter().map(|c| *c as char).collect::<String>();
-        write!(f, "{}", s)
-    }
-}
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SunLightParseParamsRaw<const MAX_LOCAL_CLIENTS: usize> {
-    pub name: GfxName64,
+    pub name: FixedString<64>,
     pub tree_scatter_intensity: f32,
     pub tree_scatter_amount: f32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub sun_settings: [GfxWorldSunColorRaw; MAX_LOCAL_CLIENTS],
 }
 assert_size!(SunLightParseParamsRaw<1>, 180);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SunLightParseParams<const MAX_LOCAL_CLIENTS: usize> {
     pub name: XString,
     pub tree_scatter_intensity: f32,
@@ -478,7 +526,7 @@ impl<const MAX_LOCAL_CLIENTS: usize> From<SunLightParseParamsRaw<MAX_LOCAL_CLIEN
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldSunColorRaw {
     pub control: u32,
     pub angles: [f32; 3],
@@ -494,7 +542,8 @@ pub(crate) struct GfxWorldSunColorRaw {
 assert_size!(GfxWorldSunColorRaw, 108);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldSunColor {
     pub control: u32,
     pub angles: Vec3,
@@ -526,7 +575,7 @@ impl From<GfxWorldSunColorRaw> for GfxWorldSunColor {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightRaw<'a> {
     pub type_: u8,
     pub can_use_shadow_map: u8,
@@ -561,7 +610,8 @@ pub(crate) struct GfxLightRaw<'a> {
 assert_size!(GfxLightRaw, 368);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLight {
     pub type_: u8,
     pub can_use_shadow_map: u8,
@@ -644,7 +694,7 @@ impl<'a> XFileDeserializeInto<GfxLight, ()> for GfxLightRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightCoronaRaw {
     pub origin: [f32; 3],
     pub radius: f32,
@@ -654,7 +704,8 @@ pub(crate) struct GfxLightCoronaRaw {
 assert_size!(GfxLightCoronaRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightCorona {
     pub origin: Vec3,
     pub radius: f32,
@@ -674,7 +725,7 @@ impl From<GfxLightCoronaRaw> for GfxLightCorona {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxShadowMapVolumeRaw {
     pub control: u32,
     #[allow(dead_code)]
@@ -683,7 +734,8 @@ pub(crate) struct GfxShadowMapVolumeRaw {
 assert_size!(GfxShadowMapVolumeRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxShadowMapVolume {
     pub control: u32,
 }
@@ -697,14 +749,15 @@ impl From<GfxShadowMapVolumeRaw> for GfxShadowMapVolume {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxVolumePlaneRaw {
     pub plane: [f32; 4],
 }
 assert_size!(GfxVolumePlaneRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxVolumePlane {
     pub plane: Vec4,
 }
@@ -717,8 +770,9 @@ impl From<GfxVolumePlaneRaw> for GfxVolumePlane {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct GfxExposureVolume {
     pub control: u32,
     pub exposure: f32,
@@ -729,8 +783,9 @@ pub struct GfxExposureVolume {
 }
 assert_size!(GfxExposureVolume, 24);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct GfxSkyDynamicIntensity {
     pub angle_0: f32,
     pub angle_1: f32,
@@ -740,7 +795,7 @@ pub struct GfxSkyDynamicIntensity {
 assert_size!(GfxSkyDynamicIntensity, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldDpvsPlanesRaw<'a> {
     pub cell_count: i32,
     pub planes: Ptr32<'a, CPlaneRaw>,
@@ -750,7 +805,8 @@ pub(crate) struct GfxWorldDpvsPlanesRaw<'a> {
 assert_size!(GfxWorldDpvsPlanesRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldDpvsPlanes {
     pub planes: Vec<CPlane>,
     pub nodes: Vec<u16>,
@@ -764,11 +820,11 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsPlanes, (i32, i32)> for GfxWorldDpvsPl
         (plane_count, node_count): (i32, i32),
     ) -> Result<GfxWorldDpvsPlanes> {
         let planes = self.planes.to_array(plane_count as _).to_vec_into(de)?;
-        let nodes = self.nodes.to_array(node_count as _).to_vec(de)?;
+        let nodes = self.nodes.to_array(node_count as _).to_vec_bulk(de)?;
         let scene_ent_cell_bits = self
             .scene_ent_cell_bits
-            .to_array(self.cell_count as usize * 512)
-            .to_vec(de)?;
+            .to_array((self.cell_count as usize).saturating_mul(512))
+            .to_vec_bulk(de)?;
 
         Ok(GfxWorldDpvsPlanes {
             planes,
@@ -779,7 +835,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsPlanes, (i32, i32)> for GfxWorldDpvsPl
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxCellRaw<'a> {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -791,7 +847,8 @@ pub(crate) struct GfxCellRaw<'a> {
 assert_size!(GfxCellRaw, 56);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxCell {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -812,7 +869,7 @@ impl<'a> XFileDeserializeInto<GfxCell, ()> for GfxCellRaw<'a> {
         let aabb_tree = self.aabb_tree.xfile_deserialize_into(de, ())?;
         let portals = self.portals.xfile_deserialize_into(de, ())?;
         let cull_groups = self.cull_groups.to_vec(de)?;
-        let reflection_probes = self.reflection_probes.to_vec(de)?;
+        let reflection_probes = self.reflection_probes.to_vec_bulk(de)?;
 
         Ok(GfxCell {
             mins,
@@ -826,7 +883,7 @@ impl<'a> XFileDeserializeInto<GfxCell, ()> for GfxCellRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxAabbTreeRaw<'a> {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -840,7 +897,8 @@ pub(crate) struct GfxAabbTreeRaw<'a> {
 assert_size!(GfxAabbTreeRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxAabbTree {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -862,7 +920,7 @@ impl<'a> XFileDeserializeInto<GfxAabbTree, ()> for GfxAabbTreeRaw<'a> {
         let smodel_indexes = self
             .smodel_indexes
             .to_array(self.smodel_index_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
 
         Ok(GfxAabbTree {
             mins,
@@ -876,10 +934,24 @@ impl<'a> XFileDeserializeInto<GfxAabbTree, ()> for GfxAabbTreeRaw<'a> {
     }
 }
 
+impl GfxAabbTree {
+    /// Returns the index of the first of this node's children, given the
+    /// node's own index in [`GfxCell::aabb_tree`].
+    ///
+    /// [`Self::children_offset`] is a byte offset from the node to its
+    /// children, so it has to be a multiple of the node size.
+    pub fn first_child(&self, index: usize) -> Option<usize> {
+        let size = size_of!(GfxAabbTreeRaw) as i32;
+        if self.children_offset % size != 0 {
+            return None;
+        }
+        index.checked_add_signed((self.children_offset / size) as isize)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxPortalRaw<'a> {
-    #[allow(dead_code)]
     pub writable: GfxPortalWritableRaw<'a>,
     pub plane: DpvsPlaneRaw,
     pub cell: Ptr32<'a, GfxCellRaw<'a>>,
@@ -889,10 +961,13 @@ pub(crate) struct GfxPortalRaw<'a> {
 assert_size!(GfxPortalRaw, 68);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxPortal {
+    pub writable: GfxPortalWritable,
     pub plane: DpvsPlane,
-    pub cell: Option<Box<GfxCell>>,
+    /// Index into [`GfxWorld::cells`] once resolved.
+    pub cell: BackRef<GfxCell>,
     pub vertices: Vec<Vec3>,
     pub hull_axis: [Vec3; 2],
 }
@@ -903,12 +978,14 @@ impl<'a> XFileDeserializeInto<GfxPortal, ()> for GfxPortalRaw<'a> {
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<GfxPortal> {
+        let writable = self.writable.xfile_deserialize_into(de, ())?;
         let plane = self.plane.into();
-        let cell = self.cell.xfile_deserialize_into(de, ())?;
+        let cell = self.cell.xfile_back_ref(de, ())?;
         let vertices = self.vertices.to_vec_into(de)?;
         let hull_axis = [self.hull_axis[0].into(), self.hull_axis[1].into()];
 
         Ok(GfxPortal {
+            writable,
             plane,
             cell,
             vertices,
@@ -918,26 +995,27 @@ impl<'a> XFileDeserializeInto<GfxPortal, ()> for GfxPortalRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxPortalWritableRaw<'a> {
     pub is_queued: bool,
     pub is_ancestor: bool,
     pub recursion_depth: u8,
     pub hull_point_count: u8,
     pub hull_points: Ptr32<'a, [f32; 2]>,
-    #[allow(dead_code)]
     pub queued_parent: Ptr32<'a, GfxPortalRaw<'a>>,
 }
 assert_size!(GfxPortalWritableRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxPortalWritable {
     pub is_queued: bool,
     pub is_ancestor: bool,
     pub recursion_depth: u8,
     pub hull_points: Vec<Vec2>,
-    pub queued_parent: Option<Box<GfxPortal>>,
+    /// Index into [`GfxWorld::portals`] once resolved.
+    pub queued_parent: BackRef<GfxPortal>,
 }
 
 impl<'a> XFileDeserializeInto<GfxPortalWritable, ()> for GfxPortalWritableRaw<'a> {
@@ -950,7 +1028,7 @@ impl<'a> XFileDeserializeInto<GfxPortalWritable, ()> for GfxPortalWritableRaw<'a
             .hull_points
             .to_array(self.hull_point_count as _)
             .to_vec_into(de)?;
-        let queued_parent = None; // FIXME
+        let queued_parent = self.queued_parent.xfile_back_ref(de, ())?;
 
         Ok(GfxPortalWritable {
             is_queued: self.is_queued,
@@ -963,7 +1041,7 @@ impl<'a> XFileDeserializeInto<GfxPortalWritable, ()> for GfxPortalWritableRaw<'a
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct DpvsPlaneRaw {
     pub coeffs: [f32; 4],
     pub side: [u8; 3],
@@ -973,7 +1051,8 @@ pub(crate) struct DpvsPlaneRaw {
 assert_size!(DpvsPlaneRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DpvsPlane {
     pub coeffs: Vec4,
     pub side: [u8; 3],
@@ -989,7 +1068,7 @@ impl From<DpvsPlaneRaw> for DpvsPlane {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldDrawRaw<'a> {
     pub reflection_probes: FatPointerCountFirstU32<'a, GfxReflectionProbeRaw<'a>>,
     pub reflection_probe_textures: Ptr32<'a, GfxTextureRaw<'a>>,
@@ -1008,7 +1087,8 @@ pub(crate) struct GfxWorldDrawRaw<'a> {
 assert_size!(GfxWorldDrawRaw, 192);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldDraw {
     pub reflection_probes: Vec<GfxReflectionProbe>,
     pub reflection_probe_textures: Vec<GfxTexture>,
@@ -1025,6 +1105,66 @@ pub struct GfxWorldDraw {
     pub indices: Vec<u16>,
 }
 
+impl GfxWorldDraw {
+    /// Returns the world's triangles, as indices into
+    /// [`GfxWorldVertexData::vertices`]. Each surface's triangles are a
+    /// contiguous range of these.
+    pub fn triangles(&self) -> impl Iterator<Item = [u16; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+
+    pub fn set_triangles(&mut self, triangles: &[[u16; 3]]) {
+        self.indices = triangles.as_flattened().to_vec();
+    }
+
+    /// Returns the position, normal, texture coordinates, lightmap
+    /// coordinates, and color of every vertex (see
+    /// [`GfxWorldVertex::unpack`]).
+    pub fn unpack_vertices(&self) -> impl Iterator<Item = (Vec3, Vec3, Vec2, Vec2, GfxColor)> + '_ {
+        self.vd.vertices.iter().map(GfxWorldVertex::unpack)
+    }
+
+    /// Replaces the world's vertices with ones packed from plain floats (see
+    /// [`GfxWorldVertex::pack`]), and updates [`Self::vertex_count`].
+    /// Vertices without a color are white. `colors` should already be in the
+    /// platform's byte order (see [`GfxColor::from_rgba`]).
+    pub fn set_vertices(
+        &mut self,
+        positions: &[Vec3],
+        normals: &[Vec3],
+        uvs: &[Vec2],
+        lmap_coords: &[Vec2],
+        colors: Option<&[GfxColor]>,
+    ) -> Result<()> {
+        let len = positions.len();
+        if normals.len() != len
+            || uvs.len() != len
+            || lmap_coords.len() != len
+            || colors.is_some_and(|c| c.len() != len)
+        {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "GfxWorldDraw: {len} positions, {} normals, {} uvs, {} lightmap coords, {:?} colors",
+                    normals.len(),
+                    uvs.len(),
+                    lmap_coords.len(),
+                    colors.map(<[_]>::len),
+                )),
+            ));
+        }
+
+        self.vd.vertices = (0..len)
+            .map(|i| {
+                let color = colors.map_or(GfxColor::WHITE, |c| c[i]);
+                GfxWorldVertex::pack(positions[i], normals[i], uvs[i], lmap_coords[i], color)
+            })
+            .collect();
+        self.vertex_count = len as _;
+        Ok(())
+    }
+}
+
 impl<'a> XFileDeserializeInto<GfxWorldDraw, ()> for GfxWorldDrawRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -1034,25 +1174,28 @@ impl<'a> XFileDeserializeInto<GfxWorldDraw, ()> for GfxWorldDrawRaw<'a> {
         let reflection_probes = self.reflection_probes.xfile_deserialize_into(de, ())?;
         let reflection_probe_textures = self
             .reflection_probe_textures
-            .to_array(self.reflection_probes.size())
+            .to_array(self.reflection_probes.len())
             .xfile_deserialize_into(de, ())?;
         let lightmaps = self.lightmaps.xfile_deserialize_into(de, ())?;
         let lightmap_primary_textures = self
             .lightmap_primary_textures
-            .to_array(self.lightmaps.size())
+            .to_array(self.lightmaps.len())
             .xfile_deserialize_into(de, ())?;
         let lightmap_secondary_textures = self
             .lightmap_secondary_textures
-            .to_array(self.lightmaps.size())
+            .to_array(self.lightmaps.len())
             .xfile_deserialize_into(de, ())?;
         let lightmap_secondary_textures_b = self
             .lightmap_secondary_textures_b
-            .to_array(self.lightmaps.size())
+            .to_array(self.lightmaps.len())
             .xfile_deserialize_into(de, ())?;
         let terrain_scorch_images = self
             .terrain_scorch_images
             .into_iter()
-            .map(|i| i.xfile_deserialize_into(de, ()).map(|r| r.map(|p| *p)))
+            .map(|i| {
+                i.xfile_deserialize_into(de, ())
+                    .map(|r: Option<Box<_>>| r.map(|p| *p))
+            })
             .collect::<Result<Vec<_>>>()?
             .into_iter()
             .flatten()
@@ -1063,7 +1206,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDraw, ()> for GfxWorldDrawRaw<'a> {
         let vld = self
             .vld
             .xfile_deserialize_into(de, self.vertex_layer_data_size)?;
-        let indices = self.indices.to_vec(de)?;
+        let indices = self.indices.to_vec_bulk(de)?;
 
         Ok(GfxWorldDraw {
             reflection_probes,
@@ -1084,7 +1227,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDraw, ()> for GfxWorldDrawRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxReflectionProbeRaw<'a> {
     pub origin: [f32; 3],
     pub image: Ptr32<'a, GfxImageRaw<'a>>,
@@ -1093,7 +1236,8 @@ pub(crate) struct GfxReflectionProbeRaw<'a> {
 assert_size!(GfxReflectionProbeRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxReflectionProbe {
     pub origin: Vec3,
     pub image: Option<Box<GfxImage>>,
@@ -1119,14 +1263,15 @@ impl<'a> XFileDeserializeInto<GfxReflectionProbe, ()> for GfxReflectionProbeRaw<
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxReflectionProbeVolumeDataRaw {
     pub volume_planes: [[f32; 4]; 6],
 }
 assert_size!(GfxReflectionProbeVolumeDataRaw, 96);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxReflectionProbeVolumeData {
     pub volume_planes: [Vec4; 6],
 }
@@ -1147,7 +1292,7 @@ impl From<GfxReflectionProbeVolumeDataRaw> for GfxReflectionProbeVolumeData {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightmapArrayRaw<'a> {
     pub primary: Ptr32<'a, GfxImageRaw<'a>>,
     pub secondary: Ptr32<'a, GfxImageRaw<'a>>,
@@ -1156,7 +1301,8 @@ pub(crate) struct GfxLightmapArrayRaw<'a> {
 assert_size!(GfxLightmapArrayRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightmapArray {
     pub primary: Option<Box<GfxImage>>,
     pub secondary: Option<Box<GfxImage>>,
@@ -1182,7 +1328,7 @@ impl<'a> XFileDeserializeInto<GfxLightmapArray, ()> for GfxLightmapArrayRaw<'a>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldVertexDataRaw<'a> {
     pub vertices: Ptr32<'a, GfxWorldVertexRaw>,
     #[cfg_attr(not(feature = "d3d9"), allow(dead_code))]
@@ -1191,7 +1337,8 @@ pub(crate) struct GfxWorldVertexDataRaw<'a> {
 assert_size!(GfxWorldVertexDataRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldVertexData {
     pub vertices: Vec<GfxWorldVertex>,
     pub world_vb: Option<Box<GfxVertexBuffer>>,
@@ -1253,28 +1400,30 @@ impl<'a> XFileDeserializeInto<GfxWorldVertexData, u32> for GfxWorldVertexDataRaw
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldVertexRaw {
     pub xyz: [f32; 3],
     pub binormal_sign: f32,
     pub color: GfxColor,
     pub tex_coord: [f32; 2],
     pub lmap_coord: [f32; 2],
-    pub normal: [u8; 4],
-    pub tangent: [u8; 4],
+    pub normal: UnitVec,
+    pub tangent: UnitVec,
 }
 assert_size!(GfxWorldVertexRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldVertex {
     pub xyz: Vec3,
     pub binormal_sign: f32,
+    /// In the platform's byte order, see [`GfxColor`].
     pub color: GfxColor,
     pub tex_coord: Vec2,
     pub lmap_coord: Vec2,
-    pub normal: [u8; 4],
-    pub tangent: [u8; 4],
+    pub normal: UnitVec,
+    pub tangent: UnitVec,
 }
 
 impl From<GfxWorldVertexRaw> for GfxWorldVertex {
@@ -1295,8 +1444,51 @@ impl From<GfxWorldVertexRaw> for GfxWorldVertex {
     }
 }
 
+impl GfxWorldVertex {
+    /// Packs a vertex from plain floats. The tangent is left zeroed, see
+    /// [`Self::with_tangent`]. `color` should already be in the platform's
+    /// byte order (see [`GfxColor::from_rgba`]).
+    pub fn pack(position: Vec3, normal: Vec3, uv: Vec2, lmap_coord: Vec2, color: GfxColor) -> Self {
+        Self {
+            xyz: position,
+            binormal_sign: 1.0,
+            color,
+            tex_coord: uv,
+            lmap_coord,
+            normal: UnitVec::encode(normal),
+            tangent: UnitVec::encode(Vec3::default()),
+        }
+    }
+
+    /// `binormal_sign` should be `1.0` or `-1.0`, depending on the
+    /// handedness of the tangent space.
+    pub fn with_tangent(self, tangent: Vec3, binormal_sign: f32) -> Self {
+        Self {
+            tangent: UnitVec::encode(tangent),
+            binormal_sign,
+            ..self
+        }
+    }
+
+    /// The reverse of [`Self::pack`]. Returns the position, normal, texture
+    /// coordinates, lightmap coordinates, and color.
+    pub fn unpack(&self) -> (Vec3, Vec3, Vec2, Vec2, GfxColor) {
+        (
+            self.xyz,
+            self.normal.decode(),
+            self.tex_coord,
+            self.lmap_coord,
+            self.color,
+        )
+    }
+
+    pub fn tangent(&self) -> Vec3 {
+        self.tangent.decode()
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldVertexLayerDataRaw<'a> {
     pub data: Ptr32<'a, u8>,
     #[cfg_attr(not(feature = "d3d9"), allow(dead_code))]
@@ -1305,7 +1497,8 @@ pub(crate) struct GfxWorldVertexLayerDataRaw<'a> {
 assert_size!(GfxWorldVertexLayerDataRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxWorldVertexLayerData {
     pub data: Vec<u8>,
     pub layer_vb: Option<Box<GfxVertexBuffer>>,
@@ -1317,7 +1510,10 @@ impl<'a> XFileDeserializeInto<GfxWorldVertexLayerData, u32> for GfxWorldVertexLa
         de: &mut impl T5XFileDeserialize,
         vertex_layer_data_size: u32,
     ) -> Result<GfxWorldVertexLayerData> {
-        let data = self.data.to_array(vertex_layer_data_size as _).to_vec(de)?;
+        let data = self
+            .data
+            .to_array(vertex_layer_data_size as _)
+            .to_vec_bulk(de)?;
 
         Ok(GfxWorldVertexLayerData {
             data,
@@ -1327,7 +1523,7 @@ impl<'a> XFileDeserializeInto<GfxWorldVertexLayerData, u32> for GfxWorldVertexLa
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightGridRaw<'a> {
     pub has_light_regions: bool,
     #[allow(dead_code)]
@@ -1345,7 +1541,8 @@ pub(crate) struct GfxLightGridRaw<'a> {
 assert_size!(GfxLightGridRaw, 56);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightGrid {
     pub has_light_regions: bool,
     pub sun_primary_light_index: usize,
@@ -1372,8 +1569,8 @@ impl<'a> XFileDeserializeInto<GfxLightGrid, ()> for GfxLightGridRaw<'a> {
                     - self.mins[self.row_axis as usize] as usize
                     + 1,
             )
-            .to_vec(de)?;
-        let raw_row_data = self.raw_row_data.to_vec(de)?;
+            .to_vec_bulk(de)?;
+        let raw_row_data = self.raw_row_data.to_vec_bulk(de)?;
         let entries = self.entries.to_vec(de)?;
         let colors = self.colors.to_vec(de)?;
 
@@ -1392,8 +1589,182 @@ impl<'a> XFileDeserializeInto<GfxLightGrid, ()> for GfxLightGridRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+/// A linear color, with each channel in `0.0..=1.0`.
+pub type Rgb = [f32; 3];
+
+/// Number of directional color samples in each
+/// [`GfxCompressedLightGridColors`].
+pub const LIGHT_GRID_SAMPLE_COUNT: usize = 56;
+
+/// World units between light grid points along X and Y.
+pub const LIGHT_GRID_SPACING_XY: f32 = 32.0;
+/// World units between light grid points along Z.
+pub const LIGHT_GRID_SPACING_Z: f32 = 64.0;
+/// Added to world coordinates before dividing by the spacing, so that grid
+/// coordinates are never negative.
+const LIGHT_GRID_BIAS: f32 = 131072.0;
+
+/// The header of one row of [`GfxLightGrid::raw_row_data`], followed by a
+/// `(z_start, z_count)` pair of bytes per column.
+struct GfxLightGridRow<'a> {
+    col_start: u32,
+    col_count: u32,
+    z_start: u32,
+    first_entry: usize,
+    /// One column's pair of bytes per element, still swapped along with the
+    /// [`u16`] they were read as.
+    lookup_table: &'a [u16],
+    little_endian: bool,
+}
+
+impl GfxLightGridRow<'_> {
+    /// Returns `(z_start, z_count)` for column `col`.
+    fn column(&self, col: usize) -> (u32, u32) {
+        let [a, b] = if self.little_endian {
+            self.lookup_table[col].to_le_bytes()
+        } else {
+            self.lookup_table[col].to_be_bytes()
+        };
+        (a as _, b as _)
+    }
+}
+
+impl GfxLightGrid {
+    /// Returns the grid point containing `position`, i.e. the one at its
+    /// lower corner, in grid coordinates (the same space as [`Self::mins`]
+    /// and [`Self::maxs`]).
+    pub fn grid_point(position: Vec3) -> [u32; 3] {
+        let [x, y, z] = position.get();
+        [
+            ((x + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_XY).max(0.0) as u32,
+            ((y + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_XY).max(0.0) as u32,
+            ((z + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_Z).max(0.0) as u32,
+        ]
+    }
+
+    /// Reads the row at `row` out of [`Self::raw_row_data`]. The row data is
+    /// really a byte stream, so anything that isn't a [`u16`] has to be put
+    /// back together in the file's byte order.
+    fn row(&self, row: usize, platform: XFilePlatform) -> Option<GfxLightGridRow<'_>> {
+        let start = *self.row_data_start.get(row)? as usize * 2;
+        let words = self.raw_row_data.get(start..start + 6)?;
+        let first_entry = if platform.is_le() {
+            words[4] as u32 | (words[5] as u32) << 16
+        } else {
+            (words[4] as u32) << 16 | words[5] as u32
+        };
+
+        let col_count = words[1] as usize;
+        let lookup_table = self.raw_row_data.get(start + 6..start + 6 + col_count)?;
+
+        Some(GfxLightGridRow {
+            col_start: words[0] as _,
+            col_count: col_count as _,
+            z_start: words[2] as _,
+            first_entry: first_entry as _,
+            lookup_table,
+            little_endian: platform.is_le(),
+        })
+    }
+
+    /// Returns the index into [`Self::entries`] of the grid point at
+    /// `point` (see [`Self::grid_point`]), or [`None`] if the grid has no
+    /// sample there.
+    ///
+    /// The grid is stored as rows along [`Self::row_axis`], each of which
+    /// covers a range of columns along [`Self::col_axis`]. Each column in
+    /// turn covers a range along Z, and its entries follow the previous
+    /// column's.
+    pub fn entry_index(&self, point: [u32; 3], platform: XFilePlatform) -> Option<usize> {
+        let (row_axis, col_axis) = (self.row_axis as usize, self.col_axis as usize);
+        let row = point
+            .get(row_axis)?
+            .checked_sub(*self.mins.get(row_axis)? as u32)?;
+        let row = self.row(row as usize, platform)?;
+
+        let col = point[col_axis].checked_sub(row.col_start)?;
+        if col >= row.col_count {
+            return None;
+        }
+
+        let skipped = (0..col as usize)
+            .map(|c| row.column(c).1 as usize)
+            .sum::<usize>();
+        let (z_start, z_count) = row.column(col as usize);
+        let z = point[2].checked_sub(row.z_start + z_start)?;
+        if z >= z_count {
+            return None;
+        }
+
+        let index = row.first_entry + skipped + z as usize;
+        (index < self.entries.len()).then_some(index)
+    }
+
+    /// Decompresses the colors of [`Self::entries`]`[index]`.
+    pub fn entry_colors(&self, index: usize) -> Option<[Rgb; LIGHT_GRID_SAMPLE_COUNT]> {
+        let entry = self.entries.get(index)?;
+        let colors = self.colors.get(entry.colors_index as usize)?;
+        Some(colors.rgb.map(|c| c.map(|c| c as f32 / 255.0)))
+    }
+
+    /// Samples the grid at `position`, blending the (up to) 8 grid points
+    /// around it the same way the engine does: trilinearly, ignoring points
+    /// the grid has no sample for.
+    ///
+    /// Returns [`None`] if none of the surrounding points have a sample.
+    pub fn sample(
+        &self,
+        position: Vec3,
+        platform: XFilePlatform,
+    ) -> Option<[Rgb; LIGHT_GRID_SAMPLE_COUNT]> {
+        let base = Self::grid_point(position);
+        let [x, y, z] = position.get();
+        let frac = [
+            ((x + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_XY - base[0] as f32).clamp(0.0, 1.0),
+            ((y + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_XY - base[1] as f32).clamp(0.0, 1.0),
+            ((z + LIGHT_GRID_BIAS) / LIGHT_GRID_SPACING_Z - base[2] as f32).clamp(0.0, 1.0),
+        ];
+
+        let mut total = 0.0;
+        let mut blended = [[0.0f32; 3]; LIGHT_GRID_SAMPLE_COUNT];
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let point = [0, 1, 2].map(|i| base[i] + offset[i] as u32);
+            let weight = (0..3)
+                .map(|i| {
+                    if offset[i] == 1 {
+                        frac[i]
+                    } else {
+                        1.0 - frac[i]
+                    }
+                })
+                .product::<f32>();
+            if weight == 0.0 {
+                continue;
+            }
+
+            let Some(colors) = self
+                .entry_index(point, platform)
+                .and_then(|i| self.entry_colors(i))
+            else {
+                continue;
+            };
+
+            total += weight;
+            for (b, c) in blended.iter_mut().zip(colors) {
+                for (b, c) in b.iter_mut().zip(c) {
+                    *b += c * weight;
+                }
+            }
+        }
+
+        (total > 0.0).then(|| blended.map(|c| c.map(|c| c / total)))
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct GfxLightGridEntry {
     pub colors_index: u16,
     pub primary_light_index: u8,
@@ -1401,16 +1772,17 @@ pub struct GfxLightGridEntry {
 }
 assert_size!(GfxLightGridEntry, 4);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct GfxCompressedLightGridColors {
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub rgb: [[u8; 3]; 56],
 }
 assert_size!(GfxCompressedLightGridColors, 168);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxBrushModelRaw {
     pub writable: GfxBrushModelWritableRaw,
     pub bounds: [[f32; 3]; 2],
@@ -1420,7 +1792,8 @@ pub(crate) struct GfxBrushModelRaw {
 assert_size!(GfxBrushModelRaw, 60);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxBrushModel {
     pub writable: GfxBrushModelWritable,
     pub bounds: [Vec3; 2],
@@ -1443,7 +1816,7 @@ impl From<GfxBrushModelRaw> for GfxBrushModel {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxBrushModelWritableRaw {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -1452,7 +1825,8 @@ pub(crate) struct GfxBrushModelWritableRaw {
 assert_size!(GfxBrushModelWritableRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxBrushModelWritable {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -1470,7 +1844,7 @@ impl From<GfxBrushModelWritableRaw> for GfxBrushModelWritable {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct MaterialMemoryRaw<'a> {
     pub material: Ptr32<'a, MaterialRaw<'a>>,
     pub memory: i32,
@@ -1478,9 +1852,10 @@ pub(crate) struct MaterialMemoryRaw<'a> {
 assert_size!(MaterialMemoryRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MaterialMemory {
-    pub material: Option<Box<Material>>,
+    pub material: Option<Shared<Material>>,
     pub memory: usize,
 }
 
@@ -1500,7 +1875,7 @@ impl<'a> XFileDeserializeInto<MaterialMemory, ()> for MaterialMemoryRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SunflareRaw<'a> {
     pub has_valid_data: bool,
     #[allow(dead_code)]
@@ -1530,11 +1905,12 @@ pub(crate) struct SunflareRaw<'a> {
 assert_size!(SunflareRaw, 96);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sunflare {
     pub has_valid_data: bool,
-    pub sprite_material: Option<Box<Material>>,
-    pub flare_material: Option<Box<Material>>,
+    pub sprite_material: Option<Shared<Material>>,
+    pub flare_material: Option<Shared<Material>>,
     pub sprite_size: f32,
     pub flare_min_size: f32,
     pub flare_min_dot: f32,
@@ -1593,31 +1969,34 @@ impl<'a> XFileDeserializeInto<Sunflare, ()> for SunflareRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct GfxSceneDynModel {
     pub info: XModelDrawInfo,
     pub dyn_ent_id: u16,
 }
 assert_size!(GfxSceneDynModel, 6);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct GfxSceneDynBrush {
     pub info: BModelDrawInfo,
     pub dyn_ent_id: u16,
 }
 assert_size!(GfxSceneDynModel, 6);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct BModelDrawInfo {
     pub surf_id: u16,
 }
 assert_size!(BModelDrawInfo, 2);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxShadowGeometryRaw<'a> {
     pub surface_count: u16,
     pub smodel_count: u16,
@@ -1627,7 +2006,8 @@ pub(crate) struct GfxShadowGeometryRaw<'a> {
 assert_size!(GfxShadowGeometryRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxShadowGeometry {
     pub sorted_surf_index: Vec<u16>,
     pub smodel_index: Vec<u16>,
@@ -1642,11 +2022,11 @@ impl<'a> XFileDeserializeInto<GfxShadowGeometry, ()> for GfxShadowGeometryRaw<'a
         let sorted_surf_index = self
             .sorted_surf_index
             .to_array(self.surface_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let smodel_index = self
             .smodel_index
             .to_array(self.smodel_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
 
         Ok(GfxShadowGeometry {
             sorted_surf_index,
@@ -1656,14 +2036,15 @@ impl<'a> XFileDeserializeInto<GfxShadowGeometry, ()> for GfxShadowGeometryRaw<'a
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightRegionRaw<'a> {
     pub hulls: FatPointerCountFirstU32<'a, GfxLightRegionHullRaw<'a>>,
 }
 assert_size!(GfxLightRegionRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightRegion {
     pub hulls: Vec<GfxLightRegionHull>,
 }
@@ -1681,7 +2062,7 @@ impl<'a> XFileDeserializeInto<GfxLightRegion, ()> for GfxLightRegionRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightRegionHullRaw<'a> {
     pub kdop_mid_point: [[f32; 3]; 3],
     pub kdop_half_size: [[f32; 3]; 3],
@@ -1690,7 +2071,8 @@ pub(crate) struct GfxLightRegionHullRaw<'a> {
 assert_size!(GfxLightRegionHullRaw, 80);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightRegionHull {
     pub kdop_mid_point: Mat3,
     pub kdop_half_size: Mat3,
@@ -1716,7 +2098,7 @@ impl<'a> XFileDeserializeInto<GfxLightRegionHull, ()> for GfxLightRegionHullRaw<
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightRegionAxisRaw {
     pub dir: [f32; 3],
     pub mid_point: f32,
@@ -1725,7 +2107,8 @@ pub(crate) struct GfxLightRegionAxisRaw {
 assert_size!(GfxLightRegionAxisRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightRegionAxis {
     pub dir: Vec3,
     pub mid_point: f32,
@@ -1743,7 +2126,7 @@ impl From<GfxLightRegionAxisRaw> for GfxLightRegionAxis {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldDpvsStaticRaw<'a> {
     pub smodel_count: u32,
     pub dynamic_smodel_count: u32,
@@ -1773,7 +2156,8 @@ pub(crate) struct GfxWorldDpvsStaticRaw<'a> {
 assert_size!(GfxWorldDpvsStaticRaw, 112);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldDpvsStatic {
     pub smodel_count: usize,
     pub dynamic_smodel_count: usize,
@@ -1834,15 +2218,15 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsStatic, (i32, i32)> for GfxWorldDpvsSt
         let smodel_vis_data_camera_saved = self
             .smodel_vis_data_camera_saved
             .to_array(self.smodel_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let surface_vis_data_camera_saved = self
             .surface_vis_data_camera_saved
             .to_array(self.static_surface_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let lod_data = self
             .lod_data
             .to_array(self.smodel_vis_data_count as usize * 2)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         let sorted_surf_index = self
             .sorted_surf_index
             .to_array(self.static_surface_count as _)
@@ -1870,7 +2254,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsStatic, (i32, i32)> for GfxWorldDpvsSt
         let surface_casts_sun_shadow = self
             .surface_casts_sun_shadow
             .to_array(self.surface_vis_data_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
 
         Ok(GfxWorldDpvsStatic {
             smodel_count: self.smodel_count as _,
@@ -1902,7 +2286,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsStatic, (i32, i32)> for GfxWorldDpvsSt
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxStaticModelInstRaw {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -1912,11 +2296,13 @@ pub(crate) struct GfxStaticModelInstRaw {
 assert_size!(GfxStaticModelInstRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxStaticModelInst {
     pub mins: Vec3,
     pub maxs: Vec3,
     pub lighting_origin: Vec3,
+    /// In the platform's byte order, see [`GfxColor`].
     pub ground_lighting: GfxColor,
 }
 
@@ -1932,7 +2318,7 @@ impl From<GfxStaticModelInstRaw> for GfxStaticModelInst {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxSurfaceRaw<'a> {
     pub tris: SrfTrianglesRaw,
     pub material: Ptr32<'a, MaterialRaw<'a>>,
@@ -1945,10 +2331,11 @@ pub(crate) struct GfxSurfaceRaw<'a> {
 assert_size!(GfxSurfaceRaw, 80);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxSurface {
     pub tris: SrfTriangles,
-    pub material: Option<Box<Material>>,
+    pub material: Option<Shared<Material>>,
     pub lightmap_index: usize,
     pub reflection_probe_index: usize,
     pub primary_light_index: usize,
@@ -1979,7 +2366,7 @@ impl<'a> XFileDeserializeInto<GfxSurface, ()> for GfxSurfaceRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SrfTrianglesRaw {
     pub mins: [f32; 3],
     pub vertex_layer_data: i32,
@@ -1994,7 +2381,8 @@ pub(crate) struct SrfTrianglesRaw {
 assert_size!(SrfTrianglesRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SrfTriangles {
     pub mins: Vec3,
     pub vertex_layer_data: i32,
@@ -2024,7 +2412,7 @@ impl From<SrfTrianglesRaw> for SrfTriangles {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxCullGroupRaw {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -2034,7 +2422,8 @@ pub(crate) struct GfxCullGroupRaw {
 assert_size!(GfxCullGroupRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxCullGroup {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -2054,7 +2443,7 @@ impl From<GfxCullGroupRaw> for GfxCullGroup {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxStaticModelDrawInstRaw<'a> {
     pub cull_dist: f32,
     pub placement: GfxPackedPlacementRaw,
@@ -2068,7 +2457,8 @@ pub(crate) struct GfxStaticModelDrawInstRaw<'a> {
 assert_size!(GfxStaticModelDrawInstRaw, 76);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxStaticModelDrawInst {
     pub cull_dist: f32,
     pub placement: GfxPackedPlacement,
@@ -2103,7 +2493,7 @@ impl<'a> XFileDeserializeInto<GfxStaticModelDrawInst, ()> for GfxStaticModelDraw
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxPackedPlacementRaw {
     pub origin: [f32; 3],
     pub axis: [[f32; 3]; 3],
@@ -2112,7 +2502,8 @@ pub(crate) struct GfxPackedPlacementRaw {
 assert_size!(GfxPackedPlacementRaw, 52);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxPackedPlacement {
     pub origin: Vec3,
     pub axis: Mat3,
@@ -2130,7 +2521,7 @@ impl From<GfxPackedPlacementRaw> for GfxPackedPlacement {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldDpvsDynamicRaw<'a> {
     pub dyn_ent_client_word_count: [u32; 2],
     pub dyn_ent_client_count: [u32; 2],
@@ -2140,7 +2531,8 @@ pub(crate) struct GfxWorldDpvsDynamicRaw<'a> {
 assert_size!(GfxWorldDpvsDynamicRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxWorldDpvsDynamic {
     pub dyn_ent_cell_bits: [Vec<u32>; 2],
     pub dyn_ent_vis_data: [[Vec<u8>; 2]; 3],
@@ -2154,10 +2546,16 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsDynamic, i32> for GfxWorldDpvsDynamicR
     ) -> Result<GfxWorldDpvsDynamic> {
         let dyn_ent_cell_bits = [
             self.dyn_ent_cell_bits[0]
-                .to_array(self.dyn_ent_client_word_count[0] as usize * cell_count as usize)
+                .to_array(
+                    (self.dyn_ent_client_word_count[0] as usize)
+                        .saturating_mul(cell_count as usize),
+                )
                 .to_vec(de)?,
             self.dyn_ent_cell_bits[1]
-                .to_array(self.dyn_ent_client_word_count[1] as usize * cell_count as usize)
+                .to_array(
+                    (self.dyn_ent_client_word_count[1] as usize)
+                        .saturating_mul(cell_count as usize),
+                )
                 .to_vec(de)?,
         ];
 
@@ -2196,7 +2594,7 @@ impl<'a> XFileDeserializeInto<GfxWorldDpvsDynamic, i32> for GfxWorldDpvsDynamicR
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWorldLodChainRaw {
     pub origin: [f32; 3],
     pub last_dist: f32,
@@ -2206,7 +2604,8 @@ pub(crate) struct GfxWorldLodChainRaw {
 assert_size!(GfxWorldLodChainRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWorldLodChain {
     pub origin: Vec3,
     pub last_dist: f32,
@@ -2225,8 +2624,9 @@ impl From<GfxWorldLodChainRaw> for GfxWorldLodChain {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct GfxWorldLodInfo {
     pub dist: f32,
     pub first_surf: u32,
@@ -2234,14 +2634,15 @@ pub struct GfxWorldLodInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxWaterBufferRaw<'a> {
     pub buffer: FatPointerCountFirstU32<'a, [f32; 4]>,
 }
 assert_size!(GfxWaterBufferRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxWaterBuffer {
     pub buffer: Vec<Vec4>,
 }
@@ -2258,16 +2659,17 @@ impl<'a> XFileDeserializeInto<GfxWaterBuffer, ()> for GfxWaterBufferRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct OccluderRaw {
     pub flags: u32,
-    pub name: GfxName16,
+    pub name: FixedString<16>,
     pub points: [[f32; 3]; 4],
 }
 assert_size!(OccluderRaw, 68);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Occluder {
     pub flags: u32,
     pub name: XString,
@@ -2290,14 +2692,15 @@ impl From<OccluderRaw> for Occluder {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxOutdoorBoundsRaw {
     pub bounds: [[f32; 3]; 2],
 }
 assert_size!(GfxOutdoorBoundsRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxOutdoorBounds {
     pub bounds: [Vec3; 2],
 }
@@ -2311,7 +2714,7 @@ impl From<GfxOutdoorBoundsRaw> for GfxOutdoorBounds {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxHeroLightRaw {
     pub type_: u8,
     #[allow(dead_code)]
@@ -2327,7 +2730,8 @@ pub(crate) struct GfxHeroLightRaw {
 assert_size!(GfxHeroLightRaw, 56);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxHeroLight {
     pub type_: u8,
     pub color: Vec3,
@@ -2355,7 +2759,7 @@ impl From<GfxHeroLightRaw> for GfxHeroLight {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxHeroLightTreeRaw {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -2363,7 +2767,8 @@ pub(crate) struct GfxHeroLightTreeRaw {
 assert_size!(GfxHeroLightTreeRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxHeroLightTree {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -1,15 +1,18 @@
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 use crate::{
-    FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize,
-    T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size,
+    FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, Shared, T5XFileDeserialize,
+    XArray, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     common::{Mat3, Vec2, Vec3},
     fx::{FxEffectDef, FxEffectDefRaw},
     techset::{Material, MaterialRaw},
 };
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GlassesRaw<'a> {
     name: XStringRaw<'a>,
     glasses: FatPointerCountFirstU32<'a, GlassRaw<'a>>,
@@ -27,7 +30,8 @@ pub(crate) struct GlassesRaw<'a> {
 assert_size!(GlassesRaw, 56);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Glasses {
     pub name: XString,
     pub glasses: Vec<Glass>,
@@ -51,7 +55,7 @@ impl<'a> XFileDeserializeInto<Glasses, ()> for GlassesRaw<'a> {
     ) -> Result<Glasses> {
         let name = self.name.xfile_deserialize_into(de, ())?;
         let glasses = self.glasses.xfile_deserialize_into(de, ())?;
-        let work_memory = self.work_memory.to_vec(de)?;
+        let work_memory = self.work_memory.to_vec_bulk(de)?;
 
         Ok(Glasses {
             name,
@@ -70,6 +74,7 @@ impl<'a> XFileDeserializeInto<Glasses, ()> for GlassesRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Glasses {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -99,7 +104,7 @@ impl XFileSerialize<()> for Glasses {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GlassRaw<'a> {
     pub glass_def: Ptr32<'a, GlassDefRaw<'a>>,
     pub index: u32,
@@ -119,7 +124,8 @@ pub(crate) struct GlassRaw<'a> {
 assert_size!(GlassRaw, 124);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Glass {
     pub glass_def: Option<Box<GlassDef>>,
     pub index: u32,
@@ -169,6 +175,7 @@ impl<'a> XFileDeserializeInto<Glass, ()> for GlassRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Glass {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let glass_def = Ptr32::from_box(&self.glass_def);
@@ -205,7 +212,7 @@ impl XFileSerialize<()> for Glass {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GlassDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub max_health: i32,
@@ -226,7 +233,8 @@ pub(crate) struct GlassDefRaw<'a> {
 assert_size!(GlassDefRaw, 60);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GlassDef {
     pub name: XString,
     pub max_health: i32,
@@ -235,9 +243,9 @@ pub struct GlassDef {
     pub max_shard_size: f32,
     pub shard_life_probability: f32,
     pub max_shards: i32,
-    pub pristine_material: Option<Box<Material>>,
-    pub cracked_material: Option<Box<Material>>,
-    pub shard_material: Option<Box<Material>>,
+    pub pristine_material: Option<Shared<Material>>,
+    pub cracked_material: Option<Shared<Material>>,
+    pub shard_material: Option<Shared<Material>>,
     pub crack_sound: XString,
     pub shatter_sound: XString,
     pub auto_shatter_sound: XString,
@@ -281,6 +289,7 @@ impl<'a> XFileDeserializeInto<GlassDef, ()> for GlassDefRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GlassDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
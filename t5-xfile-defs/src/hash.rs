@@ -0,0 +1,67 @@
+//! Stable, content-based hashing of deserialized assets.
+//!
+//! The hash is computed over a canonical serialization of the owned asset
+//! types (fixed-width little-endian `bincode`, regardless of the platform the
+//! asset was deserialized for), so two assets hash to the same value if and
+//! only if their contents are identical. This makes it cheap to detect
+//! duplicate assets across fastfiles (e.g., the copies of the same material
+//! that appear in both `common.ff` and a map zone).
+//!
+//! The value is stable across runs and machines, but not necessarily across
+//! versions of this library, since any change to the owned types changes
+//! their serialization.
+//!
+//! Rather than each asset type having its own `hash()` method, [`StableHash`]
+//! is implemented once for everything that's [`Serialize`], so it also covers
+//! single fields of an asset and collections like
+//! [`XAssetBag`](crate::xasset::XAssetBag) (whose hash depends on the order
+//! of its assets).
+
+use std::io::Write;
+
+use bincode::Options;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::{Error, ErrorKind, Result, file_line_col};
+
+/// Adapter that feeds everything written to it straight into the hasher, so
+/// large assets (e.g. [`GfxWorld`](crate::gfx_world::GfxWorld)) don't need to
+/// be serialized into an intermediate buffer first.
+struct HashWriter<'a>(&'a mut Xxh3);
+
+impl<'a> Write for HashWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Trait for computing a stable 64-bit content hash of an asset.
+///
+/// Implemented for every type that implements [`Serialize`], which includes
+/// all of the owned asset types, [`XAsset`](crate::xasset::XAsset), and
+/// collections of them.
+pub trait StableHash {
+    /// Returns the XXH3 hash of `self`'s canonical serialization.
+    fn stable_hash(&self) -> Result<u64>;
+}
+
+impl<T: Serialize + ?Sized> StableHash for T {
+    fn stable_hash(&self) -> Result<u64> {
+        let mut hasher = Xxh3::new();
+
+        bincode::DefaultOptions::new()
+            .with_little_endian()
+            .with_fixint_encoding()
+            .serialize_into(HashWriter(&mut hasher), self)
+            .map_err(|e| Error::new(file_line_col!(), ErrorKind::Bincode(e)))?;
+
+        Ok(hasher.digest())
+    }
+}
@@ -63,7 +63,8 @@
 // array, then an asset array. And herein comes the first major annoyance
 // with XFiles - the assets are essentially just the structs used by the engine
 // serialzed into a file. Pointers in said structs are either set to 0xFFFFFFFF
-// or 0xFFFFFFFE (unsure of the difference between the two), which indicates
+// or 0xFFFFFFFE (unsure of the difference between the two, see
+// [`PtrMarker`]), which indicates
 // that the data for said pointers comes after the current struct and any
 // previous 0xFFFFFFFF-pointers in said struct, NULL, or to a "real" value,
 // which is used by T5 as a pointer into a buffer allocated by the XFile
@@ -96,52 +97,106 @@
 #![allow(clippy::needless_lifetimes)]
 
 extern crate alloc;
+// so that `#[derive(XFileRead)]` works inside this crate too
+extern crate self as t5_xfile_defs;
+
+#[cfg(all(feature = "arbitrary", feature = "d3d9"))]
+compile_error!("the `arbitrary` and `d3d9` features are mutually exclusive");
 
 #[cfg(feature = "std")]
 extern crate std;
 
+// re-exported so that the versions the `common` conversions are for can be
+// named
+#[cfg(feature = "glam")]
+pub use glam;
+#[cfg(feature = "nalgebra")]
+pub use nalgebra;
+
+pub mod access;
+#[cfg(feature = "models")]
+pub mod animation;
+pub mod blocks;
+#[cfg(feature = "world")]
 pub mod clipmap;
+#[cfg(feature = "models")]
+pub mod collision_tree;
+#[cfg(feature = "world")]
 pub mod com_world;
 pub mod common;
+mod csv;
 pub mod ddl;
+#[cfg(feature = "world")]
 pub mod destructible;
 pub mod emblem;
+pub mod encoding;
+pub mod entities;
+#[cfg(feature = "ui")]
 pub mod font;
+#[cfg(feature = "fx")]
 pub mod fx;
+#[cfg(feature = "world")]
 pub mod gameworld;
+#[cfg(feature = "world")]
 pub mod gfx_world;
+#[cfg(feature = "world")]
 pub mod glass;
+#[cfg(feature = "hash")]
+pub mod hash;
+#[cfg(feature = "world")]
 pub mod light;
+pub mod localize;
+#[cfg(feature = "ui")]
 pub mod menu;
 pub mod misc;
 mod prelude;
+pub mod read;
+pub mod rename;
+pub mod render_state;
+#[cfg(feature = "models")]
+pub mod retarget;
+pub mod script;
+#[cfg(feature = "models")]
+pub mod skeleton;
+#[cfg(feature = "models")]
+pub mod skinning;
+pub mod skip;
+#[cfg(feature = "audio")]
 pub mod sound;
+pub mod stats;
 pub mod techset;
+pub mod texture;
 pub mod util;
+pub mod validate;
+pub mod visitor;
+#[cfg(feature = "fx")]
 pub mod weapon;
+#[cfg(feature = "models")]
 pub mod xanim;
 pub mod xasset;
+#[cfg(feature = "models")]
 pub mod xmodel;
 
 use alloc::{
     fmt::{Debug, Display},
-    string::String,
+    string::{String, ToString},
+    vec::Vec,
 };
-
-use serde::{Deserialize, de::DeserializeOwned};
+use core::str::FromStr;
 
 #[cfg(feature = "serde")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "d3d9")]
 use windows::Win32::Graphics::Direct3D9::IDirect3DDevice9;
 
 pub use misc::*;
+pub use read::{XFileRead, XFileReader};
 pub use util::*;
 use xasset::XAssetType;
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct XFileHeader {
     pub magic: [u8; 8],
     pub version: u32,
@@ -150,20 +205,32 @@ assert_size!(XFileHeader, 12);
 
 pub const XFILE_HEADER_MAGIC_U: &str = "IWffu100";
 pub const XFILE_HEADER_MAGIC_0: &str = "IWff0100";
+pub const XFILE_HEADER_MAGIC_A: &str = "IWffa100";
 pub const XFILE_HEADER_MAGIC_U_RAW: [u8; 8] = *b"IWffu100";
 pub const XFILE_HEADER_MAGIC_0_RAW: [u8; 8] = *b"IWff0100";
+/// Same as [`XFILE_HEADER_MAGIC_U_RAW`], but the payload is a raw DEFLATE
+/// stream instead of a zlib one.
+pub const XFILE_HEADER_MAGIC_A_RAW: [u8; 8] = *b"IWffa100";
 
 impl XFileHeader {
-    pub const fn new(platform: XFilePlatform) -> Self {
+    /// Returns [`None`] if `platform`'s version isn't known (see
+    /// [`XFileVersion::from_platform`]).
+    pub const fn new(platform: XFilePlatform) -> Option<Self> {
         let magic = if platform.is_console() {
             XFILE_HEADER_MAGIC_0_RAW
         } else {
             XFILE_HEADER_MAGIC_U_RAW
         };
 
-        let version = XFileVersion::from_platform(platform).as_u32();
+        if XFileVersion::from_platform(platform).is_none() {
+            return None;
+        }
 
-        Self { magic, version }
+        // stored in the platform's byte order like everything else
+        Some(Self {
+            magic,
+            version: XFILE_VERSION,
+        })
     }
 
     pub fn magic_string(&self) -> String {
@@ -171,30 +238,95 @@ impl XFileHeader {
     }
 
     pub const fn magic_is_valid(&self) -> bool {
+        self.magic_is_valid_for_any_compression()
+            && matches!(self.compression(), b'u' | b'0' | b'a')
+    }
+
+    /// Same as [`Self::magic_is_valid`], but without checking the
+    /// compression byte (see [`Self::compression`]), for payloads whose
+    /// codec is known some other way.
+    pub const fn magic_is_valid_for_any_compression(&self) -> bool {
         // won't work in a const fn
         // self.magic == XFILE_HEADER_MAGIC_U_RAW || self.magic == XFILE_HEADER_MAGIC_0_RAW
         self.magic[0] == b'I'
             && self.magic[1] == b'W'
             && self.magic[2] == b'f'
             && self.magic[3] == b'f'
-            && (self.magic[4] == b'u' || self.magic[4] == b'0')
             && self.magic[5] == b'1'
             && self.magic[6] == b'0'
             && self.magic[7] == b'0'
     }
+
+    /// The byte of the magic that says how the payload is compressed: `u`
+    /// and `0` for zlib, and `a` for raw DEFLATE.
+    pub const fn compression(&self) -> u8 {
+        self.magic[4]
+    }
+
+    /// Whether the magic is that of a signed or encrypted Fastfile, which
+    /// can't be read (see [`XFileProtection`]).
+    pub fn protection(&self) -> Option<XFileProtection> {
+        if self.magic.starts_with(XFILE_HEADER_MAGIC_ENCRYPTED_PREFIX) {
+            Some(XFileProtection::Encrypted)
+        } else if self.magic == XFILE_HEADER_MAGIC_SIGNED_RAW {
+            Some(XFileProtection::Signed)
+        } else {
+            None
+        }
+    }
+}
+
+/// The magic of Fastfiles whose payload is preceded by an RSA signature.
+pub const XFILE_HEADER_MAGIC_SIGNED_RAW: [u8; 8] = *b"IWffs100";
+/// The start of the magic of T6 and later Fastfiles, whose payloads are
+/// encrypted.
+pub const XFILE_HEADER_MAGIC_ENCRYPTED_PREFIX: &[u8] = b"TAff";
+/// The start of the authentication header of signed payloads, when it follows
+/// an otherwise valid header.
+pub const XFILE_SIGNED_PAYLOAD_MAGIC_RAW: [u8; 8] = *b"PHEEBs71";
+
+/// Why a Fastfile can't be read, even though it looks like one (see
+/// [`ErrorKind::ProtectedFile`]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum XFileProtection {
+    /// The payload is signed, and the signature comes before the compressed
+    /// data.
+    Signed,
+    /// The payload is encrypted.
+    Encrypted,
+}
+
+impl XFileProtection {
+    /// Detects a signed payload from its first bytes (the ones right after
+    /// the [`XFileHeader`]).
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        payload
+            .starts_with(&XFILE_SIGNED_PAYLOAD_MAGIC_RAW)
+            .then_some(Self::Signed)
+    }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+impl Display for XFileProtection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Signed => write!(f, "signed"),
+            Self::Encrypted => write!(f, "encrypted"),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct XFile {
+    /// The size of the payload following this struct.
     pub size: u32,
     pub external_size: u32,
     pub block_size: [u32; 7],
 }
 assert_size!(XFile, 36);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct ScriptString(pub u16);
 
 impl ScriptString {
@@ -214,7 +346,8 @@ impl ScriptString {
     }
 }
 
-const XFILE_VERSION: u32 = 0x000001D9u32;
+/// The version every T5 Fastfile's header has, in the platform's byte order.
+pub const XFILE_VERSION: u32 = 0x000001D9u32;
 const XFILE_VERSION_LE: u32 = XFILE_VERSION.to_le();
 const XFILE_VERSION_BE: u32 = XFILE_VERSION.to_be();
 
@@ -225,14 +358,10 @@ pub enum XFileVersion {
 }
 
 impl XFileVersion {
+    /// `version` is as read from the header, in `platform`'s byte order,
+    /// so it's [`XFILE_VERSION`] for every platform whose version is known.
     pub const fn is_valid(version: u32, platform: XFilePlatform) -> bool {
-        let version = if let Some(v) = Self::from_u32(version) {
-            v.as_u32()
-        } else {
-            return false;
-        };
-
-        version == Self::from_platform(platform).as_u32()
+        Self::from_platform(platform).is_some() && version == XFILE_VERSION
     }
 
     pub const fn is_other_endian(version: u32) -> bool {
@@ -247,12 +376,13 @@ impl XFileVersion {
         }
     }
 
-    pub const fn from_platform(platform: XFilePlatform) -> Self {
+    /// Returns [`None`] for Wii, whose Fastfiles haven't been looked at
+    /// yet.
+    pub const fn from_platform(platform: XFilePlatform) -> Option<Self> {
         match platform {
-            XFilePlatform::Windows | XFilePlatform::macOS => XFileVersion::LE,
-            XFilePlatform::Xbox360 | XFilePlatform::PS3 => XFileVersion::BE,
-            XFilePlatform::Wii => unreachable!(), // safe since the deserializer rejects Wii
-                                                  // before this function ever gets called
+            XFilePlatform::Windows | XFilePlatform::macOS => Some(XFileVersion::LE),
+            XFilePlatform::Xbox360 | XFilePlatform::PS3 => Some(XFileVersion::BE),
+            XFilePlatform::Wii => None,
         }
     }
 
@@ -286,13 +416,62 @@ impl Display for XFilePlatform {
     }
 }
 
+impl FromStr for XFilePlatform {
+    type Err = Error;
+
+    /// Accepts [`Self::name`] as well as what [`Display`] prints, ignoring
+    /// case and whitespace.
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .flat_map(char::to_lowercase);
+        Self::ALL
+            .into_iter()
+            .find(|p| normalized.clone().eq(p.name().chars()))
+            .ok_or_else(|| Error::new(file_line_col!(), ErrorKind::UnknownPlatform(s.to_string())))
+    }
+}
+
+#[cfg(feature = "clap")]
+impl clap::ValueEnum for XFilePlatform {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Self::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()))
+    }
+}
+
 impl XFilePlatform {
+    pub const ALL: [Self; 5] = [
+        Self::Windows,
+        Self::macOS,
+        Self::Xbox360,
+        Self::PS3,
+        Self::Wii,
+    ];
+
+    /// The platform's name in lowercase and without spaces, as
+    /// [`FromStr`] and the CLI take it.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Windows => "windows",
+            Self::macOS => "macos",
+            Self::Xbox360 => "xbox360",
+            Self::PS3 => "ps3",
+            Self::Wii => "wii",
+        }
+    }
+
+    /// Whether the platform's Fastfiles are little-endian. Wii is a
+    /// PowerPC console like the Xbox 360 and PS3, so it's presumed to be
+    /// big-endian.
     pub const fn is_le(&self) -> bool {
         match self {
             Self::Windows | Self::macOS => true,
-            Self::Xbox360 | Self::PS3 => false,
-            Self::Wii => unreachable!(), // safe since the deserializer rejects Wii
-                                         // before this function ever gets called
+            Self::Xbox360 | Self::PS3 | Self::Wii => false,
         }
     }
 
@@ -310,8 +489,67 @@ impl XFilePlatform {
     pub const fn is_pc(&self) -> bool {
         !self.is_console()
     }
+
+    /// The number of local clients the platform's Fastfiles have room for.
+    /// Consoles support split-screen, so theirs have room for 4, where PC
+    /// Fastfiles only have room for 1.
+    ///
+    /// This is only the default, see
+    /// [`XAsset::try_get_with_max_local_clients`](xasset::XAsset::try_get_with_max_local_clients).
+    pub const fn max_local_clients(&self) -> usize {
+        if self.is_console() { 4 } else { 1 }
+    }
+}
+
+/// The memory blocks the engine loads a Fastfile into. Everything in the
+/// payload goes into one of them, and [`XFile::block_size`] says how large
+/// each one is (indexed by [`XFileBlock::index`]).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum XFileBlock {
+    Temp = 0,
+    Runtime = 1,
+    LargeRuntime = 2,
+    PhysicalRuntime = 3,
+    #[default]
+    Virtual = 4,
+    Large = 5,
+    Physical = 6,
+}
+
+impl XFileBlock {
+    pub const COUNT: usize = 7;
+
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::Temp,
+        Self::Runtime,
+        Self::LargeRuntime,
+        Self::PhysicalRuntime,
+        Self::Virtual,
+        Self::Large,
+        Self::Physical,
+    ];
+
+    pub const fn index(self) -> usize {
+        self as _
+    }
+
+    /// The runtime blocks are only allocated, never loaded into, so nothing
+    /// in the payload ends up in them.
+    pub const fn is_runtime(self) -> bool {
+        matches!(
+            self,
+            Self::Runtime | Self::LargeRuntime | Self::PhysicalRuntime
+        )
+    }
 }
 
+/// A real offset (see [`Ptr32::is_real`]), i.e. an offset into one of the
+/// [`XFileBlock`]s, plus one so that offset 0 into [`XFileBlock::Temp`]
+/// isn't null.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct XFileOffset(u32);
 
 impl XFileOffset {
@@ -319,10 +557,23 @@ impl XFileOffset {
         Self(offset)
     }
 
+    pub const fn new(block: XFileBlock, offset: u32) -> Self {
+        Self((((block as u32) << 29) | (offset & 0x1FFFFFFF)) + 1)
+    }
+
+    pub const fn as_u32(&self) -> u32 {
+        self.0
+    }
+
     pub const fn block(&self) -> u8 {
         (((self.0 - 1) >> 29) & 0x00000007) as _
     }
 
+    /// Same as [`Self::block`], but as an [`XFileBlock`].
+    pub const fn xfile_block(&self) -> XFileBlock {
+        XFileBlock::ALL[self.block() as usize]
+    }
+
     pub const fn offset(&self) -> u32 {
         (self.0 - 1) & 0x1FFFFFFF
     }
@@ -352,12 +603,22 @@ pub enum ErrorKind {
     /// Likely indicates the file is corrupt or some deserialization logic is
     /// wrong.
     BrokenInvariant(String),
+    /// Occurs when an asset fails validation (see, e.g.,
+    /// [`xmodel::PhysConstraints::validate`]). Unlike
+    /// [`ErrorKind::BrokenInvariant`], contains every violation found rather
+    /// than just the first.
+    Validation(Vec<String>),
     /// Occurs when attempting to seek to an offset beyond the bounds of a
     /// file.
     InvalidSeek { off: u32, max: u32 },
     /// Occurs when an XFile's `magic` field is invalid.
     /// Likely indicates the file is corrupt or isn't an XFile.
     BadHeaderMagic(String),
+    /// Occurs when an XFile is signed or encrypted, e.g. because it's from a
+    /// later title or a protected build. Such files have to be stripped or
+    /// decrypted with a tool for the game they're from before they can be
+    /// read.
+    ProtectedFile(XFileProtection),
     /// Occurs when an XFile's version doesn't match the expected version
     /// ([`XFILE_VERSION`]).
     WrongVersion(u32),
@@ -368,12 +629,58 @@ pub enum ErrorKind {
     /// Occurs when an XFile's platform is unsupported
     /// (all platforms except Windows).
     UnsupportedPlatform(XFilePlatform),
+    /// Occurs when a string doesn't name a platform (see
+    /// [`XFilePlatform::from_str`]).
+    UnknownPlatform(String),
+    /// Occurs when a cache file is corrupt, was written by an incompatible
+    /// version of this library, or was created for a different platform.
+    BadCacheFile(String),
+    /// Occurs when a deserializer snapshot is corrupt or wasn't taken from
+    /// the Fastfile it's being resumed with.
+    BadSnapshot(String),
+    /// Occurs when an asset dump is corrupt or was written in a format
+    /// version this library can't read.
+    BadDump(String),
+    /// Occurs when an asset can't be renamed (see
+    /// [`xasset::XAssetBag::rename_asset`]), e.g. because another asset
+    /// already has the new name.
+    BadRename(String),
+    /// Occurs when a model's materials can't be replaced (see
+    /// [`retarget::MaterialRetarget::retarget`]), e.g. because there's no
+    /// material with the new name.
+    #[cfg(feature = "models")]
+    BadRetarget(String),
+    /// Occurs when an image pack (`.ipak` or `.xpak` file) is corrupt or
+    /// isn't an image pack.
+    BadImagePack(String),
+    /// Occurs when a sound bank (`.sabs` or `.sabl` file) is corrupt or
+    /// isn't a sound bank.
+    BadSoundBank(String),
+    /// Occurs when the asset index's database (see the `sqlite` feature)
+    /// returns an error.
+    Database(String),
     /// Occurs when some part of the library hasn't yet been implemented.
     Todo(String),
+    /// Occurs when text input (e.g. an exported `.csv` or `.str` file being
+    /// re-imported) couldn't be parsed.
+    Parse(String),
+    /// Occurs when a path given to [`ddl::DdlDef::get`] doesn't lead to a
+    /// value of the DDL.
+    BadDdlPath(String),
     /// Occurs when a [`ScriptString`] isn't a valid index.
     BadScriptString(u16),
     /// Occurs when more than [`u16::MAX`] [`ScriptString`]s are present.
     ScriptStringOverflow,
+    /// Occurs when inline pointers are nested more deeply than a
+    /// [`PtrDepth`]'s limit (which is included).
+    PtrDepthExceeded(usize),
+    /// Occurs when asked to deserialize assets for a number of local clients
+    /// other than 1 or 4.
+    UnsupportedMaxLocalClients(usize),
+    /// Occurs when an image's pixel format isn't a
+    /// [`texture::TextureFormat`], e.g. because it's from a console
+    /// Fastfile.
+    UnknownTextureFormat(i32),
     /// Occurs when an `XAsset`'s `asset_type` isn't a variant of
     /// [`XAssetType`].
     InvalidXAssetType(u32),
@@ -412,6 +719,67 @@ impl From<windows::core::Error> for ErrorKind {
     }
 }
 
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            #[cfg(feature = "bincode")]
+            Self::Bincode(e) => write!(f, "bincode error: {e}"),
+            Self::Inflate(s) => write!(f, "couldn't inflate: {s}"),
+            Self::Deflate(s) => write!(f, "couldn't deflate: {s}"),
+            Self::BadFromPrimitive(v) => write!(f, "{v} isn't a valid enum value"),
+            Self::BadBitflags(v) => write!(f, "{v:#X} contains unknown flags"),
+            Self::BadChar(c) => write!(f, "{c:#X} isn't a valid character"),
+            Self::BrokenInvariant(s) => write!(f, "broken invariant: {s}"),
+            Self::Validation(violations) => {
+                write!(f, "validation failed: {}", violations.join("; "))
+            }
+            Self::InvalidSeek { off, max } => {
+                write!(f, "offset {off:#X} is out of bounds (max {max:#X})")
+            }
+            Self::BadHeaderMagic(magic) => write!(f, "bad header magic {magic:?}"),
+            Self::ProtectedFile(protection) => write!(f, "file is {protection}"),
+            Self::WrongVersion(version) => write!(
+                f,
+                "wrong version {version:#X} (expected {:#X})",
+                XFILE_VERSION
+            ),
+            Self::WrongEndiannessForPlatform(platform) => {
+                write!(f, "wrong endianness for {platform}")
+            }
+            Self::UnimplementedPlatform(platform) => write!(f, "{platform} isn't implemented"),
+            Self::UnsupportedPlatform(platform) => write!(f, "{platform} isn't supported"),
+            Self::UnknownPlatform(s) => write!(f, "unknown platform {s:?}"),
+            Self::BadCacheFile(s) => write!(f, "bad cache file: {s}"),
+            Self::BadSnapshot(s) => write!(f, "bad snapshot: {s}"),
+            Self::BadDump(s) => write!(f, "bad dump: {s}"),
+            Self::BadRename(s) => write!(f, "can't rename: {s}"),
+            #[cfg(feature = "models")]
+            Self::BadRetarget(s) => write!(f, "can't retarget materials: {s}"),
+            Self::BadImagePack(s) => write!(f, "bad image pack: {s}"),
+            Self::BadSoundBank(s) => write!(f, "bad sound bank: {s}"),
+            Self::Database(s) => write!(f, "database error: {s}"),
+            Self::Todo(s) => write!(f, "not implemented yet: {s}"),
+            Self::Parse(s) => write!(f, "couldn't parse: {s}"),
+            Self::BadDdlPath(path) => write!(f, "no DDL value at {path:?}"),
+            Self::BadScriptString(index) => write!(f, "bad script string {index}"),
+            Self::ScriptStringOverflow => write!(f, "too many script strings"),
+            Self::PtrDepthExceeded(max) => {
+                write!(f, "pointers nested more than {max} deep")
+            }
+            Self::UnsupportedMaxLocalClients(n) => {
+                write!(f, "{n} local clients isn't supported (only 1 or 4 are)")
+            }
+            Self::UnknownTextureFormat(format) => write!(f, "unknown texture format {format}"),
+            Self::InvalidXAssetType(t) => write!(f, "{t} isn't an asset type"),
+            Self::UnusedXAssetType(t) => write!(f, "asset type {t:?} isn't used by T5"),
+            #[cfg(feature = "d3d9")]
+            Self::Windows(e) => write!(f, "D3D9 error: {e}"),
+        }
+    }
+}
+
 macro_rules! file_line_col {
     () => {
         alloc::format!("{}:{}:{}", file!(), line!(), column!())
@@ -457,14 +825,268 @@ impl Error {
     }
 }
 
+/// Formatted as the [`ErrorKind`], followed by the offset it occurred at
+/// (if any) and where in this library it was returned from, e.g.
+/// `bad script string 7 at offset 0x1C (src/lib.rs:12:34)`.
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(off) = self.off {
+            write!(f, " at offset {off:#X}")?;
+        }
+        write!(f, " ({})", self.where_)
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match &self.kind {
+            #[cfg(feature = "std")]
+            ErrorKind::Io(e) => Some(e),
+            #[cfg(feature = "bincode")]
+            ErrorKind::Bincode(e) => Some(&**e),
+            #[cfg(feature = "d3d9")]
+            ErrorKind::Windows(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
 
+/// The default limit of a [`PtrDepth`]. Far more than any real asset needs.
+pub const DEFAULT_MAX_PTR_DEPTH: usize = 256;
+
+/// Tracks how deeply the [`Ptr32`]s being followed are nested, for
+/// implementations of [`T5XFileDeserialize::enter_ptr`].
+///
+/// Every inline pointer consumes part of the stream, so nesting can't go on
+/// forever, but a crafted Fastfile can still nest pointers deeply enough to
+/// overflow the stack. Real offsets (see [`Ptr32::is_real`]) are only
+/// followed if the deserializer is asked to, and then count towards the
+/// depth like inline pointers. Linked lists (like
+/// [`GenericEventHandler::next`](menu::GenericEventHandler::next)) are
+/// followed iteratively, so they don't count towards the depth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PtrDepth {
+    depth: usize,
+    max: usize,
+}
+
+impl Default for PtrDepth {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PTR_DEPTH)
+    }
+}
+
+impl PtrDepth {
+    pub const fn new(max: usize) -> Self {
+        Self { depth: 0, max }
+    }
+
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub const fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Fails with [`ErrorKind::PtrDepthExceeded`] if entering another
+    /// pointer would exceed the limit. `pos` is only used for the error.
+    pub fn enter(&mut self, pos: u64) -> Result<()> {
+        if self.depth >= self.max {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::PtrDepthExceeded(self.max),
+            ));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn leave(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    /// Forgets about any pointers that were entered but never left, e.g.
+    /// because deserializing what they pointed to failed.
+    pub fn reset(&mut self) {
+        self.depth = 0;
+    }
+}
+
 pub trait T5XFileDeserialize {
     fn stream_pos(&mut self) -> Result<u64>;
     fn stream_len(&mut self) -> Result<u64>;
     fn silent(&self) -> bool;
 
-    fn load_from_xfile<T: DeserializeOwned>(&mut self) -> Result<T>;
+    /// Returns `true` if multi-byte values are stored little-endian.
+    fn is_little_endian(&self) -> bool;
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T>;
+
+    /// How [`XString`]s are decoded. The default implementation returns
+    /// [`StringEncoding::default`](encoding::StringEncoding::default).
+    fn string_encoding(&self) -> encoding::StringEncoding {
+        encoding::StringEncoding::default()
+    }
+
+    /// Reads the next `len` bytes in one go.
+    ///
+    /// The default implementation checks that they're there with
+    /// [`Self::check_remaining`], then calls [`Self::load_from_xfile`] `len`
+    /// times, so implementations should override it if they can do better.
+    fn load_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.check_remaining(len, 1)?;
+        (0..len).map(|_| self.load_from_xfile::<u8>()).collect()
+    }
+
+    /// Skips over the next `len` bytes without keeping them.
+    ///
+    /// The default implementation reads them with [`Self::load_bytes`] and
+    /// throws them away, so implementations should override it if they can
+    /// seek instead.
+    fn skip_bytes(&mut self, len: usize) -> Result<()> {
+        self.load_bytes(len).map(|_| ())
+    }
+
+    /// Reads `count` `T`s in a row, checking that they fit in the stream
+    /// first (see [`Self::check_array_len`]).
+    ///
+    /// Use this instead of calling [`Self::load_from_xfile`] in a loop when
+    /// `count` comes from the file, so that a corrupted count fails right
+    /// away instead of after reading up to the end of the stream.
+    fn load_array<T: XFileRead>(&mut self, count: usize) -> Result<Vec<T>> {
+        self.check_array_len(count, size_of!(T))?;
+        (0..count).map(|_| self.load_from_xfile()).collect()
+    }
+
+    /// Checks that an array of `count` elements, each `elem_size` bytes,
+    /// can actually be read before any memory is allocated for it.
+    ///
+    /// Every element has to come from the stream, so arrays extending past
+    /// its end are rejected with [`ErrorKind::BrokenInvariant`]. This catches
+    /// corrupted counts that would otherwise turn into multi-gigabyte
+    /// allocations. The array's size is then passed to
+    /// [`Self::reserve_allocation`].
+    fn check_array_len(&mut self, count: usize, elem_size: usize) -> Result<()> {
+        let len = self.check_remaining(count, elem_size)?;
+        self.reserve_allocation(len)
+    }
+
+    /// Same as [`Self::check_array_len`], but without reserving anything,
+    /// for reads that don't allocate or were already accounted for. Returns
+    /// the array's size in bytes.
+    fn check_remaining(&mut self, count: usize, elem_size: usize) -> Result<usize> {
+        let pos = self.stream_pos()?;
+        let remaining = self.stream_len()?.saturating_sub(pos);
+
+        match count.checked_mul(elem_size) {
+            Some(len) if len as u64 <= remaining => Ok(len),
+            None => Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::BrokenInvariant(alloc::format!(
+                    "array of {count} {elem_size}-byte elements overflows usize"
+                )),
+            )),
+            Some(_) => Err(Error::new_with_offset(
+                file_line_col!(),
+                pos as _,
+                ErrorKind::BrokenInvariant(alloc::format!(
+                    "array of {count} {elem_size}-byte elements extends past the end of \
+                     the stream ({remaining} bytes remaining)"
+                )),
+            )),
+        }
+    }
+
+    /// Accounts for `bytes` about to be allocated for the current asset.
+    ///
+    /// The default implementation doesn't impose a limit. Implementations
+    /// that do should return [`ErrorKind::BrokenInvariant`] once it's
+    /// exceeded.
+    fn reserve_allocation(&mut self, _bytes: usize) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called before deserializing the object behind an inline [`Ptr32`],
+    /// and followed by [`Self::leave_ptr`] once it's done.
+    ///
+    /// The default implementation doesn't impose a limit. Implementations
+    /// that do (usually with a [`PtrDepth`]) should return
+    /// [`ErrorKind::PtrDepthExceeded`] once it's exceeded.
+    fn enter_ptr(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn leave_ptr(&mut self) {}
+
+    /// Called right before the `len` bytes behind an inline pointer are
+    /// loaded, with their alignment, so that real offsets pointing at them
+    /// can be resolved later (see [`blocks`]). `marker` is the marker the
+    /// pointer was serialized with, or [`None`] for flexible arrays, which
+    /// aren't behind a pointer.
+    ///
+    /// The default implementation doesn't keep track of anything, so real
+    /// offsets never resolve.
+    fn mark_ptr_target(
+        &mut self,
+        _marker: Option<PtrMarker>,
+        _align: usize,
+        _len: usize,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called whenever a real offset is encountered. Returns where in the
+    /// payload the object it points to was loaded from, if known.
+    fn resolve_real_ptr(&mut self, _offset: XFileOffset) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Seeks to `target` (returned by [`Self::resolve_real_ptr`]) to load the
+    /// object a real offset points to again, and returns the position to
+    /// return to with [`Self::leave_real_ptr`] once it's loaded.
+    ///
+    /// Returns [`None`] if real offsets shouldn't be followed, which is what
+    /// the default implementation does. Implementations that do follow them
+    /// have to return [`None`] for targets they're already following, since
+    /// real offsets can form cycles.
+    fn enter_real_ptr(&mut self, _target: u64) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    fn leave_real_ptr(&mut self, _pos: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the object interned with [`Self::intern`] at `offset` in the
+    /// payload, if it's a `U`.
+    ///
+    /// The default implementation doesn't intern anything, so every pointer
+    /// to a [`Shared`] object loads a copy of its own.
+    #[cfg(feature = "arc")]
+    fn interned<U: core::any::Any + Send + Sync>(
+        &self,
+        _offset: u64,
+    ) -> Option<alloc::sync::Arc<U>> {
+        None
+    }
+
+    /// Called with every [`Shared`] object once it's been loaded from
+    /// `offset` in the payload, so real offsets pointing at it later can
+    /// share it (see [`Self::interned`]) rather than loading it again.
+    #[cfg(feature = "arc")]
+    fn intern<U: core::any::Any + Send + Sync>(
+        &mut self,
+        _offset: u64,
+        _object: alloc::sync::Arc<U>,
+    ) {
+    }
 
     /// Returns [`Ok(Some)`] if `string` is present, [`Ok(None)`]
     /// if not, or, depending on the implementation, [`Err`].
@@ -482,9 +1104,13 @@ pub trait T5XFileDeserialize {
     }
 }
 
+#[cfg(feature = "serde")]
 pub trait T5XFileSerialize {
     fn store_into_xfile<T: Serialize>(&mut self, t: T) -> Result<()>;
 
+    /// Returns `true` if multi-byte values are stored little-endian.
+    fn is_little_endian(&self) -> bool;
+
     /// Returns [`Ok`] if `string` was already present or was successfully
     /// inserted, or [`Err`] when [`Error::ScriptStringOverflow`] or some
     /// other error occurs.
@@ -494,4 +1120,20 @@ pub trait T5XFileSerialize {
 
     fn asset_count(&self) -> usize;
     fn asset_bytes(&self) -> Option<&[u8]>;
+
+    /// How [`XString`]s are encoded. The default implementation returns
+    /// [`StringEncoding::default`](encoding::StringEncoding::default).
+    fn string_encoding(&self) -> encoding::StringEncoding {
+        encoding::StringEncoding::default()
+    }
+
+    /// Makes `block` the one that everything stored from now on is
+    /// allocated from, until the matching [`Self::pop_block`]. Mirrors the
+    /// engine's `DB_PushStreamPos`. Everything is allocated from
+    /// [`XFileBlock::Virtual`] unless pushed otherwise.
+    ///
+    /// The default implementation doesn't keep track of blocks.
+    fn push_block(&mut self, _block: XFileBlock) {}
+
+    fn pop_block(&mut self) {}
 }
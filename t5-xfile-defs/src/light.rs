@@ -1,17 +1,20 @@
 use alloc::boxed::Box;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
+    Ptr32, Result, T5XFileDeserialize, XFileDeserializeInto, XFileRead, XString, XStringRaw,
+    assert_size,
     techset::{GfxImage, GfxImageRaw},
 };
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub attenuation: GfxLightImageRaw<'a>,
@@ -20,13 +23,15 @@ pub(crate) struct GfxLightDefRaw<'a> {
 assert_size!(GfxLightDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightDef {
     pub name: XString,
     pub attenuation: GfxLightImage,
     pub lmap_lookup_start: i32,
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxLightDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -70,16 +75,18 @@ impl<'a> XFileDeserializeInto<GfxLightDef, ()> for GfxLightDefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct GfxLightImageRaw<'a> {
     pub image: Ptr32<'a, GfxImageRaw<'a>>,
     pub sampler_state: u8,
+    #[allow(dead_code)]
     pad: [u8; 3],
 }
 assert_size!(GfxLightImageRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GfxLightImage {
     pub image: Option<Box<GfxImage>>,
     pub sampler_state: u8,
@@ -103,6 +110,7 @@ impl<'a> XFileDeserializeInto<GfxLightImage, ()> for GfxLightImageRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxLightImage {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let image = Ptr32::from_box(&self.image);
@@ -0,0 +1,312 @@
+//! Export, search, and re-import of [`LocalizeEntry`] assets.
+//!
+//! Localized strings are authored in StringEd `.str` files. Each file holds
+//! the strings for one prefix (e.g., `menu.str` holds every `MENU_*`
+//! string), and each entry looks like:
+//!
+//! ```text
+//! REFERENCE           START_GAME
+//! LANG_ENGLISH        "Start Game"
+//! ```
+//!
+//! The linker joins the file prefix and the reference with an underscore to
+//! produce the name stored in the Fastfile (`MENU_START_GAME`), so exporting
+//! splits the name back apart at the first underscore.
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, LocalizeEntry, Result, XFilePlatform, XString, csv, file_line_col,
+    xasset::{XAsset, XAssetGeneric},
+};
+
+/// Splits a localized string's name into its `.str` file prefix and its
+/// reference. Names without an underscore have an empty prefix.
+pub fn split_name(name: &str) -> (&str, &str) {
+    name.split_once('_').unwrap_or(("", name))
+}
+
+/// Joins a `.str` file prefix and a reference back into a name.
+pub fn join_name(prefix: &str, reference: &str) -> String {
+    if prefix.is_empty() {
+        reference.to_string()
+    } else {
+        format!("{prefix}_{reference}")
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Parses a double-quoted `.str` value, returning [`None`] if `s` isn't one.
+fn parse_quoted(s: &str) -> Option<String> {
+    let s = s.trim();
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+    Some(unescape(inner))
+}
+
+/// All the localized strings of one or more zones, in the order they were
+/// first seen.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LocalizeTable {
+    entries: Vec<LocalizeEntry>,
+    index: BTreeMap<String, usize>,
+}
+
+impl LocalizeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects every [`LocalizeEntry`] in `assets`, ignoring all other asset
+    /// types.
+    pub fn from_assets<'a>(assets: impl IntoIterator<Item = &'a XAsset>) -> Self {
+        let mut table = Self::new();
+        for asset in assets {
+            let entry = match asset {
+                XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e))) => e,
+                XAsset::Console(XAssetGeneric::LocalizeEntry(Some(e))) => e,
+                _ => continue,
+            };
+            table.insert(entry.name.get(), entry.value.get());
+        }
+        table
+    }
+
+    /// Converts the table back into assets suitable for serialization.
+    pub fn to_assets(&self, platform: XFilePlatform) -> Vec<XAsset> {
+        self.entries
+            .iter()
+            .cloned()
+            .map(|e| {
+                if platform.is_console() {
+                    XAsset::Console(XAssetGeneric::LocalizeEntry(Some(Box::new(e))))
+                } else {
+                    XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(e))))
+                }
+            })
+            .collect()
+    }
+
+    /// Inserts or updates the string `name`.
+    ///
+    /// Returns the old value if `name` was already present.
+    pub fn insert(&mut self, name: &str, value: &str) -> Option<String> {
+        if let Some(&i) = self.index.get(name) {
+            let old = core::mem::replace(&mut self.entries[i].value, XString(value.to_string()));
+            Some(old.0)
+        } else {
+            self.index.insert(name.to_string(), self.entries.len());
+            self.entries.push(LocalizeEntry {
+                value: XString(value.to_string()),
+                name: XString(name.to_string()),
+            });
+            None
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.index.get(name).map(|&i| self.entries[i].value.get())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LocalizeEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns every entry whose name or value contains `pattern`, ignoring
+    /// ASCII case.
+    pub fn search<'a>(&'a self, pattern: &str) -> impl Iterator<Item = &'a LocalizeEntry> {
+        let pattern = pattern.to_ascii_lowercase();
+        self.entries.iter().filter(move |e| {
+            e.name.get().to_ascii_lowercase().contains(&pattern)
+                || e.value.get().to_ascii_lowercase().contains(&pattern)
+        })
+    }
+
+    /// Updates the table with every entry in `other`. Existing strings get
+    /// their values replaced, new strings are appended.
+    ///
+    /// Returns the number of strings whose value changed or that were newly
+    /// added.
+    pub fn merge(&mut self, other: &LocalizeTable) -> usize {
+        let mut changed = 0;
+        for e in other.iter() {
+            match self.insert(e.name.get(), e.value.get()) {
+                Some(old) if old == e.value.get() => {}
+                _ => changed += 1,
+            }
+        }
+        changed
+    }
+
+    /// Exports the table as StringEd `.str` files, one per name prefix.
+    ///
+    /// Returns a map of prefix to file contents. The engine expects each file
+    /// to be named after its prefix in lowercase (e.g. `MENU` ->
+    /// `menu.str`). `language` is the language name without the `LANG_`
+    /// prefix, e.g. `ENGLISH`.
+    pub fn to_str_files(&self, language: &str) -> BTreeMap<String, String> {
+        let mut files = BTreeMap::<String, String>::new();
+
+        for e in self.entries.iter() {
+            let (prefix, reference) = split_name(e.name.get());
+            let file = files.entry(prefix.to_string()).or_insert_with(|| {
+                String::from(
+                    "VERSION             \"1\"\n\
+                     CONFIG              \"C:\\projects\\cod\\t5\\bin\\StringEd.cfg\"\n\
+                     FILENOTES           \"\"\n",
+                )
+            });
+
+            let lang = format!("LANG_{}", language.to_ascii_uppercase());
+            file.push_str(&format!(
+                "\nREFERENCE           {reference}\n{lang:<20}\"{}\"\n",
+                escape(e.value.get())
+            ));
+        }
+
+        for file in files.values_mut() {
+            file.push_str("\nENDMARKER\n");
+        }
+
+        files
+    }
+
+    /// Parses a StringEd `.str` file, reading the values for `language`.
+    ///
+    /// `prefix` is the prefix every reference in the file gets joined with,
+    /// usually the file's name in uppercase.
+    pub fn parse_str_file(contents: &str, prefix: &str, language: &str) -> Result<Self> {
+        let lang = format!("LANG_{}", language.to_ascii_uppercase());
+
+        let mut table = Self::new();
+        let mut reference = None;
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let (key, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+            match key {
+                "VERSION" | "CONFIG" | "FILENOTES" | "NOTES" | "FLAGS" => {}
+                "ENDMARKER" => break,
+                "REFERENCE" => reference = Some(rest.trim().to_string()),
+                k if k == lang => {
+                    let Some(r) = reference.as_deref() else {
+                        return Err(Error::new(
+                            file_line_col!(),
+                            ErrorKind::Parse(format!(
+                                ".str: {lang} without a REFERENCE on line {}",
+                                i + 1
+                            )),
+                        ));
+                    };
+
+                    let value = parse_quoted(rest).ok_or(Error::new(
+                        file_line_col!(),
+                        ErrorKind::Parse(format!(".str: unquoted value on line {}", i + 1)),
+                    ))?;
+
+                    table.insert(&join_name(prefix, r), &value);
+                }
+                // other languages
+                k if k.starts_with("LANG_") => {}
+                k => {
+                    return Err(Error::new(
+                        file_line_col!(),
+                        ErrorKind::Parse(format!(".str: unknown key {k} on line {}", i + 1)),
+                    ));
+                }
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Exports the table as a two-column (`name`, `value`) CSV file with a
+    /// header row.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        csv::write_record(&mut out, ["name", "value"]);
+        for e in self.entries.iter() {
+            csv::write_record(&mut out, [e.name.get(), e.value.get()]);
+        }
+        out
+    }
+
+    /// Parses a CSV file in the format produced by [`Self::to_csv`].
+    pub fn parse_csv(contents: &str) -> Result<Self> {
+        let mut table = Self::new();
+
+        for (i, record) in csv::parse(contents)?.into_iter().enumerate() {
+            if i == 0 && record.first().map(String::as_str) == Some("name") {
+                continue;
+            }
+
+            let [name, value] = record.as_slice() else {
+                return Err(Error::new(
+                    file_line_col!(),
+                    ErrorKind::Parse(format!(
+                        "CSV: expected 2 columns on record {}, found {}",
+                        i + 1,
+                        record.len()
+                    )),
+                ));
+            };
+
+            table.insert(name, value);
+        }
+
+        Ok(table)
+    }
+}
@@ -1,24 +1,96 @@
-use core::mem::transmute;
+use core::fmt::Debug;
 
 use alloc::{boxed::Box, format, vec::Vec};
 
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    T5XFileDeserialize, XFileDeserializeInto, XString, XStringRaw, assert_size,
+    BackRef, Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, InternTable, Ptr32,
+    Result, Shared, T5XFileDeserialize, XFileDeserializeInto, XFileRead, XString, XStringRaw,
+    assert_size,
     common::Vec4,
-    file_line_col,
+    file_line_col, read,
     techset::{Material, MaterialRaw},
 };
 
+/// A node of one of the singly linked lists in menus, like
+/// [`GenericEventHandler`]. Lists can be arbitrarily long, so they're built,
+/// walked, and dropped in loops rather than recursively.
+trait LinkedList: Sized {
+    fn set_next(&mut self, next: Self);
+}
+
+/// The raw version of a [`LinkedList`] node. Each node follows the previous
+/// one inline, after everything else the previous one points to.
+trait LinkedListRaw<'a>: Copy + XFileRead + Debug + 'a {
+    type Node: LinkedList;
+
+    fn next(&self) -> Ptr32<'a, Self>;
+
+    /// Deserializes everything about this node except the next one.
+    fn xfile_deserialize_node(&self, de: &mut impl T5XFileDeserialize) -> Result<Self::Node>;
+
+    fn xfile_deserialize_list(&self, de: &mut impl T5XFileDeserialize) -> Result<Self::Node> {
+        let mut nodes = Vec::new();
+        let mut raw = *self;
+        loop {
+            nodes.push(raw.xfile_deserialize_node(de)?);
+            match raw.next().xfile_load(de)? {
+                Some(next) => raw = next,
+                None => break,
+            }
+        }
+
+        // nodes always has at least one element
+        let mut head = nodes.pop().unwrap();
+        while let Some(mut node) = nodes.pop() {
+            node.set_next(head);
+            head = node;
+        }
+
+        Ok(head)
+    }
+}
+
+macro_rules! impl_linked_list {
+    ($t:ty) => {
+        impl LinkedList for $t {
+            fn set_next(&mut self, next: Self) {
+                self.next = Some(Box::new(next));
+            }
+        }
+
+        impl $t {
+            /// Iterates over this node and every node after it.
+            pub fn iter(&self) -> impl Iterator<Item = &Self> {
+                core::iter::successors(Some(self), |node| node.next.as_deref())
+            }
+
+            /// The number of nodes in the list, starting at this one.
+            pub fn chain_len(&self) -> usize {
+                self.iter().count()
+            }
+        }
+
+        impl Drop for $t {
+            fn drop(&mut self) {
+                let mut next = self.next.take();
+                while let Some(mut node) = next {
+                    next = node.next.take();
+                }
+            }
+        }
+    };
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MenuListRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub name: XStringRaw<'a>,
     pub menus: FatPointerCountFirstU32<'a, Ptr32<'a, MenuDefRaw<'a, MAX_LOCAL_CLIENTS>>>,
@@ -26,7 +98,8 @@ pub(crate) struct MenuListRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
 assert_size!(MenuListRaw<1>, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MenuList<const MAX_LOCAL_CLIENTS: usize> {
     pub name: XString,
     pub menus: Vec<Box<MenuDef<MAX_LOCAL_CLIENTS>>>,
@@ -44,21 +117,45 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuList<MAX_LOCAL
         let name = self.name.xfile_deserialize_into(de, ())?;
         //dbg!(&name);
         //dbg!(de.stream_pos()?);
-        let menus = self
-            .menus
-            .xfile_deserialize_into(de, ())?
-            .into_iter()
-            .flatten()
-            .collect();
+        let menus: Vec<Option<Box<_>>> = self.menus.xfile_deserialize_into(de, ())?;
+        let menus = menus.into_iter().flatten().collect();
         //dbg!(&menus);
         //dbg!(de.stream_pos()?);
 
-        Ok(MenuList { name, menus })
+        let mut list = MenuList { name, menus };
+        list.resolve_item_parents();
+        Ok(list)
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> MenuList<MAX_LOCAL_CLIENTS> {
+    /// Builds an [`InternTable`] mapping the offset of each menu (see
+    /// [`MenuDef::offset`]) to its index within [`Self::menus`].
+    pub fn intern_table(&self) -> InternTable {
+        let mut table = InternTable::new();
+        for (i, menu) in self.menus.iter().enumerate() {
+            if let Some(ptr) = menu.offset() {
+                table.insert(ptr, i);
+            }
+        }
+        table
+    }
+
+    /// Resolves the [`ItemDef::parent`] of every item of every menu to an
+    /// index within [`Self::menus`].
+    ///
+    /// Returns the number of items whose parent couldn't be resolved.
+    pub fn resolve_item_parents(&mut self) -> usize {
+        let table = self.intern_table();
+        self.menus
+            .iter_mut()
+            .map(|m| m.resolve_item_parents(&table))
+            .sum()
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct MenuDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub window: WindowDefRaw<'a, MAX_LOCAL_CLIENTS>,
     pub font: XStringRaw<'a>,
@@ -66,7 +163,7 @@ pub(crate) struct MenuDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub ui_3d_window_id: i32,
     pub item_count: i32,
     pub font_index: i32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub cursor_item: [i32; MAX_LOCAL_CLIENTS],
     pub fade_cycle: i32,
     pub priority: i32,
@@ -150,7 +247,8 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> Default for MenuDefRaw<'a, MAX_LOCAL_CL
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MenuDef<const MAX_LOCAL_CLIENTS: usize> {
     pub window: WindowDef<MAX_LOCAL_CLIENTS>,
     pub font: XString,
@@ -227,13 +325,11 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuDef<MAX_LOCAL_
         let rect_y_exp = self.rect_y_exp.xfile_deserialize_into(de, ())?;
         //dbg!(&rect_y_exp);
         //dbg!(de.stream_pos()?);
-        let items = self
+        let items: Vec<Option<Box<_>>> = self
             .items
             .to_array(self.item_count as _)
-            .xfile_deserialize_into(de, ())?
-            .into_iter()
-            .flatten()
-            .collect();
+            .xfile_deserialize_into(de, ())?;
+        let items = items.into_iter().flatten().collect();
         //dbg!(&items);
         //dbg!(de.stream_pos()?);
 
@@ -242,7 +338,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuDef<MAX_LOCAL_
         let full_screen = self.full_screen != 0;
         let intial_rect_info = self.intial_rect_info.into();
 
-        Ok(MenuDef {
+        let mut menu = MenuDef {
             window,
             font,
             full_screen,
@@ -278,12 +374,41 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<MenuDef<MAX_LOCAL_
             rect_x_exp,
             rect_y_exp,
             items,
-        })
+        };
+
+        // a menu on its own is its items' only possible parent
+        let mut table = InternTable::new();
+        if let Some(ptr) = menu.offset() {
+            table.insert(ptr, 0);
+        }
+        menu.resolve_item_parents(&table);
+
+        Ok(menu)
+    }
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> MenuDef<MAX_LOCAL_CLIENTS> {
+    /// Returns the offset the menu was loaded at, as recorded by its items'
+    /// [`ItemDef::parent`] pointers. Returns [`None`] if the menu has no
+    /// items, or none of them point back to it.
+    pub fn offset(&self) -> Option<u32> {
+        self.items.iter().find_map(|item| item.parent.ptr())
+    }
+
+    /// Resolves the [`ItemDef::parent`] of every item against `table`.
+    ///
+    /// Returns the number of items whose parent couldn't be resolved.
+    pub fn resolve_item_parents(&mut self, table: &InternTable) -> usize {
+        self.items
+            .iter_mut()
+            .map(|item| item.parent.resolve(table))
+            .filter(|resolved| !resolved)
+            .count()
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct WindowDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub name: XStringRaw<'a>,
     pub rect: RectDefRaw,
@@ -299,7 +424,7 @@ pub(crate) struct WindowDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub owner_draw_flags: i32,
     pub border_size: f32,
     pub static_flags: i32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub dynamic_flags: [i32; MAX_LOCAL_CLIENTS],
     pub next_time: i32,
     pub fore_color: [f32; 4],
@@ -341,7 +466,8 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> Default for WindowDefRaw<'a, MAX_LOCAL_
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct WindowDef<const MAX_LOCAL_CLIENTS: usize> {
     pub name: XString,
     pub rect: RectDef,
@@ -365,7 +491,7 @@ pub struct WindowDef<const MAX_LOCAL_CLIENTS: usize> {
     pub border_color: Vec4,
     pub outline_color: Vec4,
     pub rotation: f32,
-    pub background: Option<Box<Material>>,
+    pub background: Option<Shared<Material>>,
 }
 
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<WindowDef<MAX_LOCAL_CLIENTS>, ()>
@@ -422,7 +548,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<WindowDef<MAX_LOCA
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct RectDefRaw {
     pub x: f32,
     pub y: f32,
@@ -434,7 +560,8 @@ pub(crate) struct RectDefRaw {
 assert_size!(RectDefRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct RectDef {
     pub x: f32,
     pub y: f32,
@@ -458,7 +585,7 @@ impl Into<RectDef> for RectDefRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GenericEventHandlerRaw<'a> {
     pub name: XStringRaw<'a>,
     pub event_script: Ptr32<'a, GenericEventScriptRaw<'a>>,
@@ -467,42 +594,55 @@ pub(crate) struct GenericEventHandlerRaw<'a> {
 assert_size!(GenericEventHandlerRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GenericEventHandler {
     pub name: XString,
     pub event_script: Option<Box<GenericEventScript>>,
-    pub next: Option<Box<Self>>, // TODO
+    pub next: Option<Box<Self>>,
 }
+impl_linked_list!(GenericEventHandler);
 
-impl<'a> XFileDeserializeInto<GenericEventHandler, ()> for GenericEventHandlerRaw<'a> {
-    fn xfile_deserialize_into(
+impl<'a> LinkedListRaw<'a> for GenericEventHandlerRaw<'a> {
+    type Node = GenericEventHandler;
+
+    fn next(&self) -> Ptr32<'a, Self> {
+        self.next
+    }
+
+    fn xfile_deserialize_node(
         &self,
         de: &mut impl T5XFileDeserialize,
-        _data: (),
     ) -> Result<GenericEventHandler> {
         let name = self.name.xfile_deserialize_into(de, ())?;
         let event_script = self.event_script.xfile_deserialize_into(de, ())?;
-        let next = if self.next.is_null() {
-            None
-        } else {
-            self.next.xfile_deserialize_into(de, ())?
-        };
 
         Ok(GenericEventHandler {
             name,
             event_script,
-            next,
+            next: None,
         })
     }
 }
 
+impl<'a> XFileDeserializeInto<GenericEventHandler, ()> for GenericEventHandlerRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<GenericEventHandler> {
+        self.xfile_deserialize_list(de)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GenericEventScriptRaw<'a> {
     pub prerequisites: Ptr32<'a, ScriptConditionRaw<'a>>,
     pub condition: ExpressionStatementRaw<'a>,
     pub type_: i32,
     pub fire_on_true: bool,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub action: XStringRaw<'a>,
     pub block_id: i32,
@@ -512,7 +652,8 @@ pub(crate) struct GenericEventScriptRaw<'a> {
 assert_size!(GenericEventScriptRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GenericEventScript {
     pub prerequisites: Option<Box<ScriptCondition>>,
     pub condition: ExpressionStatement,
@@ -521,23 +662,24 @@ pub struct GenericEventScript {
     pub action: XString,
     pub block_id: i32,
     pub construct_id: i32,
-    pub next: Option<Box<Self>>, // TODO,
+    pub next: Option<Box<Self>>,
 }
+impl_linked_list!(GenericEventScript);
 
-impl<'a> XFileDeserializeInto<GenericEventScript, ()> for GenericEventScriptRaw<'a> {
-    fn xfile_deserialize_into(
+impl<'a> LinkedListRaw<'a> for GenericEventScriptRaw<'a> {
+    type Node = GenericEventScript;
+
+    fn next(&self) -> Ptr32<'a, Self> {
+        self.next
+    }
+
+    fn xfile_deserialize_node(
         &self,
         de: &mut impl T5XFileDeserialize,
-        _data: (),
     ) -> Result<GenericEventScript> {
         let prerequisites = self.prerequisites.xfile_deserialize_into(de, ())?;
         let condition = self.condition.xfile_deserialize_into(de, ())?;
         let action = self.action.xfile_deserialize_into(de, ())?;
-        let next = if self.next.is_null() {
-            None
-        } else {
-            self.next.xfile_deserialize_into(de, ())?
-        };
 
         Ok(GenericEventScript {
             prerequisites,
@@ -547,15 +689,26 @@ impl<'a> XFileDeserializeInto<GenericEventScript, ()> for GenericEventScriptRaw<
             action,
             block_id: self.block_id,
             construct_id: self.construct_id,
-            next,
+            next: None,
         })
     }
 }
 
+impl<'a> XFileDeserializeInto<GenericEventScript, ()> for GenericEventScriptRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<GenericEventScript> {
+        self.xfile_deserialize_list(de)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ScriptConditionRaw<'a> {
     pub fire_on_true: bool,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub block_id: i32,
     pub construct_id: i32,
@@ -564,37 +717,45 @@ pub(crate) struct ScriptConditionRaw<'a> {
 assert_size!(ScriptConditionRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ScriptCondition {
     pub fire_on_true: bool,
     pub block_id: i32,
     pub construct_id: i32,
-    pub next: Option<Box<Self>>, // TODO
+    pub next: Option<Box<Self>>,
 }
+impl_linked_list!(ScriptCondition);
 
-impl<'a> XFileDeserializeInto<ScriptCondition, ()> for ScriptConditionRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<ScriptCondition> {
-        let next = if self.next.is_null() {
-            None
-        } else {
-            self.next.xfile_deserialize_into(de, ())?
-        };
+impl<'a> LinkedListRaw<'a> for ScriptConditionRaw<'a> {
+    type Node = ScriptCondition;
+
+    fn next(&self) -> Ptr32<'a, Self> {
+        self.next
+    }
 
+    fn xfile_deserialize_node(&self, _de: &mut impl T5XFileDeserialize) -> Result<ScriptCondition> {
         Ok(ScriptCondition {
             fire_on_true: self.fire_on_true,
             block_id: self.block_id,
             construct_id: self.construct_id,
-            next,
+            next: None,
         })
     }
 }
 
+impl<'a> XFileDeserializeInto<ScriptCondition, ()> for ScriptConditionRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<ScriptCondition> {
+        self.xfile_deserialize_list(de)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ExpressionStatementRaw<'a> {
     pub filename: XStringRaw<'a>,
     pub line: i32,
@@ -603,7 +764,8 @@ pub(crate) struct ExpressionStatementRaw<'a> {
 assert_size!(ExpressionStatementRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ExpressionStatement {
     pub filename: XString,
     pub line: i32,
@@ -628,7 +790,7 @@ impl<'a> XFileDeserializeInto<ExpressionStatement, ()> for ExpressionStatementRa
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ExpressionRpnRaw {
     pub type_: i32,
     pub data: ExpressionRpnDataUnionRaw,
@@ -636,7 +798,8 @@ pub(crate) struct ExpressionRpnRaw {
 assert_size!(ExpressionRpnRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ExpressionRpn {
     pub data: Option<ExpressionRpnDataUnion>,
 }
@@ -653,12 +816,13 @@ impl XFileDeserializeInto<ExpressionRpn, ()> for ExpressionRpnRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ExpressionRpnDataUnionRaw([u8; 8]);
 assert_size!(ExpressionRpnDataUnionRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ExpressionRpnDataUnion {
     Constant(Operand),
     CmdIdx(i32),
@@ -672,7 +836,8 @@ impl XFileDeserializeInto<Option<ExpressionRpnDataUnion>, i32> for ExpressionRpn
     ) -> Result<Option<ExpressionRpnDataUnion>> {
         if type_ == 0 {
             Ok(Some(ExpressionRpnDataUnion::Constant(
-                unsafe { transmute::<_, OperandRaw>(self.0) }.xfile_deserialize_into(de, ())?,
+                read::from_bytes::<OperandRaw>(&self.0, de.is_little_endian())?
+                    .xfile_deserialize_into(de, ())?,
             )))
         } else {
             Ok(None)
@@ -681,14 +846,15 @@ impl XFileDeserializeInto<Option<ExpressionRpnDataUnion>, i32> for ExpressionRpn
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct OperandRaw {
     pub data_type: i32,
     pub internals: OperandInternalDataUnionRaw,
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(i32)]
 pub(crate) enum ExpDataType {
     #[default]
@@ -698,7 +864,8 @@ pub(crate) enum ExpDataType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Operand {
     pub internals: OperandInternalDataUnion,
 }
@@ -720,12 +887,13 @@ impl XFileDeserializeInto<Operand, ()> for OperandRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct OperandInternalDataUnionRaw(u32);
 assert_size!(OperandInternalDataUnionRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum OperandInternalDataUnion {
     Int(i32),
     Float(f32),
@@ -749,7 +917,7 @@ impl XFileDeserializeInto<OperandInternalDataUnion, ExpDataType> for OperandInte
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ItemKeyHandlerRaw<'a> {
     pub key: i32,
     pub key_script: Ptr32<'a, GenericEventScriptRaw<'a>>,
@@ -758,11 +926,30 @@ pub(crate) struct ItemKeyHandlerRaw<'a> {
 assert_size!(ItemKeyHandlerRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ItemKeyHandler {
     pub key: i32,
     pub key_script: Option<Box<GenericEventScript>>,
-    pub next: Option<Box<ItemKeyHandler>>, // TODO
+    pub next: Option<Box<ItemKeyHandler>>,
+}
+impl_linked_list!(ItemKeyHandler);
+
+impl<'a> LinkedListRaw<'a> for ItemKeyHandlerRaw<'a> {
+    type Node = ItemKeyHandler;
+
+    fn next(&self) -> Ptr32<'a, Self> {
+        self.next
+    }
+
+    fn xfile_deserialize_node(&self, de: &mut impl T5XFileDeserialize) -> Result<ItemKeyHandler> {
+        let key_script = self.key_script.xfile_deserialize_into(de, ())?;
+        Ok(ItemKeyHandler {
+            key: self.key,
+            key_script,
+            next: None,
+        })
+    }
 }
 
 impl<'a> XFileDeserializeInto<ItemKeyHandler, ()> for ItemKeyHandlerRaw<'a> {
@@ -771,22 +958,12 @@ impl<'a> XFileDeserializeInto<ItemKeyHandler, ()> for ItemKeyHandlerRaw<'a> {
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<ItemKeyHandler> {
-        let key_script = self.key_script.xfile_deserialize_into(de, ())?;
-        let next = if self.next.is_null() {
-            None
-        } else {
-            self.next.xfile_deserialize_into(de, ())?
-        };
-        Ok(ItemKeyHandler {
-            key: self.key,
-            key_script,
-            next,
-        })
+        self.xfile_deserialize_list(de)
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub window: WindowDefRaw<'a, MAX_LOCAL_CLIENTS>,
     pub type_: i32,
@@ -800,6 +977,7 @@ pub(crate) struct ItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub parent: Ptr32<'a, MenuDefRaw<'a, MAX_LOCAL_CLIENTS>>,
     pub rect_exp_data: Ptr32<'a, RectDataRaw<'a>>,
     pub visible_exp: ExpressionStatementRaw<'a>,
+    #[allow(dead_code)]
     pad: [u8; 4],
     pub show_bits: u64,
     pub hide_bits: u64,
@@ -813,7 +991,8 @@ pub(crate) struct ItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
 assert_size!(ItemDefRaw<1>, 272);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ItemDef<const MAX_LOCAL_CLIENTS: usize> {
     pub window: WindowDef<MAX_LOCAL_CLIENTS>,
     pub type_: i32,
@@ -824,7 +1003,9 @@ pub struct ItemDef<const MAX_LOCAL_CLIENTS: usize> {
     pub enable_dvar: XString,
     pub dvar_flags: i32,
     pub type_data: Option<ItemDefData<MAX_LOCAL_CLIENTS>>,
-    pub parent: Option<Box<MenuDef<MAX_LOCAL_CLIENTS>>>, // TODO
+    /// Index of the owning menu within [`MenuList::menus`] once resolved, or
+    /// `0` for a menu that was loaded on its own.
+    pub parent: BackRef<MenuDef<MAX_LOCAL_CLIENTS>>,
     pub rect_exp_data: Option<Box<RectData>>,
     pub visible_exp: ExpressionStatement,
     pub show_bits: u64,
@@ -860,15 +1041,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ItemDef<MAX_LOCAL_
         let type_data = self.type_data.xfile_deserialize_into(de, self.type_)?;
         //dbg!(de.stream_pos().unwrap());
         ////dbg!(&type_data);
-        let parent = if self.parent.is_null() || self.parent.is_real() {
-            None
-        } else {
-            return Err(Error::new_with_offset(
-                file_line_col!(),
-                de.stream_pos()? as _,
-                ErrorKind::Todo("ItemDef: fix recursion.".to_string()),
-            ));
-        };
+        let parent = self.parent.xfile_back_ref(de, ())?;
         //dbg!(de.stream_pos().unwrap());
         //dbg!(&parent);
         let rect_exp_data = self.rect_exp_data.xfile_deserialize_into(de, ())?;
@@ -911,12 +1084,13 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ItemDef<MAX_LOCAL_
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ItemDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
 assert_size!(ItemDefDataRaw<1>, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ItemDefData<const MAX_LOCAL_CLIENTS: usize> {
     TextDef(Option<Box<TextDef<MAX_LOCAL_CLIENTS>>>),
     ImageDef(Option<Box<ImageDef>>),
@@ -971,9 +1145,9 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct TextDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub text_rect: [RectDefRaw; MAX_LOCAL_CLIENTS],
     pub alignment: i32,
     pub font_enum: i32,
@@ -1009,7 +1183,8 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> Default for TextDefRaw<'a, MAX_LOCAL_CL
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TextDef<const MAX_LOCAL_CLIENTS: usize> {
     #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub text_rect: [RectDef; MAX_LOCAL_CLIENTS],
@@ -1058,14 +1233,15 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<TextDef<MAX_LOCAL_
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct TextExpRaw<'a> {
     pub text_exp: ExpressionStatementRaw<'a>,
 }
 assert_size!(TextExpRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TextExp {
     pub text_exp: ExpressionStatement,
 }
@@ -1083,12 +1259,13 @@ impl<'a> XFileDeserializeInto<TextExp, ()> for TextExpRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct TextDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
 assert_size!(TextDefDataRaw<1>, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TextDefData<const MAX_LOCAL_CLIENTS: usize> {
     FocusItemDef(Option<Box<FocusItemDef<MAX_LOCAL_CLIENTS>>>),
     GameMsgDef(Option<Box<GameMsgDef>>),
@@ -1134,7 +1311,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct FocusItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub mouse_enter_text: XStringRaw<'a>,
     pub mouse_exit_text: XStringRaw<'a>,
@@ -1146,7 +1323,8 @@ pub(crate) struct FocusItemDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
 assert_size!(FocusItemDefRaw<1>, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FocusItemDef<const MAX_LOCAL_CLIENTS: usize> {
     pub mouse_enter_text: XString,
     pub mouse_exit_text: XString,
@@ -1184,12 +1362,13 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<FocusItemDef<MAX_L
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct FocusDefDataRaw<'a, const MAX_LOCAL_CLIENTS: usize>(Ptr32<'a, ()>);
 assert_size!(FocusDefDataRaw<1>, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FocusDefData<const MAX_LOCAL_CLIENTS: usize> {
     ListBox(Option<Box<ListBoxDef<MAX_LOCAL_CLIENTS>>>),
     Multi(Option<Box<MultiDef>>),
@@ -1255,14 +1434,14 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct ListBoxDefRaw<'a, const MAX_LOCAL_CLIENTS: usize> {
     pub mouse_pos: i32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub cursor_pos: [i32; MAX_LOCAL_CLIENTS],
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub start_pos: [i32; MAX_LOCAL_CLIENTS],
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub end_pos: [i32; MAX_LOCAL_CLIENTS],
     pub draw_padding: i32,
     pub element_width: f32,
@@ -1320,7 +1499,8 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> Default for ListBoxDefRaw<'a, MAX_LOCAL
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ListBoxDef<const MAX_LOCAL_CLIENTS: usize> {
     pub mouse_pos: i32,
     #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
@@ -1343,9 +1523,9 @@ pub struct ListBoxDef<const MAX_LOCAL_CLIENTS: usize> {
     pub focus_color: Vec4,
     pub element_highlight_color: Vec4,
     pub element_background_color: Vec4,
-    pub select_icon: Option<Box<Material>>,
-    pub background_item_listbox: Option<Box<Material>>,
-    pub highlight_texture: Option<Box<Material>>,
+    pub select_icon: Option<Shared<Material>>,
+    pub background_item_listbox: Option<Shared<Material>>,
+    pub highlight_texture: Option<Shared<Material>>,
     pub no_blinking_highlight: bool,
     pub rows: Vec<MenuRow>,
 }
@@ -1405,7 +1585,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<ListBoxDef<MAX_LOC
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ColumnInfoRaw {
     pub element_style: i32,
     pub max_chars: i32,
@@ -1414,7 +1594,8 @@ pub(crate) struct ColumnInfoRaw {
 assert_size!(ColumnInfoRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ColumnInfo {
     pub element_style: i32,
     pub max_chars: i32,
@@ -1434,12 +1615,13 @@ impl Into<ColumnInfo> for ColumnInfoRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MenuRowRaw<'a> {
     pub cells: Ptr32<'a, MenuCellRaw<'a>>,
     pub event_name: XStringRaw<'a>,
     pub on_focus_event_name: XStringRaw<'a>,
     pub disable_arg: bool,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub status: i32,
     pub name: i32,
@@ -1447,7 +1629,8 @@ pub(crate) struct MenuRowRaw<'a> {
 assert_size!(MenuRowRaw, 24);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MenuRow {
     pub cells: Vec<MenuCell>,
     pub event_name: XString,
@@ -1482,7 +1665,7 @@ impl<'a> XFileDeserializeInto<MenuRow, i32> for MenuRowRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MenuCellRaw<'a> {
     pub type_: i32,
     pub max_chars: i32,
@@ -1491,7 +1674,8 @@ pub(crate) struct MenuCellRaw<'a> {
 assert_size!(MenuCellRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MenuCell {
     pub type_: i32,
     pub max_chars: i32,
@@ -1515,7 +1699,7 @@ impl<'a> XFileDeserializeInto<MenuCell, ()> for MenuCellRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MultiDefRaw<'a> {
     pub dvar_list: [XStringRaw<'a>; 32],
     pub dvar_str: [XStringRaw<'a>; 32],
@@ -1527,7 +1711,8 @@ pub(crate) struct MultiDefRaw<'a> {
 assert_size!(MultiDefRaw, 396);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MultiDef {
     pub dvar_list: [XString; 32],
     pub dvar_str: [XString; 32],
@@ -1570,10 +1755,11 @@ impl<'a> XFileDeserializeInto<MultiDef, ()> for MultiDefRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, XFileRead)]
 pub struct EditFieldDef<const MAX_LOCAL_CLIENTS: usize> {
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub cursor_pos: [i32; MAX_LOCAL_CLIENTS],
     pub min_val: f32,
     pub max_val: f32,
@@ -1603,14 +1789,15 @@ impl<const MAX_LOCAL_CLIENTS: usize> Default for EditFieldDef<MAX_LOCAL_CLIENTS>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct EnumDvarDefRaw<'a> {
     pub enum_dvar_name: XStringRaw<'a>,
 }
 assert_size!(EnumDvarDefRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct EnumDvarDef {
     pub enum_dvar_name: XString,
 }
@@ -1627,8 +1814,9 @@ impl<'a> XFileDeserializeInto<EnumDvarDef, ()> for EnumDvarDefRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct GameMsgDef {
     pub game_msg_window_index: i32,
     pub game_msg_window_mode: i32,
@@ -1636,14 +1824,15 @@ pub struct GameMsgDef {
 assert_size!(GameMsgDef, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct ImageDefRaw<'a> {
     pub material_exp: ExpressionStatementRaw<'a>,
 }
 assert_size!(ImageDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ImageDef {
     pub material_exp: ExpressionStatement,
 }
@@ -1661,14 +1850,15 @@ impl<'a> XFileDeserializeInto<ImageDef, ()> for ImageDefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct OwnerDrawDefRaw<'a> {
     pub data_exp: ExpressionStatementRaw<'a>,
 }
 assert_size!(OwnerDrawDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct OwnerDrawDef {
     pub data_exp: ExpressionStatement,
 }
@@ -1686,7 +1876,7 @@ impl<'a> XFileDeserializeInto<OwnerDrawDef, ()> for OwnerDrawDefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct RectDataRaw<'a> {
     pub rect_x_exp: ExpressionStatementRaw<'a>,
     pub rect_y_exp: ExpressionStatementRaw<'a>,
@@ -1696,7 +1886,8 @@ pub(crate) struct RectDataRaw<'a> {
 assert_size!(RectDataRaw, 64);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct RectData {
     pub rect_x_exp: ExpressionStatement,
     pub rect_y_exp: ExpressionStatement,
@@ -1725,7 +1916,7 @@ impl<'a> XFileDeserializeInto<RectData, ()> for RectDataRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct UIAnimInfoRaw<'a> {
     pub anim_states: FatPointerCountFirstU32<'a, Ptr32<'a, AnimParamsDefRaw<'a>>>,
     pub current_anim_state: AnimParamsDefRaw<'a>,
@@ -1737,7 +1928,8 @@ pub(crate) struct UIAnimInfoRaw<'a> {
 assert_size!(UIAnimInfoRaw, 236);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct UIAnimInfo {
     pub anim_states: Vec<Box<AnimParamsDef>>,
     pub current_anim_state: AnimParamsDef,
@@ -1753,12 +1945,8 @@ impl<'a> XFileDeserializeInto<UIAnimInfo, ()> for UIAnimInfoRaw<'a> {
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<UIAnimInfo> {
-        let anim_states = self
-            .anim_states
-            .xfile_deserialize_into(de, ())?
-            .into_iter()
-            .flatten()
-            .collect();
+        let anim_states: Vec<Option<Box<_>>> = self.anim_states.xfile_deserialize_into(de, ())?;
+        let anim_states = anim_states.into_iter().flatten().collect();
         let current_anim_state = self.current_anim_state.xfile_deserialize_into(de, ())?;
         let next_anim_state = self.next_anim_state.xfile_deserialize_into(de, ())?;
         let animating = self.animating != 0;
@@ -1775,7 +1963,7 @@ impl<'a> XFileDeserializeInto<UIAnimInfo, ()> for UIAnimInfoRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct AnimParamsDefRaw<'a> {
     pub name: XStringRaw<'a>,
     pub rect_client: RectDefRaw,
@@ -1791,7 +1979,8 @@ pub(crate) struct AnimParamsDefRaw<'a> {
 assert_size!(AnimParamsDefRaw, 108);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct AnimParamsDef {
     pub name: XString,
     pub rect_client: RectDef,
@@ -1,17 +1,26 @@
-use alloc::{ffi::CString, string::ToString, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    FatPointer, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size, common::Vec4,
+    Error, ErrorKind, FatPointerCountLastU32, Ptr32, Result, T5XFileDeserialize,
+    T5XFileDeserializeBorrowed, XArray, XFileDeserializeBorrowed, XFileDeserializeInto, XFileRead,
+    XString, XStringRaw, assert_size, common::Vec4, csv, file_line_col,
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct RawFileRaw<'a> {
     pub name: XStringRaw<'a>,
     pub len: i32,
@@ -20,7 +29,8 @@ pub(crate) struct RawFileRaw<'a> {
 assert_size!(RawFileRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RawFile {
     pub name: XString,
     pub buffer: Vec<u8>,
@@ -34,25 +44,64 @@ impl<'a> XFileDeserializeInto<RawFile, ()> for RawFileRaw<'a> {
     ) -> Result<RawFile> {
         //dbg!(&self);
         let name = self.name.xfile_deserialize_into(de, ())?;
-        let buffer = self.buffer.to_array(self.len as usize + 1).to_vec(de)?;
+        let buffer = self
+            .buffer
+            .to_array(self.len as usize + 1)
+            .to_vec_bulk(de)?;
         Ok(RawFile { name, buffer })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for RawFile {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = XStringRaw::from_u32(0xFFFFFFFF);
-        let len = self.buffer.len() as _;
+        let name = XStringRaw::from_str(self.name.get());
+        // `buffer` includes the trailing NUL, `len` doesn't.
+        let len = self.buffer.len().saturating_sub(1) as _;
         let buffer = Ptr32::unreal();
         let raw_file = RawFileRaw { name, len, buffer };
 
         ser.store_into_xfile(raw_file)?;
+        self.name.xfile_serialize(ser, ())?;
         self.buffer.xfile_serialize(ser, ())
     }
 }
 
+/// Borrowed counterpart of [`RawFile`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawFileRef<'buf> {
+    pub name: XString,
+    pub buffer: &'buf [u8],
+}
+
+impl<'buf> RawFileRef<'buf> {
+    pub fn to_owned(&self) -> RawFile {
+        RawFile {
+            name: self.name.clone(),
+            buffer: self.buffer.to_vec(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, RawFileRef<'buf>, ()> for RawFileRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<RawFileRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let buffer = if self.buffer.is_null() || self.buffer.is_real() {
+            &[]
+        } else {
+            de.load_slice(self.len as usize + 1)?
+        };
+        Ok(RawFileRef { name, buffer })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct StringTableRaw<'a> {
     pub name: XStringRaw<'a>,
     pub column_count: i32,
@@ -63,7 +112,8 @@ pub(crate) struct StringTableRaw<'a> {
 assert_size!(StringTableRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct StringTable {
     pub name: XString,
     pub column_count: usize,
@@ -78,7 +128,7 @@ impl<'a> XFileDeserializeInto<StringTable, ()> for StringTableRaw<'a> {
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<StringTable> {
-        let size = self.column_count as usize * self.row_count as usize;
+        let size = (self.column_count as usize).saturating_mul(self.row_count as usize);
 
         Ok(StringTable {
             name: self.name.xfile_deserialize_into(de, ())?,
@@ -90,6 +140,7 @@ impl<'a> XFileDeserializeInto<StringTable, ()> for StringTableRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for StringTable {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -108,13 +159,140 @@ impl XFileSerialize<()> for StringTable {
 
         ser.store_into_xfile(string_table)?;
         self.name.xfile_serialize(ser, ())?;
-        self.values.xfile_serialize(ser, ())?;
+        // The cells are loaded as an array, so all of them come before any
+        // of their names.
+        for cell in &self.values {
+            ser.store_into_xfile(StringTableCellRaw {
+                name: XStringRaw::from_str(cell.name.get()),
+                hash: cell.hash,
+            })?;
+        }
+        for cell in &self.values {
+            cell.name.xfile_serialize(ser, ())?;
+        }
         self.cell_index.xfile_serialize(ser, ())
     }
 }
 
+/// Hashes a string the same way the engine's `StringTable_HashString` does
+/// for [`StringTableCell::hash`]: `hash = tolower(c) + 31 * hash` for each
+/// byte, starting from 0.
+pub fn string_table_hash(s: &str) -> i32 {
+    s.bytes().fold(0i32, |hash, c| {
+        (c.to_ascii_lowercase() as i32).wrapping_add(hash.wrapping_mul(31))
+    })
+}
+
+impl StringTable {
+    /// Returns the string at (`row`, `column`), or [`None`] if either is out
+    /// of bounds.
+    pub fn cell(&self, row: usize, column: usize) -> Option<&str> {
+        if row >= self.row_count || column >= self.column_count {
+            return None;
+        }
+
+        self.values
+            .get(row * self.column_count + column)
+            .map(|c| c.name.get())
+    }
+
+    /// Returns the cells of `row`, or [`None`] if `row` is out of bounds.
+    pub fn row(&self, row: usize) -> Option<&[StringTableCell]> {
+        if row >= self.row_count {
+            return None;
+        }
+
+        self.values
+            .get(row * self.column_count..(row + 1) * self.column_count)
+    }
+
+    /// Converts the table to CSV, one record per row.
+    ///
+    /// String tables don't have a header row, so neither does the output.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for row in 0..self.row_count {
+            csv::write_record(
+                &mut out,
+                (0..self.column_count).map(|column| self.cell(row, column).unwrap_or_default()),
+            );
+        }
+        out
+    }
+
+    /// Builds a string table named `name` from CSV.
+    ///
+    /// The column count is the length of the longest record, and shorter
+    /// records are padded with empty cells (spreadsheets tend to drop
+    /// trailing empty cells when saving). Cell hashes and
+    /// [`Self::cell_index`] are recomputed.
+    pub fn from_csv(name: &str, csv: &str) -> Result<Self> {
+        let records = csv::parse(csv)?;
+
+        let row_count = records.len();
+        let column_count = records.iter().map(Vec::len).max().unwrap_or_default();
+
+        if row_count > i16::MAX as usize {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Parse(format!("StringTable: row_count ({row_count}) > i16::MAX")),
+            ));
+        }
+
+        let mut values = Vec::with_capacity(row_count * column_count);
+        for record in records {
+            let padding = column_count - record.len();
+            for cell in record
+                .into_iter()
+                .chain(core::iter::repeat_n(String::new(), padding))
+            {
+                values.push(StringTableCell {
+                    hash: string_table_hash(&cell),
+                    name: XString(cell),
+                });
+            }
+        }
+
+        let mut table = StringTable {
+            name: XString(name.to_string()),
+            column_count,
+            row_count,
+            values,
+            cell_index: Vec::new(),
+        };
+        table.rebuild_cell_index();
+
+        Ok(table)
+    }
+
+    /// Recomputes [`Self::cell_index`] from [`Self::values`].
+    ///
+    /// For each column, the engine expects `cell_index` to hold that column's
+    /// row numbers sorted by cell hash (ties broken by row number), so it can
+    /// binary search for a value.
+    pub fn rebuild_cell_index(&mut self) {
+        let mut cell_index = vec![0i16; self.row_count * self.column_count];
+
+        for column in 0..self.column_count {
+            let rows = &mut cell_index[column * self.row_count..(column + 1) * self.row_count];
+            for (row, r) in rows.iter_mut().enumerate() {
+                *r = row as _;
+            }
+
+            rows.sort_by_key(|&row| {
+                (
+                    self.values[row as usize * self.column_count + column].hash,
+                    row,
+                )
+            });
+        }
+
+        self.cell_index = cell_index;
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct StringTableCellRaw<'a> {
     pub name: XStringRaw<'a>,
     pub hash: i32,
@@ -122,7 +300,8 @@ pub(crate) struct StringTableCellRaw<'a> {
 assert_size!(StringTableCellRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct StringTableCell {
     pub name: XString,
     pub hash: i32,
@@ -141,6 +320,7 @@ impl<'a> XFileDeserializeInto<StringTableCell, ()> for StringTableCellRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for StringTableCell {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -156,7 +336,7 @@ impl XFileSerialize<()> for StringTableCell {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PackIndexRaw<'a> {
     pub name: XStringRaw<'a>,
     pub header: PackIndexHeaderRaw,
@@ -165,7 +345,8 @@ pub(crate) struct PackIndexRaw<'a> {
 assert_size!(PackIndexRaw, 28);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PackIndex {
     pub name: XString,
     pub header: PackIndexHeader,
@@ -189,6 +370,7 @@ impl<'a> XFileDeserializeInto<PackIndex, ()> for PackIndexRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PackIndex {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -203,13 +385,12 @@ impl XFileSerialize<()> for PackIndex {
 
         ser.store_into_xfile(pack_index)?;
         self.name.xfile_serialize(ser, ())?;
-        self.header.xfile_serialize(ser, ())?;
         self.entries.xfile_serialize(ser, ())
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PackIndexHeaderRaw {
     pub magic: u32,
     pub timestamp: u32,
@@ -220,7 +401,8 @@ pub(crate) struct PackIndexHeaderRaw {
 assert_size!(PackIndexHeaderRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PackIndexHeader {
     pub magic: u32,
     pub timestamp: u32,
@@ -253,6 +435,7 @@ impl From<PackIndexHeader> for PackIndexHeaderRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PackIndexHeader {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let pack_index_header = PackIndexHeaderRaw {
@@ -268,7 +451,7 @@ impl XFileSerialize<()> for PackIndexHeader {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PackIndexEntryRaw {
     pub hash: u32,
     pub offset: u32,
@@ -277,7 +460,8 @@ pub(crate) struct PackIndexEntryRaw {
 assert_size!(PackIndexEntryRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PackIndexEntry {
     pub hash: u32,
     pub offset: usize,
@@ -294,9 +478,10 @@ impl From<PackIndexEntryRaw> for PackIndexEntry {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PackIndexEntry {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let pack_index_entry = PackIndexEntry {
+        let pack_index_entry = PackIndexEntryRaw {
             hash: self.hash,
             offset: self.offset as _,
             size: self.size as _,
@@ -307,7 +492,7 @@ impl XFileSerialize<()> for PackIndexEntry {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct MapEntsRaw<'a> {
     pub name: XStringRaw<'a>,
     pub entity_string: FatPointerCountLastU32<'a, u8>,
@@ -315,7 +500,8 @@ pub(crate) struct MapEntsRaw<'a> {
 assert_size!(MapEntsRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MapEnts {
     pub name: XString,
     pub entity_string: XString,
@@ -329,24 +515,22 @@ impl<'a> XFileDeserializeInto<MapEnts, ()> for MapEntsRaw<'a> {
     ) -> Result<MapEnts> {
         let name = self.name.xfile_deserialize_into(de, ())?;
 
-        let mut chars = self.entity_string.to_vec(de)?;
-        if chars.is_empty() {
-            return Ok(MapEnts {
-                name,
-                entity_string: XString::new(),
-            });
-        }
-
-        if *chars.last().unwrap() != b'\0' {
-            chars.push(b'\0');
-        }
-
-        let entity_string = XString(
-            CString::from_vec_with_nul(chars)
-                .unwrap()
-                .to_string_lossy()
-                .to_string(),
-        );
+        let start = de.stream_pos()?;
+        let chars = self.entity_string.to_vec_bulk(de)?;
+        let len = chars
+            .iter()
+            .position(|&c| c == b'\0')
+            .unwrap_or(chars.len());
+        let entity_string = de
+            .string_encoding()
+            .decode(&chars[..len])
+            .map(XString)
+            .map_err(|e| match e.kind() {
+                ErrorKind::BadChar(c) => {
+                    Error::new_with_offset(file_line_col!(), start as _, ErrorKind::BadChar(*c))
+                }
+                _ => e,
+            })?;
 
         Ok(MapEnts {
             name,
@@ -355,15 +539,16 @@ impl<'a> XFileDeserializeInto<MapEnts, ()> for MapEntsRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MapEnts {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
-        let bytes = self
-            .entity_string
-            .get()
-            .chars()
-            .map(|c| c as u8)
-            .collect::<Vec<_>>();
+        let mut bytes = ser.string_encoding().encode(self.entity_string.get())?;
+        // it'd end the entity string early
+        if bytes.contains(&b'\0') {
+            return Err(Error::new(file_line_col!(), ErrorKind::BadChar(0)));
+        }
+        bytes.push(b'\0');
         let entity_string = FatPointerCountLastU32::from_slice(&bytes);
 
         let map_ents = MapEntsRaw {
@@ -373,12 +558,12 @@ impl XFileSerialize<()> for MapEnts {
 
         ser.store_into_xfile(map_ents)?;
         self.name.xfile_serialize(ser, ())?;
-        self.entity_string.xfile_serialize(ser, ())
+        bytes.xfile_serialize(ser, ())
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct LocalizeEntryRaw<'a> {
     pub value: XStringRaw<'a>,
     pub name: XStringRaw<'a>,
@@ -386,7 +571,8 @@ pub(crate) struct LocalizeEntryRaw<'a> {
 assert_size!(LocalizeEntryRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct LocalizeEntry {
     pub value: XString,
     pub name: XString,
@@ -405,6 +591,7 @@ impl<'a> XFileDeserializeInto<LocalizeEntry, ()> for LocalizeEntryRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for LocalizeEntry {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let value = XStringRaw::from_str(self.value.get());
@@ -419,7 +606,7 @@ impl XFileSerialize<()> for LocalizeEntry {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct XGlobalsRaw<'a> {
     pub name: XStringRaw<'a>,
     pub xanim_stream_buffer_size: i32,
@@ -431,7 +618,8 @@ pub(crate) struct XGlobalsRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XGlobals {
     pub name: XString,
     pub xanim_stream_buffer_size: i32,
@@ -460,6 +648,7 @@ impl<'a> XFileDeserializeInto<XGlobals, ()> for XGlobalsRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XGlobals {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
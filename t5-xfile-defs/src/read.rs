@@ -0,0 +1,231 @@
+//! Decoding of the fixed-layout Raw structs, without going through serde.
+//!
+//! Raw structs are laid out field after field with no padding or length
+//! prefixes, so all decoding them takes is reading each field's bytes in
+//! order and swapping them if the file's byte order calls for it. That's
+//! what [`XFileRead`] does, and it's derived for the Raw structs with
+//! `#[derive(XFileRead)]` from `t5-xfile-derive`. Where the bytes come from
+//! is up to the [`XFileReader`] passed in.
+//!
+//! Decoding produces the same values bincode's fixint encoding does (e.g.,
+//! [`usize`]s are 8 bytes), so Raw structs that are also serde types read
+//! the same either way.
+
+use core::{marker::PhantomData, mem::MaybeUninit};
+
+use alloc::{format, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{Error, ErrorKind, PtrMarker, Result, file_line_col};
+
+pub use t5_xfile_derive::XFileRead;
+
+/// A field of a Raw struct, or an element of an array, as reported to
+/// [`XFileReader::enter_field`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    Named(&'static str),
+    Index(usize),
+}
+
+/// Where [`XFileRead`] gets its bytes from.
+pub trait XFileReader {
+    /// Returns `true` if multi-byte values are stored little-endian.
+    fn is_little_endian(&self) -> bool;
+
+    /// Fills `buf` with the next `buf.len()` bytes.
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    /// Whether [`Self::enter_field`] and [`Self::leave_field`] should be
+    /// called. The default implementation returns `false`, so readers that
+    /// don't trace don't pay for it.
+    fn is_tracing(&self) -> bool {
+        false
+    }
+
+    /// Called before `field` is read, and followed by [`Self::leave_field`]
+    /// once it has been, if [`Self::is_tracing`] returns `true`.
+    fn enter_field(&mut self, _field: Field) {}
+
+    fn leave_field(&mut self) {}
+
+    /// Called after an inline pointer is read, with the marker it was
+    /// serialized with (see [`PtrMarker`]).
+    fn ptr_marker(&mut self, _marker: PtrMarker) {}
+}
+
+/// A type that can be decoded from an [`XFileReader`].
+pub trait XFileRead: Sized {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self>;
+}
+
+fn traced<T: XFileRead, R: XFileReader + ?Sized>(r: &mut R, field: Field) -> Result<T> {
+    if !r.is_tracing() {
+        return T::xfile_read(r);
+    }
+
+    r.enter_field(field);
+    let t = T::xfile_read(r);
+    r.leave_field();
+    t
+}
+
+/// Reads the field `name` of a Raw struct. Used by `#[derive(XFileRead)]`.
+#[doc(hidden)]
+pub fn read_field<T: XFileRead, R: XFileReader + ?Sized>(
+    r: &mut R,
+    name: &'static str,
+) -> Result<T> {
+    traced(r, Field::Named(name))
+}
+
+/// Reads straight out of a byte slice, for [`from_bytes`].
+struct SliceReader<'b> {
+    bytes: &'b [u8],
+    little_endian: bool,
+}
+
+impl XFileReader for SliceReader<'_> {
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        let Some((bytes, rest)) = self.bytes.split_at_checked(buf.len()) else {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "{} bytes needed, but only {} are left",
+                    buf.len(),
+                    self.bytes.len()
+                )),
+            ));
+        };
+        buf.copy_from_slice(bytes);
+        self.bytes = rest;
+        Ok(())
+    }
+}
+
+/// Decodes a `T` from the start of `bytes`, which are in the file's byte
+/// order.
+///
+/// This is how the members of unions, which are loaded as plain bytes
+/// since which member is active isn't known until later, are decoded once
+/// it is.
+pub fn from_bytes<T: XFileRead>(bytes: &[u8], little_endian: bool) -> Result<T> {
+    T::xfile_read(&mut SliceReader {
+        bytes,
+        little_endian,
+    })
+}
+
+/// Reads straight out of a byte slice like [`SliceReader`], noting where
+/// each inline pointer was read from, for [`ptr_markers`].
+struct PtrMarkerReader<'b> {
+    reader: SliceReader<'b>,
+    len: usize,
+    markers: Vec<(usize, PtrMarker)>,
+}
+
+impl XFileReader for PtrMarkerReader<'_> {
+    fn is_little_endian(&self) -> bool {
+        self.reader.is_little_endian()
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.reader.read_bytes(buf)
+    }
+
+    fn ptr_marker(&mut self, marker: PtrMarker) {
+        let pos = self.len - self.reader.bytes.len() - size_of::<u32>();
+        self.markers.push((pos, marker));
+    }
+}
+
+/// Decodes a `T` from the start of `bytes` like [`from_bytes`], but only
+/// returns where in `bytes` each of its inline pointers is, along with its
+/// marker, in order.
+pub fn ptr_markers<T: XFileRead>(
+    bytes: &[u8],
+    little_endian: bool,
+) -> Result<Vec<(usize, PtrMarker)>> {
+    let mut r = PtrMarkerReader {
+        reader: SliceReader {
+            bytes,
+            little_endian,
+        },
+        len: bytes.len(),
+        markers: Vec::new(),
+    };
+    T::xfile_read(&mut r)?;
+    Ok(r.markers)
+}
+
+macro_rules! impl_xfile_read_primitive {
+    ($($t:ty,)+) => {
+        $(
+            impl XFileRead for $t {
+                fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+                    let mut bytes = [0u8; size_of::<$t>()];
+                    r.read_bytes(&mut bytes)?;
+                    Ok(if r.is_little_endian() {
+                        <$t>::from_le_bytes(bytes)
+                    } else {
+                        <$t>::from_be_bytes(bytes)
+                    })
+                }
+            }
+        )+
+    };
+}
+
+impl_xfile_read_primitive!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64,);
+
+impl XFileRead for usize {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+        u64::xfile_read(r).map(|u| u as _)
+    }
+}
+
+impl XFileRead for bool {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+        match u8::xfile_read(r)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!("invalid bool {b}")),
+            )),
+        }
+    }
+}
+
+impl XFileRead for () {
+    fn xfile_read<R: XFileReader + ?Sized>(_r: &mut R) -> Result<Self> {
+        Ok(())
+    }
+}
+
+impl<T> XFileRead for PhantomData<T> {
+    fn xfile_read<R: XFileReader + ?Sized>(_r: &mut R) -> Result<Self> {
+        Ok(PhantomData)
+    }
+}
+
+impl<T: XFileRead, const N: usize> XFileRead for [T; N] {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+        let mut arr = [const { MaybeUninit::<T>::uninit() }; N];
+        for (i, elem) in arr.iter_mut().enumerate() {
+            // on error, the elements read so far are leaked, which is fine
+            // since Raw structs don't own anything
+            elem.write(traced(r, Field::Index(i))?);
+        }
+
+        // SAFETY: every element was just initialized, and `[MaybeUninit<T>; N]`
+        // has the same layout as `[T; N]`
+        Ok(unsafe { core::mem::transmute_copy(&arr) })
+    }
+}
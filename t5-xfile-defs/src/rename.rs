@@ -0,0 +1,285 @@
+//! Renaming assets, e.g. to avoid name collisions when porting them from one
+//! zone to another.
+//!
+//! Assets refer to each other by name, and most references also hold a copy
+//! of the referenced asset (e.g. every [`XModel`] holds its [`Material`]s),
+//! so [`XAssetBag::rename_asset`] renames both the asset and every
+//! reference to it the bag's other assets have.
+
+use alloc::{format, string::String};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, XString, file_line_col,
+    techset::{Material, MaterialTextureDefInfo},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+#[cfg(feature = "models")]
+use crate::xmodel::XModel;
+#[cfg(any(feature = "models", feature = "fx"))]
+use crate::{Shared, access::HasImages};
+#[cfg(feature = "fx")]
+use crate::{fx::FxEffectDef, weapon::WeaponVariantDef};
+
+/// Renames every reference to one asset.
+struct Renamer<'a> {
+    asset_type: XAssetType,
+    old: &'a str,
+    new: &'a str,
+    /// The number of references renamed so far.
+    renamed: usize,
+}
+
+impl Renamer<'_> {
+    fn matches(&self, asset_type: XAssetType, name: &XString) -> bool {
+        asset_type == self.asset_type && name.get() == self.old
+    }
+
+    fn rename(&mut self, asset_type: XAssetType, name: &mut XString) {
+        if self.matches(asset_type, name) {
+            *name = XString(String::from(self.new));
+            self.renamed += 1;
+        }
+    }
+
+    /// Whether [`Self::material`] would rename anything, so shared materials
+    /// are only made unique (see [`shared_mut`]) when they have to be.
+    #[cfg(any(feature = "models", feature = "fx"))]
+    fn material_refers(&self, material: &Material) -> bool {
+        self.matches(XAssetType::MATERIAL, &material.info.name)
+            || material
+                .technique_set
+                .as_ref()
+                .is_some_and(|techset| self.matches(XAssetType::TECHNIQUE_SET, &techset.name))
+            || material
+                .images()
+                .iter()
+                .any(|image| self.matches(XAssetType::IMAGE, &image.name))
+    }
+
+    fn material(&mut self, material: &mut Material) {
+        self.rename(XAssetType::MATERIAL, &mut material.info.name);
+        if let Some(techset) = &mut material.technique_set {
+            self.rename(XAssetType::TECHNIQUE_SET, &mut techset.name);
+        }
+        for texture in &mut material.textures {
+            if let MaterialTextureDefInfo::Image(Some(image)) = &mut texture.u {
+                self.rename(XAssetType::IMAGE, &mut image.name);
+            }
+        }
+    }
+
+    #[cfg(feature = "fx")]
+    fn shared_material(&mut self, material: &mut Option<Shared<Material>>) {
+        if let Some(material) = material
+            && self.material_refers(material)
+        {
+            self.material(shared_mut(material));
+        }
+    }
+
+    #[cfg(feature = "models")]
+    fn xmodel(&mut self, model: &mut XModel) {
+        self.rename(XAssetType::XMODEL, &mut model.name);
+        for material in &mut model.material_handles {
+            if self.material_refers(material) {
+                self.material(shared_mut(material));
+            }
+        }
+        if let Some(preset) = &mut model.phys_preset {
+            self.rename(XAssetType::PHYSPRESET, &mut preset.name);
+        }
+        if let Some(constraints) = &mut model.phys_constraints {
+            self.rename(XAssetType::PHYSCONSTRAINTS, &mut constraints.name);
+        }
+    }
+
+    #[cfg(feature = "fx")]
+    fn fx(&mut self, fx: &mut Option<Box<FxEffectDef>>) {
+        if let Some(fx) = fx {
+            self.rename(XAssetType::FX, &mut fx.name);
+        }
+    }
+
+    /// The same references as [`WeaponVariantDef::dependencies`], other
+    /// than sound aliases, which aren't assets.
+    #[cfg(feature = "fx")]
+    fn weapon(&mut self, weapon: &mut WeaponVariantDef) {
+        for anim in &mut weapon.xanims {
+            self.rename(XAssetType::XANIMPARTS, anim);
+        }
+        for material in [
+            &mut weapon.overlay_material,
+            &mut weapon.overlay_material_low_res,
+            &mut weapon.dpad_icon,
+        ] {
+            self.shared_material(material);
+        }
+
+        let Some(def) = &mut weapon.weap_def else {
+            return;
+        };
+
+        let models = def.gun_xmodel.iter_mut().flatten().chain(
+            def.world_model
+                .iter_mut()
+                .flat_map(|models| models.iter_mut()),
+        );
+        for model in models.flatten() {
+            self.xmodel(model);
+        }
+        for model in [
+            &mut def.hand_xmodel,
+            &mut def.world_clip_model,
+            &mut def.rocket_model,
+            &mut def.mounted_model,
+            &mut def.additional_melee_model,
+            &mut def.projectile_model,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            self.xmodel(model);
+        }
+
+        for material in [
+            &mut def.reticle_center,
+            &mut def.reticle_side,
+            &mut def.hud_icon,
+            &mut def.indicator_icon,
+            &mut def.ammo_counter_icon,
+            &mut def.kill_icon,
+        ] {
+            self.shared_material(material);
+        }
+
+        for fx in [
+            &mut def.view_flash_effect,
+            &mut def.world_flash_effect,
+            &mut def.view_shell_eject_effect,
+            &mut def.world_shell_eject_effect,
+            &mut def.view_last_shot_eject_effect,
+            &mut def.world_last_shot_eject_effect,
+            &mut def.proj_explosion_effect,
+            &mut def.proj_explosion_effect_2,
+            &mut def.proj_explosion_effect_3,
+            &mut def.proj_explosion_effect_4,
+            &mut def.proj_explosion_effect_5,
+            &mut def.proj_dud_effect,
+            &mut def.proj_tail_effect,
+            &mut def.proj_ignition_effect,
+            &mut def.tag_fx_preparation_effect,
+            &mut def.tag_flash_preparation_effect,
+        ] {
+            self.fx(fx);
+        }
+    }
+
+    fn asset<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        asset: &mut XAssetGeneric<MAX_LOCAL_CLIENTS>,
+    ) {
+        match asset {
+            XAssetGeneric::Material(Some(material)) => self.material(material),
+            #[cfg(feature = "models")]
+            XAssetGeneric::XModel(Some(model)) => self.xmodel(model),
+            #[cfg(feature = "fx")]
+            XAssetGeneric::Weapon(Some(weapon)) => self.weapon(weapon),
+            _ => {}
+        }
+    }
+}
+
+/// Gets at a shared asset to change it. With the `arc` feature, the asset is
+/// cloned first if it's shared with other references.
+#[cfg(all(any(feature = "models", feature = "fx"), not(feature = "arc")))]
+fn shared_mut<T>(shared: &mut Shared<T>) -> &mut T {
+    shared
+}
+
+#[cfg(all(any(feature = "models", feature = "fx"), feature = "arc"))]
+fn shared_mut<T: Clone>(shared: &mut Shared<T>) -> &mut T {
+    alloc::sync::Arc::make_mut(shared)
+}
+
+fn bad_rename(reason: String) -> Error {
+    Error::new(file_line_col!(), ErrorKind::BadRename(reason))
+}
+
+impl XAssetBag {
+    /// Renames the asset of type `asset_type` named `old` to `new`, along
+    /// with every reference to it in the bag's other assets (and the copies
+    /// of it those references hold).
+    ///
+    /// Only the references [`XAsset::dependencies`] knows about are renamed.
+    /// References by script string aren't stored in the bag; see
+    /// [`rename_script_strings`] for those.
+    ///
+    /// Returns the number of references renamed, not counting the asset
+    /// itself. Fails with [`ErrorKind::BadRename`] if there's no such asset,
+    /// if its name can't be changed (see [`XAsset::name_mut`]), or if
+    /// another asset of the same type is already named `new`.
+    pub fn rename_asset(&mut self, asset_type: XAssetType, old: &str, new: &str) -> Result<usize> {
+        if !self.contains(asset_type, old) {
+            return Err(bad_rename(format!("there's no {asset_type:?} named {old}")));
+        }
+        if old == new {
+            return Ok(0);
+        }
+        if self.contains(asset_type, new) {
+            return Err(bad_rename(format!(
+                "there's already a {asset_type:?} named {new}"
+            )));
+        }
+
+        let mut assets = core::mem::take(self).into_vec();
+
+        let i = assets
+            .iter()
+            .position(|asset| asset.asset_type() == asset_type && asset.name() == Some(old))
+            .unwrap();
+        let result = match assets[i].name_mut() {
+            Some(name) => {
+                *name = XString(String::from(new));
+
+                let mut renamer = Renamer {
+                    asset_type,
+                    old,
+                    new,
+                    renamed: 0,
+                };
+                for asset in &mut assets {
+                    match asset {
+                        XAsset::PC(a) => renamer.asset(a),
+                        XAsset::Console(a) => renamer.asset(a),
+                    }
+                }
+                Ok(renamer.renamed)
+            }
+            None => Err(bad_rename(format!("{asset_type:?}s can't be renamed"))),
+        };
+
+        *self = assets.into_iter().collect();
+        result
+    }
+}
+
+/// Renames every script string that's `old` to `new`, returning how many
+/// there were.
+///
+/// Scripts refer to some assets (e.g. models and animations) by script
+/// strings, which are stored in the Fastfile separately from its assets.
+/// When a Fastfile is re-serialized with its original script strings (see
+/// `T5XFileSerializerBuilder::with_script_strings`), they have to be renamed
+/// along with the assets (see [`XAssetBag::rename_asset`]).
+pub fn rename_script_strings(script_strings: &mut [String], old: &str, new: &str) -> usize {
+    let mut renamed = 0;
+    for s in script_strings.iter_mut().filter(|s| *s == old) {
+        *s = String::from(new);
+        renamed += 1;
+    }
+    renamed
+}
@@ -0,0 +1,341 @@
+//! Decoding the render state packed into [`GfxStateBits`].
+//!
+//! The layout is the one T5 inherited from earlier IW engine games: blending,
+//! alpha testing, culling, and color writes in the first word, and depth,
+//! polygon offset, and stencil state in the second.
+
+use alloc::vec::Vec;
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, file_line_col,
+    techset::{GfxStateBits, Material},
+};
+
+use num::FromPrimitive;
+use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const GFXS0_SRCBLEND_RGB_SHIFT: u32 = 0;
+const GFXS0_DSTBLEND_RGB_SHIFT: u32 = 4;
+const GFXS0_BLENDOP_RGB_SHIFT: u32 = 8;
+const GFXS0_ATEST_DISABLE: u32 = 0x800;
+const GFXS0_ATEST_SHIFT: u32 = 12;
+const GFXS0_CULL_SHIFT: u32 = 14;
+const GFXS0_SRCBLEND_ALPHA_SHIFT: u32 = 16;
+const GFXS0_DSTBLEND_ALPHA_SHIFT: u32 = 20;
+const GFXS0_BLENDOP_ALPHA_SHIFT: u32 = 24;
+const GFXS0_COLORWRITE_RGB: u32 = 0x8000000;
+const GFXS0_COLORWRITE_ALPHA: u32 = 0x10000000;
+const GFXS0_POLYMODE_LINE: u32 = 0x80000000;
+
+const GFXS1_DEPTHWRITE: u32 = 0x1;
+const GFXS1_DEPTHTEST_DISABLE: u32 = 0x2;
+const GFXS1_DEPTHTEST_SHIFT: u32 = 2;
+const GFXS1_POLYGON_OFFSET_SHIFT: u32 = 4;
+const GFXS1_STENCIL_FRONT_ENABLE: u32 = 0x40;
+const GFXS1_STENCIL_BACK_ENABLE: u32 = 0x80;
+const GFXS1_STENCIL_FRONT_SHIFT: u32 = 8;
+const GFXS1_STENCIL_BACK_SHIFT: u32 = 20;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum Blend {
+    #[default]
+    DISABLED = 0,
+    ZERO = 1,
+    ONE = 2,
+    SRC_COLOR = 3,
+    INV_SRC_COLOR = 4,
+    SRC_ALPHA = 5,
+    INV_SRC_ALPHA = 6,
+    DEST_ALPHA = 7,
+    INV_DEST_ALPHA = 8,
+    DEST_COLOR = 9,
+    INV_DEST_COLOR = 10,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum BlendOp {
+    #[default]
+    DISABLED = 0,
+    ADD = 1,
+    SUBTRACT = 2,
+    REV_SUBTRACT = 3,
+    MIN = 4,
+    MAX = 5,
+}
+
+/// Which pixels are kept, by their alpha.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum AlphaTest {
+    #[default]
+    DISABLED = 0,
+    GT_0 = 1,
+    LT_128 = 2,
+    GE_128 = 3,
+}
+
+/// Which faces aren't drawn.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum CullMode {
+    #[default]
+    NONE = 1,
+    BACK = 2,
+    FRONT = 3,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum DepthTest {
+    #[default]
+    DISABLED = 0xFF,
+    ALWAYS = 0,
+    LESS = 1,
+    EQUAL = 2,
+    LESS_EQUAL = 3,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum PolygonOffset {
+    #[default]
+    NONE = 0,
+    ONE = 1,
+    TWO = 2,
+    SHADOW_MAP = 3,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum StencilOp {
+    #[default]
+    KEEP = 0,
+    ZERO = 1,
+    REPLACE = 2,
+    INCR_SAT = 3,
+    DECR_SAT = 4,
+    INVERT = 5,
+    INCR = 6,
+    DECR = 7,
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
+#[repr(u8)]
+pub enum StencilFunc {
+    NEVER = 0,
+    LESS = 1,
+    EQUAL = 2,
+    LESS_EQUAL = 3,
+    GREATER = 4,
+    NOT_EQUAL = 5,
+    GREATER_EQUAL = 6,
+    #[default]
+    ALWAYS = 7,
+}
+
+/// The stencil test for one side of the faces being drawn.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct StencilState {
+    pub pass: StencilOp,
+    pub fail: StencilOp,
+    pub zfail: StencilOp,
+    pub func: StencilFunc,
+}
+
+/// The decoded form of [`GfxStateBits`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct RenderState {
+    pub src_blend_rgb: Blend,
+    pub dst_blend_rgb: Blend,
+    pub blend_op_rgb: BlendOp,
+    pub src_blend_alpha: Blend,
+    pub dst_blend_alpha: Blend,
+    pub blend_op_alpha: BlendOp,
+    pub alpha_test: AlphaTest,
+    pub cull: CullMode,
+    pub color_write_rgb: bool,
+    pub color_write_alpha: bool,
+    pub wireframe: bool,
+    pub depth_write: bool,
+    pub depth_test: DepthTest,
+    pub polygon_offset: PolygonOffset,
+    /// [`None`] if stenciling is disabled for front faces.
+    pub stencil_front: Option<StencilState>,
+    /// [`None`] if stenciling is disabled for back faces.
+    pub stencil_back: Option<StencilState>,
+}
+
+/// Decodes the `mask`-wide field at `shift` in `bits`.
+fn field<T: FromPrimitive>(bits: u32, shift: u32, mask: u32) -> Result<T> {
+    let value = (bits >> shift) & mask;
+    T::from_u32(value).ok_or(Error::new(
+        file_line_col!(),
+        ErrorKind::BadFromPrimitive(value as _),
+    ))
+}
+
+impl StencilState {
+    fn decode(bits: u32, shift: u32) -> Result<Self> {
+        Ok(Self {
+            pass: field(bits, shift, 0x7)?,
+            fail: field(bits, shift + 3, 0x7)?,
+            zfail: field(bits, shift + 6, 0x7)?,
+            func: field(bits, shift + 9, 0x7)?,
+        })
+    }
+
+    fn encode(self, shift: u32) -> u32 {
+        ((self.pass as u32) << shift)
+            | ((self.fail as u32) << (shift + 3))
+            | ((self.zfail as u32) << (shift + 6))
+            | ((self.func as u32) << (shift + 9))
+    }
+}
+
+impl GfxStateBits {
+    /// Returns [`ErrorKind::BadFromPrimitive`] if a field has a value that
+    /// isn't one of its enum's variants.
+    pub fn render_state(self) -> Result<RenderState> {
+        let [bits0, bits1] = self.load_bits;
+
+        let alpha_test = if bits0 & GFXS0_ATEST_DISABLE != 0 {
+            AlphaTest::DISABLED
+        } else {
+            field(bits0, GFXS0_ATEST_SHIFT, 0x3)?
+        };
+        let depth_test = if bits1 & GFXS1_DEPTHTEST_DISABLE != 0 {
+            DepthTest::DISABLED
+        } else {
+            field(bits1, GFXS1_DEPTHTEST_SHIFT, 0x3)?
+        };
+        let stencil_front = (bits1 & GFXS1_STENCIL_FRONT_ENABLE != 0)
+            .then(|| StencilState::decode(bits1, GFXS1_STENCIL_FRONT_SHIFT))
+            .transpose()?;
+        let stencil_back = (bits1 & GFXS1_STENCIL_BACK_ENABLE != 0)
+            .then(|| StencilState::decode(bits1, GFXS1_STENCIL_BACK_SHIFT))
+            .transpose()?;
+
+        Ok(RenderState {
+            src_blend_rgb: field(bits0, GFXS0_SRCBLEND_RGB_SHIFT, 0xF)?,
+            dst_blend_rgb: field(bits0, GFXS0_DSTBLEND_RGB_SHIFT, 0xF)?,
+            blend_op_rgb: field(bits0, GFXS0_BLENDOP_RGB_SHIFT, 0x7)?,
+            src_blend_alpha: field(bits0, GFXS0_SRCBLEND_ALPHA_SHIFT, 0xF)?,
+            dst_blend_alpha: field(bits0, GFXS0_DSTBLEND_ALPHA_SHIFT, 0xF)?,
+            blend_op_alpha: field(bits0, GFXS0_BLENDOP_ALPHA_SHIFT, 0x7)?,
+            alpha_test,
+            cull: field(bits0, GFXS0_CULL_SHIFT, 0x3)?,
+            color_write_rgb: bits0 & GFXS0_COLORWRITE_RGB != 0,
+            color_write_alpha: bits0 & GFXS0_COLORWRITE_ALPHA != 0,
+            wireframe: bits0 & GFXS0_POLYMODE_LINE != 0,
+            depth_write: bits1 & GFXS1_DEPTHWRITE != 0,
+            depth_test,
+            polygon_offset: field(bits1, GFXS1_POLYGON_OFFSET_SHIFT, 0x3)?,
+            stencil_front,
+            stencil_back,
+        })
+    }
+}
+
+impl RenderState {
+    /// The reverse of [`GfxStateBits::render_state`].
+    pub fn to_state_bits(&self) -> GfxStateBits {
+        let flag = |set: bool, bit: u32| if set { bit } else { 0 };
+
+        let alpha_test = match self.alpha_test {
+            AlphaTest::DISABLED => GFXS0_ATEST_DISABLE,
+            test => (test as u32) << GFXS0_ATEST_SHIFT,
+        };
+        let bits0 = ((self.src_blend_rgb as u32) << GFXS0_SRCBLEND_RGB_SHIFT)
+            | ((self.dst_blend_rgb as u32) << GFXS0_DSTBLEND_RGB_SHIFT)
+            | ((self.blend_op_rgb as u32) << GFXS0_BLENDOP_RGB_SHIFT)
+            | alpha_test
+            | ((self.cull as u32) << GFXS0_CULL_SHIFT)
+            | ((self.src_blend_alpha as u32) << GFXS0_SRCBLEND_ALPHA_SHIFT)
+            | ((self.dst_blend_alpha as u32) << GFXS0_DSTBLEND_ALPHA_SHIFT)
+            | ((self.blend_op_alpha as u32) << GFXS0_BLENDOP_ALPHA_SHIFT)
+            | flag(self.color_write_rgb, GFXS0_COLORWRITE_RGB)
+            | flag(self.color_write_alpha, GFXS0_COLORWRITE_ALPHA)
+            | flag(self.wireframe, GFXS0_POLYMODE_LINE);
+
+        let depth_test = match self.depth_test {
+            DepthTest::DISABLED => GFXS1_DEPTHTEST_DISABLE,
+            test => (test as u32) << GFXS1_DEPTHTEST_SHIFT,
+        };
+        let stencil = |state: Option<StencilState>, enable: u32, shift: u32| {
+            state.map_or(0, |s| enable | s.encode(shift))
+        };
+        let bits1 = flag(self.depth_write, GFXS1_DEPTHWRITE)
+            | depth_test
+            | ((self.polygon_offset as u32) << GFXS1_POLYGON_OFFSET_SHIFT)
+            | stencil(
+                self.stencil_front,
+                GFXS1_STENCIL_FRONT_ENABLE,
+                GFXS1_STENCIL_FRONT_SHIFT,
+            )
+            | stencil(
+                self.stencil_back,
+                GFXS1_STENCIL_BACK_ENABLE,
+                GFXS1_STENCIL_BACK_SHIFT,
+            );
+
+        GfxStateBits {
+            load_bits: [bits0, bits1],
+        }
+    }
+
+    /// Whether the color written is blended with what's already there.
+    pub fn is_blended(&self) -> bool {
+        self.blend_op_rgb != BlendOp::DISABLED || self.blend_op_alpha != BlendOp::DISABLED
+    }
+}
+
+impl Material {
+    /// The render state the material uses for the technique at
+    /// `technique` (an index into the technique set's techniques, as in
+    /// [`Self::state_bits_entry`]), or [`None`] if it doesn't have one.
+    pub fn render_state(&self, technique: usize) -> Result<Option<RenderState>> {
+        let Some(&entry) = self.state_bits_entry.get(technique) else {
+            return Ok(None);
+        };
+
+        self.state_bits
+            .get(entry as usize)
+            .map(|bits| bits.render_state())
+            .transpose()
+    }
+
+    /// Every render state in [`Self::state_bits`], in order.
+    pub fn render_states(&self) -> Result<Vec<RenderState>> {
+        self.state_bits
+            .iter()
+            .map(|bits| bits.render_state())
+            .collect()
+    }
+}
@@ -0,0 +1,147 @@
+//! Swapping the materials an [`XModel`] is drawn with, e.g. when porting a
+//! model to a zone whose materials are named differently.
+//!
+//! Every surface of a model is drawn with the material at the same index of
+//! [`XModel::material_handles`], and phys constraints can have a material of
+//! their own, each of them a copy of the material asset.
+//! [`MaterialRetarget::retarget`] replaces the ones named in a mapping with
+//! copies of other materials, or, if those aren't available, with
+//! placeholders to be replaced later.
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, Shared, XString, file_line_col,
+    techset::Material,
+    xasset::{XAsset, XAssetBag, XAssetGeneric},
+    xmodel::XModel,
+};
+
+/// Which materials to replace, and what with. See the
+/// [module docs](self).
+#[derive(Clone, Debug, Default)]
+pub struct MaterialRetarget {
+    /// Old material name to new material name.
+    mapping: BTreeMap<String, String>,
+    /// The materials available to replace the old ones with, by name.
+    materials: BTreeMap<String, Shared<Material>>,
+    placeholders: bool,
+}
+
+/// What [`MaterialRetarget::retarget`] did.
+#[derive(Clone, Debug, Default)]
+pub struct MaterialRetargetReport {
+    /// The number of material references replaced.
+    pub retargeted: usize,
+    /// The placeholders generated for new names there was no material for,
+    /// which have to be added to the zone along with the model.
+    pub placeholders: Vec<Material>,
+}
+
+impl MaterialRetarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the material named `old` with the one named `new`.
+    pub fn with_mapping(mut self, old: impl Into<String>, new: impl Into<String>) -> Self {
+        self.mapping.insert(old.into(), new.into());
+        self
+    }
+
+    /// Adds `material` to the materials old ones can be replaced with.
+    pub fn with_material(mut self, material: impl Into<Shared<Material>>) -> Self {
+        let material = material.into();
+        self.materials
+            .insert(material.info.name.get().to_string(), material);
+        self
+    }
+
+    /// Adds every material asset in `bag` to the materials old ones can be
+    /// replaced with.
+    pub fn with_materials_from(mut self, bag: &XAssetBag) -> Self {
+        for asset in bag.iter() {
+            let material = match asset {
+                XAsset::PC(XAssetGeneric::Material(Some(material)))
+                | XAsset::Console(XAssetGeneric::Material(Some(material))) => material,
+                _ => continue,
+            };
+            self = self.with_material(Shared::new(material.as_ref().clone()));
+        }
+        self
+    }
+
+    /// Whether to generate placeholders for new names that don't have a
+    /// material (see [`Self::with_material`]) instead of failing. A
+    /// placeholder is a copy of the material it replaces under the new name,
+    /// so the model still looks the same until the real material exists.
+    pub fn with_placeholders(mut self, placeholders: bool) -> Self {
+        self.placeholders = placeholders;
+        self
+    }
+
+    /// Replaces the materials of `model` according to the mapping.
+    ///
+    /// Fails with [`ErrorKind::BadRetarget`], without changing `model`, if
+    /// a material is mapped to a name there's no material for and
+    /// placeholders are disabled (see [`Self::with_placeholders`]).
+    pub fn retarget(&self, model: &mut XModel) -> Result<MaterialRetargetReport> {
+        let constraint_materials = model
+            .phys_constraints
+            .iter_mut()
+            .flat_map(|constraints| constraints.data.iter_mut())
+            .filter_map(|constraint| constraint.material.as_mut());
+
+        // Find every replacement before changing anything
+        let mut report = MaterialRetargetReport::default();
+        let mut replacements = BTreeMap::<String, Shared<Material>>::new();
+        let mut targets = Vec::new();
+        for material in model
+            .material_handles
+            .iter_mut()
+            .chain(constraint_materials)
+        {
+            let Some(new) = self.mapping.get(material.info.name.get()) else {
+                continue;
+            };
+
+            if !replacements.contains_key(new) {
+                let replacement = match self.materials.get(new) {
+                    Some(replacement) => replacement.clone(),
+                    None if self.placeholders => {
+                        let mut placeholder = material.as_ref().clone();
+                        placeholder.info.name = XString(new.clone());
+                        report.placeholders.push(placeholder.clone());
+                        Shared::new(placeholder)
+                    }
+                    None => {
+                        return Err(Error::new(
+                            file_line_col!(),
+                            ErrorKind::BadRetarget(format!(
+                                "there's no material named {new} to replace {} with",
+                                material.info.name.get()
+                            )),
+                        ));
+                    }
+                };
+                replacements.insert(new.clone(), replacement);
+            }
+            targets.push((material, new));
+        }
+
+        for (material, new) in targets {
+            *material = replacements[new].clone();
+            report.retargeted += 1;
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,120 @@
+//! Access to the game's scripts, for disassemblers and decompilers.
+//!
+//! T5 has no `scriptparsetree` asset (that's T6); server (`.gsc`) and
+//! client (`.csc`) scripts are stored as [`RawFile`]s and compiled when the
+//! zone is loaded, so the buffer is whatever the linker was given. A
+//! [`ScriptDisassembler`] can be plugged in to turn it into something else,
+//! whether the buffer holds source or bytecode.
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    RawFile, RawFileRef, Result,
+    xasset::{XAsset, XAssetGeneric},
+};
+
+/// Which VM a script runs in.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScriptKind {
+    /// A server script (`.gsc`).
+    GSC,
+    /// A client script (`.csc`).
+    CSC,
+}
+
+impl ScriptKind {
+    /// The kind of script a file named `name` is, from its extension.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (_, ext) = name.rsplit_once('.')?;
+        if ext.eq_ignore_ascii_case("gsc") {
+            Some(Self::GSC)
+        } else if ext.eq_ignore_ascii_case("csc") {
+            Some(Self::CSC)
+        } else {
+            None
+        }
+    }
+}
+
+/// A script and its metadata, borrowed from the [`RawFile`] it's stored in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Script<'a> {
+    pub name: &'a str,
+    pub kind: ScriptKind,
+    /// The script's contents, without the trailing NUL the linker adds to
+    /// every raw file.
+    pub buffer: &'a [u8],
+}
+
+impl<'a> Script<'a> {
+    fn new(name: &'a str, buffer: &'a [u8]) -> Option<Self> {
+        let kind = ScriptKind::from_name(name)?;
+        let buffer = buffer.strip_suffix(&[0]).unwrap_or(buffer);
+        Some(Self { name, kind, buffer })
+    }
+
+    /// The length of [`Self::buffer`], which is the length the raw file
+    /// header stores.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// The script's source, if the buffer is UTF-8 text rather than
+    /// bytecode.
+    pub fn source(&self) -> Option<&'a str> {
+        let source = core::str::from_utf8(self.buffer).ok()?;
+        (!source.contains('\0')).then_some(source)
+    }
+
+    /// Runs `disassembler` on the script.
+    pub fn disassemble<D: ScriptDisassembler>(&self, disassembler: &mut D) -> Result<D::Output> {
+        disassembler.disassemble(self)
+    }
+}
+
+/// A disassembler or decompiler for scripts, so external tools can be
+/// plugged into code that walks a zone's assets.
+pub trait ScriptDisassembler {
+    type Output;
+
+    fn disassemble(&mut self, script: &Script<'_>) -> Result<Self::Output>;
+}
+
+impl<O, F: FnMut(&Script<'_>) -> Result<O>> ScriptDisassembler for F {
+    type Output = O;
+
+    fn disassemble(&mut self, script: &Script<'_>) -> Result<O> {
+        self(script)
+    }
+}
+
+impl RawFile {
+    /// The raw file as a [`Script`], if it's named like one.
+    pub fn script(&self) -> Option<Script<'_>> {
+        Script::new(self.name.get(), &self.buffer)
+    }
+}
+
+impl<'buf> RawFileRef<'buf> {
+    /// The raw file as a [`Script`], if it's named like one.
+    pub fn script(&self) -> Option<Script<'_>> {
+        Script::new(self.name.get(), self.buffer)
+    }
+}
+
+impl XAsset {
+    /// The asset as a [`Script`], if it's a raw file named like one.
+    pub fn script(&self) -> Option<Script<'_>> {
+        match self {
+            Self::PC(XAssetGeneric::RawFile(Some(r)))
+            | Self::Console(XAssetGeneric::RawFile(Some(r))) => r.script(),
+            _ => None,
+        }
+    }
+}
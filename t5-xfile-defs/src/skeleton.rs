@@ -0,0 +1,198 @@
+//! A model's bones as a tree, with the packed data in [`XModel`] decoded.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result,
+    common::{Mat3, Mat4, Vec3, Vec4},
+    file_line_col,
+    xmodel::XModel,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [`XModel::quats`] are stored as shorts scaled by this.
+const QUAT_SCALE: f32 = 1.0 / 32767.0;
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Bone {
+    pub name: String,
+    /// [`None`] for root bones.
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// The bone's rotation relative to its parent, as a quaternion
+    /// (x, y, z, w).
+    pub local_rotation: Vec4,
+    /// The bone's translation relative to its parent.
+    pub local_translation: Vec3,
+    /// The bone's rotation in model space in the bind pose.
+    pub bind_rotation: Vec4,
+    /// The bone's position in model space in the bind pose.
+    pub bind_translation: Vec3,
+}
+
+impl Bone {
+    /// The bone's transform relative to its parent.
+    pub fn local_matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(
+            Mat3::from_quat(self.local_rotation),
+            self.local_translation,
+        )
+    }
+
+    /// The bone's model-space transform in the bind pose.
+    pub fn bind_matrix(&self) -> Mat4 {
+        Mat4::from_rotation_translation(Mat3::from_quat(self.bind_rotation), self.bind_translation)
+    }
+}
+
+/// Returned by [`XModel::skeleton`]. Parents always come before their
+/// children in [`Self::bones`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    /// The indices of the bones without a parent.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bones
+            .iter()
+            .enumerate()
+            .filter(|(_, bone)| bone.parent.is_none())
+            .map(|(i, _)| i)
+    }
+
+    /// The index of the bone named `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+
+    /// Combines `local` (one transform per bone, relative to its parent) into
+    /// model-space transforms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `local` doesn't have a transform for every bone.
+    pub fn world_matrices(&self, local: &[Mat4]) -> Vec<Mat4> {
+        assert_eq!(local.len(), self.bones.len());
+
+        let mut world = Vec::<Mat4>::with_capacity(local.len());
+        for (bone, &local) in self.bones.iter().zip(local) {
+            let matrix = match bone.parent {
+                Some(parent) => world[parent] * local,
+                None => local,
+            };
+            world.push(matrix);
+        }
+        world
+    }
+
+    /// The model-space transform of every bone, built up from the
+    /// bones' local transforms.
+    pub fn local_bind_pose(&self) -> Vec<Mat4> {
+        let local = self
+            .bones
+            .iter()
+            .map(Bone::local_matrix)
+            .collect::<Vec<_>>();
+        self.world_matrices(&local)
+    }
+}
+
+impl XModel {
+    /// Decodes the model's bones.
+    ///
+    /// Root bones come first and have no local transform of their own in
+    /// the model, so their bind pose is used for it. Every other bone's
+    /// parent is [`Self::parent_list`]'s entry for it bones before it, and
+    /// its local rotation and translation are in [`Self::quats`] and
+    /// [`Self::trans`] (four of each per bone, the last translation being
+    /// padding).
+    ///
+    /// Returns [`ErrorKind::BrokenInvariant`] if any of those arrays are
+    /// too short, or if a bone's parent isn't a bone before it.
+    pub fn skeleton(&self) -> Result<Skeleton> {
+        let num_bones = self.num_bones;
+        let num_root_bones = self.num_root_bones.min(num_bones);
+        let num_child_bones = num_bones - num_root_bones;
+
+        let too_short = |what: &str, len: usize, expected: usize| {
+            Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XModel::skeleton: {what} has {len} entries, expected {expected}",
+                )),
+            )
+        };
+        if self.bone_names.len() < num_bones {
+            return Err(too_short("bone_names", self.bone_names.len(), num_bones));
+        }
+        if self.base_mat.len() < num_bones {
+            return Err(too_short("base_mat", self.base_mat.len(), num_bones));
+        }
+        if self.parent_list.len() < num_child_bones {
+            return Err(too_short(
+                "parent_list",
+                self.parent_list.len(),
+                num_child_bones,
+            ));
+        }
+        if self.quats.len() < num_child_bones * 4 {
+            return Err(too_short("quats", self.quats.len(), num_child_bones * 4));
+        }
+        if self.trans.len() < num_child_bones * 4 {
+            return Err(too_short("trans", self.trans.len(), num_child_bones * 4));
+        }
+
+        let mut bones = Vec::<Bone>::with_capacity(num_bones);
+        for i in 0..num_bones {
+            let base = &self.base_mat[i];
+            let mut bone = Bone {
+                name: self.bone_names[i].get().to_string(),
+                parent: None,
+                children: Vec::new(),
+                local_rotation: base.quat,
+                local_translation: base.trans,
+                bind_rotation: base.quat,
+                bind_translation: base.trans,
+            };
+
+            if let Some(child) = i.checked_sub(num_root_bones) {
+                let offset = self.parent_list[child] as usize;
+                let parent = i
+                    .checked_sub(offset)
+                    .filter(|_| offset != 0)
+                    .ok_or(Error::new(
+                        file_line_col!(),
+                        ErrorKind::BrokenInvariant(format!(
+                            "XModel::skeleton: bone {i}'s parent is {offset} bones before it",
+                        )),
+                    ))?;
+
+                let q = &self.quats[child * 4..child * 4 + 4];
+                let t = &self.trans[child * 4..child * 4 + 3];
+                bone.parent = Some(parent);
+                bone.local_rotation = [q[0], q[1], q[2], q[3]]
+                    .map(|c| c as f32 * QUAT_SCALE)
+                    .into();
+                bone.local_translation = [t[0], t[1], t[2]].into();
+                bones[parent].children.push(i);
+            }
+
+            bones.push(bone);
+        }
+
+        Ok(Skeleton { bones })
+    }
+}
@@ -0,0 +1,252 @@
+//! The bone weights of skinned surfaces, with the packed data in
+//! [`XSurfaceVertexInfo`] decoded.
+//!
+//! A skinned surface's vertices are sorted by how many bones they're
+//! weighted to (1 to 4), and [`XSurfaceVertexInfo::vert_count`] holds how
+//! many there are of each. [`XSurfaceVertexInfo::verts_blend`] then holds,
+//! for each vertex in order, the first bone, followed by a bone and weight
+//! for every other one. The first bone gets whatever weight is left over,
+//! so a vertex weighted to `n` bones takes `2n - 1` entries.
+//!
+//! Bones are stored as byte offsets into the game's array of skinning
+//! matrices (see [`BONE_OFFSET_SCALE`]), and weights as fractions of
+//! 65536.
+
+use alloc::{format, string::String, vec, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, file_line_col,
+    xmodel::{XModel, XSurfaceVertexInfo},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The size of a skinning matrix, which bones in
+/// [`XSurfaceVertexInfo::verts_blend`] are multiplied by.
+pub const BONE_OFFSET_SCALE: usize = 64;
+
+/// The most bones a vertex can be weighted to.
+pub const MAX_VERT_BONES: usize = 4;
+
+/// Weights are stored as shorts scaled by this.
+const WEIGHT_SCALE: f32 = 1.0 / 65536.0;
+
+/// How far a vertex's weights can add up to something other than 1 (or a
+/// weight can be outside of 0 and 1) before [`validate_weights`] rejects
+/// them. Each stored weight can be off by half a step.
+const WEIGHT_TOLERANCE: f32 = 1.0e-3;
+
+/// One bone a vertex is weighted to.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct BoneWeight {
+    /// An index into [`XModel::bone_names`].
+    pub bone: usize,
+    pub weight: f32,
+}
+
+fn broken(reason: String) -> Error {
+    Error::new(file_line_col!(), ErrorKind::BrokenInvariant(reason))
+}
+
+impl XSurfaceVertexInfo {
+    /// Decodes [`Self::verts_blend`] into the bones each vertex is weighted
+    /// to, in the order they're stored (so the first one is the one whose
+    /// weight is implied). See the [module docs](self).
+    ///
+    /// Returns an empty list for surfaces that aren't skinned. Fails with
+    /// [`ErrorKind::BrokenInvariant`] if `verts_blend` doesn't have as many
+    /// entries as [`Self::vert_count`] says, or if a bone offset isn't a
+    /// multiple of [`BONE_OFFSET_SCALE`].
+    pub fn decode_weights(&self) -> Result<Vec<Vec<BoneWeight>>> {
+        let (blend_count, vert_count) = self.counts();
+        if self.vert_count.iter().any(|&c| c < 0) || self.verts_blend.len() != blend_count {
+            return Err(broken(format!(
+                "XSurfaceVertexInfo: verts_blend.len() ({}) doesn't match vert_count ({:?})",
+                self.verts_blend.len(),
+                self.vert_count
+            )));
+        }
+
+        let bone = |offset: u16| {
+            let offset = offset as usize;
+            if offset.is_multiple_of(BONE_OFFSET_SCALE) {
+                Ok(offset / BONE_OFFSET_SCALE)
+            } else {
+                Err(broken(format!(
+                    "XSurfaceVertexInfo: bone offset {offset} isn't a multiple of {BONE_OFFSET_SCALE}"
+                )))
+            }
+        };
+
+        let mut weights = Vec::with_capacity(vert_count);
+        let mut blend = self.verts_blend.as_slice();
+        for (i, &count) in self.vert_count.iter().enumerate() {
+            let len = 2 * i + 1;
+            for _ in 0..count {
+                let (entries, rest) = blend.split_at(len);
+                blend = rest;
+
+                let mut vert = vec![BoneWeight {
+                    bone: bone(entries[0])?,
+                    weight: 1.0,
+                }];
+                for pair in entries[1..].chunks_exact(2) {
+                    let weight = pair[1] as f32 * WEIGHT_SCALE;
+                    vert[0].weight -= weight;
+                    vert.push(BoneWeight {
+                        bone: bone(pair[0])?,
+                        weight,
+                    });
+                }
+                weights.push(vert);
+            }
+        }
+
+        Ok(weights)
+    }
+
+    /// Replaces [`Self::vert_count`] and [`Self::verts_blend`] with ones
+    /// encoded from `weights`, one list per vertex, as returned by
+    /// [`Self::decode_weights`]. The first bone of each vertex gets
+    /// whatever weight the others leave over, so it's best for it to be the
+    /// heaviest.
+    ///
+    /// The vertices have to already be sorted by how many bones they're
+    /// weighted to, as the surface's vertices are drawn in the same order.
+    /// [`Self::tension_data`] is left as it is, so it has to be updated
+    /// separately if the vertex count changes.
+    ///
+    /// Fails with [`ErrorKind::BrokenInvariant`] if a vertex is weighted to
+    /// no bones or more than [`MAX_VERT_BONES`], if the vertices aren't
+    /// sorted, or if a bone or count is too large to be stored. See
+    /// [`validate_weights`] for checking the weights themselves.
+    pub fn set_weights(&mut self, weights: &[Vec<BoneWeight>]) -> Result<()> {
+        let mut vert_count = [0i16; MAX_VERT_BONES];
+        let mut verts_blend = Vec::new();
+
+        let offset = |bone: usize| {
+            bone.checked_mul(BONE_OFFSET_SCALE)
+                .and_then(|offset| u16::try_from(offset).ok())
+                .ok_or_else(|| broken(format!("XSurfaceVertexInfo: bone {bone} is too large")))
+        };
+
+        let mut prev_len = 1;
+        for (i, vert) in weights.iter().enumerate() {
+            let len = vert.len();
+            if !(1..=MAX_VERT_BONES).contains(&len) {
+                return Err(broken(format!(
+                    "XSurfaceVertexInfo: vertex {i} is weighted to {len} bones"
+                )));
+            }
+            if len < prev_len {
+                return Err(broken(format!(
+                    "XSurfaceVertexInfo: vertex {i} is weighted to fewer bones ({len}) than the one before it ({prev_len})"
+                )));
+            }
+            prev_len = len;
+
+            let count = &mut vert_count[len - 1];
+            *count = count.checked_add(1).ok_or_else(|| {
+                broken(format!(
+                    "XSurfaceVertexInfo: more than {} vertices are weighted to {len} bones",
+                    i16::MAX
+                ))
+            })?;
+
+            verts_blend.push(offset(vert[0].bone)?);
+            for w in &vert[1..] {
+                verts_blend.push(offset(w.bone)?);
+                verts_blend
+                    .push((w.weight / WEIGHT_SCALE).round().clamp(0.0, u16::MAX as _) as u16);
+            }
+        }
+
+        self.vert_count = vert_count;
+        self.verts_blend = verts_blend;
+        Ok(())
+    }
+}
+
+fn weight_violations(weights: &[Vec<BoneWeight>], num_bones: usize) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    for (i, vert) in weights.iter().enumerate() {
+        for w in vert {
+            if w.bone >= num_bones {
+                violations.push(format!(
+                    "vertex {i}: bone {} >= num_bones ({num_bones})",
+                    w.bone
+                ));
+            }
+            if !(-WEIGHT_TOLERANCE..=1.0 + WEIGHT_TOLERANCE).contains(&w.weight) {
+                violations.push(format!(
+                    "vertex {i}: bone {} has weight {}",
+                    w.bone, w.weight
+                ));
+            }
+        }
+
+        let sum = vert.iter().map(|w| w.weight).sum::<f32>();
+        if (sum - 1.0).abs() > WEIGHT_TOLERANCE {
+            violations.push(format!("vertex {i}: weights add up to {sum}"));
+        }
+    }
+
+    violations
+}
+
+/// Checks that every vertex's weights are between 0 and 1 and add up to 1,
+/// and that every bone is less than `num_bones`, e.g. before
+/// [`XSurfaceVertexInfo::set_weights`].
+///
+/// Returns [`Err`] with [`ErrorKind::Validation`] containing all violations
+/// if any are found.
+pub fn validate_weights(weights: &[Vec<BoneWeight>], num_bones: usize) -> Result<()> {
+    let violations = weight_violations(weights, num_bones);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::new(
+            file_line_col!(),
+            ErrorKind::Validation(violations),
+        ))
+    }
+}
+
+impl XModel {
+    /// Decodes the weights of every surface (see
+    /// [`XSurfaceVertexInfo::decode_weights`]) and checks them with
+    /// [`validate_weights`].
+    ///
+    /// Returns [`Err`] with [`ErrorKind::Validation`] containing all
+    /// violations, including surfaces whose weights can't be decoded, if
+    /// any are found.
+    pub fn validate_skinning(&self) -> Result<()> {
+        let mut violations = Vec::new();
+
+        for (i, surf) in self.surfs.iter().enumerate() {
+            match surf.vert_info.decode_weights() {
+                Ok(weights) => violations.extend(
+                    weight_violations(&weights, self.num_bones)
+                        .into_iter()
+                        .map(|v| format!("XModel: surfs[{i}]: {v}")),
+                ),
+                Err(e) => violations.push(format!("XModel: surfs[{i}]: {}", e.kind())),
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Validation(violations),
+            ))
+        }
+    }
+}
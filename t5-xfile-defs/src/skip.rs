@@ -0,0 +1,693 @@
+//! Layout walkers, which find where an asset ends in the stream without
+//! building any owned values for it.
+//!
+//! A walker loads an asset's header like the deserializer would, then steps
+//! over whatever was serialized after it (strings, arrays, etc.) in the same
+//! order, seeking past anything whose size is known up front instead of
+//! reading it. That's the basis for [`XAsset::skip`], which gets the extent
+//! and name of any asset, and uses a walker for it if its type has one.
+//!
+//! Walkers are declared with `impl_xfile_skip!`, as a list of what follows
+//! the header, and should be kept in sync with the corresponding
+//! [`XFileDeserializeInto`](crate::XFileDeserializeInto) impls. The few
+//! whose layout depends on their contents (shader arguments, water, etc.)
+//! are written out by hand. Walkers only check what they need to find the
+//! end of an asset, so an asset that fails to deserialize may still walk
+//! fine. Small parts of large assets (physics presets, lights, etc.) are
+//! deserialized and thrown away rather than getting walkers of their own.
+
+use core::ops::Range;
+
+use alloc::string::{String, ToString};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, LocalizeEntryRaw, MapEntsRaw, PackIndexRaw, Ptr32, RawFileRaw, Result,
+    StringTableCellRaw, StringTableRaw, T5XFileDeserialize, XArray, XFileDeserializeInto,
+    XFileRead, XGlobalsRaw, file_line_col, size_of,
+    techset::{
+        GfxImageLoadDefRaw, GfxImageRaw, GfxTextureRaw, MaterialPassRaw, MaterialPixelShaderRaw,
+        MaterialRaw, MaterialShaderArgument, MaterialShaderArgumentRaw, MaterialTechniqueRaw,
+        MaterialTechniqueSetRaw, MaterialTextureDefRaw, MaterialVertexShaderRaw, Semantic,
+        WaterRaw,
+    },
+    xasset::{XAsset, XAssetRaw, XAssetType},
+};
+
+#[cfg(feature = "world")]
+use crate::gfx_world::{
+    GfxAabbTreeRaw, GfxCellRaw, GfxLight, GfxLightGridRaw, GfxLightRegionHullRaw,
+    GfxLightRegionRaw, GfxLightmapArrayRaw, GfxPortalRaw, GfxPortalWritableRaw,
+    GfxReflectionProbeRaw, GfxShadowGeometryRaw, GfxStaticModelDrawInstRaw, GfxSurfaceRaw,
+    GfxWaterBufferRaw, GfxWorldDpvsDynamicRaw, GfxWorldDpvsStaticRaw, GfxWorldDrawRaw, GfxWorldRaw,
+    GfxWorldStreamInfoRaw, MaterialMemoryRaw, SunflareRaw,
+};
+#[cfg(feature = "audio")]
+use crate::sound::{
+    LoadedSoundRaw, PrimedSndRaw, SndAliasListRaw, SndAliasRaw, SndBankRaw, SoundFileRaw,
+    StreamedSoundRaw,
+};
+#[cfg(feature = "models")]
+use crate::xmodel::{
+    Collmap, PhysConstraints, PhysPreset, XModelCollSurfRaw, XModelRaw, XRigidVertListRaw,
+    XSurfaceCollisionTreeRaw, XSurfaceRaw, XSurfaceVertexInfoRaw, vert_counts,
+};
+
+/// Where in the stream an asset was found by [`XAsset::skip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetExtent {
+    pub asset_type: XAssetType,
+    /// [`None`] for null assets.
+    pub name: Option<String>,
+    /// From the start of the asset's header to the end of the last thing
+    /// loaded for it.
+    pub range: Range<u64>,
+}
+
+impl AssetExtent {
+    /// Whether the asset's family isn't enabled and it was stored inline,
+    /// so it took up the rest of the zone (see
+    /// [`XAssetGeneric::ends_zone`](crate::xasset::XAssetGeneric::ends_zone)).
+    pub fn ends_zone(&self) -> bool {
+        !self.asset_type.is_enabled() && !self.range.is_empty()
+    }
+}
+
+/// A raw struct that can be walked over without deserializing it.
+pub(crate) trait XFileSkip {
+    /// Steps over everything loaded after `self`, and returns the asset's
+    /// name if the walker reads it.
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>>;
+}
+
+/// Pointers are walked like the deserializer follows them, except that real
+/// offsets aren't followed, since that wouldn't move the stream anyway.
+impl<'a, T: XFileSkip + XFileRead + Clone + core::fmt::Debug> XFileSkip for Ptr32<'a, T> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        let Some(t) = self.xfile_load(de)? else {
+            return Ok(None);
+        };
+
+        de.enter_ptr()?;
+        let name = t.xfile_skip(de);
+        de.leave_ptr();
+        name
+    }
+}
+
+impl<T: XFileSkip, const N: usize> XFileSkip for [T; N] {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        for t in self {
+            t.xfile_skip(de)?;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Same as [`XArray::to_vec`] for elements that don't have anything loaded
+/// after them, but seeks past the elements instead of reading them.
+fn skip_flat<T: XFileRead>(array: &impl XArray<T>, de: &mut impl T5XFileDeserialize) -> Result<()> {
+    if !array.is_inline() {
+        return Ok(());
+    }
+
+    de.check_array_len(array.len(), size_of!(T))?;
+    if array.is_empty() {
+        return Ok(());
+    }
+
+    let len = array.len() * size_of!(T);
+    de.mark_ptr_target(array.marker(), core::mem::align_of::<T>(), len)?;
+    de.skip_bytes(len)
+}
+
+/// Same as deserializing the array, but walks over each element instead.
+fn skip_array<T: XFileSkip + XFileRead>(
+    array: &impl XArray<T>,
+    de: &mut impl T5XFileDeserialize,
+) -> Result<()> {
+    for t in array.to_vec(de)? {
+        t.xfile_skip(de)?;
+    }
+
+    Ok(())
+}
+
+/// Declares the walker for each raw struct, as a list of what's loaded after
+/// it, in order. Each entry is one of:
+///
+/// * `name(xstring)`: the asset's name, which is read and returned.
+/// * `string(xstring)`: any other string, which has to be read to find its
+///   end.
+/// * `bulk(array)`: an array of primitives that would be read with
+///   [`XArray::to_vec_bulk`].
+/// * `flat(array)`: an array of structs or primitives with nothing loaded
+///   after them.
+/// * `array(array)`: an array of structs that have walkers of their own.
+/// * `walk(t)`: a struct embedded in this one, or a pointer to one, that has
+///   a walker of its own.
+macro_rules! impl_xfile_skip {
+    ($($raw:ident($this:ident) => [$($kind:ident($e:expr)),* $(,)?];)+) => {
+        $(
+            impl<'a> XFileSkip for $raw<'a> {
+                #[allow(unused_mut, unused_assignments)]
+                fn xfile_skip(
+                    &self,
+                    de: &mut impl T5XFileDeserialize,
+                ) -> Result<Option<String>> {
+                    let $this = self;
+                    let mut name = None;
+                    $(impl_xfile_skip!(@$kind name, de, $e);)*
+                    Ok(name)
+                }
+            }
+        )+
+    };
+    (@name $name:ident, $de:ident, $e:expr) => {
+        $name = Some($e.xfile_deserialize_into($de, ())?.0);
+    };
+    (@string $name:ident, $de:ident, $e:expr) => {
+        $e.xfile_deserialize_into($de, ())?;
+    };
+    (@bulk $name:ident, $de:ident, $e:expr) => {
+        $e.skip($de)?;
+    };
+    (@flat $name:ident, $de:ident, $e:expr) => {
+        skip_flat(&$e, $de)?;
+    };
+    (@array $name:ident, $de:ident, $e:expr) => {
+        skip_array(&$e, $de)?;
+    };
+    (@walk $name:ident, $de:ident, $e:expr) => {
+        $e.xfile_skip($de)?;
+    };
+}
+
+impl<'a> StringTableRaw<'a> {
+    fn cell_count(&self) -> usize {
+        (self.column_count as usize).saturating_mul(self.row_count as usize)
+    }
+}
+
+impl_xfile_skip! {
+    RawFileRaw(this) => [
+        name(this.name),
+        bulk(this.buffer.to_array(this.len as usize + 1)),
+    ];
+    MapEntsRaw(this) => [name(this.name), bulk(this.entity_string)];
+    LocalizeEntryRaw(this) => [string(this.value), name(this.name)];
+    StringTableRaw(this) => [
+        name(this.name),
+        array(this.values.to_array(this.cell_count())),
+        flat(this.cell_index.to_array(this.cell_count())),
+    ];
+    StringTableCellRaw(this) => [string(this.name)];
+    PackIndexRaw(this) => [
+        name(this.name),
+        flat(this.entries.to_array(this.header.count as usize)),
+    ];
+    XGlobalsRaw(this) => [name(this.name)];
+}
+
+impl_xfile_skip! {
+    MaterialRaw(this) => [
+        name(this.info.name),
+        walk(this.technique_set),
+        array(this.texture_table.to_array(this.texture_count as usize)),
+        flat(this.constant_table.to_array(this.constant_count as usize)),
+        flat(this.state_bits_table.to_array(this.state_bits_count as usize)),
+    ];
+    MaterialTechniqueRaw(this) => [array(this.passes), string(this.name)];
+    MaterialVertexShaderRaw(this) => [string(this.name), bulk(this.prog.load_def.program)];
+    MaterialPixelShaderRaw(this) => [string(this.name), bulk(this.prog.load_def.program)];
+    GfxImageRaw(this) => [name(this.name), walk(this.texture)];
+    GfxTextureRaw(this) => [walk(this.p.cast::<GfxImageLoadDefRaw>())];
+}
+
+impl<'a> XFileSkip for MaterialTechniqueSetRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        let name = self.name.xfile_deserialize_into(de, ())?.0;
+
+        // the deserializer drops techniques that fail to load, and carries
+        // on from wherever they stopped
+        for technique in self.techniques {
+            let _ = technique.xfile_skip(de);
+        }
+
+        Ok(Some(name))
+    }
+}
+
+impl<'a> XFileSkip for MaterialPassRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        self.vertex_decl.xfile_get(de)?;
+        self.vertex_shader.xfile_skip(de)?;
+        self.pixel_shader.xfile_skip(de)?;
+
+        // literal arguments are followed by their value, so each one has to
+        // be read to know how long it is
+        if self.args != 0 {
+            let argc = self.per_prim_arg_count as usize
+                + self.per_obj_arg_count as usize
+                + self.stable_arg_count as usize;
+            de.check_array_len(argc, size_of!(MaterialShaderArgumentRaw))?;
+            for _ in 0..argc {
+                let arg = de.load_from_xfile::<MaterialShaderArgumentRaw>()?;
+                let _: MaterialShaderArgument = arg.xfile_deserialize_into(de, ())?;
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl<'a> XFileSkip for MaterialTextureDefRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        if self.semantic == Semantic::WATER_MAP as u8 {
+            self.u.p.cast::<WaterRaw>().xfile_skip(de)
+        } else {
+            self.u.p.cast::<GfxImageRaw>().xfile_skip(de)
+        }
+    }
+}
+
+impl<'a> XFileSkip for WaterRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        let count = (self.m as usize).saturating_mul(self.n as usize);
+
+        if !self.h0.is_null() {
+            de.check_array_len(count, size_of!(crate::techset::Complex))?;
+            de.skip_bytes(count * size_of!(crate::techset::Complex))?;
+        }
+
+        if !self.w_term.is_null() {
+            de.check_array_len(count, size_of!(f32))?;
+            de.skip_bytes(count * size_of!(f32))?;
+        }
+
+        self.image.xfile_skip(de)
+    }
+}
+
+impl XFileSkip for GfxImageLoadDefRaw {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        skip_flat(&self.resource, de)?;
+        Ok(None)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl_xfile_skip! {
+    SndBankRaw(this) => [
+        name(this.name),
+        array(this.aliases),
+        flat(this.alias_index.to_array(this.aliases.len())),
+        flat(this.radverbs),
+        flat(this.snapshots),
+    ];
+    SndAliasListRaw(this) => [string(this.name), array(this.aliases)];
+    SndAliasRaw(this) => [
+        string(this.name),
+        string(this.subtitle),
+        string(this.secondaryname),
+        walk(this.sound_file),
+    ];
+    LoadedSoundRaw(this) => [
+        string(this.name),
+        bulk(this.sound.seek_table),
+        bulk(this.sound.data),
+    ];
+    StreamedSoundRaw(this) => [string(this.filename), walk(this.prime_snd)];
+    PrimedSndRaw(this) => [string(this.name), bulk(this.buffer)];
+}
+
+#[cfg(feature = "audio")]
+impl<'a> XFileSkip for SoundFileRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        if self.type_ == 1 {
+            self.u.0.cast::<LoadedSoundRaw>().xfile_skip(de)
+        } else {
+            self.u.0.cast::<StreamedSoundRaw>().xfile_skip(de)
+        }
+    }
+}
+
+#[cfg(feature = "models")]
+impl_xfile_skip! {
+    XSurfaceRaw(this) => [
+        walk(this.vert_info),
+        flat(this.verts0.to_array(this.vert_count as usize)),
+        array(this.vert_list.to_array(this.vert_list_count as usize)),
+        bulk(this.tri_indices.to_array(this.tri_count as usize * 3)),
+    ];
+    XSurfaceVertexInfoRaw(this) => [
+        bulk(this.verts_blend.to_array(vert_counts(this.vert_count).0)),
+        bulk(this.tension_data.to_array(vert_counts(this.vert_count).1.saturating_mul(12))),
+    ];
+    XRigidVertListRaw(this) => [walk(this.collision_tree)];
+    XSurfaceCollisionTreeRaw(this) => [flat(this.nodes), flat(this.leafs)];
+    XModelCollSurfRaw(this) => [flat(this.coll_tris)];
+}
+
+#[cfg(feature = "models")]
+impl<'a> XFileSkip for XModelRaw<'a> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        let name = self.name.xfile_deserialize_into(de, ())?.0;
+
+        let Some(child_bones) = self.num_bones.checked_sub(self.num_root_bones) else {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::BrokenInvariant(alloc::format!(
+                    "XModel: num_bones ({}) < num_root_bones ({})",
+                    self.num_bones,
+                    self.num_root_bones
+                )),
+            ));
+        };
+        let bones = self.num_bones as usize;
+        let child_bones = child_bones as usize;
+        let surfs = self.numsurfs as usize;
+
+        skip_flat(&self.bone_names.to_array(bones), de)?;
+        self.parent_list.to_array(child_bones).skip(de)?;
+        skip_flat(&self.quats.to_array(child_bones * 4), de)?;
+        self.trans.to_array(child_bones * 4).skip(de)?;
+        self.part_classification.to_array(bones).skip(de)?;
+        skip_flat(&self.base_mat.to_array(bones), de)?;
+        skip_array(&self.surfs.to_array(surfs), de)?;
+        skip_array(&self.material_handles.to_array(surfs), de)?;
+        skip_array(&self.coll_surfs, de)?;
+        skip_flat(&self.bone_info.to_array(bones), de)?;
+        skip_flat(&self.stream_info.high_mip_bounds.to_array(surfs), de)?;
+
+        let _: Option<alloc::boxed::Box<PhysPreset>> =
+            self.phys_preset.xfile_deserialize_into(de, ())?;
+        let _: alloc::vec::Vec<Collmap> = self.collmaps.xfile_deserialize_into(de, ())?;
+        let _: Option<alloc::boxed::Box<PhysConstraints>> =
+            self.phys_constraints.xfile_deserialize_into(de, ())?;
+
+        Ok(Some(name))
+    }
+}
+
+#[cfg(feature = "world")]
+impl<'a> GfxLightGridRaw<'a> {
+    fn row_count(&self) -> usize {
+        let axis = self.row_axis as usize;
+        match (self.mins.get(axis), self.maxs.get(axis)) {
+            (Some(&min), Some(&max)) => (max as usize).saturating_sub(min as usize) + 1,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(feature = "world")]
+impl_xfile_skip! {
+    GfxWorldStreamInfoRaw(this) => [flat(this.aabb_trees), flat(this.leaf_refs)];
+    GfxCellRaw(this) => [
+        array(this.aabb_tree),
+        array(this.portals),
+        flat(this.cull_groups),
+        bulk(this.reflection_probes),
+    ];
+    GfxAabbTreeRaw(this) => [
+        bulk(this.smodel_indexes.to_array(this.smodel_index_count as usize)),
+    ];
+    GfxPortalRaw(this) => [walk(this.writable), walk(this.cell), flat(this.vertices)];
+    GfxPortalWritableRaw(this) => [
+        flat(this.hull_points.to_array(this.hull_point_count as usize)),
+        walk(this.queued_parent),
+    ];
+    GfxWorldDrawRaw(this) => [
+        array(this.reflection_probes),
+        array(this.reflection_probe_textures.to_array(this.reflection_probes.len())),
+        array(this.lightmaps),
+        array(this.lightmap_primary_textures.to_array(this.lightmaps.len())),
+        array(this.lightmap_secondary_textures.to_array(this.lightmaps.len())),
+        array(this.lightmap_secondary_textures_b.to_array(this.lightmaps.len())),
+        walk(this.terrain_scorch_images),
+        flat(this.vd.vertices.to_array(this.vertex_count as usize)),
+        bulk(this.vld.data.to_array(this.vertex_layer_data_size as usize)),
+        bulk(this.indices),
+    ];
+    GfxReflectionProbeRaw(this) => [walk(this.image), flat(this.probe_volumes)];
+    GfxLightmapArrayRaw(this) => [
+        walk(this.primary),
+        walk(this.secondary),
+        walk(this.secondary_b),
+    ];
+    GfxLightGridRaw(this) => [
+        bulk(this.row_data_start.to_array(this.row_count())),
+        bulk(this.raw_row_data),
+        flat(this.entries),
+        flat(this.colors),
+    ];
+    MaterialMemoryRaw(this) => [walk(this.material)];
+    SunflareRaw(this) => [walk(this.sprite_material), walk(this.flare_material)];
+    GfxShadowGeometryRaw(this) => [
+        bulk(this.sorted_surf_index.to_array(this.surface_count as usize)),
+        bulk(this.smodel_index.to_array(this.smodel_count as usize)),
+    ];
+    GfxLightRegionRaw(this) => [array(this.hulls)];
+    GfxLightRegionHullRaw(this) => [flat(this.axis)];
+    GfxSurfaceRaw(this) => [walk(this.material)];
+    GfxStaticModelDrawInstRaw(this) => [walk(this.model)];
+    GfxWaterBufferRaw(this) => [flat(this.buffer)];
+}
+
+#[cfg(feature = "world")]
+impl<'a> GfxWorldDpvsStaticRaw<'a> {
+    fn xfile_skip_with(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        surface_count: usize,
+        cull_group_count: usize,
+    ) -> Result<()> {
+        let smodels = self.smodel_count as usize;
+        let static_surfaces = self.static_surface_count as usize;
+
+        for vis_data in self.smodel_vis_data {
+            skip_flat(&vis_data.to_array(smodels), de)?;
+        }
+        for vis_data in self.surface_vis_data {
+            skip_flat(&vis_data.to_array(static_surfaces), de)?;
+        }
+        self.smodel_vis_data_camera_saved
+            .to_array(smodels)
+            .skip(de)?;
+        self.surface_vis_data_camera_saved
+            .to_array(static_surfaces)
+            .skip(de)?;
+        self.lod_data
+            .to_array(self.smodel_vis_data_count as usize * 2)
+            .skip(de)?;
+        skip_flat(&self.sorted_surf_index.to_array(static_surfaces), de)?;
+        skip_flat(&self.smodel_insts.to_array(smodels), de)?;
+        skip_array(&self.surfaces.to_array(surface_count), de)?;
+        skip_flat(&self.cull_groups.to_array(cull_group_count), de)?;
+        skip_array(&self.smodel_draw_insts.to_array(smodels), de)?;
+        skip_flat(&self.surface_materials.to_array(static_surfaces), de)?;
+        self.surface_casts_sun_shadow
+            .to_array(self.surface_vis_data_count as usize)
+            .skip(de)
+    }
+}
+
+#[cfg(feature = "world")]
+impl<'a> GfxWorldDpvsDynamicRaw<'a> {
+    fn xfile_skip_with(&self, de: &mut impl T5XFileDeserialize, cell_count: usize) -> Result<()> {
+        let word_counts = self.dyn_ent_client_word_count.map(|c| c as usize);
+
+        for (cell_bits, word_count) in self.dyn_ent_cell_bits.into_iter().zip(word_counts) {
+            skip_flat(
+                &cell_bits.to_array(word_count.saturating_mul(cell_count)),
+                de,
+            )?;
+        }
+        for vis_data in self.dyn_ent_vis_data {
+            for (vis_data, word_count) in vis_data.into_iter().zip(word_counts) {
+                skip_flat(&vis_data.to_array(word_count * 32), de)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "world")]
+impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSkip for GfxWorldRaw<'a, MAX_LOCAL_CLIENTS> {
+    fn xfile_skip(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<String>> {
+        let name = self.name.xfile_deserialize_into(de, ())?.0;
+        self.base_name.xfile_deserialize_into(de, ())?;
+        self.stream_info.xfile_skip(de)?;
+        skip_flat(&self.sky_start_surfs, de)?;
+        self.sky_image.xfile_skip(de)?;
+        self.sky_box_model.xfile_deserialize_into(de, ())?;
+        let _: Option<alloc::boxed::Box<GfxLight>> =
+            self.sun_light.xfile_deserialize_into(de, ())?;
+        skip_flat(&self.coronas, de)?;
+        skip_flat(&self.shadow_map_volumes, de)?;
+        skip_flat(&self.shadow_map_volume_planes, de)?;
+        skip_flat(&self.exposure_volumes, de)?;
+        skip_flat(&self.exposure_volume_planes, de)?;
+
+        // the deserializer loads the planes with the node count and the nodes
+        // with the plane count, so the same is done here
+        let cell_count = self.dpvs_planes.cell_count as usize;
+        skip_flat(
+            &self.dpvs_planes.planes.to_array(self.node_count as usize),
+            de,
+        )?;
+        self.dpvs_planes
+            .nodes
+            .to_array(self.plane_count as usize)
+            .skip(de)?;
+        self.dpvs_planes
+            .scene_ent_cell_bits
+            .to_array(cell_count.saturating_mul(512))
+            .skip(de)?;
+
+        skip_array(&self.cells.to_array(cell_count), de)?;
+        self.draw.xfile_skip(de)?;
+        self.light_grid.xfile_skip(de)?;
+        skip_flat(&self.models, de)?;
+        skip_array(&self.material_memory, de)?;
+        self.sun.xfile_skip(de)?;
+        self.outdoor_image.xfile_skip(de)?;
+        self.cell_caster_bits
+            .to_array(cell_count.saturating_add(31) / 32 * cell_count)
+            .skip(de)?;
+
+        let dyn_ent_counts = self.dpvs_dyn.dyn_ent_client_count.map(|c| c as usize);
+        skip_flat(&self.scene_dyn_model.to_array(dyn_ent_counts[0]), de)?;
+        skip_flat(&self.scene_dyn_brush.to_array(dyn_ent_counts[1]), de)?;
+
+        let shadowed_lights = (self.primary_light_count as usize)
+            .saturating_sub(self.sun_primary_light_index as usize)
+            + 1;
+        self.primary_light_entity_shadow_vis
+            .to_array(shadowed_lights.saturating_mul(8192))
+            .skip(de)?;
+        for (vis, count) in self
+            .primary_light_dyn_ent_shadow_vis
+            .into_iter()
+            .zip(dyn_ent_counts)
+        {
+            skip_flat(&vis.to_array(shadowed_lights.saturating_mul(count)), de)?;
+        }
+        self.non_sun_primary_light_for_model_dyn_ent
+            .to_array(dyn_ent_counts[0])
+            .skip(de)?;
+
+        let primary_lights = self.primary_light_count as usize;
+        skip_array(&self.shadow_geom.to_array(primary_lights), de)?;
+        skip_array(&self.light_region.to_array(primary_lights), de)?;
+        self.dpvs.xfile_skip_with(
+            de,
+            self.surface_count as usize,
+            self.cull_group_count as usize,
+        )?;
+        self.dpvs_dyn.xfile_skip_with(de, cell_count)?;
+        skip_flat(&self.world_lod_chains, de)?;
+        skip_flat(&self.world_lod_infos, de)?;
+        self.world_lod_surfaces.skip(de)?;
+        self.water_buffers.xfile_skip(de)?;
+        self.water_material.xfile_skip(de)?;
+        self.corona_material.xfile_skip(de)?;
+        self.rope_material.xfile_skip(de)?;
+        skip_flat(&self.occluders, de)?;
+        skip_flat(&self.outdoor_bounds, de)?;
+        skip_flat(
+            &self.hero_lights.to_array(self.hero_light_count as usize),
+            de,
+        )?;
+        skip_flat(
+            &self
+                .hero_light_tree
+                .to_array(self.hero_light_tree_count as usize),
+            de,
+        )?;
+
+        Ok(Some(name))
+    }
+}
+
+/// Loads the header `xasset_raw` points to as a `T`, and walks over the
+/// rest of the asset.
+fn walk<T: XFileSkip + XFileRead + Clone + core::fmt::Debug>(
+    de: &mut impl T5XFileDeserialize,
+    xasset_raw: XAssetRaw,
+) -> Result<Option<String>> {
+    xasset_raw.asset_data.cast::<T>().xfile_skip(de)
+}
+
+impl XAsset {
+    /// Finds where the asset `xasset_raw` points to ends, and gets its name,
+    /// leaving the stream right after it.
+    ///
+    /// Raw files, map entities, localized strings, string tables, pack
+    /// indices, globals, materials, technique sets, images, sound banks,
+    /// models, and world geometry have layout walkers, so their vertices,
+    /// indices, shader programs, pixels, samples, etc. are seeked past
+    /// without being read.
+    /// Every other type is deserialized in full and thrown away.
+    #[doc(hidden)]
+    pub fn skip(
+        de: &mut impl T5XFileDeserialize,
+        xasset_raw: XAssetRaw,
+        max_local_clients: usize,
+    ) -> Result<AssetExtent> {
+        let start = de.stream_pos()?;
+        let asset_type =
+            num::FromPrimitive::from_u32(xasset_raw.asset_type).ok_or(Error::new_with_offset(
+                file_line_col!(),
+                start as _,
+                ErrorKind::InvalidXAssetType(xasset_raw.asset_type),
+            ))?;
+
+        // assets behind real offsets are left to the full deserializer,
+        // since it knows how to follow them
+        let walked = match asset_type {
+            _ if xasset_raw.asset_data.is_real() => None,
+            XAssetType::RAWFILE => Some(walk::<RawFileRaw>(de, xasset_raw)?),
+            XAssetType::MAP_ENTS => Some(walk::<MapEntsRaw>(de, xasset_raw)?),
+            XAssetType::LOCALIZE_ENTRY => Some(walk::<LocalizeEntryRaw>(de, xasset_raw)?),
+            XAssetType::STRINGTABLE => Some(walk::<StringTableRaw>(de, xasset_raw)?),
+            XAssetType::PACKINDEX => Some(walk::<PackIndexRaw>(de, xasset_raw)?),
+            XAssetType::XGLOBALS => Some(walk::<XGlobalsRaw>(de, xasset_raw)?),
+            XAssetType::MATERIAL => Some(walk::<MaterialRaw>(de, xasset_raw)?),
+            XAssetType::TECHNIQUE_SET => Some(walk::<MaterialTechniqueSetRaw>(de, xasset_raw)?),
+            XAssetType::IMAGE => Some(walk::<GfxImageRaw>(de, xasset_raw)?),
+            #[cfg(feature = "audio")]
+            XAssetType::SOUND => Some(walk::<SndBankRaw>(de, xasset_raw)?),
+            #[cfg(feature = "models")]
+            XAssetType::XMODEL => Some(walk::<XModelRaw>(de, xasset_raw)?),
+            #[cfg(feature = "world")]
+            XAssetType::GFXWORLD if max_local_clients == 1 => {
+                Some(walk::<GfxWorldRaw<1>>(de, xasset_raw)?)
+            }
+            #[cfg(feature = "world")]
+            XAssetType::GFXWORLD if max_local_clients == 4 => {
+                Some(walk::<GfxWorldRaw<4>>(de, xasset_raw)?)
+            }
+            _ => None,
+        };
+
+        let name = match walked {
+            Some(name) => name,
+            None => Self::try_get_with_max_local_clients(de, xasset_raw, max_local_clients)?
+                .name()
+                .map(ToString::to_string),
+        };
+
+        Ok(AssetExtent {
+            asset_type,
+            name,
+            range: start..de.stream_pos()?,
+        })
+    }
+}
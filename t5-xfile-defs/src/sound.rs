@@ -1,24 +1,22 @@
-use core::fmt::Display;
-
-use alloc::{
-    boxed::Box,
-    string::{String, ToString},
-    vec::Vec,
-};
+use alloc::{boxed::Box, string::ToString, vec::Vec};
 
 use bitflags::bitflags;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize, XString,
-    XStringRaw, assert_size, common::Vec2, file_line_col, prelude::*,
+    Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, FixedString, Ptr32, Result,
+    T5XFileDeserialize, T5XFileDeserializeBorrowed, XArray, XFileDeserializeBorrowed,
+    XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size, common::Vec2, file_line_col,
+    prelude::*,
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndBankRaw<'a> {
     pub name: XStringRaw<'a>,
     pub aliases: FatPointerCountFirstU32<'a, SndAliasListRaw<'a>>,
@@ -31,7 +29,8 @@ pub(crate) struct SndBankRaw<'a> {
 assert_size!(SndBankRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndBank {
     pub name: XString,
     pub aliases: Vec<SndAliasList>,
@@ -42,6 +41,23 @@ pub struct SndBank {
     pub snapshots: Vec<SndSnapshot>,
 }
 
+impl SndBank {
+    /// The alias list named `name`, if there is one.
+    pub fn alias_list(&self, name: &str) -> Option<&SndAliasList> {
+        self.aliases.iter().find(|list| list.name.get() == name)
+    }
+
+    /// Resolves the alias list named `name` (see [`SndAliasList::resolve`]),
+    /// looking up secondary aliases within this bank.
+    ///
+    /// Returns an empty [`Vec`] if there's no alias list named `name`.
+    pub fn resolve(&self, name: &str) -> Vec<ResolvedAlias<'_>> {
+        self.alias_list(name)
+            .map(|list| list.resolve(|name| self.alias_list(name)))
+            .unwrap_or_default()
+    }
+}
+
 impl<'a> XFileDeserializeInto<SndBank, ()> for SndBankRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -54,7 +70,7 @@ impl<'a> XFileDeserializeInto<SndBank, ()> for SndBankRaw<'a> {
         let aliases = self.aliases.xfile_deserialize_into(de, ())?;
         let alias_index = self
             .alias_index
-            .to_array(self.aliases.size() as _)
+            .to_array(self.aliases.len() as _)
             .to_vec(de)?;
         let radverbs = self.radverbs.to_vec_into(de)?;
         let snapshots = self.snapshots.to_vec_into(de)?;
@@ -71,6 +87,7 @@ impl<'a> XFileDeserializeInto<SndBank, ()> for SndBankRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndBank {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -98,8 +115,68 @@ impl XFileSerialize<()> for SndBank {
     }
 }
 
+/// Borrowed counterpart of [`SndBank`], whose loaded sounds' samples are
+/// borrowed.
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SndBankRef<'buf> {
+    pub name: XString,
+    pub aliases: Vec<SndAliasListRef<'buf>>,
+    pub alias_index: Vec<SndIndexEntry>,
+    pub pack_hash: u32,
+    pub pack_location: u32,
+    pub radverbs: Vec<SndRadverb>,
+    pub snapshots: Vec<SndSnapshot>,
+}
+
+impl<'buf> SndBankRef<'buf> {
+    pub fn to_owned(&self) -> SndBank {
+        SndBank {
+            name: self.name.clone(),
+            aliases: self.aliases.iter().map(SndAliasListRef::to_owned).collect(),
+            alias_index: self.alias_index.clone(),
+            pack_hash: self.pack_hash,
+            pack_location: self.pack_location,
+            radverbs: self.radverbs.clone(),
+            snapshots: self.snapshots.clone(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, SndBankRef<'buf>, ()> for SndBankRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<SndBankRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let aliases = self
+            .aliases
+            .to_vec(de)?
+            .iter()
+            .map(|a| a.xfile_deserialize_borrowed(de, ()))
+            .collect::<Result<Vec<_>>>()?;
+        let alias_index = self
+            .alias_index
+            .to_array(self.aliases.len() as _)
+            .to_vec(de)?;
+        let radverbs = self.radverbs.to_vec_into(de)?;
+        let snapshots = self.snapshots.to_vec_into(de)?;
+
+        Ok(SndBankRef {
+            name,
+            aliases,
+            alias_index,
+            pack_hash: self.pack_hash,
+            pack_location: self.pack_location,
+            radverbs,
+            snapshots,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndAliasListRaw<'a> {
     pub name: XStringRaw<'a>,
     pub id: u32,
@@ -108,7 +185,8 @@ pub(crate) struct SndAliasListRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SndAliasList {
     pub name: XString,
     pub id: u32,
@@ -116,6 +194,92 @@ pub struct SndAliasList {
     pub sequence: i32,
 }
 
+impl SndAliasList {
+    /// Every alias that playing this alias list can end up playing.
+    ///
+    /// Playing an alias list plays one of its [`aliases`](Self::aliases),
+    /// picked at random and weighted by [`SndAlias::probability`] (or evenly,
+    /// if they're all 0), along with the alias list named by that alias's
+    /// [`SndAlias::secondaryname`], which `lookup` finds. Secondary alias
+    /// lists are resolved the same way, so the aliases they lead to come
+    /// after the alias that named them.
+    ///
+    /// A chain ends at a secondary alias list that `lookup` can't find, or
+    /// that's already in the chain.
+    pub fn resolve<'a>(
+        &'a self,
+        lookup: impl Fn(&str) -> Option<&'a SndAliasList>,
+    ) -> Vec<ResolvedAlias<'a>> {
+        let mut resolved = Vec::new();
+        self.resolve_into(&lookup, &mut Vec::new(), 1.0, &mut resolved);
+        resolved
+    }
+
+    fn resolve_into<'a>(
+        &'a self,
+        lookup: &impl Fn(&str) -> Option<&'a SndAliasList>,
+        chain: &mut Vec<&'a str>,
+        weight: f32,
+        resolved: &mut Vec<ResolvedAlias<'a>>,
+    ) {
+        chain.push(self.name.get());
+
+        let total = self
+            .aliases
+            .iter()
+            .map(|alias| alias.probability as u32)
+            .sum::<u32>();
+        for alias in &self.aliases {
+            let weight = weight
+                * if total == 0 {
+                    1.0 / self.aliases.len() as f32
+                } else {
+                    alias.probability as f32 / total as f32
+                };
+            resolved.push(ResolvedAlias {
+                chain: chain.clone(),
+                alias,
+                weight,
+            });
+
+            let secondary = alias.secondaryname.get();
+            if secondary.is_empty() || chain.contains(&secondary) {
+                continue;
+            }
+            if let Some(list) = lookup(secondary) {
+                list.resolve_into(lookup, chain, weight, resolved);
+            }
+        }
+
+        chain.pop();
+    }
+}
+
+/// An alias that playing an alias list can end up playing. See
+/// [`SndAliasList::resolve`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedAlias<'a> {
+    /// The names of the alias lists followed to get to this alias, starting
+    /// with the one that was resolved and ending with the one that holds it.
+    pub chain: Vec<&'a str>,
+    pub alias: &'a SndAlias,
+    /// The chance (from 0 to 1) that playing the resolved alias list plays
+    /// this alias.
+    pub weight: f32,
+}
+
+impl<'a> ResolvedAlias<'a> {
+    /// Whether the alias is only played as the secondary of another.
+    pub fn is_secondary(&self) -> bool {
+        self.chain.len() > 1
+    }
+
+    /// The sound the alias plays, if it has one.
+    pub fn sound_file(&self) -> Option<&'a SoundFile> {
+        self.alias.sound_file.as_deref()
+    }
+}
+
 impl<'a> XFileDeserializeInto<SndAliasList, ()> for SndAliasListRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -134,6 +298,7 @@ impl<'a> XFileDeserializeInto<SndAliasList, ()> for SndAliasListRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndAliasList {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -152,8 +317,52 @@ impl XFileSerialize<()> for SndAliasList {
     }
 }
 
+/// Borrowed counterpart of [`SndAliasList`].
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SndAliasListRef<'buf> {
+    pub name: XString,
+    pub id: u32,
+    pub aliases: Vec<SndAliasRef<'buf>>,
+    pub sequence: i32,
+}
+
+impl<'buf> SndAliasListRef<'buf> {
+    pub fn to_owned(&self) -> SndAliasList {
+        SndAliasList {
+            name: self.name.clone(),
+            id: self.id,
+            aliases: self.aliases.iter().map(SndAliasRef::to_owned).collect(),
+            sequence: self.sequence,
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, SndAliasListRef<'buf>, ()> for SndAliasListRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<SndAliasListRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let aliases = self
+            .aliases
+            .to_vec(de)?
+            .iter()
+            .map(|a| a.xfile_deserialize_borrowed(de, ()))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(SndAliasListRef {
+            name,
+            id: self.id,
+            aliases,
+            sequence: self.sequence,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndAliasRaw<'a> {
     pub name: XStringRaw<'a>,
     pub id: u32,
@@ -195,12 +404,14 @@ pub(crate) struct SndAliasRaw<'a> {
     pub limit_count: u8,
     pub entity_limit_count: u8,
     pub snapshot_group: u8,
+    #[allow(dead_code)]
     pad: [u8; 1],
 }
 assert_size!(SndAliasRaw, 84);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SndAlias {
     pub name: XString,
     pub id: u32,
@@ -244,21 +455,15 @@ pub struct SndAlias {
     pub snapshot_group: u8,
 }
 
-impl<'a> XFileDeserializeInto<SndAlias, ()> for SndAliasRaw<'a> {
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<SndAlias> {
-        let name = self.name.xfile_deserialize_into(de, ())?;
-        dbg!(&name);
-        let subtitle = self.subtitle.xfile_deserialize_into(de, ())?;
-        //dbg!(&subtitle);
-        let secondaryname = self.secondaryname.xfile_deserialize_into(de, ())?;
-        //dbg!(&secondaryname);
-        let sound_file = self.sound_file.xfile_deserialize_into(de, ())?;
-
-        Ok(SndAlias {
+impl<'a> SndAliasRaw<'a> {
+    fn into_alias(
+        self,
+        name: XString,
+        subtitle: XString,
+        secondaryname: XString,
+        sound_file: Option<Box<SoundFile>>,
+    ) -> SndAlias {
+        SndAlias {
             name,
             id: self.id,
             subtitle,
@@ -299,10 +504,29 @@ impl<'a> XFileDeserializeInto<SndAlias, ()> for SndAliasRaw<'a> {
             limit_count: self.limit_count,
             entity_limit_count: self.entity_limit_count,
             snapshot_group: self.snapshot_group,
-        })
+        }
+    }
+}
+
+impl<'a> XFileDeserializeInto<SndAlias, ()> for SndAliasRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<SndAlias> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        dbg!(&name);
+        let subtitle = self.subtitle.xfile_deserialize_into(de, ())?;
+        //dbg!(&subtitle);
+        let secondaryname = self.secondaryname.xfile_deserialize_into(de, ())?;
+        //dbg!(&secondaryname);
+        let sound_file = self.sound_file.xfile_deserialize_into(de, ())?;
+
+        Ok(self.into_alias(name, subtitle, secondaryname, sound_file))
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndAlias {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -362,23 +586,94 @@ impl XFileSerialize<()> for SndAlias {
     }
 }
 
+/// Borrowed counterpart of [`SndAlias`]. A loaded sound is kept in
+/// [`Self::loaded`] instead, and the alias's sound file is left as
+/// [`SoundFileRef::Loaded(None)`](SoundFileRef::Loaded).
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SndAliasRef<'buf> {
+    pub alias: SndAlias,
+    pub loaded: Option<LoadedSoundRef<'buf>>,
+}
+
+impl<'buf> SndAliasRef<'buf> {
+    pub fn to_owned(&self) -> SndAlias {
+        let mut alias = self.alias.clone();
+        if let (Some(loaded), Some(sound_file)) = (&self.loaded, alias.sound_file.as_mut()) {
+            sound_file.u = SoundFileRef::Loaded(Some(Box::new(loaded.to_owned())));
+        }
+        alias
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, SndAliasRef<'buf>, ()> for SndAliasRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<SndAliasRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let subtitle = self.subtitle.xfile_deserialize_into(de, ())?;
+        let secondaryname = self.secondaryname.xfile_deserialize_into(de, ())?;
+        let (sound_file, loaded) = match self.sound_file.xfile_deserialize_borrowed(de, ())? {
+            Some((sound_file, loaded)) => (Some(Box::new(sound_file)), loaded),
+            None => (None, None),
+        };
+
+        Ok(SndAliasRef {
+            alias: self.into_alias(name, subtitle, secondaryname, sound_file),
+            loaded,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SoundFileRaw<'a> {
     pub u: SoundFileRefRaw<'a>,
     pub type_: u8,
     pub exists: u8,
+    #[allow(dead_code)]
     pad: [u8; 2],
 }
 assert_size!(SoundFileRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SoundFile {
     pub u: SoundFileRef,
     pub exists: bool,
 }
 
+impl SoundFile {
+    /// The name of the loaded sound, or the file name of the streamed one.
+    pub fn name(&self) -> Option<&str> {
+        match &self.u {
+            SoundFileRef::Loaded(loaded) => loaded.as_ref().map(|l| l.name.get()),
+            SoundFileRef::Streamed(streamed) => streamed.as_ref().map(|s| s.filename.get()),
+        }
+    }
+
+    /// Replaces a streamed sound with `sound`, loaded from the sound bank it
+    /// was streamed from, so that the sound file holds the audio itself.
+    ///
+    /// Returns `false` (and leaves the sound file as it is) if it isn't a
+    /// streamed sound.
+    pub fn fuse_streamed(&mut self, sound: SndAsset) -> bool {
+        let SoundFileRef::Streamed(streamed) = &self.u else {
+            return false;
+        };
+
+        let name = streamed
+            .as_ref()
+            .map(|s| s.filename.clone())
+            .unwrap_or_default();
+        self.u = SoundFileRef::Loaded(Some(Box::new(LoadedSound { name, sound })));
+        true
+    }
+}
+
 impl<'a> XFileDeserializeInto<SoundFile, ()> for SoundFileRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -392,6 +687,7 @@ impl<'a> XFileDeserializeInto<SoundFile, ()> for SoundFileRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SoundFile {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let (u, type_) = match &self.u {
@@ -415,13 +711,40 @@ impl XFileSerialize<()> for SoundFile {
     }
 }
 
+/// Loaded sounds are borrowed, and streamed ones deserialized as usual.
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, (SoundFile, Option<LoadedSoundRef<'buf>>), ()>
+    for SoundFileRaw<'a>
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<(SoundFile, Option<LoadedSoundRef<'buf>>)> {
+        if self.type_ != 1 {
+            return Ok((self.xfile_deserialize_into(de, ())?, None));
+        }
+
+        let loaded = self
+            .u
+            .0
+            .cast::<LoadedSoundRaw>()
+            .xfile_deserialize_borrowed(de, ())?;
+        let sound_file = SoundFile {
+            u: SoundFileRef::Loaded(None),
+            exists: self.exists != 0,
+        };
+        Ok((sound_file, loaded))
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
-pub(crate) struct SoundFileRefRaw<'a>(Ptr32<'a, ()>);
+#[derive(Copy, Clone, Debug, XFileRead)]
+pub(crate) struct SoundFileRefRaw<'a>(pub Ptr32<'a, ()>);
 assert_size!(SoundFileRefRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SoundFileRef {
     Loaded(Option<Box<LoadedSound>>),
     Streamed(Option<Box<StreamedSound>>),
@@ -450,7 +773,7 @@ impl<'a> XFileDeserializeInto<SoundFileRef, u8> for SoundFileRefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct LoadedSoundRaw<'a> {
     pub name: XStringRaw<'a>,
     pub sound: SndAssetRaw<'a>,
@@ -458,7 +781,8 @@ pub(crate) struct LoadedSoundRaw<'a> {
 assert_size!(LoadedSoundRaw, 60);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct LoadedSound {
     pub name: XString,
     pub sound: SndAsset,
@@ -478,6 +802,7 @@ impl<'a> XFileDeserializeInto<LoadedSound, ()> for LoadedSoundRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for LoadedSound {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -508,8 +833,38 @@ impl XFileSerialize<()> for LoadedSound {
     }
 }
 
+/// Borrowed counterpart of [`LoadedSound`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoadedSoundRef<'buf> {
+    pub name: XString,
+    pub sound: SndAssetRef<'buf>,
+}
+
+impl<'buf> LoadedSoundRef<'buf> {
+    pub fn to_owned(&self) -> LoadedSound {
+        LoadedSound {
+            name: self.name.clone(),
+            sound: self.sound.to_owned(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, LoadedSoundRef<'buf>, ()> for LoadedSoundRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<LoadedSoundRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let sound = self.sound.xfile_deserialize_borrowed(de, ())?;
+
+        Ok(LoadedSoundRef { name, sound })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndAssetRaw<'a> {
     pub version: u32,
     pub frame_count: u32,
@@ -527,7 +882,8 @@ pub(crate) struct SndAssetRaw<'a> {
 assert_size!(SndAssetRaw, 56);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, Eq, Hash, PartialEq, FromPrimitive)]
 #[repr(u32)]
 pub enum SndAssetFormat {
     #[default]
@@ -541,9 +897,40 @@ pub enum SndAssetFormat {
     WMA = 7,
 }
 
+impl SndAssetFormat {
+    pub fn from_u32(value: u32) -> Option<Self> {
+        num::FromPrimitive::from_u32(value)
+    }
+
+    /// The `wFormatTag` and bits per sample of the format in a `.wav` file,
+    /// for the formats that can be written as one as-is.
+    pub const fn wav_format(self) -> Option<(u16, u16)> {
+        match self {
+            Self::PCMS16 => Some((1, 16)),
+            Self::PCMS24 => Some((1, 24)),
+            Self::PCMS32 => Some((1, 32)),
+            Self::IEEE => Some((3, 32)),
+            _ => None,
+        }
+    }
+
+    /// The extension to use for sounds in the format that can't be written
+    /// as a `.wav` file (see [`SndAsset::to_wav`]).
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::PCMS16 | Self::PCMS24 | Self::PCMS32 | Self::IEEE => "wav",
+            Self::XMA4 => "xma",
+            Self::MP3 => "mp3",
+            Self::MSADPCM => "adpcm",
+            Self::WMA => "wma",
+        }
+    }
+}
+
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
-    #[derive(Copy, Clone, Debug, Deserialize)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
     pub struct SndAssetFlags: u32 {
         const LOOPING         = 0x1;
         const PAD_LOOP_BUFFER = 0x2;
@@ -551,8 +938,9 @@ bitflags! {
 }
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
-    #[derive(Copy, Clone, Debug, Deserialize)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
     pub struct SndAssetChannel: u32 {
         const L   = 0x01;
         const R   = 0x02;
@@ -566,7 +954,8 @@ bitflags! {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SndAsset {
     pub version: u32,
     pub frame_count: u32,
@@ -582,12 +971,39 @@ pub struct SndAsset {
     pub data: Vec<u8>,
 }
 
-impl<'a> XFileDeserializeInto<SndAsset, ()> for SndAssetRaw<'a> {
-    fn xfile_deserialize_into(
+impl SndAsset {
+    /// Wraps the sound's data in a `.wav` file, or returns [`None`] if its
+    /// format isn't PCM (see [`SndAssetFormat::wav_format`]). The samples
+    /// are assumed to be little-endian.
+    pub fn to_wav(&self) -> Option<Vec<u8>> {
+        let (format_tag, bits_per_sample) = self.format.wav_format()?;
+        let channel_count = self.channel_count as u16;
+        let block_align = channel_count * bits_per_sample / 8;
+        let byte_rate = self.frame_rate * block_align as u32;
+
+        let mut wav = Vec::with_capacity(44 + self.data.len());
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + self.data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&format_tag.to_le_bytes());
+        wav.extend_from_slice(&channel_count.to_le_bytes());
+        wav.extend_from_slice(&self.frame_rate.to_le_bytes());
+        wav.extend_from_slice(&byte_rate.to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&self.data);
+        Some(wav)
+    }
+}
+
+impl<'a> SndAssetRaw<'a> {
+    fn formats(
         &self,
         de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<SndAsset> {
+    ) -> Result<(SndAssetFormat, SndAssetChannel, SndAssetFlags)> {
         let format = num::FromPrimitive::from_u32(self.format).ok_or(Error::new_with_offset(
             file_line_col!(),
             de.stream_pos()? as _,
@@ -604,8 +1020,20 @@ impl<'a> XFileDeserializeInto<SndAsset, ()> for SndAssetRaw<'a> {
             de.stream_pos()? as _,
             ErrorKind::BadFromPrimitive(self.flags as _),
         ))?;
-        let seek_table = self.seek_table.to_vec(de)?;
-        let data = self.data.to_vec(de)?;
+
+        Ok((format, channel_flags, flags))
+    }
+}
+
+impl<'a> XFileDeserializeInto<SndAsset, ()> for SndAssetRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<SndAsset> {
+        let (format, channel_flags, flags) = self.formats(de)?;
+        let seek_table = self.seek_table.to_vec_bulk(de)?;
+        let data = self.data.to_vec_bulk(de)?;
 
         Ok(SndAsset {
             version: self.version,
@@ -624,8 +1052,72 @@ impl<'a> XFileDeserializeInto<SndAsset, ()> for SndAssetRaw<'a> {
     }
 }
 
+/// Borrowed counterpart of [`SndAsset`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SndAssetRef<'buf> {
+    pub version: u32,
+    pub frame_count: u32,
+    pub frame_rate: u32,
+    pub channel_count: u32,
+    pub header_size: u32,
+    pub block_size: u32,
+    pub buffer_size: u32,
+    pub format: SndAssetFormat,
+    pub channel_flags: SndAssetChannel,
+    pub flags: SndAssetFlags,
+    pub seek_table: Vec<u32>,
+    pub data: &'buf [u8],
+}
+
+impl<'buf> SndAssetRef<'buf> {
+    pub fn to_owned(&self) -> SndAsset {
+        SndAsset {
+            version: self.version,
+            frame_count: self.frame_count,
+            frame_rate: self.frame_rate,
+            channel_count: self.channel_count,
+            header_size: self.header_size,
+            block_size: self.block_size,
+            buffer_size: self.buffer_size,
+            format: self.format,
+            channel_flags: self.channel_flags,
+            flags: self.flags,
+            seek_table: self.seek_table.clone(),
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, SndAssetRef<'buf>, ()> for SndAssetRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<SndAssetRef<'buf>> {
+        let (format, channel_flags, flags) = self.formats(de)?;
+        let seek_table = self.seek_table.to_vec_bulk(de)?;
+        let data = self.data.to_slice(de)?;
+
+        Ok(SndAssetRef {
+            version: self.version,
+            frame_count: self.frame_count,
+            frame_rate: self.frame_rate,
+            channel_count: self.channel_count,
+            header_size: self.header_size,
+            block_size: self.block_size,
+            buffer_size: self.buffer_size,
+            format,
+            channel_flags,
+            flags,
+            seek_table,
+            data,
+        })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct StreamedSoundRaw<'a> {
     pub filename: XStringRaw<'a>,
     pub prime_snd: Ptr32<'a, PrimedSndRaw<'a>>,
@@ -633,7 +1125,8 @@ pub(crate) struct StreamedSoundRaw<'a> {
 assert_size!(StreamedSoundRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct StreamedSound {
     pub filename: XString,
     pub prime_snd: Option<Box<PrimedSnd>>,
@@ -656,6 +1149,7 @@ impl<'a> XFileDeserializeInto<StreamedSound, ()> for StreamedSoundRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for StreamedSound {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let filename = XStringRaw::from_str(self.filename.get());
@@ -673,7 +1167,7 @@ impl XFileSerialize<()> for StreamedSound {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct PrimedSndRaw<'a> {
     pub name: XStringRaw<'a>,
     pub buffer: FatPointerCountLastU32<'a, u8>,
@@ -681,7 +1175,8 @@ pub(crate) struct PrimedSndRaw<'a> {
 assert_size!(PrimedSndRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PrimedSnd {
     pub name: XString,
     pub buffer: Vec<u8>,
@@ -695,12 +1190,13 @@ impl<'a> XFileDeserializeInto<PrimedSnd, ()> for PrimedSndRaw<'a> {
     ) -> Result<PrimedSnd> {
         let name = self.name.xfile_deserialize_into(de, ())?;
         //dbg!(&name);
-        let buffer = self.buffer.to_vec(de)?;
+        let buffer = self.buffer.to_vec_bulk(de)?;
 
         Ok(PrimedSnd { name, buffer })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PrimedSnd {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -714,14 +1210,16 @@ impl XFileSerialize<()> for PrimedSnd {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct SndIndexEntry {
     pub value: u16,
     pub next: u16,
 }
 assert_size!(SndIndexEntry, 4);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndIndexEntry {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -729,44 +1227,9 @@ impl XFileSerialize<()> for SndIndexEntry {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
-pub(crate) struct SndName([u8; 32]);
-
-impl Display for SndName {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let len = self
-            .0
-            .into_iter()
-            .enumerate()
-            .find(|(_, c)| *c == 0)
-            .map(|(i, _)| i)
-            .unwrap_or(32);
-        let s = self.0[..len].iter().map(|c| *c as char).collect::<String>();
-        write!(f, "{}", s)
-    }
-}
-
-impl SndName {
-    fn from_str(string: &str) -> Result<Self> {
-        if string.len() > 32 {
-            return Err(Error::new(
-                file_line_col!(),
-                ErrorKind::BrokenInvariant(format!(
-                    "SndName: string \"{string}\" too long (must be <=32 bytes)."
-                )),
-            ));
-        }
-
-        let mut bytes = string.chars().map(|c| c as u8).collect::<Vec<_>>();
-        bytes.resize(32, 0);
-        Ok(Self(bytes.try_into().unwrap()))
-    }
-}
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndRadverbRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
     pub id: u32,
     pub smoothing: f32,
     pub early_time: f32,
@@ -787,7 +1250,8 @@ pub(crate) struct SndRadverbRaw {
 assert_size!(SndRadverbRaw, 96);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndRadverb {
     pub name: XString,
     pub id: u32,
@@ -834,9 +1298,10 @@ impl From<SndRadverbRaw> for SndRadverb {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndRadverb {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
+        let name = self.name.get().parse()?;
 
         let snd_radverb = SndRadverbRaw {
             name,
@@ -863,24 +1328,25 @@ impl XFileSerialize<()> for SndRadverb {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndSnapshotRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
     pub id: u32,
-    pub occlusion_name: SndName,
+    pub occlusion_name: FixedString<32>,
     pub occlusion_id: u32,
     pub fade_in: f32,
     pub fade_out: f32,
     pub distance: f32,
     pub fade_in_curve: u32,
     pub fade_out_curve: u32,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub attenuation: [f32; 64],
 }
 assert_size!(SndSnapshotRaw, 348);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndSnapshot {
     pub name: XString,
     pub id: u32,
@@ -917,10 +1383,11 @@ impl From<SndSnapshotRaw> for SndSnapshot {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndSnapshot {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
-        let occlusion_name = SndName::from_str(self.occlusion_name.get())?;
+        let name = self.name.get().parse()?;
+        let occlusion_name = self.occlusion_name.get().parse()?;
 
         let snd_snapshot = SndSnapshotRaw {
             name,
@@ -942,7 +1409,7 @@ impl XFileSerialize<()> for SndSnapshot {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct SndPatchRaw<'a> {
     pub name: XStringRaw<'a>,
     pub elements: FatPointerCountFirstU32<'a, u32>,
@@ -951,7 +1418,8 @@ pub(crate) struct SndPatchRaw<'a> {
 assert_size!(SndPatchRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndPatch {
     pub name: XString,
     pub elements: Vec<u32>,
@@ -966,7 +1434,7 @@ impl<'a> XFileDeserializeInto<SndPatch, ()> for SndPatchRaw<'a> {
     ) -> Result<SndPatch> {
         let name = self.name.xfile_deserialize_into(de, ())?;
         //dbg!(&name);
-        let elements = self.elements.to_vec(de)?;
+        let elements = self.elements.to_vec_bulk(de)?;
         let files = self.files.xfile_deserialize_into(de, ())?;
 
         Ok(SndPatch {
@@ -977,6 +1445,7 @@ impl<'a> XFileDeserializeInto<SndPatch, ()> for SndPatchRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndPatch {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -997,7 +1466,7 @@ impl XFileSerialize<()> for SndPatch {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndDriverGlobalsRaw<'a> {
     pub name: XStringRaw<'a>,
     pub groups: FatPointerCountFirstU32<'a, SndGroupRaw>,
@@ -1010,7 +1479,8 @@ pub(crate) struct SndDriverGlobalsRaw<'a> {
 assert_size!(SndDriverGlobalsRaw, 52);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndDriverGlobals {
     pub name: XString,
     pub groups: Vec<SndGroup>,
@@ -1053,6 +1523,7 @@ impl<'a> XFileDeserializeInto<SndDriverGlobals, ()> for SndDriverGlobalsRaw<'a>
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndDriverGlobals {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -1085,10 +1556,10 @@ impl XFileSerialize<()> for SndDriverGlobals {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndGroupRaw {
-    pub name: SndName,
-    pub parent_name: SndName,
+    pub name: FixedString<32>,
+    pub parent_name: FixedString<32>,
     pub id: u32,
     pub parent_index: i32,
     pub category: u32,
@@ -1098,7 +1569,8 @@ pub(crate) struct SndGroupRaw {
 assert_size!(SndGroupRaw, 80);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u32)]
 pub enum SndCategory {
     #[default]
@@ -1110,7 +1582,8 @@ pub enum SndCategory {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SndGroup {
     pub name: XString,
     pub parent_name: XString,
@@ -1146,10 +1619,11 @@ impl TryInto<SndGroup> for SndGroupRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndGroup {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
-        let parent_name = SndName::from_str(self.parent_name.get())?;
+        let name = self.name.get().parse()?;
+        let parent_name = self.parent_name.get().parse()?;
 
         let snd_group = SndGroupRaw {
             name,
@@ -1166,16 +1640,17 @@ impl XFileSerialize<()> for SndGroup {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndCurveRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
     pub id: u32,
     pub points: [[f32; 2]; 8],
 }
 assert_size!(SndCurveRaw, 100);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndCurve {
     pub name: XString,
     pub id: u32,
@@ -1196,9 +1671,10 @@ impl From<SndCurveRaw> for SndCurve {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndCurve {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
+        let name = self.name.get().parse()?;
         let points = self.points.map(|p| p.get());
 
         let snd_curve = SndCurveRaw {
@@ -1212,9 +1688,9 @@ impl XFileSerialize<()> for SndCurve {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndPanRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
     pub id: u32,
     pub front: f32,
     pub back: f32,
@@ -1226,7 +1702,8 @@ pub(crate) struct SndPanRaw {
 assert_size!(SndPanRaw, 60);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndPan {
     pub name: XString,
     pub id: u32,
@@ -1256,9 +1733,10 @@ impl From<SndPanRaw> for SndPan {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndPan {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
+        let name = self.name.get().parse()?;
 
         let snd_pan = SndPanRaw {
             name,
@@ -1276,14 +1754,15 @@ impl XFileSerialize<()> for SndPan {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndSnapshotGroupRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
 }
 assert_size!(SndSnapshotGroupRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct SndSnapshotGroup {
     pub name: XString,
 }
@@ -1296,9 +1775,10 @@ impl From<SndSnapshotGroupRaw> for SndSnapshotGroup {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndSnapshotGroup {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
+        let name = self.name.get().parse()?;
 
         let snd_snapshot_group = SndSnapshotGroupRaw { name };
 
@@ -1306,8 +1786,9 @@ impl XFileSerialize<()> for SndSnapshotGroup {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct SndContext {
     pub type_: u32,
     pub value_count: u32,
@@ -1315,6 +1796,7 @@ pub struct SndContext {
 }
 assert_size!(SndContext, 40);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndContext {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -1322,9 +1804,9 @@ impl XFileSerialize<()> for SndContext {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct SndMasterRaw {
-    pub name: SndName,
+    pub name: FixedString<32>,
     pub id: u32,
     pub notch_e: f32,
     pub notch_g: f32,
@@ -1365,7 +1847,8 @@ pub(crate) struct SndMasterRaw {
 assert_size!(SndMasterRaw, 176);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SndMaster {
     pub name: XString,
     pub id: u32,
@@ -1453,9 +1936,10 @@ impl From<SndMasterRaw> for SndMaster {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for SndMaster {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let name = SndName::from_str(self.name.get())?;
+        let name = self.name.get().parse()?;
 
         let snd_master = SndMasterRaw {
             name,
@@ -0,0 +1,154 @@
+//! Counts and size estimates for a set of assets, e.g. to keep a zone under
+//! a console's memory budget.
+
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::xasset::{XAsset, XAssetGeneric, XAssetType};
+
+#[cfg(feature = "world")]
+use crate::gfx_world::GfxWorld;
+#[cfg(feature = "audio")]
+use crate::sound::{SndBank, SoundFileRef};
+#[cfg(feature = "models")]
+use crate::xmodel::XModel;
+
+/// The number of assets kept in [`XAssetStats::largest`].
+pub const LARGEST_ASSET_COUNT: usize = 10;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct XAssetTypeStats {
+    pub count: usize,
+    /// See [`XAsset::estimated_size`].
+    pub estimated_size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct XAssetSize {
+    pub asset_type: XAssetType,
+    pub name: String,
+    pub estimated_size: u64,
+}
+
+/// Statistics about a set of assets, usually obtained with
+/// [`XAssetBag::stats`](crate::xasset::XAssetBag::stats).
+///
+/// Null assets are skipped. Sizes are only estimated if the `serde` and
+/// `bincode` features are enabled, and are 0 otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XAssetStats {
+    pub types: BTreeMap<XAssetType, XAssetTypeStats>,
+    /// The [`LARGEST_ASSET_COUNT`] largest assets, largest first.
+    pub largest: Vec<XAssetSize>,
+    /// Vertices of [`XModel`] surfaces and [`GfxWorld`]s. Collision geometry
+    /// isn't counted.
+    pub vertex_count: u64,
+    /// Triangles of [`XModel`] surfaces and [`GfxWorld`]s.
+    pub triangle_count: u64,
+    /// Samples (frames times channels) of the sounds loaded by
+    /// [`SndBank`]s. Streamed sounds aren't counted.
+    pub sound_sample_count: u64,
+}
+
+impl XAssetStats {
+    pub fn from_assets<'a>(assets: impl IntoIterator<Item = &'a XAsset>) -> Self {
+        let mut stats = Self::default();
+
+        for asset in assets {
+            let Some(name) = asset.name() else {
+                continue;
+            };
+
+            #[cfg(all(feature = "serde", feature = "bincode"))]
+            let estimated_size = asset.estimated_size();
+            #[cfg(not(all(feature = "serde", feature = "bincode")))]
+            let estimated_size = 0;
+
+            let type_stats = stats.types.entry(asset.asset_type()).or_default();
+            type_stats.count += 1;
+            type_stats.estimated_size += estimated_size;
+
+            let i = stats
+                .largest
+                .partition_point(|a| a.estimated_size >= estimated_size);
+            if i < LARGEST_ASSET_COUNT {
+                stats.largest.insert(
+                    i,
+                    XAssetSize {
+                        asset_type: asset.asset_type(),
+                        name: String::from(name),
+                        estimated_size,
+                    },
+                );
+                stats.largest.truncate(LARGEST_ASSET_COUNT);
+            }
+
+            match asset {
+                XAsset::PC(a) => stats.add_contents(a),
+                XAsset::Console(a) => stats.add_contents(a),
+            }
+        }
+
+        stats
+    }
+
+    fn add_contents<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        asset: &XAssetGeneric<MAX_LOCAL_CLIENTS>,
+    ) {
+        match asset {
+            #[cfg(feature = "models")]
+            XAssetGeneric::XModel(Some(model)) => self.add_xmodel(model),
+            #[cfg(feature = "world")]
+            XAssetGeneric::GfxWorld(Some(world)) => self.add_gfx_world(world),
+            #[cfg(feature = "audio")]
+            XAssetGeneric::Sound(Some(bank)) => self.add_snd_bank(bank),
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "models")]
+    fn add_xmodel(&mut self, model: &XModel) {
+        for surf in &model.surfs {
+            self.vertex_count += surf.verts0.len() as u64;
+            self.triangle_count += surf.tri_indices.len() as u64 / 3;
+        }
+    }
+
+    #[cfg(feature = "world")]
+    fn add_gfx_world<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        world: &GfxWorld<MAX_LOCAL_CLIENTS>,
+    ) {
+        self.vertex_count += world.draw.vertex_count as u64;
+        self.triangle_count += world.draw.indices.len() as u64 / 3;
+    }
+
+    #[cfg(feature = "audio")]
+    fn add_snd_bank(&mut self, bank: &SndBank) {
+        let loaded = bank
+            .aliases
+            .iter()
+            .flat_map(|list| &list.aliases)
+            .filter_map(|alias| alias.sound_file.as_deref())
+            .filter_map(|file| match &file.u {
+                SoundFileRef::Loaded(Some(loaded)) => Some(&loaded.sound),
+                _ => None,
+            });
+
+        for sound in loaded {
+            self.sound_sample_count += sound.frame_count as u64 * sound.channel_count as u64;
+        }
+    }
+
+    /// The number of (non-null) assets.
+    pub fn total_count(&self) -> usize {
+        self.types.values().map(|t| t.count).sum()
+    }
+
+    pub fn total_estimated_size(&self) -> u64 {
+        self.types.values().map(|t| t.estimated_size).sum()
+    }
+}
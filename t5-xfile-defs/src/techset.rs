@@ -1,32 +1,34 @@
-use core::mem::transmute;
-
 use alloc::{boxed::Box, format, vec::Vec};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountLastU32, FlexibleArray, FlexibleArrayU16,
-    FlexibleArrayU32, Ptr32, Result, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto,
-    XFileSerialize, XString, XStringRaw, assert_size,
+    BulkPrimitive, Error, ErrorKind, FatPointerCountLastU32, FlexibleArrayU16, FlexibleArrayU32,
+    Ptr32, Result, T5XFileDeserialize, T5XFileDeserializeBorrowed, XArray,
+    XFileDeserializeBorrowed, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     common::{GfxCubeTexture, GfxPixelShader, GfxVertexShader, GfxVolumeTexture, Vec2, Vec4},
-    file_line_col,
+    file_line_col, read, size_of,
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, FlexibleArray, T5XFileSerialize, XFileSerialize};
 
+use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 const MAX_TECHNIQUES: usize = 130;
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct MaterialTechniqueSetRaw<'a> {
     pub name: XStringRaw<'a>,
     pub world_vert_format: u8,
     #[allow(dead_code)]
     unused: u8,
     pub techset_flags: u16,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub techniques: [Ptr32<'a, MaterialTechniqueRaw<'a>>; MAX_TECHNIQUES],
 }
 assert_size!(MaterialTechniqueSetRaw, 528);
@@ -44,7 +46,8 @@ impl<'a> Default for MaterialTechniqueSetRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MaterialTechniqueSet {
     pub name: XString,
     pub world_vert_format: u8,
@@ -66,7 +69,7 @@ impl<'a> XFileDeserializeInto<MaterialTechniqueSet, ()> for MaterialTechniqueSet
         let techniques = self.techniques;
         let techniques = techniques
             .iter()
-            .flat_map(|p| p.xfile_deserialize_into(de, ()))
+            .flat_map(|p| -> Result<Option<Box<_>>> { p.xfile_deserialize_into(de, ()) })
             .flatten()
             .collect::<Vec<_>>();
 
@@ -81,6 +84,7 @@ impl<'a> XFileDeserializeInto<MaterialTechniqueSet, ()> for MaterialTechniqueSet
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialTechniqueSet {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -104,8 +108,63 @@ impl XFileSerialize<()> for MaterialTechniqueSet {
     }
 }
 
+/// Borrowed counterpart of [`MaterialTechniqueSet`], whose shader programs
+/// are borrowed.
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialTechniqueSetRef<'buf> {
+    pub name: XString,
+    pub world_vert_format: u8,
+    pub techset_flags: u16,
+    pub techniques: Vec<MaterialTechniqueRef<'buf>>,
+}
+
+impl<'buf> MaterialTechniqueSetRef<'buf> {
+    /// Copies the shader programs, which are decoded as `u32`s in the
+    /// file's byte order.
+    pub fn to_owned(&self, little_endian: bool) -> MaterialTechniqueSet {
+        MaterialTechniqueSet {
+            name: self.name.clone(),
+            world_vert_format: self.world_vert_format,
+            techset_flags: self.techset_flags,
+            techniques: self
+                .techniques
+                .iter()
+                .map(|t| Box::new(t.to_owned(little_endian)))
+                .collect(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, MaterialTechniqueSetRef<'buf>, ()>
+    for MaterialTechniqueSetRaw<'a>
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<MaterialTechniqueSetRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+
+        // techniques that fail to load are dropped, like when deserializing
+        let techniques = self
+            .techniques
+            .iter()
+            .flat_map(|p| p.xfile_deserialize_borrowed(de, ()))
+            .flatten()
+            .collect();
+
+        Ok(MaterialTechniqueSetRef {
+            name,
+            world_vert_format: self.world_vert_format,
+            techset_flags: self.techset_flags,
+            techniques,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialTechniqueRaw<'a> {
     pub name: XStringRaw<'a>,
     pub flags: u16,
@@ -114,7 +173,8 @@ pub(crate) struct MaterialTechniqueRaw<'a> {
 assert_size!(MaterialTechniqueRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MaterialTechnique {
     pub name: XString,
     pub flags: u16,
@@ -149,6 +209,7 @@ impl<'a> XFileDeserializeInto<MaterialTechnique, ()> for MaterialTechniqueRaw<'a
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialTechnique {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -164,8 +225,55 @@ impl XFileSerialize<()> for MaterialTechnique {
     }
 }
 
+/// Borrowed counterpart of [`MaterialTechnique`].
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialTechniqueRef<'buf> {
+    pub name: XString,
+    pub flags: u16,
+    pub passes: Vec<MaterialPassRef<'buf>>,
+}
+
+impl<'buf> MaterialTechniqueRef<'buf> {
+    pub fn to_owned(&self, little_endian: bool) -> MaterialTechnique {
+        MaterialTechnique {
+            name: self.name.clone(),
+            flags: self.flags,
+            passes: self
+                .passes
+                .iter()
+                .map(|p| p.to_owned(little_endian))
+                .collect(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, MaterialTechniqueRef<'buf>, ()>
+    for MaterialTechniqueRaw<'a>
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<MaterialTechniqueRef<'buf>> {
+        let passes = self
+            .passes
+            .to_vec(de)?
+            .iter()
+            .map(|t| t.xfile_deserialize_borrowed(de, ()))
+            .collect::<Result<Vec<_>>>()?;
+        let name = self.name.xfile_deserialize_into(de, ())?;
+
+        Ok(MaterialTechniqueRef {
+            name,
+            flags: self.flags,
+            passes,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialPassRaw<'a> {
     pub vertex_decl: Ptr32<'a, MaterialVertexDeclaration>,
     pub vertex_shader: Ptr32<'a, MaterialVertexShaderRaw<'a>>,
@@ -179,7 +287,8 @@ pub(crate) struct MaterialPassRaw<'a> {
 assert_size!(MaterialPassRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MaterialPass {
     pub vertex_decl: Option<Box<MaterialVertexDeclaration>>,
     pub vertex_shader: Option<Box<MaterialVertexShader>>,
@@ -191,6 +300,31 @@ pub struct MaterialPass {
     pub args: Vec<MaterialShaderArgument>,
 }
 
+impl<'a> MaterialPassRaw<'a> {
+    fn deserialize_args(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+    ) -> Result<Vec<MaterialShaderArgument>> {
+        let argc = self.per_prim_arg_count as u16
+            + self.per_obj_arg_count as u16
+            + self.stable_arg_count as u16;
+
+        let mut args = Vec::new();
+
+        if self.args != 0 {
+            de.check_array_len(argc as _, size_of!(MaterialShaderArgumentRaw))?;
+            args.reserve(argc as _);
+            for _ in 0..argc {
+                let arg_raw = de.load_from_xfile::<MaterialShaderArgumentRaw>()?;
+                let arg = arg_raw.xfile_deserialize_into(de, ())?;
+                args.push(arg);
+            }
+        }
+
+        Ok(args)
+    }
+}
+
 impl<'a> XFileDeserializeInto<MaterialPass, ()> for MaterialPassRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -210,24 +344,7 @@ impl<'a> XFileDeserializeInto<MaterialPass, ()> for MaterialPassRaw<'a> {
         let pixel_shader = pixel_shader.xfile_deserialize_into(de, ())?;
         //dbg!(&pixel_shader);
 
-        let argc = self.per_prim_arg_count as u16
-            + self.per_obj_arg_count as u16
-            + self.stable_arg_count as u16;
-
-        let mut args = Vec::with_capacity(argc as _);
-
-        if self.args != 0 {
-            for _ in 0..argc {
-                //let pos = xfile.stream_position()?;
-                //dbg!(pos);
-                let arg_raw = de.load_from_xfile::<MaterialShaderArgumentRaw>()?;
-                //let pos = xfile.stream_position()?;
-                //dbg!(pos);
-                let arg = arg_raw.xfile_deserialize_into(de, ())?;
-                args.push(arg);
-            }
-        }
-
+        let args = self.deserialize_args(de)?;
         //dbg!(&args);
 
         Ok(MaterialPass {
@@ -243,6 +360,7 @@ impl<'a> XFileDeserializeInto<MaterialPass, ()> for MaterialPassRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialPass {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let vertex_decl = Ptr32::from_box(&self.vertex_decl);
@@ -272,8 +390,68 @@ impl XFileSerialize<()> for MaterialPass {
     }
 }
 
+/// Borrowed counterpart of [`MaterialPass`].
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaterialPassRef<'buf> {
+    pub vertex_decl: Option<Box<MaterialVertexDeclaration>>,
+    pub vertex_shader: Option<MaterialVertexShaderRef<'buf>>,
+    pub pixel_shader: Option<MaterialPixelShaderRef<'buf>>,
+    pub per_prim_arg_count: u8,
+    pub per_obj_arg_count: u8,
+    pub stable_arg_count: u8,
+    pub custom_sampler_flags: u8,
+    pub args: Vec<MaterialShaderArgument>,
+}
+
+impl<'buf> MaterialPassRef<'buf> {
+    pub fn to_owned(&self, little_endian: bool) -> MaterialPass {
+        MaterialPass {
+            vertex_decl: self.vertex_decl.clone(),
+            vertex_shader: self
+                .vertex_shader
+                .as_ref()
+                .map(|vs| Box::new(vs.to_owned(little_endian))),
+            pixel_shader: self
+                .pixel_shader
+                .as_ref()
+                .map(|ps| Box::new(ps.to_owned(little_endian))),
+            per_prim_arg_count: self.per_prim_arg_count,
+            per_obj_arg_count: self.per_obj_arg_count,
+            stable_arg_count: self.stable_arg_count,
+            custom_sampler_flags: self.custom_sampler_flags,
+            args: self.args.clone(),
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, MaterialPassRef<'buf>, ()> for MaterialPassRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<MaterialPassRef<'buf>> {
+        let vertex_decl = self.vertex_decl.xfile_get(de)?.map(Box::new);
+        let vertex_shader = self.vertex_shader.xfile_deserialize_borrowed(de, ())?;
+        let pixel_shader = self.pixel_shader.xfile_deserialize_borrowed(de, ())?;
+        let args = self.deserialize_args(de)?;
+
+        Ok(MaterialPassRef {
+            vertex_decl,
+            vertex_shader,
+            pixel_shader,
+            per_prim_arg_count: self.per_prim_arg_count,
+            per_obj_arg_count: self.per_obj_arg_count,
+            stable_arg_count: self.stable_arg_count,
+            custom_sampler_flags: self.custom_sampler_flags,
+            args,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct MaterialVertexDeclaration {
     pub stream_count: u8,
     pub has_optional_source: bool,
@@ -284,34 +462,39 @@ pub struct MaterialVertexDeclaration {
 }
 assert_size!(MaterialVertexDeclaration, 108);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialVertexDeclaration {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct MaterialVertexStreamRouting {
     pub data: [MaterialStreamRouting; 16],
     pub decl: [u32; 18],
 }
 assert_size!(MaterialVertexStreamRouting, 104);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialVertexStreamRouting {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct MaterialStreamRouting {
     pub source: u8,
     pub data: u8,
 }
 assert_size!(MaterialStreamRouting, 2);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialStreamRouting {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -319,7 +502,7 @@ impl XFileSerialize<()> for MaterialStreamRouting {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialVertexShaderRaw<'a> {
     pub name: XStringRaw<'a>,
     pub prog: MaterialVertexShaderProgramRaw<'a>,
@@ -327,7 +510,8 @@ pub(crate) struct MaterialVertexShaderRaw<'a> {
 assert_size!(MaterialVertexShaderRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MaterialVertexShader {
     pub name: XString,
     pub prog: MaterialVertexShaderProgram,
@@ -353,6 +537,7 @@ impl<'a> XFileDeserializeInto<MaterialVertexShader, ()> for MaterialVertexShader
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialVertexShader {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -370,8 +555,46 @@ impl XFileSerialize<()> for MaterialVertexShader {
     }
 }
 
+/// Borrowed counterpart of [`MaterialVertexShader`], with the program's
+/// bytes in the file's byte order.
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialVertexShaderRef<'buf> {
+    pub name: XString,
+    pub program: &'buf [u8],
+}
+
+impl<'buf> MaterialVertexShaderRef<'buf> {
+    pub fn to_owned(&self, little_endian: bool) -> MaterialVertexShader {
+        MaterialVertexShader {
+            name: self.name.clone(),
+            prog: MaterialVertexShaderProgram {
+                vs: None,
+                load_def: GfxVertexShaderLoadDef {
+                    program: u32::from_bytes(self.program.to_vec(), little_endian),
+                },
+            },
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, MaterialVertexShaderRef<'buf>, ()>
+    for MaterialVertexShaderRaw<'a>
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<MaterialVertexShaderRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let program = self.prog.load_def.program.to_slice(de)?;
+
+        Ok(MaterialVertexShaderRef { name, program })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialVertexShaderProgramRaw<'a> {
     #[cfg_attr(not(feature = "d3d9"), allow(dead_code))]
     pub vs: Ptr32<'a, ()>,
@@ -380,7 +603,8 @@ pub(crate) struct MaterialVertexShaderProgramRaw<'a> {
 assert_size!(MaterialVertexShaderProgramRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MaterialVertexShaderProgram {
     pub vs: Option<Box<GfxVertexShader>>,
     pub load_def: GfxVertexShaderLoadDef,
@@ -429,14 +653,15 @@ impl<'a> XFileDeserializeInto<MaterialVertexShaderProgram, ()>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxVertexShaderLoadDefRaw<'a> {
     pub program: FatPointerCountLastU32<'a, u32>,
 }
 assert_size!(GfxVertexShaderLoadDefRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxVertexShaderLoadDef {
     pub program: Vec<u32>,
 }
@@ -451,7 +676,7 @@ impl<'a> XFileDeserializeInto<GfxVertexShaderLoadDef, ()> for GfxVertexShaderLoa
     ) -> Result<GfxVertexShaderLoadDef> {
         //dbg!(*self);
 
-        let program = self.program.to_vec(de)?;
+        let program = self.program.to_vec_bulk(de)?;
         //dbg!(&program[0]);
         if !de.silent() && !program.is_empty() && program[0] != DXBC_MAGIC {
             println!(
@@ -464,7 +689,7 @@ impl<'a> XFileDeserializeInto<GfxVertexShaderLoadDef, ()> for GfxVertexShaderLoa
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialPixelShaderRaw<'a> {
     pub name: XStringRaw<'a>,
     pub prog: MaterialPixelShaderProgramRaw<'a>,
@@ -472,7 +697,8 @@ pub(crate) struct MaterialPixelShaderRaw<'a> {
 assert_size!(MaterialPixelShaderRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MaterialPixelShader {
     pub name: XString,
     pub prog: MaterialPixelShaderProgram,
@@ -491,6 +717,7 @@ impl<'a> XFileDeserializeInto<MaterialPixelShader, ()> for MaterialPixelShaderRa
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialPixelShader {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -505,8 +732,46 @@ impl XFileSerialize<()> for MaterialPixelShader {
     }
 }
 
+/// Borrowed counterpart of [`MaterialPixelShader`], with the program's
+/// bytes in the file's byte order.
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MaterialPixelShaderRef<'buf> {
+    pub name: XString,
+    pub program: &'buf [u8],
+}
+
+impl<'buf> MaterialPixelShaderRef<'buf> {
+    pub fn to_owned(&self, little_endian: bool) -> MaterialPixelShader {
+        MaterialPixelShader {
+            name: self.name.clone(),
+            prog: MaterialPixelShaderProgram {
+                ps: None,
+                load_def: GfxPixelShaderLoadDef {
+                    program: u32::from_bytes(self.program.to_vec(), little_endian),
+                },
+            },
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, MaterialPixelShaderRef<'buf>, ()>
+    for MaterialPixelShaderRaw<'a>
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<MaterialPixelShaderRef<'buf>> {
+        let name = self.name.xfile_deserialize_into(de, ())?;
+        let program = self.prog.load_def.program.to_slice(de)?;
+
+        Ok(MaterialPixelShaderRef { name, program })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialPixelShaderProgramRaw<'a> {
     #[cfg_attr(not(feature = "d3d9"), allow(dead_code))]
     pub ps: Ptr32<'a, ()>,
@@ -515,7 +780,8 @@ pub(crate) struct MaterialPixelShaderProgramRaw<'a> {
 assert_size!(MaterialPixelShaderProgramRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MaterialPixelShaderProgram {
     pub ps: Option<Box<GfxPixelShader>>,
     pub load_def: GfxPixelShaderLoadDef,
@@ -564,14 +830,15 @@ impl<'a> XFileDeserializeInto<MaterialPixelShaderProgram, ()>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxPixelShaderLoadDefRaw<'a> {
     pub program: FatPointerCountLastU32<'a, u32>,
 }
 assert_size!(GfxPixelShaderLoadDefRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GfxPixelShaderLoadDef {
     pub program: Vec<u32>,
 }
@@ -586,14 +853,15 @@ impl<'a> XFileDeserializeInto<GfxPixelShaderLoadDef, ()> for GfxPixelShaderLoadD
         //let pos = xfile.stream_position()?;
         //dbg!(pos);
 
-        let program = self.program.to_vec(de)?;
+        let program = self.program.to_vec_bulk(de)?;
 
         Ok(GfxPixelShaderLoadDef { program })
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum MaterialArgumentDefRaw {
     LiteralConst([f32; 4]),
     CodeConst(MaterialArgumentCodeConst),
@@ -602,7 +870,8 @@ pub enum MaterialArgumentDefRaw {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum MaterialArgumentDef {
     LiteralConst(Vec4),
     CodeConst(MaterialArgumentCodeConst),
@@ -633,7 +902,7 @@ impl Into<MaterialArgumentDefRaw> for MaterialArgumentDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialShaderArgumentRaw {
     pub arg_type: u16,
     pub dest: u16,
@@ -642,7 +911,8 @@ pub(crate) struct MaterialShaderArgumentRaw {
 assert_size!(MaterialShaderArgumentRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct MaterialShaderArgument {
     pub arg_type: MtlArg,
     pub dest: u16,
@@ -660,7 +930,7 @@ impl XFileDeserializeInto<MaterialShaderArgument, ()> for MaterialShaderArgument
 
         //dbg!(*self);
 
-        if self.arg_type > 7 {
+        let Some(arg_type) = MtlArg::from_u16(self.arg_type) else {
             return Err(Error::new_with_offset(
                 file_line_col!(),
                 de.stream_pos()? as _,
@@ -669,35 +939,42 @@ impl XFileDeserializeInto<MaterialShaderArgument, ()> for MaterialShaderArgument
                     self.arg_type
                 )),
             ));
-        }
+        };
 
-        let u = match self.arg_type {
-            MTL_ARG_LITERAL_PIXEL_CONST | MTL_ARG_LITERAL_VERTEX_CONST => {
+        let u = match arg_type {
+            MtlArg::LITERAL_PIXEL_CONST | MtlArg::LITERAL_VERTEX_CONST => {
                 MaterialArgumentDefRaw::LiteralConst(de.load_from_xfile()?)
             }
-            MTL_ARG_CODE_PIXEL_CONST | MTL_ARG_CODE_VERTEX_CONST => {
-                MaterialArgumentDefRaw::CodeConst(MaterialArgumentCodeConst::from_u32(self.u))
+            MtlArg::CODE_PIXEL_CONST | MtlArg::CODE_VERTEX_CONST => {
+                MaterialArgumentDefRaw::CodeConst(MaterialArgumentCodeConst::from_u32(
+                    self.u,
+                    de.is_little_endian(),
+                ))
             }
-            MTL_ARG_CODE_PIXEL_SAMPLER => MaterialArgumentDefRaw::CodeSampler(self.u),
-            MTL_ARG_MATERIAL_VERTEX_CONST
-            | MTL_ARG_MATERIAL_PIXEL_SAMPLER
-            | MTL_ARG_MATERIAL_PRIM_END => MaterialArgumentDefRaw::NameHash(self.u),
-            _ => unreachable!(), // safe because of the check above
+            MtlArg::CODE_PIXEL_SAMPLER => MaterialArgumentDefRaw::CodeSampler(self.u),
+            MtlArg::MATERIAL_VERTEX_CONST
+            | MtlArg::MATERIAL_PIXEL_SAMPLER
+            | MtlArg::MATERIAL_PRIM_END => MaterialArgumentDefRaw::NameHash(self.u),
         };
 
         Ok(MaterialShaderArgument {
-            arg_type: unsafe { transmute(self.arg_type) },
+            arg_type,
             dest: self.dest,
             u: u.into(),
         })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialShaderArgument {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = match self.u {
             MaterialArgumentDef::LiteralConst(_) => Ptr32::<()>::unreal().as_u32(),
-            MaterialArgumentDef::CodeConst(c) => c.as_u32(),
+            MaterialArgumentDef::CodeConst(c) => {
+                // stored field by field rather than as the `u32`, so that
+                // it comes out right in either byte order
+                return ser.store_into_xfile((self.arg_type as u16, self.dest, c));
+            }
             MaterialArgumentDef::CodeSampler(s) => s,
             MaterialArgumentDef::NameHash(h) => h,
         };
@@ -719,8 +996,9 @@ impl XFileSerialize<()> for MaterialShaderArgument {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct MaterialArgumentCodeConst {
     pub index: u16,
     pub first_row: u8,
@@ -729,26 +1007,37 @@ pub struct MaterialArgumentCodeConst {
 assert_size!(MaterialArgumentCodeConst, 4);
 
 impl MaterialArgumentCodeConst {
-    pub fn from_u32(u: u32) -> Self {
-        unsafe { transmute(u) }
+    /// Decodes the constant from the `u32` it shares a union with, which was
+    /// read in the file's byte order.
+    pub fn from_u32(u: u32, little_endian: bool) -> Self {
+        let bytes = if little_endian {
+            u.to_le_bytes()
+        } else {
+            u.to_be_bytes()
+        };
+        // can't fail, the constant is exactly 4 bytes
+        read::from_bytes(&bytes, little_endian).unwrap_or_default()
     }
 
-    pub fn as_u32(self) -> u32 {
-        unsafe { transmute(self) }
+    /// The inverse of [`Self::from_u32`].
+    pub fn as_u32(self, little_endian: bool) -> u32 {
+        let index = if little_endian {
+            self.index.to_le_bytes()
+        } else {
+            self.index.to_be_bytes()
+        };
+        let bytes = [index[0], index[1], self.first_row, self.row_count];
+        if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
     }
 }
 
-const MTL_ARG_MATERIAL_VERTEX_CONST: u16 = 0;
-const MTL_ARG_LITERAL_VERTEX_CONST: u16 = 1;
-const MTL_ARG_MATERIAL_PIXEL_SAMPLER: u16 = 2;
-const MTL_ARG_CODE_VERTEX_CONST: u16 = 3;
-const MTL_ARG_CODE_PIXEL_SAMPLER: u16 = 4;
-const MTL_ARG_MATERIAL_PRIM_END: u16 = 6;
-const MTL_ARG_CODE_PIXEL_CONST: u16 = 5;
-const MTL_ARG_LITERAL_PIXEL_CONST: u16 = 7;
-
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u16)]
 pub enum MtlArg {
     #[default]
@@ -763,10 +1052,10 @@ pub enum MtlArg {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub(crate) struct MaterialRaw<'a> {
     pub info: MaterialInfoRaw<'a>,
-    #[serde(with = "serde_arrays")]
+    #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
     pub state_bits_entry: [u8; 130],
     pub texture_count: u8,
     pub constant_count: u8,
@@ -801,7 +1090,8 @@ impl<'a> Default for MaterialRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Material {
     pub info: MaterialInfo,
     #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))]
@@ -869,6 +1159,7 @@ impl<'a> XFileDeserializeInto<Material, ()> for MaterialRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Material {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.info.name.get());
@@ -917,7 +1208,7 @@ impl XFileSerialize<()> for Material {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialInfoRaw<'a> {
     pub name: XStringRaw<'a>,
     pub game_flags: u32,
@@ -926,6 +1217,7 @@ pub(crate) struct MaterialInfoRaw<'a> {
     pub sort_key: u8,
     pub texture_atlas_row_count: u8,
     pub texture_atlas_column_count: u8,
+    #[allow(dead_code)]
     pad2: [u8; 4],
     pub draw_surf: GfxDrawSurf,
     pub surface_type_bits: u32,
@@ -937,7 +1229,8 @@ pub(crate) struct MaterialInfoRaw<'a> {
 assert_size!(MaterialInfoRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct MaterialInfo {
     pub name: XString,
     pub game_flags: u32,
@@ -976,15 +1269,16 @@ impl<'a> XFileDeserializeInto<MaterialInfo, ()> for MaterialInfoRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default, XFileRead)]
 pub struct GfxDrawSurf {
     pub fields: u64,
 }
 assert_size!(GfxDrawSurf, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialTextureDefRaw<'a> {
     pub name_hash: u32,
     pub name_start: i8,
@@ -999,7 +1293,8 @@ pub(crate) struct MaterialTextureDefRaw<'a> {
 assert_size!(MaterialTextureDefRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct MaterialTextureDef {
     pub name_hash: u32,
     pub name_start: char,
@@ -1053,6 +1348,7 @@ impl<'a> XFileDeserializeInto<MaterialTextureDef, ()> for MaterialTextureDefRaw<
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialTextureDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let p = match &self.u {
@@ -1081,7 +1377,8 @@ impl XFileSerialize<()> for MaterialTextureDef {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u8)]
 pub enum Semantic {
     #[default]
@@ -1101,14 +1398,15 @@ pub enum Semantic {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct MaterialTextureDefInfoRaw<'a> {
-    p: Ptr32<'a, ()>,
+    pub p: Ptr32<'a, ()>,
 }
 assert_size!(MaterialTextureDefInfoRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum MaterialTextureDefInfo {
     Image(Option<Box<GfxImage>>),
     Water(Option<Box<Water>>),
@@ -1121,7 +1419,7 @@ impl Default for MaterialTextureDefInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct WaterRaw<'a> {
     pub writable: WaterWrtitable,
     pub h0: Ptr32<'a, Complex>,
@@ -1140,7 +1438,8 @@ pub(crate) struct WaterRaw<'a> {
 assert_size!(WaterRaw, 68);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct Water {
     pub writable: WaterWrtitable,
     pub h0: Vec<Complex>,
@@ -1159,22 +1458,16 @@ pub struct Water {
 
 impl<'a> XFileDeserializeInto<Water, ()> for WaterRaw<'a> {
     fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, _data: ()) -> Result<Water> {
+        let count = (self.m as usize).saturating_mul(self.n as usize);
+
         let h0 = if !self.h0.is_null() {
-            let mut h0 = Vec::new();
-            for _ in 0..self.m * self.n {
-                h0.push(de.load_from_xfile()?);
-            }
-            h0
+            de.load_array(count)?
         } else {
             Vec::new()
         };
 
         let w_term = if !self.w_term.is_null() {
-            let mut w_term = Vec::new();
-            for _ in 0..self.m * self.n {
-                w_term.push(de.load_from_xfile()?);
-            }
-            w_term
+            de.load_array(count)?
         } else {
             Vec::new()
         };
@@ -1197,6 +1490,7 @@ impl<'a> XFileDeserializeInto<Water, ()> for WaterRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Water {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let h0 = Ptr32::from_slice(&self.h0);
@@ -1225,21 +1519,24 @@ impl XFileSerialize<()> for Water {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct WaterWrtitable {
     pub float_time: f32,
 }
 assert_size!(WaterWrtitable, 4);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct Complex {
     pub real: f32,
     pub imag: f32,
 }
 assert_size!(Complex, 8);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Complex {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -1247,7 +1544,7 @@ impl XFileSerialize<()> for Complex {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxImageRaw<'a> {
     pub texture: GfxTextureRaw<'a>,
     pub map_type: u8,
@@ -1264,9 +1561,11 @@ pub(crate) struct GfxImageRaw<'a> {
     pub level_count: u8,
     pub streaming: bool,
     pub base_size: u32,
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
     pub pixels: Ptr32<'a, u8>,
     pub loaded_size: u32,
     pub skipped_mip_levels: u8,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub name: XStringRaw<'a>,
     pub hash: u32,
@@ -1274,7 +1573,8 @@ pub(crate) struct GfxImageRaw<'a> {
 assert_size!(GfxImageRaw, 52);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 #[repr(C)]
 pub struct GfxImage {
     pub texture: GfxTexture,
@@ -1298,6 +1598,42 @@ pub struct GfxImage {
     pub hash: u32,
 }
 
+impl GfxImage {
+    /// The number of mip levels that aren't in the Fastfile, i.e., that are
+    /// streamed from an image pack instead.
+    pub fn streamed_level_count(&self) -> u8 {
+        match &self.texture {
+            GfxTexture::LoadDef(Some(def)) if self.streaming => {
+                self.level_count.saturating_sub(def.level_count)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Prepends `mips`, the streamed mip levels (largest first, as they're
+    /// stored in an image pack), to the ones in the Fastfile, so that the
+    /// image's load def holds every level. The image is no longer marked as
+    /// streaming afterwards.
+    ///
+    /// Returns `false` (and leaves the image as it is) if the image doesn't
+    /// have any streamed levels.
+    pub fn fuse_streamed_mips(&mut self, mut mips: Vec<u8>) -> bool {
+        if self.streamed_level_count() == 0 {
+            return false;
+        }
+
+        let GfxTexture::LoadDef(Some(def)) = &mut self.texture else {
+            return false;
+        };
+
+        mips.append(&mut def.resource);
+        def.resource = mips;
+        def.level_count = self.level_count;
+        self.streaming = false;
+        true
+    }
+}
+
 impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -1358,6 +1694,7 @@ impl<'a> XFileDeserializeInto<GfxImage, ()> for GfxImageRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxImage {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let texture = GfxTextureRaw { p: Ptr32::unreal() };
@@ -1396,9 +1733,9 @@ impl XFileSerialize<()> for GfxImage {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxTextureRaw<'a> {
-    p: Ptr32<'a, ()>,
+    pub p: Ptr32<'a, ()>,
 }
 assert_size!(GfxTextureRaw, 4);
 
@@ -1407,7 +1744,8 @@ assert_size!(GfxTextureRaw, 4);
 // Cube -> Cubemap
 // LoadDef -> Used to load one of the above
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum GfxTexture {
     Map(Option<Box<crate::common::GfxTexture>>),
     Volmap(Option<Box<GfxVolumeTexture>>),
@@ -1437,6 +1775,7 @@ impl<'a> XFileDeserializeInto<GfxTexture, ()> for GfxTextureRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxTexture {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         match self {
@@ -1463,7 +1802,8 @@ impl XFileSerialize<()> for GfxTexture {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u8)]
 pub enum MapType {
     #[default]
@@ -1474,7 +1814,8 @@ pub enum MapType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u8)]
 pub enum ImgCategory {
     #[default]
@@ -1486,22 +1827,25 @@ pub enum ImgCategory {
     RENDER_TARGET = 0x06,
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct Picmip {
     pub platform: [u8; 2],
 }
 assert_size!(Picmip, 2);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct CardMemory {
     pub platform: [u32; 2],
 }
 assert_size!(CardMemory, 8);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, XFileRead)]
 pub struct MaterialConstantDef {
     pub name_hash: u32,
     pub name: [u8; 12],
@@ -1509,19 +1853,22 @@ pub struct MaterialConstantDef {
 }
 assert_size!(MaterialConstantDef, 32);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for MaterialConstantDef {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct GfxStateBits {
     pub load_bits: [u32; 2],
 }
 assert_size!(GfxStateBits, 8);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxStateBits {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         ser.store_into_xfile(*self)
@@ -1529,10 +1876,11 @@ impl XFileSerialize<()> for GfxStateBits {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxImageLoadDefRaw {
     pub level_count: u8,
     pub flags: u8,
+    #[allow(dead_code)]
     pad: [u8; 2],
     pub format: D3DFORMAT,
     pub resource: FlexibleArrayU32<u8>,
@@ -1540,7 +1888,8 @@ pub(crate) struct GfxImageLoadDefRaw {
 assert_size!(GfxImageLoadDefRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct GfxImageLoadDef {
     pub level_count: u8,
     pub flags: u8,
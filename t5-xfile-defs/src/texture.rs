@@ -0,0 +1,549 @@
+//! Converting the pixels in a [`GfxImage`]'s load def to formats other tools
+//! can read.
+//!
+//! Only PC images are supported. Console images are tiled (and, on Xbox
+//! 360, byte-swapped), so their pixels come out scrambled.
+
+use alloc::vec::Vec;
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::{
+    Error, ErrorKind, Result, file_line_col,
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType},
+};
+
+/// The Direct3D 9 formats PC images' pixels can be in
+/// ([`GfxImageLoadDef::format`]). Console images use their own formats,
+/// which aren't any of these.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(i32)]
+pub enum TextureFormat {
+    A8R8G8B8 = 21,
+    X8R8G8B8 = 22,
+    R5G6B5 = 23,
+    X1R5G5B5 = 24,
+    A1R5G5B5 = 25,
+    A4R4G4B4 = 26,
+    A8 = 28,
+    A8B8G8R8 = 32,
+    G16R16 = 34,
+    A16B16G16R16 = 36,
+    L8 = 50,
+    A8L8 = 51,
+    V8U8 = 60,
+    Q8W8V8U8 = 63,
+    V16U16 = 64,
+    L16 = 81,
+    R16F = 111,
+    G16R16F = 112,
+    A16B16G16R16F = 113,
+    R32F = 114,
+    G32R32F = 115,
+    A32B32G32R32F = 116,
+    DXT1 = i32::from_le_bytes(*b"DXT1"),
+    DXT3 = i32::from_le_bytes(*b"DXT3"),
+    DXT5 = i32::from_le_bytes(*b"DXT5"),
+    /// One-channel block compression (BC4).
+    ATI1 = i32::from_le_bytes(*b"ATI1"),
+    /// Two-channel block compression (BC5), used for normal maps.
+    ATI2 = i32::from_le_bytes(*b"ATI2"),
+}
+
+/// How the pixels of a format are laid out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Layout {
+    /// 4x4 blocks of `usize` bytes each.
+    Blocks(usize),
+    /// `usize` bytes per pixel.
+    Pixels(usize),
+}
+
+impl TextureFormat {
+    pub const ALL: [Self; 27] = [
+        Self::A8R8G8B8,
+        Self::X8R8G8B8,
+        Self::R5G6B5,
+        Self::X1R5G5B5,
+        Self::A1R5G5B5,
+        Self::A4R4G4B4,
+        Self::A8,
+        Self::A8B8G8R8,
+        Self::G16R16,
+        Self::A16B16G16R16,
+        Self::L8,
+        Self::A8L8,
+        Self::V8U8,
+        Self::Q8W8V8U8,
+        Self::V16U16,
+        Self::L16,
+        Self::R16F,
+        Self::G16R16F,
+        Self::A16B16G16R16F,
+        Self::R32F,
+        Self::G32R32F,
+        Self::A32B32G32R32F,
+        Self::DXT1,
+        Self::DXT3,
+        Self::DXT5,
+        Self::ATI1,
+        Self::ATI2,
+    ];
+
+    /// Returns [`ErrorKind::UnknownTextureFormat`] if `format` isn't one of
+    /// [`Self::ALL`].
+    pub fn from_d3d(format: i32) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|&f| f as i32 == format)
+            .ok_or(Error::new(
+                file_line_col!(),
+                ErrorKind::UnknownTextureFormat(format),
+            ))
+    }
+
+    pub const fn as_d3d(self) -> i32 {
+        self as i32
+    }
+
+    const fn layout(self) -> Layout {
+        match self {
+            Self::DXT1 | Self::ATI1 => Layout::Blocks(8),
+            Self::DXT3 | Self::DXT5 | Self::ATI2 => Layout::Blocks(16),
+            Self::A8 | Self::L8 => Layout::Pixels(1),
+            Self::R5G6B5
+            | Self::X1R5G5B5
+            | Self::A1R5G5B5
+            | Self::A4R4G4B4
+            | Self::A8L8
+            | Self::V8U8
+            | Self::L16
+            | Self::R16F => Layout::Pixels(2),
+            Self::A8R8G8B8
+            | Self::X8R8G8B8
+            | Self::A8B8G8R8
+            | Self::G16R16
+            | Self::Q8W8V8U8
+            | Self::V16U16
+            | Self::G16R16F
+            | Self::R32F => Layout::Pixels(4),
+            Self::A16B16G16R16 | Self::A16B16G16R16F | Self::G32R32F => Layout::Pixels(8),
+            Self::A32B32G32R32F => Layout::Pixels(16),
+        }
+    }
+
+    /// Whether the format is stored as 4x4 blocks.
+    pub const fn is_block_compressed(self) -> bool {
+        matches!(self.layout(), Layout::Blocks(_))
+    }
+
+    /// Bits per pixel, averaged over a block for block-compressed formats.
+    pub const fn bits_per_pixel(self) -> u32 {
+        match self.layout() {
+            Layout::Blocks(size) => size as u32 / 2,
+            Layout::Pixels(size) => size as u32 * 8,
+        }
+    }
+
+    pub const fn has_alpha(self) -> bool {
+        matches!(
+            self,
+            Self::A8R8G8B8
+                | Self::A1R5G5B5
+                | Self::A4R4G4B4
+                | Self::A8
+                | Self::A8B8G8R8
+                | Self::A16B16G16R16
+                | Self::A8L8
+                | Self::A16B16G16R16F
+                | Self::A32B32G32R32F
+                | Self::DXT1
+                | Self::DXT3
+                | Self::DXT5
+        )
+    }
+
+    /// The size in bytes of one `width` by `height` mip level (or slice of
+    /// one, for volume maps).
+    pub fn level_size(self, width: u32, height: u32) -> usize {
+        self.layout().level_size(width, height)
+    }
+}
+
+impl Layout {
+    fn level_size(self, width: u32, height: u32) -> usize {
+        match self {
+            Self::Blocks(size) => {
+                width.div_ceil(4).max(1) as usize * height.div_ceil(4).max(1) as usize * size
+            }
+            Self::Pixels(size) => width.max(1) as usize * height.max(1) as usize * size,
+        }
+    }
+
+    fn pitch(self, width: u32) -> usize {
+        match self {
+            Self::Blocks(size) => width.div_ceil(4).max(1) as usize * size,
+            Self::Pixels(size) => width.max(1) as usize * size,
+        }
+    }
+}
+
+// DDS header flags
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSD_DEPTH: u32 = 0x800000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xFE00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+/// The DDS pixel format for `format`: flags, FourCC, bit count, and RGBA
+/// masks. Formats without masks are written with their `D3DFORMAT` as the
+/// FourCC, as D3DX does.
+fn dds_pixel_format(format: TextureFormat) -> (u32, u32, u32, [u32; 4]) {
+    use TextureFormat::*;
+
+    let bits = format.bits_per_pixel();
+    match format {
+        A8R8G8B8 => (
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            bits,
+            [0x00FF0000, 0x0000FF00, 0x000000FF, 0xFF000000],
+        ),
+        X8R8G8B8 => (DDPF_RGB, 0, bits, [0x00FF0000, 0x0000FF00, 0x000000FF, 0]),
+        A8B8G8R8 => (
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            bits,
+            [0x000000FF, 0x0000FF00, 0x00FF0000, 0xFF000000],
+        ),
+        R5G6B5 => (DDPF_RGB, 0, bits, [0xF800, 0x07E0, 0x001F, 0]),
+        X1R5G5B5 => (DDPF_RGB, 0, bits, [0x7C00, 0x03E0, 0x001F, 0]),
+        A1R5G5B5 => (
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            bits,
+            [0x7C00, 0x03E0, 0x001F, 0x8000],
+        ),
+        A4R4G4B4 => (
+            DDPF_RGB | DDPF_ALPHAPIXELS,
+            0,
+            bits,
+            [0x0F00, 0x00F0, 0x000F, 0xF000],
+        ),
+        G16R16 => (DDPF_RGB, 0, bits, [0x0000FFFF, 0xFFFF0000, 0, 0]),
+        A8 => (DDPF_ALPHA, 0, bits, [0, 0, 0, 0xFF]),
+        L8 => (DDPF_LUMINANCE, 0, bits, [0xFF, 0, 0, 0]),
+        L16 => (DDPF_LUMINANCE, 0, bits, [0xFFFF, 0, 0, 0]),
+        A8L8 => (
+            DDPF_LUMINANCE | DDPF_ALPHAPIXELS,
+            0,
+            bits,
+            [0xFF, 0, 0, 0xFF00],
+        ),
+        _ => (DDPF_FOURCC, format.as_d3d() as u32, 0, [0; 4]),
+    }
+}
+
+/// One mip level in an image's load def (see [`GfxImage::mip_levels`]).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    /// 1 unless it's a volume map.
+    pub depth: u32,
+    /// Where the level starts in [`GfxImageLoadDef::resource`]. For
+    /// cubemaps, this is for the first face: each face has all of its
+    /// levels, one after another, before the next.
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl GfxImageLoadDef {
+    /// Returns [`ErrorKind::UnknownTextureFormat`] if the format isn't a
+    /// [`TextureFormat`], as for console images.
+    pub fn texture_format(&self) -> Result<TextureFormat> {
+        TextureFormat::from_d3d(self.format)
+    }
+}
+
+impl GfxImage {
+    /// The load def, and the width and height of the largest mip level
+    /// in it (smaller than the image's if it's still streaming).
+    fn loaded_levels(&self) -> Option<(&GfxImageLoadDef, u32, u32)> {
+        let GfxTexture::LoadDef(Some(def)) = &self.texture else {
+            return None;
+        };
+
+        let skipped = self.streamed_level_count() as u32;
+        let width = (self.width as u32).checked_shr(skipped).unwrap_or(0).max(1);
+        let height = (self.height as u32)
+            .checked_shr(skipped)
+            .unwrap_or(0)
+            .max(1);
+        Some((def, width, height))
+    }
+
+    /// The format of the image's pixels, or [`None`] if it doesn't have a
+    /// load def. Returns [`ErrorKind::UnknownTextureFormat`] if the format
+    /// isn't a [`TextureFormat`].
+    pub fn texture_format(&self) -> Result<Option<TextureFormat>> {
+        match &self.texture {
+            GfxTexture::LoadDef(Some(def)) => def.texture_format().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The mip levels in the image's load def, largest first, leaving out
+    /// the ones that are streamed (see [`Self::streamed_level_count`]).
+    /// Returns an empty list if the image doesn't have a load def.
+    ///
+    /// The levels aren't checked against the size of the load def.
+    pub fn mip_levels(&self) -> Result<Vec<MipLevel>> {
+        let Some(format) = self.texture_format()? else {
+            return Ok(Vec::new());
+        };
+        let Some((def, width, height)) = self.loaded_levels() else {
+            return Ok(Vec::new());
+        };
+
+        let skipped = self.streamed_level_count() as u32;
+        let depth = match self.map_type {
+            MapType::THREE_DIMENSIONAL => self.depth.max(1) as u32,
+            _ => 1,
+        };
+
+        let mut offset = 0;
+        let levels = (0..def.level_count.max(1) as u32)
+            .map(|i| {
+                let [width, height] = [width, height].map(|n| (n >> i).max(1));
+                let depth = depth.checked_shr(skipped + i).unwrap_or(0).max(1);
+                let size = format.level_size(width, height) * depth as usize;
+                let level = MipLevel {
+                    width,
+                    height,
+                    depth,
+                    offset,
+                    size,
+                };
+                offset += size;
+                level
+            })
+            .collect();
+        Ok(levels)
+    }
+
+    /// Wraps the mip levels in the image's load def in a `.dds` file, or
+    /// returns [`None`] if it doesn't have one or its format isn't a
+    /// [`TextureFormat`].
+    pub fn to_dds(&self) -> Option<Vec<u8>> {
+        let (def, width, height) = self.loaded_levels()?;
+        let format = def.texture_format().ok()?;
+        let layout = format.layout();
+        let (pf_flags, four_cc, bit_count, masks) = dds_pixel_format(format);
+        let level_count = def.level_count.max(1) as u32;
+
+        let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+        let pitch_or_linear_size = match layout {
+            Layout::Blocks(_) => {
+                flags |= DDSD_LINEARSIZE;
+                layout.level_size(width, height)
+            }
+            Layout::Pixels(_) => {
+                flags |= DDSD_PITCH;
+                layout.pitch(width)
+            }
+        };
+
+        let mut caps = DDSCAPS_TEXTURE;
+        if level_count > 1 {
+            flags |= DDSD_MIPMAPCOUNT;
+            caps |= DDSCAPS_COMPLEX | DDSCAPS_MIPMAP;
+        }
+
+        let (depth, caps2) = match self.map_type {
+            MapType::CUBE => {
+                caps |= DDSCAPS_COMPLEX;
+                (0, DDSCAPS2_CUBEMAP_ALL_FACES)
+            }
+            MapType::THREE_DIMENSIONAL => {
+                flags |= DDSD_DEPTH;
+                caps |= DDSCAPS_COMPLEX;
+                (self.depth.max(1) as u32, DDSCAPS2_VOLUME)
+            }
+            _ => (0, 0),
+        };
+
+        let header = [
+            124,
+            flags,
+            height,
+            width,
+            pitch_or_linear_size as u32,
+            depth,
+            level_count,
+        ];
+        let pixel_format = [32, pf_flags, four_cc, bit_count];
+
+        let mut dds = Vec::with_capacity(128 + def.resource.len());
+        dds.extend_from_slice(b"DDS ");
+        header
+            .iter()
+            .chain(&[0; 11])
+            .chain(&pixel_format)
+            .chain(&masks)
+            .chain(&[caps, caps2, 0, 0, 0])
+            .for_each(|v| dds.extend_from_slice(&v.to_le_bytes()));
+        dds.extend_from_slice(&def.resource);
+        Some(dds)
+    }
+
+    /// Decodes the largest mip level in the image's load def (the first
+    /// face of cubemaps, or the first slice of volume maps) to 8-bit RGBA.
+    /// Returns its width, height, and pixels, or [`None`] if the image
+    /// doesn't have a load def, its format isn't supported (DXT1/3/5,
+    /// A8R8G8B8, X8R8G8B8, A8B8G8R8, A8, L8, or A8L8), or the load def is
+    /// too short.
+    pub fn to_rgba8(&self) -> Option<(u32, u32, Vec<u8>)> {
+        let (def, width, height) = self.loaded_levels()?;
+        let format = def.texture_format().ok()?;
+        let level = def.resource.get(..format.level_size(width, height))?;
+
+        let pixels = match format {
+            TextureFormat::DXT1 | TextureFormat::DXT3 | TextureFormat::DXT5 => {
+                decode_blocks(format, width, height, level)
+            }
+            TextureFormat::A8R8G8B8 => level
+                .chunks_exact(4)
+                .flat_map(|p| [p[2], p[1], p[0], p[3]])
+                .collect(),
+            TextureFormat::X8R8G8B8 => level
+                .chunks_exact(4)
+                .flat_map(|p| [p[2], p[1], p[0], 0xFF])
+                .collect(),
+            TextureFormat::A8B8G8R8 => level.to_vec(),
+            TextureFormat::A8 => level.iter().flat_map(|&a| [0, 0, 0, a]).collect(),
+            TextureFormat::L8 => level.iter().flat_map(|&l| [l, l, l, 0xFF]).collect(),
+            TextureFormat::A8L8 => level
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            _ => return None,
+        };
+
+        Some((width, height, pixels))
+    }
+}
+
+fn rgb565(c: u16) -> [u8; 3] {
+    let r = (c >> 11) & 0x1F;
+    let g = (c >> 5) & 0x3F;
+    let b = c & 0x1F;
+    [
+        ((r << 3) | (r >> 2)) as u8,
+        ((g << 2) | (g >> 4)) as u8,
+        ((b << 3) | (b >> 2)) as u8,
+    ]
+}
+
+/// The four colors of a DXT color block. `one_bit_alpha` is only set for
+/// DXT1, whose blocks use the fourth color as transparent black when the
+/// first endpoint isn't greater than the second.
+fn block_colors(c0: u16, c1: u16, one_bit_alpha: bool) -> [[u8; 4]; 4] {
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16, d: u16| {
+        let [r, g, bl] = [0, 1, 2].map(|i| ((a[i] as u16 * wa + b[i] as u16 * wb) / d) as u8);
+        [r, g, bl, 0xFF]
+    };
+
+    if one_bit_alpha && c0 <= c1 {
+        [
+            [a[0], a[1], a[2], 0xFF],
+            [b[0], b[1], b[2], 0xFF],
+            mix(1, 1, 2),
+            [0; 4],
+        ]
+    } else {
+        [
+            [a[0], a[1], a[2], 0xFF],
+            [b[0], b[1], b[2], 0xFF],
+            mix(2, 1, 3),
+            mix(1, 2, 3),
+        ]
+    }
+}
+
+/// The 16 alphas of a DXT5 alpha block.
+fn block_alphas(block: &[u8]) -> [u8; 16] {
+    let (a0, a1) = (block[0] as u16, block[1] as u16);
+    let palette: [u8; 8] = core::array::from_fn(|i| match i {
+        0 => a0 as u8,
+        1 => a1 as u8,
+        _ if a0 > a1 => ((a0 * (8 - i as u16) + a1 * (i as u16 - 1)) / 7) as u8,
+        6 => 0,
+        7 => 0xFF,
+        _ => ((a0 * (6 - i as u16) + a1 * (i as u16 - 1)) / 5) as u8,
+    });
+
+    let bits = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |bits, &b| (bits << 8) | b as u64);
+    core::array::from_fn(|i| palette[((bits >> (3 * i)) & 0x7) as usize])
+}
+
+fn decode_blocks(format: TextureFormat, width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let block_size = if format == TextureFormat::DXT1 { 8 } else { 16 };
+    let blocks_wide = width.div_ceil(4).max(1);
+    let mut pixels = alloc::vec![0; width * height * 4];
+
+    for (i, block) in data.chunks_exact(block_size).enumerate() {
+        let (bx, by) = (i % blocks_wide * 4, i / blocks_wide * 4);
+        let (alpha, color) = block.split_at(block_size - 8);
+
+        let c0 = u16::from_le_bytes([color[0], color[1]]);
+        let c1 = u16::from_le_bytes([color[2], color[3]]);
+        let colors = block_colors(c0, c1, format == TextureFormat::DXT1);
+        let indices = u32::from_le_bytes([color[4], color[5], color[6], color[7]]);
+        let alphas: Option<[u8; 16]> = match format {
+            TextureFormat::DXT3 => Some(core::array::from_fn(|i| {
+                let nibble = (alpha[i / 2] >> (4 * (i % 2))) & 0xF;
+                nibble * 0x11
+            })),
+            TextureFormat::DXT5 => Some(block_alphas(alpha)),
+            _ => None,
+        };
+
+        for p in 0..16 {
+            let (x, y) = (bx + p % 4, by + p / 4);
+            if x >= width || y >= height {
+                continue;
+            }
+
+            let mut rgba = colors[((indices >> (2 * p)) & 0x3) as usize];
+            if let Some(alphas) = alphas {
+                rgba[3] = alphas[p];
+            }
+            let offset = (y * width + x) * 4;
+            pixels[offset..offset + 4].copy_from_slice(&rgba);
+        }
+    }
+
+    pixels
+}
@@ -1,24 +1,23 @@
-use core::{
-    fmt::{self, Debug},
-    marker::PhantomData,
-};
+use core::{fmt::Debug, marker::PhantomData, ops::Deref};
 
-use alloc::{
-    boxed::Box,
-    ffi::CString,
-    format,
-    string::{String, ToString},
-    vec::Vec,
-};
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec::Vec};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
-use crate::{Error, ErrorKind, Result, T5XFileDeserialize, T5XFileSerialize, file_line_col};
+#[cfg(feature = "serde")]
+use crate::T5XFileSerialize;
+use crate::{
+    Error, ErrorKind, Result, T5XFileDeserialize, XFileOffset, XFileRead, XFileReader,
+    encoding::StringEncoding, file_line_col,
+};
+#[cfg(feature = "serde")]
+use core::fmt;
 
+#[cfg(feature = "serde")]
 use serde::{
     Deserialize, Serialize,
-    de::{DeserializeOwned, SeqAccess, Visitor},
+    de::{SeqAccess, Visitor},
 };
 
 /// Helper macro to ensure the structs we're deserializing are the correct
@@ -50,11 +49,13 @@ macro_rules! size_of {
 }
 
 // ============================================================================
+#[cfg(feature = "serde")]
 #[allow(dead_code)]
 pub(crate) struct ArrayVisitor<T, const N: usize> {
     element: PhantomData<[T; N]>,
 }
 
+#[cfg(feature = "serde")]
 impl<T, const N: usize> ArrayVisitor<T, N> {
     #[allow(dead_code)]
     pub const fn new() -> Self {
@@ -64,6 +65,7 @@ impl<T, const N: usize> ArrayVisitor<T, N> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'de, T: Default + Copy + Deserialize<'de>, const N: usize> Visitor<'de>
     for ArrayVisitor<T, N>
 {
@@ -89,9 +91,10 @@ impl<'de, T: Default + Copy + Deserialize<'de>, const N: usize> Visitor<'de>
 }
 // ============================================================================
 
+#[doc(hidden)]
 #[repr(transparent)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct XStringRaw<'a>(Ptr32<'a, u8>);
 assert_size!(XStringRaw, 4);
 
@@ -125,56 +128,65 @@ impl<'a> XFileDeserializeInto<XString, ()> for XStringRaw<'a> {
         }
 
         if self.0.is_real() {
-            return Ok(XString::new());
+            let target = de.resolve_real_ptr(XFileOffset::from_u32(self.0.as_u32()))?;
+            let Some(pos) = target.map(|t| de.enter_real_ptr(t)).transpose()?.flatten() else {
+                return Ok(XString::new());
+            };
+
+            let s = XStringRaw(Ptr32::unreal()).xfile_deserialize_into(de, ());
+            de.leave_real_ptr(pos)?;
+            return s;
         }
 
+        de.mark_ptr_target(self.0.marker(), 1, 1)?;
+        let start = de.stream_pos()?;
         let mut string_buf = Vec::new();
 
         loop {
             let c = de.load_from_xfile::<u8>()?;
-
-            // Localized strings use CP1252 for languages that use the latin alphabet.
-            // `num::is_ascii` returns false for any values > 127, so valid CP1252 characters
-            // have to be manually permitted here.
-            //
-            // FIXME: come up with a more elegant solution
-            if !c.is_ascii() && c != 0xF1 && c != 0xDC && c != 0xAE && c != 0xA9 && c != 0x99 {
-                return Err(Error::new_with_offset(
-                    file_line_col!(),
-                    de.stream_pos()? as _,
-                    ErrorKind::BrokenInvariant(format!(
-                        "XString: c ({c:#02X}) is not valid EASCII",
-                    )),
-                ));
-            }
-
-            string_buf.push(c);
             if c == b'\0' {
                 break;
             }
+            string_buf.push(c);
         }
 
-        //dbg!(xfile.stream_position()?);
-        Ok(XString(
-            CString::from_vec_with_nul(string_buf)
-                .unwrap()
-                .to_string_lossy()
-                .to_string(),
-        ))
+        de.string_encoding()
+            .decode(&string_buf)
+            .map(XString)
+            .map_err(|e| match e.kind() {
+                ErrorKind::BadChar(c) => {
+                    Error::new_with_offset(file_line_col!(), start as _, ErrorKind::BadChar(*c))
+                }
+                _ => e,
+            })
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
 #[repr(transparent)]
 pub struct XString(pub String);
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XString {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
-        let mut bytes = self.0.chars().map(|c| c as u8).collect::<Vec<_>>();
+        // Empty strings get a null pointer (see [`XStringRaw::from_str`]),
+        // so there's nothing to store.
+        if self.0.is_empty() {
+            return Ok(());
+        }
+
+        let mut bytes = ser.string_encoding().encode(&self.0)?;
+        // it'd end the string early
+        if bytes.contains(&b'\0') {
+            return Err(Error::new(file_line_col!(), ErrorKind::BadChar(0)));
+        }
         bytes.push(b'\0');
 
-        ser.store_into_xfile(bytes)
+        // Byte by byte, since storing the `Vec` itself would prefix it with
+        // its length.
+        bytes.xfile_serialize(ser, ())
     }
 }
 
@@ -188,6 +200,129 @@ impl XString {
     }
 }
 
+/// A string stored inline in a struct, in a fixed `N` bytes padded with
+/// nulls (e.g. the name of a sun light). A string that takes up all `N`
+/// bytes has no null terminator.
+///
+/// Strings are decoded and encoded with [`StringEncoding::default`], like
+/// [`XString`]s are by default.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FixedString<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> FixedString<N> {
+    pub const fn new() -> Self {
+        Self([0; N])
+    }
+
+    /// The bytes up to the first null.
+    pub fn as_bytes(&self) -> &[u8] {
+        let len = self.0.iter().position(|&c| c == 0).unwrap_or(N);
+        &self.0[..len]
+    }
+
+    /// Returns [`None`] if the string isn't valid UTF-8, in which case
+    /// [`Display`](core::fmt::Display) can still decode it.
+    pub fn as_str(&self) -> Option<&str> {
+        core::str::from_utf8(self.as_bytes()).ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_bytes().is_empty()
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> core::fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // can't fail, since the default fallback replaces bad bytes
+        let s = StringEncoding::default()
+            .decode(self.as_bytes())
+            .unwrap_or_default();
+        write!(f, "{s}")
+    }
+}
+
+impl<const N: usize> Debug for FixedString<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "FixedString<{N}>({:?})", format!("{self}"))
+    }
+}
+
+impl<const N: usize> core::str::FromStr for FixedString<N> {
+    type Err = Error;
+
+    /// Returns [`ErrorKind::BrokenInvariant`] if `s` takes up more than `N`
+    /// bytes once encoded.
+    fn from_str(s: &str) -> Result<Self> {
+        let bytes = StringEncoding::default().encode(s)?;
+        if bytes.len() > N {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "FixedString<{N}>: string \"{s}\" too long (must be <={N} bytes)."
+                )),
+            ));
+        }
+
+        let mut string = Self::new();
+        string.0[..bytes.len()].copy_from_slice(&bytes);
+        Ok(string)
+    }
+}
+
+/// Truncates `s` to the first `N` bytes once encoded, rather than failing
+/// like [`FromStr`](core::str::FromStr) does.
+impl<const N: usize> From<&str> for FixedString<N> {
+    fn from(s: &str) -> Self {
+        // can't fail, since the default fallback replaces bad characters
+        StringEncoding::default()
+            .truncate(s, N)
+            .parse()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> Serialize for FixedString<N> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serde_arrays::serialize(&self.0, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> Deserialize<'de> for FixedString<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        serde_arrays::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<const N: usize> XFileRead for FixedString<N> {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+        <[u8; N]>::xfile_read(r).map(Self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> XFileSerialize<()> for FixedString<N> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        self.0.xfile_serialize(ser, ())
+    }
+}
+
 // ============================================================================
 /// Trait to deserialize [`Self`] from [`xfile`], then convert [`Self`] to
 /// [`T`].
@@ -208,29 +343,92 @@ pub trait XFileDeserializeInto<T, U: Copy> {
     fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: U) -> Result<T>;
 }
 
+/// A [`T5XFileDeserialize`] whose inflated data outlives it, so byte blobs can
+/// be borrowed from the data instead of copied out of it.
+pub trait T5XFileDeserializeBorrowed<'buf>: T5XFileDeserialize {
+    /// Returns the next `len` bytes of the stream and advances past them.
+    fn load_slice(&mut self, len: usize) -> Result<&'buf [u8]>;
+}
+
+/// Same as [`XFileDeserializeInto`], except `T` may borrow from the
+/// deserializer's data (see [`T5XFileDeserializeBorrowed`]).
+pub trait XFileDeserializeBorrowed<'buf, T, U: Copy> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        data: U,
+    ) -> Result<T>;
+}
+
+/// Same as the [`XFileDeserializeInto`] impl, except real offsets aren't
+/// followed, since the borrowed deserializer doesn't know where anything
+/// was loaded from.
+impl<'a, 'buf, T, U, V> XFileDeserializeBorrowed<'buf, Option<U>, V> for Ptr32<'a, T>
+where
+    T: XFileRead + Clone + Debug + XFileDeserializeBorrowed<'buf, U, V>,
+    V: Copy,
+{
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        data: V,
+    ) -> Result<Option<U>> {
+        let Some(t) = self.xfile_load(de)? else {
+            return Ok(None);
+        };
+
+        de.enter_ptr()?;
+        let u = t.xfile_deserialize_borrowed(de, data);
+        de.leave_ptr();
+        u.map(Some)
+    }
+}
+
 impl<'a, T, U, V, const N: usize> XFileDeserializeInto<[U; N], V> for [T; N]
 where
     U: Debug + 'a,
     [U; N]: TryFrom<&'a [U]>,
     <&'a [U] as TryInto<[U; N]>>::Error: Debug,
-    T: DeserializeOwned + Clone + Debug + XFileDeserializeInto<U, V>,
+    T: XFileRead + Clone + Debug + XFileDeserializeInto<U, V>,
     V: Copy,
 {
     fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: V) -> Result<[U; N]> {
-        self.iter()
-            .cloned()
-            .map(|t| t.xfile_deserialize_into(de, data))
-            .collect::<Result<Vec<_>>>()
-            .map(|v| TryInto::<[U; N]>::try_into(v).unwrap())
+        deserialize_array_n(self, de, data)
     }
 }
+
+/// Deserializes every element of `arr`, in order.
+///
+/// Fixed-size arrays of members should always go through this (or the
+/// [`XFileDeserializeInto`] impl for `[T; N]`, where its bounds allow it)
+/// rather than being indexed element by element, so no element can be
+/// skipped or deserialized twice.
+pub(crate) fn deserialize_array_n<T, U, V, const N: usize>(
+    arr: &[T; N],
+    de: &mut impl T5XFileDeserialize,
+    data: V,
+) -> Result<[U; N]>
+where
+    T: XFileDeserializeInto<U, V>,
+    V: Copy,
+{
+    let v = arr
+        .iter()
+        .map(|t| t.xfile_deserialize_into(de, data))
+        .collect::<Result<Vec<_>>>()?;
+
+    // exactly N elements were collected, so this can't fail
+    Ok(v.try_into().unwrap_or_else(|_| unreachable!()))
+}
 // ============================================================================
 
 // ============================================================================
+#[cfg(feature = "serde")]
 pub trait XFileSerialize<T: Copy> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: T) -> Result<()>;
 }
 
+#[cfg(feature = "serde")]
 impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Option<T> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
         if let Some(t) = self {
@@ -241,12 +439,22 @@ impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Option<T> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Box<T> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
         (**self).xfile_serialize(ser, data)
     }
 }
 
+#[cfg(feature = "arc")]
+#[cfg(feature = "serde")]
+impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for alloc::sync::Arc<T> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
+        (**self).xfile_serialize(ser, data)
+    }
+}
+
+#[cfg(feature = "serde")]
 impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Vec<T> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
         for t in self {
@@ -260,6 +468,7 @@ impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for Vec<T> {
 macro_rules! impl_xfile_serialize {
     ($($t:ty,)+) => {
         $(
+            #[cfg(feature = "serde")]
             impl XFileSerialize<()> for $t {
                 fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
                     ser.store_into_xfile(*self)
@@ -271,6 +480,7 @@ macro_rules! impl_xfile_serialize {
 
 impl_xfile_serialize!(bool, u8, i8, u16, i16, u32, i32, usize, isize, f32, f64,);
 
+#[cfg(feature = "serde")]
 impl<T, U, const N: usize> XFileSerialize<U> for [T; N]
 where
     T: Serialize + Clone + Debug + XFileSerialize<U>,
@@ -286,6 +496,38 @@ where
 }
 // ============================================================================
 
+/// The marker an inline pointer was serialized with (see [`Ptr32::is_real`]).
+///
+/// Both mean the data follows inline, and it's not known what the difference
+/// between them is. In later IW engine games, `0xFFFFFFFE` marks data that the
+/// engine also records a pointer to, so that a later real offset can point to
+/// the pointer rather than the data, but that hasn't been confirmed for T5.
+/// The deserializer can record which one every pointer used (see
+/// [`crate::blocks::InlinePtr`]) so the two cases can be compared.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+pub enum PtrMarker {
+    #[default]
+    Following = 0xFFFFFFFF,
+    Inserted = 0xFFFFFFFE,
+}
+
+impl PtrMarker {
+    pub const fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0xFFFFFFFF => Some(Self::Following),
+            0xFFFFFFFE => Some(Self::Inserted),
+            _ => None,
+        }
+    }
+
+    pub const fn as_u32(self) -> u32 {
+        self as _
+    }
+}
+
 /// Newtype to handle pointer members of serialized structs.
 ///
 /// We use this instead of a [`u32`] for two reasons. One, to differentiate
@@ -302,11 +544,21 @@ where
 ///   on any machine this is compiled for.
 ///
 /// Also, pointers are unsafe and just annoying to use compared to a [`u32`].
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug)]
 #[repr(transparent)]
 pub struct Ptr32<'a, T>(u32, PhantomData<&'a mut T>);
 
+impl<'a, T> XFileRead for Ptr32<'a, T> {
+    fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+        let p = Self::from_u32(u32::xfile_read(r)?);
+        if let Some(marker) = p.marker() {
+            r.ptr_marker(marker);
+        }
+        Ok(p)
+    }
+}
+
 impl<'a, T> Default for Ptr32<'a, T> {
     fn default() -> Self {
         Self(0, PhantomData)
@@ -333,15 +585,25 @@ impl<'a, T> Ptr32<'a, T> {
     /// should be allocated but it's left up to the engine to initialize
     /// goes.
     ///
-    /// Non-"real" offsets (`0xFFFFFFFF` or `0xFFFFFFFE`) mean that the data lies
-    /// directly after the `struct` containing them, rather than somewhere
-    /// independent.
+    /// Non-"real" offsets (`0xFFFFFFFF` or `0xFFFFFFFE`, see [`PtrMarker`])
+    /// mean that the data lies directly after the `struct` containing them,
+    /// rather than somewhere independent.
     ///
     /// (The name of this function could probably be better.)
     pub const fn is_real(&self) -> bool {
         self.as_u32() != 0xFFFFFFFF && self.as_u32() != 0xFFFFFFFE
     }
 
+    /// The marker an inline pointer was serialized with, or [`None`] if the
+    /// pointer is null or a real offset.
+    pub const fn marker(&self) -> Option<PtrMarker> {
+        PtrMarker::from_u32(self.as_u32())
+    }
+
+    pub const fn from_marker(marker: PtrMarker) -> Self {
+        Self(marker.as_u32(), PhantomData)
+    }
+
     pub const fn null() -> Self {
         Self(0x00000000, PhantomData)
     }
@@ -355,7 +617,7 @@ impl<'a, T> Ptr32<'a, T> {
         Ptr32::<'a, U>(self.0, PhantomData)
     }
 
-    pub const fn from_box<U>(b: &Option<Box<T>>) -> Ptr32<'a, U> {
+    pub const fn from_box<U>(b: &Option<impl Deref<Target = T>>) -> Ptr32<'a, U> {
         if b.is_some() {
             Ptr32::<'a, U>::unreal()
         } else {
@@ -376,34 +638,38 @@ impl<'a, T> Ptr32<'a, T> {
     }
 }
 
-impl<'a, T: DeserializeOwned + Clone + Debug + XFileDeserializeInto<U, V>, U, V: Copy>
-    XFileDeserializeInto<Option<Box<U>>, V> for Ptr32<'a, T>
-{
-    fn xfile_deserialize_into(
-        &self,
-        de: &mut impl T5XFileDeserialize,
-        data: V,
-    ) -> Result<Option<Box<U>>> {
-        if self.is_null() {
-            return Ok(None);
+impl<'a, T: XFileRead + Debug> Ptr32<'a, T> {
+    /// Validates a real offset and resolves it (see
+    /// [`T5XFileDeserialize::resolve_real_ptr`]).
+    fn resolve_real(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<u64>> {
+        if self.0 & 0x1FFFFFFF > de.stream_len().unwrap() as u32 {
+            return Err(Error::new_with_offset(
+                file_line_col!(),
+                de.stream_pos()? as _,
+                ErrorKind::InvalidSeek {
+                    off: self.0 & 0x1FFFFFFF,
+                    max: de.stream_len().unwrap() as u32,
+                },
+            ));
         }
 
-        let t = if self.is_real() {
-            if self.0 & 0x1FFFFFFF > de.stream_len().unwrap() as u32 {
-                return Err(Error::new_with_offset(
-                    file_line_col!(),
-                    de.stream_pos()? as _,
-                    ErrorKind::InvalidSeek {
-                        off: self.0 & 0x1FFFFFFF,
-                        max: de.stream_len().unwrap() as u32,
-                    },
-                ));
-            }
+        de.resolve_real_ptr(XFileOffset::from_u32(self.0))
+    }
 
-            //println!("ignoring offset {:#010X}", self.as_u32());
+    /// Loads the raw object this pointer points to, without converting it or
+    /// following any of its own pointers. Real offsets are validated and
+    /// resolved, but not followed, so they load as [`None`].
+    pub(crate) fn xfile_load(&self, de: &mut impl T5XFileDeserialize) -> Result<Option<T>> {
+        if self.is_null() {
             return Ok(None);
+        }
+
+        if self.is_real() {
+            self.resolve_real(de)?;
+            Ok(None)
         } else {
             // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
+            de.mark_ptr_target(self.marker(), core::mem::align_of::<T>(), size_of!(T))?;
             let old = de.stream_pos()?;
             let t = de.load_from_xfile::<T>()?;
             let new = de.stream_pos()?;
@@ -416,14 +682,112 @@ impl<'a, T: DeserializeOwned + Clone + Debug + XFileDeserializeInto<U, V>, U, V:
                 new - old,
                 size_of!(T)
             );
-            t
+            Ok(Some(t))
+        }
+    }
+}
+
+impl<'a, T: XFileRead + Clone + Debug + XFileDeserializeInto<U, V>, U, V: Copy>
+    XFileDeserializeInto<Option<Box<U>>, V> for Ptr32<'a, T>
+{
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        data: V,
+    ) -> Result<Option<Box<U>>> {
+        if self.is_null() {
+            return Ok(None);
+        }
+
+        if self.is_real() {
+            let Some(target) = self.resolve_real(de)? else {
+                return Ok(None);
+            };
+            let Some(pos) = de.enter_real_ptr(target)? else {
+                return Ok(None);
+            };
+
+            // the object is loaded as if it followed inline, which it did
+            // the first time around
+            let u = Self::unreal().xfile_deserialize_inline(de, data);
+            de.leave_real_ptr(pos)?;
+            return u;
+        }
+
+        self.xfile_deserialize_inline(de, data)
+    }
+}
+
+/// Objects are interned by where they're loaded from (see
+/// [`T5XFileDeserialize::intern`]), so a real offset pointing at one that's
+/// already been loaded shares it, even if real offsets aren't being
+/// followed.
+#[cfg(feature = "arc")]
+impl<
+    'a,
+    T: XFileRead + Clone + Debug + XFileDeserializeInto<U, V>,
+    U: core::any::Any + Send + Sync,
+    V: Copy,
+> XFileDeserializeInto<Option<alloc::sync::Arc<U>>, V> for Ptr32<'a, T>
+{
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        data: V,
+    ) -> Result<Option<alloc::sync::Arc<U>>> {
+        if self.is_null() {
+            return Ok(None);
+        }
+
+        if self.is_real() {
+            let Some(target) = self.resolve_real(de)? else {
+                return Ok(None);
+            };
+            if let Some(u) = de.interned(target) {
+                return Ok(Some(u));
+            }
+            let Some(pos) = de.enter_real_ptr(target)? else {
+                return Ok(None);
+            };
+
+            let u = Self::unreal().xfile_deserialize_into(de, data);
+            de.leave_real_ptr(pos)?;
+            return u;
+        }
+
+        let offset = de.stream_pos()?;
+        let Some(u) = self.xfile_deserialize_inline(de, data)? else {
+            return Ok(None);
         };
+        let u = alloc::sync::Arc::<U>::from(u);
+        de.intern(offset, alloc::sync::Arc::clone(&u));
+        Ok(Some(u))
+    }
+}
 
-        t.xfile_deserialize_into(de, data).map(Box::new).map(Some)
+impl<'a, T: XFileRead + Clone + Debug> Ptr32<'a, T> {
+    /// Same as [`XFileDeserializeInto::xfile_deserialize_into`], except real
+    /// offsets are never followed.
+    pub(crate) fn xfile_deserialize_inline<U, V: Copy>(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        data: V,
+    ) -> Result<Option<Box<U>>>
+    where
+        T: XFileDeserializeInto<U, V>,
+    {
+        let Some(t) = self.xfile_load(de)? else {
+            return Ok(None);
+        };
+
+        de.enter_ptr()?;
+        let u = t.xfile_deserialize_into(de, data);
+        de.leave_ptr();
+        u.map(Box::new).map(Some)
     }
 }
 
-impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
+impl<'a, T: XFileRead + Debug> Ptr32<'a, T> {
     /// Same principle as [`XFileInto::xfile_into`], except it doesn't do any
     /// type conversion. Useful for the rare structs that don't need any such
     /// conversion.
@@ -437,6 +801,7 @@ impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
             return Ok(None);
         } else {
             // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
+            de.mark_ptr_target(self.marker(), core::mem::align_of::<T>(), size_of!(T))?;
             let old = de.stream_pos()?;
             let t = de.load_from_xfile::<T>();
             let new = de.stream_pos()?;
@@ -456,6 +821,173 @@ impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
     }
 }
 
+/// A pointer from one part of an asset back to another part of the same
+/// asset, like a [`GfxPortal`](crate::gfx_world::GfxPortal) pointing to the
+/// [`GfxCell`](crate::gfx_world::GfxCell) it leads into.
+///
+/// These are almost always "real" offsets (see [`Ptr32::is_real`]), since the
+/// pointed-to object was already loaded somewhere else. Following them would
+/// duplicate the object at best and recurse forever at worst, so they're
+/// handled in two phases:
+/// 1. While deserializing, only the raw offset is kept
+///    ([`BackRef::Ptr`]). If the object does follow inline, it's loaded like
+///    any other pointer ([`BackRef::Inline`]).
+/// 2. Once the owner is complete, [`BackRef::resolve`] looks the offset up in
+///    an [`InternTable`] to find the index of the object within whatever
+///    collection owns it.
+///
+/// The raw offset is kept even after resolving, so unresolved and resolved
+/// references alike serialize back out exactly as they came in.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BackRef<T> {
+    #[default]
+    Null,
+    Inline(Box<T>),
+    Ptr {
+        ptr: u32,
+        index: Option<usize>,
+    },
+}
+
+impl<T> BackRef<T> {
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the index of the referenced object within its owner's
+    /// collection, if [`Self::resolve`] was able to find it.
+    pub const fn index(&self) -> Option<usize> {
+        match self {
+            Self::Ptr { index, .. } => *index,
+            _ => None,
+        }
+    }
+
+    /// Returns the raw offset, if the object didn't follow inline.
+    pub const fn ptr(&self) -> Option<u32> {
+        match self {
+            Self::Ptr { ptr, .. } => Some(*ptr),
+            _ => None,
+        }
+    }
+
+    /// Resolves [`Self::Ptr`] against `table`.
+    ///
+    /// Returns `false` if `self` is a [`Self::Ptr`] whose offset isn't in
+    /// `table`.
+    pub fn resolve(&mut self, table: &InternTable) -> bool {
+        match self {
+            Self::Ptr { ptr, index } => {
+                *index = table.get(*ptr);
+                index.is_some()
+            }
+            _ => true,
+        }
+    }
+
+    pub const fn to_ptr32<'a, U>(&self) -> Ptr32<'a, U> {
+        match self {
+            Self::Null => Ptr32::null(),
+            Self::Inline(_) => Ptr32::unreal(),
+            Self::Ptr { ptr, .. } => Ptr32::from_u32(*ptr),
+        }
+    }
+}
+
+impl<'a, T: XFileRead + Clone + Debug> Ptr32<'a, T> {
+    /// First phase of deserializing a [`BackRef`]. Real offsets are kept
+    /// as-is, and inline objects are loaded like [`XFileDeserializeInto`]
+    /// would.
+    #[cfg(any(feature = "world", feature = "ui"))]
+    pub(crate) fn xfile_back_ref<U, V: Copy>(
+        self,
+        de: &mut impl T5XFileDeserialize,
+        data: V,
+    ) -> Result<BackRef<U>>
+    where
+        T: XFileDeserializeInto<U, V>,
+    {
+        // validates real offsets and loads inline ones
+        let inline: Option<Box<U>> = self.xfile_deserialize_inline(de, data)?;
+
+        Ok(match inline {
+            Some(t) => BackRef::Inline(t),
+            None if self.is_null() => BackRef::Null,
+            None => BackRef::Ptr {
+                ptr: self.as_u32(),
+                index: None,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: XFileSerialize<U>, U: Copy> XFileSerialize<U> for BackRef<T> {
+    fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, data: U) -> Result<()> {
+        match self {
+            // only inline objects have anything to write, everything else
+            // is already covered by the pointer written by the owner
+            Self::Inline(t) => t.xfile_serialize(ser, data),
+            Self::Null | Self::Ptr { .. } => Ok(()),
+        }
+    }
+}
+
+/// Maps the raw offsets held by [`BackRef`]s to indices within the collection
+/// they point into.
+///
+/// Tables have to be built by whoever knows where a collection was loaded,
+/// e.g. from the engine's memory while the zone is loaded. The offsets the
+/// deserializer computes for itself are approximate, so they're only used to
+/// resolve real offsets to positions in the payload (see [`crate::blocks`]).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InternTable {
+    indices: BTreeMap<u32, usize>,
+}
+
+impl InternTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a table for a contiguous array of `count` elements, each
+    /// `stride` bytes, where the first element lives at offset `base`.
+    pub fn from_array(base: u32, stride: u32, count: usize) -> Self {
+        let mut table = Self::new();
+        for i in 0..count {
+            let Some(ptr) = (i as u32)
+                .checked_mul(stride)
+                .and_then(|o| base.checked_add(o))
+            else {
+                break;
+            };
+            table.insert(ptr, i);
+        }
+        table
+    }
+
+    /// Interns `ptr` as the element at `index`.
+    ///
+    /// Returns the old index if `ptr` was already present.
+    pub fn insert(&mut self, ptr: u32, index: usize) -> Option<usize> {
+        self.indices.insert(ptr, index)
+    }
+
+    pub fn get(&self, ptr: u32) -> Option<usize> {
+        self.indices.get(&ptr).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
 /// Newtype for flexible array members of serialzed structs.
 ///
 /// In C, we might have a struct like:
@@ -468,15 +1000,15 @@ impl<'a, T: DeserializeOwned + Debug> Ptr32<'a, T> {
 /// ```
 /// This can't be easily represented in Rust, so this type encapsulates `count`
 /// and `bytes` and allows the correct number of [`T`]s to be deserialized into
-/// a [`Vec<T>`] (see [`FlexibleArrayU16::to_vec`]).
+/// a [`Vec<T>`] (see [`XArray::to_vec`]).
 ///
 /// This type and [`FlexibleArrayU32`] are exactly the same except that
 /// [`FlexibleArrayU16::count`] is a [`u16`] (as the name implies), and
 /// [`FlexibleArrayU32::count`] is a [`u32`].
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 #[repr(transparent)]
-pub(crate) struct FlexibleArrayU16<T: DeserializeOwned> {
+pub(crate) struct FlexibleArrayU16<T: XFileRead> {
     count: u16,
     _p: PhantomData<T>,
 }
@@ -493,49 +1025,44 @@ pub(crate) struct FlexibleArrayU16<T: DeserializeOwned> {
 /// ```
 /// This can't be easily represented in Rust, so this type encapsulates `count`
 /// and `bytes` and allows the correct number of [`T`]s to be deserialized into
-/// a [`Vec<T>`] (see [`FlexibleArrayU32::to_vec`]).
+/// a [`Vec<T>`] (see [`XArray::to_vec`]).
 ///
 /// This type and [`FlexibleArrayU16`] are exactly the same except that
 /// [`FlexibleArrayU32::count`] is a [`u32`] (as the name implies), and
 /// [`FlexibleArrayU16::count`] is a [`u16`].
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 #[repr(transparent)]
-pub(crate) struct FlexibleArrayU32<T: DeserializeOwned> {
+pub(crate) struct FlexibleArrayU32<T: XFileRead> {
     count: u32,
     _p: PhantomData<T>,
 }
 
-pub(crate) trait FlexibleArray<T: DeserializeOwned> {
-    fn count(&self) -> usize;
-
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) trait FlexibleArray<T: XFileRead>: XArray<T> {
     fn new(count: usize) -> Self;
-
-    fn to_vec(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<T>> {
-        let mut vt = Vec::new();
-
-        let old = de.stream_pos()?;
-        for _ in 0..self.count() {
-            vt.push(de.load_from_xfile()?);
-        }
-        let new = de.stream_pos()?;
-        // bincode will sometimes deserialize less than sizeof!(T) bytes
-        // since it treats all structs as packed. those instances need to be
-        // caught and fixed, so this is how we catch them
-        assert!(new == old + size_of!(T) as u64 * self.count() as u64);
-
-        Ok(vt)
-    }
 }
 
 macro_rules! impl_flexible_array {
     ($($s:ident,)+) => {
         $(
-            impl<T: DeserializeOwned> FlexibleArray<T> for $s<T> {
-                fn count(&self) -> usize {
+            impl<T: XFileRead> sealed::Sealed for $s<T> {}
+
+            impl<T: XFileRead> XArray<T> for $s<T> {
+                fn len(&self) -> usize {
                     self.count as _
                 }
 
+                fn is_inline(&self) -> bool {
+                    true
+                }
+
+                fn marker(&self) -> Option<PtrMarker> {
+                    None
+                }
+            }
+
+            impl<T: XFileRead> FlexibleArray<T> for $s<T> {
                 fn new(count: usize) -> Self {
                     Self {
                         count: count as _,
@@ -549,50 +1076,204 @@ macro_rules! impl_flexible_array {
 
 impl_flexible_array!(FlexibleArrayU16, FlexibleArrayU32,);
 
-pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
-    fn size(&self) -> usize;
-    fn p(&self) -> Ptr32<'a, T>;
+mod sealed {
+    pub trait Sealed {}
+}
 
-    fn new(p: Ptr32<'a, T>, size: usize) -> Self;
+/// Primitive types that [`FatPointer::to_vec_bulk`] can read with a single
+/// [`T5XFileDeserialize::load_bytes`] instead of one
+/// [`T5XFileDeserialize::load_from_xfile`] per element.
+pub trait BulkPrimitive: XFileRead + Copy + sealed::Sealed {
+    /// Converts `bytes` (whose length must be a multiple of
+    /// `size_of::<Self>()`) to [`Self`]s, swapping bytes if needed.
+    fn from_bytes(bytes: Vec<u8>, little_endian: bool) -> Vec<Self>;
+}
 
-    fn is_null(&self) -> bool {
-        self.p().is_null()
+impl sealed::Sealed for u8 {}
+
+impl BulkPrimitive for u8 {
+    fn from_bytes(bytes: Vec<u8>, _little_endian: bool) -> Vec<Self> {
+        bytes
+    }
+}
+
+macro_rules! impl_bulk_primitive {
+    ($($t:ty,)+) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl BulkPrimitive for $t {
+                fn from_bytes(bytes: Vec<u8>, little_endian: bool) -> Vec<Self> {
+                    let chunks = bytes.chunks_exact(size_of!($t));
+                    if little_endian {
+                        chunks.map(|c| <$t>::from_le_bytes(c.try_into().unwrap())).collect()
+                    } else {
+                        chunks.map(|c| <$t>::from_be_bytes(c.try_into().unwrap())).collect()
+                    }
+                }
+            }
+        )+
+    }
+}
+
+impl_bulk_primitive!(u16, u32, i16, i32, f32,);
+
+/// An array of [`T`]s that's deserialized from the stream, either because it
+/// immediately follows its containing struct (a [`FlexibleArrayU16`], etc.),
+/// or because it's pointed to by a [`FatPointer`].
+///
+/// Every method that reads the elements first checks that they fit in what's
+/// left of the stream (see [`T5XFileDeserialize::check_array_len`]), so a
+/// corrupt count results in an [`Err`] rather than a huge allocation.
+pub trait XArray<T: XFileRead>: sealed::Sealed {
+    /// The number of elements in the array.
+    fn len(&self) -> usize;
+
+    /// Whether the elements are in the stream at its current position. Null
+    /// arrays and arrays pointing to data that was already loaded aren't,
+    /// and deserialize as empty.
+    fn is_inline(&self) -> bool;
+
+    /// The marker of the pointer pointing to the array, if it's inline.
+    /// Always [`None`] for flexible arrays, which aren't pointed to.
+    fn marker(&self) -> Option<PtrMarker>;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     fn to_vec(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<T>> {
-        if self.is_null() {
+        self.iter_deserialize(de)?.collect()
+    }
+
+    /// Same as [`Self::to_vec`], but reads every element at once. Much faster
+    /// for the large primitive arrays found in maps (index buffers, cell
+    /// bits, etc.).
+    fn to_vec_bulk(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<T>>
+    where
+        T: BulkPrimitive,
+    {
+        if !self.is_inline() {
             return Ok(Vec::new());
         }
 
-        let v = if self.p().is_real() {
-            //eprintln!("ignoring offset {:#010X}", self.p().as_u32());
-            return Ok(Vec::new());
-        } else {
-            // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
-            let old = de.stream_pos()?;
-            let mut v = Vec::new();
-            for _ in 0..self.size() {
-                v.push(de.load_from_xfile::<T>()?);
+        de.check_array_len(self.len(), size_of!(T))?;
+        let bytes = de.load_bytes(self.len() * size_of!(T))?;
+        Ok(T::from_bytes(bytes, de.is_little_endian()))
+    }
+
+    /// Skips over the elements without deserializing them, for when only
+    /// what comes after them is needed.
+    fn skip(&self, de: &mut impl T5XFileDeserialize) -> Result<()> {
+        if !self.is_inline() {
+            return Ok(());
+        }
+
+        de.check_array_len(self.len(), size_of!(T))?;
+        de.skip_bytes(self.len() * size_of!(T))
+    }
+
+    /// Same as [`Self::to_vec_bulk`], but borrows the elements' bytes, in the
+    /// file's byte order, instead of decoding them.
+    fn to_slice<'buf>(&self, de: &mut impl T5XFileDeserializeBorrowed<'buf>) -> Result<&'buf [u8]> {
+        if !self.is_inline() {
+            return Ok(&[]);
+        }
+
+        de.check_array_len(self.len(), size_of!(T))?;
+        de.load_slice(self.len() * size_of!(T))
+    }
+
+    fn to_vec_into<U: From<T>>(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<U>> {
+        self.to_vec(de)
+            .map(|v| v.into_iter().map(Into::<U>::into).collect())
+    }
+
+    /// Deserializes the elements one at a time, e.g. to process an array
+    /// that's too large to hold in memory all at once.
+    ///
+    /// Fails immediately if the array doesn't fit in the stream. The
+    /// iterator stops after the first element that fails to deserialize.
+    fn iter_deserialize<'d, D: T5XFileDeserialize>(
+        &self,
+        de: &'d mut D,
+    ) -> Result<XArrayIter<'d, D, T>> {
+        let remaining = if self.is_inline() {
+            de.check_array_len(self.len(), size_of!(T))?;
+            if !self.is_empty() {
+                de.mark_ptr_target(
+                    self.marker(),
+                    core::mem::align_of::<T>(),
+                    self.len() * size_of!(T),
+                )?;
             }
-            let new = de.stream_pos()?;
+            self.len()
+        } else {
+            0
+        };
+
+        Ok(XArrayIter {
+            de,
+            remaining,
+            _p: PhantomData,
+        })
+    }
+}
+
+/// Iterator returned by [`XArray::iter_deserialize`].
+pub struct XArrayIter<'d, D, T> {
+    de: &'d mut D,
+    remaining: usize,
+    _p: PhantomData<T>,
+}
+
+impl<D: T5XFileDeserialize, T: XFileRead> Iterator for XArrayIter<'_, D, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let t = self.de.stream_pos().and_then(|old| {
+            let t = self.de.load_from_xfile::<T>()?;
+            let new = self.de.stream_pos()?;
             // bincode will sometimes deserialize less than sizeof!(T) bytes
             // since it treats all structs as packed. those instances need to be
             // caught and fixed, so this is how we catch them
-            assert!(
-                new == old + size_of!(T) as u64 * self.size() as u64,
-                "new ({new}) != old ({old}) + {} ({})",
-                size_of!(T) * self.size(),
-                new - old
-            );
-            v
-        };
+            if new != old + size_of!(T) as u64 {
+                return Err(Error::new_with_offset(
+                    file_line_col!(),
+                    old as _,
+                    ErrorKind::BrokenInvariant(format!(
+                        "{} deserialized from {} bytes instead of {}",
+                        core::any::type_name::<T>(),
+                        new - old,
+                        size_of!(T)
+                    )),
+                ));
+            }
+            Ok(t)
+        });
 
-        Ok(v)
+        self.remaining = if t.is_ok() { self.remaining - 1 } else { 0 };
+        Some(t)
     }
 
-    fn to_vec_into<U: From<T>>(&self, de: &mut impl T5XFileDeserialize) -> Result<Vec<U>> {
-        self.to_vec(de)
-            .map(|v| v.into_iter().map(Into::<U>::into).collect())
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// An [`XArray`] that's pointed to by a [`Ptr32`], with the number of
+/// elements stored next to the pointer.
+pub trait FatPointer<'a, T: XFileRead + 'a>: XArray<T> + Sized {
+    fn p(&self) -> Ptr32<'a, T>;
+
+    fn new(p: Ptr32<'a, T>, size: usize) -> Self;
+
+    fn is_null(&self) -> bool {
+        self.p().is_null()
     }
 
     fn from_slice<U>(s: &[U]) -> Self {
@@ -607,7 +1288,24 @@ pub trait FatPointer<'a, T: DeserializeOwned + 'a>: Sized {
 macro_rules! impl_fat_pointer {
     ($($s:ident,)+) => {
         $(
-            impl<'a, T: Debug + Clone + DeserializeOwned + 'a> FatPointer<'a, T>
+            impl<'a, T: Debug + Clone + XFileRead + 'a> sealed::Sealed for $s<'a, T> {}
+
+            impl<'a, T: Debug + Clone + XFileRead + 'a> XArray<T> for $s<'a, T> {
+                fn len(&self) -> usize {
+                    self.size as _
+                }
+
+                fn is_inline(&self) -> bool {
+                    // no need to seek for 0xFFFFFFFF / 0xFFFFFFFE
+                    !self.p.is_null() && !self.p.is_real()
+                }
+
+                fn marker(&self) -> Option<PtrMarker> {
+                    self.p.marker()
+                }
+            }
+
+            impl<'a, T: Debug + Clone + XFileRead + 'a> FatPointer<'a, T>
                 for $s<'a, T>
             {
                 fn new(p: Ptr32<'a, T>, size: usize) -> Self {
@@ -617,10 +1315,6 @@ macro_rules! impl_fat_pointer {
                     }
                 }
 
-                fn size(&self) -> usize {
-                    self.size as _
-                }
-
                 fn p(&self) -> Ptr32<'a, T> {
                     self.p.clone()
                 }
@@ -638,8 +1332,8 @@ macro_rules! impl_fat_pointer {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u8`], and comes before the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub struct FatPointerCountFirstU8<'a, T: Debug + Clone> {
     pub size: u8,
     pub p: Ptr32<'a, T>,
@@ -654,8 +1348,8 @@ pub struct FatPointerCountFirstU8<'a, T: Debug + Clone> {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u16`], and comes before the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub struct FatPointerCountFirstU16<'a, T: Debug + Clone> {
     pub size: u16,
     pub p: Ptr32<'a, T>,
@@ -670,8 +1364,8 @@ pub struct FatPointerCountFirstU16<'a, T: Debug + Clone> {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u32`], and comes before the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub struct FatPointerCountFirstU32<'a, T> {
     pub size: u32,
     pub p: Ptr32<'a, T>,
@@ -686,8 +1380,8 @@ pub struct FatPointerCountFirstU32<'a, T> {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u8`], and comes after the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub struct FatPointerCountLastU8<'a, T> {
     pub p: Ptr32<'a, T>,
     pub size: u8,
@@ -702,8 +1396,8 @@ pub struct FatPointerCountLastU8<'a, T> {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u16`], and comes after the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, XFileRead)]
 pub struct FatPointerCountLastU16<'a, T> {
     pub p: Ptr32<'a, T>,
     pub size: u16,
@@ -718,15 +1412,15 @@ pub struct FatPointerCountLastU16<'a, T> {
 /// of a [`u32`].
 ///
 /// In this case, [`Self::size`] is a [`u32`], and comes after the pointer.
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct FatPointerCountLastU32<'a, T> {
     pub p: Ptr32<'a, T>,
     pub size: u32,
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct Ptr32Array<'a, T> {
     pub p: Ptr32<'a, T>,
     pub size: usize,
@@ -742,18 +1436,35 @@ impl_fat_pointer!(
     Ptr32Array,
 );
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct Ptr32ArrayConst<'a, T, const N: usize>(Ptr32<'a, T>);
 
 // Can't use the macro for this since it has the const generic parameter
-impl<'a, T: Debug + Clone + DeserializeOwned + 'a, const N: usize> FatPointer<'a, T>
+impl<'a, T: Debug + Clone + XFileRead + 'a, const N: usize> sealed::Sealed
+    for Ptr32ArrayConst<'a, T, N>
+{
+}
+
+impl<'a, T: Debug + Clone + XFileRead + 'a, const N: usize> XArray<T>
     for Ptr32ArrayConst<'a, T, N>
 {
-    fn size(&self) -> usize {
+    fn len(&self) -> usize {
         N
     }
 
+    fn is_inline(&self) -> bool {
+        !self.0.is_null() && !self.0.is_real()
+    }
+
+    fn marker(&self) -> Option<PtrMarker> {
+        self.0.marker()
+    }
+}
+
+impl<'a, T: Debug + Clone + XFileRead + 'a, const N: usize> FatPointer<'a, T>
+    for Ptr32ArrayConst<'a, T, N>
+{
     fn p(&self) -> Ptr32<'a, T> {
         self.0.clone()
     }
@@ -773,7 +1484,7 @@ macro_rules! impl_xfile_into_for_fat_pointer {
         $(
             impl<'a, T, U, V> XFileDeserializeInto<Vec<U>, V> for $s<'a, T>
             where
-                T: DeserializeOwned + Debug + Clone + XFileDeserializeInto<U, V>,
+                T: XFileRead + Debug + Clone + XFileDeserializeInto<U, V>,
                 V: Copy,
             {
                 fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: V) -> Result<Vec<U>> {
@@ -799,7 +1510,7 @@ impl_xfile_into_for_fat_pointer!(
 // Can't use the macro for this since it has the const generic parameter
 impl<'a, T, U, V, const N: usize> XFileDeserializeInto<Vec<U>, V> for Ptr32ArrayConst<'a, T, N>
 where
-    T: DeserializeOwned + Debug + Clone + XFileDeserializeInto<U, V>,
+    T: XFileRead + Debug + Clone + XFileDeserializeInto<U, V>,
     V: Copy,
 {
     fn xfile_deserialize_into(&self, de: &mut impl T5XFileDeserialize, data: V) -> Result<Vec<U>> {
@@ -811,3 +1522,156 @@ where
     }
 }
 // ===============================================================================
+
+// ===============================================================================
+/// A reference to an asset that many other assets point to, like a
+/// [`Material`](crate::techset::Material) used by a lot of surfaces.
+///
+/// A [`Box`] by default. With the `arc` feature, it's an
+/// [`Arc`](alloc::sync::Arc) instead, so clones of a loaded zone (or parts of
+/// one) are cheap and can be handed to other threads.
+#[cfg(not(feature = "arc"))]
+pub type Shared<T> = Box<T>;
+#[cfg(feature = "arc")]
+pub type Shared<T> = alloc::sync::Arc<T>;
+
+// ===============================================================================
+
+// ===============================================================================
+/// The byte order an endian-tagged value (e.g. [`U32`]) is stored in.
+pub trait Endianness: Copy + Default + Debug + Eq + core::hash::Hash {
+    const LITTLE: bool;
+}
+
+/// Little-endian, like PC Fastfiles.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct LE;
+
+/// Big-endian, like console Fastfiles.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub struct BE;
+
+impl Endianness for LE {
+    const LITTLE: bool = true;
+}
+
+impl Endianness for BE {
+    const LITTLE: bool = false;
+}
+
+macro_rules! endian_primitives {
+    ($($name:ident($t:ty),)+) => {
+        $(
+            #[doc = concat!(
+                "A `", stringify!($t), "` stored in byte order `E`, whatever byte order ",
+                "the serializer or deserializer handling it uses. Useful for files that ",
+                "mix byte orders, or whose byte order is only known at runtime.\n\n",
+                "Values are compared by their bytes.",
+            )]
+            #[repr(transparent)]
+            #[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+            pub struct $name<E: Endianness>([u8; size_of::<$t>()], PhantomData<E>);
+
+            impl<E: Endianness> $name<E> {
+                pub fn new(value: $t) -> Self {
+                    let bytes = if E::LITTLE {
+                        value.to_le_bytes()
+                    } else {
+                        value.to_be_bytes()
+                    };
+                    Self(bytes, PhantomData)
+                }
+
+                pub fn get(self) -> $t {
+                    if E::LITTLE {
+                        <$t>::from_le_bytes(self.0)
+                    } else {
+                        <$t>::from_be_bytes(self.0)
+                    }
+                }
+
+                /// The value's bytes, in byte order `E`.
+                pub const fn to_bytes(self) -> [u8; size_of::<$t>()] {
+                    self.0
+                }
+
+                pub const fn from_bytes(bytes: [u8; size_of::<$t>()]) -> Self {
+                    Self(bytes, PhantomData)
+                }
+            }
+
+            impl<E: Endianness> From<$t> for $name<E> {
+                fn from(value: $t) -> Self {
+                    Self::new(value)
+                }
+            }
+
+            impl<E: Endianness> From<$name<E>> for $t {
+                fn from(value: $name<E>) -> Self {
+                    value.get()
+                }
+            }
+
+            impl<E: Endianness> Debug for $name<E> {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    Debug::fmt(&self.get(), f)
+                }
+            }
+
+            impl<E: Endianness> core::fmt::Display for $name<E> {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    core::fmt::Display::fmt(&self.get(), f)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<E: Endianness> Serialize for $name<E> {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> core::result::Result<S::Ok, S::Error> {
+                    self.0.serialize(serializer)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de, E: Endianness> Deserialize<'de> for $name<E> {
+                fn deserialize<D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> core::result::Result<Self, D::Error> {
+                    <[u8; size_of::<$t>()]>::deserialize(deserializer).map(Self::from_bytes)
+                }
+            }
+
+            // the bytes are kept as they are, whatever the reader's byte order
+            impl<E: Endianness> XFileRead for $name<E> {
+                fn xfile_read<R: XFileReader + ?Sized>(r: &mut R) -> Result<Self> {
+                    <[u8; size_of::<$t>()]>::xfile_read(r).map(Self::from_bytes)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<E: Endianness> XFileSerialize<()> for $name<E> {
+                fn xfile_serialize(
+                    &self,
+                    ser: &mut impl T5XFileSerialize,
+                    _data: (),
+                ) -> Result<()> {
+                    self.0.xfile_serialize(ser, ())
+                }
+            }
+        )+
+    };
+}
+
+endian_primitives!(
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+);
+// ===============================================================================
@@ -0,0 +1,212 @@
+//! Checks of the indices assets store into their own arrays, which are
+//! otherwise taken as-is from the Fastfile.
+//!
+//! Deserialization doesn't check them, since most are only ever followed
+//! by the engine, so a corrupted or hand-edited zone can still be loaded
+//! and inspected. [`XAssetBag::validate_references`] reports the ones that
+//! are out of range.
+
+#[cfg(feature = "world")]
+use alloc::format;
+use alloc::{string::String, vec::Vec};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+use crate::xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType};
+
+#[cfg(feature = "world")]
+use crate::gfx_world::GfxWorld;
+
+/// An index that's out of range of the array it indexes, as reported by
+/// [`XAssetBag::validate_references`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BadReference {
+    pub asset_type: XAssetType,
+    pub asset_name: String,
+    /// Path to the index within the asset, e.g.
+    /// `dpvs.surfaces[3].lightmap_index`.
+    pub field: String,
+    /// For fields that start a range (e.g. `start_surf_index`), the last
+    /// index in the range.
+    pub index: usize,
+    /// The length of the indexed array.
+    pub len: usize,
+}
+
+impl core::fmt::Display for BadReference {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:?} {}: {} reaches index {}, but there are only {}",
+            self.asset_type, self.asset_name, self.field, self.index, self.len
+        )
+    }
+}
+
+/// Collects the [`BadReference`]s of a single asset.
+#[cfg_attr(not(feature = "world"), allow(dead_code))]
+struct Validator<'a> {
+    asset: &'a XAsset,
+    name: &'a str,
+    bad: &'a mut Vec<BadReference>,
+}
+
+#[cfg_attr(not(feature = "world"), allow(dead_code))]
+impl Validator<'_> {
+    fn check(&mut self, field: impl FnOnce() -> String, index: usize, len: usize) {
+        if index >= len {
+            self.bad.push(BadReference {
+                asset_type: self.asset.asset_type(),
+                asset_name: String::from(self.name),
+                field: field(),
+                index,
+                len,
+            });
+        }
+    }
+
+    /// Checks the `count` indices starting at `start`. Empty ranges are
+    /// never out of range.
+    fn check_range(
+        &mut self,
+        field: impl FnOnce() -> String,
+        start: usize,
+        count: usize,
+        len: usize,
+    ) {
+        if count != 0 {
+            self.check(field, start.saturating_add(count - 1), len);
+        }
+    }
+
+    fn validate<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        asset: &XAssetGeneric<MAX_LOCAL_CLIENTS>,
+    ) {
+        match asset {
+            #[cfg(feature = "world")]
+            XAssetGeneric::GfxWorld(Some(world)) => self.validate_gfx_world(world),
+            _ => {}
+        }
+    }
+
+    #[cfg(feature = "world")]
+    fn validate_gfx_world<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        world: &GfxWorld<MAX_LOCAL_CLIENTS>,
+    ) {
+        let primary_lights = world.primary_light_count as usize;
+        let lightmaps = world.draw.lightmaps.len();
+        let probes = world.draw.reflection_probes.len();
+        let surfaces = world.dpvs.surfaces.len();
+
+        self.check(
+            || String::from("sun_primary_light_index"),
+            world.sun_primary_light_index,
+            primary_lights,
+        );
+        self.check(
+            || String::from("light_grid.sun_primary_light_index"),
+            world.light_grid.sun_primary_light_index,
+            primary_lights,
+        );
+        for (i, entry) in world.light_grid.entries.iter().enumerate() {
+            self.check(
+                || format!("light_grid.entries[{i}].primary_light_index"),
+                entry.primary_light_index as _,
+                primary_lights,
+            );
+        }
+
+        for (i, surf) in world.dpvs.surfaces.iter().enumerate() {
+            self.check(
+                || format!("dpvs.surfaces[{i}].lightmap_index"),
+                surf.lightmap_index,
+                lightmaps,
+            );
+            self.check(
+                || format!("dpvs.surfaces[{i}].reflection_probe_index"),
+                surf.reflection_probe_index,
+                probes,
+            );
+            self.check(
+                || format!("dpvs.surfaces[{i}].primary_light_index"),
+                surf.primary_light_index,
+                primary_lights,
+            );
+        }
+        for (i, inst) in world.dpvs.smodel_draw_insts.iter().enumerate() {
+            self.check(
+                || format!("dpvs.smodel_draw_insts[{i}].reflection_probe_index"),
+                inst.reflection_probe_index,
+                probes,
+            );
+            self.check(
+                || format!("dpvs.smodel_draw_insts[{i}].primary_light_index"),
+                inst.primary_light_index,
+                primary_lights,
+            );
+        }
+
+        for (i, group) in world.dpvs.cull_groups.iter().enumerate() {
+            self.check_range(
+                || format!("dpvs.cull_groups[{i}].start_surf_index"),
+                group.start_surf_index,
+                group.surface_count,
+                surfaces,
+            );
+        }
+        for (i, model) in world.models.iter().enumerate() {
+            self.check_range(
+                || format!("models[{i}].start_surf_index"),
+                model.start_surf_index,
+                model.surface_count,
+                surfaces,
+            );
+        }
+
+        // AABB tree leaves index into the sorted surfaces rather than the
+        // surfaces themselves
+        let sorted = world.dpvs.sorted_surf_index.len();
+        for (i, cell) in world.cells.iter().enumerate() {
+            for (j, tree) in cell.aabb_tree.iter().enumerate() {
+                self.check_range(
+                    || format!("cells[{i}].aabb_tree[{j}].start_surf_index"),
+                    tree.start_surf_index,
+                    tree.surface_count,
+                    sorted,
+                );
+            }
+        }
+    }
+}
+
+impl XAssetBag {
+    /// Checks the indices every asset stores into its own arrays (e.g.
+    /// [`GfxSurface::lightmap_index`](crate::gfx_world::GfxSurface::lightmap_index)),
+    /// and returns the ones that are out of range, in asset order.
+    ///
+    /// Only [`GfxWorld`]s are currently checked.
+    pub fn validate_references(&self) -> Vec<BadReference> {
+        let mut bad = Vec::new();
+
+        for asset in self.iter() {
+            let Some(name) = asset.name() else {
+                continue;
+            };
+
+            let mut validator = Validator {
+                asset,
+                name,
+                bad: &mut bad,
+            };
+            match asset {
+                XAsset::PC(a) => validator.validate(a),
+                XAsset::Console(a) => validator.validate(a),
+            }
+        }
+
+        bad
+    }
+}
@@ -0,0 +1,248 @@
+//! Processing assets without matching on every variant of [`XAsset`].
+//!
+//! Implement [`XAssetVisitor`] for the asset types you care about (every
+//! method does nothing by default), then hand it to [`XAsset::accept`],
+//! [`XAssetBag::accept`], or the deserializer to have it called with each
+//! asset. New asset types get a new method with a default, so visitors keep
+//! compiling as the [`XAsset`] enum grows.
+
+use crate::{
+    LocalizeEntry, MapEnts, PackIndex, RawFile, StringTable, XGlobals,
+    ddl::DdlRoot,
+    emblem::EmblemSet,
+    techset::{GfxImage, Material, MaterialTechniqueSet},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+#[cfg(feature = "world")]
+use crate::clipmap::ClipMap;
+#[cfg(feature = "world")]
+use crate::com_world::ComWorld;
+#[cfg(feature = "world")]
+use crate::destructible::DestructibleDef;
+#[cfg(feature = "ui")]
+use crate::font::Font;
+#[cfg(feature = "fx")]
+use crate::fx::{FxEffectDef, FxImpactTable};
+#[cfg(feature = "world")]
+use crate::gameworld::{GameWorldMp, GameWorldSp};
+#[cfg(feature = "world")]
+use crate::gfx_world::GfxWorld;
+#[cfg(feature = "world")]
+use crate::glass::Glasses;
+#[cfg(feature = "world")]
+use crate::light::GfxLightDef;
+#[cfg(feature = "ui")]
+use crate::menu::{MenuDef, MenuList};
+#[cfg(feature = "audio")]
+use crate::sound::{SndBank, SndDriverGlobals, SndPatch};
+#[cfg(feature = "fx")]
+use crate::weapon::WeaponVariantDef;
+#[cfg(feature = "models")]
+use crate::xanim::XAnimParts;
+#[cfg(feature = "models")]
+use crate::xmodel::{PhysConstraints, PhysPreset, XModel, XModelPieces};
+
+/// Called with each asset of a given type. See the [module docs](self).
+///
+/// Assets that are deserialized for more than one local client (see
+/// [`XAsset::max_local_clients`]) have a few types that are generic over
+/// the number of clients, so the methods for them are too.
+#[allow(unused_variables)]
+pub trait XAssetVisitor {
+    /// Called before the type-specific method of every asset, including
+    /// null ones.
+    fn visit_asset(&mut self, asset: &XAsset) {}
+
+    /// Called for every null asset (see [`XAsset::is_none`]) instead of a
+    /// type-specific method.
+    fn visit_null(&mut self, asset_type: XAssetType) {}
+
+    #[cfg(feature = "models")]
+    fn visit_xmodel_pieces(&mut self, pieces: &XModelPieces) {}
+
+    #[cfg(feature = "models")]
+    fn visit_phys_preset(&mut self, preset: &PhysPreset) {}
+
+    #[cfg(feature = "models")]
+    fn visit_phys_constraints(&mut self, constraints: &PhysConstraints) {}
+
+    #[cfg(feature = "world")]
+    fn visit_destructible_def(&mut self, def: &DestructibleDef) {}
+
+    #[cfg(feature = "models")]
+    fn visit_xanim_parts(&mut self, parts: &XAnimParts) {}
+
+    #[cfg(feature = "models")]
+    fn visit_xmodel(&mut self, model: &XModel) {}
+
+    fn visit_material(&mut self, material: &Material) {}
+
+    fn visit_technique_set(&mut self, techset: &MaterialTechniqueSet) {}
+
+    fn visit_image(&mut self, image: &GfxImage) {}
+
+    #[cfg(feature = "audio")]
+    fn visit_sound(&mut self, bank: &SndBank) {}
+
+    #[cfg(feature = "audio")]
+    fn visit_sound_patch(&mut self, patch: &SndPatch) {}
+
+    #[cfg(feature = "world")]
+    fn visit_clip_map(&mut self, clip_map: &ClipMap) {}
+
+    #[cfg(feature = "world")]
+    fn visit_clip_map_pvs(&mut self, clip_map: &ClipMap) {}
+
+    #[cfg(feature = "world")]
+    fn visit_com_world(&mut self, world: &ComWorld) {}
+
+    #[cfg(feature = "world")]
+    fn visit_game_world_sp(&mut self, world: &GameWorldSp) {}
+
+    #[cfg(feature = "world")]
+    fn visit_game_world_mp(&mut self, world: &GameWorldMp) {}
+
+    fn visit_map_ents(&mut self, ents: &MapEnts) {}
+
+    #[cfg(feature = "world")]
+    fn visit_gfx_world<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        world: &GfxWorld<MAX_LOCAL_CLIENTS>,
+    ) {
+    }
+
+    #[cfg(feature = "world")]
+    fn visit_light_def(&mut self, light: &GfxLightDef) {}
+
+    #[cfg(feature = "ui")]
+    fn visit_font(&mut self, font: &Font) {}
+
+    #[cfg(feature = "ui")]
+    fn visit_menu_list<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+        list: &MenuList<MAX_LOCAL_CLIENTS>,
+    ) {
+    }
+
+    #[cfg(feature = "ui")]
+    fn visit_menu<const MAX_LOCAL_CLIENTS: usize>(&mut self, menu: &MenuDef<MAX_LOCAL_CLIENTS>) {}
+
+    fn visit_localize_entry(&mut self, entry: &LocalizeEntry) {}
+
+    #[cfg(feature = "fx")]
+    fn visit_weapon(&mut self, weapon: &WeaponVariantDef) {}
+
+    #[cfg(feature = "audio")]
+    fn visit_snd_driver_globals(&mut self, globals: &SndDriverGlobals) {}
+
+    #[cfg(feature = "fx")]
+    fn visit_fx(&mut self, fx: &FxEffectDef) {}
+
+    #[cfg(feature = "fx")]
+    fn visit_impact_fx(&mut self, table: &FxImpactTable) {}
+
+    fn visit_raw_file(&mut self, raw_file: &RawFile) {}
+
+    fn visit_string_table(&mut self, table: &StringTable) {}
+
+    fn visit_pack_index(&mut self, index: &PackIndex) {}
+
+    fn visit_xglobals(&mut self, globals: &XGlobals) {}
+
+    fn visit_ddl(&mut self, ddl: &DdlRoot) {}
+
+    #[cfg(feature = "world")]
+    fn visit_glasses(&mut self, glasses: &Glasses) {}
+
+    fn visit_emblem_set(&mut self, set: &EmblemSet) {}
+}
+
+impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+    /// Calls `visitor`'s method for the asset's type, or
+    /// [`XAssetVisitor::visit_null`] if it's null. Doesn't call
+    /// [`XAssetVisitor::visit_asset`] (see [`XAsset::accept`]).
+    pub fn accept(&self, visitor: &mut impl XAssetVisitor) {
+        match self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(Some(a)) => visitor.visit_xmodel_pieces(a),
+            #[cfg(feature = "models")]
+            Self::PhysPreset(Some(a)) => visitor.visit_phys_preset(a),
+            #[cfg(feature = "models")]
+            Self::PhysConstraints(Some(a)) => visitor.visit_phys_constraints(a),
+            #[cfg(feature = "world")]
+            Self::DestructibleDef(Some(a)) => visitor.visit_destructible_def(a),
+            #[cfg(feature = "models")]
+            Self::XAnimParts(Some(a)) => visitor.visit_xanim_parts(a),
+            #[cfg(feature = "models")]
+            Self::XModel(Some(a)) => visitor.visit_xmodel(a),
+            Self::Material(Some(a)) => visitor.visit_material(a),
+            Self::TechniqueSet(Some(a)) => visitor.visit_technique_set(a),
+            Self::Image(Some(a)) => visitor.visit_image(a),
+            #[cfg(feature = "audio")]
+            Self::Sound(Some(a)) => visitor.visit_sound(a),
+            #[cfg(feature = "audio")]
+            Self::SoundPatch(Some(a)) => visitor.visit_sound_patch(a),
+            #[cfg(feature = "world")]
+            Self::ClipMap(Some(a)) => visitor.visit_clip_map(a),
+            #[cfg(feature = "world")]
+            Self::ClipMapPVS(Some(a)) => visitor.visit_clip_map_pvs(a),
+            #[cfg(feature = "world")]
+            Self::ComWorld(Some(a)) => visitor.visit_com_world(a),
+            #[cfg(feature = "world")]
+            Self::GameWorldSp(Some(a)) => visitor.visit_game_world_sp(a),
+            #[cfg(feature = "world")]
+            Self::GameWorldMp(Some(a)) => visitor.visit_game_world_mp(a),
+            Self::MapEnts(Some(a)) => visitor.visit_map_ents(a),
+            #[cfg(feature = "world")]
+            Self::GfxWorld(Some(a)) => visitor.visit_gfx_world(a),
+            #[cfg(feature = "world")]
+            Self::LightDef(Some(a)) => visitor.visit_light_def(a),
+            #[cfg(feature = "ui")]
+            Self::Font(Some(a)) => visitor.visit_font(a),
+            #[cfg(feature = "ui")]
+            Self::MenuList(Some(a)) => visitor.visit_menu_list(a),
+            #[cfg(feature = "ui")]
+            Self::Menu(Some(a)) => visitor.visit_menu(a),
+            Self::LocalizeEntry(Some(a)) => visitor.visit_localize_entry(a),
+            #[cfg(feature = "fx")]
+            Self::Weapon(Some(a)) => visitor.visit_weapon(a),
+            #[cfg(feature = "audio")]
+            Self::SndDriverGlobals(Some(a)) => visitor.visit_snd_driver_globals(a),
+            #[cfg(feature = "fx")]
+            Self::Fx(Some(a)) => visitor.visit_fx(a),
+            #[cfg(feature = "fx")]
+            Self::ImpactFx(Some(a)) => visitor.visit_impact_fx(a),
+            Self::RawFile(Some(a)) => visitor.visit_raw_file(a),
+            Self::StringTable(Some(a)) => visitor.visit_string_table(a),
+            Self::PackIndex(Some(a)) => visitor.visit_pack_index(a),
+            Self::XGlobals(Some(a)) => visitor.visit_xglobals(a),
+            Self::Ddl(Some(a)) => visitor.visit_ddl(a),
+            #[cfg(feature = "world")]
+            Self::Glasses(Some(a)) => visitor.visit_glasses(a),
+            Self::EmblemSet(Some(a)) => visitor.visit_emblem_set(a),
+            _ => visitor.visit_null(self.asset_type()),
+        }
+    }
+}
+
+impl XAsset {
+    /// Calls [`XAssetVisitor::visit_asset`], then `visitor`'s method for
+    /// the asset's type (see [`XAssetGeneric::accept`]).
+    pub fn accept(&self, visitor: &mut impl XAssetVisitor) {
+        visitor.visit_asset(self);
+        match self {
+            Self::PC(a) => a.accept(visitor),
+            Self::Console(a) => a.accept(visitor),
+        }
+    }
+}
+
+impl XAssetBag {
+    /// Has `visitor` visit every asset, in order (see [`XAsset::accept`]).
+    pub fn accept(&self, visitor: &mut impl XAssetVisitor) {
+        for asset in self.iter() {
+            asset.accept(visitor);
+        }
+    }
+}
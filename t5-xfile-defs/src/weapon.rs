@@ -1,18 +1,26 @@
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 use crate::{
-    Error, ErrorKind, FatPointer, Ptr32, Ptr32ArrayConst, Result, ScriptString, T5XFileDeserialize,
-    XFileDeserializeInto, XString, XStringRaw, assert_size,
+    Error, ErrorKind, FatPointer, Ptr32, Ptr32ArrayConst, Result, ScriptString, Shared,
+    T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead, XString, XStringRaw, assert_size,
     common::{Vec2, Vec3},
-    file_line_col, fx, techset, xmodel,
+    file_line_col, fx, techset,
+    xasset::XAssetType,
+    xmodel,
 };
 
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, XFileRead)]
 pub(crate) struct WeaponVariantDefRaw<'a> {
     pub internal_name: XStringRaw<'a>,
     pub variant_count: i32,
@@ -51,6 +59,7 @@ pub(crate) struct WeaponVariantDefRaw<'a> {
     pub full_metal_jacket: bool,
     pub hollow_point: bool,
     pub rapid_fire: bool,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub overlay_material: Ptr32<'a, techset::MaterialRaw<'a>>,
     pub overlay_material_low_res: Ptr32<'a, techset::MaterialRaw<'a>>,
@@ -66,7 +75,8 @@ pub(crate) struct WeaponVariantDefRaw<'a> {
 assert_size!(WeaponVariantDefRaw, 228);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeaponIconRatioType {
     #[default]
     ONE_TO_ONE = 0,
@@ -76,7 +86,8 @@ pub enum WeaponIconRatioType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct WeaponVariantDef {
     pub internal_name: XString,
     pub variant_count: usize,
@@ -115,9 +126,9 @@ pub struct WeaponVariantDef {
     pub full_metal_jacket: bool,
     pub hollow_point: bool,
     pub rapid_fire: bool,
-    pub overlay_material: Option<Box<techset::Material>>,
-    pub overlay_material_low_res: Option<Box<techset::Material>>,
-    pub dpad_icon: Option<Box<techset::Material>>,
+    pub overlay_material: Option<Shared<techset::Material>>,
+    pub overlay_material_low_res: Option<Shared<techset::Material>>,
+    pub dpad_icon: Option<Shared<techset::Material>>,
     pub dpad_icon_ratio: WeaponIconRatioType,
     pub left_hand_offset: Vec3,
     pub left_hand_rotation: Vec3,
@@ -218,7 +229,7 @@ impl<'a> XFileDeserializeInto<WeaponVariantDef, ()> for WeaponVariantDefRaw<'a>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[derive(Clone, Default, Debug, XFileRead)]
 pub(crate) struct WeaponDefRaw<'a> {
     pub overlay_name: XStringRaw<'a>,
     pub gun_xmodel: Ptr32ArrayConst<'a, Ptr32<'a, xmodel::XModelRaw<'a>>, 16>,
@@ -485,6 +496,7 @@ pub(crate) struct WeaponDefRaw<'a> {
     pub aim_padding: f32,
     pub enemy_crosshair_range: f32,
     pub crosshair_color_change: bool,
+    #[allow(dead_code)]
     pad6: [u8; 3],
     pub move_speed_scale: f32,
     pub ads_move_speed_scale: f32,
@@ -758,7 +770,8 @@ pub(crate) struct WeaponDefRaw<'a> {
 assert_size!(WeaponDefRaw, 2056);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapType {
     #[default]
     BULLET = 0,
@@ -773,7 +786,8 @@ pub enum WeapType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapClass {
     #[default]
     RIFLE = 0,
@@ -793,7 +807,8 @@ pub enum WeapClass {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum PenetrateType {
     #[default]
     NONE = 0,
@@ -804,7 +819,8 @@ pub enum PenetrateType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum ImpactType {
     #[default]
     NONE = 0,
@@ -827,7 +843,8 @@ pub enum ImpactType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapInventoryType {
     #[default]
     PRIMARY = 0,
@@ -839,7 +856,8 @@ pub enum WeapInventoryType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapFireType {
     #[default]
     FULLAUTO = 0,
@@ -853,7 +871,8 @@ pub enum WeapFireType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapClipType {
     #[default]
     BOTTOM = 0,
@@ -866,7 +885,8 @@ pub enum WeapClipType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum OffhandClass {
     #[default]
     NONE = 0,
@@ -878,7 +898,8 @@ pub enum OffhandClass {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum OffhandSlot {
     #[default]
     NONE = 0,
@@ -890,7 +911,8 @@ pub enum OffhandSlot {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapStance {
     #[default]
     STAND = 0,
@@ -900,7 +922,8 @@ pub enum WeapStance {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum ActiveReticleType {
     #[default]
     NONE = 0,
@@ -910,7 +933,8 @@ pub enum ActiveReticleType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum AmmoCounterClipType {
     #[default]
     NONE = 0,
@@ -924,7 +948,8 @@ pub enum AmmoCounterClipType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapOverlayReticle {
     #[default]
     NONE = 0,
@@ -933,7 +958,8 @@ pub enum WeapOverlayReticle {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapOverlayInterface {
     #[default]
     NONE = 0,
@@ -943,7 +969,8 @@ pub enum WeapOverlayInterface {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapProjExplosion {
     #[default]
     GRENADE = 0,
@@ -960,7 +987,8 @@ pub enum WeapProjExplosion {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapStickinessType {
     #[default]
     NONE = 0,
@@ -973,7 +1001,8 @@ pub enum WeapStickinessType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum WeapRotateType {
     #[default]
     GRENADE_ROTATE = 0,
@@ -983,7 +1012,8 @@ pub enum WeapRotateType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, FromPrimitive)]
 pub enum GuidedMissileType {
     #[default]
     NONE = 0,
@@ -997,7 +1027,8 @@ pub enum GuidedMissileType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct WeaponDef {
     pub overlay_name: XString,
     pub gun_xmodel: Option<[Option<Box<xmodel::XModel>>; 16]>,
@@ -1104,8 +1135,8 @@ pub struct WeaponDef {
     pub world_shell_eject_effect: Option<Box<fx::FxEffectDef>>,
     pub view_last_shot_eject_effect: Option<Box<fx::FxEffectDef>>,
     pub world_last_shot_eject_effect: Option<Box<fx::FxEffectDef>>,
-    pub reticle_center: Option<Box<techset::Material>>,
-    pub reticle_side: Option<Box<techset::Material>>,
+    pub reticle_center: Option<Shared<techset::Material>>,
+    pub reticle_side: Option<Shared<techset::Material>>,
     pub reticle_center_size: i32,
     pub reticle_side_size: i32,
     pub reticle_min_ofs: i32,
@@ -1153,11 +1184,11 @@ pub struct WeaponDef {
     pub rocket_model: Option<Box<xmodel::XModel>>,
     pub mounted_model: Option<Box<xmodel::XModel>>,
     pub additional_melee_model: Option<Box<xmodel::XModel>>,
-    pub hud_icon: Option<Box<techset::Material>>,
+    pub hud_icon: Option<Shared<techset::Material>>,
     pub hud_icon_ratio: WeaponIconRatioType,
-    pub indicator_icon: Option<Box<techset::Material>>,
+    pub indicator_icon: Option<Shared<techset::Material>>,
     pub indicator_icon_ratio: WeaponIconRatioType,
-    pub ammo_counter_icon: Option<Box<techset::Material>>,
+    pub ammo_counter_icon: Option<Shared<techset::Material>>,
     pub ammo_counter_icon_ratio: WeaponIconRatioType,
     pub ammo_counter_clip: AmmoCounterClipType,
     pub start_ammo: i32,
@@ -1330,7 +1361,7 @@ pub struct WeaponDef {
     pub no_quick_drop_when_empty: bool,
     pub keep_crosshair_when_ads: bool,
     pub use_only_alt_weaopon_hide_tags_in_alt_mode: bool,
-    pub kill_icon: Option<Box<techset::Material>>,
+    pub kill_icon: Option<Shared<techset::Material>>,
     pub kill_icon_ratio: WeaponIconRatioType,
     pub flip_kill_icon: bool,
     pub no_partial_reload: bool,
@@ -2425,7 +2456,7 @@ impl<'a> XFileDeserializeInto<WeaponDef, ()> for WeaponDefRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct FlameTableRaw<'a> {
     pub flame_var_stream_chunk_gravity_start: f32,
     pub flame_var_stream_chunk_gravity_end: f32,
@@ -2550,7 +2581,8 @@ pub(crate) struct FlameTableRaw<'a> {
 assert_size!(FlameTableRaw, 476);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct FlameTable {
     pub flame_var_stream_chunk_gravity_start: f32,
     pub flame_var_stream_chunk_gravity_end: f32,
@@ -2659,14 +2691,14 @@ pub struct FlameTable {
     pub flame_var_collision_speed_scale: f32,
     pub flame_var_collision_volume_scale: f32,
     pub name: XString,
-    pub fire: Option<Box<techset::Material>>,
-    pub smoke: Option<Box<techset::Material>>,
-    pub heat: Option<Box<techset::Material>>,
-    pub drips: Option<Box<techset::Material>>,
-    pub stream_fuel: Option<Box<techset::Material>>,
-    pub stream_fuel_2: Option<Box<techset::Material>>,
-    pub stream_flame: Option<Box<techset::Material>>,
-    pub stream_flame_2: Option<Box<techset::Material>>,
+    pub fire: Option<Shared<techset::Material>>,
+    pub smoke: Option<Shared<techset::Material>>,
+    pub heat: Option<Shared<techset::Material>>,
+    pub drips: Option<Shared<techset::Material>>,
+    pub stream_fuel: Option<Shared<techset::Material>>,
+    pub stream_fuel_2: Option<Shared<techset::Material>>,
+    pub stream_flame: Option<Shared<techset::Material>>,
+    pub stream_flame_2: Option<Shared<techset::Material>>,
     pub flame_off_loop_sound: XString,
     pub flame_ignite_sound: XString,
     pub flame_on_loop_sound: XString,
@@ -2830,3 +2862,272 @@ impl<'a> XFileDeserializeInto<FlameTable, ()> for FlameTableRaw<'a> {
         })
     }
 }
+
+/// The assets and sound aliases a weapon references, returned by
+/// [`WeaponDef::dependencies`] and [`WeaponVariantDef::dependencies`].
+#[derive(Clone, Default, Debug, PartialEq, Eq)]
+pub struct WeaponDependencies {
+    /// The type and name of every asset referenced, in the order they're
+    /// referenced, without duplicates.
+    pub assets: Vec<(XAssetType, String)>,
+    /// Every sound alias referenced, in the order they're referenced,
+    /// without duplicates.
+    pub sound_aliases: Vec<String>,
+}
+
+impl WeaponDependencies {
+    fn push_asset(&mut self, asset_type: XAssetType, name: &str) {
+        let dep = (asset_type, name.to_string());
+        if !name.is_empty() && !self.assets.contains(&dep) {
+            self.assets.push(dep);
+        }
+    }
+
+    fn push_xmodel(&mut self, model: &Option<Box<xmodel::XModel>>) {
+        if let Some(model) = model {
+            self.push_asset(XAssetType::XMODEL, model.name.get());
+        }
+    }
+
+    fn push_material(&mut self, material: &Option<Shared<techset::Material>>) {
+        if let Some(material) = material {
+            self.push_asset(XAssetType::MATERIAL, material.info.name.get());
+        }
+    }
+
+    fn push_fx(&mut self, fx: &Option<Box<fx::FxEffectDef>>) {
+        if let Some(fx) = fx {
+            self.push_asset(XAssetType::FX, fx.name.get());
+        }
+    }
+
+    fn push_sound_alias(&mut self, alias: &str) {
+        if !alias.is_empty() && !self.sound_aliases.iter().any(|a| a == alias) {
+            self.sound_aliases.push(alias.to_string());
+        }
+    }
+
+    /// Formats the dependencies as lines of a zone source file
+    /// (`<type>,<name>`).
+    ///
+    /// Sound aliases can't be added to a zone on their own (only the sound
+    /// banks containing them can), so they're listed in comments at the
+    /// end.
+    pub fn to_zone_source(&self) -> String {
+        let mut source = String::new();
+        for (asset_type, name) in &self.assets {
+            source += &format!("{},{name}\n", asset_type.zone_source_name());
+        }
+        if !self.sound_aliases.is_empty() {
+            source += "\n// sound aliases\n";
+            for alias in &self.sound_aliases {
+                source += &format!("// {alias}\n");
+            }
+        }
+        source
+    }
+}
+
+impl WeaponDef {
+    /// Every model, material, effect, and sound alias the weapon references
+    /// directly, including those of its flame tables.
+    pub fn dependencies(&self) -> WeaponDependencies {
+        let mut deps = WeaponDependencies::default();
+
+        for model in self.gun_xmodel.iter().flatten() {
+            deps.push_xmodel(model);
+        }
+        for model in self.world_model.iter().flat_map(|models| models.iter()) {
+            deps.push_xmodel(model);
+        }
+        for model in [
+            &self.hand_xmodel,
+            &self.world_clip_model,
+            &self.rocket_model,
+            &self.mounted_model,
+            &self.additional_melee_model,
+            &self.projectile_model,
+        ] {
+            deps.push_xmodel(model);
+        }
+
+        for material in [
+            &self.reticle_center,
+            &self.reticle_side,
+            &self.hud_icon,
+            &self.indicator_icon,
+            &self.ammo_counter_icon,
+            &self.kill_icon,
+        ] {
+            deps.push_material(material);
+        }
+
+        for fx in [
+            &self.view_flash_effect,
+            &self.world_flash_effect,
+            &self.view_shell_eject_effect,
+            &self.world_shell_eject_effect,
+            &self.view_last_shot_eject_effect,
+            &self.world_last_shot_eject_effect,
+            &self.proj_explosion_effect,
+            &self.proj_explosion_effect_2,
+            &self.proj_explosion_effect_3,
+            &self.proj_explosion_effect_4,
+            &self.proj_explosion_effect_5,
+            &self.proj_dud_effect,
+            &self.proj_tail_effect,
+            &self.proj_ignition_effect,
+            &self.tag_fx_preparation_effect,
+            &self.tag_flash_preparation_effect,
+        ] {
+            deps.push_fx(fx);
+        }
+
+        for sound in [
+            &self.pickup_sound,
+            &self.pickup_sound_player,
+            &self.ammo_pickup_sound,
+            &self.ammo_pickup_sound_player,
+            &self.projectile_sound,
+            &self.pullback_sound,
+            &self.pullback_sound_player,
+            &self.fire_sound,
+            &self.fire_sound_player,
+            &self.fire_loop_sound,
+            &self.fire_loop_sound_player,
+            &self.fire_loop_end_sound,
+            &self.fire_loop_end_sound_player,
+            &self.fire_stop_sound,
+            &self.fire_stop_sound_player,
+            &self.fire_last_sound,
+            &self.fire_last_sound_player,
+            &self.empty_fire_sound,
+            &self.empty_fire_sound_player,
+            &self.crack_sound,
+            &self.whiz_by_sound,
+            &self.melee_swipe_sound,
+            &self.melee_swipe_sound_player,
+            &self.melee_hit_sound,
+            &self.melee_miss_sound,
+            &self.rechamber_sound,
+            &self.rechamber_sound_player,
+            &self.reload_sound,
+            &self.reload_sound_player,
+            &self.reload_empty_sound,
+            &self.reload_empty_sound_player,
+            &self.reload_start_sound,
+            &self.reload_start_sound_player,
+            &self.reload_end_sound,
+            &self.reload_end_sound_player,
+            &self.rotate_loop_sound,
+            &self.rotate_loop_sound_player,
+            &self.deploy_sound,
+            &self.deploy_sound_player,
+            &self.finish_deploy_sound,
+            &self.finish_deploy_sound_player,
+            &self.breakdown_sound,
+            &self.breakdown_sound_player,
+            &self.finish_breakdown_sound,
+            &self.finish_breakdown_sound_player,
+            &self.detonate_sound,
+            &self.detonate_sound_player,
+            &self.night_vision_wear_sound,
+            &self.night_vision_wear_sound_player,
+            &self.night_vision_remove_sound,
+            &self.night_vision_remove_sound_player,
+            &self.alt_switch_sound,
+            &self.alt_switch_sound_player,
+            &self.raise_sound,
+            &self.raise_sound_player,
+            &self.first_raise_sound,
+            &self.first_raise_sound_player,
+            &self.put_away_sound,
+            &self.put_away_sound_player,
+            &self.overheat_sound,
+            &self.overheat_sound_player,
+            &self.ads_zoom_sound,
+            &self.spin_loop_sound,
+            &self.spin_loop_sound_player,
+            &self.start_spin_sound,
+            &self.start_spin_sound_player,
+            &self.stop_spin_sound,
+            &self.stop_spin_sound_player,
+            &self.stack_sound,
+            &self.proj_explosion_sound,
+            &self.proj_dud_sound,
+            &self.mortar_shell_sound,
+            &self.tank_shell_sound,
+            &self.proj_ignition_sound,
+        ] {
+            deps.push_sound_alias(sound.get());
+        }
+        for sound in self.bounce_sound.iter().flat_map(|sounds| sounds.iter()) {
+            deps.push_sound_alias(sound.get());
+        }
+        for sound in self
+            .notetrack_sound_map_values
+            .iter()
+            .flat_map(|sounds| sounds.iter())
+        {
+            deps.push_sound_alias(sound);
+        }
+
+        let flame_tables = [
+            &self.flame_table_first_person_ptr,
+            &self.flame_table_third_person_ptr,
+        ];
+        for table in flame_tables.into_iter().flatten() {
+            let materials = [
+                &table.fire,
+                &table.smoke,
+                &table.heat,
+                &table.drips,
+                &table.stream_fuel,
+                &table.stream_fuel_2,
+                &table.stream_flame,
+                &table.stream_flame_2,
+            ];
+            for material in materials {
+                deps.push_material(material);
+            }
+            let sounds = [
+                &table.flame_off_loop_sound,
+                &table.flame_ignite_sound,
+                &table.flame_on_loop_sound,
+                &table.flame_cooldown_sound,
+            ];
+            for sound in sounds {
+                deps.push_sound_alias(sound.get());
+            }
+        }
+
+        deps
+    }
+}
+
+impl WeaponVariantDef {
+    /// Every animation, model, material, effect, and sound alias the weapon
+    /// variant references directly, including those of its
+    /// [`WeaponDef`] (see [`WeaponDef::dependencies`]).
+    pub fn dependencies(&self) -> WeaponDependencies {
+        let mut deps = self
+            .weap_def
+            .as_ref()
+            .map(|def| def.dependencies())
+            .unwrap_or_default();
+
+        for anim in &self.xanims {
+            deps.push_asset(XAssetType::XANIMPARTS, anim.get());
+        }
+        let materials = [
+            &self.overlay_material,
+            &self.overlay_material_low_res,
+            &self.dpad_icon,
+        ];
+        for material in materials {
+            deps.push_material(material);
+        }
+
+        deps
+    }
+}
@@ -1,18 +1,22 @@
+#[cfg(feature = "serde")]
 use core::mem::transmute;
 
 use alloc::{boxed::Box, vec::Vec};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    FatPointer, Ptr32, Result, ScriptString, T5XFileDeserialize, T5XFileSerialize,
-    XFileDeserializeInto, XFileSerialize, XString, XStringRaw, assert_size, common::Vec3,
+    Ptr32, Result, ScriptString, T5XFileDeserialize, XArray, XFileDeserializeInto, XFileRead,
+    XString, XStringRaw, assert_size, common::Vec3, read,
 };
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimPartsRaw<'a> {
     pub name: XStringRaw<'a>,
     pub data_byte_count: u16,
@@ -30,6 +34,7 @@ pub(crate) struct XAnimPartsRaw<'a> {
     pub notify_count: u8,
     pub asset_type: u8,
     pub is_default: bool,
+    #[allow(dead_code)]
     pad: [u8; 3],
     pub random_data_short_count: u32,
     pub index_count: u32,
@@ -50,10 +55,23 @@ pub(crate) struct XAnimPartsRaw<'a> {
 }
 assert_size!(XAnimPartsRaw, 104);
 
+// Indices into `XAnimParts::bone_count`. The rotation types partition the
+// parts (in the order of `XAnimParts::names`); the translation types count
+// the parts that have each kind of translation.
+pub const PART_TYPE_NO_QUAT: usize = 0;
+pub const PART_TYPE_HALF_QUAT: usize = 1;
+pub const PART_TYPE_FULL_QUAT: usize = 2;
+pub const PART_TYPE_HALF_QUAT_NO_SIZE: usize = 3;
+pub const PART_TYPE_FULL_QUAT_NO_SIZE: usize = 4;
+pub const PART_TYPE_SMALL_TRANS: usize = 5;
+pub const PART_TYPE_TRANS: usize = 6;
+pub const PART_TYPE_TRANS_NO_SIZE: usize = 7;
+pub const PART_TYPE_NO_TRANS: usize = 8;
 pub const PART_TYPE_ALL: usize = 9;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XAnimParts {
     pub name: XString,
     pub numframes: u16,
@@ -110,7 +128,7 @@ impl<'a> XFileDeserializeInto<XAnimParts, ()> for XAnimPartsRaw<'a> {
         let data_byte = self
             .data_byte
             .to_array(self.data_byte_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         //dbg!(&data_byte.len());
         let data_short = self
             .data_short
@@ -125,7 +143,7 @@ impl<'a> XFileDeserializeInto<XAnimParts, ()> for XAnimPartsRaw<'a> {
         let random_data_byte = self
             .random_data_byte
             .to_array(self.random_data_byte_count as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         //dbg!(&random_data_byte.len());
         let random_data_short = self
             .random_data_short
@@ -173,6 +191,7 @@ impl<'a> XFileDeserializeInto<XAnimParts, ()> for XAnimPartsRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'a> XFileSerialize<()> for XAnimParts {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -242,12 +261,13 @@ impl<'a> XFileSerialize<()> for XAnimParts {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimIndicesRaw<'a>(Ptr32<'a, ()>);
 assert_size!(XAnimIndicesRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum XAnimIndices {
     _1(Vec<u8>),
     _2(Vec<u16>),
@@ -281,16 +301,18 @@ impl<'a> From<XAnimIndices> for XAnimIndicesRaw<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimNotifyInfoRaw {
     pub name: ScriptString,
+    #[allow(dead_code)]
     pad: [u8; 2],
     pub time: f32,
 }
 assert_size!(XAnimNotifyInfoRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XAnimNotifyInfo {
     pub name: XString,
     pub time: f32,
@@ -309,6 +331,7 @@ impl XFileDeserializeInto<XAnimNotifyInfo, ()> for XAnimNotifyInfoRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XAnimNotifyInfo {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = ser.get_or_insert_script_string(self.name.get())?;
@@ -325,7 +348,7 @@ impl XFileSerialize<()> for XAnimNotifyInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDeltaPartRaw<'a> {
     pub trans: Ptr32<'a, XAnimPartTransRaw>,
     pub quat: Ptr32<'a, XAnimDeltaPartQuatRaw>,
@@ -333,7 +356,8 @@ pub(crate) struct XAnimDeltaPartRaw<'a> {
 assert_size!(XAnimDeltaPartRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XAnimDeltaPart {
     pub trans: Option<Box<XAnimPartTrans>>,
     pub quat: Option<Box<XAnimDeltaPartQuat>>,
@@ -353,6 +377,7 @@ impl<'a> XFileDeserializeInto<XAnimDeltaPart, u16> for XAnimDeltaPartRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XAnimDeltaPart {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let trans = Ptr32::from_box(&self.trans);
@@ -367,17 +392,19 @@ impl XFileSerialize<()> for XAnimDeltaPart {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimPartTransRaw {
     pub size: u16,
     pub small_trans: u8,
+    #[allow(dead_code)]
     pad: [u8; 1],
     pub u: XAnimPartTransDataRaw,
 }
 assert_size!(XAnimPartTransRaw, 36);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XAnimPartTrans {
     pub size: u16,
     pub small_trans: u8,
@@ -401,6 +428,7 @@ impl XFileDeserializeInto<XAnimPartTrans, u16> for XAnimPartTransRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XAnimPartTrans {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = if let Some(u) = &self.u {
@@ -462,12 +490,13 @@ impl XFileSerialize<()> for XAnimPartTrans {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimPartTransDataRaw([u8; 32]);
 assert_size!(XAnimPartTransDataRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum XAnimPartTransData {
     Frames(XAnimPartTransFrames),
     Frame0(Vec3),
@@ -480,19 +509,21 @@ impl XFileDeserializeInto<Option<XAnimPartTransData>, (u16, u8, u16)> for XAnimP
         (numframes, small_trans, size): (u16, u8, u16),
     ) -> Result<Option<XAnimPartTransData>> {
         if size == 0 {
-            Ok(Some(XAnimPartTransData::Frame0(unsafe {
-                transmute::<[u8; 12], _>(self.0[..12].try_into().unwrap())
-            })))
+            Ok(Some(XAnimPartTransData::Frame0(read::from_bytes(
+                &self.0,
+                de.is_little_endian(),
+            )?)))
         } else {
-            let frames = unsafe { transmute::<_, XAnimPartTransFramesRaw>(self.0) }
-                .xfile_deserialize_into(de, (numframes, small_trans, size))?;
+            let frames =
+                read::from_bytes::<XAnimPartTransFramesRaw>(&self.0, de.is_little_endian())?
+                    .xfile_deserialize_into(de, (numframes, small_trans, size))?;
             Ok(Some(XAnimPartTransData::Frames(frames)))
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimPartTransFramesRaw<'a> {
     pub mins: [f32; 3],
     pub maxs: [f32; 3],
@@ -502,7 +533,8 @@ pub(crate) struct XAnimPartTransFramesRaw<'a> {
 assert_size!(XAnimPartTransFramesRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct XAnimPartTransFrames {
     pub mins: Vec3,
     pub maxs: Vec3,
@@ -532,12 +564,13 @@ impl<'a> XFileDeserializeInto<XAnimPartTransFrames, (u16, u8, u16)>
     }
 }
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDynamicFramesRaw<'a>(Ptr32<'a, ()>);
 assert_size!(XAnimDynamicFramesRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum XAnimDynamicFrames {
     _1(Vec<[u8; 3]>),
     _2(Vec<[u16; 3]>),
@@ -572,12 +605,13 @@ impl<'a> XFileDeserializeInto<XAnimDynamicFrames, (u8, u16)> for XAnimDynamicFra
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDynamicIndicesRaw<'a>(Ptr32<'a, ()>);
 assert_size!(XAnimDynamicFramesRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum XAnimDynamicIndices {
     _1(Vec<u8>),
     _2(Vec<u16>),
@@ -605,16 +639,18 @@ impl<'a> XFileDeserializeInto<XAnimDynamicIndices, (u16, u16)> for XAnimDynamicI
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDeltaPartQuatRaw {
     pub size: u16,
+    #[allow(dead_code)]
     pad: [u8; 2],
     pub u: XAnimDeltaPartQuatDataRaw,
 }
 assert_size!(XAnimDeltaPartQuatRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct XAnimDeltaPartQuat {
     pub size: u16,
     pub u: Option<XAnimDeltaPartQuatData>,
@@ -633,6 +669,7 @@ impl XFileDeserializeInto<XAnimDeltaPartQuat, u16> for XAnimDeltaPartQuatRaw {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XAnimDeltaPartQuat {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let u = if let Some(u) = &self.u {
@@ -683,12 +720,13 @@ impl XFileSerialize<()> for XAnimDeltaPartQuat {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDeltaPartQuatDataRaw([u8; 8]);
 assert_size!(XAnimDeltaPartQuatDataRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum XAnimDeltaPartQuatData {
     Frames(XAnimDeltaPartQuatDataFrames),
     Frame0([i16; 2]),
@@ -703,23 +741,24 @@ impl<'a> XFileDeserializeInto<Option<XAnimDeltaPartQuatData>, (u16, u16)>
         (numframes, size): (u16, u16),
     ) -> Result<Option<XAnimDeltaPartQuatData>> {
         if size == 0 {
-            let frames = unsafe {
-                transmute::<[u8; 4], Ptr32<'a, [i16; 2]>>(self.0[0..4].try_into().unwrap())
-            }
-            .xfile_get(de)?
-            .unwrap_or_default();
+            let frames = read::from_bytes::<Ptr32<'a, [i16; 2]>>(&self.0, de.is_little_endian())?
+                .xfile_get(de)?
+                .unwrap_or_default();
             Ok(Some(XAnimDeltaPartQuatData::Frame0(frames)))
         } else {
             Ok(Some(XAnimDeltaPartQuatData::Frames(
-                unsafe { transmute::<_, XAnimDeltaPartQuatDataFramesRaw>(self.0) }
-                    .xfile_deserialize_into(de, (numframes, size))?,
+                read::from_bytes::<XAnimDeltaPartQuatDataFramesRaw>(
+                    &self.0,
+                    de.is_little_endian(),
+                )?
+                .xfile_deserialize_into(de, (numframes, size))?,
             )))
         }
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, XFileRead)]
 pub(crate) struct XAnimDeltaPartQuatDataFramesRaw<'a> {
     pub frames: Ptr32<'a, [i16; 2]>,
     pub indices: XAnimDynamicIndicesRaw<'a>,
@@ -727,7 +766,8 @@ pub(crate) struct XAnimDeltaPartQuatDataFramesRaw<'a> {
 assert_size!(XAnimDeltaPartQuatDataFramesRaw, 8);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct XAnimDeltaPartQuatDataFrames {
     pub frames: Vec<[i16; 2]>,
     pub indices: XAnimDynamicIndices,
@@ -1,5 +1,15 @@
-use alloc::{boxed::Box, vec::Vec};
+use core::any::Any;
+
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
@@ -7,38 +17,70 @@ use crate::prelude::*;
 
 use crate::{
     Error, ErrorKind, FatPointerCountFirstU32, LocalizeEntry, LocalizeEntryRaw, MapEnts,
-    MapEntsRaw, PackIndex, PackIndexRaw, Ptr32, RawFile, RawFileRaw, Result, StringTable,
-    StringTableRaw, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFilePlatform,
-    XFileSerialize, XGlobals, XGlobalsRaw, XString, XStringRaw, assert_size,
-    clipmap::{ClipMap, ClipMapRaw},
-    com_world::{ComWorld, ComWorldRaw},
+    MapEntsRaw, PackIndex, PackIndexRaw, Ptr32, RawFile, RawFileRaw, RawFileRef, Result,
+    StringTable, StringTableRaw, T5XFileDeserialize, T5XFileDeserializeBorrowed,
+    XFileDeserializeBorrowed, XFileDeserializeInto, XFilePlatform, XFileRead, XGlobals,
+    XGlobalsRaw, XString, XStringRaw,
+    access::HasImages,
+    assert_size,
     ddl::{DdlRoot, DdlRootRaw},
-    destructible::{DestructibleDef, DestructibleDefRaw},
     emblem::{EmblemSet, EmblemSetRaw},
     file_line_col,
-    font::{Font, FontRaw},
-    fx::{FxEffectDef, FxEffectDefRaw, FxImpactTable, FxImpactTableRaw},
-    gameworld::{GameWorldMp, GameWorldMpRaw, GameWorldSp, GameWorldSpRaw},
-    gfx_world::{GfxWorld, GfxWorldRaw},
-    glass::{Glasses, GlassesRaw},
-    light::{GfxLightDef, GfxLightDefRaw},
-    menu::{MenuDef, MenuDefRaw, MenuList, MenuListRaw},
-    sound::{SndBank, SndBankRaw, SndDriverGlobals, SndDriverGlobalsRaw, SndPatch, SndPatchRaw},
+    stats::XAssetStats,
     techset::{
-        GfxImage, GfxImageRaw, Material, MaterialRaw, MaterialTechniqueSet, MaterialTechniqueSetRaw,
+        GfxImage, GfxImageRaw, Material, MaterialRaw, MaterialTechniqueSet,
+        MaterialTechniqueSetRaw, MaterialTechniqueSetRef,
     },
-    weapon::{WeaponVariantDef, WeaponVariantDefRaw},
-    xanim::{XAnimParts, XAnimPartsRaw},
-    xmodel::{PhysConstraints, PhysConstraintsRaw, PhysPreset, PhysPresetRaw, XModel, XModelRaw},
+};
+#[cfg(feature = "serde")]
+use crate::{T5XFileSerialize, XFileSerialize};
+
+#[cfg(feature = "models")]
+use crate::access::HasMaterials;
+#[cfg(feature = "world")]
+use crate::clipmap::{ClipMap, ClipMapRaw};
+#[cfg(feature = "world")]
+use crate::com_world::{ComWorld, ComWorldRaw};
+#[cfg(feature = "world")]
+use crate::destructible::{DestructibleDef, DestructibleDefRaw};
+#[cfg(feature = "ui")]
+use crate::font::{Font, FontRaw};
+#[cfg(feature = "fx")]
+use crate::fx::{FxEffectDef, FxEffectDefRaw, FxImpactTable, FxImpactTableRaw};
+#[cfg(feature = "world")]
+use crate::gameworld::{GameWorldMp, GameWorldMpRaw, GameWorldSp, GameWorldSpRaw};
+#[cfg(feature = "world")]
+use crate::gfx_world::{GfxWorld, GfxWorldRaw};
+#[cfg(feature = "world")]
+use crate::glass::{Glasses, GlassesRaw};
+#[cfg(feature = "world")]
+use crate::light::{GfxLightDef, GfxLightDefRaw};
+#[cfg(feature = "ui")]
+use crate::menu::{MenuDef, MenuDefRaw, MenuList, MenuListRaw};
+#[cfg(feature = "audio")]
+use crate::sound::{
+    SndBank, SndBankRaw, SndBankRef, SndDriverGlobals, SndDriverGlobalsRaw, SndPatch, SndPatchRaw,
+};
+#[cfg(feature = "fx")]
+use crate::weapon::{WeaponVariantDef, WeaponVariantDefRaw};
+#[cfg(feature = "models")]
+use crate::xanim::{XAnimParts, XAnimPartsRaw};
+#[cfg(feature = "models")]
+use crate::xmodel::{
+    PhysConstraints, PhysConstraintsRaw, PhysPreset, PhysPresetRaw, XModel, XModelPieces,
+    XModelPiecesRaw, XModelRaw, XModelRef,
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum XAsset {
     PC(XAssetGeneric<1>),
     Console(XAssetGeneric<4>),
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XAsset {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         match self {
@@ -49,17 +91,38 @@ impl XFileSerialize<()> for XAsset {
 }
 
 impl XAsset {
+    #[doc(hidden)]
     pub fn try_get(
         de: &mut impl T5XFileDeserialize,
         xasset_raw: XAssetRaw,
         platform: XFilePlatform,
     ) -> Result<Self> {
-        let asset = if platform.is_pc() {
-            Self::PC(xasset_raw.xfile_deserialize_into(de, ())?)
-        } else {
-            Self::Console(xasset_raw.xfile_deserialize_into(de, ())?)
-        };
-        Ok(asset)
+        Self::try_get_with_max_local_clients(de, xasset_raw, platform.max_local_clients())
+    }
+
+    /// Same as [`Self::try_get`], but for Fastfiles with room for
+    /// `max_local_clients` local clients rather than the platform's default
+    /// (see [`XFilePlatform::max_local_clients`]).
+    ///
+    /// [`GfxWorld`], [`MenuList`], and [`MenuDef`] are laid out differently
+    /// depending on the number of local clients, so it has to be known
+    /// before they can be deserialized. Only 1 ([`Self::PC`]) and 4
+    /// ([`Self::Console`]) are supported; anything else fails with
+    /// [`ErrorKind::UnsupportedMaxLocalClients`].
+    #[doc(hidden)]
+    pub fn try_get_with_max_local_clients(
+        de: &mut impl T5XFileDeserialize,
+        xasset_raw: XAssetRaw,
+        max_local_clients: usize,
+    ) -> Result<Self> {
+        match max_local_clients {
+            1 => Ok(Self::PC(xasset_raw.xfile_deserialize_into(de, ())?)),
+            4 => Ok(Self::Console(xasset_raw.xfile_deserialize_into(de, ())?)),
+            _ => Err(Error::new(
+                file_line_col!(),
+                ErrorKind::UnsupportedMaxLocalClients(max_local_clients),
+            )),
+        }
     }
 
     pub fn name(&self) -> Option<&str> {
@@ -69,6 +132,14 @@ impl XAsset {
         }
     }
 
+    /// See [`XAssetGeneric::name_mut`].
+    pub fn name_mut(&mut self) -> Option<&mut XString> {
+        match self {
+            Self::PC(a) => a.name_mut(),
+            Self::Console(a) => a.name_mut(),
+        }
+    }
+
     pub fn is_some(&self) -> bool {
         match self {
             Self::PC(a) => a.is_some(),
@@ -80,6 +151,14 @@ impl XAsset {
         !self.is_some()
     }
 
+    /// See [`XAssetGeneric::ends_zone`].
+    pub fn ends_zone(&self) -> bool {
+        match self {
+            Self::PC(a) => a.ends_zone(),
+            Self::Console(a) => a.ends_zone(),
+        }
+    }
+
     pub fn is_pc(&self) -> bool {
         matches!(self, Self::PC(_))
     }
@@ -88,88 +167,419 @@ impl XAsset {
         !self.is_pc()
     }
 
+    /// The number of local clients the asset was deserialized for (see
+    /// [`Self::try_get_with_max_local_clients`]).
+    pub fn max_local_clients(&self) -> usize {
+        match self {
+            Self::PC(_) => 1,
+            Self::Console(_) => 4,
+        }
+    }
+
+    /// The asset as an [`XAssetGeneric`] for `MAX_LOCAL_CLIENTS` local
+    /// clients, or [`None`] if it was deserialized for a different number
+    /// (see [`Self::max_local_clients`]). Lets code that's generic over the
+    /// number of local clients get at the asset without matching on
+    /// [`Self::PC`] and [`Self::Console`].
+    pub fn as_generic<const MAX_LOCAL_CLIENTS: usize>(
+        &self,
+    ) -> Option<&XAssetGeneric<MAX_LOCAL_CLIENTS>> {
+        match self {
+            Self::PC(a) => (a as &dyn Any).downcast_ref(),
+            Self::Console(a) => (a as &dyn Any).downcast_ref(),
+        }
+    }
+
+    /// See [`Self::as_generic`].
+    pub fn as_generic_mut<const MAX_LOCAL_CLIENTS: usize>(
+        &mut self,
+    ) -> Option<&mut XAssetGeneric<MAX_LOCAL_CLIENTS>> {
+        match self {
+            Self::PC(a) => (a as &mut dyn Any).downcast_mut(),
+            Self::Console(a) => (a as &mut dyn Any).downcast_mut(),
+        }
+    }
+
+    /// The reverse of [`Self::as_generic`]. Only 1 and 4 local clients are
+    /// supported (see [`Self::try_get_with_max_local_clients`]); anything
+    /// else fails with [`ErrorKind::UnsupportedMaxLocalClients`].
+    pub fn from_generic<const MAX_LOCAL_CLIENTS: usize>(
+        asset: XAssetGeneric<MAX_LOCAL_CLIENTS>,
+    ) -> Result<Self> {
+        let asset: Box<dyn Any> = Box::new(asset);
+        let asset = match asset.downcast::<XAssetGeneric<1>>() {
+            Ok(asset) => return Ok(Self::PC(*asset)),
+            Err(asset) => asset,
+        };
+        match asset.downcast::<XAssetGeneric<4>>() {
+            Ok(asset) => Ok(Self::Console(*asset)),
+            Err(_) => Err(Error::new(
+                file_line_col!(),
+                ErrorKind::UnsupportedMaxLocalClients(MAX_LOCAL_CLIENTS),
+            )),
+        }
+    }
+
     pub fn asset_type(&self) -> XAssetType {
         match self {
             Self::PC(a) => a.asset_type(),
             Self::Console(a) => a.asset_type(),
         }
     }
+
+    /// See [`XAssetGeneric::dependencies`].
+    pub fn dependencies(&self) -> Vec<(XAssetType, String)> {
+        match self {
+            Self::PC(a) => a.dependencies(),
+            Self::Console(a) => a.dependencies(),
+        }
+    }
+
+    /// The sound bank, if the asset is a (non-null) [`SndBank`].
+    #[cfg(feature = "audio")]
+    pub fn snd_bank_mut(&mut self) -> Option<&mut SndBank> {
+        match self {
+            Self::PC(XAssetGeneric::Sound(Some(bank)))
+            | Self::Console(XAssetGeneric::Sound(Some(bank))) => Some(bank),
+            _ => None,
+        }
+    }
+
+    /// The image, if the asset is a (non-null) [`GfxImage`].
+    pub fn image_mut(&mut self) -> Option<&mut GfxImage> {
+        match self {
+            Self::PC(XAssetGeneric::Image(Some(image)))
+            | Self::Console(XAssetGeneric::Image(Some(image))) => Some(image),
+            _ => None,
+        }
+    }
+
+    /// The image, if the asset is a (non-null) [`GfxImage`].
+    pub fn image(&self) -> Option<&GfxImage> {
+        match self {
+            Self::PC(XAssetGeneric::Image(Some(image)))
+            | Self::Console(XAssetGeneric::Image(Some(image))) => Some(image),
+            _ => None,
+        }
+    }
+
+    /// The entities, if the asset is a (non-null) [`MapEnts`].
+    pub fn map_ents(&self) -> Option<&MapEnts> {
+        match self {
+            Self::PC(XAssetGeneric::MapEnts(Some(map_ents)))
+            | Self::Console(XAssetGeneric::MapEnts(Some(map_ents))) => Some(map_ents),
+            _ => None,
+        }
+    }
+
+    /// The globals, if the asset is a (non-null) [`XGlobals`].
+    pub fn xglobals(&self) -> Option<&XGlobals> {
+        match self {
+            Self::PC(XAssetGeneric::XGlobals(Some(xglobals)))
+            | Self::Console(XAssetGeneric::XGlobals(Some(xglobals))) => Some(xglobals),
+            _ => None,
+        }
+    }
+
+    /// Estimates how many bytes the asset takes up, from the size of its
+    /// fixed-width `bincode` serialization. Doesn't match the asset's size
+    /// in a Fastfile exactly, but is in the same ballpark.
+    #[cfg(all(feature = "serde", feature = "bincode"))]
+    pub fn estimated_size(&self) -> u64 {
+        use bincode::Options;
+
+        bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .serialized_size(self)
+            .unwrap_or_default()
+    }
+}
+
+/// Borrowed counterpart of [`XAsset`].
+///
+/// Assets with large byte blobs borrow them from the inflated Fastfile data
+/// instead of copying them: [`RawFile`] buffers, shader programs (in
+/// technique sets), sound samples (in sound banks) and vertex data (in
+/// models). Every other asset type is deserialized as usual and stored in
+/// [`XAssetRef::Owned`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum XAssetRef<'buf> {
+    RawFile(Option<RawFileRef<'buf>>),
+    TechniqueSet(Option<MaterialTechniqueSetRef<'buf>>),
+    #[cfg(feature = "audio")]
+    Sound(Option<SndBankRef<'buf>>),
+    #[cfg(feature = "models")]
+    XModel(Option<XModelRef<'buf>>),
+    Owned(XAsset),
+}
+
+impl<'buf> XAssetRef<'buf> {
+    #[doc(hidden)]
+    pub fn try_get(
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        xasset_raw: XAssetRaw,
+        platform: XFilePlatform,
+    ) -> Result<Self> {
+        Self::try_get_with_max_local_clients(de, xasset_raw, platform.max_local_clients())
+    }
+
+    /// See [`XAsset::try_get_with_max_local_clients`].
+    #[doc(hidden)]
+    pub fn try_get_with_max_local_clients(
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        xasset_raw: XAssetRaw,
+        max_local_clients: usize,
+    ) -> Result<Self> {
+        Ok(match num::FromPrimitive::from_u32(xasset_raw.asset_type) {
+            Some(XAssetType::RAWFILE) => Self::RawFile(
+                xasset_raw
+                    .asset_data
+                    .cast::<RawFileRaw>()
+                    .xfile_get(de)?
+                    .map(|r| r.xfile_deserialize_borrowed(de, ()))
+                    .transpose()?,
+            ),
+            Some(XAssetType::TECHNIQUE_SET) => Self::TechniqueSet(
+                xasset_raw
+                    .asset_data
+                    .cast::<MaterialTechniqueSetRaw>()
+                    .xfile_get(de)?
+                    .map(|t| t.xfile_deserialize_borrowed(de, ()))
+                    .transpose()?,
+            ),
+            #[cfg(feature = "audio")]
+            Some(XAssetType::SOUND) => Self::Sound(
+                xasset_raw
+                    .asset_data
+                    .cast::<SndBankRaw>()
+                    .xfile_get(de)?
+                    .map(|b| b.xfile_deserialize_borrowed(de, ()))
+                    .transpose()?,
+            ),
+            #[cfg(feature = "models")]
+            Some(XAssetType::XMODEL) => Self::XModel(
+                xasset_raw
+                    .asset_data
+                    .cast::<XModelRaw>()
+                    .xfile_get(de)?
+                    .map(|m| m.xfile_deserialize_borrowed(de, ()))
+                    .transpose()?,
+            ),
+            _ => Self::Owned(XAsset::try_get_with_max_local_clients(
+                de,
+                xasset_raw,
+                max_local_clients,
+            )?),
+        })
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::RawFile(r) => r.as_ref().map(|r| r.name.get()),
+            Self::TechniqueSet(t) => t.as_ref().map(|t| t.name.get()),
+            #[cfg(feature = "audio")]
+            Self::Sound(b) => b.as_ref().map(|b| b.name.get()),
+            #[cfg(feature = "models")]
+            Self::XModel(m) => m.as_ref().map(|m| m.model.name.get()),
+            Self::Owned(a) => a.name(),
+        }
+    }
+
+    pub fn is_some(&self) -> bool {
+        match self {
+            Self::RawFile(r) => r.is_some(),
+            Self::TechniqueSet(t) => t.is_some(),
+            #[cfg(feature = "audio")]
+            Self::Sound(b) => b.is_some(),
+            #[cfg(feature = "models")]
+            Self::XModel(m) => m.is_some(),
+            Self::Owned(a) => a.is_some(),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    pub fn asset_type(&self) -> XAssetType {
+        match self {
+            Self::RawFile(_) => XAssetType::RAWFILE,
+            Self::TechniqueSet(_) => XAssetType::TECHNIQUE_SET,
+            #[cfg(feature = "audio")]
+            Self::Sound(_) => XAssetType::SOUND,
+            #[cfg(feature = "models")]
+            Self::XModel(_) => XAssetType::XMODEL,
+            Self::Owned(a) => a.asset_type(),
+        }
+    }
+
+    /// See [`XAssetGeneric::ends_zone`].
+    pub fn ends_zone(&self) -> bool {
+        match self {
+            Self::Owned(a) => a.ends_zone(),
+            _ => false,
+        }
+    }
+
+    /// Copies any borrowed data, converting `self` into an [`XAsset`].
+    pub fn into_owned(self, platform: XFilePlatform) -> XAsset {
+        match self {
+            Self::Owned(a) => a,
+            _ if platform.is_console() => XAsset::Console(self.into_generic(platform.is_le())),
+            _ => XAsset::PC(self.into_generic(platform.is_le())),
+        }
+    }
+
+    fn into_generic<const MAX_LOCAL_CLIENTS: usize>(
+        self,
+        little_endian: bool,
+    ) -> XAssetGeneric<MAX_LOCAL_CLIENTS> {
+        match self {
+            Self::RawFile(r) => XAssetGeneric::RawFile(r.map(|r| Box::new(r.to_owned()))),
+            Self::TechniqueSet(t) => {
+                XAssetGeneric::TechniqueSet(t.map(|t| Box::new(t.to_owned(little_endian))))
+            }
+            #[cfg(feature = "audio")]
+            Self::Sound(b) => XAssetGeneric::Sound(b.map(|b| Box::new(b.to_owned()))),
+            #[cfg(feature = "models")]
+            Self::XModel(m) => {
+                XAssetGeneric::XModel(m.map(|m| Box::new(m.to_owned(little_endian))))
+            }
+            // owned assets are handled by `into_owned`
+            Self::Owned(_) => unreachable!(),
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
 pub enum XAssetGeneric<const MAX_LOCAL_CLIENTS: usize = 1> {
+    #[cfg(feature = "models")]
+    XModelPieces(Option<Box<XModelPieces>>),
+    #[cfg(feature = "models")]
     PhysPreset(Option<Box<PhysPreset>>),
+    #[cfg(feature = "models")]
     PhysConstraints(Option<Box<PhysConstraints>>),
+    #[cfg(feature = "world")]
     DestructibleDef(Option<Box<DestructibleDef>>),
+    #[cfg(feature = "models")]
     XAnimParts(Option<Box<XAnimParts>>),
+    #[cfg(feature = "models")]
     XModel(Option<Box<XModel>>),
     Material(Option<Box<Material>>),
     TechniqueSet(Option<Box<MaterialTechniqueSet>>),
     Image(Option<Box<GfxImage>>),
+    #[cfg(feature = "audio")]
     Sound(Option<Box<SndBank>>),
+    #[cfg(feature = "audio")]
     SoundPatch(Option<Box<SndPatch>>),
+    #[cfg(feature = "world")]
     ClipMap(Option<Box<ClipMap>>),
+    #[cfg(feature = "world")]
     ClipMapPVS(Option<Box<ClipMap>>),
+    #[cfg(feature = "world")]
     ComWorld(Option<Box<ComWorld>>),
+    #[cfg(feature = "world")]
     GameWorldSp(Option<Box<GameWorldSp>>),
+    #[cfg(feature = "world")]
     GameWorldMp(Option<Box<GameWorldMp>>),
     MapEnts(Option<Box<MapEnts>>),
+    #[cfg(feature = "world")]
     GfxWorld(Option<Box<GfxWorld<MAX_LOCAL_CLIENTS>>>),
+    #[cfg(feature = "world")]
     LightDef(Option<Box<GfxLightDef>>),
+    #[cfg(feature = "ui")]
     Font(Option<Box<Font>>),
+    #[cfg(feature = "ui")]
     MenuList(Option<Box<MenuList<MAX_LOCAL_CLIENTS>>>),
+    #[cfg(feature = "ui")]
     Menu(Option<Box<MenuDef<MAX_LOCAL_CLIENTS>>>),
     LocalizeEntry(Option<Box<LocalizeEntry>>),
+    #[cfg(feature = "fx")]
     Weapon(Option<Box<WeaponVariantDef>>),
+    #[cfg(feature = "audio")]
     SndDriverGlobals(Option<Box<SndDriverGlobals>>),
+    #[cfg(feature = "fx")]
     Fx(Option<Box<FxEffectDef>>),
+    #[cfg(feature = "fx")]
     ImpactFx(Option<Box<FxImpactTable>>),
     RawFile(Option<Box<RawFile>>),
     StringTable(Option<Box<StringTable>>),
     PackIndex(Option<Box<PackIndex>>),
     XGlobals(Option<Box<XGlobals>>),
     Ddl(Option<Box<DdlRoot>>),
+    #[cfg(feature = "world")]
     Glasses(Option<Box<Glasses>>),
     EmblemSet(Option<Box<EmblemSet>>),
+    /// An asset whose family's feature isn't enabled (see
+    /// [`XAssetType::family`]).
+    Blob(Box<XAssetBlob>),
 }
 
 impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
     pub fn is_some(&self) -> bool {
         match self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(p) => p.is_some(),
+            #[cfg(feature = "models")]
             Self::PhysPreset(p) => p.is_some(),
+            #[cfg(feature = "models")]
             Self::PhysConstraints(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::DestructibleDef(p) => p.is_some(),
+            #[cfg(feature = "models")]
             Self::XAnimParts(p) => p.is_some(),
+            #[cfg(feature = "models")]
             Self::XModel(p) => p.is_some(),
             Self::Material(p) => p.is_some(),
             Self::TechniqueSet(p) => p.is_some(),
             Self::Image(p) => p.is_some(),
+            #[cfg(feature = "audio")]
             Self::Sound(p) => p.is_some(),
+            #[cfg(feature = "audio")]
             Self::SoundPatch(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::ClipMap(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::ClipMapPVS(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::ComWorld(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::GameWorldSp(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::GameWorldMp(p) => p.is_some(),
             Self::MapEnts(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::GfxWorld(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::LightDef(p) => p.is_some(),
+            #[cfg(feature = "ui")]
             Self::Font(p) => p.is_some(),
+            #[cfg(feature = "ui")]
             Self::MenuList(p) => p.is_some(),
+            #[cfg(feature = "ui")]
             Self::Menu(p) => p.is_some(),
             Self::LocalizeEntry(p) => p.is_some(),
+            #[cfg(feature = "fx")]
             Self::Weapon(p) => p.is_some(),
+            #[cfg(feature = "audio")]
             Self::SndDriverGlobals(p) => p.is_some(),
+            #[cfg(feature = "fx")]
             Self::Fx(p) => p.is_some(),
+            #[cfg(feature = "fx")]
             Self::ImpactFx(p) => p.is_some(),
             Self::RawFile(p) => p.is_some(),
             Self::StringTable(p) => p.is_some(),
             Self::PackIndex(p) => p.is_some(),
             Self::XGlobals(p) => p.is_some(),
             Self::Ddl(p) => p.is_some(),
+            #[cfg(feature = "world")]
             Self::Glasses(p) => p.is_some(),
             Self::EmblemSet(p) => p.is_some(),
+            Self::Blob(_) => true,
         }
     }
 
@@ -177,85 +587,294 @@ impl<const MAX_LOCAL_CLIENTS: usize> XAssetGeneric<MAX_LOCAL_CLIENTS> {
         !self.is_some()
     }
 
+    /// Whether the asset is a [`Self::Blob`] that took up the rest of the
+    /// zone, so no assets after it can be deserialized.
+    pub fn ends_zone(&self) -> bool {
+        matches!(self, Self::Blob(blob) if !blob.data.is_empty())
+    }
+
     pub fn name(&self) -> Option<&str> {
         match self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "models")]
             Self::PhysPreset(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "models")]
             Self::PhysConstraints(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::DestructibleDef(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "models")]
             Self::XAnimParts(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "models")]
             Self::XModel(p) => p.as_ref().map(|p| p.name.get()),
             Self::Material(p) => p.as_ref().map(|p| p.info.name.get()),
             Self::TechniqueSet(p) => p.as_ref().map(|p| p.name.get()),
             Self::Image(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "audio")]
             Self::Sound(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "audio")]
             Self::SoundPatch(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::ClipMap(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::ClipMapPVS(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::ComWorld(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::GameWorldSp(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::GameWorldMp(p) => p.as_ref().map(|p| p.name.get()),
             Self::MapEnts(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::GfxWorld(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::LightDef(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "ui")]
             Self::Font(p) => p.as_ref().map(|p| p.font_name.get()),
+            #[cfg(feature = "ui")]
             Self::MenuList(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "ui")]
             Self::Menu(p) => p.as_ref().map(|p| p.window.name.get()),
             Self::LocalizeEntry(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "fx")]
             Self::Weapon(p) => p.as_ref().map(|p| p.internal_name.get()),
+            #[cfg(feature = "audio")]
             Self::SndDriverGlobals(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "fx")]
             Self::Fx(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "fx")]
             Self::ImpactFx(p) => p.as_ref().map(|p| p.name.get()),
             Self::RawFile(p) => p.as_ref().map(|p| p.name.get()),
             Self::StringTable(p) => p.as_ref().map(|p| p.name.get()),
             Self::PackIndex(p) => p.as_ref().map(|p| p.name.get()),
             Self::XGlobals(p) => p.as_ref().map(|p| p.name.get()),
             Self::Ddl(p) => p.as_ref().map(|p| p.name.get()),
+            #[cfg(feature = "world")]
             Self::Glasses(p) => p.as_ref().map(|p| p.name.get()),
             Self::EmblemSet(_) => Some("emblemset"),
+            Self::Blob(_) => None,
+        }
+    }
+
+    /// The asset's name, for renaming it. [`None`] for null assets, and for
+    /// emblem sets and blobs, whose names can't be changed.
+    pub fn name_mut(&mut self) -> Option<&mut XString> {
+        match self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "models")]
+            Self::PhysPreset(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "models")]
+            Self::PhysConstraints(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::DestructibleDef(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "models")]
+            Self::XAnimParts(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "models")]
+            Self::XModel(p) => p.as_mut().map(|p| &mut p.name),
+            Self::Material(p) => p.as_mut().map(|p| &mut p.info.name),
+            Self::TechniqueSet(p) => p.as_mut().map(|p| &mut p.name),
+            Self::Image(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "audio")]
+            Self::Sound(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "audio")]
+            Self::SoundPatch(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::ClipMap(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::ClipMapPVS(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::ComWorld(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::GameWorldSp(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::GameWorldMp(p) => p.as_mut().map(|p| &mut p.name),
+            Self::MapEnts(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::GfxWorld(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::LightDef(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "ui")]
+            Self::Font(p) => p.as_mut().map(|p| &mut p.font_name),
+            #[cfg(feature = "ui")]
+            Self::MenuList(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "ui")]
+            Self::Menu(p) => p.as_mut().map(|p| &mut p.window.name),
+            Self::LocalizeEntry(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "fx")]
+            Self::Weapon(p) => p.as_mut().map(|p| &mut p.internal_name),
+            #[cfg(feature = "audio")]
+            Self::SndDriverGlobals(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "fx")]
+            Self::Fx(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "fx")]
+            Self::ImpactFx(p) => p.as_mut().map(|p| &mut p.name),
+            Self::RawFile(p) => p.as_mut().map(|p| &mut p.name),
+            Self::StringTable(p) => p.as_mut().map(|p| &mut p.name),
+            Self::PackIndex(p) => p.as_mut().map(|p| &mut p.name),
+            Self::XGlobals(p) => p.as_mut().map(|p| &mut p.name),
+            Self::Ddl(p) => p.as_mut().map(|p| &mut p.name),
+            #[cfg(feature = "world")]
+            Self::Glasses(p) => p.as_mut().map(|p| &mut p.name),
+            Self::EmblemSet(_) => None,
+            Self::Blob(_) => None,
         }
     }
 
     pub fn asset_type(&self) -> XAssetType {
         match *self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(_) => XAssetType::XMODELPIECES,
+            #[cfg(feature = "models")]
             Self::PhysPreset(_) => XAssetType::PHYSPRESET,
+            #[cfg(feature = "models")]
             Self::PhysConstraints(_) => XAssetType::PHYSCONSTRAINTS,
+            #[cfg(feature = "world")]
             Self::DestructibleDef(_) => XAssetType::DESTRUCTIBLEDEF,
+            #[cfg(feature = "models")]
             Self::XAnimParts(_) => XAssetType::XANIMPARTS,
+            #[cfg(feature = "models")]
             Self::XModel(_) => XAssetType::XMODEL,
             Self::Material(_) => XAssetType::MATERIAL,
             Self::TechniqueSet(_) => XAssetType::TECHNIQUE_SET,
             Self::Image(_) => XAssetType::IMAGE,
+            #[cfg(feature = "audio")]
             Self::Sound(_) => XAssetType::SOUND,
-            Self::SoundPatch(_) => XAssetType::SOUND,
+            #[cfg(feature = "audio")]
+            Self::SoundPatch(_) => XAssetType::SOUND_PATCH,
+            #[cfg(feature = "world")]
             Self::ClipMap(_) => XAssetType::CLIPMAP,
+            #[cfg(feature = "world")]
             Self::ClipMapPVS(_) => XAssetType::CLIPMAP_PVS,
+            #[cfg(feature = "world")]
             Self::ComWorld(_) => XAssetType::COMWORLD,
+            #[cfg(feature = "world")]
             Self::GameWorldSp(_) => XAssetType::GAMEWORLD_SP,
+            #[cfg(feature = "world")]
             Self::GameWorldMp(_) => XAssetType::GAMEWORLD_MP,
             Self::MapEnts(_) => XAssetType::MAP_ENTS,
+            #[cfg(feature = "world")]
             Self::GfxWorld(_) => XAssetType::GFXWORLD,
+            #[cfg(feature = "world")]
             Self::LightDef(_) => XAssetType::LIGHT_DEF,
+            #[cfg(feature = "ui")]
             Self::Font(_) => XAssetType::FONT,
+            #[cfg(feature = "ui")]
             Self::MenuList(_) => XAssetType::MENULIST,
+            #[cfg(feature = "ui")]
             Self::Menu(_) => XAssetType::MENU,
             Self::LocalizeEntry(_) => XAssetType::LOCALIZE_ENTRY,
+            #[cfg(feature = "fx")]
             Self::Weapon(_) => XAssetType::WEAPON,
+            #[cfg(feature = "audio")]
             Self::SndDriverGlobals(_) => XAssetType::SNDDRIVER_GLOBALS,
+            #[cfg(feature = "fx")]
             Self::Fx(_) => XAssetType::FX,
+            #[cfg(feature = "fx")]
             Self::ImpactFx(_) => XAssetType::IMPACT_FX,
             Self::RawFile(_) => XAssetType::RAWFILE,
             Self::StringTable(_) => XAssetType::STRINGTABLE,
             Self::PackIndex(_) => XAssetType::PACKINDEX,
             Self::XGlobals(_) => XAssetType::XGLOBALS,
             Self::Ddl(_) => XAssetType::DDL,
+            #[cfg(feature = "world")]
             Self::Glasses(_) => XAssetType::GLASSES,
             Self::EmblemSet(_) => XAssetType::EMBLEMSET,
+            Self::Blob(ref blob) => blob.asset_type,
+        }
+    }
+
+    /// The type and name of every asset the asset references directly, in
+    /// the order they're referenced, without duplicates.
+    ///
+    /// Currently only the references of materials (to their technique set
+    /// and images), models (to their materials and physics), weapons (see
+    /// [`WeaponVariantDef::dependencies`]), and render worlds and entities
+    /// (to their map's loadscreen and globals, which are found by name; see
+    /// [`map_name`]) are known; every other type of asset has none.
+    pub fn dependencies(&self) -> Vec<(XAssetType, String)> {
+        let mut deps = Vec::new();
+        let mut push = |asset_type, name: &XString| {
+            let dep = (asset_type, name.get().to_string());
+            if !deps.contains(&dep) {
+                deps.push(dep);
+            }
+        };
+
+        match self {
+            Self::Material(Some(material)) => {
+                if let Some(techset) = &material.technique_set {
+                    push(XAssetType::TECHNIQUE_SET, &techset.name);
+                }
+                for image in material.images() {
+                    push(XAssetType::IMAGE, &image.name);
+                }
+            }
+            #[cfg(feature = "models")]
+            Self::XModel(Some(model)) => {
+                for material in model.materials() {
+                    push(XAssetType::MATERIAL, &material.info.name);
+                }
+                if let Some(preset) = &model.phys_preset {
+                    push(XAssetType::PHYSPRESET, &preset.name);
+                }
+                if let Some(constraints) = &model.phys_constraints {
+                    push(XAssetType::PHYSCONSTRAINTS, &constraints.name);
+                }
+            }
+            #[cfg(feature = "fx")]
+            Self::Weapon(Some(weapon)) => {
+                for (asset_type, name) in weapon.dependencies().assets {
+                    push(asset_type, &XString(name));
+                }
+            }
+            #[cfg(feature = "world")]
+            Self::GfxWorld(Some(world)) => push_map(&mut push, world.name.get()),
+            Self::MapEnts(Some(map_ents)) => push_map(&mut push, map_ents.name.get()),
+            _ => {}
         }
+
+        deps
     }
 }
 
+/// The name of the map a world asset named `bsp_name` belongs to, which is
+/// the name of the map's other assets, e.g. `mp_array` for
+/// `maps/mp/mp_array.d3dbsp`.
+pub fn map_name(bsp_name: &str) -> &str {
+    let name = bsp_name.rsplit('/').next().unwrap_or(bsp_name);
+    name.strip_suffix(".d3dbsp").unwrap_or(name)
+}
+
+/// The name of the image shown while `map` loads.
+pub fn loadscreen_name(map: &str) -> String {
+    format!("loadscreen_{map}")
+}
+
+/// Pushes the loadscreen and globals of the map a world asset named
+/// `bsp_name` belongs to.
+fn push_map(push: &mut impl FnMut(XAssetType, &XString), bsp_name: &str) {
+    let map = map_name(bsp_name);
+    push(XAssetType::IMAGE, &XString(loadscreen_name(map)));
+    push(XAssetType::XGLOBALS, &XString(map.to_string()));
+}
+
+/// The names the world assets of `map` can have: the singleplayer one, then
+/// the multiplayer one.
+fn bsp_names(map: &str) -> [String; 2] {
+    [
+        format!("maps/{map}.d3dbsp"),
+        format!("maps/mp/{map}.d3dbsp"),
+    ]
+}
+
+// The `*Raw` types and the functions that take them are only public so the
+// deserializer and serializer can read and write the asset list. They're
+// the Fastfile's layout rather than part of the API, so they're hidden.
+
+#[doc(hidden)]
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct XAssetListRaw<'a> {
     pub strings: FatPointerCountFirstU32<'a, XStringRaw<'a>>,
     pub assets: FatPointerCountFirstU32<'a, XAssetRaw<'a>>,
@@ -268,14 +887,18 @@ pub struct XAssetList {
     pub assets: Vec<XAsset>,
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+// Unlike the other raw structs, this one is persisted in asset caches, so it
+// has to be deserializable too.
+#[doc(hidden)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub struct XAssetRaw<'a> {
     pub asset_type: u32,
     pub asset_data: Ptr32<'a, ()>,
 }
 assert_size!(XAssetRaw, 8);
 
+#[cfg(feature = "serde")]
 impl<'a> XFileSerialize<()> for XAssetList {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let script_strings = ser.script_strings();
@@ -307,9 +930,208 @@ impl<'a> XFileSerialize<()> for XAssetList {
     }
 }
 
+/// A collection of [`XAsset`]s keyed by type and name.
+///
+/// Mirrors how the engine tracks loaded assets: there can only be one asset
+/// of a given type with a given name, and inserting another one replaces the
+/// existing one (see [`XAssetBag::insert`]). Null assets have no name, so
+/// they're never stored.
+///
+/// With the `hash` feature, a bag's
+/// [`StableHash`](crate::hash::StableHash) covers its assets in order, so
+/// two bags only hash the same if their assets were first inserted in the
+/// same order.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, Default)]
+pub struct XAssetBag {
+    assets: Vec<XAsset>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    index: BTreeMap<(XAssetType, String), usize>,
+}
+
+// so a loaded zone can be shared between threads. the Direct3D objects
+// created with the `d3d9` feature aren't thread-safe, so it only holds
+// without it.
+#[cfg(not(feature = "d3d9"))]
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<XAssetBag>();
+};
+
+impl XAssetBag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts `asset`, replacing any existing asset with the same type and
+    /// name.
+    ///
+    /// A replaced asset keeps its position in the bag, so iteration order is
+    /// the order in which each (type, name) pair was first seen.
+    ///
+    /// Returns the replaced asset, if any. Null assets are discarded and
+    /// [`None`] is returned.
+    pub fn insert(&mut self, asset: XAsset) -> Option<XAsset> {
+        let name = asset.name()?.to_string();
+        let key = (asset.asset_type(), name);
+
+        if let Some(&i) = self.index.get(&key) {
+            Some(core::mem::replace(&mut self.assets[i], asset))
+        } else {
+            self.index.insert(key, self.assets.len());
+            self.assets.push(asset);
+            None
+        }
+    }
+
+    pub fn get(&self, asset_type: XAssetType, name: &str) -> Option<&XAsset> {
+        self.index
+            .get(&(asset_type, name.to_string()))
+            .map(|&i| &self.assets[i])
+    }
+
+    pub fn contains(&self, asset_type: XAssetType, name: &str) -> bool {
+        self.get(asset_type, name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &XAsset> {
+        self.assets.iter()
+    }
+
+    /// Returns all assets of type `asset_type`.
+    pub fn iter_type(&self, asset_type: XAssetType) -> impl Iterator<Item = &XAsset> {
+        self.assets
+            .iter()
+            .filter(move |a| a.asset_type() == asset_type)
+    }
+
+    pub fn as_slice(&self) -> &[XAsset] {
+        &self.assets
+    }
+
+    pub fn into_vec(self) -> Vec<XAsset> {
+        self.assets
+    }
+
+    /// Counts the assets in the bag by type, estimates their sizes, and
+    /// totals up their geometry and sound samples (see [`XAssetStats`]).
+    pub fn stats(&self) -> XAssetStats {
+        XAssetStats::from_assets(&self.assets)
+    }
+
+    /// The assets in `roots`, along with every asset in the bag they depend
+    /// on, directly or indirectly (see [`XAsset::dependencies`]).
+    ///
+    /// Every asset comes after the assets it depends on, which is the order
+    /// the engine expects them to be loaded in. Roots and dependencies that
+    /// aren't in the bag are skipped.
+    pub fn closure<'a>(&self, roots: impl IntoIterator<Item = (XAssetType, &'a str)>) -> XAssetBag {
+        let mut visited = vec![false; self.assets.len()];
+        let mut closure = XAssetBag::new();
+        for (asset_type, name) in roots {
+            if let Some(&i) = self.index.get(&(asset_type, name.to_string())) {
+                self.visit(i, &mut visited, &mut closure);
+            }
+        }
+        closure
+    }
+
+    /// Every asset of `map` (e.g. `mp_array`) in the bag: its world assets
+    /// (collision, lights, game world, entities, and render world), its
+    /// loadscreen and globals, and everything they depend on, in the order of
+    /// [`Self::closure`].
+    pub fn map(&self, map: &str) -> XAssetBag {
+        const WORLD_TYPES: [XAssetType; 7] = [
+            XAssetType::CLIPMAP,
+            XAssetType::CLIPMAP_PVS,
+            XAssetType::COMWORLD,
+            XAssetType::GAMEWORLD_SP,
+            XAssetType::GAMEWORLD_MP,
+            XAssetType::MAP_ENTS,
+            XAssetType::GFXWORLD,
+        ];
+
+        let bsp_names = bsp_names(map);
+        let loadscreen = loadscreen_name(map);
+        let roots = bsp_names
+            .iter()
+            .flat_map(|name| WORLD_TYPES.map(|t| (t, name.as_str())))
+            .chain([
+                (XAssetType::IMAGE, loadscreen.as_str()),
+                (XAssetType::XGLOBALS, map),
+            ]);
+        self.closure(roots)
+    }
+
+    /// The entities of `map` (e.g. `mp_array`), if they're in the bag.
+    pub fn map_ents(&self, map: &str) -> Option<&MapEnts> {
+        bsp_names(map)
+            .iter()
+            .find_map(|name| self.get(XAssetType::MAP_ENTS, name)?.map_ents())
+    }
+
+    /// The loadscreen of `map` (e.g. `mp_array`), if it's in the bag.
+    pub fn loadscreen(&self, map: &str) -> Option<&GfxImage> {
+        self.get(XAssetType::IMAGE, &loadscreen_name(map))?.image()
+    }
+
+    /// The globals of `map` (e.g. `mp_array`), if they're in the bag.
+    pub fn xglobals(&self, map: &str) -> Option<&XGlobals> {
+        self.get(XAssetType::XGLOBALS, map)?.xglobals()
+    }
+
+    fn visit(&self, i: usize, visited: &mut [bool], closure: &mut XAssetBag) {
+        if visited[i] {
+            return;
+        }
+        visited[i] = true;
+
+        for key in self.assets[i].dependencies() {
+            if let Some(&dep) = self.index.get(&key) {
+                self.visit(dep, visited, closure);
+            }
+        }
+        closure.insert(self.assets[i].clone());
+    }
+}
+
+impl Extend<XAsset> for XAssetBag {
+    fn extend<T: IntoIterator<Item = XAsset>>(&mut self, iter: T) {
+        for asset in iter {
+            self.insert(asset);
+        }
+    }
+}
+
+impl FromIterator<XAsset> for XAssetBag {
+    fn from_iter<T: IntoIterator<Item = XAsset>>(iter: T) -> Self {
+        let mut bag = Self::new();
+        bag.extend(iter);
+        bag
+    }
+}
+
+impl IntoIterator for XAssetBag {
+    type Item = XAsset;
+    type IntoIter = alloc::vec::IntoIter<XAsset>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.assets.into_iter()
+    }
+}
+
 /// T5 doesn't actually use all of these.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive)]
 #[repr(u32)]
 pub enum XAssetType {
     #[default]
@@ -360,6 +1182,294 @@ pub enum XAssetType {
     ASSETLIST = 0x2C,
 }
 
+/// The contents of an asset whose family's feature isn't enabled, so its
+/// layout isn't compiled in (see [`XAssetGeneric::Blob`]).
+///
+/// Since there's no telling where such an asset ends, `data` is everything
+/// from the asset to the end of the zone (or nothing, if the asset isn't
+/// stored inline). In the former case, no assets after it can be
+/// deserialized, and the deserializer stops there (see
+/// [`XAssetGeneric::ends_zone`]).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct XAssetBlob {
+    pub asset_type: XAssetType,
+    pub data: Vec<u8>,
+}
+
+/// A group of asset types that can be left out of the build by disabling
+/// its cargo feature. Every family is enabled by default.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum XAssetFamily {
+    /// Collision, render and game worlds, lights, glass, and destructibles.
+    /// Needs [`Self::Models`] and [`Self::Fx`].
+    World,
+    /// Models, their physics, and animations.
+    Models,
+    /// Menus and fonts.
+    Ui,
+    /// Sound banks, patches, and driver globals.
+    Audio,
+    /// Effects and impact effects, and weapons, which use them. Needs
+    /// [`Self::Models`].
+    Fx,
+}
+
+impl XAssetFamily {
+    pub const ALL: [Self; 5] = [Self::World, Self::Models, Self::Ui, Self::Audio, Self::Fx];
+
+    /// The name of the family's cargo feature.
+    pub const fn feature(self) -> &'static str {
+        match self {
+            Self::World => "world",
+            Self::Models => "models",
+            Self::Ui => "ui",
+            Self::Audio => "audio",
+            Self::Fx => "fx",
+        }
+    }
+
+    /// Whether the family's feature is enabled.
+    pub const fn is_enabled(self) -> bool {
+        match self {
+            Self::World => cfg!(feature = "world"),
+            Self::Models => cfg!(feature = "models"),
+            Self::Ui => cfg!(feature = "ui"),
+            Self::Audio => cfg!(feature = "audio"),
+            Self::Fx => cfg!(feature = "fx"),
+        }
+    }
+}
+
+/// How much of an [`XAssetType`] this library supports.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SupportStatus {
+    /// Neither deserialization nor serialization is implemented. Assets of
+    /// this type fail with [`ErrorKind::UnusedXAssetType`].
+    None,
+    /// Assets of this type can be deserialized, but not serialized.
+    Deserialize,
+    /// Assets of this type can be both deserialized and serialized.
+    Serialize,
+}
+
+impl SupportStatus {
+    pub const fn can_deserialize(self) -> bool {
+        !matches!(self, Self::None)
+    }
+
+    pub const fn can_serialize(self) -> bool {
+        matches!(self, Self::Serialize)
+    }
+}
+
+impl core::fmt::Display for SupportStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::None => "none",
+            Self::Deserialize => "deserialize",
+            Self::Serialize => "deserialize + serialize",
+        })
+    }
+}
+
+impl XAssetType {
+    /// Every variant, in order.
+    pub const ALL: [Self; 45] = [
+        Self::XMODELPIECES,
+        Self::PHYSPRESET,
+        Self::PHYSCONSTRAINTS,
+        Self::DESTRUCTIBLEDEF,
+        Self::XANIMPARTS,
+        Self::XMODEL,
+        Self::MATERIAL,
+        Self::TECHNIQUE_SET,
+        Self::IMAGE,
+        Self::SOUND,
+        Self::SOUND_PATCH,
+        Self::CLIPMAP,
+        Self::CLIPMAP_PVS,
+        Self::COMWORLD,
+        Self::GAMEWORLD_SP,
+        Self::GAMEWORLD_MP,
+        Self::MAP_ENTS,
+        Self::GFXWORLD,
+        Self::LIGHT_DEF,
+        Self::UI_MAP,
+        Self::FONT,
+        Self::MENULIST,
+        Self::MENU,
+        Self::LOCALIZE_ENTRY,
+        Self::WEAPON,
+        Self::WEAPONDEF,
+        Self::WEAPON_VARIANT,
+        Self::SNDDRIVER_GLOBALS,
+        Self::FX,
+        Self::IMPACT_FX,
+        Self::AITYPE,
+        Self::MPTYPE,
+        Self::MPBODY,
+        Self::MPHEAD,
+        Self::CHARACTER,
+        Self::XMODELALIAS,
+        Self::RAWFILE,
+        Self::STRINGTABLE,
+        Self::PACKINDEX,
+        Self::XGLOBALS,
+        Self::DDL,
+        Self::GLASSES,
+        Self::EMBLEMSET,
+        Self::STRING,
+        Self::ASSETLIST,
+    ];
+
+    /// The name the mod tools use for this asset type, as in the first
+    /// column of a zone source file.
+    pub const fn zone_source_name(self) -> &'static str {
+        match self {
+            Self::XMODELPIECES => "xmodelpieces",
+            Self::PHYSPRESET => "physpreset",
+            Self::PHYSCONSTRAINTS => "physconstraints",
+            Self::DESTRUCTIBLEDEF => "destructibledef",
+            Self::XANIMPARTS => "xanim",
+            Self::XMODEL => "xmodel",
+            Self::MATERIAL => "material",
+            Self::TECHNIQUE_SET => "techniqueset",
+            Self::IMAGE => "image",
+            Self::SOUND => "sound",
+            Self::SOUND_PATCH => "soundpatch",
+            Self::CLIPMAP => "clipmap",
+            Self::CLIPMAP_PVS => "clipmap_pvs",
+            Self::COMWORLD => "comworld",
+            Self::GAMEWORLD_SP => "gameworldsp",
+            Self::GAMEWORLD_MP => "gameworldmp",
+            Self::MAP_ENTS => "mapents",
+            Self::GFXWORLD => "gfxworld",
+            Self::LIGHT_DEF => "lightdef",
+            Self::UI_MAP => "uimap",
+            Self::FONT => "font",
+            Self::MENULIST => "menufile",
+            Self::MENU => "menu",
+            Self::LOCALIZE_ENTRY => "localize",
+            Self::WEAPON => "weapon",
+            Self::WEAPONDEF => "weapondef",
+            Self::WEAPON_VARIANT => "weaponvariant",
+            Self::SNDDRIVER_GLOBALS => "snddriverglobals",
+            Self::FX => "fx",
+            Self::IMPACT_FX => "impactfx",
+            Self::AITYPE => "aitype",
+            Self::MPTYPE => "mptype",
+            Self::MPBODY => "mpbody",
+            Self::MPHEAD => "mphead",
+            Self::CHARACTER => "character",
+            Self::XMODELALIAS => "xmodelalias",
+            Self::RAWFILE => "rawfile",
+            Self::STRINGTABLE => "stringtable",
+            Self::PACKINDEX => "packindex",
+            Self::XGLOBALS => "xglobals",
+            Self::DDL => "ddl",
+            Self::GLASSES => "glasses",
+            Self::EMBLEMSET => "emblemset",
+            Self::STRING => "string",
+            Self::ASSETLIST => "assetlist",
+        }
+    }
+
+    /// The family this asset type belongs to, or [`None`] if it's always
+    /// compiled in.
+    pub const fn family(self) -> Option<XAssetFamily> {
+        match self {
+            Self::XMODELPIECES
+            | Self::PHYSPRESET
+            | Self::PHYSCONSTRAINTS
+            | Self::XANIMPARTS
+            | Self::XMODEL => Some(XAssetFamily::Models),
+            Self::DESTRUCTIBLEDEF
+            | Self::CLIPMAP
+            | Self::CLIPMAP_PVS
+            | Self::COMWORLD
+            | Self::GAMEWORLD_SP
+            | Self::GAMEWORLD_MP
+            | Self::GFXWORLD
+            | Self::LIGHT_DEF
+            | Self::GLASSES => Some(XAssetFamily::World),
+            Self::SOUND | Self::SOUND_PATCH | Self::SNDDRIVER_GLOBALS => Some(XAssetFamily::Audio),
+            Self::FONT | Self::MENULIST | Self::MENU => Some(XAssetFamily::Ui),
+            Self::FX | Self::IMPACT_FX | Self::WEAPON => Some(XAssetFamily::Fx),
+            _ => None,
+        }
+    }
+
+    /// Whether assets of this type are compiled in, i.e. it has no family
+    /// or its family's feature is enabled. Assets of other types are
+    /// deserialized as [`XAssetGeneric::Blob`]s.
+    pub const fn is_enabled(self) -> bool {
+        match self.family() {
+            Some(family) => family.is_enabled(),
+            None => true,
+        }
+    }
+
+    /// Returns how much of this asset type is supported.
+    ///
+    /// Must be kept in sync with the deserialization and serialization of
+    /// [`XAssetGeneric`].
+    pub const fn support_status(self) -> SupportStatus {
+        match self {
+            Self::XMODELPIECES
+            | Self::PHYSPRESET
+            | Self::PHYSCONSTRAINTS
+            | Self::DESTRUCTIBLEDEF
+            | Self::XANIMPARTS
+            | Self::XMODEL
+            | Self::MATERIAL
+            | Self::TECHNIQUE_SET
+            | Self::IMAGE
+            | Self::SOUND
+            | Self::SOUND_PATCH
+            | Self::COMWORLD
+            | Self::GAMEWORLD_SP
+            | Self::GAMEWORLD_MP
+            | Self::MAP_ENTS
+            | Self::LIGHT_DEF
+            | Self::FONT
+            | Self::LOCALIZE_ENTRY
+            | Self::SNDDRIVER_GLOBALS
+            | Self::FX
+            | Self::IMPACT_FX
+            | Self::RAWFILE
+            | Self::STRINGTABLE
+            | Self::PACKINDEX
+            | Self::XGLOBALS
+            | Self::DDL
+            | Self::GLASSES
+            | Self::EMBLEMSET => SupportStatus::Serialize,
+            Self::CLIPMAP
+            | Self::CLIPMAP_PVS
+            | Self::GFXWORLD
+            | Self::MENULIST
+            | Self::MENU
+            | Self::WEAPON => SupportStatus::Deserialize,
+            // none of these occur in retail zones (they're either only used
+            // by the tools or not used at all)
+            Self::UI_MAP
+            | Self::WEAPONDEF
+            | Self::WEAPON_VARIANT
+            | Self::AITYPE
+            | Self::MPTYPE
+            | Self::MPBODY
+            | Self::MPHEAD
+            | Self::CHARACTER
+            | Self::XMODELALIAS
+            | Self::STRING
+            | Self::ASSETLIST => SupportStatus::None,
+        }
+    }
+}
+
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_LOCAL_CLIENTS>, ()>
     for XAssetRaw<'a>
 {
@@ -377,26 +1487,37 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
             ))?;
         //println!("type={:?} ({})", asset_type, self.asset_type);
         Ok(match asset_type {
+            #[cfg(feature = "models")]
+            XAssetType::XMODELPIECES => XAssetGeneric::XModelPieces(
+                self.asset_data
+                    .cast::<XModelPiecesRaw>()
+                    .xfile_deserialize_into(de, ())?,
+            ),
+            #[cfg(feature = "models")]
             XAssetType::PHYSPRESET => XAssetGeneric::PhysPreset(
                 self.asset_data
                     .cast::<PhysPresetRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "models")]
             XAssetType::PHYSCONSTRAINTS => XAssetGeneric::PhysConstraints(
                 self.asset_data
                     .cast::<PhysConstraintsRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::DESTRUCTIBLEDEF => XAssetGeneric::DestructibleDef(
                 self.asset_data
                     .cast::<DestructibleDefRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "models")]
             XAssetType::XANIMPARTS => XAssetGeneric::XAnimParts(
                 self.asset_data
                     .cast::<XAnimPartsRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "models")]
             XAssetType::XMODEL => XAssetGeneric::XModel(
                 self.asset_data
                     .cast::<XModelRaw>()
@@ -417,36 +1538,43 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<GfxImageRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "audio")]
             XAssetType::SOUND => XAssetGeneric::Sound(
                 self.asset_data
                     .cast::<SndBankRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "audio")]
             XAssetType::SOUND_PATCH => XAssetGeneric::SoundPatch(
                 self.asset_data
                     .cast::<SndPatchRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::CLIPMAP => XAssetGeneric::ClipMap(
                 self.asset_data
                     .cast::<ClipMapRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::CLIPMAP_PVS => XAssetGeneric::ClipMapPVS(
                 self.asset_data
                     .cast::<ClipMapRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::COMWORLD => XAssetGeneric::ComWorld(
                 self.asset_data
                     .cast::<ComWorldRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::GAMEWORLD_SP => XAssetGeneric::GameWorldSp(
                 self.asset_data
                     .cast::<GameWorldSpRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::GAMEWORLD_MP => XAssetGeneric::GameWorldMp(
                 self.asset_data
                     .cast::<GameWorldMpRaw>()
@@ -457,26 +1585,31 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<MapEntsRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::GFXWORLD => XAssetGeneric::GfxWorld(
                 self.asset_data
                     .cast::<GfxWorldRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::LIGHT_DEF => XAssetGeneric::LightDef(
                 self.asset_data
                     .cast::<GfxLightDefRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "ui")]
             XAssetType::FONT => XAssetGeneric::Font(
                 self.asset_data
                     .cast::<FontRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "ui")]
             XAssetType::MENULIST => XAssetGeneric::MenuList(
                 self.asset_data
                     .cast::<MenuListRaw<MAX_LOCAL_CLIENTS>>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "ui")]
             XAssetType::MENU => XAssetGeneric::Menu(
                 self.asset_data
                     .cast::<MenuDefRaw<MAX_LOCAL_CLIENTS>>()
@@ -487,21 +1620,25 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<LocalizeEntryRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "fx")]
             XAssetType::WEAPON => XAssetGeneric::Weapon(
                 self.asset_data
                     .cast::<WeaponVariantDefRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "audio")]
             XAssetType::SNDDRIVER_GLOBALS => XAssetGeneric::SndDriverGlobals(
                 self.asset_data
                     .cast::<SndDriverGlobalsRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "fx")]
             XAssetType::FX => XAssetGeneric::Fx(
                 self.asset_data
                     .cast::<FxEffectDefRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "fx")]
             XAssetType::IMPACT_FX => XAssetGeneric::ImpactFx(
                 self.asset_data
                     .cast::<FxImpactTableRaw>()
@@ -532,6 +1669,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<DdlRootRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            #[cfg(feature = "world")]
             XAssetType::GLASSES => XAssetGeneric::Glasses(
                 self.asset_data
                     .cast::<GlassesRaw>()
@@ -542,6 +1680,17 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
                     .cast::<EmblemSetRaw>()
                     .xfile_deserialize_into(de, ())?,
             ),
+            asset_type if !asset_type.is_enabled() => {
+                // without the asset's layout, there's no telling where it
+                // ends, so the blob is everything left
+                let data = if self.asset_data.is_null() || self.asset_data.is_real() {
+                    Vec::new()
+                } else {
+                    let len = de.stream_len()? - de.stream_pos()?;
+                    de.load_bytes(len as _)?
+                };
+                XAssetGeneric::Blob(Box::new(XAssetBlob { asset_type, data }))
+            }
             _ => {
                 //dbg!(asset_type);
                 return Err(Error::new_with_offset(
@@ -554,10 +1703,26 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileDeserializeInto<XAssetGeneric<MAX_
     }
 }
 
+#[cfg(feature = "serde")]
 impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MAX_LOCAL_CLIENTS> {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        if let Self::Blob(blob) = self {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo(format!(
+                    "serializing {:?} assets without the `{}` feature",
+                    blob.asset_type,
+                    blob.asset_type.family().map_or("", XAssetFamily::feature),
+                )),
+            ));
+        }
+
         let asset_type = self.asset_type() as _;
-        let asset_data = Ptr32::unreal();
+        let asset_data = if self.is_some() {
+            Ptr32::unreal()
+        } else {
+            Ptr32::null()
+        };
 
         let asset = XAssetRaw {
             asset_type,
@@ -566,6 +1731,15 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
 
         ser.store_into_xfile(asset)?;
         match self {
+            #[cfg(feature = "models")]
+            Self::XModelPieces(p) => {
+                if let Some(p) = p {
+                    p.xfile_serialize(ser, ())
+                } else {
+                    Ok(())
+                }
+            }
+            #[cfg(feature = "models")]
             Self::PhysPreset(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -573,6 +1747,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "models")]
             Self::PhysConstraints(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -580,6 +1755,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "world")]
             Self::DestructibleDef(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -587,6 +1763,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "models")]
             Self::XAnimParts(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -594,6 +1771,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "models")]
             Self::XModel(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -622,6 +1800,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "audio")]
             Self::Sound(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -629,6 +1808,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "audio")]
             Self::SoundPatch(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -638,6 +1818,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
             }
             // Self::ClipMap(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
             // Self::ClipMapPVS(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            #[cfg(feature = "world")]
             Self::ComWorld(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -645,6 +1826,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "world")]
             Self::GameWorldSp(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -652,6 +1834,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "world")]
             Self::GameWorldMp(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -667,6 +1850,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                 }
             }
             // Self::GfxWorld(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            #[cfg(feature = "world")]
             Self::LightDef(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -674,6 +1858,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "ui")]
             Self::Font(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -691,6 +1876,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                 }
             }
             // Self::Weapon(p) => if let Some(p) = p { p.xfile_serialize(ser, ()) } else { Ok(()) },
+            #[cfg(feature = "audio")]
             Self::SndDriverGlobals(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -698,6 +1884,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "fx")]
             Self::Fx(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -705,6 +1892,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "fx")]
             Self::ImpactFx(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -747,6 +1935,7 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
+            #[cfg(feature = "world")]
             Self::Glasses(p) => {
                 if let Some(p) = p {
                     p.xfile_serialize(ser, ())
@@ -761,7 +1950,10 @@ impl<'a, const MAX_LOCAL_CLIENTS: usize> XFileSerialize<()> for XAssetGeneric<MA
                     Ok(())
                 }
             }
-            _ => todo!(),
+            _ => Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Todo(format!("serializing {:?} assets", self.asset_type())),
+            )),
         }
     }
 }
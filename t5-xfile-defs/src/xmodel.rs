@@ -1,23 +1,28 @@
-use alloc::{boxed::Box, format, vec::Vec};
+use core::ops::Range;
+
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 use bitflags::bitflags;
 use num::FromPrimitive;
 use num_derive::FromPrimitive;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
 use crate::{
-    Error, ErrorKind, FatPointer, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result,
-    ScriptString, T5XFileDeserialize, T5XFileSerialize, XFileDeserializeInto, XFileSerialize,
-    XString, XStringRaw, assert_size,
-    common::{GfxIndexBuffer, GfxVertexBuffer, Mat3, Vec3, Vec4},
-    file_line_col,
+    Error, ErrorKind, FatPointerCountFirstU32, FatPointerCountLastU32, Ptr32, Result, ScriptString,
+    Shared, T5XFileDeserialize, T5XFileDeserializeBorrowed, XArray, XFileDeserializeBorrowed,
+    XFileDeserializeInto, XFilePlatform, XFileRead, XString, XStringRaw, assert_size,
+    common::{GfxIndexBuffer, GfxVertexBuffer, Mat3, Vec2, Vec3, Vec4},
+    file_line_col, read, size_of,
     techset::{Material, MaterialRaw},
 };
+#[cfg(feature = "serde")]
+use crate::{FatPointer, T5XFileSerialize, XFileSerialize};
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelRaw<'a> {
     pub name: XStringRaw<'a>,
     pub num_bones: u8,
@@ -57,7 +62,8 @@ pub(crate) struct XModelRaw<'a> {
 assert_size!(XModelRaw, 252);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(u8)]
 pub enum XModelLodRampType {
     #[default]
@@ -69,7 +75,8 @@ pub enum XModelLodRampType {
 pub const MAX_LODS: usize = 4;
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModel {
     pub name: XString,
     pub num_bones: usize,
@@ -83,7 +90,7 @@ pub struct XModel {
     pub part_classification: Vec<u8>,
     pub base_mat: Vec<DObjAnimMat>,
     pub surfs: Vec<XSurface>,
-    pub material_handles: Vec<Box<Material>>,
+    pub material_handles: Vec<Shared<Material>>,
     pub lod_info: [XModelLodInfo; MAX_LODS],
     pub load_dist_auto_generated: u8,
     pub coll_surfs: Vec<XModelCollSurf>,
@@ -103,12 +110,14 @@ pub struct XModel {
     pub phys_constraints: Option<Box<PhysConstraints>>,
 }
 
-impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
-    fn xfile_deserialize_into(
+impl<'a> XModelRaw<'a> {
+    /// Deserializes everything but the surfaces, which `surfs` deserializes
+    /// when it's their turn. [`XModel::surfs`] is left empty.
+    fn deserialize_with<D: T5XFileDeserialize, S>(
         &self,
-        de: &mut impl T5XFileDeserialize,
-        _data: (),
-    ) -> Result<XModel> {
+        de: &mut D,
+        surfs: impl FnOnce(&mut D) -> Result<S>,
+    ) -> Result<(XModel, S)> {
         //dbg!(self);
         //dbg!(xfile.stream_position()?);
 
@@ -157,7 +166,7 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
         let parent_list = self
             .parent_list
             .to_array(self.num_bones as usize - self.num_root_bones as usize)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         //dbg!(&parent_list);
         //dbg!(de.stream_pos()?);
         let quats = self
@@ -169,13 +178,13 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
         let trans = self
             .trans
             .to_array((self.num_bones as usize - self.num_root_bones as usize) * 4)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         //dbg!(&trans);
         //dbg!(de.stream_pos()?);
         let part_classification = self
             .part_classification
             .to_array(self.num_bones as _)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
         //dbg!(&part_classification);
         //dbg!(de.stream_pos()?);
         let base_mat = self
@@ -184,19 +193,13 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
             .to_vec_into(de)?;
         //dbg!(&base_mat);
         //dbg!(de.stream_pos()?);
-        let surfs = self
-            .surfs
-            .to_array(self.numsurfs as _)
-            .xfile_deserialize_into(de, ())?;
-        //dbg!(&surfs);
+        let surfs = surfs(de)?;
         //dbg!(de.stream_pos()?);
-        let material_handles = self
+        let material_handles: Vec<Option<Shared<_>>> = self
             .material_handles
             .to_array(self.numsurfs as _)
-            .xfile_deserialize_into(de, ())?
-            .into_iter()
-            .flatten()
-            .collect();
+            .xfile_deserialize_into(de, ())?;
+        let material_handles = material_handles.into_iter().flatten().collect();
         //dbg!(&material_handles);
         //dbg!(de.stream_pos()?);
         let lod_info = [
@@ -250,7 +253,7 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
         //dbg!(&phys_constraints);
         //dbg!(de.stream_pos()?);
 
-        Ok(XModel {
+        let model = XModel {
             name,
             num_bones: self.num_bones as _,
             num_root_bones: self.num_root_bones as _,
@@ -262,7 +265,7 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
             trans,
             part_classification,
             base_mat,
-            surfs,
+            surfs: Vec::new(),
             material_handles,
             lod_info,
             load_dist_auto_generated: self.load_dist_auto_generated,
@@ -281,12 +284,188 @@ impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
             phys_preset,
             collmaps,
             phys_constraints,
-        })
+        };
+
+        Ok((model, surfs))
+    }
+}
+
+impl<'a> XFileDeserializeInto<XModel, ()> for XModelRaw<'a> {
+    fn xfile_deserialize_into(
+        &self,
+        de: &mut impl T5XFileDeserialize,
+        _data: (),
+    ) -> Result<XModel> {
+        let (mut model, surfs) = self.deserialize_with(de, |de| {
+            self.surfs
+                .to_array(self.numsurfs as _)
+                .xfile_deserialize_into(de, ())
+        })?;
+        model.surfs = surfs;
+        Ok(model)
+    }
+}
+
+impl XModel {
+    /// The number of LODs the model has, clamped to [`MAX_LODS`].
+    pub fn lod_count(&self) -> usize {
+        (self.num_lods.max(0) as usize).min(MAX_LODS)
+    }
+
+    /// The indices into [`Self::surfs`] (and [`Self::material_handles`]) of
+    /// the surfaces drawn for `lod`, or [`None`] if the model doesn't have
+    /// that LOD.
+    ///
+    /// The range is clamped to the surfaces actually present.
+    pub fn lod_surface_range(&self, lod: usize) -> Option<Range<usize>> {
+        if lod >= self.lod_count() {
+            return None;
+        }
+
+        let info = &self.lod_info[lod];
+        let start = info.surf_index.min(self.surfs.len());
+        let end = info
+            .surf_index
+            .saturating_add(info.numsurfs)
+            .min(self.surfs.len());
+        Some(start..end)
+    }
+
+    /// The surfaces drawn for `lod` (0 being the most detailed), or an empty
+    /// slice if the model doesn't have that LOD.
+    pub fn surfaces_for_lod(&self, lod: usize) -> &[XSurface] {
+        self.lod_surface_range(lod)
+            .map_or(&[], |range| &self.surfs[range])
+    }
+
+    /// The LOD the game draws the model with at `dist` units from the
+    /// camera, or [`None`] if it's too far away to be drawn at all.
+    ///
+    /// Each LOD is used up to its [`XModelLodInfo::dist`]; a distance of 0
+    /// means the LOD has no limit.
+    pub fn lod_for_distance(&self, dist: f32) -> Option<usize> {
+        self.lod_info[..self.lod_count()]
+            .iter()
+            .position(|info| info.dist == 0.0 || dist < info.dist)
+    }
+
+    /// Checks that the model's counts agree with the arrays they describe,
+    /// which the serializer writes as they are:
+    /// * `num_bones`, `num_root_bones`, and `numsurfs` fit in a byte, and
+    ///   `num_root_bones` doesn't exceed `num_bones`.
+    /// * The per-bone arrays have `num_bones` entries (`parent_list` one
+    ///   per non-root bone, and `quats` and `trans` four), and the
+    ///   per-surface arrays have `numsurfs`. Arrays that are always loaded
+    ///   (`bone_names`, `base_mat`, `surfs`) can't be empty when their count
+    ///   isn't 0; the others can, since they're written as null pointers.
+    /// * `num_lods` and `coll_lod` are at most [`MAX_LODS`].
+    /// * Every surface has whole triangles, and as much blend and tension
+    ///   data as its [`XSurfaceVertexInfo::vert_count`] says.
+    ///
+    /// Returns [`Err`] with [`ErrorKind::Validation`] containing all
+    /// violations if any are found. See [`Self::recompute_counts`] for
+    /// fixing the counts after editing the arrays.
+    pub fn validate_counts(&self) -> Result<()> {
+        let violations = self.count_violations();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Validation(violations),
+            ))
+        }
+    }
+
+    fn count_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        for (name, count) in [
+            ("num_bones", self.num_bones),
+            ("num_root_bones", self.num_root_bones),
+            ("numsurfs", self.numsurfs),
+        ] {
+            if count > u8::MAX as usize {
+                violations.push(format!("XModel: {name} ({count}) > u8::MAX"));
+            }
+        }
+
+        if self.num_root_bones > self.num_bones {
+            violations.push(format!(
+                "XModel: num_root_bones ({}) > num_bones ({})",
+                self.num_root_bones, self.num_bones
+            ));
+        }
+
+        let num_child_bones = self.num_bones.saturating_sub(self.num_root_bones);
+        let mut check = |name: &str, len: usize, expected: usize, nullable: bool| {
+            if len != expected && !(nullable && len == 0) {
+                violations.push(format!(
+                    "XModel: {name}.len() ({len}) doesn't match its count ({expected})"
+                ));
+            }
+        };
+        check("bone_names", self.bone_names.len(), self.num_bones, false);
+        check("parent_list", self.parent_list.len(), num_child_bones, true);
+        check("quats", self.quats.len(), num_child_bones * 4, true);
+        check("trans", self.trans.len(), num_child_bones * 4, true);
+        check(
+            "part_classification",
+            self.part_classification.len(),
+            self.num_bones,
+            true,
+        );
+        check("base_mat", self.base_mat.len(), self.num_bones, false);
+        check("bone_info", self.bone_info.len(), self.num_bones, true);
+        check("surfs", self.surfs.len(), self.numsurfs, false);
+        check(
+            "material_handles",
+            self.material_handles.len(),
+            self.numsurfs,
+            true,
+        );
+        check(
+            "stream_info.high_mip_bounds",
+            self.stream_info.high_mip_bounds.len(),
+            self.numsurfs,
+            true,
+        );
+
+        if self.num_lods > MAX_LODS as i16 {
+            violations.push(format!("XModel: num_lods ({}) > MAX_LODS", self.num_lods));
+        }
+
+        if self.coll_lod > MAX_LODS as i16 {
+            violations.push(format!("XModel: coll_lod ({}) > MAX_LODS", self.coll_lod));
+        }
+
+        for (i, surf) in self.surfs.iter().enumerate() {
+            violations.extend(
+                surf.count_violations()
+                    .into_iter()
+                    .map(|v| format!("XModel: surfs[{i}]: {v}")),
+            );
+        }
+
+        violations
+    }
+
+    /// Sets `num_bones`, `num_root_bones`, and `numsurfs` from the lengths
+    /// of [`Self::bone_names`], [`Self::parent_list`], and [`Self::surfs`].
+    /// The other arrays still have to agree with them for
+    /// [`Self::validate_counts`] to pass.
+    pub fn recompute_counts(&mut self) {
+        self.num_bones = self.bone_names.len();
+        self.num_root_bones = self.num_bones.saturating_sub(self.parent_list.len());
+        self.numsurfs = self.surfs.len();
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XModel {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        self.validate_counts()?;
+
         let name = XStringRaw::from_str(self.name.get());
         let bone_names = Ptr32::from_slice(&self.bone_names);
         let parent_list = Ptr32::from_slice(&self.parent_list);
@@ -358,8 +537,54 @@ impl XFileSerialize<()> for XModel {
     }
 }
 
+/// Borrowed counterpart of [`XModel`], whose surfaces' vertices are
+/// borrowed. The surfaces are in [`Self::surfs`], and [`XModel::surfs`] is
+/// left empty.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct XModelRef<'buf> {
+    pub model: Box<XModel>,
+    pub surfs: Vec<XSurfaceRef<'buf>>,
+}
+
+impl<'buf> XModelRef<'buf> {
+    /// Copies the vertices, which are decoded in the file's byte order.
+    pub fn to_owned(&self, little_endian: bool) -> XModel {
+        XModel {
+            surfs: self
+                .surfs
+                .iter()
+                .map(|s| s.to_owned(little_endian))
+                .collect(),
+            ..(*self.model).clone()
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, XModelRef<'buf>, ()> for XModelRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<XModelRef<'buf>> {
+        let (model, surfs) = self.deserialize_with(de, |de| {
+            self.surfs
+                .to_array(self.numsurfs as _)
+                .to_vec(de)?
+                .iter()
+                .map(|s| s.xfile_deserialize_borrowed(de, ()))
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        Ok(XModelRef {
+            model: Box::new(model),
+            surfs,
+        })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct DObjAnimMatRaw {
     pub quat: [f32; 4],
     pub trans: [f32; 3],
@@ -368,7 +593,8 @@ pub(crate) struct DObjAnimMatRaw {
 assert_size!(DObjAnimMatRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct DObjAnimMat {
     pub quat: Vec4,
     pub trans: Vec3,
@@ -385,6 +611,7 @@ impl From<DObjAnimMatRaw> for DObjAnimMat {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for DObjAnimMat {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let mat = DObjAnimMatRaw {
@@ -397,7 +624,7 @@ impl XFileSerialize<()> for DObjAnimMat {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XSurfaceRaw<'a> {
     pub tile_mode: u8,
     pub vert_list_count: u8,
@@ -420,7 +647,8 @@ assert_size!(XSurfaceRaw, 68);
 
 bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-    #[derive(Copy, Clone, Default, Debug)]
+    #[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+    #[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
     pub struct XSurfaceFlags: u16 {
         const SKINNED  = 0x02;
         const DEFORMED = 0x80;
@@ -428,7 +656,8 @@ bitflags! {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XSurface {
     pub tile_mode: u8,
     pub flags: XSurfaceFlags,
@@ -443,6 +672,16 @@ pub struct XSurface {
     pub part_bits: [i32; 5],
 }
 
+impl<'a> XSurfaceRaw<'a> {
+    fn surface_flags(&self, de: &mut impl T5XFileDeserialize) -> Result<XSurfaceFlags> {
+        XSurfaceFlags::from_bits(self.flags).ok_or(Error::new_with_offset(
+            file_line_col!(),
+            de.stream_pos()? as _,
+            ErrorKind::BadBitflags(self.flags as _),
+        ))
+    }
+}
+
 impl<'a> XFileDeserializeInto<XSurface, ()> for XSurfaceRaw<'a> {
     fn xfile_deserialize_into(
         &self,
@@ -453,11 +692,7 @@ impl<'a> XFileDeserializeInto<XSurface, ()> for XSurfaceRaw<'a> {
         //let pos = de.stream_pos()?;
         //dbg!(pos);
 
-        let flags = XSurfaceFlags::from_bits(self.flags).ok_or(Error::new_with_offset(
-            file_line_col!(),
-            de.stream_pos()? as _,
-            ErrorKind::BadBitflags(self.flags as _),
-        ))?;
+        let flags = self.surface_flags(de)?;
         let vert_info = self.vert_info.xfile_deserialize_into(de, ())?;
         let verts0 = self.verts0.to_array(self.vert_count as _).to_vec_into(de)?;
         let vert_list = self
@@ -467,7 +702,7 @@ impl<'a> XFileDeserializeInto<XSurface, ()> for XSurfaceRaw<'a> {
         let tri_indices = self
             .tri_indices
             .to_array(self.tri_count as usize * 3)
-            .to_vec(de)?;
+            .to_vec_bulk(de)?;
 
         Ok(XSurface {
             tile_mode: self.tile_mode,
@@ -485,6 +720,96 @@ impl<'a> XFileDeserializeInto<XSurface, ()> for XSurfaceRaw<'a> {
     }
 }
 
+impl XSurface {
+    /// Returns the surface's triangles, as indices into [`Self::verts0`].
+    pub fn triangles(&self) -> impl Iterator<Item = [u16; 3]> + '_ {
+        self.tri_indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]])
+    }
+
+    pub fn set_triangles(&mut self, triangles: &[[u16; 3]]) {
+        self.tri_indices = triangles.as_flattened().to_vec();
+    }
+
+    /// Returns the position, normal, texture coordinates, and color of every
+    /// vertex (see [`GfxPackedVertex::unpack`]).
+    pub fn unpack_vertices(&self) -> impl Iterator<Item = (Vec3, Vec3, Vec2, GfxColor)> + '_ {
+        self.verts0.iter().map(GfxPackedVertex::unpack)
+    }
+
+    /// The ways the surface's arrays disagree with each other (see
+    /// [`XModel::validate_counts`]). The other counts are written from the
+    /// arrays' lengths.
+    fn count_violations(&self) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if !self.tri_indices.len().is_multiple_of(3) {
+            violations.push(format!(
+                "tri_indices.len() ({}) isn't a multiple of 3",
+                self.tri_indices.len()
+            ));
+        }
+
+        let (blend_count, vert_count) = self.vert_info.counts();
+        let vert_info = &self.vert_info;
+        if !vert_info.verts_blend.is_empty() && vert_info.verts_blend.len() != blend_count {
+            violations.push(format!(
+                "vert_info.verts_blend.len() ({}) doesn't match vert_info.vert_count ({blend_count})",
+                vert_info.verts_blend.len()
+            ));
+        }
+        if !vert_info.tension_data.is_empty() && vert_info.tension_data.len() != vert_count * 12 {
+            violations.push(format!(
+                "vert_info.tension_data.len() ({}) doesn't match vert_info.vert_count ({})",
+                vert_info.tension_data.len(),
+                vert_count * 12
+            ));
+        }
+
+        violations
+    }
+
+    /// Replaces the surface's vertices with ones packed from plain floats
+    /// (see [`GfxPackedVertex::pack`]). Vertices without a color are white.
+    /// `colors` should already be in the platform's byte order (see
+    /// [`GfxColor::from_rgba`]).
+    ///
+    /// [`Self::vert_info`] and [`Self::vert_list`] are left as they are, so
+    /// they have to be updated separately if the vertex count changes.
+    pub fn set_vertices(
+        &mut self,
+        positions: &[Vec3],
+        normals: &[Vec3],
+        uvs: &[Vec2],
+        colors: Option<&[GfxColor]>,
+    ) -> Result<()> {
+        let len = positions.len();
+        if normals.len() != len
+            || uvs.len() != len
+            || colors.is_some_and(|c| c.len() != len)
+            || len > u16::MAX as usize
+        {
+            return Err(Error::new(
+                file_line_col!(),
+                ErrorKind::BrokenInvariant(format!(
+                    "XSurface: {len} positions, {} normals, {} uvs, {:?} colors",
+                    normals.len(),
+                    uvs.len(),
+                    colors.map(<[_]>::len),
+                )),
+            ));
+        }
+
+        self.verts0 = (0..len)
+            .map(|i| {
+                let color = colors.map_or(GfxColor::WHITE, |c| c[i]);
+                GfxPackedVertex::pack(positions[i], normals[i], uvs[i], color)
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XSurface {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let tri_indices = Ptr32::from_slice(&self.tri_indices);
@@ -504,7 +829,7 @@ impl XFileSerialize<()> for XSurface {
             vert_list_count: self.vert_list.len() as _,
             flags: self.flags.bits(),
             vert_count: self.verts0.len() as _,
-            tri_count: self.tri_indices.len() as u16 * 3,
+            tri_count: (self.tri_indices.len() / 3) as _,
             base_tri_index: self.base_tri_index as _,
             base_vert_index: self.base_vert_index as _,
             tri_indices,
@@ -525,8 +850,82 @@ impl XFileSerialize<()> for XSurface {
     }
 }
 
+/// Borrowed counterpart of [`XSurface`], with the bytes of
+/// [`XSurface::verts0`] in the file's byte order.
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct XSurfaceRef<'buf> {
+    pub tile_mode: u8,
+    pub flags: XSurfaceFlags,
+    pub base_tri_index: usize,
+    pub base_vert_index: usize,
+    pub tri_indices: Vec<u16>,
+    pub vert_info: XSurfaceVertexInfo,
+    pub verts0: &'buf [u8],
+    pub vert_list: Vec<XRigidVertList>,
+    pub part_bits: [i32; 5],
+}
+
+impl<'buf> XSurfaceRef<'buf> {
+    pub fn to_owned(&self, little_endian: bool) -> XSurface {
+        let verts0 = self
+            .verts0
+            .chunks_exact(size_of!(GfxPackedVertexRaw))
+            // each chunk is exactly one vertex, so reading it can't fail
+            .map(|v| read::from_bytes::<GfxPackedVertexRaw>(v, little_endian).unwrap())
+            .map(Into::into)
+            .collect();
+
+        XSurface {
+            tile_mode: self.tile_mode,
+            flags: self.flags,
+            base_tri_index: self.base_tri_index,
+            base_vert_index: self.base_vert_index,
+            tri_indices: self.tri_indices.clone(),
+            vert_info: self.vert_info.clone(),
+            verts0,
+            vb0: None,
+            vert_list: self.vert_list.clone(),
+            index_buffer: None,
+            part_bits: self.part_bits,
+        }
+    }
+}
+
+impl<'a, 'buf> XFileDeserializeBorrowed<'buf, XSurfaceRef<'buf>, ()> for XSurfaceRaw<'a> {
+    fn xfile_deserialize_borrowed(
+        &self,
+        de: &mut impl T5XFileDeserializeBorrowed<'buf>,
+        _data: (),
+    ) -> Result<XSurfaceRef<'buf>> {
+        let flags = self.surface_flags(de)?;
+        let vert_info = self.vert_info.xfile_deserialize_into(de, ())?;
+        let verts0 = self.verts0.to_array(self.vert_count as _).to_slice(de)?;
+        let vert_list = self
+            .vert_list
+            .to_array(self.vert_list_count as _)
+            .xfile_deserialize_into(de, ())?;
+        let tri_indices = self
+            .tri_indices
+            .to_array(self.tri_count as usize * 3)
+            .to_vec_bulk(de)?;
+
+        Ok(XSurfaceRef {
+            tile_mode: self.tile_mode,
+            flags,
+            base_tri_index: self.base_tri_index as _,
+            base_vert_index: self.base_vert_index as _,
+            tri_indices,
+            vert_info,
+            verts0,
+            vert_list,
+            part_bits: self.part_bits,
+        })
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XSurfaceVertexInfoRaw<'a> {
     pub vert_count: [i16; 4],
     pub verts_blend: Ptr32<'a, u16>,
@@ -535,39 +934,59 @@ pub(crate) struct XSurfaceVertexInfoRaw<'a> {
 assert_size!(XSurfaceVertexInfoRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XSurfaceVertexInfo {
     pub vert_count: [i16; 4],
     pub verts_blend: Vec<u16>,
     pub tension_data: Vec<f32>,
 }
 
+/// The number of blend entries and vertices described by
+/// [`XSurfaceVertexInfo::vert_count`] (vertices weighted to 1, 2, 3, and 4
+/// bones, which take 1, 3, 5, and 7 blend entries each).
+pub(crate) fn vert_counts(vert_count: [i16; 4]) -> (usize, usize) {
+    // saturating so that corrupted (negative) counts get rejected by
+    // `check_array_len` instead of overflowing
+    vert_count
+        .iter()
+        .zip([1, 3, 5, 7])
+        .fold((0usize, 0usize), |(blend, verts), (&count, n)| {
+            let count = count as usize;
+            (
+                blend.saturating_add(count.saturating_mul(n)),
+                verts.saturating_add(count),
+            )
+        })
+}
+
+impl XSurfaceVertexInfo {
+    /// The number of entries [`Self::verts_blend`] should have, and the
+    /// number of vertices [`Self::vert_count`] adds up to.
+    pub fn counts(&self) -> (usize, usize) {
+        vert_counts(self.vert_count)
+    }
+}
+
 impl<'a> XFileDeserializeInto<XSurfaceVertexInfo, ()> for XSurfaceVertexInfoRaw<'a> {
     fn xfile_deserialize_into(
         &self,
         de: &mut impl T5XFileDeserialize,
         _data: (),
     ) -> Result<XSurfaceVertexInfo> {
-        let blend_count = self.vert_count[0] as usize
-            + self.vert_count[1] as usize * 3
-            + self.vert_count[2] as usize * 5
-            + self.vert_count[3] as usize * 7;
-        let tension_count = (self.vert_count[0] as usize
-            + self.vert_count[1] as usize
-            + self.vert_count[2] as usize
-            + self.vert_count[3] as usize)
-            * 12;
+        let (blend_count, vert_count) = vert_counts(self.vert_count);
+        let tension_count = vert_count.saturating_mul(12);
 
         Ok(XSurfaceVertexInfo {
             vert_count: self.vert_count,
-            verts_blend: self.verts_blend.to_array(blend_count).to_vec(de)?,
-            tension_data: self.tension_data.to_array(tension_count).to_vec(de)?,
+            verts_blend: self.verts_blend.to_array(blend_count).to_vec_bulk(de)?,
+            tension_data: self.tension_data.to_array(tension_count).to_vec_bulk(de)?,
         })
     }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct GfxPackedVertexRaw {
     pub xyz: [f32; 3],
     pub binormal_sign: f32,
@@ -579,16 +998,59 @@ pub(crate) struct GfxPackedVertexRaw {
 assert_size!(GfxPackedVertexRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct GfxPackedVertex {
     pub xyz: Vec3,
     pub binormal_sign: f32,
+    /// In the platform's byte order, see [`GfxColor`].
     pub color: GfxColor,
     pub tex_coord: TexCoords,
     pub normal: UnitVec,
     pub tangent: UnitVec,
 }
 
+impl GfxPackedVertex {
+    /// Packs a vertex from plain floats. The tangent is left zeroed, see
+    /// [`Self::with_tangent`]. `color` should already be in the platform's
+    /// byte order (see [`GfxColor::from_rgba`]).
+    pub fn pack(position: Vec3, normal: Vec3, uv: Vec2, color: GfxColor) -> Self {
+        Self {
+            xyz: position,
+            binormal_sign: 1.0,
+            color,
+            tex_coord: TexCoords::encode(uv),
+            normal: UnitVec::encode(normal),
+            tangent: UnitVec::encode(Vec3::default()),
+        }
+    }
+
+    /// `binormal_sign` should be `1.0` or `-1.0`, depending on the
+    /// handedness of the tangent space.
+    pub fn with_tangent(self, tangent: Vec3, binormal_sign: f32) -> Self {
+        Self {
+            tangent: UnitVec::encode(tangent),
+            binormal_sign,
+            ..self
+        }
+    }
+
+    /// The reverse of [`Self::pack`]. Returns the position, normal, texture
+    /// coordinates, and color.
+    pub fn unpack(&self) -> (Vec3, Vec3, Vec2, GfxColor) {
+        (
+            self.xyz,
+            self.normal.decode(),
+            self.tex_coord.decode(),
+            self.color,
+        )
+    }
+
+    pub fn tangent(&self) -> Vec3 {
+        self.tangent.decode()
+    }
+}
+
 impl From<GfxPackedVertexRaw> for GfxPackedVertex {
     fn from(value: GfxPackedVertexRaw) -> Self {
         Self {
@@ -602,6 +1064,7 @@ impl From<GfxPackedVertexRaw> for GfxPackedVertex {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for GfxPackedVertex {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let packed_vertex = GfxPackedVertexRaw {
@@ -616,23 +1079,181 @@ impl XFileSerialize<()> for GfxPackedVertex {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+/// An 8-bit-per-channel color, with its channels in whichever order the
+/// platform's GPU expects:
+///
+/// | Platform        | Byte order |
+/// |-----------------|------------|
+/// | Windows / macOS | BGRA       |
+/// | Consoles        | RGBA       |
+///
+/// On PC, it's a `D3DCOLOR` (ARGB in a little-endian [`u32`]). The channels
+/// are kept in the file's order everywhere (vertices, static model
+/// lighting, etc.), so use [`Self::to_rgba`] or [`Self::to_rgba_f32`] when
+/// exporting, and [`Self::from_rgba`] or [`Self::from_rgba_f32`] when
+/// importing.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct GfxColor(pub [u8; 4]);
 assert_size!(GfxColor, 4);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+impl GfxColor {
+    /// Opaque white, which is the same in every byte order.
+    pub const WHITE: Self = Self([0xFF; 4]);
+
+    pub const fn to_rgba(self, platform: XFilePlatform) -> [u8; 4] {
+        let [a, b, c, d] = self.0;
+        if platform.is_console() {
+            [a, b, c, d]
+        } else {
+            [c, b, a, d]
+        }
+    }
+
+    pub const fn from_rgba(rgba: [u8; 4], platform: XFilePlatform) -> Self {
+        // swapping red and blue is its own inverse
+        Self(Self(rgba).to_rgba(platform))
+    }
+
+    /// Returns the channels in RGBA order, scaled to `0.0..=1.0`.
+    pub fn to_rgba_f32(self, platform: XFilePlatform) -> [f32; 4] {
+        self.to_rgba(platform).map(|c| c as f32 / 255.0)
+    }
+
+    /// Takes the channels in RGBA order, in `0.0..=1.0`. Channels outside of
+    /// that range are clamped.
+    pub fn from_rgba_f32(rgba: [f32; 4], platform: XFilePlatform) -> Self {
+        Self::from_rgba(
+            rgba.map(|c| (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8),
+            platform,
+        )
+    }
+}
+
+/// A pair of texture coordinates, packed into a [`u32`]. See
+/// [`Self::decode`] and [`Self::encode`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct TexCoords(pub u32);
 assert_size!(TexCoords, 4);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+/// A normal or tangent, packed into 4 bytes. See [`Self::decode`] and
+/// [`Self::encode`].
+///
+/// Each component gets 8 bits, so a decoded vector is only accurate to
+/// within about 1/127 of the original, and generally isn't exactly
+/// normalized.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct UnitVec(pub [u8; 4]);
 assert_size!(UnitVec, 4);
 
+impl UnitVec {
+    /// Decodes the vector. The first three bytes are the components, biased
+    /// by 127, and the last byte scales all three of them.
+    pub fn decode(self) -> Vec3 {
+        let scale = (self.0[3] as f32 + 192.0) / 32385.0;
+        Vec3::from([0, 1, 2].map(|i| (self.0[i] as f32 - 127.0) * scale))
+    }
+
+    /// Encodes `v`, choosing the smallest scale that can still represent
+    /// its largest component, which gives the most precision. Any vector
+    /// with no component larger than 1 in magnitude fits.
+    pub fn encode(v: Vec3) -> Self {
+        let v = v.get();
+        let max = v.iter().fold(0.0f32, |max, c| max.max(c.abs()));
+
+        // ceil(max * 255 - 192)
+        let w = (max * 255.0 - 192.0).clamp(0.0, 255.0);
+        let w = if (w as u8 as f32) < w {
+            w as u8 + 1
+        } else {
+            w as u8
+        };
+
+        let scale = (w as f32 + 192.0) / 32385.0;
+        let [x, y, z] = v.map(|c| (c / scale + 127.5).clamp(0.0, 254.0) as u8);
+        Self([x, y, z, w])
+    }
+}
+
+/// Converts `value` to the bits of an IEEE 754 half-precision float,
+/// rounding to nearest.
+fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7FFFFF;
+
+    if exp == 0xFF {
+        // infinity or NaN
+        return sign | 0x7C00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let exp = exp - 127 + 15;
+    if exp >= 0x1F {
+        sign | 0x7C00
+    } else if exp <= 0 {
+        // subnormal, or too small to represent at all
+        if exp < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x800000;
+        let shift = (14 - exp) as u32;
+        let rounding = (mantissa >> (shift - 1)) & 1;
+        sign | ((mantissa >> shift) + rounding) as u16
+    } else {
+        // a carry out of the mantissa correctly bumps the exponent
+        let rounding = (mantissa >> 12) & 1;
+        sign | (((exp as u32) << 10 | mantissa >> 13) + rounding) as u16
+    }
+}
+
+/// Converts the bits of an IEEE 754 half-precision float to an [`f32`].
+fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exp = ((half >> 10) & 0x1F) as u32;
+    let mantissa = (half & 0x3FF) as u32;
+
+    let bits = match exp {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // subnormal, which is always normal as an f32
+            let mut exp = 127 - 15 + 1;
+            let mut mantissa = mantissa;
+            while mantissa & 0x400 == 0 {
+                mantissa <<= 1;
+                exp -= 1;
+            }
+            sign | exp << 23 | (mantissa & 0x3FF) << 13
+        }
+        0x1F => sign | 0x7F800000 | mantissa << 13,
+        _ => sign | (exp + 127 - 15) << 23 | mantissa << 13,
+    };
+    f32::from_bits(bits)
+}
+
+impl TexCoords {
+    /// Decodes the coordinates, which are a pair of IEEE 754 half-precision
+    /// floats, `u` in the low 16 bits and `v` in the high 16 bits.
+    pub fn decode(self) -> Vec2 {
+        Vec2::from([f16_to_f32(self.0 as u16), f16_to_f32((self.0 >> 16) as u16)])
+    }
+
+    /// Encodes `uv`, rounding each coordinate to the nearest half-precision
+    /// float. Coordinates too large in magnitude to represent (above 65504)
+    /// become infinite.
+    pub fn encode(uv: Vec2) -> Self {
+        let [u, v] = uv.get();
+        Self(f32_to_f16(u) as u32 | (f32_to_f16(v) as u32) << 16)
+    }
+}
+
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XRigidVertListRaw<'a> {
     pub bone_offset: u16,
     pub vert_count: u16,
@@ -643,7 +1264,8 @@ pub(crate) struct XRigidVertListRaw<'a> {
 assert_size!(XRigidVertListRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XRigidVertList {
     pub bone_offset: usize,
     pub vert_count: usize,
@@ -670,6 +1292,7 @@ impl<'a> XFileDeserializeInto<XRigidVertList, ()> for XRigidVertListRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XRigidVertList {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let collision_tree = Ptr32::from_box(&self.collision_tree);
@@ -686,7 +1309,7 @@ impl XFileSerialize<()> for XRigidVertList {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XSurfaceCollisionTreeRaw<'a> {
     pub trans: [f32; 3],
     pub scale: [f32; 3],
@@ -696,7 +1319,8 @@ pub(crate) struct XSurfaceCollisionTreeRaw<'a> {
 assert_size!(XSurfaceCollisionTreeRaw, 40);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XSurfaceCollisionTree {
     pub trans: Vec3,
     pub scale: Vec3,
@@ -721,6 +1345,7 @@ impl<'a> XFileDeserializeInto<XSurfaceCollisionTree, ()> for XSurfaceCollisionTr
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XSurfaceCollisionTree {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let nodes = FatPointerCountFirstU32::from_slice(&self.nodes);
@@ -738,7 +1363,7 @@ impl XFileSerialize<()> for XSurfaceCollisionTree {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XSurfaceCollisionNodeRaw {
     pub aabb: XSurfaceCollisionAabb,
     pub child_begin_index: u16,
@@ -747,7 +1372,8 @@ pub(crate) struct XSurfaceCollisionNodeRaw {
 assert_size!(XSurfaceCollisionNodeRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XSurfaceCollisionNode {
     pub aabb: XSurfaceCollisionAabb,
     pub child_begin_index: usize,
@@ -764,6 +1390,7 @@ impl From<XSurfaceCollisionNodeRaw> for XSurfaceCollisionNode {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XSurfaceCollisionNode {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let node = XSurfaceCollisionNodeRaw {
@@ -775,8 +1402,9 @@ impl XFileSerialize<()> for XSurfaceCollisionNode {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct XSurfaceCollisionAabb {
     pub mins: [u16; 3],
     pub maxs: [u16; 3],
@@ -784,14 +1412,15 @@ pub struct XSurfaceCollisionAabb {
 assert_size!(XSurfaceCollisionAabb, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XSurfaceCollisionLeafRaw {
     pub triangle_begin_index: u16,
 }
 assert_size!(XSurfaceCollisionLeafRaw, 2);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct XSurfaceCollisionLeaf {
     pub triangle_begin_index: usize,
 }
@@ -804,6 +1433,7 @@ impl From<XSurfaceCollisionLeafRaw> for XSurfaceCollisionLeaf {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XSurfaceCollisionLeaf {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let leaf = XSurfaceCollisionLeafRaw {
@@ -814,7 +1444,7 @@ impl XFileSerialize<()> for XSurfaceCollisionLeaf {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelLodInfoRaw {
     pub dist: f32,
     pub numsurfs: u16,
@@ -829,7 +1459,8 @@ pub(crate) struct XModelLodInfoRaw {
 assert_size!(XModelLodInfoRaw, 32);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelLodInfo {
     pub dist: f32,
     pub numsurfs: usize,
@@ -910,7 +1541,7 @@ impl TryInto<XModelLodInfoRaw> for XModelLodInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelCollSurfRaw<'a> {
     pub coll_tris: FatPointerCountLastU32<'a, XModelCollTriRaw>,
     pub mins: [f32; 3],
@@ -922,7 +1553,8 @@ pub(crate) struct XModelCollSurfRaw<'a> {
 assert_size!(XModelCollSurfRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelCollSurf {
     pub coll_tris: Vec<XModelCollTri>,
     pub mins: Vec3,
@@ -949,6 +1581,7 @@ impl<'a> XFileDeserializeInto<XModelCollSurf, ()> for XModelCollSurfRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XModelCollSurf {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let coll_tris = FatPointerCountLastU32::from_slice(&self.coll_tris);
@@ -967,7 +1600,7 @@ impl XFileSerialize<()> for XModelCollSurf {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelCollTriRaw {
     pub plane: [f32; 4],
     pub svec: [f32; 4],
@@ -976,7 +1609,8 @@ pub(crate) struct XModelCollTriRaw {
 assert_size!(XModelCollTriRaw, 48);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelCollTri {
     pub plane: Vec4,
     pub svec: Vec4,
@@ -993,6 +1627,7 @@ impl From<XModelCollTriRaw> for XModelCollTri {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XModelCollTri {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let coll_tri = XModelCollTriRaw {
@@ -1005,18 +1640,20 @@ impl XFileSerialize<()> for XModelCollTri {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XBoneInfoRaw {
     pub bounds: [[f32; 3]; 2],
     pub offset: [f32; 3],
     pub radius_squared: f32,
     pub collmap: u8,
+    #[allow(dead_code)]
     pad: [u8; 3],
 }
 assert_size!(XBoneInfoRaw, 44);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XBoneInfo {
     pub bounds: [Vec3; 2],
     pub offset: Vec3,
@@ -1035,6 +1672,7 @@ impl From<XBoneInfoRaw> for XBoneInfo {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XBoneInfo {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let bone_info = XBoneInfoRaw {
@@ -1049,14 +1687,15 @@ impl XFileSerialize<()> for XBoneInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelStreamInfoRaw<'a> {
     pub high_mip_bounds: Ptr32<'a, XModelHighMipBoundsRaw>,
 }
 assert_size!(XModelStreamInfoRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelStreamInfo {
     pub high_mip_bounds: Vec<XModelHighMipBounds>,
 }
@@ -1077,7 +1716,7 @@ impl<'a> XFileDeserializeInto<XModelStreamInfo, u8> for XModelStreamInfoRaw<'a>
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelHighMipBoundsRaw {
     pub center: [f32; 3],
     pub himip_radius_sq: f32,
@@ -1085,7 +1724,8 @@ pub(crate) struct XModelHighMipBoundsRaw {
 assert_size!(XModelHighMipBoundsRaw, 16);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelHighMipBounds {
     pub center: Vec3,
     pub himip_radius_sq: f32,
@@ -1101,7 +1741,7 @@ impl From<XModelHighMipBoundsRaw> for XModelHighMipBounds {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct PhysPresetRaw<'a> {
     pub name: XStringRaw<'a>,
     pub flags: i32,
@@ -1122,7 +1762,8 @@ pub(crate) struct PhysPresetRaw<'a> {
 assert_size!(PhysPresetRaw, 84);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PhysPreset {
     pub name: XString,
     pub flags: i32,
@@ -1192,6 +1833,7 @@ impl<'a> XFileDeserializeInto<PhysPreset, ()> for PhysPresetRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PhysPreset {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -1222,14 +1864,15 @@ impl XFileSerialize<()> for PhysPreset {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CollmapRaw<'a> {
     pub geom_list: Ptr32<'a, PhysGeomListRaw<'a>>,
 }
 assert_size!(CollmapRaw, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct Collmap {
     pub geom_list: Option<Box<PhysGeomList>>,
 }
@@ -1246,6 +1889,7 @@ impl<'a> XFileDeserializeInto<Collmap, ()> for CollmapRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for Collmap {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let geom_list = Ptr32::from_box(&self.geom_list);
@@ -1256,7 +1900,7 @@ impl XFileSerialize<()> for Collmap {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct PhysGeomListRaw<'a> {
     pub geoms: FatPointerCountFirstU32<'a, PhysGeomInfoRaw<'a>>,
     pub contents: i32,
@@ -1264,7 +1908,8 @@ pub(crate) struct PhysGeomListRaw<'a> {
 assert_size!(PhysGeomListRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PhysGeomList {
     pub geoms: Vec<PhysGeomInfo>,
     pub contents: i32,
@@ -1283,6 +1928,7 @@ impl<'a> XFileDeserializeInto<PhysGeomList, ()> for PhysGeomListRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PhysGeomList {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let geoms = FatPointerCountFirstU32::from_slice(&self.geoms);
@@ -1296,7 +1942,7 @@ impl XFileSerialize<()> for PhysGeomList {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct PhysGeomInfoRaw<'a> {
     pub brush: Ptr32<'a, BrushWrapperRaw<'a>>,
     pub type_: i32,
@@ -1307,7 +1953,8 @@ pub(crate) struct PhysGeomInfoRaw<'a> {
 assert_size!(PhysGeomInfoRaw, 68);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(i32)]
 pub enum PhysGeomType {
     #[default]
@@ -1317,7 +1964,8 @@ pub enum PhysGeomType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PhysGeomInfo {
     pub brush: Option<Box<BrushWrapper>>,
     pub type_: PhysGeomType,
@@ -1346,6 +1994,7 @@ impl<'a> XFileDeserializeInto<PhysGeomInfo, ()> for PhysGeomInfoRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PhysGeomInfo {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let brush = Ptr32::from_box(&self.brush);
@@ -1362,7 +2011,7 @@ impl XFileSerialize<()> for PhysGeomInfo {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct BrushWrapperRaw<'a> {
     pub mins: [f32; 3],
     pub contents: i32,
@@ -1376,7 +2025,8 @@ pub(crate) struct BrushWrapperRaw<'a> {
 assert_size!(BrushWrapperRaw, 96);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct BrushWrapper {
     pub mins: Vec3,
     pub contents: i32,
@@ -1404,12 +2054,13 @@ impl<'a> XFileDeserializeInto<BrushWrapper, ()> for BrushWrapperRaw<'a> {
             verts: self.verts.to_vec_into(de)?,
             planes: self
                 .planes
-                .to_array(self.sides.size() as _)
+                .to_array(self.sides.len() as _)
                 .to_vec_into(de)?,
         })
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for BrushWrapper {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let sides = FatPointerCountFirstU32::from_slice(&self.sides);
@@ -1434,7 +2085,7 @@ impl XFileSerialize<()> for BrushWrapper {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CBrushSideRaw<'a> {
     pub plane: Ptr32<'a, CPlaneRaw>,
     pub cflags: i32,
@@ -1443,7 +2094,8 @@ pub(crate) struct CBrushSideRaw<'a> {
 assert_size!(CBrushSideRaw, 12);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct CBrushSide {
     pub plane: Option<Box<CPlane>>,
     pub cflags: i32,
@@ -1464,6 +2116,7 @@ impl<'a> XFileDeserializeInto<CBrushSide, ()> for CBrushSideRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for CBrushSide {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let plane = Ptr32::from_box(&self.plane);
@@ -1478,7 +2131,7 @@ impl XFileSerialize<()> for CBrushSide {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct CPlaneRaw {
     pub normal: [f32; 3],
     pub dist: f32,
@@ -1490,7 +2143,8 @@ pub(crate) struct CPlaneRaw {
 assert_size!(CPlaneRaw, 20);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct CPlaneType(u8);
 
 impl CPlaneType {
@@ -1508,7 +2162,8 @@ impl CPlaneType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 pub struct CPlaneSignbits(u8);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
@@ -1561,7 +2216,8 @@ impl CPlaneSignbits {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct CPlane {
     pub normal: Vec3,
     pub dist: f32,
@@ -1580,6 +2236,7 @@ impl From<CPlaneRaw> for CPlane {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for CPlane {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let plane = CPlaneRaw {
@@ -1594,16 +2251,19 @@ impl XFileSerialize<()> for CPlane {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct PhysConstraintsRaw<'a> {
     pub name: XStringRaw<'a>,
     pub count: u32,
-    pub data: [PhysConstraintRaw<'a>; 16],
+    pub data: [PhysConstraintRaw<'a>; MAX_PHYS_CONSTRAINTS],
 }
 assert_size!(PhysConstraintsRaw, 2696);
 
+pub const MAX_PHYS_CONSTRAINTS: usize = 16;
+
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PhysConstraints {
     pub name: XString,
     pub count: usize,
@@ -1620,7 +2280,7 @@ impl<'a> XFileDeserializeInto<PhysConstraints, ()> for PhysConstraintsRaw<'a> {
 
         let name = self.name.xfile_deserialize_into(de, ())?;
         //dbg!(&name);
-        Ok(PhysConstraints {
+        let phys_constraints = PhysConstraints {
             name,
             count: self.count as usize,
             data: self
@@ -1628,12 +2288,121 @@ impl<'a> XFileDeserializeInto<PhysConstraints, ()> for PhysConstraintsRaw<'a> {
                 .iter()
                 .map(|r| r.xfile_deserialize_into(de, ()))
                 .collect::<Result<Vec<_>>>()?,
-        })
+        };
+
+        // Only warn, since rejecting constraints the game would load anyway
+        // would make the whole zone unreadable. Use `PhysConstraints::validate`
+        // to check them strictly.
+        if !de.silent() {
+            for violation in phys_constraints.violations(None) {
+                println!("Warning: {violation}");
+            }
+        }
+
+        Ok(phys_constraints)
+    }
+}
+
+impl PhysConstraints {
+    /// Checks every invariant the engine expects of `self`:
+    /// * `count` and the length of `data` are at most
+    ///   [`MAX_PHYS_CONSTRAINTS`], and `count` doesn't exceed the length of
+    ///   `data`.
+    /// * Every used constraint has a valid [`ConstraintType`].
+    /// * Every attach point of type [`AttachPointType::BONE`] names a bone.
+    ///
+    /// Returns [`Err`] with [`ErrorKind::Validation`] containing all
+    /// violations if any are found.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_impl(None)
+    }
+
+    /// Same as [`Self::validate`], but additionally checks that every bone
+    /// referenced by an attach point is in `bone_names` (e.g. the
+    /// [`XModel::bone_names`] of the model the constraints belong to).
+    pub fn validate_with_bones(&self, bone_names: &[XString]) -> Result<()> {
+        self.validate_impl(Some(bone_names))
+    }
+
+    fn validate_impl(&self, bone_names: Option<&[XString]>) -> Result<()> {
+        let violations = self.violations(bone_names);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new(
+                file_line_col!(),
+                ErrorKind::Validation(violations),
+            ))
+        }
+    }
+
+    fn violations(&self, bone_names: Option<&[XString]>) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if self.count > MAX_PHYS_CONSTRAINTS {
+            violations.push(format!(
+                "PhysConstraints: count ({}) > MAX_PHYS_CONSTRAINTS",
+                self.count
+            ));
+        }
+
+        if self.data.len() > MAX_PHYS_CONSTRAINTS {
+            violations.push(format!(
+                "PhysConstraints: data.len() ({}) > MAX_PHYS_CONSTRAINTS",
+                self.data.len()
+            ));
+        }
+
+        if self.count > self.data.len() {
+            violations.push(format!(
+                "PhysConstraints: count ({}) > data.len() ({})",
+                self.count,
+                self.data.len()
+            ));
+        }
+
+        for (i, c) in self.data.iter().take(self.count).enumerate() {
+            if matches!(c.type_, ConstraintType::NUM_TYPES) {
+                violations.push(format!(
+                    "PhysConstraints: data[{i}].type_ (NUM_TYPES) is not a valid type"
+                ));
+            }
+
+            let attach_points = [
+                (1, c.attach_point_type1, &c.target_bone1),
+                (2, c.attach_point_type2, &c.target_bone2),
+            ];
+
+            for (n, attach_point_type, target_bone) in attach_points {
+                if !matches!(attach_point_type, AttachPointType::BONE) {
+                    continue;
+                }
+
+                if target_bone.get().is_empty() {
+                    violations.push(format!(
+                        "PhysConstraints: data[{i}].attach_point_type{n} is BONE, \
+                         but target_bone{n} is empty"
+                    ));
+                } else if let Some(bone_names) = bone_names
+                    && !bone_names.iter().any(|b| b.get() == target_bone.get())
+                {
+                    violations.push(format!(
+                        "PhysConstraints: data[{i}].target_bone{n} ({}) is not a bone",
+                        target_bone.get()
+                    ));
+                }
+            }
+        }
+
+        violations
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for PhysConstraints {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
+        self.validate()?;
+
         let name = XStringRaw::from_str(self.name.get());
         let mut data = self
             .data
@@ -1684,15 +2453,7 @@ impl XFileSerialize<()> for PhysConstraints {
                 })
             })
             .collect::<Result<Vec<PhysConstraintRaw>>>()?;
-        if data.len() > 16 {
-            return Err(Error::new(
-                file_line_col!(),
-                ErrorKind::BrokenInvariant(
-                    "PhysConstraints::data must have 16 or less elements.".to_string(),
-                ),
-            ));
-        }
-        data.resize(16, PhysConstraintRaw::default());
+        data.resize(MAX_PHYS_CONSTRAINTS, PhysConstraintRaw::default());
         let data = data.try_into().unwrap();
         let phys_constraints = PhysConstraintsRaw {
             name,
@@ -1700,6 +2461,7 @@ impl XFileSerialize<()> for PhysConstraints {
             data,
         };
         ser.store_into_xfile(phys_constraints)?;
+        self.name.xfile_serialize(ser, ())?;
         for phys_constraint in self.data.iter() {
             phys_constraint.target_bone1.xfile_serialize(ser, ())?;
             phys_constraint.target_bone2.xfile_serialize(ser, ())?;
@@ -1710,7 +2472,7 @@ impl XFileSerialize<()> for PhysConstraints {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct PhysConstraintRaw<'a> {
     pub targetname: ScriptString,
     #[allow(dead_code)]
@@ -1751,7 +2513,8 @@ pub(crate) struct PhysConstraintRaw<'a> {
 assert_size!(PhysConstraintRaw, 168);
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(i32)]
 pub enum ConstraintType {
     #[default]
@@ -1769,7 +2532,8 @@ pub enum ConstraintType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Default, Debug, FromPrimitive)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, FromPrimitive)]
 #[repr(i32)]
 pub enum AttachPointType {
     #[default]
@@ -1780,7 +2544,8 @@ pub enum AttachPointType {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct PhysConstraint {
     pub targetname: XString,
     pub type_: ConstraintType,
@@ -1807,7 +2572,7 @@ pub struct PhysConstraint {
     pub spin_scale: f32,
     pub min_angle: f32,
     pub max_angle: f32,
-    pub material: Option<Box<Material>>,
+    pub material: Option<Shared<Material>>,
     pub constraint_handle: i32,
     pub rope_index: usize,
     pub centity_num: [i32; 4],
@@ -1882,8 +2647,9 @@ impl<'a> XFileDeserializeInto<PhysConstraint, ()> for PhysConstraintRaw<'a> {
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, XFileRead)]
 pub struct XModelDrawInfo {
     pub lod: u16,
     pub surf_id: u16,
@@ -1891,15 +2657,16 @@ pub struct XModelDrawInfo {
 assert_size!(XModelDrawInfo, 4);
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelPiecesRaw<'a> {
     pub name: XStringRaw<'a>,
     pub pieces: FatPointerCountFirstU32<'a, XModelPieceRaw<'a>>,
 }
 assert_size!(XModelPiecesRaw, 12);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelPieces {
     pub name: XString,
     pub pieces: Vec<XModelPiece>,
@@ -1918,6 +2685,7 @@ impl<'a> XFileDeserializeInto<XModelPieces, ()> for XModelPiecesRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XModelPieces {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let name = XStringRaw::from_str(self.name.get());
@@ -1931,15 +2699,16 @@ impl XFileSerialize<()> for XModelPieces {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Copy, Clone, Default, Debug, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, XFileRead)]
 pub(crate) struct XModelPieceRaw<'a> {
     pub model: Ptr32<'a, XModelRaw<'a>>,
     pub offset: [f32; 3],
 }
 assert_size!(XModelPieceRaw, 16);
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
-#[derive(Clone, Default, Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct XModelPiece {
     pub model: Option<Box<XModel>>,
     pub offset: Vec3,
@@ -1958,6 +2727,7 @@ impl<'a> XFileDeserializeInto<XModelPiece, ()> for XModelPieceRaw<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
 impl XFileSerialize<()> for XModelPiece {
     fn xfile_serialize(&self, ser: &mut impl T5XFileSerialize, _data: ()) -> Result<()> {
         let model = Ptr32::from_box(&self.model);
@@ -0,0 +1,139 @@
+//! Only built with the `audio` family disabled, e.g.:
+//! `cargo test -p t5-xfile-defs --no-default-features --features std,serde,bincode,world,ui`
+#![cfg(not(feature = "audio"))]
+
+use t5_xfile_defs::{
+    Ptr32, Result, ScriptString, T5XFileDeserialize, XFileRead, XFileReader,
+    xasset::{XAsset, XAssetGeneric, XAssetRaw, XAssetType},
+};
+
+/// Deserializes from an in-memory buffer.
+struct SliceDeserializer {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl XFileReader for SliceDeserializer {
+    fn is_little_endian(&self) -> bool {
+        true
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        buf.copy_from_slice(&self.buf[self.pos..self.pos + buf.len()]);
+        self.pos += buf.len();
+        Ok(())
+    }
+}
+
+impl T5XFileDeserialize for SliceDeserializer {
+    fn stream_pos(&mut self) -> Result<u64> {
+        Ok(self.pos as _)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        Ok(self.buf.len() as _)
+    }
+
+    fn silent(&self) -> bool {
+        true
+    }
+
+    fn is_little_endian(&self) -> bool {
+        true
+    }
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T> {
+        T::xfile_read(self)
+    }
+
+    fn get_script_string(&self, _string: ScriptString) -> Result<Option<&str>> {
+        Ok(None)
+    }
+}
+
+fn xasset_raw(asset_type: XAssetType, asset_data: u32) -> XAssetRaw<'static> {
+    XAssetRaw {
+        asset_type: asset_type as _,
+        asset_data: Ptr32::from_u32(asset_data),
+    }
+}
+
+/// An inline raw file named `a`, containing `abc`.
+fn raw_file() -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend(0xFFFFFFFFu32.to_le_bytes());
+    buf.extend(3i32.to_le_bytes());
+    buf.extend(0xFFFFFFFFu32.to_le_bytes());
+    buf.extend(b"a\0abc\0");
+    buf
+}
+
+#[test]
+fn disabled() {
+    assert!(!XAssetType::SOUND.is_enabled());
+    assert!(XAssetType::RAWFILE.is_enabled());
+}
+
+#[test]
+fn blob_ends_zone() {
+    let sound = [0x5A; 16];
+    let mut de = SliceDeserializer {
+        buf: [&sound[..], &raw_file()].concat(),
+        pos: 0,
+    };
+
+    let asset = XAsset::try_get_with_max_local_clients(
+        &mut de,
+        xasset_raw(XAssetType::SOUND, 0xFFFFFFFF),
+        1,
+    )
+    .unwrap();
+    let XAsset::PC(XAssetGeneric::Blob(blob)) = &asset else {
+        panic!("{asset:?}");
+    };
+    assert_eq!(blob.asset_type, XAssetType::SOUND);
+    assert_eq!(blob.data, de.buf);
+    assert!(asset.ends_zone());
+    assert_eq!(de.pos, de.buf.len());
+
+    // same when skipping
+    de.pos = 0;
+    let extent = XAsset::skip(&mut de, xasset_raw(XAssetType::SOUND, 0xFFFFFFFF), 1).unwrap();
+    assert_eq!(extent.range, 0..de.buf.len() as u64);
+    assert!(extent.ends_zone());
+}
+
+#[test]
+fn assets_after_blob() {
+    // null and real assets don't take up anything, so the raw file after
+    // them can still be read
+    for asset_data in [0, 0x10000004] {
+        let mut de = SliceDeserializer {
+            buf: raw_file(),
+            pos: 0,
+        };
+
+        let sound = XAsset::try_get_with_max_local_clients(
+            &mut de,
+            xasset_raw(XAssetType::SOUND, asset_data),
+            1,
+        )
+        .unwrap();
+        assert_eq!(sound.asset_type(), XAssetType::SOUND);
+        assert!(!sound.ends_zone());
+        assert_eq!(de.pos, 0);
+
+        let raw_file = XAsset::try_get_with_max_local_clients(
+            &mut de,
+            xasset_raw(XAssetType::RAWFILE, 0xFFFFFFFF),
+            1,
+        )
+        .unwrap();
+        let XAsset::PC(XAssetGeneric::RawFile(Some(raw_file))) = &raw_file else {
+            panic!("{raw_file:?}");
+        };
+        assert_eq!(raw_file.name.get(), "a");
+        assert_eq!(raw_file.buffer, b"abc\0");
+        assert_eq!(de.pos, de.buf.len());
+    }
+}
@@ -0,0 +1,68 @@
+//! `#[derive(XFileRead)]` for the fixed-layout structs in t5-xfile-defs.
+//!
+//! Fields are read in declaration order with no padding in between, which is
+//! exactly how the Raw structs are laid out on disk. Named fields are
+//! reported to the reader (see `XFileReader::enter_field`) so that they can
+//! be traced; members of tuple structs aren't, since the tuple struct itself
+//! already is.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, parse_macro_input};
+
+#[proc_macro_derive(XFileRead)]
+pub fn derive_xfile_read(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(name, "XFileRead can only be derived for structs")
+            .to_compile_error()
+            .into();
+    };
+
+    let body = match &data.fields {
+        Fields::Named(fields) => {
+            let fields = fields.named.iter().map(|f| {
+                let ident = f.ident.as_ref().unwrap();
+                let label = ident.to_string();
+                let label = label.strip_prefix("r#").unwrap_or(&label);
+                quote! { #ident: ::t5_xfile_defs::read::read_field(r, #label)? }
+            });
+            quote! { Self { #(#fields,)* } }
+        }
+        Fields::Unnamed(fields) => {
+            let fields = fields
+                .unnamed
+                .iter()
+                .map(|_| quote! { ::t5_xfile_defs::XFileRead::xfile_read(r)? });
+            quote! { Self(#(#fields,)*) }
+        }
+        Fields::Unit => quote! { Self },
+    };
+
+    // Bound every field's type rather than every type parameter, so that,
+    // e.g., a `Ptr32<'a, T>` doesn't need `T: XFileRead`.
+    let mut generics = input.generics.clone();
+    if generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        for field in data.fields.iter() {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(syn::parse_quote! { #ty: ::t5_xfile_defs::XFileRead });
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::t5_xfile_defs::XFileRead for #name #ty_generics #where_clause {
+            fn xfile_read<R: ::t5_xfile_defs::XFileReader + ?Sized>(
+                r: &mut R,
+            ) -> ::t5_xfile_defs::Result<Self> {
+                Ok(#body)
+            }
+        }
+    }
+    .into()
+}
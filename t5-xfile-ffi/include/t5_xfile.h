@@ -0,0 +1,61 @@
+/* C bindings for t5-xfile-deserializer. Build the t5-xfile-ffi crate and
+ * link against the resulting static or dynamic library. */
+
+#ifndef T5_XFILE_H
+#define T5_XFILE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum T5XFileStatus {
+    T5_XFILE_OK = 0,
+    T5_XFILE_NULL_POINTER = 1,
+    T5_XFILE_INVALID_ARGUMENT = 2,
+    T5_XFILE_IO = 3,
+    T5_XFILE_BAD_HEADER = 4,
+    T5_XFILE_UNSUPPORTED_PLATFORM = 5,
+    T5_XFILE_INFLATE = 6,
+    T5_XFILE_DESERIALIZE = 7,
+    T5_XFILE_SERIALIZE = 8,
+    T5_XFILE_OUT_OF_RANGE = 9,
+    T5_XFILE_PANIC = 10,
+} T5XFileStatus;
+
+enum {
+    T5_XFILE_PLATFORM_WINDOWS = 0,
+    T5_XFILE_PLATFORM_MACOS = 1,
+    T5_XFILE_PLATFORM_XBOX360 = 2,
+    T5_XFILE_PLATFORM_PS3 = 3,
+    T5_XFILE_PLATFORM_WII = 4,
+};
+
+typedef struct T5XFileHandle T5XFileHandle;
+
+T5XFileStatus t5_xfile_open(const char *path, uint32_t platform,
+                            int allow_unsupported_platforms,
+                            T5XFileHandle **out);
+T5XFileStatus t5_xfile_open_bytes(const uint8_t *data, size_t len,
+                                  uint32_t platform,
+                                  int allow_unsupported_platforms,
+                                  T5XFileHandle **out);
+void t5_xfile_close(T5XFileHandle *handle);
+
+size_t t5_xfile_asset_count(const T5XFileHandle *handle);
+T5XFileStatus t5_xfile_asset_type(const T5XFileHandle *handle, size_t index,
+                                  uint32_t *out);
+const char *t5_xfile_asset_name(const T5XFileHandle *handle, size_t index);
+T5XFileStatus t5_xfile_asset_json(const T5XFileHandle *handle, size_t index,
+                                  char **out);
+void t5_xfile_string_free(char *s);
+
+const char *t5_xfile_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* T5_XFILE_H */
@@ -0,0 +1,370 @@
+//! C bindings. See `include/t5_xfile.h` for the C declarations.
+//!
+//! Every function that can fail returns a [`T5XFileStatus`]. On failure,
+//! [`t5_xfile_last_error`] returns a description of what went wrong. Panics
+//! are caught at the boundary and reported as [`T5XFileStatus::Panic`]
+//! rather than unwinding into C.
+//!
+//! Assets are only available as JSON (see [`t5_xfile_asset_json`]); there's
+//! no CBOR output.
+
+use core::ffi::{c_char, c_int};
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    panic::{self, AssertUnwindSafe},
+};
+
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    t5_xfile_defs::{Error, ErrorKind, XFilePlatform, xasset::XAsset},
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum T5XFileStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidArgument = 2,
+    Io = 3,
+    BadHeader = 4,
+    UnsupportedPlatform = 5,
+    Inflate = 6,
+    Deserialize = 7,
+    Serialize = 8,
+    OutOfRange = 9,
+    Panic = 10,
+}
+
+/// An opened Fastfile and all of its assets.
+pub struct T5XFileHandle {
+    assets: Vec<XAsset>,
+    names: Vec<CString>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(status: T5XFileStatus, message: impl Into<Vec<u8>>) -> T5XFileStatus {
+    let mut message = message.into();
+    message.retain(|&c| c != 0);
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).unwrap_or_default());
+    status
+}
+
+/// Runs `f`, returning `on_panic` instead of unwinding into the caller if
+/// it panics.
+fn catch_panic<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown panic");
+        set_last_error(T5XFileStatus::Panic, format!("panicked: {message}"));
+        on_panic
+    })
+}
+
+fn status_for(e: &Error) -> T5XFileStatus {
+    match e.kind() {
+        ErrorKind::Io(_) => T5XFileStatus::Io,
+        ErrorKind::Inflate(_) => T5XFileStatus::Inflate,
+        ErrorKind::BadHeaderMagic(_)
+        | ErrorKind::WrongVersion(_)
+        | ErrorKind::WrongEndiannessForPlatform(_)
+        | ErrorKind::ProtectedFile(_) => T5XFileStatus::BadHeader,
+        ErrorKind::UnsupportedPlatform(_) | ErrorKind::UnimplementedPlatform(_) => {
+            T5XFileStatus::UnsupportedPlatform
+        }
+        _ => T5XFileStatus::Deserialize,
+    }
+}
+
+fn platform_from_u32(platform: u32) -> Option<XFilePlatform> {
+    match platform {
+        0 => Some(XFilePlatform::Windows),
+        1 => Some(XFilePlatform::macOS),
+        2 => Some(XFilePlatform::Xbox360),
+        3 => Some(XFilePlatform::PS3),
+        4 => Some(XFilePlatform::Wii),
+        _ => None,
+    }
+}
+
+fn open(
+    builder: T5XFileDeserializerBuilder,
+    out: *mut *mut T5XFileHandle,
+) -> Result<(), T5XFileStatus> {
+    let assets = builder
+        .with_silent(true)
+        .build()
+        .and_then(|de| de.inflate())
+        .and_then(|de| de.no_cache())
+        .and_then(|de| de.deserialize_remaining())
+        .map_err(|e| set_last_error(status_for(&e), e.to_string()))?;
+
+    let names = assets
+        .iter()
+        .map(|a| {
+            let mut name = a.name().unwrap_or_default().as_bytes().to_vec();
+            name.retain(|&c| c != 0);
+            CString::new(name).unwrap_or_default()
+        })
+        .collect();
+
+    let handle = Box::new(T5XFileHandle { assets, names });
+    // SAFETY: the caller guarantees `out` is valid, and it was checked for
+    // null before getting here.
+    unsafe { *out = Box::into_raw(handle) };
+    Ok(())
+}
+
+fn status(r: Result<(), T5XFileStatus>) -> T5XFileStatus {
+    r.err().unwrap_or(T5XFileStatus::Ok)
+}
+
+/// Opens and fully deserializes the Fastfile at `path`.
+///
+/// `platform` is `0` for Windows, `1` for macOS, `2` for Xbox 360, `3` for
+/// PS3, and `4` for Wii. Platforms other than Windows are rejected unless
+/// `allow_unsupported_platforms` is non-zero.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string and `out` must be valid for
+/// writes. On success, `*out` must eventually be passed to
+/// [`t5_xfile_close`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_open(
+    path: *const c_char,
+    platform: u32,
+    allow_unsupported_platforms: c_int,
+    out: *mut *mut T5XFileHandle,
+) -> T5XFileStatus {
+    catch_panic(T5XFileStatus::Panic, || {
+        if path.is_null() || out.is_null() {
+            return set_last_error(T5XFileStatus::NullPointer, "path or out is null");
+        }
+        let Some(platform) = platform_from_u32(platform) else {
+            return set_last_error(
+                T5XFileStatus::InvalidArgument,
+                format!("invalid platform {platform}"),
+            );
+        };
+
+        // SAFETY: guaranteed by the caller.
+        let path = unsafe { CStr::from_ptr(path) };
+        let Ok(path) = path.to_str() else {
+            return set_last_error(T5XFileStatus::InvalidArgument, "path isn't valid UTF-8");
+        };
+
+        let mut file = match std::fs::File::open(path) {
+            Ok(f) => f,
+            Err(e) => return set_last_error(T5XFileStatus::Io, e.to_string()),
+        };
+
+        status(open(
+            T5XFileDeserializerBuilder::from_file(
+                &mut file,
+                platform,
+                allow_unsupported_platforms != 0,
+            ),
+            out,
+        ))
+    })
+}
+
+/// Same as [`t5_xfile_open`], but for a Fastfile that's already in memory.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes and `out` must be valid for
+/// writes. On success, `*out` must eventually be passed to
+/// [`t5_xfile_close`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_open_bytes(
+    data: *const u8,
+    len: usize,
+    platform: u32,
+    allow_unsupported_platforms: c_int,
+    out: *mut *mut T5XFileHandle,
+) -> T5XFileStatus {
+    catch_panic(T5XFileStatus::Panic, || {
+        if data.is_null() || out.is_null() {
+            return set_last_error(T5XFileStatus::NullPointer, "data or out is null");
+        }
+        let Some(platform) = platform_from_u32(platform) else {
+            return set_last_error(
+                T5XFileStatus::InvalidArgument,
+                format!("invalid platform {platform}"),
+            );
+        };
+
+        // SAFETY: guaranteed by the caller.
+        let bytes = unsafe { core::slice::from_raw_parts(data, len) };
+
+        status(open(
+            T5XFileDeserializerBuilder::from_bytes(
+                bytes,
+                platform,
+                allow_unsupported_platforms != 0,
+            ),
+            out,
+        ))
+    })
+}
+
+/// Frees a handle returned by [`t5_xfile_open`] or [`t5_xfile_open_bytes`].
+/// Does nothing if `handle` is null.
+///
+/// # Safety
+///
+/// `handle` must be null or a handle that hasn't already been closed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_close(handle: *mut T5XFileHandle) {
+    catch_panic((), || {
+        if !handle.is_null() {
+            // SAFETY: guaranteed by the caller.
+            drop(unsafe { Box::from_raw(handle) });
+        }
+    })
+}
+
+/// Returns the number of assets in the Fastfile, or `0` if `handle` is null.
+///
+/// # Safety
+///
+/// `handle` must be null or a valid handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_asset_count(handle: *const T5XFileHandle) -> usize {
+    catch_panic(0, || {
+        // SAFETY: guaranteed by the caller.
+        unsafe { handle.as_ref() }.map_or(0, |h| h.assets.len())
+    })
+}
+
+/// Gets the `XAssetType` of the asset at `index`.
+///
+/// # Safety
+///
+/// `handle` must be null or a valid handle, and `out` must be valid for
+/// writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_asset_type(
+    handle: *const T5XFileHandle,
+    index: usize,
+    out: *mut u32,
+) -> T5XFileStatus {
+    catch_panic(T5XFileStatus::Panic, || {
+        // SAFETY: guaranteed by the caller.
+        let Some(handle) = (unsafe { handle.as_ref() }) else {
+            return set_last_error(T5XFileStatus::NullPointer, "handle is null");
+        };
+        if out.is_null() {
+            return set_last_error(T5XFileStatus::NullPointer, "out is null");
+        }
+        let Some(asset) = handle.assets.get(index) else {
+            return set_last_error(
+                T5XFileStatus::OutOfRange,
+                format!("index {index} >= {}", handle.assets.len()),
+            );
+        };
+
+        // SAFETY: guaranteed by the caller.
+        unsafe { *out = asset.asset_type() as u32 };
+        T5XFileStatus::Ok
+    })
+}
+
+/// Returns the name of the asset at `index` (empty for null assets), or null
+/// if `handle` is null or `index` is out of range.
+///
+/// The string is owned by `handle` and lives until it's closed.
+///
+/// # Safety
+///
+/// `handle` must be null or a valid handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_asset_name(
+    handle: *const T5XFileHandle,
+    index: usize,
+) -> *const c_char {
+    catch_panic(core::ptr::null(), || {
+        // SAFETY: guaranteed by the caller.
+        unsafe { handle.as_ref() }
+            .and_then(|h| h.names.get(index))
+            .map_or(core::ptr::null(), |n| n.as_ptr())
+    })
+}
+
+/// Serializes the asset at `index` to JSON.
+///
+/// On success, `*out` must eventually be passed to [`t5_xfile_string_free`].
+///
+/// # Safety
+///
+/// `handle` must be null or a valid handle, and `out` must be valid for
+/// writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_asset_json(
+    handle: *const T5XFileHandle,
+    index: usize,
+    out: *mut *mut c_char,
+) -> T5XFileStatus {
+    catch_panic(T5XFileStatus::Panic, || {
+        // SAFETY: guaranteed by the caller.
+        let Some(handle) = (unsafe { handle.as_ref() }) else {
+            return set_last_error(T5XFileStatus::NullPointer, "handle is null");
+        };
+        if out.is_null() {
+            return set_last_error(T5XFileStatus::NullPointer, "out is null");
+        }
+        let Some(asset) = handle.assets.get(index) else {
+            return set_last_error(
+                T5XFileStatus::OutOfRange,
+                format!("index {index} >= {}", handle.assets.len()),
+            );
+        };
+
+        let json = match serde_json::to_string(asset) {
+            Ok(j) => j,
+            Err(e) => return set_last_error(T5XFileStatus::Serialize, e.to_string()),
+        };
+        // JSON strings escape NULs, so this can't fail
+        let json = CString::new(json).unwrap();
+
+        // SAFETY: guaranteed by the caller.
+        unsafe { *out = json.into_raw() };
+        T5XFileStatus::Ok
+    })
+}
+
+/// Frees a string returned by [`t5_xfile_asset_json`]. Does nothing if `s`
+/// is null.
+///
+/// # Safety
+///
+/// `s` must be null or a string returned by [`t5_xfile_asset_json`] that
+/// hasn't already been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn t5_xfile_string_free(s: *mut c_char) {
+    catch_panic((), || {
+        if !s.is_null() {
+            // SAFETY: guaranteed by the caller.
+            drop(unsafe { CString::from_raw(s) });
+        }
+    })
+}
+
+/// Returns a description of the last error that occurred on this thread.
+/// Empty if no error has occurred yet.
+///
+/// The string lives until the next failing call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn t5_xfile_last_error() -> *const c_char {
+    catch_panic(core::ptr::null(), || {
+        LAST_ERROR.with(|e| e.borrow().as_ptr())
+    })
+}
@@ -0,0 +1,92 @@
+//! 010 Editor binary templates (`.bt`).
+//!
+//! Every struct becomes a `typedef struct`, so the template declares no
+//! variables by itself. Apply a struct at the cursor from 010's Template
+//! Results panel, or add declarations at the end of the generated file.
+
+use std::fmt::Write;
+
+use crate::{
+    Layouts, Options,
+    layout::{FieldKind, Primitive},
+};
+
+/// Identifiers 010's C-like syntax reserves that are valid Rust field
+/// names.
+const KEYWORDS: &[&str] = &[
+    "case", "char", "default", "double", "float", "int", "local", "long", "short", "signed",
+    "sizeof", "switch", "typedef", "union", "unsigned", "void",
+];
+
+fn primitive_name(p: Primitive) -> &'static str {
+    match p {
+        Primitive::U8 | Primitive::Bool => "ubyte",
+        Primitive::I8 => "byte",
+        Primitive::U16 => "uint16",
+        Primitive::I16 => "int16",
+        Primitive::U32 => "uint32",
+        Primitive::I32 => "int32",
+        Primitive::U64 => "uint64",
+        Primitive::I64 => "int64",
+        Primitive::F32 => "float",
+        Primitive::F64 => "double",
+    }
+}
+
+fn field_name(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_owned()
+    }
+}
+
+pub fn generate(layouts: &Layouts, options: &Options) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "//------------------------------------------------\n\
+         //--- 010 Editor Binary Template\n\
+         //\n\
+         //   Purpose: Call of Duty: Black Ops (T5) Fastfile structures\n\
+         //    Author: generated by t5-xfile-templates from t5-xfile-defs\n\
+         //------------------------------------------------\n\
+         // Do not edit, regenerate instead.\n"
+    )
+    .unwrap();
+
+    if options.little_endian {
+        writeln!(out, "LittleEndian();\n").unwrap();
+    } else {
+        writeln!(out, "BigEndian();\n").unwrap();
+    }
+    writeln!(out, "typedef uint32 Ptr32 <format=hex>;").unwrap();
+
+    for s in layouts.structs.iter() {
+        writeln!(out, "\n// {}\ntypedef struct {{", s.file).unwrap();
+
+        for f in s.fields.iter() {
+            let (ty, comment) = match &f.kind {
+                FieldKind::Primitive(p) => (primitive_name(*p), None),
+                FieldKind::Ptr(target) => ("Ptr32", Some(format!("-> {target}"))),
+                FieldKind::Struct(name) => (name.as_str(), None),
+                FieldKind::Padding => ("ubyte", Some("implicit padding".to_owned())),
+            };
+
+            write!(out, "    {ty} {}", field_name(&f.name)).unwrap();
+            if f.count != 1 {
+                write!(out, "[{}]", f.count).unwrap();
+            }
+            out.push(';');
+            if let Some(comment) = comment {
+                write!(out, " // {comment}").unwrap();
+            }
+            out.push('\n');
+        }
+
+        writeln!(out, "}} {}; // {} bytes", s.name, s.size).unwrap();
+    }
+
+    out
+}
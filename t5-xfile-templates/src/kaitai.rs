@@ -0,0 +1,105 @@
+//! Kaitai Struct definitions (`.ksy`).
+//!
+//! Every struct becomes an entry under `types`, named in snake case (e.g.
+//! `RawFileRaw` becomes `raw_file_raw`). The top-level type has no `seq`, so
+//! the generated file is meant to be imported by another `.ksy`.
+
+use std::fmt::Write;
+
+use crate::{
+    Layouts, Options,
+    layout::{FieldKind, Primitive},
+    snake_case,
+};
+
+fn primitive_name(p: Primitive) -> &'static str {
+    match p {
+        Primitive::U8 | Primitive::Bool => "u1",
+        Primitive::I8 => "s1",
+        Primitive::U16 => "u2",
+        Primitive::I16 => "s2",
+        Primitive::U32 => "u4",
+        Primitive::I32 => "s4",
+        Primitive::U64 => "u8",
+        Primitive::I64 => "s8",
+        Primitive::F32 => "f4",
+        Primitive::F64 => "f8",
+    }
+}
+
+/// Kaitai identifiers have to start with a lowercase letter.
+fn field_id(name: &str) -> String {
+    let id = snake_case(name.trim_start_matches('_'));
+    if id.starts_with(|c: char| c.is_ascii_lowercase()) {
+        id
+    } else {
+        format!("f{id}")
+    }
+}
+
+pub fn generate(layouts: &Layouts, options: &Options) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "# Generated by t5-xfile-templates from t5-xfile-defs. Do not edit,\n\
+         # regenerate instead.\n\
+         meta:\n  \
+           id: t5_xfile\n  \
+           title: \"Call of Duty: Black Ops (T5) Fastfile structures\"\n  \
+           endian: {}\n\
+         types:",
+        if options.little_endian { "le" } else { "be" }
+    )
+    .unwrap();
+
+    for s in layouts.structs.iter() {
+        writeln!(
+            out,
+            "  {}:\n    doc: \"{} ({}), {} bytes\"",
+            snake_case(&s.name),
+            s.name,
+            s.file,
+            s.size
+        )
+        .unwrap();
+
+        if s.fields.is_empty() {
+            continue;
+        }
+
+        writeln!(out, "    seq:").unwrap();
+        for f in s.fields.iter() {
+            let id = field_id(&f.name);
+            let (ty, doc) = match &f.kind {
+                FieldKind::Primitive(p) => (primitive_name(*p).to_owned(), None),
+                FieldKind::Ptr(target) => ("u4".to_owned(), Some(format!("pointer to {target}"))),
+                FieldKind::Struct(name) => (snake_case(name), None),
+                FieldKind::Padding => {
+                    writeln!(
+                        out,
+                        "      - id: {id}\n        size: {}\n        doc: \"implicit padding\"",
+                        f.size
+                    )
+                    .unwrap();
+                    continue;
+                }
+            };
+
+            writeln!(out, "      - id: {id}\n        type: {ty}").unwrap();
+            if f.count != 1 {
+                writeln!(
+                    out,
+                    "        repeat: expr\n        repeat-expr: {}",
+                    f.count
+                )
+                .unwrap();
+            }
+            if let Some(doc) = doc {
+                writeln!(out, "        doc: \"{doc}\"").unwrap();
+            }
+        }
+    }
+
+    out
+}
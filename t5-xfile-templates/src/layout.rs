@@ -0,0 +1,787 @@
+//! Recovers the on-disk layout of the Raw structs from t5-xfile-defs' source.
+//!
+//! Layouts follow C's alignment rules, like the game's own structs: each
+//! field is aligned to its own alignment, and each struct is padded to a
+//! multiple of its most aligned field's. Some Raw structs rely on Rust
+//! inserting the same padding for `assert_size!` to pass, so the padding is
+//! made explicit (see [`FieldKind::Padding`]). Every struct checked
+//! with `assert_size!` is resolved twice: once with the generic arguments
+//! given to `assert_size!`, to check the recovered size against the
+//! asserted one, and once with `MAX_LOCAL_CLIENTS` set for the target
+//! platform, which is the layout that gets emitted.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use quote::ToTokens;
+use syn::{
+    Attribute, BinOp, Expr, GenericArgument, GenericParam, Item, ItemStruct, Lit, Meta,
+    PathArguments, Token, Type,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+};
+
+use crate::{Error, Options, Result};
+
+/// Features t5-xfile-defs is assumed to be built with when evaluating
+/// `#[cfg]`s. The `d3d9` variants are replaced by placeholders otherwise.
+const ENABLED_FEATURES: &[&str] = &["serde", "std"];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+    Bool,
+}
+
+impl Primitive {
+    fn from_ident(ident: &str) -> Option<Self> {
+        Some(match ident {
+            "u8" => Self::U8,
+            "i8" => Self::I8,
+            "u16" => Self::U16,
+            "i16" => Self::I16,
+            "u32" => Self::U32,
+            "i32" => Self::I32,
+            // what `assert_size!` sees, and what bincode writes
+            "u64" | "usize" => Self::U64,
+            "i64" | "isize" => Self::I64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "bool" => Self::Bool,
+            _ => return None,
+        })
+    }
+
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::I8 | Self::Bool => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::U64 | Self::I64 | Self::F64 => 8,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    Primitive(Primitive),
+    /// A [`Ptr32`](t5_xfile_defs::Ptr32) (or `XStringRaw`) to the named
+    /// type.
+    Ptr(String),
+    /// Another struct in [`Layouts::structs`].
+    Struct(String),
+    /// Bytes the C compiler inserted to align the next field (or the end of
+    /// the struct), which don't have a field of their own in the Raw struct.
+    Padding,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+    /// Number of elements, flattened across every array dimension. 1 for
+    /// fields that aren't arrays.
+    pub count: usize,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Struct {
+    pub name: String,
+    /// The file in t5-xfile-defs the struct is defined in, e.g. `misc.rs`.
+    pub file: String,
+    pub fields: Vec<Field>,
+    pub size: usize,
+    pub align: usize,
+}
+
+impl Struct {
+    /// Whether any of the struct's fields is [`FieldKind::Padding`].
+    pub fn has_implicit_padding(&self) -> bool {
+        self.fields.iter().any(|f| f.kind == FieldKind::Padding)
+    }
+}
+
+/// The recovered layouts, along with whatever couldn't be recovered.
+#[derive(Clone, Debug, Default)]
+pub struct Layouts {
+    /// Every resolved struct, each one after the structs it contains.
+    pub structs: Vec<Struct>,
+    /// `assert_size!`-checked types that couldn't be resolved, and why.
+    pub skipped: Vec<(String, String)>,
+    /// `assert_size!`-checked types whose recovered size doesn't match the
+    /// asserted one, as `(name, asserted, recovered)`.
+    pub mismatched: Vec<(String, usize, usize)>,
+}
+
+impl Layouts {
+    /// Parses every `.rs` file in `dir`, which should be t5-xfile-defs'
+    /// `src` directory.
+    pub fn from_dir(dir: impl AsRef<Path>, options: &Options) -> Result<Self> {
+        let mut paths = fs::read_dir(dir.as_ref())?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.retain(|p| p.extension().is_some_and(|e| e == "rs"));
+        paths.sort();
+
+        let mut source = Source::default();
+        for path in paths {
+            let text = fs::read_to_string(&path)?;
+            let file = syn::parse_file(&text).map_err(|e| Error::Parse(path.clone(), e))?;
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            source.add_items(&name, file.items);
+        }
+
+        Ok(source.layouts(options))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Struct> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+}
+
+struct SizeAssertion {
+    ty: Type,
+    size: Expr,
+}
+
+impl Parse for SizeAssertion {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let size = input.parse()?;
+        Ok(Self { ty, size })
+    }
+}
+
+#[derive(Default)]
+struct Source {
+    structs: HashMap<String, (String, ItemStruct)>,
+    aliases: HashMap<String, Type>,
+    consts: HashMap<String, Expr>,
+    /// `bitflags!` types and `#[repr]` enums.
+    reprs: HashMap<String, Primitive>,
+    assertions: Vec<SizeAssertion>,
+}
+
+fn cfg_matches(meta: &Meta) -> bool {
+    match meta {
+        Meta::NameValue(nv) if nv.path.is_ident("feature") => match &nv.value {
+            Expr::Lit(l) => match &l.lit {
+                Lit::Str(s) => ENABLED_FEATURES.contains(&s.value().as_str()),
+                _ => false,
+            },
+            _ => false,
+        },
+        Meta::List(list) => {
+            let Ok(nested) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+            else {
+                return false;
+            };
+            if list.path.is_ident("not") {
+                !nested.iter().all(cfg_matches)
+            } else if list.path.is_ident("all") {
+                nested.iter().all(cfg_matches)
+            } else if list.path.is_ident("any") {
+                nested.iter().any(cfg_matches)
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+fn is_enabled(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("cfg"))
+        .all(|a| a.parse_args::<Meta>().is_ok_and(|m| cfg_matches(&m)))
+}
+
+fn repr(attrs: &[Attribute]) -> Option<Primitive> {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("repr"))
+        .find_map(|a| Primitive::from_ident(&a.parse_args::<syn::Ident>().ok()?.to_string()))
+}
+
+fn tokens(t: &impl ToTokens) -> String {
+    t.to_token_stream().to_string()
+}
+
+/// The identifier of a single-segment path type, e.g. `T` or `N`.
+fn single_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) if p.qself.is_none() && p.path.segments.len() == 1 => {
+            Some(p.path.segments[0].ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The non-lifetime generic arguments of a path's last segment.
+fn generic_args(path: &syn::Path) -> Vec<&GenericArgument> {
+    match &path.segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(a) => a
+            .args
+            .iter()
+            .filter(|a| !matches!(a, GenericArgument::Lifetime(_)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `name` is one of t5-xfile-defs' helpers for pointers with a
+/// count, as opposed to a struct from the game.
+fn is_wrapper(name: &str) -> bool {
+    ["FatPointer", "FlexibleArray", "Ptr32Array"]
+        .iter()
+        .any(|w| name.starts_with(w))
+}
+
+/// Whether `ident` appears in `ty` anywhere other than as the target of a
+/// pointer, i.e. whether it can change `ty`'s layout.
+fn affects_layout(ty: &Type, ident: &str) -> bool {
+    match ty {
+        Type::Array(a) => affects_layout(&a.elem, ident),
+        Type::Paren(p) => affects_layout(&p.elem, ident),
+        Type::Group(g) => affects_layout(&g.elem, ident),
+        Type::Tuple(t) => t.elems.iter().any(|e| affects_layout(e, ident)),
+        Type::Path(p) => {
+            let last = p.path.segments.last().unwrap();
+            if p.path.segments.len() == 1 && last.ident == ident {
+                return true;
+            }
+            if last.ident == "Ptr32" || last.ident == "PhantomData" {
+                return false;
+            }
+            generic_args(&p.path).into_iter().any(|a| match a {
+                GenericArgument::Type(t) => affects_layout(t, ident),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Whether the constant `ident` is used as an array length in `ty` (or in
+/// the generic arguments of a struct in it), other than behind a pointer.
+fn sizes_array(ty: &Type, ident: &str) -> bool {
+    let mentions = |e: &Expr| {
+        tokens(e)
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .any(|w| w == ident)
+    };
+
+    match ty {
+        Type::Array(a) => mentions(&a.len) || sizes_array(&a.elem, ident),
+        Type::Paren(p) => sizes_array(&p.elem, ident),
+        Type::Group(g) => sizes_array(&g.elem, ident),
+        Type::Tuple(t) => t.elems.iter().any(|e| sizes_array(e, ident)),
+        Type::Path(p) => {
+            let last = p.path.segments.last().unwrap();
+            if last.ident == "Ptr32" || last.ident == "PhantomData" {
+                return false;
+            }
+            generic_args(&p.path).into_iter().any(|a| match a {
+                GenericArgument::Type(t) => {
+                    single_ident(t).is_some_and(|t| t == ident) || sizes_array(t, ident)
+                }
+                GenericArgument::Const(e) => mentions(e),
+                _ => false,
+            })
+        }
+        _ => false,
+    }
+}
+
+impl Source {
+    fn add_items(&mut self, file: &str, items: Vec<Item>) {
+        for item in items {
+            match item {
+                Item::Struct(s) if is_enabled(&s.attrs) => {
+                    self.structs
+                        .entry(s.ident.to_string())
+                        .or_insert_with(|| (file.to_owned(), s));
+                }
+                Item::Enum(e) if is_enabled(&e.attrs) => {
+                    if let Some(r) = repr(&e.attrs) {
+                        self.reprs.insert(e.ident.to_string(), r);
+                    }
+                }
+                Item::Type(t) if is_enabled(&t.attrs) => {
+                    self.aliases.insert(t.ident.to_string(), *t.ty);
+                }
+                Item::Const(c) if is_enabled(&c.attrs) => {
+                    self.consts.insert(c.ident.to_string(), *c.expr);
+                }
+                Item::Macro(m) if is_enabled(&m.attrs) => {
+                    let Some(name) = m.mac.path.segments.last().map(|s| s.ident.to_string()) else {
+                        continue;
+                    };
+                    if name == "assert_size" {
+                        if let Ok(a) = m.mac.parse_body::<SizeAssertion>() {
+                            self.assertions.push(a);
+                        }
+                    } else if name == "bitflags" {
+                        self.add_bitflags(m.mac.tokens);
+                    }
+                }
+                Item::Mod(m) if is_enabled(&m.attrs) => {
+                    if let Some((_, items)) = m.content {
+                        self.add_items(file, items);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Picks `struct Name: Repr` out of a `bitflags!` invocation.
+    fn add_bitflags(&mut self, tokens: proc_macro2::TokenStream) {
+        use proc_macro2::TokenTree;
+
+        let tokens = tokens.into_iter().collect::<Vec<_>>();
+        for w in tokens.windows(4) {
+            if let [
+                TokenTree::Ident(s),
+                TokenTree::Ident(name),
+                TokenTree::Punct(colon),
+                TokenTree::Ident(ty),
+            ] = w
+                && s == "struct"
+                && colon.as_char() == ':'
+                && let Some(p) = Primitive::from_ident(&ty.to_string())
+            {
+                self.reprs.insert(name.to_string(), p);
+            }
+        }
+    }
+
+    fn layouts(&self, options: &Options) -> Layouts {
+        let mut checker = Resolver::new(self, None);
+        let mut emitter = Resolver::new(self, Some(options.max_local_clients));
+        let mut layouts = Layouts::default();
+        let mut seen = HashSet::new();
+
+        for a in self.assertions.iter() {
+            let name = tokens(&a.ty).replace(' ', "");
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+
+            let checked = checker
+                .eval(&a.size, &Env::new())
+                .and_then(|size| Ok((size, checker.resolve(&a.ty, &Env::new())?)));
+            let (asserted, resolved) = match checked {
+                Ok(c) => c,
+                Err(e) => {
+                    layouts.skipped.push((name, e));
+                    continue;
+                }
+            };
+            if resolved.size != asserted {
+                layouts.mismatched.push((name, asserted, resolved.size));
+                continue;
+            }
+
+            if let Err(e) = emitter.resolve(&a.ty, &Env::new()) {
+                layouts.skipped.push((name, e));
+            }
+        }
+
+        // only keep the wrappers that didn't get inlined
+        let referenced = emitter
+            .order
+            .iter()
+            .flat_map(|s| s.fields.iter())
+            .filter_map(|f| match &f.kind {
+                FieldKind::Struct(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        layouts.structs = emitter
+            .order
+            .into_iter()
+            .filter(|s| !is_wrapper(&s.name) || referenced.contains(&s.name))
+            .collect();
+        layouts
+    }
+}
+
+#[derive(Clone)]
+enum Bound {
+    Type(Box<Type>, Env),
+    Const(usize),
+}
+
+type Env = HashMap<String, Bound>;
+
+struct Resolved {
+    /// [`None`] for zero-sized types.
+    kind: Option<FieldKind>,
+    count: usize,
+    size: usize,
+    align: usize,
+}
+
+struct Resolver<'s> {
+    source: &'s Source,
+    max_local_clients: Option<usize>,
+    order: Vec<Struct>,
+    index: BTreeMap<String, usize>,
+    visiting: HashSet<String>,
+}
+
+impl<'s> Resolver<'s> {
+    fn new(source: &'s Source, max_local_clients: Option<usize>) -> Self {
+        Self {
+            source,
+            max_local_clients,
+            order: Vec::new(),
+            index: BTreeMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    fn eval(&self, expr: &Expr, env: &Env) -> core::result::Result<usize, String> {
+        match expr {
+            Expr::Lit(l) => match &l.lit {
+                Lit::Int(i) => i.base10_parse().map_err(|e| e.to_string()),
+                _ => Err(format!("can't evaluate `{}`", tokens(expr))),
+            },
+            Expr::Paren(p) => self.eval(&p.expr, env),
+            Expr::Group(g) => self.eval(&g.expr, env),
+            Expr::Block(b) if b.block.stmts.len() == 1 => match &b.block.stmts[0] {
+                syn::Stmt::Expr(e, None) => self.eval(e, env),
+                _ => Err(format!("can't evaluate `{}`", tokens(expr))),
+            },
+            Expr::Binary(b) => {
+                let (l, r) = (self.eval(&b.left, env)?, self.eval(&b.right, env)?);
+                match b.op {
+                    BinOp::Add(_) => Ok(l + r),
+                    BinOp::Sub(_) => Ok(l - r),
+                    BinOp::Mul(_) => Ok(l * r),
+                    BinOp::Div(_) if r != 0 => Ok(l / r),
+                    _ => Err(format!("can't evaluate `{}`", tokens(expr))),
+                }
+            }
+            Expr::Path(p) if p.path.segments.len() == 1 => {
+                self.eval_ident(&p.path.segments[0].ident.to_string(), env)
+            }
+            _ => Err(format!("can't evaluate `{}`", tokens(expr))),
+        }
+    }
+
+    fn eval_ident(&self, ident: &str, env: &Env) -> core::result::Result<usize, String> {
+        match env.get(ident) {
+            Some(Bound::Const(n)) => Ok(*n),
+            Some(Bound::Type(..)) => Err(format!("`{ident}` is a type")),
+            None => match self.source.consts.get(ident) {
+                Some(e) => self.eval(e, &Env::new()),
+                None => Err(format!("unknown constant `{ident}`")),
+            },
+        }
+    }
+
+    /// Evaluates a const generic argument, which syn parses as a type if
+    /// it's a bare identifier.
+    fn eval_arg(&self, arg: &GenericArgument, env: &Env) -> core::result::Result<usize, String> {
+        match arg {
+            GenericArgument::Const(e) => self.eval(e, env),
+            GenericArgument::Type(t) => match single_ident(t) {
+                Some(ident) => self.eval_ident(&ident, env),
+                None => Err(format!("can't evaluate `{}`", tokens(t))),
+            },
+            _ => Err(format!("can't evaluate `{}`", tokens(arg))),
+        }
+    }
+
+    /// The name a pointer's target is shown as.
+    fn target_name(&self, ty: &Type, env: &Env) -> String {
+        match ty {
+            Type::Tuple(t) if t.elems.is_empty() => "void".to_owned(),
+            Type::Array(a) => format!(
+                "{}[{}]",
+                self.target_name(&a.elem, env),
+                self.eval(&a.len, env)
+                    .map_or_else(|_| tokens(&a.len), |n| n.to_string())
+            ),
+            Type::Path(p) => {
+                let ident = p.path.segments.last().unwrap().ident.to_string();
+                match env.get(&ident) {
+                    Some(Bound::Type(t, env)) if p.path.segments.len() == 1 => {
+                        self.target_name(t, env)
+                    }
+                    _ if ident == "XStringRaw" => "XString".to_owned(),
+                    _ => ident,
+                }
+            }
+            _ => tokens(ty),
+        }
+    }
+
+    fn resolve(&mut self, ty: &Type, env: &Env) -> core::result::Result<Resolved, String> {
+        let zero_sized = Resolved {
+            kind: None,
+            count: 0,
+            size: 0,
+            align: 1,
+        };
+
+        let p = match ty {
+            Type::Paren(p) => return self.resolve(&p.elem, env),
+            Type::Group(g) => return self.resolve(&g.elem, env),
+            Type::Tuple(t) if t.elems.is_empty() => return Ok(zero_sized),
+            Type::Array(a) => {
+                let n = self.eval(&a.len, env)?;
+                let elem = self.resolve(&a.elem, env)?;
+                return Ok(Resolved {
+                    kind: elem.kind,
+                    count: elem.count * n,
+                    size: elem.size * n,
+                    align: elem.align,
+                });
+            }
+            Type::Path(p) if p.qself.is_none() => p,
+            _ => return Err(format!("unsupported type `{}`", tokens(ty))),
+        };
+
+        let ident = p.path.segments.last().unwrap().ident.to_string();
+        let one = |kind, size, align| Resolved {
+            kind: Some(kind),
+            count: 1,
+            size,
+            align,
+        };
+
+        if p.path.segments.len() == 1
+            && let Some(bound) = env.get(&ident)
+        {
+            return match bound {
+                Bound::Type(t, env) => self.resolve(t, env),
+                Bound::Const(_) => Err(format!("`{ident}` is a constant")),
+            };
+        }
+
+        if let Some(prim) = Primitive::from_ident(&ident) {
+            return Ok(one(FieldKind::Primitive(prim), prim.size(), prim.size()));
+        }
+
+        match ident.as_str() {
+            "PhantomData" => return Ok(zero_sized),
+            "Ptr32" => {
+                let target = match generic_args(&p.path).first() {
+                    Some(GenericArgument::Type(t)) => self.target_name(t, env),
+                    _ => "void".to_owned(),
+                };
+                return Ok(one(FieldKind::Ptr(target), 4, 4));
+            }
+            "XStringRaw" => return Ok(one(FieldKind::Ptr("XString".to_owned()), 4, 4)),
+            _ => {}
+        }
+
+        if let Some(prim) = self.source.reprs.get(&ident) {
+            return Ok(one(FieldKind::Primitive(*prim), prim.size(), prim.size()));
+        }
+
+        if let Some(alias) = self.source.aliases.get(&ident) {
+            return self.resolve(alias, &Env::new());
+        }
+
+        if self.source.structs.contains_key(&ident) {
+            let index = self.instantiate(&ident, &generic_args(&p.path), env)?;
+            let s = &self.order[index];
+            return Ok(if s.size == 0 {
+                zero_sized
+            } else {
+                one(FieldKind::Struct(s.name.clone()), s.size, s.align)
+            });
+        }
+
+        Err(format!("unknown type `{ident}`"))
+    }
+
+    fn align_of(&self, kind: &FieldKind) -> usize {
+        match kind {
+            FieldKind::Primitive(p) => p.size(),
+            FieldKind::Ptr(_) => 4,
+            FieldKind::Struct(name) => self.order[self.index[name]].align,
+            FieldKind::Padding => 1,
+        }
+    }
+
+    /// Resolves the struct `ident` with the generic arguments `args`, and
+    /// returns its index into [`Self::order`].
+    fn instantiate(
+        &mut self,
+        ident: &str,
+        args: &[&GenericArgument],
+        env: &Env,
+    ) -> core::result::Result<usize, String> {
+        let source = self.source;
+        let (file, item) = &source.structs[ident];
+
+        let mut inner = Env::new();
+        let mut name = ident.to_owned();
+        let params = item
+            .generics
+            .params
+            .iter()
+            .filter(|p| !matches!(p, GenericParam::Lifetime(_)));
+
+        for (i, param) in params.enumerate() {
+            match param {
+                GenericParam::Const(c) => {
+                    let param = c.ident.to_string();
+                    let value = match (self.max_local_clients, args.get(i)) {
+                        (Some(n), _) if param == "MAX_LOCAL_CLIENTS" => n,
+                        (_, Some(arg)) => self.eval_arg(arg, env)?,
+                        (_, None) => match &c.default {
+                            Some(d) => self.eval(d, &inner)?,
+                            None => return Err(format!("{ident}: missing `{param}`")),
+                        },
+                    };
+                    // MAX_LOCAL_CLIENTS is the same throughout, but other
+                    // constants can differ between uses of the same struct
+                    if param != "MAX_LOCAL_CLIENTS"
+                        && item.fields.iter().any(|f| sizes_array(&f.ty, &param))
+                    {
+                        name.push_str(&format!("_{value}"));
+                    }
+                    inner.insert(param, Bound::Const(value));
+                }
+                GenericParam::Type(t) => {
+                    let param = t.ident.to_string();
+                    let bound = match (args.get(i), &t.default) {
+                        (Some(GenericArgument::Type(arg)), _) => {
+                            Bound::Type(Box::new((*arg).clone()), env.clone())
+                        }
+                        (None, Some(d)) => Bound::Type(Box::new(d.clone()), inner.clone()),
+                        _ => return Err(format!("{ident}: missing `{param}`")),
+                    };
+                    if item.fields.iter().any(|f| affects_layout(&f.ty, &param))
+                        && let Bound::Type(t, env) = &bound
+                    {
+                        name.push('_');
+                        name.push_str(&self.target_name(t, env).replace(['[', ']'], "_"));
+                    }
+                    inner.insert(param, bound);
+                }
+                GenericParam::Lifetime(_) => unreachable!(),
+            }
+        }
+
+        if let Some(&index) = self.index.get(&name) {
+            return Ok(index);
+        }
+        if !self.visiting.insert(name.clone()) {
+            return Err(format!("{name} contains itself"));
+        }
+
+        // (name, kind, count, size, align)
+        let mut members = Vec::new();
+        for (i, field) in item.fields.iter().enumerate() {
+            if !is_enabled(&field.attrs) {
+                continue;
+            }
+
+            let field_name = match &field.ident {
+                Some(f) => f.to_string().trim_start_matches("r#").to_owned(),
+                None => format!("_{i}"),
+            };
+            let resolved = self
+                .resolve(&field.ty, &inner)
+                .map_err(|e| format!("{name}.{field_name}: {e}"))?;
+            let Some(kind) = resolved.kind.filter(|_| resolved.size != 0) else {
+                continue;
+            };
+
+            // wrappers stand in for adjacent members of the C struct, so
+            // they don't get aligned as a whole
+            if let FieldKind::Struct(wrapper) = &kind
+                && resolved.count == 1
+                && is_wrapper(wrapper)
+            {
+                let wrapper = &self.order[self.index[wrapper]];
+                for f in wrapper
+                    .fields
+                    .iter()
+                    .filter(|f| f.kind != FieldKind::Padding)
+                {
+                    let align = self.align_of(&f.kind);
+                    members.push((
+                        format!("{field_name}_{}", f.name),
+                        f.kind.clone(),
+                        f.count,
+                        f.size,
+                        align,
+                    ));
+                }
+            } else {
+                members.push((
+                    field_name,
+                    kind,
+                    resolved.count,
+                    resolved.size,
+                    resolved.align,
+                ));
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut size = 0;
+        let mut align = 1;
+        let pad = |fields: &mut Vec<Field>, size: &mut usize, to: usize| {
+            let n = size.next_multiple_of(to) - *size;
+            if n != 0 {
+                fields.push(Field {
+                    name: format!("_pad{}", fields.len()),
+                    kind: FieldKind::Padding,
+                    count: n,
+                    size: n,
+                });
+                *size += n;
+            }
+        };
+
+        for (field_name, kind, count, field_size, field_align) in members {
+            pad(&mut fields, &mut size, field_align);
+            align = align.max(field_align);
+            size += field_size;
+            fields.push(Field {
+                name: field_name,
+                kind,
+                count,
+                size: field_size,
+            });
+        }
+
+        pad(&mut fields, &mut size, align);
+        self.visiting.remove(&name);
+
+        let s = Struct {
+            name: name.clone(),
+            file: file.clone(),
+            fields,
+            size,
+            align,
+        };
+        self.index.insert(name, self.order.len());
+        self.order.push(s);
+        Ok(self.order.len() - 1)
+    }
+}
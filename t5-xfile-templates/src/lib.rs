@@ -0,0 +1,98 @@
+//! Generates 010 Editor binary templates and Kaitai Struct definitions from
+//! the Raw structs in t5-xfile-defs.
+//!
+//! The Raw structs are the exact on-disk layout of everything in a
+//! Fastfile, and every one that matters is checked with `assert_size!`.
+//! Rather than maintaining templates for external tools by hand, this crate
+//! parses t5-xfile-defs' source, recovers the layout of every
+//! `assert_size!`-checked struct (and of every struct they contain), checks
+//! the recovered size against the asserted one, and writes the result out
+//! in the tool's format. Regenerating the templates after changing a Raw
+//! struct keeps them in sync.
+//!
+//! Pointers are emitted as plain 32-bit integers, annotated with the type
+//! they point to, since where the pointed-to data actually lives depends on
+//! the order the deserializer loads things in.
+
+pub mod bt;
+pub mod kaitai;
+pub mod layout;
+
+use std::path::{Path, PathBuf};
+
+use t5_xfile_defs::XFilePlatform;
+
+pub use layout::Layouts;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// One of t5-xfile-defs' source files couldn't be parsed.
+    Parse(PathBuf, syn::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{e}"),
+            Self::Parse(path, e) => write!(f, "{}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// Which variant of the layouts to generate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub little_endian: bool,
+    /// Consoles support split-screen, so their Fastfiles have room for 4
+    /// local clients where PC Fastfiles only have room for 1.
+    pub max_local_clients: usize,
+}
+
+impl Options {
+    pub const fn new(platform: XFilePlatform) -> Self {
+        Self {
+            little_endian: platform.is_le(),
+            max_local_clients: platform.max_local_clients(),
+        }
+    }
+}
+
+/// t5-xfile-defs' `src` directory, as of when this crate was built.
+pub fn defs_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join("t5-xfile-defs")
+        .join("src")
+}
+
+/// Converts a Rust type name to the snake case Kaitai Struct requires, e.g.
+/// `XModelRaw` to `x_model_raw`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i != 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if (prev.is_ascii_lowercase() || prev.is_ascii_digit() || next_is_lower) && prev != '_'
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    snake
+}
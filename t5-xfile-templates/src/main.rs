@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use t5_xfile_defs::XFilePlatform;
+use t5_xfile_templates::{Layouts, Options, bt, defs_dir, kaitai};
+
+use clap::{arg, command};
+
+fn main() {
+    let matches = command!()
+        .arg(
+            arg!(
+                -p --platform <PLATFORM>
+                "Specifies which platform to generate templates for"
+            )
+            .value_parser(clap::value_parser!(XFilePlatform)),
+        )
+        .arg(
+            arg!(--defs <DIR> "t5-xfile-defs' src directory")
+                .required(false)
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--bt <FILE> "Writes an 010 Editor binary template to FILE")
+                .required(false)
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--ksy <FILE> "Writes a Kaitai Struct definition to FILE")
+                .required(false)
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+        .get_matches();
+
+    let Some(&platform) = matches.get_one::<XFilePlatform>("platform") else {
+        println!(
+            "must specify the platform to generate templates for \
+             (-p/--platform, see --help for a list of valid platforms)"
+        );
+        return;
+    };
+    if platform == XFilePlatform::Wii {
+        println!("{platform} isn't supported yet");
+        return;
+    }
+
+    let options = Options::new(platform);
+    let dir = matches
+        .get_one::<PathBuf>("defs")
+        .cloned()
+        .unwrap_or_else(defs_dir);
+
+    let layouts = match Layouts::from_dir(&dir, &options) {
+        Ok(l) => l,
+        Err(e) => {
+            println!("{e}");
+            return;
+        }
+    };
+
+    for (name, reason) in layouts.skipped.iter() {
+        eprintln!("skipped {name}: {reason}");
+    }
+    for (name, asserted, recovered) in layouts.mismatched.iter() {
+        eprintln!("skipped {name}: asserted {asserted} bytes, recovered {recovered}");
+    }
+
+    for s in layouts.structs.iter().filter(|s| s.has_implicit_padding()) {
+        eprintln!("note: {} ({}) has implicit padding", s.name, s.file);
+    }
+
+    if let Some(path) = matches.get_one::<PathBuf>("bt") {
+        std::fs::write(path, bt::generate(&layouts, &options)).unwrap();
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("ksy") {
+        std::fs::write(path, kaitai::generate(&layouts, &options)).unwrap();
+    }
+
+    println!("{} structs", layouts.structs.len());
+}
@@ -0,0 +1,54 @@
+use t5_xfile_defs::XFilePlatform;
+use t5_xfile_templates::{Layouts, Options, bt, defs_dir, kaitai, layout::FieldKind};
+
+fn layouts(platform: XFilePlatform) -> Layouts {
+    Layouts::from_dir(defs_dir(), &Options::new(platform)).unwrap()
+}
+
+#[test]
+fn sizes_match_assertions() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::Xbox360] {
+        let layouts = layouts(platform);
+        assert_eq!(layouts.mismatched, []);
+        // the D3D9 handles are asserted against a constant defined in an impl
+        assert!(
+            layouts
+                .skipped
+                .iter()
+                .all(|(name, _)| name.starts_with("D3D9"))
+        );
+    }
+}
+
+#[test]
+fn max_local_clients() {
+    let pc = layouts(XFilePlatform::Windows);
+    let console = layouts(XFilePlatform::Xbox360);
+    assert_eq!(pc.get("GfxWorldRaw").unwrap().size, 1084);
+    assert!(console.get("GfxWorldRaw").unwrap().size > 1084);
+}
+
+#[test]
+fn fat_pointers_are_inlined() {
+    let layouts = layouts(XFilePlatform::Windows);
+    let node = layouts.get("PathNodeConstantRaw").unwrap();
+    let names = node
+        .fields
+        .iter()
+        .map(|f| f.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names[names.len() - 2..], ["links_size", "links_p"]);
+    assert!(node.fields.iter().any(|f| f.kind == FieldKind::Padding));
+}
+
+#[test]
+fn generate() {
+    let options = Options::new(XFilePlatform::Windows);
+    let layouts = Layouts::from_dir(defs_dir(), &options).unwrap();
+
+    let bt = bt::generate(&layouts, &options);
+    assert!(bt.contains("    Ptr32 buffer; // -> u8\n} RawFileRaw; // 12 bytes\n"));
+
+    let ksy = kaitai::generate(&layouts, &options);
+    assert!(ksy.contains("  raw_file_raw:\n    doc: \"RawFileRaw (misc.rs), 12 bytes\"\n"));
+}
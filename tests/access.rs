@@ -0,0 +1,94 @@
+use t5_xfile_defs::{
+    Shared, XString,
+    access::{HasImages, HasMaterials, HasName},
+    font::Font,
+    techset::{GfxImage, Material, MaterialInfo, MaterialTextureDef, MaterialTextureDefInfo},
+    xasset::{XAsset, XAssetGeneric},
+    xmodel::XModel,
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn material(name: &str, images: &[&str]) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: xstring(name),
+            ..Default::default()
+        },
+        textures: images
+            .iter()
+            .map(|image| MaterialTextureDef {
+                u: MaterialTextureDefInfo::Image(Some(Box::new(GfxImage {
+                    name: xstring(image),
+                    ..Default::default()
+                }))),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Generic over every asset with materials.
+fn material_names(asset: &(impl HasMaterials + ?Sized)) -> Vec<&str> {
+    asset.materials().into_iter().map(|m| m.name()).collect()
+}
+
+#[test]
+fn accessors() {
+    let model = XModel {
+        name: xstring("rock"),
+        material_handles: vec![
+            Shared::new(material("mc/mtl_rock", &["~-grock", "~-grock_n"])),
+            Shared::new(material("mc/mtl_moss", &[])),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(model.name(), "rock");
+    assert_eq!(material_names(&model), ["mc/mtl_rock", "mc/mtl_moss"]);
+
+    let font = Font {
+        font_name: xstring("fonts/normalfont"),
+        pixel_height: 16,
+        material: Some(Shared::new(material("fonts/normalfont", &[]))),
+        glow_material: None,
+        glyphs: Vec::new(),
+    };
+    assert_eq!(font.name(), "fonts/normalfont");
+    assert_eq!(material_names(&font), ["fonts/normalfont"]);
+
+    let images = model.material_handles[0]
+        .images()
+        .into_iter()
+        .map(|i| i.name())
+        .collect::<Vec<_>>();
+    assert_eq!(images, ["~-grock", "~-grock_n"]);
+}
+
+#[test]
+fn xasset_accessors() {
+    let model = XAsset::PC(XAssetGeneric::XModel(Some(Box::new(XModel {
+        material_handles: vec![Shared::new(material("mc/mtl_rock", &["~-grock"]))],
+        ..Default::default()
+    }))));
+    assert_eq!(
+        material_names(model.as_has_materials().unwrap()),
+        ["mc/mtl_rock"]
+    );
+    assert!(model.as_has_images().is_none());
+
+    let material = XAsset::Console(XAssetGeneric::Material(Some(Box::new(material(
+        "mc/mtl_rock",
+        &["~-grock"],
+    )))));
+    assert_eq!(material.as_has_images().unwrap().images().len(), 1);
+    assert!(material.as_has_materials().is_none());
+
+    assert!(
+        XAsset::PC(XAssetGeneric::XModel(None))
+            .as_has_materials()
+            .is_none()
+    );
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::{ErrorKind, XFilePlatform, xasset::XAssetType};
+use t5_xfile_deserializer::testgen::SyntheticXFile;
+
+use common::try_deserialize_with;
+
+fn deserialize(
+    xfile: &SyntheticXFile,
+    limit: Option<usize>,
+) -> t5_xfile_defs::Result<Vec<t5_xfile_defs::xasset::XAsset>> {
+    try_deserialize_with(xfile, |builder| match limit {
+        Some(limit) => builder.with_allocation_limit(limit),
+        None => builder,
+    })
+}
+
+fn assert_broken_invariant(result: t5_xfile_defs::Result<Vec<t5_xfile_defs::xasset::XAsset>>) {
+    let err = result.unwrap_err();
+    assert!(
+        matches!(err.kind(), ErrorKind::BrokenInvariant(_)),
+        "{err:?}"
+    );
+}
+
+/// A pack index claiming far more entries than there are bytes left is
+/// rejected, with or without a limit.
+#[test]
+fn oversized_count() {
+    let mut asset = Vec::new();
+    // name (inline), magic, timestamp, count, alignment, data_start,
+    // entries (inline)
+    for field in [u32::MAX, 0, 0, 0x1000_0000, 0, 0, u32::MAX] {
+        asset.extend_from_slice(&field.to_le_bytes());
+    }
+    asset.extend_from_slice(b"sound.pak\0");
+    let xfile =
+        SyntheticXFile::new(XFilePlatform::Windows).with_raw_asset(XAssetType::PACKINDEX, asset);
+
+    assert_broken_invariant(deserialize(&xfile, None));
+    assert_broken_invariant(deserialize(&xfile, Some(usize::MAX)));
+}
+
+#[test]
+fn limit() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", &[b' '; 80])
+        .with_rawfile("maps/b.gsc", &[b' '; 80]);
+
+    assert_eq!(deserialize(&xfile, None).unwrap().len(), 2);
+    // the limit is per asset
+    assert_eq!(deserialize(&xfile, Some(100)).unwrap().len(), 2);
+    assert_broken_invariant(deserialize(&xfile, Some(64)));
+}
@@ -0,0 +1,158 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    common::{Vec3, Vec4},
+    xanim::{XAnimIndices, XAnimParts},
+    xmodel::{DObjAnimMat, XModel},
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn assert_close(a: Vec3, b: [f32; 3]) {
+    let a = a.get();
+    assert!((0..3).all(|i| (a[i] - b[i]).abs() < 1e-3), "{a:?} != {b:?}");
+}
+
+fn bind(trans: [f32; 3]) -> DObjAnimMat {
+    DObjAnimMat {
+        quat: Vec4::from([0.0, 0.0, 0.0, 1.0]),
+        trans: Vec3::from(trans),
+        trans_weight: 2.0,
+    }
+}
+
+/// `tag_origin`, then `j_arm` 10 units along X, then `j_hand` 5 more.
+fn model() -> XModel {
+    XModel {
+        num_bones: 3,
+        num_root_bones: 1,
+        bone_names: ["tag_origin", "j_arm", "j_hand"].map(xstring).to_vec(),
+        parent_list: vec![1, 1],
+        quats: vec![0, 0, 0, 32767, 0, 0, 0, 32767],
+        trans: vec![10.0, 0.0, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0],
+        base_mat: vec![
+            bind([0.0, 0.0, 0.0]),
+            bind([10.0, 0.0, 0.0]),
+            bind([15.0, 0.0, 0.0]),
+        ],
+        ..Default::default()
+    }
+}
+
+/// Over one second, `j_arm` turns 90 degrees left and moves from 10 to 20
+/// units along X. `j_hand` is held 8 units along the arm, and `j_tail`
+/// isn't in the model.
+fn anim() -> XAnimParts {
+    let q = 23170;
+    let floats = |f: &[f32]| f.iter().map(|f| f.to_bits() as i32).collect::<Vec<_>>();
+
+    XAnimParts {
+        name: xstring("anim_wave"),
+        numframes: 10,
+        loop_: false,
+        delta: false,
+        left_hand_grip_ik: false,
+        streamable: false,
+        streamed_file_size: 0,
+        // no quat, half quat, full quat, half quat (1), full quat (1), small
+        // trans, trans, trans (1), no trans, all
+        bone_count: [1, 1, 0, 0, 1, 1, 0, 1, 1, 3],
+        notify_count: 0,
+        asset_type: 0,
+        is_default: false,
+        index_count: 4,
+        framerate: 10.0,
+        frequency: 0.1,
+        primed_length: 0.0,
+        loop_entry_time: 0.0,
+        names: ["j_tail", "j_arm", "j_hand"].map(xstring).to_vec(),
+        data_byte: vec![1, 2],
+        data_short: vec![1, 0, 0, 0, 32767, 1],
+        data_int: floats(&[10.0, 0.0, 0.0, 0.1, 0.0, 0.0, 8.0, 0.0, 0.0]),
+        random_data_short: vec![0, 32767, q, q],
+        random_data_byte: vec![0, 0, 0, 100, 0, 0],
+        random_data_int: Vec::new(),
+        indices: XAnimIndices::_1(vec![0, 10, 0, 10]),
+        notify: Vec::new(),
+        delta_part: None,
+    }
+}
+
+#[test]
+fn tracks() {
+    let tracks = anim().tracks().unwrap();
+    assert_eq!(tracks.len(), 3);
+    assert_eq!(tracks[0].name, "j_tail");
+    assert!(tracks[0].rotation.is_none() && tracks[0].translation.is_none());
+
+    let rotation = tracks[1].rotation.as_ref().unwrap();
+    assert_eq!(rotation.frames, [0, 10]);
+    assert_eq!(rotation.values[0].get()[..3], [0.0; 3]);
+
+    let translation = tracks[1].translation.as_ref().unwrap();
+    assert_eq!(translation.frames, [0, 10]);
+    assert_close(translation.values[1], [20.0, 0.0, 0.0]);
+
+    let hand = tracks[2].translation.as_ref().unwrap();
+    assert_close(hand.values[0], [8.0, 0.0, 0.0]);
+    assert_eq!(hand.frames, [0]);
+}
+
+#[test]
+fn bind_and_sample() {
+    let anim = anim();
+    let skeleton = model().skeleton().unwrap();
+    let bound = anim.bind(&skeleton).unwrap();
+    assert_eq!(bound.bones, [None, Some(1), Some(2)]);
+    assert_eq!(bound.unbound_tracks().collect::<Vec<_>>(), ["j_tail"]);
+    assert_eq!(anim.duration(), 1.0);
+
+    let origin = Vec3::from([0.0; 3]);
+    let start = bound.sample(0.0);
+    assert_close(start[1].transform_point(origin), [10.0, 0.0, 0.0]);
+    assert_close(start[2].transform_point(origin), [18.0, 0.0, 0.0]);
+
+    let middle = bound.sample(0.5);
+    assert_close(middle[1].transform_point(origin), [15.0, 0.0, 0.0]);
+
+    let end = bound.sample(1.0);
+    assert_close(end[1].transform_point(origin), [20.0, 0.0, 0.0]);
+    assert_close(end[2].transform_point(origin), [20.0, 8.0, 0.0]);
+    // past the end of the animation
+    assert_eq!(bound.sample(5.0), end);
+
+    // the hand's vertices follow it
+    let skinning = bound.skinning_matrices(0.0);
+    assert_close(
+        skinning[2].transform_point(Vec3::from([15.0, 1.0, 0.0])),
+        [18.0, 1.0, 0.0],
+    );
+    assert_close(skinning[0].transform_point(origin), [0.0; 3]);
+}
+
+#[test]
+fn frames() {
+    let mut anim = anim();
+    let skeleton = model().skeleton().unwrap();
+    assert_eq!(anim.bind(&skeleton).unwrap().frame_at(2.0), 10.0);
+    assert_eq!(anim.bind(&skeleton).unwrap().frame_at(-1.0), 0.0);
+
+    anim.loop_ = true;
+    let bound = anim.bind(&skeleton).unwrap();
+    assert_eq!(bound.frame_at(1.5), 5.0);
+    assert_eq!(bound.frame_at(-0.25), 7.5);
+}
+
+#[test]
+fn truncated() {
+    let mut anim = anim();
+    anim.data_int.truncate(6);
+    let err = anim.tracks().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+
+    let mut anim = self::anim();
+    anim.data_byte[1] = 7;
+    let err = anim.tracks().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+}
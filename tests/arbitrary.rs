@@ -0,0 +1,63 @@
+//! Runs the same check as `fuzz/fuzz_targets/roundtrip.rs` over a fixed set
+//! of inputs, so the harness is built and exercised without cargo-fuzz.
+//!
+//! Arbitrary values of most asset types break invariants that the raw
+//! structs don't check yet (that's what the fuzz target is for), so only
+//! types without any are generated here.
+
+#![cfg(all(
+    feature = "serializer",
+    feature = "deserializer",
+    feature = "arbitrary"
+))]
+
+use arbitrary::{Arbitrary, Unstructured};
+use t5_xfile_defs::{
+    LocalizeEntry, XFilePlatform,
+    fx::FxEffectDef,
+    misc::XGlobals,
+    xasset::{XAsset, XAssetGeneric},
+    xmodel::PhysConstraints,
+};
+use t5_xfile_deserializer::roundtrip::{self, RoundTripOutcome};
+
+/// `len` pseudo-random bytes, the same for the same `seed`.
+fn input(seed: u64, len: usize) -> Vec<u8> {
+    // xorshift64*
+    let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545F4914F6CDD1D) >> 56) as u8
+        })
+        .collect()
+}
+
+/// Round-trips arbitrary `T`s as assets, and returns how many of them were
+/// checked (the rest were rejected by the serializer).
+fn check<T: for<'a> Arbitrary<'a>>(asset: impl Fn(Option<Box<T>>) -> XAssetGeneric) -> usize {
+    let mut checked = 0;
+    for seed in 0..256 {
+        let input = input(seed, 4096);
+        let Ok(t) = Option::<Box<T>>::arbitrary(&mut Unstructured::new(&input)) else {
+            continue;
+        };
+
+        let report = roundtrip::check_asset(&XAsset::PC(asset(t)), XFilePlatform::Windows);
+        if !matches!(report.outcome, RoundTripOutcome::SerializeFailed(_)) {
+            assert!(report.is_ok(), "seed {seed}: {report:#?}");
+            checked += 1;
+        }
+    }
+    checked
+}
+
+#[test]
+fn roundtrip() {
+    assert!(check::<LocalizeEntry>(XAssetGeneric::LocalizeEntry) > 0);
+    assert!(check::<XGlobals>(XAssetGeneric::XGlobals) > 0);
+    assert!(check::<FxEffectDef>(XAssetGeneric::Fx) > 0);
+    assert!(check::<PhysConstraints>(XAssetGeneric::PhysConstraints) > 0);
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "deserializer")]
+
+use std::path::PathBuf;
+
+use t5_xfile_deserializer::{
+    batch::{Batch, process_dir},
+    testgen::SyntheticXFile,
+};
+
+use t5_xfile_defs::{XFilePlatform, xasset::XAssetType};
+
+fn install(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-batch-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let files = [
+        ("zone/Common/a.ff", "maps/a.gsc"),
+        ("zone/Common/b.ff", "maps/b.gsc"),
+        ("zone/English/c.FF", "maps/c.gsc"),
+    ];
+    for (file, rawfile) in files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let xfile =
+            SyntheticXFile::new(XFilePlatform::Windows).with_rawfile(rawfile, b"main() { }");
+        std::fs::write(path, xfile.to_bytes()).unwrap();
+    }
+    std::fs::write(dir.join("zone/Common/broken.ff"), b"not a Fastfile").unwrap();
+    std::fs::write(dir.join("zone/Common/a.ipak"), b"not a Fastfile either").unwrap();
+
+    dir
+}
+
+#[test]
+fn process() {
+    let dir = install("process");
+
+    let mut names = Vec::new();
+    let report = process_dir(&dir, XFilePlatform::Windows, |bag| {
+        assert_eq!(bag.len(), 1);
+        names.extend(bag.iter().map(|a| a.name().unwrap_or_default().to_string()));
+    })
+    .unwrap();
+    names.sort();
+    assert_eq!(names, ["maps/a.gsc", "maps/b.gsc", "maps/c.gsc"]);
+
+    assert_eq!(
+        report.processed,
+        [
+            dir.join("zone/Common/a.ff"),
+            dir.join("zone/Common/b.ff"),
+            dir.join("zone/English/c.FF"),
+        ]
+    );
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, dir.join("zone/Common/broken.ff"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn options() {
+    let dir = install("options");
+
+    for threads in [1, 2, 8] {
+        let batch = Batch::new(&dir, XFilePlatform::Windows)
+            .with_threads(threads)
+            .with_max_pending(0);
+        assert_eq!(batch.paths().unwrap().len(), 4);
+
+        let mut paths = Vec::new();
+        let report = batch
+            .run(|path, bag| {
+                assert!(
+                    bag.get(XAssetType::RAWFILE, "maps/a.gsc").is_some() == path.ends_with("a.ff")
+                );
+                paths.push(path.to_path_buf());
+            })
+            .unwrap();
+        paths.sort();
+        assert_eq!(paths, report.processed);
+    }
+
+    assert!(process_dir(dir.join("missing"), XFilePlatform::Windows, |_| ()).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
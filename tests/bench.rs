@@ -0,0 +1,48 @@
+#![cfg(feature = "bench")]
+
+use t5_xfile_defs::XFilePlatform;
+use t5_xfile_deserializer::bench::{BenchZone, measure};
+
+#[test]
+fn bench_zone() {
+    let zone = BenchZone::new(XFilePlatform::Windows)
+        .with_script_strings(10)
+        .with_rawfiles(2, 100)
+        .with_pack_index_entries(50)
+        .with_menu_key_handlers(50);
+    assert_eq!(zone.to_synthetic().asset_types().count(), 4);
+
+    let m = zone.bench_deserialize(3).unwrap();
+    assert_eq!(m.times.len(), 3);
+    assert_eq!(m.bytes, zone.to_synthetic().payload().len());
+    assert!(m.min() <= m.median());
+
+    #[cfg(feature = "serializer")]
+    {
+        assert_eq!(zone.bench_serialize(1).unwrap().times.len(), 1);
+        assert!(
+            BenchZone::new(XFilePlatform::Windows)
+                .with_script_strings(1)
+                .bench_serialize(1)
+                .is_err()
+        );
+    }
+}
+
+#[test]
+fn measure_stops_at_errors() {
+    let mut runs = 0;
+    let result = measure("fails", 0, 5, || {
+        runs += 1;
+        if runs == 3 {
+            Err(t5_xfile_defs::Error::new(
+                String::new(),
+                t5_xfile_defs::ErrorKind::Todo(String::new()),
+            ))
+        } else {
+            Ok(())
+        }
+    });
+    assert!(result.is_err());
+    assert_eq!(runs, 3);
+}
@@ -0,0 +1,248 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    xasset::{XAsset, XAssetGeneric, XAssetRef},
+};
+use t5_xfile_deserializer::{
+    T5XFileBorrowedDeserializer, T5XFileDeserializerBuilder, testgen::SyntheticXFile,
+};
+
+/// Checks that the raw file's buffer is borrowed from `payload`, and that
+/// the localize entry is owned.
+fn check(assets: &[XAssetRef], payload: &[u8]) {
+    assert_eq!(assets.len(), 2);
+
+    let XAssetRef::RawFile(Some(raw_file)) = &assets[0] else {
+        panic!("{:?}", assets[0]);
+    };
+    assert_eq!(raw_file.name.get(), "maps/a.gsc");
+    assert_eq!(raw_file.buffer, b"main() { }\0");
+    assert!(payload.as_ptr_range().contains(&raw_file.buffer.as_ptr()));
+    assert!(raw_file.buffer.as_ptr_range().end <= payload.as_ptr_range().end);
+
+    let XAssetRef::Owned(XAsset::PC(XAssetGeneric::LocalizeEntry(Some(entry)))) = &assets[1] else {
+        panic!("{:?}", assets[1]);
+    };
+    assert_eq!(entry.value.get(), "Start Game");
+}
+
+#[test]
+fn buffers_point_into_payload() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let bytes = xfile.to_bytes();
+    let inflated = || {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+    };
+
+    let payload = inflated().into_payload();
+    let assets = T5XFileBorrowedDeserializer::new(&payload, xfile.platform(), true)
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    check(&assets, &payload);
+
+    // the same assets borrowed from the deserializer itself
+    let de = inflated();
+    assert_eq!(
+        de.borrowed().unwrap().deserialize_remaining().unwrap(),
+        assets
+    );
+}
+
+/// Shader programs, sound samples and model vertices written by the
+/// serializer are borrowed from the payload, and copy back into the same
+/// assets the owned deserializer returns.
+#[cfg(feature = "serializer")]
+#[test]
+fn blobs_point_into_payload() {
+    use t5_xfile_defs::{
+        T5XFileSerialize, XString,
+        common::Vec3,
+        sound::{
+            LoadedSound, SndAlias, SndAliasList, SndAsset, SndAssetChannel, SndAssetFlags,
+            SndAssetFormat, SndBank, SoundFile, SoundFileRef,
+        },
+        techset::{
+            GfxPixelShaderLoadDef, GfxVertexShaderLoadDef, MaterialPass, MaterialPixelShader,
+            MaterialPixelShaderProgram, MaterialTechnique, MaterialTechniqueSet,
+            MaterialVertexShader, MaterialVertexShaderProgram,
+        },
+        xmodel::{GfxPackedVertex, XModel, XSurface, XSurfaceVertexInfo},
+    };
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    let techset = MaterialTechniqueSet {
+        name: XString("wc_default".to_string()),
+        world_vert_format: 0,
+        techset_flags: 0,
+        techniques: vec![Box::new(MaterialTechnique {
+            name: XString("lit".to_string()),
+            flags: 0,
+            passes: vec![MaterialPass {
+                vertex_decl: Some(Box::default()),
+                vertex_shader: Some(Box::new(MaterialVertexShader {
+                    name: XString("vs".to_string()),
+                    prog: MaterialVertexShaderProgram {
+                        vs: None,
+                        load_def: GfxVertexShaderLoadDef {
+                            program: vec![0xFFFE0300; 64],
+                        },
+                    },
+                })),
+                pixel_shader: Some(Box::new(MaterialPixelShader {
+                    name: XString("ps".to_string()),
+                    prog: MaterialPixelShaderProgram {
+                        ps: None,
+                        load_def: GfxPixelShaderLoadDef {
+                            program: vec![0xFFFF0300; 32],
+                        },
+                    },
+                })),
+                per_prim_arg_count: 0,
+                per_obj_arg_count: 0,
+                stable_arg_count: 0,
+                custom_sampler_flags: 0,
+                args: Vec::new(),
+            }],
+        })],
+    };
+    let mut model = XModel {
+        name: XString("m".to_string()),
+        surfs: vec![XSurface {
+            tri_indices: vec![0, 1, 2],
+            vert_info: XSurfaceVertexInfo {
+                vert_count: [3, 0, 0, 0],
+                verts_blend: vec![0; 3],
+                tension_data: vec![0.0; 36],
+            },
+            verts0: vec![
+                GfxPackedVertex {
+                    xyz: Vec3::from([1.0, 2.0, 3.0]),
+                    binormal_sign: -1.0,
+                    ..Default::default()
+                };
+                3
+            ],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    model.recompute_counts();
+    let bank = SndBank {
+        name: XString("mp_a.all".to_string()),
+        aliases: vec![SndAliasList {
+            name: XString("amb_wind".to_string()),
+            aliases: vec![SndAlias {
+                name: XString("amb_wind".to_string()),
+                sound_file: Some(Box::new(SoundFile {
+                    u: SoundFileRef::Loaded(Some(Box::new(LoadedSound {
+                        name: XString("wind.wav".to_string()),
+                        sound: SndAsset {
+                            version: 14,
+                            frame_count: 512,
+                            frame_rate: 44100,
+                            channel_count: 1,
+                            header_size: 0,
+                            block_size: 2,
+                            buffer_size: 1024,
+                            format: SndAssetFormat::PCMS16,
+                            channel_flags: SndAssetChannel::C,
+                            flags: SndAssetFlags::LOOPING,
+                            seek_table: vec![0; 2],
+                            data: vec![0x5A; 1024],
+                        },
+                    }))),
+                    exists: true,
+                })),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        alias_index: Vec::new(),
+        pack_hash: 0,
+        pack_location: 0,
+        radverbs: Vec::new(),
+        snapshots: Vec::new(),
+    };
+
+    let mut xfile = SyntheticXFile::new(XFilePlatform::Windows);
+    for asset in [
+        XAssetGeneric::TechniqueSet(Some(Box::new(techset))),
+        XAssetGeneric::XModel(Some(Box::new(model))),
+        XAssetGeneric::Sound(Some(Box::new(bank))),
+    ] {
+        let asset_type = asset.asset_type();
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .build()
+            .unwrap();
+        ser.serialize_assets(std::iter::once(XAsset::PC(asset)))
+            .unwrap();
+        xfile = xfile.with_raw_asset(asset_type, ser.asset_bytes().unwrap()[8..].to_vec());
+    }
+    let bytes = xfile.to_bytes();
+    let inflated = || {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+    };
+
+    let payload = inflated().into_payload();
+    let in_payload = |blob: &[u8]| {
+        assert!(!blob.is_empty());
+        assert!(payload.as_ptr_range().contains(&blob.as_ptr()));
+        assert!(blob.as_ptr_range().end <= payload.as_ptr_range().end);
+    };
+    let assets = T5XFileBorrowedDeserializer::new(&payload, xfile.platform(), true)
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(assets.len(), 3);
+
+    let XAssetRef::TechniqueSet(Some(techset)) = &assets[0] else {
+        panic!("{:?}", assets[0]);
+    };
+    let pass = &techset.techniques[0].passes[0];
+    let vs = pass.vertex_shader.as_ref().unwrap().program;
+    assert_eq!(vs.len(), 64 * 4);
+    in_payload(vs);
+    in_payload(pass.pixel_shader.as_ref().unwrap().program);
+
+    let XAssetRef::XModel(Some(model)) = &assets[1] else {
+        panic!("{:?}", assets[1]);
+    };
+    assert!(model.model.surfs.is_empty());
+    assert_eq!(model.surfs[0].verts0.len(), 3 * 32);
+    in_payload(model.surfs[0].verts0);
+
+    let XAssetRef::Sound(Some(bank)) = &assets[2] else {
+        panic!("{:?}", assets[2]);
+    };
+    let loaded = bank.aliases[0].aliases[0].loaded.as_ref().unwrap();
+    assert_eq!(loaded.sound.data, [0x5A; 1024]);
+    in_payload(loaded.sound.data);
+
+    let owned = inflated()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(
+        assets
+            .into_iter()
+            .map(|a| a.into_owned(xfile.platform()))
+            .collect::<Vec<_>>(),
+        owned
+    );
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "deserializer")]
+
+use std::io::Read;
+
+use t5_xfile_defs::{XFilePlatform, xasset::XAsset};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+fn deserialize(builder: T5XFileDeserializerBuilder) -> t5_xfile_defs::Result<Vec<XAsset>> {
+    builder
+        .with_silent(true)
+        .build()?
+        .inflate()?
+        .no_cache()?
+        .deserialize_remaining()
+}
+
+/// Only implements [`Read`], so nothing can seek.
+struct ReadOnly<'a>(&'a [u8]);
+
+impl Read for ReadOnly<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // a few bytes at a time, like a slow stream
+        let n = buf.len().min(self.0.len()).min(7);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn sources_agree() {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-builder-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for platform in [XFilePlatform::Windows, XFilePlatform::Xbox360] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_rawfile("maps/a.gsc", b"main() { }")
+            .with_localize_entry("MENU_START_GAME", "Start Game")
+            .with_string_table("mp/t.csv", 2, &["a", "b", "c", ""]);
+        let bytes = xfile.to_bytes();
+        let path = dir.join(format!("{platform:?}.ff"));
+        std::fs::write(&path, &bytes).unwrap();
+
+        let from_bytes = deserialize(T5XFileDeserializerBuilder::from_bytes(
+            &bytes, platform, true,
+        ))
+        .unwrap();
+        assert_eq!(from_bytes.len(), 3);
+        let from_reader = deserialize(T5XFileDeserializerBuilder::from_reader(
+            ReadOnly(&bytes),
+            platform,
+            true,
+        ))
+        .unwrap();
+        assert_eq!(from_reader, from_bytes);
+        let mut file = std::fs::File::open(&path).unwrap();
+        let from_file = deserialize(T5XFileDeserializerBuilder::from_file(
+            &mut file, platform, true,
+        ))
+        .unwrap();
+        assert_eq!(from_file, from_bytes);
+
+        // and they all fail the same way on a truncated file
+        let truncated = &bytes[..bytes.len() / 2];
+        std::fs::write(&path, truncated).unwrap();
+        let mut file = std::fs::File::open(&path).unwrap();
+        let errors = [
+            deserialize(T5XFileDeserializerBuilder::from_bytes(
+                truncated, platform, true,
+            )),
+            deserialize(T5XFileDeserializerBuilder::from_reader(
+                ReadOnly(truncated),
+                platform,
+                true,
+            )),
+            deserialize(T5XFileDeserializerBuilder::from_file(
+                &mut file, platform, true,
+            )),
+        ]
+        .map(|r| r.unwrap_err().kind().to_string());
+        assert_eq!(errors[0], errors[1]);
+        assert_eq!(errors[0], errors[2]);
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,115 @@
+use std::fmt::Debug;
+
+use t5_xfile_defs::{
+    BulkPrimitive, Ptr32, Result, ScriptString, T5XFileDeserialize, XArray, XFileRead, XFileReader,
+};
+
+/// Deserializes from an in-memory buffer, with only the required methods
+/// implemented, so arrays are read the slow way.
+struct SliceDeserializer {
+    buf: Vec<u8>,
+    pos: usize,
+    little_endian: bool,
+}
+
+impl XFileReader for SliceDeserializer {
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        buf.copy_from_slice(&self.buf[self.pos..self.pos + buf.len()]);
+        self.pos += buf.len();
+        Ok(())
+    }
+}
+
+impl T5XFileDeserialize for SliceDeserializer {
+    fn stream_pos(&mut self) -> Result<u64> {
+        Ok(self.pos as _)
+    }
+
+    fn stream_len(&mut self) -> Result<u64> {
+        Ok(self.buf.len() as _)
+    }
+
+    fn silent(&self) -> bool {
+        true
+    }
+
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn load_from_xfile<T: XFileRead>(&mut self) -> Result<T> {
+        T::xfile_read(self)
+    }
+
+    fn get_script_string(&self, _string: ScriptString) -> Result<Option<&str>> {
+        Ok(None)
+    }
+}
+
+/// Reads the start of `bytes` as `count` `T`s, both in bulk and one by one.
+fn read_both<T: BulkPrimitive + XFileRead + Debug>(
+    bytes: &[u8],
+    count: usize,
+    little_endian: bool,
+) -> (Vec<T>, Vec<T>) {
+    let array = Ptr32::<T>::from_u32(0xFFFFFFFF).to_array(count);
+    let de = || SliceDeserializer {
+        buf: bytes.to_vec(),
+        pos: 0,
+        little_endian,
+    };
+
+    let mut bulk_de = de();
+    let bulk = array.to_vec_bulk(&mut bulk_de).unwrap();
+    let mut each_de = de();
+    let each = array.to_vec(&mut each_de).unwrap();
+    assert_eq!(bulk_de.pos, each_de.pos);
+    (bulk, each)
+}
+
+#[test]
+fn same_as_per_element() {
+    let bytes = (0..64u8).map(|b| b.wrapping_mul(37)).collect::<Vec<_>>();
+
+    for little_endian in [true, false] {
+        let (bulk, each) = read_both::<u8>(&bytes, 64, little_endian);
+        assert_eq!(bulk, each);
+        let (bulk, each) = read_both::<u16>(&bytes, 32, little_endian);
+        assert_eq!(bulk, each);
+        let (bulk, each) = read_both::<i16>(&bytes, 31, little_endian);
+        assert_eq!(bulk, each);
+        let (bulk, each) = read_both::<u32>(&bytes, 16, little_endian);
+        assert_eq!(bulk, each);
+        let (bulk, each) = read_both::<i32>(&bytes, 15, little_endian);
+        assert_eq!(bulk, each);
+        // compared bitwise, since some of them are NaN
+        let (bulk, each) = read_both::<f32>(&bytes, 16, little_endian);
+        assert_eq!(
+            bulk.iter().map(|f| f.to_bits()).collect::<Vec<_>>(),
+            each.iter().map(|f| f.to_bits()).collect::<Vec<_>>()
+        );
+    }
+
+    let (le, _) = read_both::<u32>(&[1, 2, 3, 4], 1, true);
+    let (be, _) = read_both::<u32>(&[1, 2, 3, 4], 1, false);
+    assert_eq!((le[0], be[0]), (0x04030201, 0x01020304));
+}
+
+#[test]
+fn not_inline() {
+    let mut de = SliceDeserializer {
+        buf: vec![1; 8],
+        pos: 0,
+        little_endian: true,
+    };
+    for p in [0, 0x10000004] {
+        let array = Ptr32::<u32>::from_u32(p).to_array(2);
+        assert!(array.to_vec_bulk(&mut de).unwrap().is_empty());
+        assert!(array.to_vec(&mut de).unwrap().is_empty());
+    }
+    assert_eq!(de.pos, 0);
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "cache")]
+
+use std::path::PathBuf;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{
+    CACHE_MAGIC, CACHE_VERSION, CacheSuccess, T5XFileDeserializerBuilder, build_cache, cache_info,
+    cache_is_fresh, cache_path_for, testgen::SyntheticXFile,
+};
+
+/// Writes a Fastfile with one raw file to a fresh directory, and returns its
+/// path.
+fn install(name: &str, contents: &[u8]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-cache-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let path = dir.join("a.ff");
+    write(&path, contents);
+    path
+}
+
+fn write(path: &PathBuf, contents: &[u8]) {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows).with_rawfile("maps/a.gsc", contents);
+    std::fs::write(path, xfile.to_bytes()).unwrap();
+}
+
+fn raw_file(cache_path: &PathBuf) -> Vec<u8> {
+    let mut file = std::fs::File::open(cache_path).unwrap();
+    let assets =
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+    match assets.into_iter().next().unwrap() {
+        XAsset::PC(XAssetGeneric::RawFile(Some(raw_file))) => raw_file.buffer,
+        asset => panic!("{asset:?}"),
+    }
+}
+
+/// Caches from older versions are stale, and get replaced.
+#[test]
+fn old_versions() {
+    let path = install("old", b"main() { }");
+    let cache_path = cache_path_for(&path);
+    let platform = XFilePlatform::Windows;
+
+    // Version 1 was just the inflated payload, and version 2 had the same
+    // magic as now.
+    let inflated =
+        T5XFileDeserializerBuilder::from_bytes(&std::fs::read(&path).unwrap(), platform, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .into_payload();
+    let v2 = [&CACHE_MAGIC[..], &2u32.to_le_bytes(), &[0; 64]].concat();
+    assert_eq!(CACHE_VERSION, 3);
+
+    for old in [inflated, v2] {
+        std::fs::write(&cache_path, old).unwrap();
+        assert!(!cache_is_fresh(&cache_path, &path, platform).unwrap());
+        let err = cache_info(&cache_path).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::BadCacheFile(_)), "{err:?}");
+
+        let success = build_cache(&path, platform, true, false).unwrap();
+        assert!(matches!(success, Some(CacheSuccess::CacheOverwritten)));
+        assert!(cache_is_fresh(&cache_path, &path, platform).unwrap());
+        assert_eq!(cache_info(&cache_path).unwrap().assets, 1);
+        assert_eq!(raw_file(&cache_path), b"main() { }\0");
+    }
+}
+
+#[test]
+fn stale() {
+    let path = install("stale", b"main() { }");
+    let cache_path = cache_path_for(&path);
+    let platform = XFilePlatform::Windows;
+
+    assert!(!cache_is_fresh(&cache_path, &path, platform).unwrap());
+    let success = build_cache(&path, platform, true, false).unwrap();
+    assert!(matches!(success, Some(CacheSuccess::CacheCreated)));
+    assert!(cache_is_fresh(&cache_path, &path, platform).unwrap());
+    // already fresh
+    assert!(build_cache(&path, platform, true, false).unwrap().is_none());
+    // but not for another platform
+    assert!(!cache_is_fresh(&cache_path, &path, XFilePlatform::Xbox360).unwrap());
+
+    // same length, different contents
+    write(&path, b"main(){ 1}");
+    assert!(!cache_is_fresh(&cache_path, &path, platform).unwrap());
+    build_cache(&path, platform, true, false).unwrap().unwrap();
+    assert_eq!(raw_file(&cache_path), b"main(){ 1}\0");
+}
+
+/// Lengths in a corrupt header aren't trusted any further than the file.
+#[test]
+fn corrupt_lengths() {
+    let path = install("corrupt", b"main() { }");
+    let cache_path = cache_path_for(&path);
+    let platform = XFilePlatform::Windows;
+    build_cache(&path, platform, true, false).unwrap();
+
+    // The script strings' count comes right after the magic, version,
+    // platform, source and the XFile.
+    let mut cache = std::fs::read(&cache_path).unwrap();
+    let info = cache_info(&cache_path).unwrap();
+    assert_eq!(info.script_strings, 0);
+    let offset = 8 + 4 + 1 + 24 + size_of::<t5_xfile_defs::XFile>();
+    assert_eq!(cache[offset..offset + 8], 0u64.to_le_bytes());
+    cache[offset..offset + 8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+    std::fs::write(&cache_path, cache).unwrap();
+
+    let err = cache_info(&cache_path).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Bincode(_)), "{err:?}");
+    assert!(!cache_is_fresh(&cache_path, &path, platform).unwrap());
+}
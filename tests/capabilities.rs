@@ -0,0 +1,91 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform,
+    xasset::{SupportStatus, XAssetType},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+/// Every type that's supposed to deserialize does, and every one that isn't
+/// fails the way [`SupportStatus::None`] says. Likewise for serializing.
+#[test]
+fn support_status() {
+    for asset_type in XAssetType::ALL {
+        let xfile = SyntheticXFile::new(XFilePlatform::Windows).with_null_asset(asset_type);
+        let bytes = xfile.to_bytes();
+        let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining();
+
+        match asset_type.support_status() {
+            SupportStatus::None => {
+                let err = assets.unwrap_err();
+                assert!(
+                    matches!(err.kind(), ErrorKind::UnusedXAssetType(t) if *t == asset_type),
+                    "{asset_type:?}: {err:?}"
+                );
+            }
+            _ => {
+                let assets = assets.unwrap_or_else(|e| panic!("{asset_type:?}: {e:?}"));
+                assert_eq!(assets[0].asset_type(), asset_type);
+                assert!(assets[0].is_none());
+
+                #[cfg(feature = "serializer")]
+                {
+                    let serialized =
+                        t5_xfile_deserializer::T5XFileSerializerBuilder::new(xfile.platform())
+                            .with_silent(true)
+                            .build()
+                            .unwrap()
+                            .serialize_assets(assets.into_iter());
+                    if asset_type.support_status().can_serialize() {
+                        serialized.unwrap_or_else(|e| panic!("{asset_type:?}: {e:?}"));
+                    } else {
+                        let err = serialized.unwrap_err();
+                        assert!(
+                            matches!(err.kind(), ErrorKind::Todo(_)),
+                            "{asset_type:?}: {err:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn cli() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_t5_xfile_deserializer"))
+        .arg("capabilities")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    let mut lines = stdout.lines();
+    assert_eq!(
+        lines.next().unwrap().split_whitespace().collect::<Vec<_>>(),
+        ["ASSET", "TYPE", "DESERIALIZE", "SERIALIZE"]
+    );
+    for asset_type in XAssetType::ALL {
+        let status = asset_type.support_status();
+        let yes_no = |b| if b { "yes" } else { "no" };
+        let name = format!("{asset_type:?}");
+        assert_eq!(
+            lines.next().unwrap().split_whitespace().collect::<Vec<_>>(),
+            [
+                name.as_str(),
+                yes_no(status.can_deserialize()),
+                yes_no(status.can_serialize())
+            ],
+        );
+    }
+    assert_eq!(lines.next(), None);
+}
@@ -0,0 +1,115 @@
+use t5_xfile_defs::{
+    XFilePlatform,
+    common::{Vec2, Vec3},
+    xmodel::{GfxColor, TexCoords, UnitVec},
+};
+
+fn unit_vec_round_trip(v: [f32; 3]) -> [f32; 3] {
+    UnitVec::encode(Vec3::from(v)).decode().get()
+}
+
+fn tex_coords_round_trip(uv: [f32; 2]) -> [f32; 2] {
+    TexCoords::encode(Vec2::from(uv)).decode().get()
+}
+
+#[test]
+fn unit_vec_axes() {
+    assert_eq!(
+        UnitVec::encode(Vec3::from([1.0, 0.0, 0.0])),
+        UnitVec([254, 127, 127, 63])
+    );
+    assert_eq!(
+        UnitVec::encode(Vec3::from([0.0, -1.0, 0.0])),
+        UnitVec([127, 0, 127, 63])
+    );
+
+    for axis in [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0]] {
+        assert_eq!(unit_vec_round_trip(axis), axis);
+    }
+    assert_eq!(unit_vec_round_trip([0.0; 3]), [0.0; 3]);
+}
+
+#[test]
+fn unit_vec_round_trip_is_close() {
+    let mut v = [0.0f32; 3];
+    for i in 0..1000 {
+        // points spread over the sphere
+        let t = i as f32 * 0.618_034;
+        let z = 1.0 - 2.0 * (i as f32 + 0.5) / 1000.0;
+        let r = (1.0 - z * z).sqrt();
+        v[0] = r * (t * core::f32::consts::TAU).cos();
+        v[1] = r * (t * core::f32::consts::TAU).sin();
+        v[2] = z;
+
+        let decoded = unit_vec_round_trip(v);
+        for (a, b) in v.iter().zip(decoded) {
+            assert!((a - b).abs() <= 1.0 / 127.0, "{v:?} -> {decoded:?}");
+        }
+    }
+}
+
+#[test]
+fn unit_vec_uses_the_smallest_scale() {
+    let short = UnitVec::encode(Vec3::from([0.1, 0.0, 0.0]));
+    let long = UnitVec::encode(Vec3::from([1.0, 0.0, 0.0]));
+    assert!(short.0[3] < long.0[3]);
+    assert!((short.decode().get()[0] - 0.1).abs() < 0.001);
+}
+
+#[test]
+fn tex_coords_bits() {
+    assert_eq!(TexCoords::encode(Vec2::from([1.0, 0.0])), TexCoords(0x3C00));
+    assert_eq!(
+        TexCoords::encode(Vec2::from([0.0, -2.0])),
+        TexCoords(0xC000 << 16)
+    );
+    assert_eq!(TexCoords(0x3800_3C00).decode().get(), [1.0, 0.5]);
+}
+
+#[test]
+fn tex_coords_are_exact_or_close() {
+    for uv in [[0.0, 1.0], [0.5, -0.25], [2048.0, -65504.0]] {
+        assert_eq!(tex_coords_round_trip(uv), uv);
+    }
+
+    for uv in [[0.1, 3.3], [-7.3, 123.456]] {
+        let decoded = tex_coords_round_trip(uv);
+        for (a, b) in uv.iter().zip(decoded) {
+            assert!((a - b).abs() <= a.abs() / 2048.0, "{uv:?} -> {decoded:?}");
+        }
+    }
+}
+
+#[test]
+fn tex_coords_out_of_range() {
+    // subnormal
+    let [u, _] = tex_coords_round_trip([1.0e-6, 0.0]);
+    assert!((u - 1.0e-6).abs() < 6.0e-8);
+
+    assert_eq!(tex_coords_round_trip([1.0e-9, 0.0]), [0.0, 0.0]);
+    assert_eq!(
+        tex_coords_round_trip([1.0e6, -1.0e6]),
+        [f32::INFINITY, f32::NEG_INFINITY]
+    );
+}
+
+#[test]
+fn gfx_color_byte_order() {
+    let pc = GfxColor([0x10, 0x20, 0x30, 0x40]);
+    assert_eq!(pc.to_rgba(XFilePlatform::Windows), [0x30, 0x20, 0x10, 0x40]);
+    assert_eq!(pc.to_rgba(XFilePlatform::Xbox360), [0x10, 0x20, 0x30, 0x40]);
+
+    for platform in [XFilePlatform::Windows, XFilePlatform::PS3] {
+        let rgba = [0.0, 0.25, 1.0, 0.5];
+        let color = GfxColor::from_rgba_f32(rgba, platform);
+        assert_eq!(color.to_rgba(platform), [0, 64, 255, 128]);
+        for (a, b) in rgba.iter().zip(color.to_rgba_f32(platform)) {
+            assert!((a - b).abs() <= 1.0 / 255.0);
+        }
+    }
+
+    assert_eq!(
+        GfxColor::from_rgba_f32([2.0, -1.0, 1.0, 1.0], XFilePlatform::Windows),
+        GfxColor([0xFF, 0x00, 0xFF, 0xFF])
+    );
+}
@@ -0,0 +1,150 @@
+use t5_xfile_defs::{
+    collision_tree::XSurfaceCollisionTreeBuilder,
+    common::Vec3,
+    xmodel::{
+        GfxPackedVertex, XRigidVertList, XSurface, XSurfaceCollisionAabb, XSurfaceCollisionTree,
+    },
+};
+
+/// A `size` by `size` grid of quads, two triangles each.
+fn grid(size: u16) -> (Vec<Vec3>, Vec<[u16; 3]>) {
+    let positions = (0..=size)
+        .flat_map(|y| (0..=size).map(move |x| Vec3::from([x as f32, y as f32, (x * y) as f32])))
+        .collect();
+    let triangles = (0..size)
+        .flat_map(|y| {
+            (0..size).flat_map(move |x| {
+                let i = y * (size + 1) + x;
+                [
+                    [i, i + 1, i + size + 1],
+                    [i + 1, i + size + 2, i + size + 1],
+                ]
+            })
+        })
+        .collect();
+    (positions, triangles)
+}
+
+fn contains(aabb: &XSurfaceCollisionAabb, p: [u16; 3]) -> bool {
+    (0..3).all(|i| aabb.mins[i] <= p[i] && p[i] <= aabb.maxs[i])
+}
+
+/// Checks that every triangle is in exactly one leaf, and within the bounds
+/// of every node above it.
+fn check(tree: &XSurfaceCollisionTree, positions: &[Vec3], triangles: &[[u16; 3]]) {
+    let mut covered = vec![0; triangles.len()];
+    let mut stack = vec![(0, vec![])];
+    while let Some((i, mut ancestors)) = stack.pop() {
+        let node = &tree.nodes[i];
+        ancestors.push(node.aabb);
+        if node.child_count != 0 {
+            for child in node.child_begin_index..node.child_begin_index + node.child_count {
+                stack.push((child, ancestors.clone()));
+            }
+            continue;
+        }
+
+        let leaf = node.child_begin_index;
+        let begin = tree.leafs[leaf].triangle_begin_index;
+        let end = tree
+            .leafs
+            .get(leaf + 1)
+            .map_or(triangles.len(), |l| l.triangle_begin_index);
+        assert!(begin < end);
+        for t in begin..end {
+            covered[t] += 1;
+            for &v in &triangles[t] {
+                let p = tree.quantize(positions[v as usize]);
+                assert!(ancestors.iter().all(|aabb| contains(aabb, p)));
+            }
+        }
+    }
+    assert!(covered.iter().all(|&c| c == 1));
+}
+
+#[test]
+fn build() {
+    let (positions, mut triangles) = grid(16);
+    let mut sorted = triangles.clone();
+
+    for max in [1, 4, 7] {
+        let tree = XSurfaceCollisionTreeBuilder::new()
+            .with_max_leaf_triangles(max)
+            .build(&positions, &mut triangles)
+            .unwrap();
+        check(&tree, &positions, &triangles);
+        assert!(tree.leafs.len() >= triangles.len() / max);
+
+        // the whole grid is spread over the quantized range
+        assert_eq!(tree.quantize(Vec3::from([0.0; 3])), [0; 3]);
+        assert_eq!(
+            tree.quantize(Vec3::from([16.0, 16.0, 256.0])),
+            [u16::MAX; 3]
+        );
+        let p = tree.dequantize(tree.quantize(Vec3::from([8.0, 4.0, 32.0])));
+        for (a, b) in p.get().into_iter().zip([8.0, 4.0, 32.0]) {
+            assert!((a - b).abs() < 0.01);
+        }
+    }
+
+    // the triangles are only reordered
+    let mut reordered = triangles.clone();
+    reordered.sort();
+    sorted.sort();
+    assert_eq!(reordered, sorted);
+
+    let tree = XSurfaceCollisionTreeBuilder::new()
+        .build(&positions, &mut [])
+        .unwrap();
+    assert!(tree.nodes.is_empty() && tree.leafs.is_empty());
+    assert!(
+        XSurfaceCollisionTreeBuilder::new()
+            .build(&positions[..3], &mut triangles)
+            .is_err()
+    );
+}
+
+#[test]
+fn surface() {
+    let (positions, triangles) = grid(4);
+    let mut surface = XSurface {
+        verts0: positions
+            .iter()
+            .map(|&xyz| GfxPackedVertex {
+                xyz,
+                ..Default::default()
+            })
+            .collect(),
+        vert_list: vec![
+            XRigidVertList {
+                tri_offset: 0,
+                tri_count: 12,
+                ..Default::default()
+            },
+            XRigidVertList {
+                tri_offset: 12,
+                tri_count: 20,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    surface.set_triangles(&triangles);
+
+    XSurfaceCollisionTreeBuilder::new()
+        .build_for_surface(&mut surface)
+        .unwrap();
+    let triangles = surface.triangles().collect::<Vec<_>>();
+    for list in &surface.vert_list {
+        let tree = list.collision_tree.as_ref().unwrap();
+        let triangles = &triangles[list.tri_offset..list.tri_offset + list.tri_count];
+        check(tree, &positions, triangles);
+    }
+
+    surface.vert_list[1].tri_count = 21;
+    assert!(
+        XSurfaceCollisionTreeBuilder::new()
+            .build_for_surface(&mut surface)
+            .is_err()
+    );
+}
@@ -0,0 +1,58 @@
+//! Helpers shared by the integration tests.
+
+// each test only uses some of these
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+#[cfg(feature = "deserializer")]
+use t5_xfile_defs::{Result, xasset::XAsset};
+#[cfg(feature = "deserializer")]
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+/// Decodes all-zero bytes, which gives empty arrays and zeroed fields.
+pub fn zeroed<T: serde::de::DeserializeOwned>() -> T {
+    bincode::deserialize(&vec![0u8; 1 << 16]).unwrap()
+}
+
+/// Deserializes every asset in `xfile`.
+#[cfg(feature = "deserializer")]
+pub fn deserialize(xfile: &SyntheticXFile) -> Vec<XAsset> {
+    try_deserialize_with(xfile, |builder| builder).unwrap()
+}
+
+/// Same as [`deserialize`], but `configure` can change the builder's
+/// settings first, and errors are returned instead of panicking.
+#[cfg(feature = "deserializer")]
+pub fn try_deserialize_with(
+    xfile: &SyntheticXFile,
+    configure: impl for<'a> FnOnce(T5XFileDeserializerBuilder<'a>) -> T5XFileDeserializerBuilder<'a>,
+) -> Result<Vec<XAsset>> {
+    let bytes = xfile.to_bytes();
+    configure(
+        T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true).with_silent(true),
+    )
+    .build()?
+    .inflate()?
+    .no_cache()?
+    .deserialize_remaining()
+}
+
+/// Compares `actual` with the contents of `tests/fixtures/{name}`, or
+/// rewrites the file if `UPDATE_GOLDEN` is set.
+pub fn check_golden(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("{}: {e} (run with UPDATE_GOLDEN=1)", path.display()));
+    assert!(
+        expected == actual,
+        "{name} doesn't match, run with UPDATE_GOLDEN=1 if that's intended:\n{actual}"
+    );
+}
@@ -0,0 +1,163 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    ddl::{DdlDef, DdlEnumDef, DdlMemberDef, DdlStructDef, DdlType, DdlValue},
+};
+
+fn member(name: &str, ty: DdlType, offset: i32, size: i32) -> DdlMemberDef {
+    DdlMemberDef {
+        name: XString(name.to_string()),
+        size,
+        offset,
+        type_: ty as i32,
+        external_index: -1,
+        min: 0,
+        max: 0,
+        server_delta: 0,
+        client_delta: 0,
+        array_size: 1,
+        enum_index: -1,
+        permission: 0,
+    }
+}
+
+fn strings(names: &[&str]) -> Vec<XString> {
+    names.iter().map(|n| XString(n.to_string())).collect()
+}
+
+fn def() -> DdlDef {
+    let weapons = DdlMemberDef {
+        external_index: 1,
+        array_size: 2,
+        enum_index: 1,
+        ..member("weapons", DdlType::STRUCT, 19, 32)
+    };
+    let team = DdlMemberDef {
+        external_index: 0,
+        ..member("team", DdlType::ENUM, 17, 2)
+    };
+
+    DdlDef {
+        version: 1,
+        size: 120,
+        struct_list: vec![
+            DdlStructDef {
+                name: XString("root".to_string()),
+                size: 120,
+                members: vec![
+                    member("rank", DdlType::UINT, 0, 5),
+                    member("score", DdlType::INT, 5, 12),
+                    team,
+                    weapons,
+                    member("name", DdlType::STRING, 56, 32),
+                    member("accuracy", DdlType::FLOAT, 88, 32),
+                ],
+            },
+            DdlStructDef {
+                name: XString("weapon".to_string()),
+                size: 16,
+                members: vec![
+                    member("kills", DdlType::UINT, 0, 10),
+                    member("deaths", DdlType::UINT, 10, 6),
+                ],
+            },
+        ],
+        enum_list: vec![
+            DdlEnumDef {
+                name: XString("team".to_string()),
+                members: strings(&["allies", "axis", "free"]),
+            },
+            DdlEnumDef {
+                name: XString("weapon".to_string()),
+                members: strings(&["ak47", "m16"]),
+            },
+        ],
+    }
+}
+
+/// Packs `(offset, width, value)`s least significant bit first.
+fn pack(len: usize, values: &[(u64, u64, u64)]) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    for &(offset, width, value) in values {
+        for i in 0..width {
+            if value >> i & 1 != 0 {
+                let bit = offset + i;
+                bytes[(bit / 8) as usize] |= 1 << (bit % 8);
+            }
+        }
+    }
+    bytes
+}
+
+#[test]
+fn get() {
+    let def = def();
+    let buffer = pack(
+        15,
+        &[
+            (0, 5, 17),
+            (5, 12, (-300i64 as u64) & 0xFFF),
+            (17, 2, 1),
+            (19, 10, 1000),
+            (29, 6, 42),
+            (35, 10, 7),
+            (45, 6, 3),
+            (56, 32, u32::from_le_bytes(*b"Bob\0") as u64),
+            (88, 32, 0.25f32.to_bits() as u64),
+        ],
+    );
+
+    assert_eq!(def.get(&buffer, "rank").unwrap(), DdlValue::UInt(17));
+    assert_eq!(def.get(&buffer, "score").unwrap(), DdlValue::Int(-300));
+    assert_eq!(
+        def.get(&buffer, "team").unwrap(),
+        DdlValue::Enum {
+            value: 1,
+            name: Some("axis")
+        }
+    );
+    assert_eq!(
+        def.get(&buffer, "weapons[ak47].kills").unwrap(),
+        DdlValue::UInt(1000)
+    );
+    assert_eq!(
+        def.get(&buffer, "weapons.0.deaths").unwrap(),
+        DdlValue::UInt(42)
+    );
+    assert_eq!(
+        def.get(&buffer, "WEAPONS[1].kills").unwrap(),
+        DdlValue::UInt(7)
+    );
+    assert_eq!(
+        def.get(&buffer, "weapons.m16.deaths").unwrap(),
+        DdlValue::UInt(3)
+    );
+    assert_eq!(
+        def.get(&buffer, "name").unwrap(),
+        DdlValue::String("Bob".to_string())
+    );
+    assert_eq!(def.get(&buffer, "accuracy").unwrap(), DdlValue::Float(0.25));
+}
+
+#[test]
+fn bad_paths() {
+    let def = def();
+    let buffer = [0u8; 15];
+
+    for path in [
+        "kills",
+        "weapons",
+        "weapons[0]",
+        "weapons[2].kills",
+        "weapons[famas].kills",
+        "rank.x",
+    ] {
+        let err = def.get(&buffer, path).unwrap_err();
+        assert!(
+            matches!(err.kind(), ErrorKind::BadDdlPath(_)),
+            "{path}: {err:?}"
+        );
+    }
+
+    let err = def.get(&buffer[..8], "accuracy").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::InvalidSeek { .. }));
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "deserializer")]
+
+use std::io::Cursor;
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform,
+    xasset::{XAssetBag, XAssetType},
+};
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    dump::{DUMP_VERSION, DumpReader, DumpVersion, read_dump, write_dump},
+    testgen::SyntheticXFile,
+};
+
+fn bag() -> XAssetBag {
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() {}")
+        .with_string_table("mp/t.csv", 2, &["a", "b", "c", "d"])
+        .with_menu("main", 3)
+        .with_localize_entry("MENU_A", "A")
+        .to_bytes();
+    T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, true)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap()
+        .into_iter()
+        .collect()
+}
+
+fn dump() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_dump(&bag(), &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn round_trip() {
+    let debug = |bag: &XAssetBag| format!("{:?}", bag.iter().collect::<Vec<_>>());
+    assert_eq!(debug(&read_dump(&dump()[..]).unwrap()), debug(&bag()));
+}
+
+#[test]
+fn lazy() {
+    let mut dump = DumpReader::new(Cursor::new(dump())).unwrap();
+    assert_eq!(dump.version(), DUMP_VERSION);
+
+    let types = dump
+        .manifest()
+        .entries
+        .iter()
+        .map(|e| (e.asset_type, e.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            (XAssetType::RAWFILE, "maps/a.gsc"),
+            (XAssetType::STRINGTABLE, "mp/t.csv"),
+            (XAssetType::MENU, "main"),
+            (XAssetType::LOCALIZE_ENTRY, "MENU_A"),
+        ]
+    );
+
+    let menu = dump.load(XAssetType::MENU, "main").unwrap().unwrap();
+    assert_eq!(menu.name(), Some("main"));
+    assert!(dump.load(XAssetType::MENU, "other").unwrap().is_none());
+    assert!(dump.load_entry(4).is_err());
+}
+
+/// Bumps the version in `dump`, and appends `extra` to its manifest.
+fn with_version(mut dump: Vec<u8>, major: u16, minor: u16, extra: &[u8]) -> Vec<u8> {
+    dump[8..10].copy_from_slice(&major.to_le_bytes());
+    dump[10..12].copy_from_slice(&minor.to_le_bytes());
+
+    let manifest_len = u64::from_le_bytes(dump[12..20].try_into().unwrap());
+    let manifest_end = 20 + manifest_len as usize;
+    dump.splice(manifest_end..manifest_end, extra.iter().copied());
+    dump[12..20].copy_from_slice(&(manifest_len + extra.len() as u64).to_le_bytes());
+    dump
+}
+
+#[test]
+fn versions() {
+    // newer minor versions may append to the manifest
+    let newer_minor = with_version(dump(), DUMP_VERSION.major, DUMP_VERSION.minor + 1, &[1; 7]);
+    assert_eq!(read_dump(&newer_minor[..]).unwrap().len(), 4);
+
+    let newer_major = with_version(dump(), DUMP_VERSION.major + 1, 0, &[]);
+    let err = read_dump(&newer_major[..]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadDump(_)), "{err}");
+
+    // there's nothing to migrate from yet
+    let older = DumpVersion {
+        major: DUMP_VERSION.major - 1,
+        minor: 0,
+    };
+    assert!(older.migration().is_none());
+    let older_major = with_version(dump(), older.major, older.minor, &[]);
+    assert!(read_dump(&older_major[..]).is_err());
+
+    let mut bad_magic = dump();
+    bad_magic[0] = b'X';
+    let err = read_dump(&bad_magic[..]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadDump(_)), "{err}");
+
+    let truncated = &dump()[..30];
+    assert!(read_dump(truncated).is_err());
+}
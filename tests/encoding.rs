@@ -0,0 +1,132 @@
+use t5_xfile_defs::{
+    ErrorKind,
+    encoding::{Charset, EncodingFallback, StringEncoding},
+};
+
+#[test]
+fn decode() {
+    let auto = StringEncoding::default();
+    // "Señor™" in Windows-1252 and UTF-8
+    assert_eq!(auto.decode(b"Se\xF1or\x99").unwrap(), "Señor™");
+    assert_eq!(auto.decode("Señor™".as_bytes()).unwrap(), "Señor™");
+
+    let windows_1252 = StringEncoding::new(Charset::Windows1252, EncodingFallback::Replace);
+    assert_eq!(windows_1252.decode("é".as_bytes()).unwrap(), "Ã©");
+    assert_eq!(windows_1252.decode(b"a\x81b").unwrap(), "a\u{FFFD}b");
+
+    let utf8 = StringEncoding::new(Charset::Utf8, EncodingFallback::Latin1);
+    assert_eq!(utf8.decode(b"\xE2\x82\xAC\xF1").unwrap(), "€ñ");
+
+    let strict = StringEncoding::new(Charset::Utf8, EncodingFallback::Error);
+    let err = strict.decode(b"Se\xF1or").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadChar(0xF1)));
+}
+
+#[test]
+fn encode() {
+    let auto = StringEncoding::default();
+    assert_eq!(auto.encode("Señor™").unwrap(), b"Se\xF1or\x99");
+    assert_eq!(auto.encode("日本").unwrap(), "日本".as_bytes());
+
+    let windows_1252 = StringEncoding::new(Charset::Windows1252, EncodingFallback::Replace);
+    assert_eq!(windows_1252.encode("a日b").unwrap(), b"a?b");
+
+    let latin1 = StringEncoding::new(Charset::Windows1252, EncodingFallback::Latin1);
+    let bytes = b"a\x81\x8D\x8F\x90\x9Db";
+    assert_eq!(
+        latin1.encode(&latin1.decode(bytes).unwrap()).unwrap(),
+        bytes
+    );
+
+    let strict = StringEncoding::new(Charset::Windows1252, EncodingFallback::Error);
+    let err = strict.encode("日").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadChar(0x65E5)));
+}
+
+#[cfg(feature = "deserializer")]
+#[test]
+fn localized_strings() {
+    use t5_xfile_defs::{
+        XFilePlatform,
+        xasset::{XAsset, XAssetGeneric, XAssetType},
+    };
+    use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+    let mut entry = [0xFFu8; 8].to_vec();
+    entry.extend_from_slice(b"Se\xF1or\0MENU_SENOR\0");
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::LOCALIZE_ENTRY, entry)
+        .to_bytes();
+
+    let deserialize = |encoding| {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_string_encoding(encoding)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_next()
+    };
+
+    let Some(XAsset::PC(XAssetGeneric::LocalizeEntry(Some(entry)))) =
+        deserialize(StringEncoding::default()).unwrap()
+    else {
+        panic!("expected a localize entry");
+    };
+    assert_eq!(entry.value.get(), "Señor");
+
+    let strict = StringEncoding::new(Charset::Utf8, EncodingFallback::Error);
+    let err = deserialize(strict).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadChar(0xF1)));
+}
+
+#[cfg(feature = "serializer")]
+#[test]
+fn map_ents() {
+    use t5_xfile_defs::{
+        MapEnts, T5XFileSerialize, XFilePlatform, XString,
+        xasset::{XAsset, XAssetGeneric, XAssetType},
+    };
+    use t5_xfile_deserializer::{
+        T5XFileDeserializerBuilder, T5XFileSerializerBuilder, testgen::SyntheticXFile,
+    };
+
+    let map_ents = MapEnts {
+        name: XString("maps/mp/mp_a.d3dbsp".to_string()),
+        entity_string: XString("{\n\"message\" \"Señor™\"\n}\n".to_string()),
+    };
+
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets(std::iter::once(XAsset::PC(XAssetGeneric::MapEnts(Some(
+        Box::new(map_ents.clone()),
+    )))))
+    .unwrap();
+    let asset = ser.asset_bytes().unwrap()[8..].to_vec();
+    let entity_string = b"{\n\"message\" \"Se\xF1or\x99\"\n}\n\0";
+    assert_eq!(&asset[8..12], (entity_string.len() as u32).to_le_bytes());
+    assert!(asset.ends_with(entity_string));
+
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::MAP_ENTS, asset)
+        .to_bytes();
+    let asset = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_next()
+        .unwrap();
+    let Some(XAsset::PC(XAssetGeneric::MapEnts(Some(deserialized)))) = asset else {
+        panic!("expected map entities");
+    };
+    assert_eq!(*deserialized, map_ents);
+}
@@ -0,0 +1,43 @@
+use t5_xfile_defs::{BE, F32, LE, U16, U32};
+
+#[test]
+fn byte_order() {
+    let be = U32::<BE>::new(0x12345678);
+    assert_eq!(be.to_bytes(), [0x12, 0x34, 0x56, 0x78]);
+    assert_eq!(be.get(), 0x12345678);
+
+    let le = U32::<LE>::new(0x12345678);
+    assert_eq!(le.to_bytes(), [0x78, 0x56, 0x34, 0x12]);
+    assert_eq!(u32::from(le), 0x12345678);
+
+    let f = F32::<BE>::from(1.5);
+    assert_eq!(f.to_bytes(), 1.5f32.to_be_bytes());
+    assert_eq!(f.to_string(), "1.5");
+    assert_eq!(format!("{:?}", U16::<BE>::from_bytes([0x01, 0x02])), "258");
+}
+
+#[test]
+fn bincode() {
+    use bincode::Options;
+
+    #[derive(serde::Deserialize)]
+    struct Header {
+        magic: U32<BE>,
+        scale: F32<BE>,
+        count: U16<LE>,
+    }
+
+    // the outer format is little-endian, but the tagged fields aren't
+    let mut bytes = vec![0xDE, 0xAD, 0xBE, 0xEF];
+    bytes.extend_from_slice(&2.0f32.to_be_bytes());
+    bytes.extend_from_slice(&[0x03, 0x00]);
+
+    let header = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .deserialize::<Header>(&bytes)
+        .unwrap();
+    assert_eq!(header.magic.get(), 0xDEADBEEF);
+    assert_eq!(header.scale.get(), 2.0);
+    assert_eq!(header.count.get(), 3);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use std::collections::HashSet;
+
+use t5_xfile_defs::{
+    LocalizeEntry, XFilePlatform, XString,
+    common::{ApproxEq, Mat3, Vec3},
+};
+use t5_xfile_deserializer::testgen::SyntheticXFile;
+
+use common::deserialize;
+
+#[test]
+fn deserialized_assets() {
+    let xfile = |script: &[u8]| {
+        SyntheticXFile::new(XFilePlatform::Windows)
+            .with_rawfile("maps/a.gsc", script)
+            .with_localize_entry("MENU_START_GAME", "Start Game")
+            .with_gfx_world("maps/mp/mp_test", [&[[1.0, -2.0, 0.5, 1e-3]], &[]])
+    };
+
+    let assets = deserialize(&xfile(b"main() { }"));
+    assert_eq!(assets.len(), 3);
+    assert_eq!(assets, deserialize(&xfile(b"main() { }")));
+
+    let changed = deserialize(&xfile(b"main() {}"));
+    assert_ne!(assets[0], changed[0]);
+    assert_eq!(assets[1..], changed[1..]);
+}
+
+#[test]
+fn hash() {
+    let entry = |name: &str, value: &str| LocalizeEntry {
+        value: XString(value.to_string()),
+        name: XString(name.to_string()),
+    };
+
+    let entries = [
+        entry("MENU_START_GAME", "Start Game"),
+        entry("MENU_QUIT", "Quit"),
+        entry("MENU_START_GAME", "Start Game"),
+        entry("MENU_START_GAME", "Start"),
+    ];
+    let unique = entries.iter().collect::<HashSet<_>>();
+    assert_eq!(unique.len(), 3);
+}
+
+#[test]
+fn approx_eq() {
+    let a = Vec3::from([1.0, 2.0, 3.0]);
+    let b = Vec3::from([1.0, 2.0005, 3.0]);
+    assert_ne!(a, b);
+    assert!(a.approx_eq(&b, 1e-3));
+    assert!(!a.approx_eq(&b, 1e-4));
+
+    let m = Mat3::from([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    let n = Mat3::from([[1.0, 0.0, 0.0], [0.0, 0.9999, 0.0], [0.0, 0.0, 1.0]]);
+    assert!(m.approx_eq(&n, 1e-3));
+    assert!(!m.approx_eq(&n, 1e-5));
+
+    // lengths and presence have to match exactly
+    assert!(vec![a, b].approx_eq(&vec![b, a], 1e-3));
+    assert!(!vec![a].approx_eq(&vec![a, a], 1.0));
+    assert!(Some(a).approx_eq(&Some(b), 1e-3));
+    assert!(!Some(a).approx_eq(&None, 1.0));
+    assert!(None::<Vec3>.approx_eq(&None, 0.0));
+}
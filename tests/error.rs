@@ -0,0 +1,58 @@
+use std::error::Error as _;
+
+use t5_xfile_defs::{Error, ErrorKind, XFilePlatform};
+
+fn io_error() -> Error {
+    Error::new_with_offset(
+        "src/lib.rs:1:2".to_string(),
+        0x1C,
+        ErrorKind::Io(std::io::ErrorKind::UnexpectedEof.into()),
+    )
+}
+
+#[test]
+fn display() {
+    let e = Error::new_with_offset(
+        "src/lib.rs:12:34".to_string(),
+        0x1C,
+        ErrorKind::BadScriptString(7),
+    );
+    assert_eq!(
+        e.to_string(),
+        "bad script string 7 at offset 0x1C (src/lib.rs:12:34)"
+    );
+
+    let e = Error::new(
+        "src/lib.rs:1:2".to_string(),
+        ErrorKind::UnsupportedPlatform(XFilePlatform::Xbox360),
+    );
+    assert_eq!(e.to_string(), "Xbox 360 isn't supported (src/lib.rs:1:2)");
+
+    assert!(io_error().to_string().starts_with("I/O error: "));
+}
+
+#[test]
+fn sources() {
+    let e = io_error();
+    let source = e.source().unwrap();
+    let io = source.downcast_ref::<std::io::Error>().unwrap();
+    assert_eq!(io.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    let e = Error::new("".to_string(), ErrorKind::ScriptStringOverflow);
+    assert!(e.source().is_none());
+}
+
+// what `anyhow::Error: From<E>` requires
+fn assert_anyhow_compatible<E: std::error::Error + Send + Sync + 'static>() {}
+
+#[test]
+fn question_mark() {
+    assert_anyhow_compatible::<Error>();
+
+    fn fails() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Err(io_error())?;
+        Ok(())
+    }
+    let e = fails().unwrap_err();
+    assert!(e.downcast_ref::<Error>().is_some());
+}
@@ -0,0 +1,329 @@
+use t5_xfile_defs::{
+    Shared, XString,
+    common::{CoordinateConvention, Handedness, Mat3, Mat4, Units, UpAxis, Vec2, Vec3},
+    gfx_world::GfxPackedPlacement,
+    techset::{
+        GfxImage, Material, MaterialInfo, MaterialTextureDef, MaterialTextureDefInfo, Semantic,
+    },
+    xmodel::{XModel, XModelLodInfo, XSurface},
+};
+use t5_xfile_deserializer::export::{
+    ExportMaterial, IDENTITY, Node, Scene, file_name, placement_matrix,
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn material(name: &str, color_map: &str) -> Material {
+    let texture = |semantic, image: &str| MaterialTextureDef {
+        semantic,
+        u: MaterialTextureDefInfo::Image(Some(Box::new(GfxImage {
+            name: xstring(image),
+            ..Default::default()
+        }))),
+        ..Default::default()
+    };
+
+    Material {
+        info: MaterialInfo {
+            name: xstring(name),
+            ..Default::default()
+        },
+        textures: vec![
+            texture(Semantic::NORMAL_MAP, "~-gcrate_n"),
+            texture(Semantic::COLOR_MAP, color_map),
+        ],
+        ..Default::default()
+    }
+}
+
+fn model() -> XModel {
+    let mut surf = XSurface::default();
+    surf.set_vertices(
+        &[[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]].map(Vec3::from),
+        &[[0.0, 0.0, 1.0]; 3].map(Vec3::from),
+        &[[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]].map(Vec2::from),
+        None,
+    )
+    .unwrap();
+    surf.set_triangles(&[[0, 1, 2]]);
+
+    let mut lod_info = <[XModelLodInfo; 4]>::default();
+    lod_info[0].numsurfs = 1;
+    // the second LOD isn't exported
+    lod_info[1].surf_index = 1;
+    lod_info[1].numsurfs = 1;
+
+    XModel {
+        name: xstring("com_crate"),
+        surfs: vec![surf.clone(), surf],
+        material_handles: vec![
+            Shared::new(material("mc/mtl_crate", "~-gcrate")),
+            Shared::new(material("mc/mtl_crate_lod1", "~-gcrate")),
+        ],
+        lod_info,
+        num_lods: 2,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn from_xmodel() {
+    let scene = Scene::from_xmodel(&model());
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].indices, [0, 1, 2]);
+    assert_eq!(scene.meshes[0].positions[1], [1.0, 0.0, 0.0]);
+    assert_eq!(scene.meshes[0].material, Some(0));
+    assert_eq!(
+        scene.materials,
+        [ExportMaterial {
+            name: "mc/mtl_crate".to_string(),
+            color_map: Some("~-gcrate".to_string()),
+        }]
+    );
+    assert_eq!(scene.nodes[0].meshes, [0]);
+}
+
+#[test]
+fn lods() {
+    let mut model = model();
+    model.lod_info[0].dist = 500.0;
+    model.lod_info[1].dist = 1000.0;
+
+    assert_eq!(model.lod_count(), 2);
+    assert_eq!(model.lod_surface_range(1), Some(1..2));
+    assert_eq!(model.surfaces_for_lod(0).len(), 1);
+    assert!(model.surfaces_for_lod(2).is_empty());
+    assert_eq!(model.lod_for_distance(100.0), Some(0));
+    assert_eq!(model.lod_for_distance(500.0), Some(1));
+    assert_eq!(model.lod_for_distance(2000.0), None);
+    model.lod_info[1].dist = 0.0;
+    assert_eq!(model.lod_for_distance(2000.0), Some(1));
+
+    // surfaces past the end are left out
+    model.lod_info[1].numsurfs = 5;
+    assert_eq!(model.lod_surface_range(1), Some(1..2));
+
+    let mut scene = Scene::new();
+    assert_eq!(scene.add_xmodel_lod(&model, 1), [0]);
+    assert_eq!(scene.meshes[0].name, "com_crate_lod1_0");
+    assert_eq!(scene.materials[0].name, "mc/mtl_crate_lod1");
+    assert!(scene.add_xmodel_lod(&model, 3).is_empty());
+
+    let scene = Scene::from_xmodel_lods(&model);
+    let names = scene
+        .nodes
+        .iter()
+        .map(|n| n.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["com_crate", "com_crate_lod1"]);
+    assert_eq!(scene.nodes[1].meshes, [1]);
+}
+
+#[test]
+fn obj() {
+    let mut scene = Scene::from_xmodel(&model());
+    // the same mesh again, moved up by 2
+    let mut matrix = IDENTITY;
+    matrix[14] = 2.0;
+    scene.nodes.push(Node {
+        name: "moved".to_string(),
+        meshes: vec![0],
+        matrix,
+    });
+
+    let (obj, mtl) = scene.to_obj("com_crate.mtl", "../textures");
+    let lines = obj.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "mtllib com_crate.mtl");
+    assert!(lines.contains(&"vt 0 1"));
+    assert!(lines.contains(&"usemtl mc/mtl_crate"));
+    assert!(lines.contains(&"f 1/1/1 2/2/2 3/3/3"));
+    assert!(lines.contains(&"v 0 1 2"));
+    assert!(lines.contains(&"f 4/4/4 5/5/5 6/6/6"));
+
+    assert!(mtl.contains("newmtl mc/mtl_crate\n"));
+    assert!(mtl.contains("map_Kd ../textures/~-gcrate.png\n"));
+}
+
+#[test]
+fn gltf() {
+    let scene = Scene::from_xmodel(&model());
+    let (json, bin) = scene.to_gltf("com_crate.bin", "");
+
+    // positions, normals, uvs, and indices
+    assert_eq!(bin.len(), 3 * 12 + 3 * 12 + 3 * 8 + 3 * 4);
+    assert_eq!(bin[12..16], 1.0f32.to_le_bytes());
+    assert!(json.starts_with("{\"asset\":{\"version\":\"2.0\""));
+    assert!(json.contains("\"buffers\":[{\"uri\":\"com_crate.bin\",\"byteLength\":108}]"));
+    assert!(json.contains("\"images\":[{\"uri\":\"~-gcrate.png\"}]"));
+    assert!(json.contains("\"min\":[0,0,0],\"max\":[1,1,0]"));
+    assert!(json.contains("\"nodes\":[{\"name\":\"com_crate\",\"mesh\":0,"));
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+}
+
+#[test]
+fn file_names() {
+    assert_eq!(file_name("mc/mtl_crate"), "mc_mtl_crate");
+    assert_eq!(file_name("~-gcrate"), "~-gcrate");
+}
+
+#[test]
+fn placement() {
+    // turned 90 degrees to the left, doubled in size, and moved
+    let placement = GfxPackedPlacement {
+        origin: Vec3::from([10.0, 20.0, 30.0]),
+        axis: Mat3::from([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]),
+        scale: 2.0,
+    };
+    let m = placement_matrix(&placement);
+    let transform = |p: [f32; 3]| {
+        [0, 1, 2].map(|i| m[i] * p[0] + m[4 + i] * p[1] + m[8 + i] * p[2] + m[12 + i])
+    };
+
+    assert_eq!(transform([0.0; 3]), [10.0, 20.0, 30.0]);
+    assert_eq!(transform([1.0, 0.0, 0.0]), [10.0, 22.0, 30.0]);
+    assert_eq!(transform([0.0, 1.0, 1.0]), [8.0, 20.0, 32.0]);
+}
+
+#[test]
+fn conventions() {
+    let p = [1.0, 2.0, 3.0];
+    assert_eq!(CoordinateConvention::NATIVE.convert_point(p), p);
+    assert_eq!(
+        CoordinateConvention::default(),
+        CoordinateConvention::NATIVE
+    );
+
+    let gltf = CoordinateConvention::Y_UP_METERS;
+    assert_eq!(gltf.convert_point([0.0, 0.0, 100.0]), [0.0, 2.54, 0.0]);
+    assert_eq!(gltf.convert_direction([1.0, 0.0, 0.0]), [0.0, 0.0, 1.0]);
+    assert!(!gltf.flips_winding());
+
+    let unreal = CoordinateConvention::UNREAL;
+    assert_eq!(unreal.convert_point([1.0, 1.0, 1.0]), [2.54, -2.54, 2.54]);
+    assert!(unreal.flips_winding());
+
+    let unity = CoordinateConvention::new(UpAxis::Y, Handedness::Left, Units::Inches);
+    assert_eq!(
+        Vec3::from(p).convert_direction(unity),
+        Vec3::from([-2.0, 3.0, 1.0])
+    );
+
+    // converting a transform and then applying it should be the same as
+    // applying it and then converting
+    let placement = GfxPackedPlacement {
+        origin: Vec3::from([10.0, 20.0, 30.0]),
+        axis: Mat3::from([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]),
+        scale: 2.0,
+    };
+    let m = placement_matrix(&placement);
+    let rows = [0, 1, 2, 3].map(|i| [m[i * 4], m[i * 4 + 1], m[i * 4 + 2], m[i * 4 + 3]]);
+    let apply = |m: [[f32; 4]; 4], p: [f32; 3]| {
+        [0, 1, 2].map(|i| m[0][i] * p[0] + m[1][i] * p[1] + m[2][i] * p[2] + m[3][i])
+    };
+    for convention in [gltf, unreal, unity] {
+        let converted = Mat4::from(rows).convert(convention).get();
+        let expected = convention.convert_point(apply(rows, p));
+        let actual = apply(converted, convention.convert_point(p));
+        assert!((0..3).all(|i| (expected[i] - actual[i]).abs() < 1.0e-4));
+
+        let rotated = placement.axis.convert(convention).get();
+        let axis = convention.convert_rotation(placement.axis.get());
+        assert_eq!(rotated, axis);
+    }
+}
+
+#[test]
+fn converted_obj() {
+    let scene = Scene::from_xmodel(&model()).with_convention(CoordinateConvention::UNREAL);
+    let (obj, _) = scene.to_obj("com_crate.mtl", "");
+    let lines = obj.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"v 2.54 0 0"));
+    assert!(lines.contains(&"v 0 -2.54 0"));
+    assert!(lines.contains(&"vn 0 0 1"));
+    // mirrored, so wound the other way
+    assert!(lines.contains(&"f 1/1/1 3/3/3 2/2/2"));
+}
+
+#[test]
+fn path_graph() {
+    use t5_xfile_defs::gameworld::{
+        NodeType, PathData, PathLink, PathNode, PathNodeConstant, SpawnFlags,
+    };
+    use t5_xfile_deserializer::export::{path_graph_to_dot, path_graph_to_json};
+
+    let link = |node_num, dist| PathLink {
+        dist,
+        node_num,
+        disconnect_count: 0,
+        negotiation_link: 0,
+        bad_place_count: [0; 4],
+    };
+    let node = |type_, origin, targetname: &str, links| PathNode {
+        constant: PathNodeConstant {
+            type_,
+            origin: Vec3::from(origin),
+            targetname: xstring(targetname),
+            links,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut data = PathData {
+        nodes: vec![
+            node(
+                NodeType::PATHNODE,
+                [0.0, 0.0, 0.0],
+                "start",
+                vec![link(1, 64.0), link(2, 1.0)],
+            ),
+            node(
+                NodeType::COVER_STAND,
+                [64.0, 0.0, 8.0],
+                "",
+                vec![link(0, 64.0)],
+            ),
+            // a spare node, which links to it are dropped along with
+            node(NodeType::BADNODE, [0.0; 3], "", vec![]),
+        ],
+        basenodes: vec![],
+        chain_node_for_node: vec![],
+        node_for_chain_node: vec![],
+        path_vis: vec![],
+        node_tree: vec![],
+    };
+    data.nodes[1].constant.spawnflags = SpawnFlags::DISABLED;
+    data.nodes[1].constant.links[0].negotiation_link = 1;
+
+    let graph = data.graph();
+    assert_eq!(graph.nodes.len(), 2);
+    assert_eq!(graph.nodes[0].targetname, "start");
+    assert_eq!(graph.nodes[1].origin, [64.0, 0.0, 8.0]);
+    assert!(graph.nodes[1].disabled);
+    let links = graph
+        .links
+        .iter()
+        .map(|l| (l.from, l.to, l.negotiation))
+        .collect::<Vec<_>>();
+    assert_eq!(links, [(0, 1, false), (1, 0, true)]);
+
+    let dot = path_graph_to_dot(&graph);
+    assert!(dot.starts_with("digraph paths {"));
+    assert!(dot.contains("0 [label=\"0: PATHNODE\\nstart\", pos=\"0,0!\"];"));
+    assert!(dot.contains("1 [label=\"1: COVER_STAND\", pos=\"64,0!\", style=dashed];"));
+    assert!(dot.contains("1 -> 0 [label=\"64\", style=dashed];"));
+
+    assert_eq!(
+        path_graph_to_json(&graph),
+        "{\"nodes\":[\
+         {\"index\":0,\"type\":\"PATHNODE\",\"origin\":[0,0,0],\"angle\":0,\
+         \"targetname\":\"start\",\"disabled\":false},\
+         {\"index\":1,\"type\":\"COVER_STAND\",\"origin\":[64,0,8],\"angle\":0,\
+         \"targetname\":\"\",\"disabled\":true}],\
+         \"links\":[\
+         {\"from\":0,\"to\":1,\"dist\":64,\"negotiation\":false,\"disconnected\":false},\
+         {\"from\":1,\"to\":0,\"dist\":64,\"negotiation\":true,\"disconnected\":false}]}"
+    );
+}
@@ -0,0 +1,32 @@
+use t5_xfile_defs::xasset::{XAsset, XAssetBlob, XAssetFamily, XAssetGeneric, XAssetType};
+
+#[test]
+fn families() {
+    assert_eq!(XAssetType::XMODEL.family(), Some(XAssetFamily::Models));
+    assert_eq!(XAssetType::GFXWORLD.family(), Some(XAssetFamily::World));
+    assert_eq!(XAssetType::MENU.family(), Some(XAssetFamily::Ui));
+    assert_eq!(XAssetType::SOUND.family(), Some(XAssetFamily::Audio));
+    assert_eq!(XAssetType::WEAPON.family(), Some(XAssetFamily::Fx));
+    assert_eq!(XAssetType::MATERIAL.family(), None);
+    assert_eq!(XAssetType::RAWFILE.family(), None);
+
+    // the deserializer enables every family
+    assert!(XAssetFamily::ALL.iter().all(|f| f.is_enabled()));
+    assert!(XAssetType::ALL.iter().all(|t| t.is_enabled()));
+    assert_eq!(
+        XAssetFamily::ALL.map(XAssetFamily::feature),
+        ["world", "models", "ui", "audio", "fx"]
+    );
+}
+
+#[test]
+fn blobs() {
+    let blob = XAsset::PC(XAssetGeneric::Blob(Box::new(XAssetBlob {
+        asset_type: XAssetType::SOUND,
+        data: vec![0xFF; 4],
+    })));
+    assert_eq!(blob.asset_type(), XAssetType::SOUND);
+    assert!(blob.is_some());
+    assert_eq!(blob.name(), None);
+    assert!(blob.dependencies().is_empty());
+}
@@ -0,0 +1,50 @@
+use t5_xfile_defs::{ErrorKind, FixedString};
+
+#[test]
+fn parse_and_display() {
+    let name = "sun_light".parse::<FixedString<16>>().unwrap();
+    assert_eq!(name.as_str(), Some("sun_light"));
+    assert_eq!(name.len(), 9);
+    assert_eq!(name.to_string(), "sun_light");
+    assert_eq!(&name.0[9..], [0; 7]);
+    assert!(FixedString::<16>::default().is_empty());
+
+    // filling every byte leaves no room for a null terminator, which is fine
+    let full = "0123456789abcdef".parse::<FixedString<16>>().unwrap();
+    assert_eq!(full.as_str(), Some("0123456789abcdef"));
+
+    let err = "0123456789abcdefg".parse::<FixedString<16>>().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+
+    // Windows-1252, like the engine's strings
+    let name = "señor".parse::<FixedString<8>>().unwrap();
+    assert_eq!(name.as_bytes(), b"se\xF1or");
+    assert_eq!(name.as_str(), None);
+    assert_eq!(name.to_string(), "señor");
+}
+
+#[test]
+fn truncate() {
+    let name = FixedString::<4>::from("abcdef");
+    assert_eq!(name.as_str(), Some("abcd"));
+
+    // three bytes each in UTF-8, so only one fits
+    let name = FixedString::<4>::from("日本");
+    assert_eq!(name.to_string(), "日");
+
+    // the prefix that fits is Windows-1252, even though the whole string
+    // would be UTF-8
+    let name = FixedString::<4>::from("ñaña日");
+    assert_eq!(name.as_bytes(), b"\xF1a\xF1a");
+}
+
+#[test]
+fn serde() {
+    let name = "mp_nuked".parse::<FixedString<16>>().unwrap();
+    let bytes = bincode::serialize(&name).unwrap();
+    assert_eq!(bytes, name.0);
+    assert_eq!(
+        bincode::deserialize::<FixedString<16>>(&bytes).unwrap(),
+        name
+    );
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "deserializer")]
+
+use std::path::{Path, PathBuf};
+
+use t5_xfile_deserializer::{GameDir, ZoneSet, ipak::ImagePacks};
+
+use t5_xfile_defs::{ErrorKind, XFilePlatform};
+
+fn game_dir(name: &str, files: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-game-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    for file in files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, file).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn discovery() {
+    let root = game_dir(
+        "discovery",
+        &[
+            "loose.ff",
+            "zone/Common/common_mp.ff",
+            "zone/Common/a.SABS",
+            "zone/Common/notes.txt",
+            "zone/English/common_mp.ff",
+            "zone/English/en_mp.ff",
+            "zone/French/en_mp.ff",
+            "b.sabl",
+        ],
+    );
+    let dir = GameDir::new(&root);
+    let zone = root.join("zone");
+
+    assert_eq!(
+        dir.companion_dirs().unwrap(),
+        [
+            root.clone(),
+            zone.clone(),
+            zone.join("Common"),
+            zone.join("English"),
+            zone.join("French"),
+        ]
+    );
+    assert_eq!(
+        dir.companion_files(&["sabs", "sabl"]).unwrap(),
+        [root.join("b.sabl"), zone.join("Common/a.SABS")]
+    );
+    assert_eq!(dir.languages().unwrap(), ["English", "French"]);
+
+    // common zones take priority over localized ones
+    let common = zone.join("Common/common_mp.ff");
+    assert_eq!(dir.find_zone("common_mp").unwrap(), Some(common.clone()));
+    assert_eq!(dir.find_zone("common_mp.ff").unwrap(), Some(common));
+    assert_eq!(dir.find_zone("loose").unwrap(), Some(root.join("loose.ff")));
+    assert_eq!(
+        dir.find_zone("en_mp").unwrap(),
+        Some(zone.join("English/en_mp.ff"))
+    );
+    assert_eq!(dir.find_zone("missing").unwrap(), None);
+
+    assert_eq!(
+        dir.localized_zones("en_mp").unwrap(),
+        [
+            ("English".to_string(), zone.join("English/en_mp.ff")),
+            ("French".to_string(), zone.join("French/en_mp.ff")),
+        ]
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn install_without_zones() {
+    let root = game_dir("flat", &["a.ff"]);
+    let dir = GameDir::new(&root);
+    assert_eq!(dir.companion_dirs().unwrap(), std::slice::from_ref(&root));
+    assert!(dir.languages().unwrap().is_empty());
+    assert_eq!(dir.find_zone("a").unwrap(), Some(root.join("a.ff")));
+    std::fs::remove_dir_all(root).unwrap();
+
+    let e = GameDir::new(Path::new("/nonexistent/game"))
+        .companion_dirs()
+        .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::Io(_)));
+}
+
+#[test]
+fn companions_in_zone_dirs() {
+    // packs in the zone directories are opened too
+    let root = game_dir("companions", &["zone/Common/a.ipak"]);
+    let e = ImagePacks::from_game_dir(&root).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::BadImagePack(_)));
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn zone_set_zone_name() {
+    let root = game_dir("zone_set", &["zone/Common/common_mp.ff"]);
+    let dir = GameDir::new(&root);
+
+    let zones = ZoneSet::new(XFilePlatform::Windows)
+        .with_zone_name(&dir, "common_mp")
+        .unwrap();
+    assert_eq!(zones.zones(), [root.join("zone/Common/common_mp.ff")]);
+    assert!(
+        ZoneSet::new(XFilePlatform::Windows)
+            .with_zone_name(&dir, "mp_nuked")
+            .is_err()
+    );
+
+    std::fs::remove_dir_all(root).unwrap();
+}
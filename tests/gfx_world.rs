@@ -0,0 +1,128 @@
+//! Golden-file tests for map worlds, which can only be deserialized (so not
+//! round-tripped through the serializer). The expected summaries are in
+//! `tests/fixtures`; run with `UPDATE_GOLDEN=1` to rewrite them after an
+//! intended change.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    gfx_world::GfxWaterBuffer,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{summary::summarize, testgen::SyntheticXFile};
+
+use common::{check_golden, deserialize};
+
+/// Deserializes the only asset in `xfile`.
+fn deserialize_only(xfile: &SyntheticXFile) -> XAsset {
+    let mut assets = deserialize(xfile);
+    assert_eq!(assets.len(), 1);
+    assets.pop().unwrap()
+}
+
+#[test]
+fn water_buffers() {
+    let first = [[1.0, 2.0, 3.0, 4.0], [0.5, -0.5, 0.25, -0.25]];
+    let second = [[-1.0, 0.0, 1.0, 2.0]];
+
+    for platform in [XFilePlatform::Windows, XFilePlatform::Xbox360] {
+        let xfile =
+            SyntheticXFile::new(platform).with_gfx_world("maps/mp/mp_test", [&first, &second]);
+        let (water_buffers, summary) = match deserialize_only(&xfile) {
+            XAsset::PC(XAssetGeneric::GfxWorld(Some(world))) => {
+                (world.water_buffers.clone(), summarize(&world).unwrap())
+            }
+            XAsset::Console(XAssetGeneric::GfxWorld(Some(world))) => {
+                (world.water_buffers.clone(), summarize(&world).unwrap())
+            }
+            asset => panic!("{asset:?}"),
+        };
+        // each buffer is loaded from its own pointer, in order
+        let buffer = |b: &GfxWaterBuffer| b.buffer.iter().map(|v| v.get()).collect::<Vec<_>>();
+        assert_eq!(buffer(&water_buffers[0]), first);
+        assert_eq!(buffer(&water_buffers[1]), second);
+
+        let platform = format!("{platform:?}").to_lowercase();
+        check_golden(&format!("gfx_world_{platform}.txt"), &summary);
+    }
+}
+
+#[test]
+fn portal_back_refs() {
+    use t5_xfile_defs::{
+        BackRef, InternTable,
+        common::{Vec2, Vec3, Vec4},
+        gfx_world::{DpvsPlane, GfxCell, GfxPortal, GfxPortalWritable},
+    };
+
+    let xfile =
+        SyntheticXFile::new(XFilePlatform::Windows).with_gfx_world("maps/mp/mp_test", [&[], &[]]);
+    let XAsset::PC(XAssetGeneric::GfxWorld(Some(mut world))) = deserialize_only(&xfile) else {
+        panic!();
+    };
+    assert!(world.cells.is_empty());
+
+    // where the engine loaded the cells and their portals
+    let cells = InternTable::from_array(0x1000, 0x40, 2);
+    let portals = InternTable::from_array(0x2000, 68, 3);
+
+    fn back_ref<T>(ptr: u32) -> BackRef<T> {
+        BackRef::Ptr { ptr, index: None }
+    }
+    let portal = |cell, queued_parent| GfxPortal {
+        writable: GfxPortalWritable {
+            is_queued: false,
+            is_ancestor: false,
+            recursion_depth: 0,
+            hull_points: vec![Vec2::from([0.0, 0.0])],
+            queued_parent,
+        },
+        plane: DpvsPlane {
+            coeffs: Vec4::from([0.0, 0.0, 1.0, 0.0]),
+            side: [0, 1, 2],
+        },
+        cell,
+        vertices: Vec::new(),
+        hull_axis: [Vec3::from([1.0, 0.0, 0.0]), Vec3::from([0.0, 1.0, 0.0])],
+    };
+    let cell = |portals| GfxCell {
+        mins: Vec3::from([0.0; 3]),
+        maxs: Vec3::from([1.0; 3]),
+        aabb_tree: Vec::new(),
+        portals,
+        cull_groups: Vec::new(),
+        reflection_probes: Vec::new(),
+    };
+    world.cells = vec![
+        cell(vec![
+            portal(back_ref(0x1040), BackRef::Null),
+            portal(back_ref(0x1000), back_ref(0x2000)),
+        ]),
+        // the last cell doesn't exist
+        cell(vec![portal(back_ref(0x1080), back_ref(0x2000 + 68))]),
+    ];
+
+    assert_eq!(world.resolve_back_refs(&cells, &portals), 1);
+    let resolved = world
+        .portals()
+        .map(|p| (p.cell.index(), p.writable.queued_parent.index()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        resolved,
+        [(Some(1), None), (Some(0), Some(0)), (None, Some(1))]
+    );
+
+    // the raw offsets are kept, so they serialize back out the same
+    let last = world.portal(2).unwrap();
+    assert_eq!(last.cell.ptr(), Some(0x1080));
+    assert_eq!(last.cell.to_ptr32::<()>().as_u32(), 0x1080);
+    assert_eq!(
+        world.portal(1).unwrap().writable.queued_parent.ptr(),
+        Some(0x2000)
+    );
+    assert!(world.portal(0).unwrap().writable.queued_parent.is_null());
+    assert_eq!(world.portal(3), None);
+}
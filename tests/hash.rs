@@ -0,0 +1,78 @@
+#![cfg(all(feature = "deserializer", feature = "hash"))]
+
+mod common;
+
+use t5_xfile_defs::{
+    LocalizeEntry, RawFile, XFilePlatform, XString,
+    hash::StableHash,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::testgen::SyntheticXFile;
+
+use common::deserialize;
+
+fn raw_file(name: &str, buffer: &[u8]) -> RawFile {
+    RawFile {
+        name: XString(name.to_string()),
+        buffer: buffer.to_vec(),
+    }
+}
+
+#[test]
+fn stable() {
+    // the same value on every run and machine (but not necessarily every
+    // version of the library)
+    let a = raw_file("maps/a.gsc", b"main() { }\0");
+    assert_eq!(a.stable_hash().unwrap(), a.clone().stable_hash().unwrap());
+    assert_eq!(a.stable_hash().unwrap(), 0xAEBD5037A7C78FFC);
+
+    // only the contents matter
+    assert_ne!(
+        a.stable_hash().unwrap(),
+        raw_file("maps/a.gsc", b"main() {}\0")
+            .stable_hash()
+            .unwrap()
+    );
+    assert_ne!(
+        a.stable_hash().unwrap(),
+        raw_file("maps/b.gsc", b"main() { }\0")
+            .stable_hash()
+            .unwrap()
+    );
+    let entry = LocalizeEntry {
+        value: XString("main() { }".to_string()),
+        name: XString("maps/a.gsc".to_string()),
+    };
+    assert_ne!(a.stable_hash().unwrap(), entry.stable_hash().unwrap());
+}
+
+#[test]
+fn same_across_platforms() {
+    let hash = |asset: &XAsset| match asset {
+        XAsset::PC(XAssetGeneric::RawFile(Some(r)))
+        | XAsset::Console(XAssetGeneric::RawFile(Some(r))) => r.stable_hash().unwrap(),
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e)))
+        | XAsset::Console(XAssetGeneric::LocalizeEntry(Some(e))) => e.stable_hash().unwrap(),
+        _ => panic!("{asset:?}"),
+    };
+
+    let hashes = [
+        XFilePlatform::Windows,
+        XFilePlatform::Xbox360,
+        XFilePlatform::PS3,
+    ]
+    .map(|platform| {
+        let xfile = SyntheticXFile::new(platform)
+            .with_rawfile("maps/a.gsc", b"main() { }")
+            .with_localize_entry("MENU_START_GAME", "Start Game");
+        deserialize(&xfile).iter().map(hash).collect::<Vec<_>>()
+    });
+    assert_eq!(hashes[0], hashes[1]);
+    assert_eq!(hashes[0], hashes[2]);
+    assert_eq!(
+        hashes[0][0],
+        raw_file("maps/a.gsc", b"main() { }\0")
+            .stable_hash()
+            .unwrap()
+    );
+}
@@ -0,0 +1,185 @@
+#![cfg(feature = "deserializer")]
+
+use std::{io::Cursor, path::PathBuf};
+
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    ipak::{
+        IPAK_MAGIC, IPAK_SECTION_DATA, IPAK_SECTION_INDEX, IPAK_VERSION, ImagePack, ImagePacks,
+    },
+    testgen::SyntheticXFile,
+};
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform,
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture},
+};
+
+const NAME_HASH: u32 = 0x12345678;
+
+/// "abcd", then an 8-byte match 4 bytes back, then the end of the stream.
+const LZO_CHUNK: [u8; 10] = [21, b'a', b'b', b'c', b'd', 236, 0, 0x11, 0, 0];
+const STORED_CHUNK: [u8; 4] = *b"0123";
+const EXPECTED: &[u8] = b"0123abcdabcdabcd";
+
+/// A pack with a single image made of a stored chunk and an LZO chunk.
+fn pack(little_endian: bool) -> Vec<u8> {
+    let u32_bytes = |v: u32| {
+        if little_endian {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        }
+    };
+
+    let mut data = Vec::new();
+    data.extend(u32_bytes(2 << 24));
+    data.extend(u32_bytes(STORED_CHUNK.len() as u32));
+    data.extend(u32_bytes((1 << 24) | LZO_CHUNK.len() as u32));
+    data.resize(128, 0);
+    data.extend(STORED_CHUNK);
+    data.extend(LZO_CHUNK);
+
+    let index_offset = 16 + 2 * 16;
+    let data_offset = index_offset + 16;
+    let header = [
+        IPAK_VERSION,
+        (data_offset + data.len()) as u32,
+        2,
+        IPAK_SECTION_INDEX,
+        index_offset as u32,
+        16,
+        1,
+        IPAK_SECTION_DATA,
+        data_offset as u32,
+        data.len() as u32,
+        1,
+        0xDEADBEEF,
+        NAME_HASH,
+        0,
+        data.len() as u32,
+    ];
+
+    let mut magic = IPAK_MAGIC;
+    if !little_endian {
+        magic.reverse();
+    }
+    magic
+        .into_iter()
+        .chain(header.into_iter().flat_map(u32_bytes))
+        .chain(data)
+        .collect()
+}
+
+fn game_dir(name: &str, files: &[(&str, Vec<u8>)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-ipak-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        std::fs::write(dir.join(file), contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn read() {
+    for little_endian in [true, false] {
+        let mut pack = ImagePack::new(Cursor::new(pack(little_endian))).unwrap();
+        assert_eq!(pack.is_little_endian(), little_endian);
+        assert_eq!(pack.entries().count(), 1);
+        assert!(pack.get(0).is_none());
+
+        let entry = *pack.get(NAME_HASH).unwrap();
+        assert_eq!(pack.read(entry).unwrap(), EXPECTED);
+    }
+}
+
+#[test]
+fn bad_pack() {
+    let mut bytes = pack(true);
+    bytes[0] = b'X';
+    let e = ImagePack::new(Cursor::new(&bytes)).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::BadImagePack(_)));
+
+    // a chunk with an unknown compression type
+    let mut bytes = pack(true);
+    bytes[64 + 11] = 7;
+    let mut pack = ImagePack::new(Cursor::new(bytes)).unwrap();
+    let entry = *pack.get(NAME_HASH).unwrap();
+    let e = pack.read(entry).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BadImagePack(_)));
+}
+
+#[test]
+fn fuse() {
+    let dir = game_dir(
+        "fuse",
+        &[
+            ("a.ipak", pack(true)),
+            ("b.xpak", pack(false)),
+            ("readme.txt", b"not a pack".to_vec()),
+        ],
+    );
+    let mut packs = ImagePacks::from_game_dir(&dir).unwrap();
+    assert_eq!(packs.paths().count(), 2);
+
+    let image = |hash| GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count: 1,
+            resource: b"z".to_vec(),
+            ..Default::default()
+        }))),
+        level_count: 3,
+        streaming: true,
+        hash,
+        ..Default::default()
+    };
+
+    let mut fused = image(NAME_HASH);
+    assert_eq!(fused.streamed_level_count(), 2);
+    assert!(packs.fuse(&mut fused).unwrap());
+    assert!(!fused.streaming);
+    assert_eq!(fused.streamed_level_count(), 0);
+    let GfxTexture::LoadDef(Some(def)) = &fused.texture else {
+        unreachable!()
+    };
+    assert_eq!(def.level_count, 3);
+    assert_eq!(def.resource, [EXPECTED, b"z"].concat());
+
+    // fused images, and images that aren't in any pack, are left alone
+    assert!(!packs.fuse(&mut fused).unwrap());
+    let mut missing = image(0);
+    assert!(!packs.fuse(&mut missing).unwrap());
+    assert_eq!(missing, image(0));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn builder_game_dir() {
+    let xfile =
+        SyntheticXFile::new(XFilePlatform::Windows).with_rawfile("maps/a.gsc", b"main() { }");
+    let bytes = xfile.to_bytes();
+    let build = |dir| {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_game_dir(dir)
+            .build()
+    };
+
+    let dir = game_dir("good", &[("a.ipak", pack(true))]);
+    let assets = build(dir.clone())
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(assets.len(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+
+    let dir = game_dir("bad", &[("a.ipak", b"KAPI".to_vec())]);
+    assert!(build(dir.clone()).is_err());
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,87 @@
+use t5_xfile_defs::{
+    XFilePlatform,
+    common::Vec3,
+    gfx_world::{GfxCompressedLightGridColors, GfxLightGrid, GfxLightGridEntry},
+};
+
+/// One row along X (at grid y = 4096) with two columns along Z: the first
+/// covering z 2048..=2049, the second only z 2049.
+fn grid() -> GfxLightGrid {
+    let entry = |colors_index| GfxLightGridEntry {
+        colors_index,
+        primary_light_index: 0,
+        needs_trace: 0,
+    };
+    let colors = |v| GfxCompressedLightGridColors { rgb: [[v; 3]; 56] };
+
+    GfxLightGrid {
+        has_light_regions: false,
+        sun_primary_light_index: 0,
+        mins: [4096, 4096, 2048],
+        maxs: [4097, 4096, 2049],
+        row_axis: 1,
+        col_axis: 0,
+        row_data_start: vec![0],
+        raw_row_data: vec![
+            4096, // col_start
+            2,    // col_count
+            2048, // z_start
+            2,    // z_count
+            0,
+            0, // first_entry
+            u16::from_le_bytes([0, 2]),
+            u16::from_le_bytes([1, 1]),
+        ],
+        entries: vec![entry(0), entry(1), entry(1)],
+        colors: vec![colors(0), colors(255)],
+    }
+}
+
+#[test]
+fn entry_index() {
+    let grid = grid();
+    let le = XFilePlatform::Windows;
+    assert_eq!(grid.entry_index([4096, 4096, 2048], le), Some(0));
+    assert_eq!(grid.entry_index([4096, 4096, 2049], le), Some(1));
+    assert_eq!(grid.entry_index([4097, 4096, 2048], le), None);
+    assert_eq!(grid.entry_index([4097, 4096, 2049], le), Some(2));
+    assert_eq!(grid.entry_index([4098, 4096, 2048], le), None);
+    assert_eq!(grid.entry_index([4096, 4097, 2048], le), None);
+}
+
+#[test]
+fn sample() {
+    let grid = grid();
+    let le = XFilePlatform::Windows;
+
+    // exactly on grid points
+    let at = |z| grid.sample(Vec3::from([0.0, 0.0, z]), le).map(|s| s[0][0]);
+    assert_eq!(at(0.0), Some(0.0));
+    assert_eq!(at(64.0), Some(1.0));
+
+    // halfway between them
+    assert_eq!(at(32.0), Some(0.5));
+
+    // in the middle of all 8, 3 of which have samples
+    let [x, _, _] = GfxLightGrid::grid_point(Vec3::from([16.0, 0.0, 0.0]));
+    assert_eq!(x, 4096);
+    let s = grid.sample(Vec3::from([16.0, 16.0, 32.0]), le).unwrap();
+    assert!((s[0][0] - 2.0 / 3.0).abs() < 1.0e-6);
+
+    assert_eq!(grid.sample(Vec3::from([0.0, 64.0, 0.0]), le), None);
+}
+
+#[test]
+fn big_endian_row_data() {
+    let mut grid = grid();
+    grid.raw_row_data[4..].copy_from_slice(&[
+        0,
+        0,
+        u16::from_be_bytes([0, 2]),
+        u16::from_be_bytes([1, 1]),
+    ]);
+    assert_eq!(
+        grid.entry_index([4097, 4096, 2049], XFilePlatform::Xbox360),
+        Some(2)
+    );
+}
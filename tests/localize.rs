@@ -0,0 +1,105 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    localize::{LocalizeTable, join_name, split_name},
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+fn table() -> LocalizeTable {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_QUIT", "Quit \"now\"\n")
+        .with_localize_entry("WEAPON_AK47", "AK-47, \"Kalashnikov\"");
+    let bytes = xfile.to_bytes();
+    let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    LocalizeTable::from_assets(&assets)
+}
+
+#[test]
+fn names() {
+    assert_eq!(split_name("MENU_START_GAME"), ("MENU", "START_GAME"));
+    assert_eq!(split_name("NOPREFIX"), ("", "NOPREFIX"));
+    assert_eq!(join_name("MENU", "START_GAME"), "MENU_START_GAME");
+    assert_eq!(join_name("", "NOPREFIX"), "NOPREFIX");
+}
+
+#[test]
+fn lookup() {
+    let mut table = table();
+    // the raw file is ignored
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get("MENU_START_GAME"), Some("Start Game"));
+    assert_eq!(table.get("MENU_MISSING"), None);
+
+    let found = table
+        .search("QUIT")
+        .map(|e| e.name.get())
+        .collect::<Vec<_>>();
+    assert_eq!(found, ["MENU_QUIT"]);
+    assert_eq!(table.search("kalashnikov").count(), 1);
+    assert_eq!(table.search("menu_").count(), 2);
+
+    let mut update = LocalizeTable::new();
+    update.insert("MENU_START_GAME", "Start");
+    update.insert("MENU_QUIT", "Quit \"now\"\n");
+    update.insert("MENU_OPTIONS", "Options");
+    // one changed and one new
+    assert_eq!(table.merge(&update), 2);
+    assert_eq!(table.get("MENU_START_GAME"), Some("Start"));
+    assert_eq!(table.iter().last().unwrap().name.get(), "MENU_OPTIONS");
+
+    let assets = table.to_assets(XFilePlatform::Windows);
+    assert_eq!(assets.len(), 4);
+    assert!(matches!(
+        &assets[0],
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e))) if e.value.get() == "Start"
+    ));
+    assert_eq!(LocalizeTable::from_assets(&assets), table);
+}
+
+#[test]
+fn str_files() {
+    let table = table();
+    let files = table.to_str_files("english");
+    assert_eq!(files.keys().collect::<Vec<_>>(), ["MENU", "WEAPON"]);
+    assert!(files["MENU"].contains("REFERENCE           QUIT\n"));
+    assert!(files["MENU"].contains("LANG_ENGLISH        \"Quit \\\"now\\\"\\n\"\n"));
+
+    let mut parsed = LocalizeTable::new();
+    for (prefix, contents) in &files {
+        parsed.merge(&LocalizeTable::parse_str_file(contents, prefix, "ENGLISH").unwrap());
+    }
+    assert_eq!(parsed, table);
+
+    // other languages are skipped
+    assert!(
+        LocalizeTable::parse_str_file(&files["MENU"], "MENU", "FRENCH")
+            .unwrap()
+            .is_empty()
+    );
+    assert!(LocalizeTable::parse_str_file("LANG_ENGLISH \"x\"\n", "MENU", "ENGLISH").is_err());
+    assert!(
+        LocalizeTable::parse_str_file("REFERENCE X\nLANG_ENGLISH x\n", "MENU", "ENGLISH").is_err()
+    );
+}
+
+#[test]
+fn csv() {
+    let table = table();
+    let csv = table.to_csv();
+    assert!(csv.starts_with("name,value\r\n"));
+    assert_eq!(LocalizeTable::parse_csv(&csv).unwrap(), table);
+    assert!(LocalizeTable::parse_csv("a,b,c\r\n").is_err());
+}
@@ -0,0 +1,98 @@
+use t5_xfile_defs::{
+    MapEnts, XGlobals, XString,
+    com_world::ComWorld,
+    techset::GfxImage,
+    xasset::{self, XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn image(name: &str) -> XAssetGeneric {
+    XAssetGeneric::Image(Some(Box::new(GfxImage {
+        name: xstring(name),
+        ..Default::default()
+    })))
+}
+
+fn bag() -> XAssetBag {
+    [
+        image("loadscreen_mp_array"),
+        image("loadscreen_mp_cracked"),
+        XAssetGeneric::ComWorld(Some(Box::new(ComWorld {
+            name: xstring("maps/mp/mp_array.d3dbsp"),
+            ..Default::default()
+        }))),
+        XAssetGeneric::XGlobals(Some(Box::new(XGlobals {
+            name: xstring("mp_array"),
+            gump_reserve: 2,
+            ..Default::default()
+        }))),
+        XAssetGeneric::MapEnts(Some(Box::new(MapEnts {
+            name: xstring("maps/mp/mp_array.d3dbsp"),
+            entity_string: xstring("{\n\"classname\" \"worldspawn\"\n}\n"),
+        }))),
+    ]
+    .into_iter()
+    .map(XAsset::PC)
+    .collect()
+}
+
+#[test]
+fn names() {
+    assert_eq!(xasset::map_name("maps/mp/mp_array.d3dbsp"), "mp_array");
+    assert_eq!(xasset::map_name("maps/kowloon.d3dbsp"), "kowloon");
+    assert_eq!(xasset::map_name("mp_array"), "mp_array");
+    assert_eq!(xasset::loadscreen_name("mp_array"), "loadscreen_mp_array");
+}
+
+#[test]
+fn accessors() {
+    let bag = bag();
+    assert_eq!(bag.xglobals("mp_array").unwrap().gump_reserve, 2);
+    assert_eq!(
+        bag.loadscreen("mp_array").unwrap().name.get(),
+        "loadscreen_mp_array"
+    );
+    let ents = bag.map_ents("mp_array").unwrap().entities().unwrap();
+    assert_eq!(ents[0].classname(), Some("worldspawn"));
+
+    assert!(bag.xglobals("mp_cracked").is_none());
+    assert!(bag.map_ents("mp_cracked").is_none());
+    assert!(bag.loadscreen("mp_nuked").is_none());
+}
+
+#[test]
+fn dependencies() {
+    let bag = bag();
+    let map_ents = bag
+        .get(XAssetType::MAP_ENTS, "maps/mp/mp_array.d3dbsp")
+        .unwrap();
+    assert_eq!(
+        map_ents.dependencies(),
+        [
+            (XAssetType::IMAGE, "loadscreen_mp_array".to_string()),
+            (XAssetType::XGLOBALS, "mp_array".to_string()),
+        ]
+    );
+
+    let map = bag.map("mp_array");
+    let names = map
+        .iter()
+        .map(|a| (a.asset_type(), a.name().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            (XAssetType::COMWORLD, "maps/mp/mp_array.d3dbsp"),
+            (XAssetType::IMAGE, "loadscreen_mp_array"),
+            (XAssetType::XGLOBALS, "mp_array"),
+            (XAssetType::MAP_ENTS, "maps/mp/mp_array.d3dbsp"),
+        ]
+    );
+
+    // a map with only a loadscreen in the bag
+    assert_eq!(bag.map("mp_cracked").len(), 1);
+    assert!(bag.map("mp_nuked").is_empty());
+}
@@ -0,0 +1,96 @@
+use t5_xfile_defs::{
+    ErrorKind, MapEnts, XString,
+    entities::{self, Entity},
+};
+
+const LUMP: &str = r#"{
+"classname" "worldspawn"
+"ambient" ".1"
+}
+{
+"classname" "trigger_multiple"
+"targetname" "door_trig"
+"target" "door"
+"origin" "0 0 64"
+}
+{
+"classname" "script_struct"
+"targetname" "door"
+"script_noteworthy" "two words"
+}
+"#;
+
+fn map_ents() -> MapEnts {
+    MapEnts {
+        name: XString("maps/mp/mp_test.d3dbsp".to_string()),
+        entity_string: XString(LUMP.to_string()),
+    }
+}
+
+#[test]
+fn parse_and_query() {
+    let map_ents = map_ents();
+    let ents = map_ents.entities().unwrap();
+    assert_eq!(ents.len(), 3);
+    assert_eq!(ents[0].classname(), Some("worldspawn"));
+    assert_eq!(ents[0].get("ambient"), Some(".1"));
+    assert_eq!(ents[1].origin(), Some([0.0, 0.0, 64.0]));
+    assert_eq!(ents[2].get("script_noteworthy"), Some("two words"));
+    assert_eq!(ents[2].origin(), None);
+
+    let triggers = map_ents.find_by_classname("trigger_multiple").unwrap();
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(triggers[0].get("target"), Some("door"));
+
+    let door = entities::by_targetname(&ents, "door").collect::<Vec<_>>();
+    assert_eq!(door.len(), 1);
+    assert_eq!(door[0].classname(), Some("script_struct"));
+    assert!(map_ents.find_by_targetname("nothing").unwrap().is_empty());
+}
+
+#[test]
+fn edit_and_write() {
+    let mut map_ents = map_ents();
+    assert_eq!(
+        entities::write(&map_ents.entities().unwrap()).unwrap(),
+        LUMP
+    );
+
+    let mut ents = map_ents.entities().unwrap();
+    assert_eq!(ents[1].set("target", "gate"), Some("door".to_string()));
+    assert_eq!(ents[1].remove("origin"), Some("0 0 64".to_string()));
+    let mut spawn = Entity::new();
+    spawn.set("classname", "mp_tdm_spawn");
+    spawn.set("origin", "1 2 3");
+    ents.push(spawn);
+    map_ents.set_entities(&ents).unwrap();
+
+    let reparsed = map_ents.entities().unwrap();
+    assert_eq!(reparsed, ents);
+    assert_eq!(reparsed[1].get("target"), Some("gate"));
+    assert_eq!(reparsed[3].origin(), Some([1.0, 2.0, 3.0]));
+
+    let mut bad = Entity::new();
+    bad.set("classname", "a\"b");
+    let err = map_ents.set_entities(&[bad]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+    assert_eq!(map_ents.entities().unwrap(), ents);
+}
+
+#[test]
+fn malformed() {
+    for (lump, message) in [
+        ("{\n\"classname\" \"a\"\n", "unterminated entity"),
+        ("{\n\"classname\" \"a\n}\n", "unterminated string on line 2"),
+        ("{\n\"classname\"\n}\n", "key without a value on line 3"),
+        ("{\n{\n}\n", "unexpected '{' on line 2"),
+        ("\"classname\" \"a\"\n", "unexpected '\"' on line 1"),
+    ] {
+        let err = entities::parse(lump).unwrap_err();
+        let ErrorKind::Parse(s) = err.kind() else {
+            panic!("{err:?}");
+        };
+        assert!(s.contains(message), "{s:?} for {lump:?}");
+    }
+    assert!(entities::parse("").unwrap().is_empty());
+}
@@ -0,0 +1,107 @@
+use t5_xfile_defs::common::{Mat3, Mat4, Vec3, Vec4};
+#[cfg(feature = "glam")]
+use t5_xfile_defs::glam;
+#[cfg(feature = "nalgebra")]
+use t5_xfile_defs::nalgebra;
+
+/// Turns 90 degrees to the left (+X to +Y).
+fn turn() -> Mat3 {
+    Mat3::from([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+}
+
+/// [`turn`], then moves by (10, 20, 30).
+fn placement() -> Mat4 {
+    Mat4::from([
+        [0.0, 1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [10.0, 20.0, 30.0, 1.0],
+    ])
+}
+
+#[test]
+fn mul() {
+    assert_eq!(
+        turn() * Vec3::from([1.0, 0.0, 0.0]),
+        Vec3::from([0.0, 1.0, 0.0])
+    );
+    assert_eq!(
+        turn() * turn() * Vec3::from([1.0, 2.0, 3.0]),
+        Vec3::from([-1.0, -2.0, 3.0])
+    );
+
+    let twice = placement() * placement();
+    assert_eq!(
+        twice.transform_point(Vec3::from([1.0, 0.0, 0.0])),
+        Vec3::from([-11.0, 30.0, 60.0])
+    );
+}
+
+#[test]
+fn transform() {
+    let m = placement();
+    assert_eq!(
+        m.transform_point(Vec3::from([1.0, 2.0, 3.0])),
+        Vec3::from([8.0, 21.0, 33.0])
+    );
+    assert_eq!(
+        m.transform_vector(Vec3::from([1.0, 2.0, 3.0])),
+        Vec3::from([-2.0, 1.0, 3.0])
+    );
+}
+
+#[test]
+fn from_quat() {
+    let half = core::f32::consts::FRAC_1_SQRT_2;
+    let m = Mat3::from_quat(Vec4::from([0.0, 0.0, half, half])).get();
+    let expected = turn().get();
+    for (col, expected) in m.iter().zip(expected) {
+        for (a, b) in col.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-6, "{m:?}");
+        }
+    }
+    // not normalized
+    assert_eq!(
+        Mat3::from_quat(Vec4::from([0.0, 0.0, 0.0, 2.0])),
+        Mat3::identity()
+    );
+    assert_eq!(Mat3::from_quat(Vec4::default()), Mat3::identity());
+
+    assert_eq!(
+        Mat4::from_rotation_translation(turn(), Vec3::from([10.0, 20.0, 30.0])),
+        placement()
+    );
+    assert_eq!(Mat4::identity() * placement(), placement());
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam() {
+    let v = glam::Vec3::from(Vec3::from([1.0, 2.0, 3.0]));
+    assert_eq!(v, glam::Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(Vec3::from(v), Vec3::from([1.0, 2.0, 3.0]));
+
+    let m = glam::Mat4::from(placement());
+    assert_eq!(
+        m.transform_point3(v),
+        glam::Vec3::from(placement().transform_point(Vec3::from(v)))
+    );
+    assert_eq!(Mat4::from(m), placement());
+    assert_eq!(glam::Mat3::from(turn()) * glam::Vec3::X, glam::Vec3::Y);
+}
+
+#[cfg(feature = "nalgebra")]
+#[test]
+fn nalgebra() {
+    let p = nalgebra::Point3::from(Vec3::from([1.0, 2.0, 3.0]));
+    let m = nalgebra::Matrix4::from(placement());
+    assert_eq!(
+        Vec3::from((m * p.to_homogeneous()).xyz()),
+        placement().transform_point(Vec3::from([1.0, 2.0, 3.0]))
+    );
+    assert_eq!(Mat4::from(m), placement());
+    assert_eq!(
+        nalgebra::Matrix3::from(turn()) * nalgebra::Vector3::x(),
+        nalgebra::Vector3::y()
+    );
+}
@@ -0,0 +1,106 @@
+use t5_xfile_defs::menu::ScriptCondition;
+
+fn chain(len: usize) -> ScriptCondition {
+    let mut head = ScriptCondition {
+        fire_on_true: false,
+        block_id: 0,
+        construct_id: len as i32 - 1,
+        next: None,
+    };
+    for i in (0..len - 1).rev() {
+        head = ScriptCondition {
+            fire_on_true: false,
+            block_id: 0,
+            construct_id: i as i32,
+            next: Some(Box::new(head)),
+        };
+    }
+    head
+}
+
+#[test]
+fn linked_list() {
+    let list = chain(3);
+    assert_eq!(list.chain_len(), 3);
+    assert_eq!(
+        list.iter().map(|c| c.construct_id).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert_eq!(list.next.as_ref().unwrap().chain_len(), 2);
+}
+
+#[test]
+fn long_linked_list() {
+    // deep enough to overflow the stack if dropped recursively
+    let list = chain(1_000_000);
+    assert_eq!(list.chain_len(), 1_000_000);
+    drop(list);
+}
+
+#[cfg(feature = "deserializer")]
+#[test]
+fn item_parents() {
+    use t5_xfile_defs::{
+        XFilePlatform, XString,
+        menu::{MenuDef, MenuList},
+        xasset::{XAsset, XAssetGeneric},
+    };
+    use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+    fn parents<const N: usize>(menu: &MenuDef<N>) -> Vec<(Option<u32>, Option<usize>)> {
+        menu.items
+            .iter()
+            .map(|item| (item.parent.ptr(), item.parent.index()))
+            .collect()
+    }
+
+    fn check<const N: usize>(menus: Vec<Box<MenuDef<N>>>) {
+        // On their own, each menu is its items' parent, but the second one
+        // has an item that points somewhere else.
+        assert_eq!(parents(&menus[0]), [(Some(0x40), Some(0)); 2]);
+        assert_eq!(
+            parents(&menus[1]),
+            [(Some(0x80), Some(0)), (Some(0xC0), None)]
+        );
+        assert_eq!(menus[1].offset(), Some(0x80));
+
+        let mut list = MenuList {
+            name: XString("ui/menus.txt".to_string()),
+            menus,
+        };
+        assert_eq!(list.intern_table().len(), 2);
+        assert_eq!(list.resolve_item_parents(), 1);
+        assert_eq!(parents(&list.menus[0]), [(Some(0x40), Some(0)); 2]);
+        assert_eq!(
+            parents(&list.menus[1]),
+            [(Some(0x80), Some(1)), (Some(0xC0), None)]
+        );
+    }
+
+    // Only PC menus: on consoles, `ItemDefRaw` is padded for one local
+    // client rather than four, so items can't be deserialized yet.
+    for platform in [XFilePlatform::Windows, XFilePlatform::macOS] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_menu_items("a", 1, &[0x40, 0x40])
+            .with_menu_items("b", 0, &[0x80, 0xC0]);
+        let bytes = xfile.to_bytes();
+        let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, platform, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+
+        match <[XAsset; 2]>::try_from(assets).unwrap() {
+            [
+                XAsset::PC(XAssetGeneric::Menu(Some(a))),
+                XAsset::PC(XAssetGeneric::Menu(Some(b))),
+            ] => check(vec![a, b]),
+            assets => panic!("{assets:?}"),
+        }
+    }
+}
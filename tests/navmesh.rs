@@ -0,0 +1,116 @@
+#![cfg(feature = "navmesh")]
+
+use t5_xfile_defs::{clipmap::CBrush, common::Vec3};
+use t5_xfile_deserializer::navmesh::{NavMesh, NavMeshOptions, TILES_MAGIC, TILES_VERSION};
+
+fn box_brush(mins: [f32; 3], maxs: [f32; 3], contents: i32) -> CBrush {
+    CBrush {
+        mins: Vec3::from(mins),
+        contents,
+        maxs: Vec3::from(maxs),
+        sides: vec![],
+        axial_cflags: [[0; 3]; 2],
+        axial_sflags: [[0; 3]; 2],
+        verts: vec![],
+    }
+}
+
+/// Two floor tiles, one with a ceiling too low to stand under, and a
+/// non-solid brush.
+fn brushes() -> Vec<CBrush> {
+    vec![
+        box_brush([0.0, 0.0, -16.0], [128.0, 128.0, 0.0], 1),
+        box_brush([128.0, 0.0, -16.0], [256.0, 128.0, 0.0], 1),
+        box_brush([0.0, 0.0, 40.0], [128.0, 128.0, 48.0], 1),
+        box_brush([512.0, 0.0, 0.0], [640.0, 128.0, 16.0], 0x20),
+    ]
+}
+
+fn polygon(navmesh: &NavMesh, i: usize) -> Vec<[f32; 3]> {
+    navmesh.polygons[i]
+        .iter()
+        .map(|&v| navmesh.vertices[v as usize])
+        .collect()
+}
+
+#[test]
+fn walkable_faces() {
+    let navmesh = NavMesh::from_brushes(&brushes(), &NavMeshOptions::default());
+    assert_eq!(navmesh.polygons.len(), 2);
+
+    let mut tops = (0..2)
+        .map(|i| {
+            let polygon = polygon(&navmesh, i);
+            assert!(polygon.iter().all(|p| p[2] == polygon[0][2]));
+
+            // counterclockwise seen from above
+            let area = polygon
+                .iter()
+                .zip(polygon.iter().cycle().skip(1))
+                .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+                .sum::<f32>();
+            assert!(area > 0.0);
+
+            let min_x = polygon.iter().map(|p| p[0]).fold(f32::MAX, f32::min);
+            (min_x, polygon[0][2])
+        })
+        .collect::<Vec<_>>();
+    tops.sort_by(|a, b| a.0.total_cmp(&b.0));
+    // the ceiling's top, and the floor that isn't under it
+    assert_eq!(tops, [(0.0, 48.0), (128.0, 0.0)]);
+
+    // with a shorter agent, the floor under the ceiling is walkable too
+    let navmesh = NavMesh::from_brushes(&brushes(), &NavMeshOptions::new().with_agent_height(32.0));
+    assert_eq!(navmesh.polygons.len(), 3);
+    // and the two floors share an edge
+    assert_eq!(navmesh.vertices.len(), 10);
+}
+
+#[test]
+fn slopes() {
+    // a unit cube with its top corner along +X+Y+Z cut off, making a face
+    // about 55 degrees steep
+    let n = 1.0 / 3.0f32.sqrt();
+    let mut brush = box_brush([0.0; 3], [1.0; 3], 1);
+    brush.sides.push(t5_xfile_defs::xmodel::CBrushSide {
+        plane: Some(Box::new(t5_xfile_defs::xmodel::CPlane {
+            normal: Vec3::from([n; 3]),
+            dist: 2.0 * n,
+            ..Default::default()
+        })),
+        cflags: 0,
+        sflags: 0,
+    });
+    let brushes = [brush];
+
+    let navmesh = NavMesh::from_brushes(&brushes, &NavMeshOptions::default());
+    assert_eq!(navmesh.polygons.len(), 1);
+
+    let navmesh = NavMesh::from_brushes(&brushes, &NavMeshOptions::new().with_max_slope(60.0));
+    assert_eq!(navmesh.polygons.len(), 2);
+}
+
+#[test]
+fn output() {
+    let navmesh = NavMesh::from_brushes(&brushes(), &NavMeshOptions::default());
+
+    let obj = navmesh.to_obj();
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+    let faces = obj
+        .lines()
+        .filter(|l| l.starts_with("f "))
+        .collect::<Vec<_>>();
+    assert_eq!(faces.len(), 2);
+    assert_eq!(faces[0].split(' ').count(), 5);
+
+    let tiles = navmesh.tiles(128.0);
+    assert_eq!(tiles.keys().copied().collect::<Vec<_>>(), [(0, 0), (1, 0)]);
+
+    let bytes = navmesh.to_tiles(128.0);
+    assert_eq!(bytes[..4], TILES_MAGIC);
+    assert_eq!(bytes[4..8], TILES_VERSION.to_le_bytes());
+    assert_eq!(bytes[8..12], 128.0f32.to_le_bytes());
+    assert_eq!(bytes[12..16], 8u32.to_le_bytes());
+    // header, vertices, tile count, and two tiles of one quad each
+    assert_eq!(bytes.len(), 16 + 8 * 12 + 4 + 2 * (12 + 4 + 4 * 4));
+}
@@ -0,0 +1,60 @@
+use t5_xfile_defs::{ErrorKind, XFILE_VERSION, XFileHeader, XFilePlatform, XFileVersion};
+
+#[test]
+fn round_trip() {
+    for platform in XFilePlatform::ALL {
+        assert_eq!(platform.name().parse::<XFilePlatform>().unwrap(), platform);
+        assert_eq!(
+            platform.to_string().parse::<XFilePlatform>().unwrap(),
+            platform
+        );
+    }
+
+    assert_eq!(
+        " XBOX 360 ".parse::<XFilePlatform>().unwrap(),
+        XFilePlatform::Xbox360
+    );
+    let err = "dreamcast".parse::<XFilePlatform>().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::UnknownPlatform(s) if s == "dreamcast"));
+}
+
+#[test]
+fn value_enum() {
+    use clap::ValueEnum;
+
+    assert_eq!(
+        XFilePlatform::from_str("ps3", false).unwrap(),
+        XFilePlatform::PS3
+    );
+    let names = XFilePlatform::value_variants()
+        .iter()
+        .map(|p| p.to_possible_value().unwrap().get_name().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["windows", "macos", "xbox360", "ps3", "wii"]);
+}
+
+#[test]
+fn helpers_dont_panic() {
+    for platform in XFilePlatform::ALL {
+        assert_eq!(platform.is_be(), !platform.is_le());
+        assert_eq!(
+            XFileHeader::new(platform).is_some(),
+            XFileVersion::from_platform(platform).is_some()
+        );
+    }
+
+    assert!(XFilePlatform::Wii.is_be());
+    assert!(XFileVersion::from_platform(XFilePlatform::Wii).is_none());
+    assert!(!XFileVersion::is_valid(XFILE_VERSION, XFilePlatform::Wii));
+
+    // headers are read in the platform's byte order, so the version is the
+    // same for every platform
+    for platform in [XFilePlatform::Windows, XFilePlatform::PS3] {
+        assert!(XFileVersion::is_valid(XFILE_VERSION, platform));
+        assert!(!XFileVersion::is_valid(
+            XFILE_VERSION.swap_bytes(),
+            platform
+        ));
+        assert_eq!(XFileHeader::new(platform).unwrap().version, XFILE_VERSION);
+    }
+}
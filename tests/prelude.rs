@@ -0,0 +1,33 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_deserializer::{prelude::*, testgen::SyntheticXFile};
+
+#[test]
+fn deserialize_with_prelude() {
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_map_ents("maps/mp/mp_a.d3dbsp", "{\n}\n")
+        .to_bytes();
+
+    let assets: Result<Vec<XAsset>> =
+        T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate())
+            .and_then(|de| de.no_cache())
+            .and_then(|de| de.deserialize_remaining());
+    let bag = assets.unwrap().into_iter().collect::<XAssetBag>();
+    assert!(bag.contains(XAssetType::MAP_ENTS, "maps/mp/mp_a.d3dbsp"));
+
+    let Some(XAsset::PC(XAssetGeneric::MapEnts(Some(map_ents)))) = bag.iter().next() else {
+        panic!("expected map entities");
+    };
+    assert_eq!(HasName::name(&**map_ents), "maps/mp/mp_a.d3dbsp");
+
+    let options = T5XFileOpenOptions::new(XFilePlatform::Windows).with_silent(true);
+    let err: Error = T5XFileDeserializer::open("does/not/exist.ff", options)
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind(), ErrorKind::Io(_)));
+
+    assert_eq!(Vec3::from([1.0, 2.0, 3.0]), Vec3::from([1.0, 2.0, 3.0]));
+}
@@ -0,0 +1,62 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    com_world::{ComPrimaryLight, GfxLightType, PrimaryLight},
+    gfx_world::{GfxLightRegion, GfxShadowGeometry},
+};
+
+fn light(type_: GfxLightType, name: &str) -> ComPrimaryLight {
+    ComPrimaryLight {
+        type_: type_ as u8,
+        def_name: XString(name.to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn correlate() {
+    let lights = [
+        light(GfxLightType::NONE, ""),
+        light(GfxLightType::DIR, "sun"),
+        light(GfxLightType::SPOT, "light_spot"),
+        light(GfxLightType::OMNI, "light_omni"),
+    ];
+    let shadow_geom = (0..4)
+        .map(|i| GfxShadowGeometry {
+            sorted_surf_index: vec![i],
+            smodel_index: vec![],
+        })
+        .collect::<Vec<_>>();
+    let light_region = vec![GfxLightRegion { hulls: vec![] }; 4];
+
+    let list = PrimaryLight::correlate(&lights, 1, 4, &shadow_geom, &light_region).unwrap();
+    let summary = list
+        .iter()
+        .map(|l| {
+            (
+                l.id,
+                l.light.def_name.get(),
+                l.light_type().unwrap(),
+                l.is_sun,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            (1, "sun", GfxLightType::DIR, true),
+            (2, "light_spot", GfxLightType::SPOT, false),
+            (3, "light_omni", GfxLightType::OMNI, false),
+        ]
+    );
+    assert_eq!(list[1].shadow_geometry.unwrap().sorted_surf_index, [2u16]);
+    assert!(list.iter().all(|l| l.region.is_some()));
+
+    // the GfxWorld only has shadows and regions for some of the lights
+    let list = PrimaryLight::correlate(&lights, 1, 4, &shadow_geom[..2], &[]).unwrap();
+    assert!(list[0].shadow_geometry.is_some());
+    assert!(list[1].shadow_geometry.is_none());
+    assert!(list.iter().all(|l| l.region.is_none()));
+
+    let err = PrimaryLight::correlate(&lights, 1, 5, &shadow_geom, &light_region).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+}
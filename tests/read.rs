@@ -0,0 +1,135 @@
+use bincode::Options;
+use t5_xfile_defs::{
+    ErrorKind, FixedString, Result, XFileHeader, XFileRead, XFileReader,
+    common::Vec3,
+    read::{self, Field},
+    techset::MaterialArgumentCodeConst,
+};
+
+struct SliceReader<'a> {
+    buf: &'a [u8],
+    little_endian: bool,
+    fields: Option<Vec<Field>>,
+}
+
+impl<'a> SliceReader<'a> {
+    fn new(buf: &'a [u8], little_endian: bool) -> Self {
+        Self {
+            buf,
+            little_endian,
+            fields: None,
+        }
+    }
+}
+
+impl XFileReader for SliceReader<'_> {
+    fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        let (bytes, rest) = self.buf.split_at(buf.len());
+        buf.copy_from_slice(bytes);
+        self.buf = rest;
+        Ok(())
+    }
+
+    fn is_tracing(&self) -> bool {
+        self.fields.is_some()
+    }
+
+    fn enter_field(&mut self, field: Field) {
+        self.fields.as_mut().unwrap().push(field);
+    }
+}
+
+#[derive(Debug, PartialEq, XFileRead)]
+struct Header {
+    magic: [u8; 4],
+    count: u16,
+    origin: Vec3,
+    name: FixedString<4>,
+}
+
+#[derive(Debug, PartialEq, XFileRead)]
+struct Wrapped(u32, [i8; 2]);
+
+const BYTES: [u8; 22] = [
+    b'I', b'W', b'f', b'f', // magic
+    0x01, 0x02, // count
+    0x00, 0x00, 0x80, 0x3F, // origin
+    0x00, 0x00, 0x00, 0x40, //
+    0x00, 0x00, 0x40, 0x40, //
+    b'a', b'b', 0x00, 0x00, // name
+];
+
+#[test]
+fn derived() {
+    let header = Header::xfile_read(&mut SliceReader::new(&BYTES, true)).unwrap();
+    assert_eq!(&header.magic, b"IWff");
+    assert_eq!(header.count, 0x0201);
+    assert_eq!(header.origin, Vec3::from([1.0, 2.0, 3.0]));
+    assert_eq!(header.name.to_string(), "ab");
+
+    let header = Header::xfile_read(&mut SliceReader::new(&BYTES, false)).unwrap();
+    assert_eq!(header.count, 0x0102);
+
+    let wrapped = Wrapped::xfile_read(&mut SliceReader::new(&[1, 0, 0, 0, 0xFF, 2], true));
+    assert_eq!(wrapped.unwrap(), Wrapped(1, [-1, 2]));
+}
+
+#[test]
+fn same_as_bincode() {
+    let bytes = *b"IWffu100\xD9\x01\x00\x00";
+    let read = XFileHeader::xfile_read(&mut SliceReader::new(&bytes, true)).unwrap();
+    let deserialized: XFileHeader = bincode::DefaultOptions::new()
+        .with_little_endian()
+        .with_fixint_encoding()
+        .deserialize(&bytes)
+        .unwrap();
+    assert_eq!(read.magic, deserialized.magic);
+    assert_eq!(read.version, deserialized.version);
+    assert_eq!(read.version, 0x1D9);
+}
+
+#[test]
+fn traced_fields() {
+    let mut r = SliceReader::new(&BYTES, true);
+    r.fields = Some(Vec::new());
+    Header::xfile_read(&mut r).unwrap();
+
+    let fields = r.fields.unwrap();
+    assert_eq!(fields[0], Field::Named("magic"));
+    assert_eq!(fields[1..5], [0, 1, 2, 3].map(Field::Index));
+    assert_eq!(fields[5], Field::Named("count"));
+    assert!(fields.contains(&Field::Named("origin")));
+    assert!(fields.contains(&Field::Named("name")));
+}
+
+#[test]
+fn from_bytes() {
+    let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+    let wrapped = read::from_bytes::<Wrapped>(&bytes, false).unwrap();
+    assert_eq!(wrapped, Wrapped(0x01020304, [5, 6]));
+    let wrapped = read::from_bytes::<Wrapped>(&bytes, true).unwrap();
+    assert_eq!(wrapped, Wrapped(0x04030201, [5, 6]));
+
+    let e = read::from_bytes::<Wrapped>(&bytes[..5], true).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e:?}");
+}
+
+#[test]
+fn union_members() {
+    // index 0x0102, first row 3, row count 4, as laid out in the file
+    let expected = MaterialArgumentCodeConst {
+        index: 0x0102,
+        first_row: 3,
+        row_count: 4,
+    };
+    let le = u32::from_le_bytes([0x02, 0x01, 3, 4]);
+    let be = u32::from_be_bytes([0x01, 0x02, 3, 4]);
+    assert_eq!(MaterialArgumentCodeConst::from_u32(le, true), expected);
+    assert_eq!(MaterialArgumentCodeConst::from_u32(be, false), expected);
+    assert_eq!(expected.as_u32(true), le);
+    assert_eq!(expected.as_u32(false), be);
+}
@@ -0,0 +1,66 @@
+//! Checks that the fixed-layout reader (the `fast-reader` feature) decodes
+//! the synthetic fixtures the same as the default `Read`-based one, on every
+//! platform (so in both byte orders).
+//!
+//! The readers are picked at compile time, so the expected summaries in
+//! `tests/fixtures` are generated with the default reader and compared
+//! against whichever one this is built with. CI runs this both with and
+//! without `fast-reader`.
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_defs::XFilePlatform;
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder, summary::summarize, testgen::SyntheticXFile,
+};
+
+use common::check_golden;
+
+fn fixture(platform: XFilePlatform) -> SyntheticXFile {
+    SyntheticXFile::new(platform)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_map_ents(
+            "maps/mp/mp_test.d3dbsp",
+            "{\n\"classname\" \"worldspawn\"\n}\n",
+        )
+        .with_pack_index("sound.pak", &[(1, 0, 0x10), (0xDEADBEEF, 0x800, 0x20)])
+        .with_string_table("mp/t.csv", 2, &["a", "b", "c", ""])
+        .with_menu("menu", 2)
+        .with_gfx_world(
+            "maps/mp/mp_test",
+            [
+                &[[1.0, -2.0, 0.5, 1e-3]],
+                &[[f32::MAX, f32::MIN, 0.0, -0.0]],
+            ],
+        )
+}
+
+#[test]
+fn same_as_default_reader() {
+    // Wii Fastfiles can't be deserialized yet
+    for platform in XFilePlatform::ALL
+        .into_iter()
+        .filter(|&p| p != XFilePlatform::Wii)
+    {
+        let xfile = fixture(platform);
+        let bytes = xfile.to_bytes();
+        let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, platform, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+        assert_eq!(assets.len(), 7);
+
+        let summary = summarize(&assets).unwrap();
+        let platform = format!("{platform:?}").to_lowercase();
+        check_golden(&format!("reader_{platform}.txt"), &summary);
+    }
+}
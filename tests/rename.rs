@@ -0,0 +1,151 @@
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    rename::rename_script_strings,
+    techset::{GfxImage, Material, MaterialTextureDef, MaterialTextureDefInfo},
+    weapon::{WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+    xmodel::XModel,
+};
+
+use common::zeroed;
+
+fn name(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn material() -> Material {
+    let mut image: GfxImage = zeroed();
+    image.name = name("img_a");
+    let mut material: Material = zeroed();
+    material.info.name = name("mtl_a");
+    material.textures = vec![MaterialTextureDef {
+        u: MaterialTextureDefInfo::Image(Some(Box::new(image))),
+        ..zeroed()
+    }];
+    material
+}
+
+fn model() -> XModel {
+    let mut model: XModel = zeroed();
+    model.name = name("mdl_a");
+    model.material_handles = vec![material().into()];
+    model
+}
+
+fn bag() -> XAssetBag {
+    let mut image: GfxImage = zeroed();
+    image.name = name("img_a");
+
+    let mut weapon: WeaponVariantDef = zeroed();
+    weapon.internal_name = name("wpn_a");
+    weapon.xanims = vec![name("anim_a"), name("anim_b")];
+    weapon.weap_def = Some(Box::new(WeaponDef {
+        hand_xmodel: Some(Box::new(model())),
+        ..zeroed()
+    }));
+
+    [
+        XAssetGeneric::Image(Some(Box::new(image))),
+        XAssetGeneric::Material(Some(Box::new(material()))),
+        XAssetGeneric::XModel(Some(Box::new(model()))),
+        XAssetGeneric::Weapon(Some(Box::new(weapon))),
+    ]
+    .into_iter()
+    .map(XAsset::PC)
+    .collect()
+}
+
+fn image_names(asset: &XAsset) -> Vec<String> {
+    let materials = match asset {
+        XAsset::PC(XAssetGeneric::Material(Some(m))) => vec![m.as_ref().clone()],
+        XAsset::PC(XAssetGeneric::XModel(Some(m))) => {
+            m.material_handles.iter().map(|m| (**m).clone()).collect()
+        }
+        XAsset::PC(XAssetGeneric::Weapon(Some(w))) => {
+            let model = w.weap_def.as_ref().unwrap().hand_xmodel.as_ref().unwrap();
+            model
+                .material_handles
+                .iter()
+                .map(|m| (**m).clone())
+                .collect()
+        }
+        _ => vec![],
+    };
+    materials
+        .iter()
+        .flat_map(|m| &m.textures)
+        .filter_map(|t| match &t.u {
+            MaterialTextureDefInfo::Image(Some(image)) => Some(image.name.get().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn references() {
+    let mut bag = bag();
+    assert_eq!(
+        bag.rename_asset(XAssetType::IMAGE, "img_a", "img_b")
+            .unwrap(),
+        3
+    );
+
+    assert!(!bag.contains(XAssetType::IMAGE, "img_a"));
+    assert!(bag.contains(XAssetType::IMAGE, "img_b"));
+    for asset in bag.iter() {
+        assert!(image_names(asset).iter().all(|n| n == "img_b"));
+    }
+    // the order of the bag is kept
+    let names = bag.iter().map(|a| a.name().unwrap()).collect::<Vec<_>>();
+    assert_eq!(names, ["img_b", "mtl_a", "mdl_a", "wpn_a"]);
+
+    assert_eq!(
+        bag.rename_asset(XAssetType::XMODEL, "mdl_a", "mdl_b")
+            .unwrap(),
+        1
+    );
+    let XAsset::PC(XAssetGeneric::Weapon(Some(weapon))) =
+        bag.get(XAssetType::WEAPON, "wpn_a").unwrap()
+    else {
+        unreachable!()
+    };
+    let def = weapon.weap_def.as_ref().unwrap();
+    assert_eq!(def.hand_xmodel.as_ref().unwrap().name.get(), "mdl_b");
+
+    // only the weapon refers to its animations, which aren't in the bag
+    let err = bag
+        .rename_asset(XAssetType::XANIMPARTS, "anim_a", "anim_c")
+        .unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadRename(_)), "{err}");
+}
+
+#[test]
+fn errors() {
+    let mut bag = bag();
+    let bad = |e: t5_xfile_defs::Error| matches!(e.kind(), ErrorKind::BadRename(_));
+
+    let missing = bag.rename_asset(XAssetType::IMAGE, "img_x", "img_b");
+    assert!(bad(missing.unwrap_err()));
+    assert_eq!(
+        bag.rename_asset(XAssetType::IMAGE, "img_a", "img_a")
+            .unwrap(),
+        0
+    );
+
+    let mut image: GfxImage = zeroed();
+    image.name = name("img_b");
+    bag.insert(XAsset::PC(XAssetGeneric::Image(Some(Box::new(image)))));
+    let taken = bag.rename_asset(XAssetType::IMAGE, "img_a", "img_b");
+    assert!(bad(taken.unwrap_err()));
+    assert!(bag.contains(XAssetType::IMAGE, "img_a"));
+    assert_eq!(bag.len(), 5);
+}
+
+#[test]
+fn script_strings() {
+    let mut strings = ["mdl_a", "tag_origin", "mdl_a"].map(String::from);
+    assert_eq!(rename_script_strings(&mut strings, "mdl_a", "mdl_b"), 2);
+    assert_eq!(strings, ["mdl_b", "tag_origin", "mdl_b"]);
+}
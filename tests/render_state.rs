@@ -0,0 +1,113 @@
+use t5_xfile_defs::{
+    ErrorKind,
+    render_state::{
+        AlphaTest, Blend, BlendOp, CullMode, DepthTest, PolygonOffset, RenderState, StencilFunc,
+        StencilOp, StencilState,
+    },
+    techset::{GfxStateBits, Material},
+};
+
+fn bits(load_bits: [u32; 2]) -> GfxStateBits {
+    GfxStateBits { load_bits }
+}
+
+#[test]
+fn decode() {
+    // opaque: no blending, back faces culled, depth tested and written
+    let opaque = bits([0x18008800, 0x00000005]).render_state().unwrap();
+    assert_eq!(
+        opaque,
+        RenderState {
+            cull: CullMode::BACK,
+            color_write_rgb: true,
+            color_write_alpha: true,
+            depth_write: true,
+            depth_test: DepthTest::LESS,
+            ..Default::default()
+        }
+    );
+    assert!(!opaque.is_blended());
+
+    // alpha blended, alpha tested, depth test disabled, decal offset
+    let blend = bits([0x00657165, 0x00000012]).render_state().unwrap();
+    assert_eq!(blend.src_blend_rgb, Blend::SRC_ALPHA);
+    assert_eq!(blend.dst_blend_rgb, Blend::INV_SRC_ALPHA);
+    assert_eq!(blend.blend_op_rgb, BlendOp::ADD);
+    assert_eq!(blend.src_blend_alpha, Blend::SRC_ALPHA);
+    assert_eq!(blend.dst_blend_alpha, Blend::INV_SRC_ALPHA);
+    assert_eq!(blend.blend_op_alpha, BlendOp::DISABLED);
+    assert_eq!(blend.alpha_test, AlphaTest::GE_128);
+    assert_eq!(blend.cull, CullMode::NONE);
+    assert_eq!(blend.depth_test, DepthTest::DISABLED);
+    assert_eq!(blend.polygon_offset, PolygonOffset::ONE);
+    assert!(!blend.color_write_rgb && !blend.depth_write);
+    assert!(blend.is_blended());
+}
+
+#[test]
+fn round_trip() {
+    let state = RenderState {
+        src_blend_rgb: Blend::ONE,
+        dst_blend_rgb: Blend::ONE,
+        blend_op_rgb: BlendOp::ADD,
+        src_blend_alpha: Blend::ZERO,
+        dst_blend_alpha: Blend::INV_DEST_COLOR,
+        blend_op_alpha: BlendOp::MAX,
+        alpha_test: AlphaTest::GT_0,
+        cull: CullMode::FRONT,
+        color_write_rgb: true,
+        color_write_alpha: false,
+        wireframe: true,
+        depth_write: false,
+        depth_test: DepthTest::EQUAL,
+        polygon_offset: PolygonOffset::SHADOW_MAP,
+        stencil_front: Some(StencilState {
+            pass: StencilOp::REPLACE,
+            fail: StencilOp::KEEP,
+            zfail: StencilOp::DECR,
+            func: StencilFunc::NOT_EQUAL,
+        }),
+        stencil_back: None,
+    };
+    assert_eq!(state.to_state_bits().render_state().unwrap(), state);
+
+    for raw in [[0x18008800, 0x00000005], [0x00657165, 0x00000012]] {
+        let state = bits(raw).render_state().unwrap();
+        assert_eq!(state.to_state_bits(), bits(raw));
+    }
+}
+
+#[test]
+fn bad_field() {
+    // 0xF isn't a blend
+    let err = bits([0x0000000F, 0]).render_state().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadFromPrimitive(0xF)));
+    // culling can't be 0
+    let err = bits([0x00000000, 0]).render_state().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadFromPrimitive(0)));
+}
+
+#[test]
+fn material() {
+    let opaque = bits([0x18008800, 0x00000005]);
+    let additive = bits([0x08004922, 0x00000002]);
+
+    let mut material = Material {
+        state_bits: vec![opaque, additive],
+        ..Default::default()
+    };
+    material.state_bits_entry[3] = 1;
+    material.state_bits_entry[4] = 0xFF;
+
+    assert_eq!(
+        material.render_state(0).unwrap(),
+        Some(opaque.render_state().unwrap())
+    );
+    let state = material.render_state(3).unwrap().unwrap();
+    assert_eq!(state.src_blend_rgb, Blend::ONE);
+    assert_eq!(state.dst_blend_rgb, Blend::ONE);
+    assert!(state.color_write_rgb && !state.color_write_alpha);
+    assert_eq!(material.render_state(4).unwrap(), None);
+    assert_eq!(material.render_state(1000).unwrap(), None);
+    assert_eq!(material.render_states().unwrap().len(), 2);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    retarget::MaterialRetarget,
+    techset::Material,
+    xasset::{XAsset, XAssetBag, XAssetGeneric},
+    xmodel::{PhysConstraint, PhysConstraints, XModel},
+};
+
+use common::zeroed;
+
+fn material(name: &str, state_flags: u8) -> Material {
+    let mut material: Material = zeroed();
+    material.info.name = XString(name.to_string());
+    material.state_flags = state_flags;
+    material
+}
+
+fn model() -> XModel {
+    let mut model: XModel = zeroed();
+    model.material_handles = ["mtl_a", "mtl_b", "mtl_a", "mtl_c"]
+        .map(|name| material(name, 0).into())
+        .to_vec();
+    model.phys_constraints = Some(Box::new(PhysConstraints {
+        name: XString("pc".to_string()),
+        count: 1,
+        data: vec![PhysConstraint {
+            material: Some(material("mtl_b", 0).into()),
+            ..Default::default()
+        }],
+    }));
+    model
+}
+
+fn names(model: &XModel) -> Vec<&str> {
+    model
+        .material_handles
+        .iter()
+        .chain(
+            model.phys_constraints.as_ref().unwrap().data[0]
+                .material
+                .as_ref(),
+        )
+        .map(|m| m.info.name.get())
+        .collect()
+}
+
+#[test]
+fn retarget() {
+    let bag = [material("new_a", 1), material("new_b", 2)]
+        .map(|m| XAsset::PC(XAssetGeneric::Material(Some(Box::new(m)))))
+        .into_iter()
+        .collect::<XAssetBag>();
+    let retarget = MaterialRetarget::new()
+        .with_mapping("mtl_a", "new_a")
+        .with_mapping("mtl_b", "new_b")
+        .with_materials_from(&bag);
+
+    let mut model = model();
+    let report = retarget.retarget(&mut model).unwrap();
+    assert_eq!(report.retargeted, 4);
+    assert!(report.placeholders.is_empty());
+    assert_eq!(names(&model), ["new_a", "new_b", "new_a", "mtl_c", "new_b"]);
+    // the surfaces get copies of the new materials, not just their names
+    assert_eq!(model.material_handles[0].state_flags, 1);
+    assert_eq!(model.material_handles[1].state_flags, 2);
+}
+
+#[test]
+fn placeholders() {
+    let retarget = MaterialRetarget::new()
+        .with_mapping("mtl_a", "new_a")
+        .with_mapping("mtl_c", "new_c")
+        .with_material(material("new_c", 3));
+
+    let mut model = model();
+    let err = retarget.retarget(&mut model).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadRetarget(_)), "{err}");
+    // nothing is changed when retargeting fails
+    assert_eq!(names(&model), ["mtl_a", "mtl_b", "mtl_a", "mtl_c", "mtl_b"]);
+
+    let report = retarget
+        .with_placeholders(true)
+        .retarget(&mut model)
+        .unwrap();
+    assert_eq!(report.retargeted, 3);
+    assert_eq!(names(&model), ["new_a", "mtl_b", "new_a", "new_c", "mtl_b"]);
+    // one placeholder per new name
+    assert_eq!(report.placeholders.len(), 1);
+    assert_eq!(report.placeholders[0].info.name.get(), "new_a");
+    assert_eq!(model.material_handles[3].state_flags, 3);
+}
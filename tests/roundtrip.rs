@@ -0,0 +1,68 @@
+#![cfg(all(feature = "serializer", feature = "deserializer"))]
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+    xmodel::XModel,
+};
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    roundtrip::{RoundTripOutcome, check_asset, check_assets},
+    testgen::SyntheticXFile,
+};
+
+#[test]
+fn synthetic() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::Xbox360] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_rawfile("maps/a.gsc", b"main() { }")
+            .with_localize_entry("MENU_START_GAME", "Start Game")
+            .with_string_table("mp/t.csv", 2, &["a", "b", "c", ""])
+            .with_map_ents(
+                "maps/mp/mp_test.d3dbsp",
+                "{\n\"classname\" \"worldspawn\"\n}\n",
+            )
+            .with_null_asset(XAssetType::LOCALIZE_ENTRY);
+        let bytes = xfile.to_bytes();
+        let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, platform, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+        assert_eq!(assets.len(), 5);
+
+        let reports = check_assets(&assets, platform);
+        assert_eq!(reports.len(), 5);
+        for report in &reports {
+            assert!(report.is_ok(), "{platform:?}: {report:?}");
+            assert!(report.serialized_len.unwrap() > 0);
+        }
+        assert_eq!(reports[0].asset_type, XAssetType::RAWFILE);
+        assert_eq!(reports[0].name.as_deref(), Some("maps/a.gsc"));
+        assert_eq!(reports[4].name, None);
+    }
+}
+
+#[test]
+fn serialize_failed() {
+    // no surfaces, but `numsurfs` says one
+    let model = XModel {
+        name: XString("m".to_string()),
+        numsurfs: 1,
+        ..Default::default()
+    };
+    let asset = XAsset::PC(XAssetGeneric::XModel(Some(Box::new(model))));
+    let report = check_asset(&asset, XFilePlatform::Windows);
+    assert!(!report.is_ok());
+    assert_eq!(report.name.as_deref(), Some("m"));
+    assert_eq!(report.serialized_len, None);
+    assert!(
+        matches!(report.outcome, RoundTripOutcome::SerializeFailed(_)),
+        "{report:?}"
+    );
+}
@@ -0,0 +1,343 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_deserializer::{
+    T5XFileDeserializer, T5XFileDeserializerBuilder, T5XFileDeserializerDeserialize,
+    testgen::SyntheticXFile,
+};
+
+use t5_xfile_defs::{T5XFileDeserialize, XFilePlatform, xasset::XAssetType};
+
+fn deserializer(bytes: &[u8]) -> T5XFileDeserializer<'_, T5XFileDeserializerDeserialize> {
+    T5XFileDeserializerBuilder::from_bytes(bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+}
+
+#[test]
+fn scan_names() {
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", &[b'a'; 4096])
+        .with_map_ents(
+            "maps/mp/mp_a.d3dbsp",
+            "{\n\"classname\" \"worldspawn\"\n}\n",
+        )
+        .with_null_asset(XAssetType::GFXWORLD)
+        .with_string_table("mp/a.csv", 2, &["x", "y"])
+        .with_localize_entry("MENU_A", "A")
+        .with_rawfile("maps/b.gsc", b"")
+        .to_bytes();
+
+    let names = deserializer(&bytes).scan_names().unwrap();
+    assert_eq!(
+        names,
+        [
+            (XAssetType::RAWFILE, Some("maps/a.gsc".to_string())),
+            (
+                XAssetType::MAP_ENTS,
+                Some("maps/mp/mp_a.d3dbsp".to_string())
+            ),
+            (XAssetType::GFXWORLD, None),
+            (XAssetType::STRINGTABLE, Some("mp/a.csv".to_string())),
+            (XAssetType::LOCALIZE_ENTRY, Some("MENU_A".to_string())),
+            (XAssetType::RAWFILE, Some("maps/b.gsc".to_string())),
+        ]
+    );
+
+    // same as deserializing everything
+    let assets = deserializer(&bytes).deserialize_remaining().unwrap();
+    assert_eq!(
+        names,
+        assets
+            .iter()
+            .map(|a| (a.asset_type(), a.name().map(ToString::to_string)))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn truncated_rawfile() {
+    let mut bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", &[b'a'; 64])
+        .to_bytes();
+    bytes.truncate(bytes.len() - 16);
+
+    assert!(deserializer(&bytes).scan_names().is_err());
+}
+
+#[test]
+fn skip_next() {
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", &[b'a'; 4096])
+        .with_pack_index("mp_a", &[(1, 0, 16), (2, 16, 32)])
+        .with_string_table("mp/a.csv", 2, &["x", "y", "z", "w"])
+        .with_null_asset(XAssetType::GFXWORLD)
+        .with_map_ents("maps/mp/mp_a.d3dbsp", "{\n}\n")
+        .to_bytes();
+
+    let mut de = deserializer(&bytes);
+    let mut extents = Vec::new();
+    while let Some(extent) = de.skip_next().unwrap() {
+        extents.push(extent);
+    }
+    assert_eq!(extents.len(), 5);
+    assert_eq!(extents[1].asset_type, XAssetType::PACKINDEX);
+    assert_eq!(extents[1].name.as_deref(), Some("mp_a"));
+    assert!(extents[0].range.end - extents[0].range.start > 4096);
+    assert!(extents[3].range.is_empty());
+
+    // the walkers end up exactly where the deserializer does
+    let mut de = deserializer(&bytes);
+    for extent in &extents {
+        assert_eq!(de.stream_pos().unwrap(), extent.range.start);
+        de.deserialize_next().unwrap().unwrap();
+        assert_eq!(de.stream_pos().unwrap(), extent.range.end);
+    }
+    assert_eq!(de.stream_pos().unwrap(), de.stream_len().unwrap());
+}
+
+/// Assets with walkers that aren't just strings and flat arrays, written by
+/// the serializer, walk to exactly where the deserializer ends up.
+#[cfg(feature = "serializer")]
+#[test]
+fn walkers() {
+    use t5_xfile_defs::T5XFileSerialize;
+    use t5_xfile_defs::{
+        XString,
+        common::Vec4,
+        sound::{
+            LoadedSound, SndAlias, SndAliasList, SndAsset, SndAssetChannel, SndAssetFlags,
+            SndAssetFormat, SndBank, SoundFile, SoundFileRef,
+        },
+        techset::{
+            GfxImage, GfxImageLoadDef, GfxPixelShaderLoadDef, GfxTexture, GfxVertexShaderLoadDef,
+            Material, MaterialArgumentDef, MaterialInfo, MaterialPass, MaterialPixelShader,
+            MaterialPixelShaderProgram, MaterialShaderArgument, MaterialTechnique,
+            MaterialTechniqueSet, MaterialTextureDef, MaterialTextureDefInfo, MaterialVertexShader,
+            MaterialVertexShaderProgram, MtlArg, Semantic, Water,
+        },
+        xasset::{XAsset, XAssetGeneric},
+        xmodel::{
+            GfxPackedVertex, PhysPreset, XModel, XModelCollSurf, XModelCollTri, XRigidVertList,
+            XSurface, XSurfaceCollisionLeaf, XSurfaceCollisionNode, XSurfaceCollisionTree,
+            XSurfaceVertexInfo,
+        },
+    };
+    use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+    let image = |name: &str| GfxImage {
+        name: XString(name.to_string()),
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count: 1,
+            resource: vec![0x7F; 1024],
+            ..Default::default()
+        }))),
+        ..Default::default()
+    };
+    let pass = MaterialPass {
+        vertex_decl: Some(Box::default()),
+        vertex_shader: Some(Box::new(MaterialVertexShader {
+            name: XString("vs".to_string()),
+            prog: MaterialVertexShaderProgram {
+                vs: None,
+                load_def: GfxVertexShaderLoadDef {
+                    program: vec![0xFFFE0300; 256],
+                },
+            },
+        })),
+        pixel_shader: Some(Box::new(MaterialPixelShader {
+            name: XString("ps".to_string()),
+            prog: MaterialPixelShaderProgram {
+                ps: None,
+                load_def: GfxPixelShaderLoadDef {
+                    program: vec![0xFFFF0300; 128],
+                },
+            },
+        })),
+        per_prim_arg_count: 0,
+        per_obj_arg_count: 0,
+        stable_arg_count: 2,
+        custom_sampler_flags: 0,
+        args: vec![
+            MaterialShaderArgument {
+                arg_type: MtlArg::LITERAL_PIXEL_CONST,
+                dest: 0,
+                u: MaterialArgumentDef::LiteralConst(Vec4::from([1.0; 4])),
+            },
+            MaterialShaderArgument {
+                arg_type: MtlArg::CODE_PIXEL_SAMPLER,
+                dest: 1,
+                u: MaterialArgumentDef::CodeSampler(3),
+            },
+        ],
+    };
+    let material = |name: &str| Material {
+        info: MaterialInfo {
+            name: XString(name.to_string()),
+            ..Default::default()
+        },
+        textures: vec![MaterialTextureDef {
+            semantic: Semantic::COLOR_MAP,
+            u: MaterialTextureDefInfo::Image(Some(Box::new(image("~a")))),
+            ..Default::default()
+        }],
+        technique_set: Some(Box::new(MaterialTechniqueSet {
+            name: XString("wc_default".to_string()),
+            world_vert_format: 0,
+            techset_flags: 0,
+            techniques: vec![Box::new(MaterialTechnique {
+                name: XString("lit".to_string()),
+                flags: 0,
+                passes: vec![pass.clone()],
+            })],
+        })),
+        constants: vec![Default::default(); 2],
+        state_bits: vec![Default::default(); 3],
+        ..Default::default()
+    };
+    let surface = XSurface {
+        tri_indices: vec![0, 1, 2, 2, 1, 3],
+        vert_info: XSurfaceVertexInfo {
+            vert_count: [4, 0, 0, 0],
+            verts_blend: vec![0; 4],
+            tension_data: vec![0.0; 48],
+        },
+        verts0: vec![GfxPackedVertex::default(); 4],
+        vert_list: vec![XRigidVertList {
+            vert_count: 4,
+            tri_count: 2,
+            collision_tree: Some(Box::new(XSurfaceCollisionTree {
+                nodes: vec![XSurfaceCollisionNode::default(); 3],
+                leafs: vec![XSurfaceCollisionLeaf::default(); 2],
+                ..Default::default()
+            })),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    // No bones: the serializer writes bone names as strings rather than
+    // script string indices, so they wouldn't read back.
+    let mut model = XModel {
+        name: XString("m".to_string()),
+        surfs: vec![surface],
+        coll_surfs: vec![XModelCollSurf {
+            coll_tris: vec![XModelCollTri::default(); 5],
+            ..Default::default()
+        }],
+        phys_preset: Some(Box::new(PhysPreset {
+            name: XString("default".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    model.recompute_counts();
+
+    let mut water = material("mc/water");
+    water.textures = vec![MaterialTextureDef {
+        semantic: Semantic::WATER_MAP,
+        u: MaterialTextureDefInfo::Water(Some(Box::new(Water {
+            m: 4,
+            n: 4,
+            h0: vec![Default::default(); 16],
+            w_term: vec![0.5; 16],
+            image: Some(Box::new(image("~water"))),
+            ..Default::default()
+        }))),
+        ..Default::default()
+    }];
+
+    let bank = SndBank {
+        name: XString("mp_a.all".to_string()),
+        aliases: vec![SndAliasList {
+            name: XString("amb_wind".to_string()),
+            aliases: vec![SndAlias {
+                name: XString("amb_wind".to_string()),
+                sound_file: Some(Box::new(SoundFile {
+                    u: SoundFileRef::Loaded(Some(Box::new(LoadedSound {
+                        name: XString("wind.wav".to_string()),
+                        sound: SndAsset {
+                            version: 14,
+                            frame_count: 2048,
+                            frame_rate: 44100,
+                            channel_count: 1,
+                            header_size: 0,
+                            block_size: 2,
+                            buffer_size: 4096,
+                            format: SndAssetFormat::PCMS16,
+                            channel_flags: SndAssetChannel::C,
+                            flags: SndAssetFlags::LOOPING,
+                            seek_table: vec![0; 4],
+                            data: vec![0; 4096],
+                        },
+                    }))),
+                    exists: true,
+                })),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        alias_index: Vec::new(),
+        pack_hash: 0,
+        pack_location: 0,
+        radverbs: Vec::new(),
+        snapshots: Vec::new(),
+    };
+
+    let mut xfile = SyntheticXFile::new(XFilePlatform::Windows);
+    for asset in [
+        XAssetGeneric::Material(Some(Box::new(material("mc/c")))),
+        XAssetGeneric::Material(Some(Box::new(water))),
+        XAssetGeneric::Image(Some(Box::new(image("~b")))),
+        XAssetGeneric::TechniqueSet(material("mc/d").technique_set),
+        XAssetGeneric::XModel(Some(Box::new(model))),
+        XAssetGeneric::Sound(Some(Box::new(bank))),
+    ] {
+        let asset_type = asset.asset_type();
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .build()
+            .unwrap();
+        ser.serialize_assets(std::iter::once(XAsset::PC(asset)))
+            .unwrap();
+        for string in ser.script_strings() {
+            xfile = xfile.with_script_string(string);
+        }
+        xfile = xfile.with_raw_asset(asset_type, ser.asset_bytes().unwrap()[8..].to_vec());
+    }
+    let bytes = xfile
+        .with_gfx_world("maps/mp/mp_a", [&[[1.0; 4]; 3], &[]])
+        .to_bytes();
+
+    let mut de = deserializer(&bytes);
+    let mut extents = Vec::new();
+    while let Some(extent) = de.skip_next().unwrap() {
+        extents.push(extent);
+    }
+    assert_eq!(
+        extents
+            .iter()
+            .map(|e| e.name.as_deref().unwrap())
+            .collect::<Vec<_>>(),
+        [
+            "mc/c",
+            "mc/water",
+            "~b",
+            "wc_default",
+            "m",
+            "mp_a.all",
+            "maps/mp/mp_a"
+        ]
+    );
+
+    let mut de = deserializer(&bytes);
+    for extent in &extents {
+        assert_eq!(de.stream_pos().unwrap(), extent.range.start);
+        de.deserialize_next().unwrap().unwrap();
+        assert_eq!(de.stream_pos().unwrap(), extent.range.end);
+    }
+    assert_eq!(de.stream_pos().unwrap(), de.stream_len().unwrap());
+}
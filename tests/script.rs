@@ -0,0 +1,70 @@
+use t5_xfile_defs::{
+    RawFile, XString,
+    script::{Script, ScriptDisassembler, ScriptKind},
+    xasset::{XAsset, XAssetGeneric},
+};
+
+fn raw_file(name: &str, contents: &[u8]) -> RawFile {
+    let mut buffer = contents.to_vec();
+    buffer.push(0);
+    RawFile {
+        name: XString(name.to_string()),
+        buffer,
+    }
+}
+
+#[test]
+fn scripts_from_raw_files() {
+    let gsc = raw_file("maps/mp/gametypes/tdm.gsc", b"main()\n{\n}\n");
+    let script = gsc.script().unwrap();
+    assert_eq!(script.name, "maps/mp/gametypes/tdm.gsc");
+    assert_eq!(script.kind, ScriptKind::GSC);
+    assert_eq!(script.len(), 11);
+    assert_eq!(script.source(), Some("main()\n{\n}\n"));
+
+    let csc = raw_file("clientscripts/mp/_fx.CSC", &[0x80, 0x00, 0x13]);
+    let script = csc.script().unwrap();
+    assert_eq!(script.kind, ScriptKind::CSC);
+    assert_eq!(script.buffer, &[0x80, 0x00, 0x13]);
+    assert_eq!(script.source(), None);
+
+    assert!(raw_file("maps/mp/mp_test.arena", b"{}").script().is_none());
+    assert!(raw_file("gsc", b"").script().is_none());
+
+    let asset = XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(gsc.clone()))));
+    assert_eq!(asset.script(), gsc.script());
+}
+
+struct LineCounter {
+    seen: Vec<String>,
+}
+
+impl ScriptDisassembler for LineCounter {
+    type Output = usize;
+
+    fn disassemble(&mut self, script: &Script<'_>) -> t5_xfile_defs::Result<usize> {
+        self.seen.push(script.name.to_string());
+        Ok(script.source().map_or(0, |s| s.lines().count()))
+    }
+}
+
+#[test]
+fn disassemblers() {
+    let gsc = raw_file("a.gsc", b"main()\n{\n}\n");
+    let script = gsc.script().unwrap();
+
+    let mut counter = LineCounter { seen: Vec::new() };
+    assert_eq!(script.disassemble(&mut counter).unwrap(), 3);
+    assert_eq!(counter.seen, ["a.gsc"]);
+
+    let mut hexdump = |s: &Script<'_>| {
+        Ok(s.buffer
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>())
+    };
+    assert_eq!(
+        script.disassemble(&mut hexdump).unwrap(),
+        "6d61696e28290a7b0a7d0a"
+    );
+}
@@ -0,0 +1,556 @@
+#![cfg(feature = "serializer")]
+
+use t5_xfile_deserializer::T5XFileSerializerBuilder;
+
+use t5_xfile_defs::{
+    LocalizeEntry, T5XFileSerialize, XFileBlock, XFilePlatform, XString,
+    xasset::{XAsset, XAssetGeneric},
+};
+
+#[test]
+fn block_sizes() {
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    let empty = ser.xfile();
+    // just the asset list
+    assert_eq!(empty.block_size[XFileBlock::Virtual.index()], 16);
+    assert_eq!(empty.size, 16);
+
+    ser.serialize_assets(std::iter::once(XAsset::PC(XAssetGeneric::LocalizeEntry(
+        Some(Box::new(LocalizeEntry {
+            value: XString("Start Game".to_string()),
+            name: XString("MENU_START_GAME".to_string()),
+        })),
+    ))))
+    .unwrap();
+    let virtual_size = ser.xfile().block_size[XFileBlock::Virtual.index()];
+    assert!(virtual_size > 16);
+
+    assert_eq!(ser.current_block(), XFileBlock::Virtual);
+    ser.push_block(XFileBlock::Temp);
+    ser.store_into_xfile(1u8).unwrap();
+    ser.pop_block();
+    ser.store_into_xfile(1u32).unwrap();
+
+    let xfile = ser.xfile();
+    assert_eq!(xfile.block_size[XFileBlock::Temp.index()], 4);
+    assert_eq!(
+        xfile.block_size[XFileBlock::Virtual.index()],
+        virtual_size + 4
+    );
+    assert_eq!(xfile.size, virtual_size + 8);
+    assert!(
+        XFileBlock::ALL
+            .iter()
+            .filter(|b| b.is_runtime())
+            .all(|b| xfile.block_size[b.index()] == 0)
+    );
+}
+
+/// `asset` (which mustn't be null) serialized on its own and deserialized back, through a
+/// synthesized Fastfile (the serializer doesn't write the asset list
+/// entries itself).
+#[cfg(feature = "deserializer")]
+fn round_trip(asset: XAssetGeneric) -> XAsset {
+    round_trip_patched(asset, |_| {})
+}
+
+/// Same as [`round_trip`], but `patch` can change the serialized asset
+/// (starting with its header) before it's deserialized.
+#[cfg(feature = "deserializer")]
+fn round_trip_patched(asset: XAssetGeneric, patch: impl FnOnce(&mut [u8])) -> XAsset {
+    use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+    let asset_type = asset.asset_type();
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets(std::iter::once(XAsset::PC(asset)))
+        .unwrap();
+
+    let mut xfile = SyntheticXFile::new(XFilePlatform::Windows);
+    for string in ser.script_strings() {
+        xfile = xfile.with_script_string(string);
+    }
+    // The serialized asset starts with its entry in the asset list (its
+    // type and header pointer), which the synthesized file writes itself.
+    let mut bytes = ser.asset_bytes().unwrap()[8..].to_vec();
+    patch(&mut bytes);
+    let xfile = xfile.with_raw_asset(asset_type, bytes);
+
+    let bytes = xfile.to_bytes();
+    let mut assets = T5XFileDeserializerBuilder::from_bytes(&bytes, xfile.platform(), true)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(assets.len(), 1);
+    assets.remove(0)
+}
+
+#[cfg(feature = "deserializer")]
+#[test]
+fn misc_round_trip() {
+    use t5_xfile_defs::{
+        RawFile,
+        misc::{
+            MapEnts, PackIndex, PackIndexEntry, PackIndexHeader, StringTable, StringTableCell,
+            string_table_hash,
+        },
+    };
+
+    let cells = ["a", "b", "c", ""]
+        .into_iter()
+        .map(|s| StringTableCell {
+            name: XString(s.to_string()),
+            hash: string_table_hash(s),
+        })
+        .collect();
+    let assets = [
+        XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: XString("maps/a.gsc".to_string()),
+            buffer: b"main() { }\0".to_vec(),
+        }))),
+        XAssetGeneric::LocalizeEntry(Some(Box::new(LocalizeEntry {
+            value: XString("Start Game".to_string()),
+            name: XString("MENU_START_GAME".to_string()),
+        }))),
+        XAssetGeneric::StringTable(Some(Box::new(StringTable {
+            name: XString("mp/t.csv".to_string()),
+            column_count: 2,
+            row_count: 2,
+            values: cells,
+            cell_index: vec![0, 1, 2, 3],
+        }))),
+        XAssetGeneric::MapEnts(Some(Box::new(MapEnts {
+            name: XString("maps/mp/mp_test.d3dbsp".to_string()),
+            entity_string: XString("{\n\"classname\" \"worldspawn\"\n}\n".to_string()),
+        }))),
+        XAssetGeneric::PackIndex(Some(Box::new(PackIndex {
+            name: XString("sound.pak".to_string()),
+            header: PackIndexHeader {
+                magic: 0x4B434150,
+                timestamp: 1,
+                count: 2,
+                alignment: 0x800,
+                data_start: 0x1000,
+            },
+            entries: vec![
+                PackIndexEntry {
+                    hash: 1,
+                    offset: 0,
+                    size: 0x10,
+                },
+                PackIndexEntry {
+                    hash: 2,
+                    offset: 0x800,
+                    size: 0x20,
+                },
+            ],
+        }))),
+    ];
+
+    for asset in assets {
+        assert_eq!(round_trip(asset.clone()), XAsset::PC(asset));
+    }
+}
+
+#[test]
+fn xmodel_counts() {
+    use t5_xfile_defs::{
+        ErrorKind,
+        xmodel::{DObjAnimMat, XModel, XSurface},
+    };
+
+    let mut model = XModel {
+        name: XString("m".to_string()),
+        num_bones: 2,
+        num_root_bones: 1,
+        numsurfs: 4,
+        bone_names: vec![
+            XString("tag_origin".to_string()),
+            XString("j_a".to_string()),
+        ],
+        parent_list: vec![1],
+        base_mat: vec![DObjAnimMat::default(); 2],
+        surfs: vec![XSurface::default(); 2],
+        ..Default::default()
+    };
+    let serialize = |model: &XModel| {
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .build()
+            .unwrap();
+        ser.serialize_assets(std::iter::once(XAsset::PC(XAssetGeneric::XModel(Some(
+            Box::new(model.clone()),
+        )))))
+    };
+
+    // two surfaces, but `numsurfs` says four
+    let err = serialize(&model).unwrap_err();
+    let ErrorKind::Validation(violations) = err.kind() else {
+        panic!("{err:?}");
+    };
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].contains("surfs.len() (2)"));
+
+    model.surfs[1].tri_indices = vec![0, 1];
+    model.quats = vec![0; 3];
+    let err = model.validate_counts().unwrap_err();
+    let ErrorKind::Validation(violations) = err.kind() else {
+        panic!("{err:?}");
+    };
+    assert_eq!(violations.len(), 3);
+    assert!(violations[2].starts_with("XModel: surfs[1]: tri_indices"));
+
+    model.surfs[1].tri_indices = vec![0, 1, 1];
+    model.quats = vec![0; 4];
+    model.numsurfs = 0;
+    model.num_bones = 7;
+    model.recompute_counts();
+    assert_eq!(
+        (model.num_bones, model.num_root_bones, model.numsurfs),
+        (2, 1, 2)
+    );
+    serialize(&model).unwrap();
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn reuse_unchanged() {
+    use t5_xfile_deserializer::{
+        T5XFileDeserializerBuilder, serializer::OriginalXFile, testgen::SyntheticXFile,
+    };
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_script_string("tag_origin")
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_string_table("mp/t.csv", 2, &["a", "b", "c", "d"]);
+    let bytes = xfile.to_bytes();
+    let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_trace(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+    let mut assets = Vec::new();
+    while let Some(asset) = de.deserialize_next().unwrap() {
+        assets.push(asset);
+    }
+    let trace = de.take_trace().unwrap();
+    let payload = xfile.payload();
+    let script_strings = vec!["tag_origin".to_string()];
+    let original = OriginalXFile::new(&payload, &trace, &assets, script_strings.clone()).unwrap();
+
+    let mut changed = assets.clone();
+    let XAsset::PC(XAssetGeneric::LocalizeEntry(Some(entry))) = &mut changed[1] else {
+        panic!("{:?}", changed[1]);
+    };
+    entry.value = XString("Start".to_string());
+
+    let serializer = || {
+        T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .with_script_strings(script_strings.clone())
+            .build()
+            .unwrap()
+    };
+    let mut reusing = serializer();
+    let reused = reusing
+        .serialize_assets_reusing(changed.clone().into_iter(), &original)
+        .unwrap();
+    assert_eq!(reused, 2);
+
+    // the same bytes as serializing everything
+    let mut full = serializer();
+    full.serialize_assets(changed.into_iter()).unwrap();
+    assert_eq!(reusing.asset_bytes(), full.asset_bytes());
+    assert_eq!(reusing.asset_count(), 3);
+
+    // the script strings have to be the original's
+    let err = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .serialize_assets_reusing(assets.clone().into_iter(), &original)
+        .unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        t5_xfile_defs::ErrorKind::BrokenInvariant(_)
+    ));
+}
+
+#[cfg(all(feature = "deserializer", feature = "arc"))]
+#[test]
+fn interned_materials() {
+    use std::sync::Arc;
+
+    use t5_xfile_defs::{
+        Shared, XFileOffset,
+        font::Font,
+        techset::{Material, MaterialInfo},
+        xasset::XAssetType,
+    };
+    use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+    let font = Font {
+        font_name: XString("fonts/a".to_string()),
+        pixel_height: 16,
+        material: Some(Shared::new(Material {
+            info: MaterialInfo {
+                name: XString("fonts/a".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })),
+        glow_material: None,
+        glyphs: Vec::new(),
+    };
+    let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+        .with_silent(true)
+        .build()
+        .unwrap();
+    ser.serialize_assets(std::iter::once(XAsset::PC(XAssetGeneric::Font(Some(
+        Box::new(font),
+    )))))
+    .unwrap();
+    let first = ser.asset_bytes().unwrap()[8..].to_vec();
+
+    // the asset array takes up 16 bytes of the virtual block, the first
+    // font's header 24 and its name 8, so its material starts at 48. the
+    // second font's material is a real offset pointing at it.
+    let material = XFileOffset::new(XFileBlock::Virtual, 48);
+    let second = [u32::MAX, 16, 0, material.as_u32(), 0, 0]
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .chain(*b"fonts/b\0")
+        .collect();
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::FONT, first)
+        .with_raw_asset(XAssetType::FONT, second);
+    let bytes = xfile.to_bytes();
+
+    // real offsets aren't followed, but the material was already loaded
+    let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    let material = |asset: &XAsset| match asset {
+        XAsset::PC(XAssetGeneric::Font(Some(font))) => font.material.clone().unwrap(),
+        _ => unreachable!(),
+    };
+    assert_eq!(material(&assets[0]).info.name.get(), "fonts/a");
+    assert!(Arc::ptr_eq(&material(&assets[0]), &material(&assets[1])));
+}
+
+#[cfg(feature = "deserializer")]
+#[test]
+fn phys_round_trip() {
+    use t5_xfile_defs::{
+        common::Vec3,
+        xmodel::{
+            AttachPointType, ConstraintType, MAX_PHYS_CONSTRAINTS, PhysConstraint, PhysConstraints,
+            PhysPreset,
+        },
+    };
+
+    let preset = PhysPreset {
+        name: XString("barrel".to_string()),
+        flags: 1,
+        mass: 20.0,
+        bounce: 0.25,
+        friction: 0.5,
+        bullet_force_scale: 1.0,
+        explosive_force_scale: 2.0,
+        snd_alias_prefix: XString("phys_barrel".to_string()),
+        can_float: true,
+        gravity_scale: 1.0,
+        center_of_mass_offset: Vec3::from([0.0, 0.0, 8.0]),
+        buoyancy_box_min: Vec3::from([-1.0; 3]),
+        buoyancy_box_max: Vec3::from([1.0; 3]),
+        ..Default::default()
+    };
+    let preset = XAssetGeneric::PhysPreset(Some(Box::new(preset)));
+    assert_eq!(round_trip(preset.clone()), XAsset::PC(preset));
+
+    // all of the unused constraints are written (and read back) too
+    let mut data = vec![PhysConstraint::default(); MAX_PHYS_CONSTRAINTS];
+    data[0] = PhysConstraint {
+        targetname: XString("chain".to_string()),
+        type_: ConstraintType::ROPE,
+        attach_point_type1: AttachPointType::BONE,
+        target_ent1: XString("lamp".to_string()),
+        target_bone1: XString("tag_origin".to_string()),
+        attach_point_type2: AttachPointType::WORLD,
+        pos: Vec3::from([1.0, 2.0, 3.0]),
+        distance: 64.0,
+        rope_index: 1,
+        ..Default::default()
+    };
+    let constraints = PhysConstraints {
+        name: XString("lamp".to_string()),
+        count: 1,
+        data,
+    };
+    let asset = XAssetGeneric::PhysConstraints(Some(Box::new(constraints.clone())));
+    assert_eq!(round_trip(asset.clone()), XAsset::PC(asset));
+
+    // A BONE attach point without a bone name is invalid, but only the
+    // serializer rejects it, not the deserializer.
+    let mut invalid = constraints;
+    invalid.data[0].target_bone1 = XString::default();
+    invalid.data[0].attach_point_type1 = AttachPointType::WORLD;
+    let asset = XAssetGeneric::PhysConstraints(Some(Box::new(invalid.clone())));
+    let deserialized = round_trip_patched(asset, |bytes| {
+        // data[0].attach_point_type1
+        bytes[16..20].copy_from_slice(&(AttachPointType::BONE as i32).to_le_bytes());
+    });
+    invalid.data[0].attach_point_type1 = AttachPointType::BONE;
+    assert!(invalid.validate().is_err());
+    assert_eq!(
+        deserialized,
+        XAsset::PC(XAssetGeneric::PhysConstraints(Some(Box::new(invalid))))
+    );
+}
+
+/// The same assets always serialize to the same bytes, with script strings
+/// numbered in the order they're first used.
+#[test]
+fn deterministic() {
+    use t5_xfile_defs::xmodel::{
+        AttachPointType, MAX_PHYS_CONSTRAINTS, PhysConstraint, PhysConstraints,
+    };
+
+    let constraints = |name: &str, names: &[[&str; 3]]| {
+        let mut data = vec![PhysConstraint::default(); MAX_PHYS_CONSTRAINTS];
+        for (constraint, [targetname, ent1, ent2]) in data.iter_mut().zip(names) {
+            *constraint = PhysConstraint {
+                targetname: XString(targetname.to_string()),
+                attach_point_type1: AttachPointType::WORLD,
+                target_ent1: XString(ent1.to_string()),
+                attach_point_type2: AttachPointType::WORLD,
+                target_ent2: XString(ent2.to_string()),
+                ..Default::default()
+            };
+        }
+        XAsset::PC(XAssetGeneric::PhysConstraints(Some(Box::new(
+            PhysConstraints {
+                name: XString(name.to_string()),
+                count: names.len() as _,
+                data,
+            },
+        ))))
+    };
+    let assets = vec![
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(Box::new(
+            LocalizeEntry {
+                value: XString("Start Game".to_string()),
+                name: XString("MENU_START_GAME".to_string()),
+            },
+        )))),
+        constraints(
+            "a",
+            &[["chain", "lamp", "ceiling"], ["rope", "lamp", "j_a"]],
+        ),
+        XAsset::PC(XAssetGeneric::LocalizeEntry(None)),
+        constraints("b", &[["wire", "ceiling", "pole"]]),
+    ];
+    let serialize = || {
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .with_script_strings(["j_a".to_string()])
+            .build()
+            .unwrap();
+        ser.serialize_assets(assets.clone().into_iter()).unwrap();
+        let script_strings = ser
+            .script_strings()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        (script_strings, ser.deflate().unwrap())
+    };
+
+    let (script_strings, bytes) = serialize();
+    assert_eq!(
+        script_strings,
+        [
+            "j_a", "chain", "lamp", "ceiling", "rope", "", "wire", "pole"
+        ]
+    );
+    for _ in 0..4 {
+        assert_eq!(serialize(), (script_strings.clone(), bytes.clone()));
+    }
+}
+
+/// The markers recorded while deserializing an asset are written back
+/// when they're passed to the serializer, and `0xFFFFFFFF` otherwise, or
+/// when it's deterministic.
+#[cfg(feature = "deserializer")]
+#[test]
+fn ptr_markers() {
+    use t5_xfile_defs::{PtrMarker, xasset::XAssetType};
+    use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+    let data = [PtrMarker::Inserted, PtrMarker::Following]
+        .into_iter()
+        .flat_map(|m| m.as_u32().to_le_bytes())
+        .chain(*b"Start Game\0B\0")
+        .collect::<Vec<_>>();
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::LOCALIZE_ENTRY, data.clone());
+    let bytes = xfile.to_bytes();
+    let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_record_ptr_markers(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+    let asset = de.deserialize_next().unwrap().unwrap();
+    // the asset's header, then its value and name
+    assert_eq!(
+        de.ptr_markers(),
+        [vec![
+            PtrMarker::Following,
+            PtrMarker::Inserted,
+            PtrMarker::Following
+        ]]
+    );
+
+    let serialize = |markers: &[Vec<PtrMarker>], deterministic| {
+        let mut ser = T5XFileSerializerBuilder::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .with_ptr_markers(markers.to_vec())
+            .with_deterministic(deterministic)
+            .build()
+            .unwrap();
+        ser.serialize_assets(std::iter::once(asset.clone()))
+            .unwrap();
+        // skip the asset's entry in the asset list
+        ser.asset_bytes().unwrap()[8..].to_vec()
+    };
+    assert_eq!(serialize(de.ptr_markers(), false), data);
+    let canonical = serialize(&[], false);
+    assert_eq!(serialize(de.ptr_markers(), true), canonical);
+    assert_eq!(canonical[..4], PtrMarker::Following.as_u32().to_le_bytes());
+}
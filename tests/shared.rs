@@ -0,0 +1,64 @@
+use std::{sync::Arc, thread};
+
+use t5_xfile_defs::{
+    Shared, XString,
+    access::HasMaterials,
+    techset::{Material, MaterialInfo},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+    xmodel::XModel,
+};
+
+fn material(name: &str) -> Material {
+    Material {
+        info: MaterialInfo {
+            name: XString(name.to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn model(name: &str) -> XModel {
+    XModel {
+        name: XString(name.to_string()),
+        material_handles: vec![Shared::new(material("mc/mtl_rock"))],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn bag_between_threads() {
+    let mut bag = XAssetBag::new();
+    for name in ["rock_a", "rock_b"] {
+        bag.insert(XAsset::PC(XAssetGeneric::XModel(Some(Box::new(model(
+            name,
+        ))))));
+    }
+    let bag = Arc::new(bag);
+
+    let handles = ["rock_a", "rock_b"].map(|name| {
+        let bag = Arc::clone(&bag);
+        thread::spawn(move || {
+            let Some(XAsset::PC(XAssetGeneric::XModel(Some(model)))) =
+                bag.get(XAssetType::XMODEL, name)
+            else {
+                panic!("{name} isn't in the bag");
+            };
+            model.materials()[0].info.name.get().to_string()
+        })
+    });
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), "mc/mtl_rock");
+    }
+}
+
+#[cfg(feature = "arc")]
+#[test]
+fn clones_share_materials() {
+    let model = model("rock");
+    let clone = model.clone();
+    assert!(Arc::ptr_eq(
+        &model.material_handles[0],
+        &clone.material_handles[0]
+    ));
+}
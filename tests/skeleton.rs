@@ -0,0 +1,89 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    common::{Mat4, Vec3, Vec4},
+    xmodel::{DObjAnimMat, XModel},
+};
+
+const HALF: f32 = core::f32::consts::FRAC_1_SQRT_2;
+
+fn assert_close(a: Mat4, b: Mat4) {
+    for (a, b) in a.get().as_flattened().iter().zip(b.get().as_flattened()) {
+        assert!((a - b).abs() < 1e-3, "{a:?} != {b:?}");
+    }
+}
+
+fn bind(quat: [f32; 4], trans: [f32; 3]) -> DObjAnimMat {
+    DObjAnimMat {
+        quat: Vec4::from(quat),
+        trans: Vec3::from(trans),
+        trans_weight: 2.0,
+    }
+}
+
+/// `tag_origin`, then `j_arm` turned 90 degrees left, 10 units along X, then
+/// `j_hand` 5 units along the arm.
+fn model() -> XModel {
+    let q = (HALF * 32767.0) as i16;
+    XModel {
+        num_bones: 3,
+        num_root_bones: 1,
+        bone_names: ["tag_origin", "j_arm", "j_hand"]
+            .map(|s| XString(s.to_string()))
+            .to_vec(),
+        parent_list: vec![1, 1],
+        quats: vec![0, 0, q, q, 0, 0, 0, 32767],
+        trans: vec![10.0, 0.0, 0.0, 0.0, 5.0, 0.0, 0.0, 0.0],
+        base_mat: vec![
+            bind([0.0, 0.0, 0.0, 1.0], [0.0, 0.0, 0.0]),
+            bind([0.0, 0.0, HALF, HALF], [10.0, 0.0, 0.0]),
+            bind([0.0, 0.0, HALF, HALF], [10.0, 5.0, 0.0]),
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn hierarchy() {
+    let skeleton = model().skeleton().unwrap();
+    assert_eq!(skeleton.bones.len(), 3);
+    assert_eq!(skeleton.roots().collect::<Vec<_>>(), [0]);
+    assert_eq!(skeleton.find("j_hand"), Some(2));
+    assert_eq!(skeleton.find("j_foot"), None);
+
+    let [origin, arm, hand] = &skeleton.bones[..] else {
+        unreachable!()
+    };
+    assert_eq!(origin.parent, None);
+    assert_eq!(origin.children, [1]);
+    assert_eq!(arm.parent, Some(0));
+    assert_eq!(arm.children, [2]);
+    assert_eq!(hand.parent, Some(1));
+    assert_eq!(hand.local_translation.get(), [5.0, 0.0, 0.0]);
+    assert!((arm.local_rotation.get()[3] - HALF).abs() < 1e-4);
+}
+
+#[test]
+fn bind_pose() {
+    let skeleton = model().skeleton().unwrap();
+    let from_locals = skeleton.local_bind_pose();
+    for (bone, world) in skeleton.bones.iter().zip(from_locals) {
+        assert_close(world, bone.bind_matrix());
+    }
+
+    let hand = skeleton.bones[2].bind_matrix();
+    let tip = hand.transform_point(Vec3::from([1.0, 0.0, 0.0])).get();
+    assert!((tip[0] - 10.0).abs() < 1e-3 && (tip[1] - 6.0).abs() < 1e-3);
+}
+
+#[test]
+fn broken() {
+    let mut model = model();
+    model.parent_list[1] = 3;
+    let err = model.skeleton().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+
+    let mut model = self::model();
+    model.quats.truncate(4);
+    let err = model.skeleton().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BrokenInvariant(_)));
+}
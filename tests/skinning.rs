@@ -0,0 +1,97 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    skinning::{BoneWeight, validate_weights},
+    xmodel::{XModel, XSurface, XSurfaceVertexInfo},
+};
+
+fn w(bone: usize, weight: f32) -> BoneWeight {
+    BoneWeight { bone, weight }
+}
+
+fn weights() -> Vec<Vec<BoneWeight>> {
+    vec![
+        vec![w(0, 1.0)],
+        vec![w(1, 1.0)],
+        vec![w(1, 0.75), w(2, 0.25)],
+        vec![w(2, 0.5), w(0, 0.25), w(1, 0.125), w(3, 0.125)],
+    ]
+}
+
+#[test]
+fn round_trip() {
+    let mut info = XSurfaceVertexInfo::default();
+    info.set_weights(&weights()).unwrap();
+
+    assert_eq!(info.vert_count, [2, 1, 0, 1]);
+    assert_eq!(info.counts(), (info.verts_blend.len(), 4));
+    assert_eq!(
+        info.verts_blend,
+        [
+            0, 64, 64, 128, 0x4000, 128, 0, 0x4000, 64, 0x2000, 192, 0x2000
+        ]
+    );
+    assert_eq!(info.decode_weights().unwrap(), weights());
+    validate_weights(&weights(), 4).unwrap();
+
+    assert!(
+        XSurfaceVertexInfo::default()
+            .decode_weights()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn encode_errors() {
+    let broken = |weights: &[Vec<BoneWeight>]| {
+        let err = XSurfaceVertexInfo::default()
+            .set_weights(weights)
+            .unwrap_err();
+        matches!(err.kind(), ErrorKind::BrokenInvariant(_))
+    };
+
+    assert!(broken(&[vec![]]));
+    assert!(broken(&[vec![w(0, 0.2); 5]]));
+    // vertices weighted to more bones have to come last
+    assert!(broken(&[vec![w(0, 0.5), w(1, 0.5)], vec![w(0, 1.0)]]));
+    assert!(broken(&[vec![w(1024, 1.0)]]));
+}
+
+#[test]
+fn validate() {
+    let Err(err) = validate_weights(
+        &[
+            vec![w(4, 1.0)],
+            vec![w(0, 0.5), w(1, 0.25)],
+            vec![w(0, 1.5), w(1, -0.5)],
+        ],
+        4,
+    ) else {
+        panic!("expected the weights to be invalid");
+    };
+    let ErrorKind::Validation(violations) = err.kind() else {
+        panic!("{err}");
+    };
+    assert_eq!(violations.len(), 4, "{violations:?}");
+
+    let mut model = XModel {
+        name: XString("mdl".to_string()),
+        num_bones: 2,
+        surfs: vec![XSurface::default(), XSurface::default()],
+        ..Default::default()
+    };
+    model.surfs[0].vert_info.set_weights(&weights()).unwrap();
+    model.surfs[1].vert_info.verts_blend = vec![1];
+    let err = model.validate_skinning().unwrap_err();
+    let ErrorKind::Validation(violations) = err.kind() else {
+        panic!("{err}");
+    };
+    // bones 2 (twice) and 3 are out of range, and the second surface can't
+    // be decoded
+    assert_eq!(violations.len(), 4, "{violations:?}");
+    assert!(violations[3].starts_with("XModel: surfs[1]: "));
+
+    model.num_bones = 4;
+    model.surfs.pop();
+    model.validate_skinning().unwrap();
+}
@@ -0,0 +1,106 @@
+use t5_xfile_defs::{
+    XString,
+    sound::{SndAlias, SndAliasList, SndBank, SoundFile, SoundFileRef, StreamedSound},
+};
+
+fn alias(name: &str, probability: u8, secondary: &str, sound: Option<SoundFileRef>) -> SndAlias {
+    SndAlias {
+        name: XString(name.to_string()),
+        secondaryname: XString(secondary.to_string()),
+        probability,
+        sound_file: sound.map(|u| Box::new(SoundFile { u, exists: true })),
+        ..Default::default()
+    }
+}
+
+fn list(name: &str, aliases: Vec<SndAlias>) -> SndAliasList {
+    SndAliasList {
+        name: XString(name.to_string()),
+        aliases,
+        ..Default::default()
+    }
+}
+
+fn streamed(filename: &str) -> Option<SoundFileRef> {
+    Some(SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+        filename: XString(filename.to_string()),
+        prime_snd: None,
+    }))))
+}
+
+fn bank(aliases: Vec<SndAliasList>) -> SndBank {
+    SndBank {
+        name: XString("bank".to_string()),
+        aliases,
+        alias_index: Vec::new(),
+        pack_hash: 0,
+        pack_location: 0,
+        radverbs: Vec::new(),
+        snapshots: Vec::new(),
+    }
+}
+
+#[test]
+fn resolve() {
+    let bank = bank(vec![
+        list(
+            "wpn_fire",
+            vec![
+                alias("wpn_fire", 3, "wpn_tail", streamed("fire_a.wav")),
+                alias("wpn_fire", 1, "", streamed("fire_b.wav")),
+            ],
+        ),
+        list(
+            "wpn_tail",
+            vec![
+                alias("wpn_tail", 0, "", streamed("tail_a.wav")),
+                alias("wpn_tail", 0, "", streamed("tail_b.wav")),
+            ],
+        ),
+    ]);
+
+    let resolved = bank.resolve("wpn_fire");
+    let summary = resolved
+        .iter()
+        .map(|r| {
+            (
+                r.chain.join(">"),
+                r.sound_file().and_then(SoundFile::name).unwrap(),
+                r.weight,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("wpn_fire".to_string(), "fire_a.wav", 0.75),
+            ("wpn_fire>wpn_tail".to_string(), "tail_a.wav", 0.375),
+            ("wpn_fire>wpn_tail".to_string(), "tail_b.wav", 0.375),
+            ("wpn_fire".to_string(), "fire_b.wav", 0.25),
+        ]
+    );
+    assert!(!resolved[0].is_secondary());
+    assert!(resolved[1].is_secondary());
+
+    assert!(bank.resolve("missing").is_empty());
+}
+
+#[test]
+fn cycles_and_missing() {
+    let bank = bank(vec![
+        list("a", vec![alias("a", 0, "b", streamed("ping"))]),
+        list("b", vec![alias("b", 0, "a", None)]),
+        list("c", vec![alias("c", 0, "nowhere", None)]),
+    ]);
+
+    let resolved = bank.resolve("a");
+    assert_eq!(resolved.len(), 2);
+    assert_eq!(
+        resolved[0].sound_file().and_then(SoundFile::name),
+        Some("ping")
+    );
+    assert_eq!(resolved[1].chain, ["a", "b"]);
+    assert_eq!(resolved[1].sound_file(), None);
+
+    assert_eq!(bank.resolve("c").len(), 1);
+}
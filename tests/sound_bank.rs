@@ -0,0 +1,204 @@
+#![cfg(feature = "deserializer")]
+
+use std::{io::Cursor, path::PathBuf};
+
+use t5_xfile_deserializer::{
+    T5XFileDeserializerBuilder,
+    sound_bank::{SAB_MAGIC, SoundBankFile, SoundBanks},
+    testgen::SyntheticXFile,
+};
+
+use t5_xfile_defs::{
+    ErrorKind, XFilePlatform, XString,
+    sound::{
+        SndAssetChannel, SndAssetFlags, SndAssetFormat, SoundFile, SoundFileRef, StreamedSound,
+    },
+    xasset::{XAsset, XAssetGeneric, XAssetType},
+};
+
+const ID: u32 = 0xABCD;
+const AUDIO: [u8; 8] = [1, 0, 2, 0, 3, 0, 4, 0];
+
+/// A bank with a single looping 16-bit stereo sound at 48 kHz.
+fn bank(little_endian: bool) -> Vec<u8> {
+    let u32_bytes = |v: u32| {
+        if little_endian {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        }
+    };
+    let u64_bytes = |v: u64| {
+        if little_endian {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        }
+    };
+
+    let entry_offset = 56u64;
+    let audio_offset = entry_offset + 20;
+    let mut bytes = Vec::new();
+    for v in [SAB_MAGIC, 14, 20, 0, 0, 1, 0, 0] {
+        bytes.extend(u32_bytes(v));
+    }
+    for v in [audio_offset + AUDIO.len() as u64, entry_offset, 0] {
+        bytes.extend(u64_bytes(v));
+    }
+    for v in [ID, AUDIO.len() as u32, audio_offset as u32, 2] {
+        bytes.extend(u32_bytes(v));
+    }
+    bytes.extend([6, 2, 1, SndAssetFormat::PCMS16 as u8]);
+    bytes.extend(AUDIO);
+    bytes
+}
+
+fn game_dir(name: &str, files: &[(&str, Vec<u8>)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-sab-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        std::fs::write(dir.join(file), contents).unwrap();
+    }
+    dir
+}
+
+/// A sound bank asset with one alias (with [`ID`]) for a streamed sound.
+fn snd_bank_asset() -> Vec<u8> {
+    let ptrs = |ptrs: &[u32]| {
+        ptrs.iter()
+            .flat_map(|p| p.to_le_bytes())
+            .collect::<Vec<_>>()
+    };
+    let inline = u32::MAX;
+
+    [
+        // SndBank
+        ptrs(&[inline, 1, inline, 0, 0, 0, 0, 0, 0, 0]),
+        b"bank\0".to_vec(),
+        // SndAliasList
+        ptrs(&[inline, 1, inline, 1, 0]),
+        b"list\0".to_vec(),
+        // SndAlias
+        ptrs(&[inline, ID, 0, 0, inline]),
+        vec![0; 64],
+        b"a\0".to_vec(),
+        // SoundFile
+        ptrs(&[inline]),
+        vec![0, 1, 0, 0],
+        // StreamedSound
+        ptrs(&[inline, 0]),
+        b"snd/a\0".to_vec(),
+    ]
+    .concat()
+}
+
+#[test]
+fn read() {
+    for little_endian in [true, false] {
+        let mut bank = SoundBankFile::new(Cursor::new(bank(little_endian))).unwrap();
+        assert_eq!(bank.header().version, 14);
+        assert!(bank.get(0).is_none());
+
+        let entry = *bank.get(ID).unwrap();
+        let sound = bank.read(entry).unwrap();
+        assert_eq!(sound.data, AUDIO);
+        assert_eq!(sound.frame_count, 2);
+        assert_eq!(sound.frame_rate, 48000);
+        assert_eq!(sound.channel_count, 2);
+        assert_eq!(sound.channel_flags, SndAssetChannel::L | SndAssetChannel::R);
+        assert_eq!(sound.flags, SndAssetFlags::LOOPING);
+        assert_eq!(sound.format, SndAssetFormat::PCMS16);
+
+        let wav = sound.to_wav().unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        // block align
+        assert_eq!(wav[32], 4);
+        assert_eq!(wav[44..], AUDIO);
+    }
+}
+
+#[test]
+fn bad_bank() {
+    let mut bytes = bank(true);
+    bytes[0] = 0;
+    let e = SoundBankFile::new(Cursor::new(&bytes)).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::BadSoundBank(_)));
+
+    // an entry that lies past the end of the bank
+    let mut bytes = bank(true);
+    bytes[32] = 8;
+    let e = SoundBankFile::new(Cursor::new(&bytes)).err().unwrap();
+    assert!(matches!(e.kind(), ErrorKind::BadSoundBank(_)));
+}
+
+#[test]
+fn fuse_streamed() {
+    let dir = game_dir("fuse", &[("a.sabs", bank(true))]);
+    let mut banks = SoundBanks::from_game_dir(&dir).unwrap();
+    assert_eq!(banks.paths().count(), 1);
+    assert!(banks.read_sound(0).unwrap().is_none());
+
+    let sound = banks.read_sound(ID).unwrap().unwrap();
+    let mut file = SoundFile {
+        u: SoundFileRef::Streamed(Some(Box::new(StreamedSound {
+            filename: XString("snd/a".to_string()),
+            prime_snd: None,
+        }))),
+        exists: true,
+    };
+    assert!(file.fuse_streamed(sound.clone()));
+    let SoundFileRef::Loaded(Some(loaded)) = &file.u else {
+        unreachable!()
+    };
+    assert_eq!(loaded.name.get(), "snd/a");
+    assert_eq!(loaded.sound, sound);
+
+    // sounds that are already loaded are left alone
+    assert!(!file.fuse_streamed(sound));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn builder_game_dir() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::SOUND, snd_bank_asset());
+    let bytes = xfile.to_bytes();
+    let deserialize = |dir: Option<PathBuf>| {
+        let de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true);
+        let de = match dir {
+            Some(dir) => de.with_game_dir(dir),
+            None => de,
+        };
+        let mut assets = de
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+        let XAsset::PC(XAssetGeneric::Sound(Some(bank))) = assets.remove(0) else {
+            unreachable!()
+        };
+        let alias = &bank.aliases[0].aliases[0];
+        alias.sound_file.as_deref().unwrap().u.clone()
+    };
+
+    let SoundFileRef::Streamed(Some(streamed)) = deserialize(None) else {
+        panic!("sound should still be streamed");
+    };
+    assert_eq!(streamed.filename.get(), "snd/a");
+
+    let dir = game_dir("builder", &[("a.sabs", bank(true))]);
+    let SoundFileRef::Loaded(Some(loaded)) = deserialize(Some(dir.clone())) else {
+        panic!("sound should have been loaded");
+    };
+    assert_eq!(loaded.name.get(), "snd/a");
+    assert_eq!(loaded.sound.data, AUDIO);
+    std::fs::remove_dir_all(dir).unwrap();
+}
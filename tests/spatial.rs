@@ -0,0 +1,164 @@
+use t5_xfile_defs::{
+    clipmap::CBrush,
+    common::{Aabb, Vec3},
+    gfx_world::GfxAabbTree,
+    xmodel::{CBrushSide, CPlane},
+};
+
+fn v(v: [f32; 3]) -> Vec3 {
+    Vec3::from(v)
+}
+
+/// A unit cube at the origin, with its top corner along +X+Y+Z cut off by
+/// the plane x + y + z = 2.
+fn brush() -> CBrush {
+    let n = 1.0 / 3.0f32.sqrt();
+    CBrush {
+        mins: v([0.0; 3]),
+        contents: 1,
+        maxs: v([1.0; 3]),
+        sides: vec![CBrushSide {
+            plane: Some(Box::new(CPlane {
+                normal: v([n; 3]),
+                dist: 2.0 * n,
+                ..Default::default()
+            })),
+            cflags: 0,
+            sflags: 0,
+        }],
+        axial_cflags: [[0; 3]; 2],
+        axial_sflags: [[0; 3]; 2],
+        verts: Vec::new(),
+    }
+}
+
+#[test]
+fn aabb() {
+    let a = Aabb::new(v([0.0; 3]), v([1.0; 3]));
+    assert!(a.contains(v([0.5; 3])));
+    assert!(a.contains(v([1.0; 3])));
+    assert!(!a.contains(v([1.5, 0.5, 0.5])));
+
+    assert!(a.intersects(Aabb::new(v([1.0; 3]), v([2.0; 3]))));
+    assert!(!a.intersects(Aabb::new(v([1.5, 0.0, 0.0]), v([2.0; 3]))));
+
+    assert_eq!(
+        Aabb::from_origin_half_size(v([1.0; 3]), v([0.5; 3])),
+        Aabb::new(v([0.5; 3]), v([1.5; 3]))
+    );
+
+    assert_eq!(
+        a.segment_intersection(v([-1.0, 0.5, 0.5]), v([3.0, 0.5, 0.5])),
+        Some((0.25, 0.5))
+    );
+    assert_eq!(
+        a.segment_intersection(v([0.5; 3]), v([0.5, 0.5, 3.0])),
+        Some((0.0, 0.2))
+    );
+    assert_eq!(
+        a.segment_intersection(v([-1.0, 2.0, 0.5]), v([3.0, 2.0, 0.5])),
+        None
+    );
+    assert_eq!(
+        a.segment_intersection(v([-3.0, 0.5, 0.5]), v([-1.0, 0.5, 0.5])),
+        None
+    );
+}
+
+#[test]
+fn brush_trace() {
+    let brush = brush();
+
+    // through an axial side
+    assert_eq!(
+        brush.trace(v([-1.0, 0.25, 0.25]), v([1.0, 0.25, 0.25])),
+        Some((0.5, [-1.0, 0.0, 0.0]))
+    );
+
+    // through the cut corner
+    let (fraction, normal) = brush.trace(v([2.0; 3]), v([0.0; 3])).unwrap();
+    assert!((fraction - 2.0 / 3.0).abs() < 1.0e-6);
+    assert!(
+        normal
+            .iter()
+            .all(|c| (c - 1.0 / 3.0f32.sqrt()).abs() < 1.0e-6)
+    );
+
+    // into the bounds where the corner was cut off
+    assert_eq!(brush.trace(v([2.0, 0.9, 0.9]), v([0.95, 0.9, 0.9])), None);
+
+    // from inside
+    assert_eq!(
+        brush.trace(v([0.25; 3]), v([3.0, 0.25, 0.25])),
+        Some((0.0, [0.0; 3]))
+    );
+
+    // short of it
+    assert_eq!(brush.trace(v([-2.0, 0.5, 0.5]), v([-1.0, 0.5, 0.5])), None);
+}
+
+#[test]
+fn brush_polygons() {
+    let brush = brush();
+    let polygons = brush.polygons();
+
+    // +X, -X, +Y, -Y, +Z, -Z, then the cut, which leaves a triangle on each
+    // side touching the cut corner
+    assert_eq!(
+        polygons.iter().map(Vec::len).collect::<Vec<_>>(),
+        [3, 4, 3, 4, 3, 4, 3]
+    );
+
+    let planes = [
+        [1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, -1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, -1.0],
+        [1.0, 1.0, 1.0],
+    ];
+    for (polygon, normal) in polygons.iter().zip(planes) {
+        for p in polygon {
+            assert!(p.iter().all(|&c| (-1.0e-4..=1.0 + 1.0e-4).contains(&c)));
+        }
+
+        // counterclockwise seen from outside
+        let (a, b, c) = (polygon[0], polygon[1], polygon[2]);
+        let ab = [0, 1, 2].map(|i| b[i] - a[i]);
+        let ac = [0, 1, 2].map(|i| c[i] - a[i]);
+        let cross = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        assert!((0..3).map(|i| cross[i] * normal[i]).sum::<f32>() > 0.0);
+    }
+
+    let cut = &polygons[6];
+    for corner in [[1.0, 1.0, 0.0], [1.0, 0.0, 1.0], [0.0, 1.0, 1.0]] {
+        assert!(
+            cut.iter()
+                .any(|p| (0..3).all(|i| (p[i] - corner[i]).abs() < 1.0e-4))
+        );
+    }
+}
+
+#[test]
+fn aabb_tree_children() {
+    let tree = |children_offset| GfxAabbTree {
+        mins: v([0.0; 3]),
+        maxs: v([0.0; 3]),
+        child_count: 2,
+        surface_count: 0,
+        start_surf_index: 0,
+        smodel_indexes: Vec::new(),
+        children_offset,
+    };
+
+    assert_eq!(tree(40).first_child(0), Some(1));
+    assert_eq!(tree(120).first_child(2), Some(5));
+    assert_eq!(tree(-40).first_child(2), Some(1));
+    assert_eq!(tree(-120).first_child(2), None);
+    assert_eq!(tree(20).first_child(0), None);
+}
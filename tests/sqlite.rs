@@ -0,0 +1,206 @@
+#![cfg(feature = "sqlite")]
+
+use t5_xfile_deserializer::{batch::Batch, sqlite::AssetIndex, testgen::SyntheticXFile};
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    hash::StableHash,
+    techset::{
+        GfxImage, Material, MaterialInfo, MaterialTechniqueSet, MaterialTextureDef,
+        MaterialTextureDefInfo,
+    },
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+    xmodel::{PhysPreset, XModel},
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn material(name: &str, image: &str) -> Material {
+    let texture = |image: &str| MaterialTextureDef {
+        u: MaterialTextureDefInfo::Image(Some(Box::new(GfxImage {
+            name: xstring(image),
+            ..Default::default()
+        }))),
+        ..Default::default()
+    };
+
+    Material {
+        info: MaterialInfo {
+            name: xstring(name),
+            ..Default::default()
+        },
+        // the same image twice is only one dependency
+        textures: vec![texture(image), texture(image)],
+        technique_set: Some(Box::new(MaterialTechniqueSet {
+            name: xstring("wc_l_sm_r0c0"),
+            world_vert_format: 0,
+            techset_flags: 0,
+            techniques: Vec::new(),
+        })),
+        ..Default::default()
+    }
+}
+
+fn model(name: &str, materials: &[&str]) -> XModel {
+    XModel {
+        name: xstring(name),
+        material_handles: materials
+            .iter()
+            .map(|m| Box::new(material(m, "~-gstone")))
+            .collect(),
+        phys_preset: Some(Box::new(PhysPreset {
+            name: xstring("default"),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+fn bag(assets: impl IntoIterator<Item = XAssetGeneric>) -> XAssetBag {
+    let mut bag = XAssetBag::new();
+    for asset in assets {
+        bag.insert(XAsset::PC(asset));
+    }
+    bag
+}
+
+#[test]
+fn dependencies() {
+    let material = XAsset::PC(XAssetGeneric::Material(Some(Box::new(material(
+        "mc/mtl_rock",
+        "~-grock",
+    )))));
+    assert_eq!(
+        material.dependencies(),
+        [
+            (XAssetType::TECHNIQUE_SET, "wc_l_sm_r0c0".to_string()),
+            (XAssetType::IMAGE, "~-grock".to_string()),
+        ]
+    );
+
+    let model = XAsset::PC(XAssetGeneric::XModel(Some(Box::new(model(
+        "rock",
+        &["mc/mtl_rock", "mc/mtl_moss"],
+    )))));
+    assert_eq!(
+        model.dependencies(),
+        [
+            (XAssetType::MATERIAL, "mc/mtl_rock".to_string()),
+            (XAssetType::MATERIAL, "mc/mtl_moss".to_string()),
+            (XAssetType::PHYSPRESET, "default".to_string()),
+        ]
+    );
+
+    assert!(
+        XAsset::PC(XAssetGeneric::Material(None))
+            .dependencies()
+            .is_empty()
+    );
+}
+
+#[test]
+fn index() {
+    let mut index = AssetIndex::open_in_memory().unwrap();
+
+    let rock = material("mc/mtl_rock", "~-grock");
+    let common = bag([
+        XAssetGeneric::Material(Some(Box::new(rock.clone()))),
+        XAssetGeneric::XModel(Some(Box::new(model("rock", &["mc/mtl_rock"])))),
+    ]);
+    index.insert_zone("common_mp", &common).unwrap();
+    index
+        .insert_zone(
+            "mp_nuked",
+            &bag([XAssetGeneric::Material(Some(Box::new(rock.clone())))]),
+        )
+        .unwrap();
+
+    assert_eq!(index.zones().unwrap(), ["common_mp", "mp_nuked"]);
+    assert_eq!(
+        index
+            .zones_containing(XAssetType::MATERIAL, "mc/mtl_rock")
+            .unwrap(),
+        ["common_mp", "mp_nuked"]
+    );
+    assert!(
+        index
+            .zones_containing(XAssetType::IMAGE, "mc/mtl_rock")
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        index
+            .dependents(XAssetType::MATERIAL, "mc/mtl_rock")
+            .unwrap(),
+        [("XMODEL".to_string(), "rock".to_string())]
+    );
+
+    let hash = XAsset::PC(XAssetGeneric::Material(Some(Box::new(rock))))
+        .stable_hash()
+        .unwrap();
+    assert_eq!(
+        index
+            .hash("mp_nuked", XAssetType::MATERIAL, "mc/mtl_rock")
+            .unwrap(),
+        Some(hash)
+    );
+    assert_eq!(
+        index.hash("mp_nuked", XAssetType::XMODEL, "rock").unwrap(),
+        None
+    );
+
+    // reindexing a zone replaces it
+    index.insert_zone("common_mp", &bag([])).unwrap();
+    assert_eq!(
+        index
+            .zones_containing(XAssetType::MATERIAL, "mc/mtl_rock")
+            .unwrap(),
+        ["mp_nuked"]
+    );
+    assert!(
+        index
+            .dependents(XAssetType::MATERIAL, "mc/mtl_rock")
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn index_batch() {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-sqlite-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    for zone in ["a", "b"] {
+        let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+            .with_rawfile("maps/shared.gsc", b"main() { }")
+            .with_rawfile(&format!("maps/{zone}.gsc"), b"main() { }");
+        std::fs::write(dir.join(format!("{zone}.ff")), xfile.to_bytes()).unwrap();
+    }
+
+    let db = dir.join("index.db");
+    let mut index = AssetIndex::open(&db).unwrap();
+    let report = index
+        .insert_batch(&Batch::new(&dir, XFilePlatform::Windows))
+        .unwrap();
+    assert_eq!(report.processed.len(), 2);
+    drop(index);
+
+    // the index persists
+    let index = AssetIndex::open(&db).unwrap();
+    assert_eq!(
+        index
+            .zones_containing(XAssetType::RAWFILE, "maps/shared.gsc")
+            .unwrap(),
+        ["a", "b"]
+    );
+    assert_eq!(
+        index
+            .zones_containing(XAssetType::RAWFILE, "maps/b.gsc")
+            .unwrap(),
+        ["b"]
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,30 @@
+use t5_xfile_defs::misc::{StringTable, string_table_hash};
+
+#[test]
+fn hash() {
+    assert_eq!(string_table_hash(""), 0);
+    assert_eq!(string_table_hash("a"), 97);
+    assert_eq!(string_table_hash("ab"), 97 * 31 + 98);
+    // case-insensitive
+    assert_eq!(string_table_hash("AB"), string_table_hash("ab"));
+    assert_eq!(string_table_hash("mp_nuked"), 133910919);
+    // wraps around
+    assert_eq!(string_table_hash("killstreak_rcbomb_mp"), -560748308);
+}
+
+#[test]
+fn from_csv() {
+    let table = StringTable::from_csv("mp/t.csv", "b,x\na\nB,y\n").unwrap();
+    assert_eq!((table.row_count, table.column_count), (3, 2));
+    assert_eq!(table.cell(1, 1), Some(""));
+    assert!(
+        table
+            .values
+            .iter()
+            .all(|c| c.hash == string_table_hash(c.name.get()))
+    );
+
+    // each column's rows, sorted by hash (and then row)
+    assert_eq!(table.cell_index, [1, 0, 2, 1, 0, 2]);
+    assert_eq!(table.to_csv(), "b,x\r\na,\r\nB,y\r\n");
+}
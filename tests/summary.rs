@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use t5_xfile_defs::{
+    XString,
+    misc::RawFile,
+    xasset::{XAsset, XAssetGeneric},
+};
+use t5_xfile_deserializer::summary::{SummaryOptions, summarize, summarize_with};
+
+#[derive(Serialize)]
+struct Node {
+    name: &'static str,
+    origin: [f32; 3],
+    kind: Kind,
+    ids: HashMap<u32, Option<u16>>,
+}
+
+#[derive(Serialize)]
+enum Kind {
+    Leaf,
+    Branch { children: Vec<u8> },
+}
+
+fn node() -> Node {
+    Node {
+        name: "n",
+        origin: [1.0, -0.00001, 1.0 / 3.0],
+        kind: Kind::Leaf,
+        ids: [(20, None), (3, Some(7))].into_iter().collect(),
+    }
+}
+
+#[test]
+fn canonical() {
+    assert_eq!(
+        summarize(&node()).unwrap(),
+        "
+  ids: 
+    20: None
+    3: Some 7
+  kind: Leaf
+  name: \"n\"
+  origin: 
+    [0]: 1
+    [1]: 0
+    [2]: 0.3333
+"
+    );
+
+    let summary = summarize_with(&node(), SummaryOptions::new().with_float_digits(6)).unwrap();
+    assert!(summary.contains("[1]: -0.00001\n"), "{summary}");
+}
+
+#[test]
+fn long_sequences() {
+    let branch = |children: Vec<u8>| Kind::Branch { children };
+
+    let short = summarize(&branch(vec![1, 2])).unwrap();
+    assert_eq!(short, "Branch \n  children: \n    [0]: 1\n    [1]: 2\n");
+
+    let long = summarize(&branch(vec![0; 100])).unwrap();
+    assert!(long.contains("children: <100 elements, hash "), "{long}");
+    assert_ne!(long, summarize(&branch(vec![1; 100])).unwrap());
+    assert_eq!(long, summarize(&branch(vec![0; 100])).unwrap());
+
+    let full = summarize_with(
+        &branch(vec![0; 100]),
+        SummaryOptions::new().with_max_elements(None),
+    )
+    .unwrap();
+    assert!(full.contains("[99]: 0\n"));
+}
+
+#[test]
+fn asset() {
+    let asset = XAsset::PC(XAssetGeneric::RawFile(Some(Box::new(RawFile {
+        name: XString("maps/a.gsc".to_string()),
+        buffer: b"main(){}\0".to_vec(),
+    }))));
+
+    let summary = summarize(&asset).unwrap();
+    assert!(summary.starts_with("PC RawFile Some \n"), "{summary}");
+    assert!(summary.contains("  name: \"maps/a.gsc\"\n"), "{summary}");
+}
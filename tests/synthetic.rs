@@ -0,0 +1,930 @@
+//! Parses Fastfiles made by [`t5_xfile_deserializer::testgen`].
+
+#![cfg(feature = "deserializer")]
+
+mod common;
+
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+use t5_xfile_defs::{
+    ErrorKind, LocalizeEntry, PtrMarker, RawFile, ScriptString, T5XFileDeserialize, XFileBlock,
+    XFileOffset, XFilePlatform, XString,
+    common::Vec3,
+    menu::MenuList,
+    misc::{StringTable, StringTableCell, string_table_hash},
+    stats::XAssetStats,
+    xanim::{XAnimDeltaPartQuatData, XAnimPartTransData},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+use common::deserialize;
+
+fn text_assets_expected() -> Vec<XAssetGeneric<1>> {
+    let cells = ["a", "b", "", "d"]
+        .into_iter()
+        .map(|s| StringTableCell {
+            name: XString(s.to_string()),
+            hash: string_table_hash(s),
+        })
+        .collect();
+
+    vec![
+        XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: XString("maps/a.gsc".to_string()),
+            buffer: b"main() { }\0".to_vec(),
+        }))),
+        XAssetGeneric::RawFile(None),
+        XAssetGeneric::LocalizeEntry(Some(Box::new(LocalizeEntry {
+            value: XString("Start Game".to_string()),
+            name: XString("MENU_START_GAME".to_string()),
+        }))),
+        XAssetGeneric::StringTable(Some(Box::new(StringTable {
+            name: XString("mp/t.csv".to_string()),
+            column_count: 2,
+            row_count: 2,
+            values: cells,
+            cell_index: vec![0, 1, 2, 3],
+        }))),
+    ]
+}
+
+#[test]
+fn every_asset_type() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::macOS] {
+        let xfile = SyntheticXFile::every_asset_type(platform);
+        let assets = deserialize(&xfile);
+
+        assert_eq!(
+            assets.iter().map(XAsset::asset_type).collect::<Vec<_>>(),
+            xfile.asset_types().collect::<Vec<_>>()
+        );
+        assert!(assets.iter().all(XAsset::is_none));
+    }
+}
+
+#[test]
+fn text_assets() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::macOS] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_rawfile("maps/a.gsc", b"main() { }")
+            .with_null_asset(XAssetType::RAWFILE)
+            .with_localize_entry("MENU_START_GAME", "Start Game")
+            .with_string_table("mp/t.csv", 2, &["a", "b", "", "d"]);
+        let expected = text_assets_expected()
+            .into_iter()
+            .map(XAsset::PC)
+            .collect::<Vec<_>>();
+        let assets = deserialize(&xfile);
+
+        assert_eq!(assets, expected, "{platform}");
+    }
+}
+
+#[test]
+fn script_strings() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_script_string("tag_origin")
+        .with_script_string("")
+        .with_script_string("j_head")
+        .with_null_asset(XAssetType::XMODEL);
+    let bytes = xfile.to_bytes();
+    let de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+
+    let strings = (0..3)
+        .map(|i| de.get_script_string(ScriptString(i)).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(strings, [Some("tag_origin"), Some(""), Some("j_head")]);
+}
+
+#[test]
+fn max_local_clients() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_null_asset(XAssetType::MENU);
+    let bytes = xfile.to_bytes();
+    let de = |max_local_clients| {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_max_local_clients(max_local_clients)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+    };
+
+    let assets = de(4).deserialize_remaining().unwrap();
+    assert!(assets.iter().all(XAsset::is_console));
+    assert_eq!(assets[0].name(), Some("MENU_START_GAME"));
+    assert_eq!(assets[1], XAsset::Console(XAssetGeneric::Menu(None)));
+
+    let assets = de(1).deserialize_remaining().unwrap();
+    assert!(assets.iter().all(|a| a.max_local_clients() == 1));
+
+    let e = de(2).deserialize_next().unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::UnsupportedMaxLocalClients(2)));
+}
+
+#[test]
+fn as_generic() {
+    fn menu_count<const MAX_LOCAL_CLIENTS: usize>(asset: &XAsset) -> Option<usize> {
+        match asset.as_generic::<MAX_LOCAL_CLIENTS>()? {
+            XAssetGeneric::MenuList(Some(list)) => Some(list.menus.len()),
+            _ => None,
+        }
+    }
+
+    let mut asset = XAsset::Console(XAssetGeneric::MenuList(Some(Box::new(MenuList {
+        name: XString("ui/menus.txt".to_string()),
+        menus: Vec::new(),
+    }))));
+    assert_eq!(menu_count::<4>(&asset), Some(0));
+    assert_eq!(menu_count::<1>(&asset), None);
+    assert!(asset.as_generic_mut::<4>().is_some());
+    assert!(asset.as_generic_mut::<1>().is_none());
+
+    assert_eq!(
+        XAsset::from_generic(XAssetGeneric::<4>::MenuList(None)).unwrap(),
+        XAsset::Console(XAssetGeneric::MenuList(None))
+    );
+    assert!(
+        XAsset::from_generic(XAssetGeneric::<1>::MenuList(None))
+            .unwrap()
+            .is_pc()
+    );
+    let e = XAsset::from_generic(XAssetGeneric::<2>::MenuList(None)).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::UnsupportedMaxLocalClients(2)));
+}
+
+#[test]
+fn menu_key_handlers() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::PS3] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_menu("main", 100_000)
+            .with_rawfile("after.txt", b"");
+        let assets = deserialize(&xfile);
+
+        let menu = match &assets[0] {
+            XAsset::PC(XAssetGeneric::Menu(Some(menu))) => &menu.on_key,
+            XAsset::Console(XAssetGeneric::Menu(Some(menu))) => &menu.on_key,
+            asset => panic!("{:?}", asset.asset_type()),
+        };
+        let keys = core::iter::successors(menu.as_deref(), |h| h.next.as_deref())
+            .map(|h| h.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, (0..100_000).collect::<Vec<_>>());
+        assert_eq!(assets[1].name(), Some("after.txt"));
+    }
+}
+
+#[test]
+fn corrupt_array_count() {
+    // a string table claiming 1000000 rows, with none following
+    let data = [u32::MAX, 1, 1_000_000, u32::MAX, u32::MAX]
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .chain(*b"t.csv\0")
+        .collect();
+    let xfile =
+        SyntheticXFile::new(XFilePlatform::Windows).with_raw_asset(XAssetType::STRINGTABLE, data);
+    let bytes = xfile.to_bytes();
+    let e = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_next()
+        .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e:?}");
+}
+
+#[test]
+fn checked_array_reads() {
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .to_bytes();
+    let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+
+    let pos = de.stream_pos().unwrap();
+    let remaining = (de.stream_len().unwrap() - pos) as usize;
+
+    // past the end, overflowing, and for raw bytes
+    let e = de.load_array::<u32>(remaining / 4 + 1).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e:?}");
+    assert!(
+        e.to_string().contains(&format!("at offset {pos:#X}")),
+        "{e}"
+    );
+    let e = de.load_array::<u64>(usize::MAX).unwrap_err();
+    assert!(e.to_string().contains("overflows"), "{e}");
+    let e = de.load_bytes(remaining + 1).unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BrokenInvariant(_)), "{e:?}");
+
+    // nothing was read
+    assert_eq!(de.stream_pos().unwrap(), pos);
+    assert_eq!(de.load_array::<u8>(remaining).unwrap().len(), remaining);
+}
+
+/// `le_bytes`, reversed if `platform` is big-endian.
+fn ordered<const N: usize>(mut le_bytes: [u8; N], platform: XFilePlatform) -> [u8; N] {
+    if !platform.is_le() {
+        le_bytes.reverse();
+    }
+    le_bytes
+}
+
+/// An animation with a delta part whose translation and rotation are both
+/// stored in unions, in `platform`'s byte order.
+fn delta_anim(platform: XFilePlatform) -> Vec<u8> {
+    let inline = ordered(u32::MAX.to_le_bytes(), platform);
+
+    let mut header = vec![0u8; 104];
+    header[..4].copy_from_slice(&inline); // name
+    header[10..12].copy_from_slice(&ordered(1u16.to_le_bytes(), platform)); // numframes
+    header[100..].copy_from_slice(&inline); // delta_part
+
+    let mut data = header;
+    data.extend_from_slice(b"anim_delta\0");
+    // XAnimDeltaPart, both inline
+    data.extend_from_slice(&inline);
+    data.extend_from_slice(&inline);
+    // XAnimPartTrans with no frames, so just the translation
+    data.extend_from_slice(&[0; 4]);
+    for f in [1.0f32, 2.0, 3.0] {
+        data.extend_from_slice(&ordered(f.to_le_bytes(), platform));
+    }
+    data.extend_from_slice(&[0; 20]);
+    // XAnimDeltaPartQuat with no frames, so just a pointer to the rotation
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&inline);
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&ordered(0x0102i16.to_le_bytes(), platform));
+    data.extend_from_slice(&ordered((-2i16).to_le_bytes(), platform));
+    data
+}
+
+#[test]
+fn unions_in_both_byte_orders() {
+    for platform in [XFilePlatform::Windows, XFilePlatform::PS3] {
+        let assets = deserialize(
+            &SyntheticXFile::new(platform)
+                .with_raw_asset(XAssetType::XANIMPARTS, delta_anim(platform)),
+        );
+
+        let anim = match &assets[0] {
+            XAsset::PC(XAssetGeneric::XAnimParts(Some(a))) => a,
+            XAsset::Console(XAssetGeneric::XAnimParts(Some(a))) => a,
+            a => panic!("{:?}", a.asset_type()),
+        };
+        assert_eq!(anim.name.get(), "anim_delta");
+        let delta = anim.delta_part.as_ref().unwrap();
+        assert_eq!(
+            delta.trans.as_ref().unwrap().u,
+            Some(XAnimPartTransData::Frame0(Vec3::from([1.0, 2.0, 3.0]))),
+            "{platform}"
+        );
+        assert_eq!(
+            delta.quat.as_ref().unwrap().u,
+            Some(XAnimDeltaPartQuatData::Frame0([0x0102, -2])),
+            "{platform}"
+        );
+    }
+}
+
+#[test]
+fn stats() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_rawfile("maps/b.gsc", &[0; 256])
+        .with_null_asset(XAssetType::RAWFILE)
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let stats = deserialize(&xfile)
+        .into_iter()
+        .collect::<XAssetBag>()
+        .stats();
+
+    assert_eq!(stats.total_count(), 3);
+    assert_eq!(stats.types[&XAssetType::RAWFILE].count, 2);
+    assert_eq!(stats.types[&XAssetType::LOCALIZE_ENTRY].count, 1);
+    assert_eq!(
+        stats
+            .largest
+            .iter()
+            .map(|a| a.name.as_str())
+            .collect::<Vec<_>>(),
+        ["maps/b.gsc", "MENU_START_GAME", "maps/a.gsc"]
+    );
+    assert!(stats.largest[0].estimated_size > 256);
+    assert_eq!(
+        stats.total_estimated_size(),
+        stats.largest.iter().map(|a| a.estimated_size).sum::<u64>()
+    );
+    assert_eq!(
+        (
+            stats.vertex_count,
+            stats.triangle_count,
+            stats.sound_sample_count
+        ),
+        (0, 0, 0)
+    );
+    assert_eq!(XAssetStats::from_assets(&[]), XAssetStats::default());
+}
+
+#[test]
+fn real_ptrs() {
+    // the asset array takes up 16 bytes of the virtual block, the first
+    // entry's header 8, so its value starts at 24
+    let offset = XFileOffset::new(XFileBlock::Virtual, 24);
+    let data = [offset.as_u32(), u32::MAX]
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .chain(*b"B\0")
+        .collect();
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_localize_entry("A", "Start Game")
+        .with_raw_asset(XAssetType::LOCALIZE_ENTRY, data);
+    let bytes = xfile.to_bytes();
+    let de = |follow| {
+        let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_follow_real_ptrs(follow)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap();
+        let assets = (0..2)
+            .map(|_| de.deserialize_next().unwrap().unwrap())
+            .collect::<Vec<_>>();
+        (de, assets)
+    };
+    let value = |asset: &XAsset| match asset {
+        XAsset::PC(XAssetGeneric::LocalizeEntry(Some(e))) => e.value.get().to_string(),
+        _ => unreachable!(),
+    };
+
+    let (unfollowed, assets) = de(false);
+    assert_eq!(value(&assets[1]), "");
+    let [real_ptr] = unfollowed.real_ptrs() else {
+        panic!("{:?}", unfollowed.real_ptrs());
+    };
+    assert_eq!(real_ptr.offset, offset);
+    assert_eq!(real_ptr.offset.xfile_block(), XFileBlock::Virtual);
+    assert!(real_ptr.target.is_some());
+    assert_eq!(real_ptr.target_marker, Some(PtrMarker::Following));
+
+    let (_, assets) = de(true);
+    assert_eq!(value(&assets[1]), "Start Game");
+    assert_eq!(assets[1].name(), Some("B"));
+}
+
+#[test]
+fn null_ptrs_arent_real() {
+    // null asset headers, and a menu whose boxed pointers are all null
+    // except for its key handlers, the last of which has a null `next`
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_null_asset(XAssetType::RAWFILE)
+        .with_null_asset(XAssetType::LOCALIZE_ENTRY)
+        .with_menu("menu", 2);
+    let bytes = xfile.to_bytes();
+    let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+    while de.deserialize_next().unwrap().is_some() {}
+
+    assert!(de.real_ptrs().is_empty(), "{:?}", de.real_ptrs());
+}
+
+#[test]
+fn ptr_markers() {
+    let data = [PtrMarker::Inserted, PtrMarker::Following]
+        .into_iter()
+        .flat_map(|m| m.as_u32().to_le_bytes())
+        .chain(*b"Start Game\0B\0")
+        .collect();
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_raw_asset(XAssetType::LOCALIZE_ENTRY, data);
+    let bytes = xfile.to_bytes();
+    let de = |record| {
+        let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_record_ptr_markers(record)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap();
+        let asset = de.deserialize_next().unwrap().unwrap();
+        (de, asset)
+    };
+
+    let (unrecorded, asset) = de(false);
+    assert_eq!(asset.name(), Some("B"));
+    assert!(unrecorded.inline_ptrs().is_empty());
+    // the asset array, the asset's header, then its name
+    assert_eq!(unrecorded.ptr_marker_count(PtrMarker::Following), 3);
+    assert_eq!(unrecorded.ptr_marker_count(PtrMarker::Inserted), 1);
+
+    let (recorded, _) = de(true);
+    let markers = recorded
+        .inline_ptrs()
+        .iter()
+        .map(|p| p.marker)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        markers,
+        [
+            PtrMarker::Following,
+            PtrMarker::Following,
+            PtrMarker::Inserted,
+            PtrMarker::Following
+        ]
+    );
+    let [_, header, value, name] = recorded.inline_ptrs() else {
+        unreachable!()
+    };
+    assert_eq!(value.pos, header.pos + 8);
+    assert_eq!(name.pos, value.pos + b"Start Game\0".len() as u64);
+    assert_eq!(value.offset.xfile_block(), XFileBlock::Virtual);
+}
+
+#[test]
+fn max_ptr_depth() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_null_asset(XAssetType::RAWFILE);
+    let bytes = xfile.to_bytes();
+    let de = |depth| {
+        T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_max_ptr_depth(depth)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+    };
+
+    // the asset's header is behind the only pointer
+    let e = de(0).deserialize_next().unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::PtrDepthExceeded(0)), "{e:?}");
+
+    // nothing else in a raw file is behind a pointer
+    let mut de = de(1);
+    assert!(de.deserialize_next().unwrap().is_some());
+    assert!(de.deserialize_next().unwrap().is_some_and(|a| a.is_none()));
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn trace() {
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_script_string("tag_origin")
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let bytes = xfile.to_bytes();
+    let mut de = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_trace(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap();
+    while de.deserialize_next().unwrap().is_some() {}
+    let trace = de.take_trace().unwrap();
+
+    // everything that was read is covered, without gaps or overlaps
+    let top_level = trace
+        .entries()
+        .iter()
+        .filter(|e| e.depth == 0)
+        .collect::<Vec<_>>();
+    assert_eq!(top_level.first().unwrap().offset, 0);
+    assert!(top_level.windows(2).all(|w| w[0].end() == w[1].offset));
+    assert_eq!(
+        top_level.last().unwrap().end(),
+        xfile.payload().len() as u64
+    );
+
+    assert_eq!(trace.assets().len(), 2);
+    let rawfile = &trace.assets()[0];
+    assert_eq!(rawfile.asset_type, XAssetType::RAWFILE);
+    assert_eq!(rawfile.name.as_deref(), Some("maps/a.gsc"));
+
+    let len = trace.lookup(rawfile.offset + 4).unwrap();
+    assert_eq!(len.path, "RawFileRaw.len");
+    assert_eq!((len.offset, len.len, len.depth), (rawfile.offset + 4, 4, 1));
+    assert!(
+        trace
+            .asset_entries(0)
+            .all(|e| { e.offset >= rawfile.offset && e.end() <= rawfile.offset + rawfile.len })
+    );
+}
+
+#[test]
+fn protected() {
+    use t5_xfile_defs::XFileProtection;
+
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .to_bytes();
+    let protection = |bytes: &[u8]| {
+        let res = T5XFileDeserializerBuilder::from_bytes(bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .build()
+            .and_then(|de| de.inflate());
+        match res.map(|_| ()).unwrap_err().kind() {
+            ErrorKind::ProtectedFile(protection) => Some(*protection),
+            _ => None,
+        }
+    };
+
+    let mut encrypted = bytes.clone();
+    encrypted[..8].copy_from_slice(b"TAff0100");
+    assert_eq!(protection(&encrypted), Some(XFileProtection::Encrypted));
+
+    let mut signed = bytes.clone();
+    signed[..8].copy_from_slice(b"IWffs100");
+    assert_eq!(protection(&signed), Some(XFileProtection::Signed));
+
+    // a valid header, followed by a signed payload
+    let mut signed = bytes[..12].to_vec();
+    signed.extend_from_slice(b"PHEEBs71");
+    signed.extend_from_slice(&[0; 0x100]);
+    assert_eq!(protection(&signed), Some(XFileProtection::Signed));
+
+    // just corrupt
+    let mut corrupt = bytes[..12].to_vec();
+    corrupt.extend_from_slice(&[0; 0x100]);
+    assert_eq!(protection(&corrupt), None);
+}
+
+#[test]
+fn codecs() {
+    use t5_xfile_deserializer::codec::{CompressionCodec, RawDeflate};
+
+    /// Payloads stored as they are.
+    struct Stored;
+
+    impl CompressionCodec for Stored {
+        fn name(&self) -> &str {
+            "stored"
+        }
+
+        fn inflate(&self, compressed: &[u8]) -> t5_xfile_defs::Result<Vec<u8>> {
+            Ok(compressed.to_vec())
+        }
+
+        fn deflate(&self, payload: &[u8]) -> t5_xfile_defs::Result<Vec<u8>> {
+            Ok(payload.to_vec())
+        }
+    }
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let expected = deserialize(&xfile);
+    let with_payload = |compression: u8, payload: Vec<u8>| {
+        let mut bytes = xfile.to_bytes()[..12].to_vec();
+        bytes[4] = compression;
+        bytes.extend(payload);
+        bytes
+    };
+
+    // picked from the header
+    let bytes = with_payload(b'a', RawDeflate.deflate(&xfile.payload()).unwrap());
+    let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(assets, expected);
+
+    // an unknown compression byte is only accepted with a codec for it
+    let bytes = with_payload(b'x', xfile.payload());
+    let e = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .map(|_| ())
+        .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::BadHeaderMagic(_)));
+
+    let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .with_codec(Stored)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+    assert_eq!(assets, expected);
+
+    // and the same goes for opening it by path
+    #[cfg(feature = "cache")]
+    {
+        use t5_xfile_deserializer::{CacheMode, T5XFileDeserializer, T5XFileOpenOptions};
+
+        let dir = std::env::temp_dir().join(format!("t5-xfile-codec-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.ff");
+        std::fs::write(&path, &bytes).unwrap();
+
+        let options = T5XFileOpenOptions::new(XFilePlatform::Windows)
+            .with_silent(true)
+            .with_cache(CacheMode::Disabled)
+            .with_codec(Stored);
+        let assets = T5XFileDeserializer::open(&path, options)
+            .and_then(|de| de.deserialize_remaining())
+            .unwrap();
+        assert_eq!(assets, expected);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn archive() {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-archive-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_null_asset(XAssetType::RAWFILE)
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_string_table("mp/t.csv", 2, &["a", "b", "", "d"]);
+    let bytes = xfile.to_bytes();
+    let expected = deserialize(&xfile);
+
+    let path = dir.join("a.archive");
+    T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .archive(&path)
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap()[..8], *b"T5XFARCH");
+
+    let mut file = std::fs::File::open(&path).unwrap();
+    let assets =
+        T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+    assert_eq!(assets, expected);
+
+    // archives are only valid for the platform they were made for
+    let mut file = std::fs::File::open(&path).unwrap();
+    let err = T5XFileDeserializerBuilder::from_cache_file(&mut file, XFilePlatform::macOS, true)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind(), ErrorKind::BadCacheFile(_)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn open() {
+    use t5_xfile_deserializer::{CacheMode, T5XFileDeserializer, T5XFileOpenOptions};
+
+    let dir = std::env::temp_dir().join(format!("t5-xfile-open-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let expected = deserialize(&xfile);
+    let path = dir.join("a.ff");
+    std::fs::write(&path, xfile.to_bytes()).unwrap();
+
+    let options = || T5XFileOpenOptions::new(XFilePlatform::Windows).with_silent(true);
+    let open = |path: &std::path::Path, cache| {
+        T5XFileDeserializer::open(path, options().with_cache(cache))
+            .and_then(|de| de.deserialize_remaining())
+    };
+
+    // read-only and disabled never create the cache
+    assert_eq!(open(&path, CacheMode::ReadOnly).unwrap(), expected);
+    assert_eq!(open(&path, CacheMode::Disabled).unwrap(), expected);
+    assert!(!dir.join("a.cache").exists());
+
+    assert_eq!(open(&path, CacheMode::ReadWrite).unwrap(), expected);
+    assert!(dir.join("a.cache").exists());
+
+    // without the Fastfile, only the cache is left to load
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(open(&path, CacheMode::ReadOnly).unwrap(), expected);
+    assert_eq!(
+        open(&dir.join("a.cache"), CacheMode::Disabled).unwrap(),
+        expected
+    );
+    let err = open(&path, CacheMode::Disabled).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Io(_)));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Silently opening an allowed console Fastfile works the same whether it's
+/// loaded from the Fastfile or from its cache.
+#[cfg(feature = "cache")]
+#[test]
+fn open_console_cached() {
+    use t5_xfile_deserializer::{CacheMode, T5XFileDeserializer, T5XFileOpenOptions};
+
+    let dir = std::env::temp_dir().join(format!("t5-xfile-open-console-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let xfile =
+        SyntheticXFile::new(XFilePlatform::Xbox360).with_rawfile("maps/a.gsc", b"main() { }");
+    let expected = deserialize(&xfile);
+    let path = dir.join("a.ff");
+    std::fs::write(&path, xfile.to_bytes()).unwrap();
+
+    let open = || {
+        let options = T5XFileOpenOptions::new(XFilePlatform::Xbox360)
+            .with_allow_unsupported_platforms(true)
+            .with_silent(true)
+            .with_cache(CacheMode::ReadWrite);
+        T5XFileDeserializer::open(&path, options).and_then(|de| de.deserialize_remaining())
+    };
+
+    assert_eq!(open().unwrap(), expected);
+    assert!(dir.join("a.cache").exists());
+    assert_eq!(open().unwrap(), expected);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "cache")]
+#[test]
+fn cache_management() {
+    use t5_xfile_deserializer::{
+        CacheSuccess, build_cache, cache_info, cache_is_fresh, cache_path_for, clear_cache,
+    };
+
+    let dir = std::env::temp_dir().join(format!("t5-xfile-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    let path = dir.join("a.ff");
+    std::fs::write(&path, xfile.to_bytes()).unwrap();
+    let cache_path = cache_path_for(&path);
+    assert_eq!(cache_path, dir.join("a.cache"));
+
+    assert!(!clear_cache(&path).unwrap());
+    let built = build_cache(&path, XFilePlatform::Windows, false, false).unwrap();
+    assert!(matches!(built, Some(CacheSuccess::CacheCreated)));
+    assert!(
+        build_cache(&path, XFilePlatform::Windows, false, false)
+            .unwrap()
+            .is_none()
+    );
+    let built = build_cache(&path, XFilePlatform::Windows, false, true).unwrap();
+    assert!(matches!(built, Some(CacheSuccess::CacheOverwritten)));
+
+    let info = cache_info(&cache_path).unwrap();
+    assert!(!info.archive);
+    assert_eq!(info.platform, Some(XFilePlatform::Windows));
+    assert_eq!(info.assets, 2);
+    assert_eq!(info.source.len, std::fs::metadata(&path).unwrap().len());
+
+    // a Fastfile of the same size with different contents makes the cache
+    // stale
+    let changed = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/b.gsc", b"main() { }")
+        .with_localize_entry("MENU_START_GAME", "Start Game");
+    std::fs::write(&path, changed.to_bytes()).unwrap();
+    assert_eq!(info.source.len, std::fs::metadata(&path).unwrap().len());
+    assert!(!cache_is_fresh(&cache_path, &path, XFilePlatform::Windows).unwrap());
+    assert!(
+        build_cache(&path, XFilePlatform::Windows, false, false)
+            .unwrap()
+            .is_some()
+    );
+    assert!(cache_is_fresh(&cache_path, &path, XFilePlatform::Windows).unwrap());
+
+    assert!(clear_cache(&path).unwrap());
+    assert!(!cache_path.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn snapshot_and_resume() {
+    use t5_xfile_deserializer::{DeserializerSnapshot, T5XFileDeserializer};
+
+    let xfile = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_null_asset(XAssetType::RAWFILE)
+        .with_localize_entry("MENU_START_GAME", "Start Game")
+        .with_string_table("mp/t.csv", 2, &["a", "b", "", "d"]);
+    let expected = deserialize(&xfile);
+    let bytes = xfile.to_bytes();
+    fn de(bytes: &[u8]) -> T5XFileDeserializer<'_> {
+        T5XFileDeserializerBuilder::from_bytes(bytes, XFilePlatform::Windows, false)
+            .with_silent(true)
+            .with_follow_real_ptrs(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+    }
+
+    let mut first = de(&bytes);
+    first.deserialize_next().unwrap();
+    first.skip_next().unwrap();
+    let mut saved = Vec::new();
+    first.snapshot().write(&mut saved).unwrap();
+
+    let snapshot = DeserializerSnapshot::read(saved.as_slice()).unwrap();
+    assert_eq!(snapshot.remaining_assets(), 2);
+    assert_eq!(snapshot.deserialized_assets(), 1);
+    let mut second = de(&bytes);
+    second.resume(&snapshot).unwrap();
+    assert_eq!(second.deserialize_remaining().unwrap(), expected[2..]);
+
+    // a snapshot of another Fastfile
+    let other = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .to_bytes();
+    let err = de(&other).resume(&snapshot).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadSnapshot(_)));
+
+    let err = DeserializerSnapshot::read(&b"T5XFCACH\x02\0\0\0"[..]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::BadSnapshot(_)));
+
+    // real offsets into assets before the snapshot still resolve
+    let offset = XFileOffset::new(XFileBlock::Virtual, 24);
+    let data = [offset.as_u32(), u32::MAX]
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .chain(*b"B\0")
+        .collect();
+    let bytes = SyntheticXFile::new(XFilePlatform::Windows)
+        .with_localize_entry("A", "Start Game")
+        .with_raw_asset(XAssetType::LOCALIZE_ENTRY, data)
+        .to_bytes();
+    let mut first = de(&bytes);
+    first.deserialize_next().unwrap();
+    let mut second = de(&bytes);
+    second.resume(&first.snapshot()).unwrap();
+    let Some(XAsset::PC(XAssetGeneric::LocalizeEntry(Some(entry)))) =
+        second.deserialize_next().unwrap()
+    else {
+        panic!();
+    };
+    assert_eq!(entry.value.get(), "Start Game");
+}
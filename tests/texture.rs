@@ -0,0 +1,212 @@
+use t5_xfile_defs::{
+    ErrorKind, XString,
+    techset::{GfxImage, GfxImageLoadDef, GfxTexture, MapType},
+    texture::{MipLevel, TextureFormat},
+};
+use t5_xfile_deserializer::png::{self, PNG_SIGNATURE};
+
+fn image(
+    width: u16,
+    height: u16,
+    format: TextureFormat,
+    level_count: u8,
+    resource: Vec<u8>,
+) -> GfxImage {
+    GfxImage {
+        texture: GfxTexture::LoadDef(Some(Box::new(GfxImageLoadDef {
+            level_count,
+            flags: 0,
+            format: format.as_d3d(),
+            resource,
+        }))),
+        map_type: MapType::TWO_DIMENSIONAL,
+        width,
+        height,
+        depth: 1,
+        level_count,
+        name: XString("~test".to_string()),
+        ..Default::default()
+    }
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn rgba8() {
+    // BGRA in memory
+    let image = image(
+        2,
+        1,
+        TextureFormat::A8R8G8B8,
+        1,
+        vec![0x30, 0x20, 0x10, 0x40, 0x00, 0x00, 0xFF, 0x80],
+    );
+    assert_eq!(
+        image.to_rgba8(),
+        Some((2, 1, vec![0x10, 0x20, 0x30, 0x40, 0xFF, 0x00, 0x00, 0x80]))
+    );
+
+    assert!(GfxImage::default().to_rgba8().is_none());
+    let short = self::image(4, 4, TextureFormat::A8R8G8B8, 1, vec![0; 8]);
+    assert!(short.to_rgba8().is_none());
+}
+
+#[test]
+fn dxt() {
+    // red and blue endpoints, every pixel uses the first, except the
+    // last, which uses the second
+    let block = [0x00, 0xF8, 0x1F, 0x00, 0x00, 0x00, 0x00, 0x40];
+    let (width, height, pixels) = image(4, 4, TextureFormat::DXT1, 1, block.to_vec())
+        .to_rgba8()
+        .unwrap();
+    assert_eq!((width, height), (4, 4));
+    assert_eq!(pixels[..4], [0xFF, 0x00, 0x00, 0xFF]);
+    assert_eq!(pixels[60..], [0x00, 0x00, 0xFF, 0xFF]);
+
+    // alpha endpoints 0xFF and 0x00, every pixel uses the first
+    let mut block5 = vec![0xFF, 0x00, 0, 0, 0, 0, 0, 0];
+    block5.extend_from_slice(&block);
+    let (_, _, pixels) = image(2, 2, TextureFormat::DXT5, 1, block5)
+        .to_rgba8()
+        .unwrap();
+    // the block is cropped to the image
+    assert_eq!(pixels.len(), 2 * 2 * 4);
+    assert_eq!(pixels[..4], [0xFF, 0x00, 0x00, 0xFF]);
+}
+
+#[test]
+fn dds() {
+    // 8x8 and 4x4 mips
+    let image = image(8, 8, TextureFormat::DXT1, 2, vec![0xAB; 32 + 8]);
+    let dds = image.to_dds().unwrap();
+    assert_eq!(&dds[..4], b"DDS ");
+    assert_eq!(u32_at(&dds, 4), 124);
+    assert_eq!(u32_at(&dds, 12), 8); // height
+    assert_eq!(u32_at(&dds, 16), 8); // width
+    assert_eq!(u32_at(&dds, 20), 32); // linear size
+    assert_eq!(u32_at(&dds, 28), 2); // mip count
+    assert_eq!(&dds[84..88], b"DXT1");
+    assert_eq!(dds.len(), 128 + 40);
+    assert!(dds[128..].iter().all(|&b| b == 0xAB));
+
+    let mut unsupported = image;
+    let GfxTexture::LoadDef(Some(def)) = &mut unsupported.texture else {
+        unreachable!()
+    };
+    def.format = 0x7F;
+    assert!(unsupported.to_dds().is_none());
+}
+
+#[test]
+fn streaming_dds() {
+    // only the 4x4 mip of an 8x8 image is in the Fastfile
+    let mut image = image(8, 8, TextureFormat::DXT1, 2, vec![0; 8]);
+    image.streaming = true;
+    let GfxTexture::LoadDef(Some(def)) = &mut image.texture else {
+        unreachable!()
+    };
+    def.level_count = 1;
+
+    let dds = image.to_dds().unwrap();
+    assert_eq!(u32_at(&dds, 12), 4);
+    assert_eq!(u32_at(&dds, 28), 1);
+    assert_eq!(image.to_rgba8().unwrap().0, 4);
+}
+
+#[test]
+fn encode_png() {
+    let png = png::encode_rgba8(1, 1, &[0xFF, 0x00, 0x00, 0xFF]);
+    assert_eq!(png[..8], PNG_SIGNATURE);
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(png[16..24], [0, 0, 0, 1, 0, 0, 0, 1]);
+    // IEND always has the same CRC
+    assert_eq!(
+        png[png.len() - 12..],
+        [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+    );
+
+    let image = image(
+        1,
+        1,
+        TextureFormat::A8R8G8B8,
+        1,
+        vec![0x00, 0x00, 0xFF, 0xFF],
+    );
+    assert_eq!(png::from_image(&image), Some(png));
+}
+
+#[test]
+fn formats() {
+    for format in TextureFormat::ALL {
+        assert_eq!(TextureFormat::from_d3d(format.as_d3d()).unwrap(), format);
+    }
+    assert!(matches!(
+        TextureFormat::from_d3d(0x1A200154).unwrap_err().kind(),
+        ErrorKind::UnknownTextureFormat(0x1A200154)
+    ));
+
+    assert_eq!(TextureFormat::DXT1.bits_per_pixel(), 4);
+    assert_eq!(TextureFormat::ATI2.bits_per_pixel(), 8);
+    assert_eq!(TextureFormat::A16B16G16R16F.bits_per_pixel(), 64);
+    assert!(TextureFormat::DXT5.is_block_compressed());
+    assert!(!TextureFormat::R32F.is_block_compressed());
+    assert!(TextureFormat::A8L8.has_alpha());
+    assert!(!TextureFormat::X8R8G8B8.has_alpha());
+    assert_eq!(TextureFormat::DXT1.level_size(2, 2), 8);
+
+    let image = image(4, 4, TextureFormat::DXT5, 1, vec![0; 16]);
+    assert_eq!(image.texture_format().unwrap(), Some(TextureFormat::DXT5));
+    assert_eq!(GfxImage::default().texture_format().unwrap(), None);
+
+    // an Xbox 360 format
+    let mut console = image.clone();
+    let GfxTexture::LoadDef(Some(def)) = &mut console.texture else {
+        unreachable!()
+    };
+    def.format = 0x1A200154;
+    assert!(console.texture_format().is_err());
+    assert!(console.to_dds().is_none());
+}
+
+#[test]
+fn mip_levels() {
+    let image = image(8, 4, TextureFormat::A8R8G8B8, 3, vec![0; 128 + 32 + 8]);
+    assert_eq!(
+        image.mip_levels().unwrap(),
+        [
+            MipLevel {
+                width: 8,
+                height: 4,
+                depth: 1,
+                offset: 0,
+                size: 128,
+            },
+            MipLevel {
+                width: 4,
+                height: 2,
+                depth: 1,
+                offset: 128,
+                size: 32,
+            },
+            MipLevel {
+                width: 2,
+                height: 1,
+                depth: 1,
+                offset: 160,
+                size: 8,
+            },
+        ]
+    );
+    assert!(GfxImage::default().mip_levels().unwrap().is_empty());
+}
+
+#[test]
+fn float_dds() {
+    let image = image(1, 1, TextureFormat::A16B16G16R16F, 1, vec![0; 8]);
+    let dds = image.to_dds().unwrap();
+    // no masks, so the format goes in the FourCC
+    assert_eq!(u32_at(&dds, 84), 113);
+    assert_eq!(dds.len(), 128 + 8);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use t5_xfile_defs::{
+    XString,
+    gfx_world::{GfxAabbTree, GfxSurface, GfxWorld},
+    validate::BadReference,
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+use common::zeroed;
+
+fn world() -> GfxWorld<1> {
+    let mut world: GfxWorld<1> = zeroed();
+    world.name = XString("maps/mp/mp_a.d3dbsp".to_string());
+    world.primary_light_count = 2;
+    world.draw.lightmaps = vec![zeroed()];
+    world.draw.reflection_probes = vec![zeroed(), zeroed()];
+    world.dpvs.surfaces = vec![zeroed::<GfxSurface>(); 3];
+    world.dpvs.sorted_surf_index = vec![0, 1, 2];
+    world.cells = vec![zeroed()];
+    world
+}
+
+fn validate(world: GfxWorld<1>) -> Vec<BadReference> {
+    let bag: XAssetBag = [XAsset::PC(XAssetGeneric::GfxWorld(Some(Box::new(world))))]
+        .into_iter()
+        .collect();
+    bag.validate_references()
+}
+
+#[test]
+fn valid() {
+    let mut world = world();
+    world.dpvs.surfaces[2].reflection_probe_index = 1;
+    world.dpvs.surfaces[2].primary_light_index = 1;
+    world.cells[0].aabb_tree = vec![GfxAabbTree {
+        start_surf_index: 1,
+        surface_count: 2,
+        ..zeroed()
+    }];
+    assert_eq!(validate(world), []);
+}
+
+#[test]
+fn out_of_range() {
+    let mut world = world();
+    world.sun_primary_light_index = 2;
+    world.dpvs.surfaces[1].lightmap_index = 1;
+    world.dpvs.surfaces[2].primary_light_index = 5;
+    world.cells[0].aabb_tree = vec![
+        zeroed(),
+        GfxAabbTree {
+            start_surf_index: 2,
+            surface_count: 2,
+            ..zeroed()
+        },
+    ];
+
+    let bad = validate(world);
+    let fields = bad
+        .iter()
+        .map(|b| (b.field.as_str(), b.index, b.len))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        [
+            ("sun_primary_light_index", 2, 2),
+            ("dpvs.surfaces[1].lightmap_index", 1, 1),
+            ("dpvs.surfaces[2].primary_light_index", 5, 2),
+            ("cells[0].aabb_tree[1].start_surf_index", 3, 3),
+        ]
+    );
+    assert!(
+        bad.iter()
+            .all(|b| b.asset_type == XAssetType::GFXWORLD && b.asset_name == "maps/mp/mp_a.d3dbsp")
+    );
+    assert_eq!(
+        bad[1].to_string(),
+        "GFXWORLD maps/mp/mp_a.d3dbsp: dpvs.surfaces[1].lightmap_index reaches index 1, but \
+         there are only 1"
+    );
+}
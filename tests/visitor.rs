@@ -0,0 +1,97 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+use t5_xfile_defs::{
+    LocalizeEntry, RawFile, XFilePlatform,
+    gfx_world::GfxWorld,
+    visitor::XAssetVisitor,
+    xasset::{XAsset, XAssetBag, XAssetType},
+};
+
+#[derive(Default)]
+struct Names {
+    assets: usize,
+    names: Vec<String>,
+    nulls: Vec<XAssetType>,
+    worlds: usize,
+}
+
+impl XAssetVisitor for Names {
+    fn visit_asset(&mut self, _: &XAsset) {
+        self.assets += 1;
+    }
+
+    fn visit_null(&mut self, asset_type: XAssetType) {
+        self.nulls.push(asset_type);
+    }
+
+    fn visit_raw_file(&mut self, raw_file: &RawFile) {
+        self.names.push(raw_file.name.get().to_string());
+    }
+
+    fn visit_localize_entry(&mut self, entry: &LocalizeEntry) {
+        self.names.push(entry.name.get().to_string());
+    }
+
+    fn visit_gfx_world<const MAX_LOCAL_CLIENTS: usize>(&mut self, _: &GfxWorld<MAX_LOCAL_CLIENTS>) {
+        self.worlds += 1;
+    }
+}
+
+fn xfile() -> Vec<u8> {
+    SyntheticXFile::new(XFilePlatform::Windows)
+        .with_rawfile("maps/a.gsc", b"main() { }")
+        .with_null_asset(XAssetType::GFXWORLD)
+        .with_string_table("mp/a.csv", 1, &["x"])
+        .with_localize_entry("MENU_A", "A")
+        .to_bytes()
+}
+
+#[test]
+fn accept_remaining() {
+    let bytes = xfile();
+    let mut names = Names::default();
+    let visited = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .accept_remaining(&mut names)
+        .unwrap();
+
+    assert_eq!(visited, 4);
+    assert_eq!(names.assets, 4);
+    assert_eq!(names.names, ["maps/a.gsc", "MENU_A"]);
+    assert_eq!(names.nulls, [XAssetType::GFXWORLD]);
+    assert_eq!(names.worlds, 0);
+}
+
+#[test]
+fn accept_bag() {
+    let bytes = xfile();
+    let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, XFilePlatform::Windows, false)
+        .with_silent(true)
+        .build()
+        .unwrap()
+        .inflate()
+        .unwrap()
+        .no_cache()
+        .unwrap()
+        .deserialize_remaining()
+        .unwrap();
+
+    let mut bag = XAssetBag::new();
+    for asset in assets {
+        bag.insert(asset);
+    }
+
+    let mut names = Names::default();
+    bag.accept(&mut names);
+    assert_eq!(names.assets, bag.len());
+    names.names.sort();
+    assert_eq!(names.names, ["MENU_A", "maps/a.gsc"]);
+}
@@ -0,0 +1,174 @@
+use t5_xfile_defs::{
+    RawFile, XString,
+    common::{Vec3, Vec4},
+    fx::{FxEffectDef, FxEffectDefFlags},
+    techset::{Material, MaterialInfo},
+    weapon::{FlameTable, WeaponDef, WeaponVariantDef},
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+    xmodel::XModel,
+};
+
+fn xstring(s: &str) -> XString {
+    XString(s.to_string())
+}
+
+fn model(name: &str) -> Option<Box<XModel>> {
+    Some(Box::new(XModel {
+        name: xstring(name),
+        ..Default::default()
+    }))
+}
+
+/// A `Box` for [`XAssetGeneric::Material`], or a
+/// [`Shared`](t5_xfile_defs::Shared) for the materials other assets use.
+fn material<P: From<Material>>(name: &str) -> Option<P> {
+    Some(P::from(Material {
+        info: MaterialInfo {
+            name: xstring(name),
+            ..Default::default()
+        },
+        ..Default::default()
+    }))
+}
+
+fn fx(name: &str) -> Option<Box<FxEffectDef>> {
+    Some(Box::new(FxEffectDef {
+        name: xstring(name),
+        flags: FxEffectDefFlags::empty(),
+        ef_priority: 0,
+        total_size: 0,
+        msec_looping_life: 0,
+        elem_def_count_looping: 0,
+        elem_def_count_one_shot: 0,
+        elem_def_count_emission: 0,
+        elem_defs: Vec::new(),
+        bounding_box_dim: Vec3::default(),
+        bounding_sphere: Vec4::default(),
+    }))
+}
+
+fn weapon() -> WeaponVariantDef {
+    let mut gun_xmodel: [Option<Box<XModel>>; 16] = Default::default();
+    gun_xmodel[0] = model("viewmodel_ak47");
+    gun_xmodel[1] = model("viewmodel_ak47_gl");
+    let mut world_model: [Option<Box<XModel>>; 16] = Default::default();
+    world_model[0] = model("weapon_ak47");
+    world_model[1] = model("weapon_ak47");
+
+    WeaponVariantDef {
+        internal_name: xstring("ak47_mp"),
+        weap_def: Some(Box::new(WeaponDef {
+            gun_xmodel: Some(gun_xmodel),
+            hand_xmodel: model("viewmodel_hands_cloth"),
+            world_model: Some(Box::new(world_model)),
+            view_flash_effect: fx("weapon/muzzleflashes/fx_ak47"),
+            world_flash_effect: fx("weapon/muzzleflashes/fx_ak47"),
+            hud_icon: material("hud_ak47"),
+            kill_icon: material("hud_ak47"),
+            fire_sound: xstring("wpn_ak47_fire"),
+            fire_sound_player: xstring("wpn_ak47_fire_plr"),
+            reload_sound: xstring(""),
+            flame_table_first_person_ptr: Some(Box::new(FlameTable {
+                fire: material("flame_fire"),
+                flame_ignite_sound: xstring("wpn_flame_ignite"),
+                ..Default::default()
+            })),
+            ..Default::default()
+        })),
+        xanims: vec![xstring(""), xstring("viewmodel_ak47_idle")],
+        dpad_icon: material("hud_ak47_dpad"),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn dependencies() {
+    let deps = weapon().dependencies();
+    let assets = deps
+        .assets
+        .iter()
+        .map(|(asset_type, name)| (*asset_type, name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        assets,
+        [
+            (XAssetType::XMODEL, "viewmodel_ak47"),
+            (XAssetType::XMODEL, "viewmodel_ak47_gl"),
+            (XAssetType::XMODEL, "weapon_ak47"),
+            (XAssetType::XMODEL, "viewmodel_hands_cloth"),
+            (XAssetType::MATERIAL, "hud_ak47"),
+            (XAssetType::FX, "weapon/muzzleflashes/fx_ak47"),
+            (XAssetType::MATERIAL, "flame_fire"),
+            (XAssetType::XANIMPARTS, "viewmodel_ak47_idle"),
+            (XAssetType::MATERIAL, "hud_ak47_dpad"),
+        ]
+    );
+    assert_eq!(
+        deps.sound_aliases,
+        ["wpn_ak47_fire", "wpn_ak47_fire_plr", "wpn_flame_ignite"]
+    );
+
+    let asset = XAsset::PC(XAssetGeneric::Weapon(Some(Box::new(weapon()))));
+    assert_eq!(asset.dependencies(), deps.assets);
+}
+
+#[test]
+fn zone_source() {
+    let source = weapon().dependencies().to_zone_source();
+    let lines = source.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "xmodel,viewmodel_ak47");
+    assert!(lines.contains(&"fx,weapon/muzzleflashes/fx_ak47"));
+    assert!(lines.contains(&"xanim,viewmodel_ak47_idle"));
+    assert!(lines.ends_with(&[
+        "// sound aliases",
+        "// wpn_ak47_fire",
+        "// wpn_ak47_fire_plr",
+        "// wpn_flame_ignite",
+    ]));
+
+    assert_eq!(WeaponDef::default().dependencies().to_zone_source(), "");
+}
+
+#[test]
+fn closure() {
+    let mut weapon_ak47 = model("weapon_ak47").unwrap();
+    weapon_ak47.material_handles = vec![material("mtl_ak47").unwrap()];
+
+    let bag = [
+        XAssetGeneric::Material(material("hud_ak47")),
+        XAssetGeneric::Weapon(Some(Box::new(weapon()))),
+        XAssetGeneric::RawFile(Some(Box::new(RawFile {
+            name: xstring("maps/a.gsc"),
+            buffer: Vec::new(),
+        }))),
+        XAssetGeneric::XModel(Some(weapon_ak47)),
+        XAssetGeneric::Material(material("mtl_ak47")),
+    ]
+    .into_iter()
+    .map(XAsset::PC)
+    .collect::<XAssetBag>();
+
+    let closure = bag.closure([
+        (XAssetType::WEAPON, "ak47_mp"),
+        (XAssetType::WEAPON, "m16_mp"),
+    ]);
+    let names = closure
+        .iter()
+        .map(|a| (a.asset_type(), a.name().unwrap()))
+        .collect::<Vec<_>>();
+    // everything after what it depends on, and only what's in the bag
+    assert_eq!(
+        names,
+        [
+            (XAssetType::MATERIAL, "mtl_ak47"),
+            (XAssetType::XMODEL, "weapon_ak47"),
+            (XAssetType::MATERIAL, "hud_ak47"),
+            (XAssetType::WEAPON, "ak47_mp"),
+        ]
+    );
+
+    assert!(
+        bag.closure([(XAssetType::RAWFILE, "maps/b.gsc")])
+            .is_empty()
+    );
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "deserializer")]
+
+use t5_xfile_defs::{
+    XFilePlatform,
+    common::Vec3,
+    xasset::{XAsset, XAssetGeneric},
+    xmodel::XModelPieces,
+};
+use t5_xfile_deserializer::{T5XFileDeserializerBuilder, testgen::SyntheticXFile};
+
+fn pieces(asset: &XAsset) -> &XModelPieces {
+    match asset {
+        XAsset::PC(XAssetGeneric::XModelPieces(Some(pieces))) => pieces,
+        XAsset::Console(XAssetGeneric::XModelPieces(Some(pieces))) => pieces,
+        asset => panic!("{asset:?}"),
+    }
+}
+
+#[test]
+fn deserialize() {
+    let offsets = [[1.0, -2.0, 0.5], [0.0, 0.0, 64.0]];
+    for platform in [XFilePlatform::Windows, XFilePlatform::Xbox360] {
+        let xfile = SyntheticXFile::new(platform)
+            .with_xmodel_pieces("p_barrel_pieces", &offsets)
+            .with_xmodel_pieces("p_empty_pieces", &[]);
+        let bytes = xfile.to_bytes();
+        let assets = T5XFileDeserializerBuilder::from_bytes(&bytes, platform, true)
+            .with_silent(true)
+            .build()
+            .unwrap()
+            .inflate()
+            .unwrap()
+            .no_cache()
+            .unwrap()
+            .deserialize_remaining()
+            .unwrap();
+        assert_eq!(assets.len(), 2);
+
+        let barrel = pieces(&assets[0]);
+        assert_eq!(assets[0].name(), Some("p_barrel_pieces"));
+        assert_eq!(barrel.pieces.len(), 2);
+        for (piece, offset) in barrel.pieces.iter().zip(offsets) {
+            assert!(piece.model.is_none());
+            assert_eq!(piece.offset, Vec3::from(offset));
+        }
+        assert!(pieces(&assets[1]).pieces.is_empty());
+
+        #[cfg(feature = "serializer")]
+        for report in t5_xfile_deserializer::roundtrip::check_assets(&assets, platform) {
+            assert!(report.is_ok(), "{platform:?}: {report:?}");
+        }
+    }
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "deserializer")]
+
+use std::path::PathBuf;
+
+use t5_xfile_deserializer::{ZoneSet, testgen::SyntheticXFile};
+
+use t5_xfile_defs::{
+    XFilePlatform, XString,
+    sound::SndPatch,
+    xasset::{XAsset, XAssetBag, XAssetGeneric, XAssetType},
+};
+
+fn zones(name: &str, zones: &[&[(&str, &[u8])]]) -> (PathBuf, ZoneSet) {
+    let dir = std::env::temp_dir().join(format!("t5-xfile-zone-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut set = ZoneSet::new(XFilePlatform::Windows);
+    for (i, rawfiles) in zones.iter().enumerate() {
+        let xfile = rawfiles.iter().fold(
+            SyntheticXFile::new(XFilePlatform::Windows),
+            |x, (name, contents)| x.with_rawfile(name, contents),
+        );
+        let path = dir.join(format!("{i}.ff"));
+        std::fs::write(&path, xfile.to_bytes()).unwrap();
+        set = set.with_zone(path);
+    }
+
+    (dir, set)
+}
+
+fn buffer(asset: Option<&XAsset>) -> &[u8] {
+    match asset {
+        Some(XAsset::PC(XAssetGeneric::RawFile(Some(raw_file)))) => &raw_file.buffer,
+        asset => panic!("{asset:?}"),
+    }
+}
+
+#[test]
+fn deserialize() {
+    let (dir, set) = zones(
+        "deserialize",
+        &[
+            &[("maps/a.gsc", b"a"), ("maps/b.gsc", b"b")],
+            &[("maps/c.gsc", b"c"), ("maps/a.gsc", b"a2")],
+        ],
+    );
+
+    let bag = set.deserialize().unwrap();
+    assert_eq!(bag.len(), 3);
+    // later zones take priority, but replaced assets keep their place
+    assert_eq!(buffer(bag.get(XAssetType::RAWFILE, "maps/a.gsc")), b"a2\0");
+    assert_eq!(buffer(bag.get(XAssetType::RAWFILE, "maps/b.gsc")), b"b\0");
+    assert_eq!(
+        bag.iter().map(|a| a.name().unwrap()).collect::<Vec<_>>(),
+        ["maps/a.gsc", "maps/b.gsc", "maps/c.gsc"]
+    );
+    assert_eq!(bag.iter_type(XAssetType::RAWFILE).count(), 3);
+    assert!(!bag.contains(XAssetType::RAWFILE, "maps/d.gsc"));
+    assert!(!bag.contains(XAssetType::LOCALIZE_ENTRY, "maps/a.gsc"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn sound_patch_type() {
+    let patch = XAsset::PC(XAssetGeneric::SoundPatch(Some(Box::new(SndPatch {
+        name: XString("patch".to_string()),
+        elements: Vec::new(),
+        files: Vec::new(),
+    }))));
+    assert_eq!(patch.asset_type(), XAssetType::SOUND_PATCH);
+
+    let bag = XAssetBag::from_iter([patch.clone()]);
+    assert_eq!(bag.get(XAssetType::SOUND_PATCH, "patch"), Some(&patch));
+    assert!(!bag.contains(XAssetType::SOUND, "patch"));
+}
+
+#[cfg(feature = "hash")]
+#[test]
+fn dedupe() {
+    let (dir, set) = zones(
+        "dedupe",
+        &[
+            &[("maps/shared.gsc", b"main() { }"), ("maps/a.gsc", b"a")],
+            &[("maps/shared.gsc", b"main() { }"), ("maps/a.gsc", b"b")],
+            &[("maps/shared.gsc", b"main() { }")],
+        ],
+    );
+
+    let deduped = set.dedupe().unwrap();
+    assert_eq!(deduped.zones(), set.zones());
+    assert_eq!(deduped.total_count(), 5);
+    // the two versions of maps/a.gsc differ, so they're both kept
+    assert_eq!(deduped.unique_count(), 3);
+
+    let duplicates = deduped.duplicates().collect::<Vec<_>>();
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].0.name(), Some("maps/shared.gsc"));
+    assert_eq!(duplicates[0].1, 3);
+
+    let (shared, zones) = deduped.iter().next().unwrap();
+    assert_eq!(shared.name(), Some("maps/shared.gsc"));
+    assert_eq!(zones.collect::<Vec<_>>(), set.zones());
+
+    // every zone sees the same copy
+    let a = deduped
+        .get_in_zone(0, XAssetType::RAWFILE, "maps/shared.gsc")
+        .unwrap();
+    let c = deduped
+        .get_in_zone(2, XAssetType::RAWFILE, "maps/shared.gsc")
+        .unwrap();
+    assert!(std::ptr::eq(a, c));
+    assert_eq!(deduped.zone(2).count(), 1);
+    assert!(
+        deduped
+            .get_in_zone(2, XAssetType::RAWFILE, "maps/a.gsc")
+            .is_none()
+    );
+
+    // later zones take priority, same as in ZoneSet::deserialize
+    let bag = set.deserialize().unwrap();
+    let a = deduped.get(XAssetType::RAWFILE, "maps/a.gsc").unwrap();
+    assert_eq!(Some(a), bag.get(XAssetType::RAWFILE, "maps/a.gsc"));
+    assert_ne!(
+        Some(a),
+        deduped.get_in_zone(0, XAssetType::RAWFILE, "maps/a.gsc")
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn split_and_merge() {
+    let (dir, set) = zones(
+        "split",
+        &[
+            &[("maps/shared.gsc", b"main() { }"), ("maps/a.gsc", b"a")],
+            &[("maps/a.gsc", b"b"), ("maps/b.gsc", b"b")],
+        ],
+    );
+
+    let merged = set.merge().unwrap();
+    assert_eq!(merged.len(), 3);
+    // later zones take priority, same as in ZoneSet::deserialize
+    assert_eq!(
+        merged.get(XAssetType::RAWFILE, "maps/a.gsc"),
+        set.deserialize()
+            .unwrap()
+            .get(XAssetType::RAWFILE, "maps/a.gsc")
+    );
+
+    let split = set.split_by_type().unwrap();
+    assert_eq!(
+        split.keys().copied().collect::<Vec<_>>(),
+        [XAssetType::RAWFILE]
+    );
+    assert_eq!(split[&XAssetType::RAWFILE].len(), 3);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "hash")]
+#[test]
+fn bag_hash() {
+    use t5_xfile_defs::hash::StableHash;
+
+    let (dir, set) = zones(
+        "hash",
+        &[
+            &[("maps/a.gsc", b"a"), ("maps/b.gsc", b"b")],
+            &[("maps/a.gsc", b"a")],
+        ],
+    );
+
+    let bag = set.deserialize().unwrap();
+    let hash = bag.stable_hash().unwrap();
+    assert_eq!(hash, set.deserialize().unwrap().stable_hash().unwrap());
+    // replacing an asset with an identical one doesn't change the hash
+    let first = ZoneSet::new(XFilePlatform::Windows).with_zone(&set.zones()[0]);
+    assert_eq!(hash, first.deserialize().unwrap().stable_hash().unwrap());
+
+    // but the order of the assets does
+    let reversed = bag.as_slice().iter().rev().cloned().collect::<XAssetBag>();
+    assert_ne!(hash, reversed.stable_hash().unwrap());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}